@@ -1,10 +1,26 @@
 use crate::commands::update::Update;
-use crate::commands::{Command, CommandResult};
+use crate::commands::{Command, CommandError, CommandResult, unknown_command_error};
 use crate::commands::{
-    add::Add, delete::Delete, export::Export, help::Help, here::Here, import::Import, init::Init,
-    parent_entity::ParentEntity, show::Show, tui::Tui, upsert::Upsert, version::Version,
+    add::Add, alias::Alias, archive::Archive, backup::Backup, broken::Broken, clean::Clean,
+    compact::Compact, complete::Complete, completions::Completions, count::Count, dedupe::Dedupe,
+    delete::Delete, diff::Diff, doctor::Doctor, edit::Edit, examples::Examples, exists::Exists,
+    export::Export, fzf::Fzf, grep::Grep, group::Group, help::Help, here::Here, history::History,
+    import::Import, init::Init, last::Last, list::List, man::Man, migrate_data::MigrateData,
+    note::Note, open::Open, parent_entity::ParentEntity, paths::Paths, pin::Pin, profile::Profile,
+    prune::Prune, random::Random, restore::Restore, restore_trash::RestoreTrash, search::Search,
+    serve::Serve, session::Session, set_default::SetDefault, show::Show, sync::GitSync,
+    trash::Trash, tree::Tree, tui::Tui, unalias::Unalias, unarchive::Unarchive, undo::Undo,
+    unpin::Unpin, upsert::Upsert, verify::Verify, version::Version, which::Which,
 };
-use std::env;
+use crate::utils::command::END_OF_OPTIONS;
+use crate::utils::context::Context;
+use crate::utils::format::Format;
+use crate::utils::json::Json;
+use crate::utils::log::{self, Level};
+use crate::utils::prompt::Prompt;
+use crate::utils::style;
+use crate::utils::tap_data_store::{DataStoreHandle, StoreOptions, is_profile_name, read_only_env};
+use std::{env, path::PathBuf};
 
 /// Collects command-line arguments, skipping the first argument (the program name).
 ///
@@ -15,28 +31,861 @@ pub fn collect_args() -> Vec<String> {
     env::args().skip(1).collect()
 }
 
-// TODO: add tests for these entry see CLI book: https://rust-cli.github.io/book/tutorial/testing.html
-pub fn run(args: Vec<String>) -> Result<CommandResult, String> {
-    match args.len() {
-        0 => Help::default().run(args),
-        _ => match args[0].as_str() {
-            // General:
-            "--help" => Help::default().run(Vec::from(&args[1..])),
-            "-v" | "--version" => Version::default().run(Vec::from(&args[1..])),
-            // Utilities:
-            "--update" => Update::default().run(Vec::from(&args[1..])),
-            "--tui" => Tui::default().run(Vec::from(&args[1..])),
-            "-i" | "--init" => Init::default().run(Vec::from(&args[1..])),
-            "--import" => Import::default().run(Vec::from(&args[1..])),
-            "--export" => Export::default().run(Vec::from(&args[1..])),
-            // Adding, Updating, and Deleting Links:
-            "-a" | "--add" => Add::default().run(Vec::from(&args[1..])),
-            "-d" | "--delete" => Delete::default().run(Vec::from(&args[1..])),
-            "-s" | "--show" => Show::default().run(Vec::from(&args[1..])),
-            "-u" | "--upsert" => Upsert::default().run(Vec::from(&args[1..])),
-            // Opening links:
-            "here" => Here::default().run(Vec::from(&args[1..])),
-            _parent_entity => ParentEntity::default().run(Vec::from(&args[..])),
-        },
+/// Runs tap with the given arguments like the `tap` binary does: the output is printed to stdout,
+/// errors to stderr, and the exit code is returned.
+pub fn main(args: Vec<String>) -> i32 {
+    let format = output_format(&args);
+    let (mut ctx, args) = match context(&args) {
+        Ok(parsed) => parsed,
+        Err(e) => return print_error(e, format),
+    };
+    let _output = OutputScope::new(&ctx);
+    match run_in(&mut ctx, args) {
+        Ok(res) => {
+            let code = res.exit_code();
+            let res = res.render(format);
+            // Nothing to print, e.g. `tap --list` of an empty data store, prints no empty line
+            if ctx.level > Level::Quiet && !res.is_empty() {
+                println!("{}", res);
+            }
+            code
+        }
+        Err(e) => print_error(e, format),
+    }
+}
+
+/// Prints `e` to stderr in `format`, returning the exit code it ends tap with
+fn print_error(e: CommandError, format: Format) -> i32 {
+    match e {
+        // e.g. the answer of tap --exists
+        e if e.message.is_empty() => e.kind.exit_code(),
+        e if format.is_structured() => {
+            let error = Json::object([("error", Json::String(e.message))]);
+            eprintln!("{}", CommandResult::Json(error).render(format));
+            e.kind.exit_code()
+        }
+        e => {
+            eprintln!("{} {}", style::error("ERROR:"), e);
+            e.kind.exit_code()
+        }
+    }
+}
+
+/// The format results and errors are printed in. It is looked up before `run` reads the global
+/// flags, so errors reading them are printed in it too. The `--json` flag of a command is the
+/// same as `--format json`.
+fn output_format(args: &[String]) -> Format {
+    let format = args
+        .iter()
+        .position(|a| a == "--format")
+        .and_then(|i| args.get(i + 1))
+        .and_then(|name| Format::parse(name));
+    match format {
+        Some(format) => format,
+        None if args.iter().any(|a| a == "--json") => Format::Json,
+        None => Format::Plain,
+    }
+}
+
+/// Runs `args` like `main`, returning the result instead of printing it
+#[cfg(test)]
+pub(crate) fn run(args: Vec<String>) -> Result<CommandResult, CommandError> {
+    let (mut ctx, args) = context(&args)?;
+    let _output = OutputScope::new(&ctx);
+    run_in(&mut ctx, args)
+}
+
+/// Runs the command named by `args`, what is left of the arguments after the global flags
+fn run_in(ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+    match args.first() {
+        None => Help::default().run(ctx, args),
+        Some(first) => {
+            let (cmd, consumed) = route(first)?;
+            cmd.run(ctx, Vec::from(&args[consumed..]))
+        }
+    }
+}
+
+/// Reads the global flags, which come before the command in any order, into the `Context` the
+/// command runs in. Returns it along with the arguments after the global flags.
+fn context(args: &[String]) -> Result<(Context, Vec<String>), CommandError> {
+    let mut rest = args;
+    let (mut data_file, mut index_file) = (None, None);
+    let mut options = StoreOptions {
+        read_only: read_only_env(),
+        ..StoreOptions::default()
+    };
+    let mut level = Level::Normal;
+    let mut format = Format::default();
+    let (mut no_color, mut no_input) = (false, false);
+    loop {
+        match rest {
+            [flag, tail @ ..] if flag == "--read-only" => {
+                options.read_only = true;
+                rest = tail;
+            }
+            [flag, tail @ ..] if flag == "-q" || flag == "--quiet" => {
+                level = Level::Quiet;
+                rest = tail;
+            }
+            [flag, tail @ ..] if flag == "--verbose" => {
+                level = Level::Verbose;
+                rest = tail;
+            }
+            [flag, tail @ ..] if flag == "--no-color" => {
+                no_color = true;
+                rest = tail;
+            }
+            [flag, tail @ ..] if flag == "--no-input" => {
+                no_input = true;
+                rest = tail;
+            }
+            [flag, tail @ ..] if flag == "--no-hooks" => {
+                options.no_hooks = true;
+                rest = tail;
+            }
+            [flag, path, tail @ ..] if flag == "--file" => {
+                data_file = Some(PathBuf::from(path));
+                rest = tail;
+            }
+            [flag, path, tail @ ..] if flag == "--index" => {
+                index_file = Some(PathBuf::from(path));
+                rest = tail;
+            }
+            // `tap --profile list` and `tap --profile use <Name>` are the --profile command
+            [flag, name, tail @ ..]
+                if flag == "--profile" && !["list", "use", "--help"].contains(&name.as_str()) =>
+            {
+                if !is_profile_name(name) {
+                    return Err(CommandError::usage(format!(
+                        "{name} is not a valid profile name, use letters, digits, -, _, and ."
+                    )));
+                }
+                options.profile = Some(name.to_string());
+                rest = tail;
+            }
+            [flag, name, tail @ ..] if flag == "--format" => {
+                format = Format::parse(name).ok_or_else(|| {
+                    CommandError::usage(format!(
+                        "unknown format {name}, the supported formats are: {}",
+                        Format::ALL.map(Format::name).join(", ")
+                    ))
+                })?;
+                rest = tail;
+            }
+            [flag] if flag == "--file" || flag == "--index" => {
+                return Err(CommandError::usage(format!("expected a path after {flag}")));
+            }
+            [flag] if flag == "--format" => {
+                return Err(CommandError::usage(
+                    "expected a format after --format".to_string(),
+                ));
+            }
+            _ => break,
+        }
+    }
+    if index_file.is_some() && data_file.is_none() {
+        return Err(CommandError::usage(
+            "--index can only be used together with --file".to_string(),
+        ));
+    }
+    if options.profile.is_some() && data_file.is_some() {
+        return Err(CommandError::usage(
+            "--profile and --file can not be used together, --file picks the data file itself"
+                .to_string(),
+        ));
+    }
+    if let Some(data_file) = data_file {
+        options = options.with_files(data_file, index_file);
+    }
+    options.operation = Some(format!("tap {}", rest.join(" ")));
+    let ctx = Context {
+        store: DataStoreHandle::new(options),
+        prompt: Prompt::stdin(no_input),
+        format,
+        level,
+        no_color,
+        ..Context::default()
+    };
+    Ok((ctx, rest.to_vec()))
+}
+
+/// Applies the `level` and `no_color` of a `Context` to the notes of `log` and the colors of
+/// `style` while it lives, going back to the defaults when dropped, so the flags of one run never
+/// carry over to the next. These two are the only flags kept outside of the `Context`, see
+/// `log::LEVEL`.
+struct OutputScope;
+
+impl OutputScope {
+    fn new(ctx: &Context) -> Self {
+        log::set_level(ctx.level);
+        style::set_no_color(ctx.no_color);
+        OutputScope
+    }
+}
+
+impl Drop for OutputScope {
+    fn drop(&mut self) {
+        log::set_level(Level::Normal);
+        style::set_no_color(false);
+    }
+}
+
+/// The command selected by the first argument after the global flags, along with the number of
+/// arguments it takes up: 1 for a command, 0 when the argument is the start of a parent entity name
+fn route(first: &str) -> Result<(Box<dyn Command>, usize), CommandError> {
+    let cmd: Box<dyn Command> = match first {
+        // General:
+        "--help" => Box::new(Help::default()),
+        "--examples" => Box::new(Examples::default()),
+        "-v" | "--version" => Box::new(Version::default()),
+        // Utilities:
+        "--update" => Box::new(Update::default()),
+        "--tui" => Box::new(Tui::default()),
+        "-i" | "--init" => Box::new(Init::default()),
+        "--completions" => Box::new(Completions::default()),
+        "--doctor" => Box::new(Doctor::default()),
+        "--backup" => Box::new(Backup::default()),
+        "--restore" => Box::new(Restore::default()),
+        "--undo" => Box::new(Undo::default()),
+        "--history" => Box::new(History::default()),
+        "--trash" => Box::new(Trash::default()),
+        "--restore-trash" => Box::new(RestoreTrash::default()),
+        "--broken" => Box::new(Broken::default()),
+        "--clean" => Box::new(Clean::default()),
+        "--dedupe" => Box::new(Dedupe::default()),
+        "--verify" => Box::new(Verify::default()),
+        "--edit" => Box::new(Edit::default()),
+        "--where" => Box::new(Paths::default()),
+        "--profile" => Box::new(Profile::default()),
+        "--compact" => Box::new(Compact::default()),
+        "--prune" => Box::new(Prune::default()),
+        "--migrate-data" => Box::new(MigrateData::default()),
+        "--import" => Box::new(Import::default()),
+        "--diff" => Box::new(Diff::default()),
+        "--export" => Box::new(Export::default()),
+        "--sync" => Box::new(GitSync::default()),
+        "--serve" => Box::new(Serve::default()),
+        // Adding, Updating, and Deleting Links:
+        "-a" | "--add" => Box::new(Add::default()),
+        "-d" | "--delete" => Box::new(Delete::default()),
+        "-s" | "--show" => Box::new(Show::default()),
+        "--list" => Box::new(List::default()),
+        "--tree" => Box::new(Tree::default()),
+        "-u" | "--upsert" => Box::new(Upsert::default()),
+        "--pin" => Box::new(Pin::default()),
+        "--unpin" => Box::new(Unpin::default()),
+        "--note" => Box::new(Note::default()),
+        "--set-default" => Box::new(SetDefault::default()),
+        "--alias" => Box::new(Alias::default()),
+        "--unalias" => Box::new(Unalias::default()),
+        "--archive" => Box::new(Archive::default()),
+        "--unarchive" => Box::new(Unarchive::default()),
+        "--which" => Box::new(Which::default()),
+        "--exists" => Box::new(Exists::default()),
+        "--count" => Box::new(Count::default()),
+        "--search" => Box::new(Search::default()),
+        "--grep" => Box::new(Grep::default()),
+        // Opening links:
+        "here" => Box::new(Here::default()),
+        "--open" => Box::new(Open::default()),
+        "--last" => Box::new(Last::default()),
+        "--session" => Box::new(Session::default()),
+        "--group" => Box::new(Group::default()),
+        "--random" => Box::new(Random::default()),
+        "--fzf" => Box::new(Fzf::default()),
+        // Hidden, used by shell completion scripts
+        "__complete" => Box::new(Complete::default()),
+        // Hidden, used by packagers
+        "--generate-man" => Box::new(Man::default()),
+        // Everything after `--` is a name, so `tap -- here` opens a parent named here
+        END_OF_OPTIONS => return Ok((Box::new(ParentEntity::default()), 0)),
+        // Parent entity names can not start with '-', so this is a mistyped command
+        flag if flag.starts_with('-') && flag != "--parent-entity" => {
+            return Err(unknown_command_error(flag));
+        }
+        _parent_entity => return Ok((Box::new(ParentEntity::default()), 0)),
+    };
+    Ok((cmd, 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::{CommandErrorKind, registry};
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    fn help(cmd: impl Command) -> Result<CommandResult, CommandError> {
+        Ok(CommandResult::Value(cmd.help_message()))
+    }
+
+    #[test]
+    fn test_run_no_args() {
+        assert_eq!(
+            run(vec![]),
+            Help::default().run(&mut Context::default(), vec![])
+        );
+    }
+
+    #[test]
+    fn test_run_help() {
+        assert_eq!(
+            run(args(&["--help"])),
+            Help::default().run(&mut Context::default(), vec![])
+        );
+    }
+
+    #[test]
+    fn test_run_version() {
+        assert_eq!(run(args(&["-v", "--help"])), help(Version::default()));
+        assert_eq!(
+            run(args(&["--version", "--help"])),
+            help(Version::default())
+        );
+    }
+
+    #[test]
+    fn test_run_utilities() {
+        assert_eq!(run(args(&["--update", "--help"])), help(Update::default()));
+        assert_eq!(run(args(&["--tui", "--help"])), help(Tui::default()));
+        assert_eq!(run(args(&["-i", "--help"])), help(Init::default()));
+        assert_eq!(run(args(&["--init", "--help"])), help(Init::default()));
+        assert_eq!(
+            run(args(&["--completions", "--help"])),
+            help(Completions::default())
+        );
+        assert_eq!(run(args(&["--doctor", "--help"])), help(Doctor::default()));
+        assert_eq!(run(args(&["--backup", "--help"])), help(Backup::default()));
+        assert_eq!(
+            run(args(&["--restore", "--help"])),
+            help(Restore::default())
+        );
+        assert_eq!(run(args(&["--undo", "--help"])), help(Undo::default()));
+        assert_eq!(
+            run(args(&["--history", "--help"])),
+            help(History::default())
+        );
+        assert_eq!(run(args(&["--broken", "--help"])), help(Broken::default()));
+        assert_eq!(run(args(&["--clean", "--help"])), help(Clean::default()));
+        assert_eq!(run(args(&["--dedupe", "--help"])), help(Dedupe::default()));
+        assert_eq!(run(args(&["--verify", "--help"])), help(Verify::default()));
+        assert_eq!(run(args(&["--edit", "--help"])), help(Edit::default()));
+        assert_eq!(run(args(&["--where", "--help"])), help(Paths::default()));
+        assert_eq!(
+            run(args(&["--profile", "--help"])),
+            help(Profile::default())
+        );
+        assert_eq!(
+            run(args(&["--compact", "--help"])),
+            help(Compact::default())
+        );
+        assert_eq!(
+            run(args(&["--migrate-data", "--help"])),
+            help(MigrateData::default())
+        );
+        assert_eq!(run(args(&["--import", "--help"])), help(Import::default()));
+        assert_eq!(run(args(&["--diff", "--help"])), help(Diff::default()));
+        assert_eq!(run(args(&["--export", "--help"])), help(Export::default()));
+        assert_eq!(run(args(&["--sync", "--help"])), help(GitSync::default()));
+        assert_eq!(run(args(&["--serve", "--help"])), help(Serve::default()));
+    }
+
+    #[test]
+    fn test_run_add() {
+        assert_eq!(run(args(&["-a", "--help"])), help(Add::default()));
+        assert_eq!(run(args(&["--add", "--help"])), help(Add::default()));
+    }
+
+    #[test]
+    fn test_run_delete() {
+        assert_eq!(run(args(&["-d", "--help"])), help(Delete::default()));
+        assert_eq!(run(args(&["--delete", "--help"])), help(Delete::default()));
+    }
+
+    #[test]
+    fn test_run_show() {
+        assert_eq!(run(args(&["-s", "--help"])), help(Show::default()));
+        assert_eq!(run(args(&["--show", "--help"])), help(Show::default()));
+        assert_eq!(run(args(&["--list", "--help"])), help(List::default()));
+        assert_eq!(run(args(&["--tree", "--help"])), help(Tree::default()));
+    }
+
+    #[test]
+    fn test_run_upsert() {
+        assert_eq!(run(args(&["-u", "--help"])), help(Upsert::default()));
+        assert_eq!(run(args(&["--upsert", "--help"])), help(Upsert::default()));
+    }
+
+    #[test]
+    fn test_run_pin() {
+        assert_eq!(run(args(&["--pin", "--help"])), help(Pin::default()));
+        assert_eq!(run(args(&["--unpin", "--help"])), help(Unpin::default()));
+    }
+
+    #[test]
+    fn test_run_note() {
+        assert_eq!(run(args(&["--note", "--help"])), help(Note::default()));
+    }
+
+    #[test]
+    fn test_run_alias() {
+        assert_eq!(run(args(&["--alias", "--help"])), help(Alias::default()));
+    }
+
+    #[test]
+    fn test_run_archive() {
+        assert_eq!(
+            run(args(&["--archive", "--help"])),
+            help(Archive::default())
+        );
+    }
+
+    #[test]
+    fn test_run_unarchive() {
+        assert_eq!(
+            run(args(&["--unarchive", "--help"])),
+            help(Unarchive::default())
+        );
+    }
+
+    #[test]
+    fn test_run_trash() {
+        assert_eq!(run(args(&["--trash", "--help"])), help(Trash::default()));
+    }
+
+    #[test]
+    fn test_run_restore_trash() {
+        assert_eq!(
+            run(args(&["--restore-trash", "--help"])),
+            help(RestoreTrash::default())
+        );
+    }
+
+    #[test]
+    fn test_run_prune() {
+        assert_eq!(run(args(&["--prune", "--help"])), help(Prune::default()));
+    }
+
+    #[test]
+    fn test_run_unalias() {
+        assert_eq!(
+            run(args(&["--unalias", "--help"])),
+            help(Unalias::default())
+        );
+    }
+
+    #[test]
+    fn test_run_set_default() {
+        assert_eq!(
+            run(args(&["--set-default", "--help"])),
+            help(SetDefault::default())
+        );
+    }
+
+    #[test]
+    fn test_run_exists() {
+        assert_eq!(run(args(&["--exists", "--help"])), help(Exists::default()));
+        assert_eq!(run(args(&["--count", "--help"])), help(Count::default()));
+    }
+
+    #[test]
+    fn test_run_search() {
+        assert_eq!(run(args(&["--search", "--help"])), help(Search::default()));
+    }
+
+    #[test]
+    fn test_run_grep() {
+        assert_eq!(run(args(&["--grep", "--help"])), help(Grep::default()));
+    }
+
+    #[test]
+    fn test_run_random() {
+        assert_eq!(run(args(&["--random", "--help"])), help(Random::default()));
+    }
+
+    #[test]
+    fn test_run_fzf() {
+        assert_eq!(run(args(&["--fzf", "--help"])), help(Fzf::default()));
+    }
+
+    #[test]
+    fn test_run_which() {
+        assert_eq!(run(args(&["--which", "--help"])), help(Which::default()));
+    }
+
+    #[test]
+    fn test_run_here() {
+        assert_eq!(run(args(&["here", "--help"])), help(Here::default()));
+        assert_eq!(run(args(&["--open", "--help"])), help(Open::default()));
+        assert_eq!(run(args(&["--last", "--help"])), help(Last::default()));
+        assert_eq!(
+            run(args(&["--session", "--help"])),
+            help(Session::default())
+        );
+        assert_eq!(run(args(&["--group", "--help"])), help(Group::default()));
+    }
+
+    #[test]
+    fn test_run_parent_entity() {
+        assert_eq!(
+            run(args(&["--parent-entity", "--help"])),
+            help(ParentEntity::default())
+        );
+        // Any other first argument is looked up as a parent entity
+        let res = run(args(&["not-a-stored-parent", "link"]));
+        let err = res.unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::ParentNotFound);
+        assert!(err.message.contains("not-a-stored-parent"));
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("tap_cli_{name}_{timestamp}"));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_run_file() {
+        let dir = temp_dir("file");
+        let data_file = dir.join("demo.tap_data");
+        std::fs::write(
+            &data_file,
+            "search-engines->\n  google|https://google.com\nwork->\n  jira|https://jira.com\n",
+        )
+        .unwrap();
+        let file = data_file.display().to_string();
+        let expected: Result<String, CommandError> =
+            Ok("Parent Entities:\n  search-engines (1)\n  work (1)".to_string());
+        assert_eq!(
+            run(args(&["--file", &file, "--show"])).map(|res| res.to_string()),
+            expected
+        );
+
+        assert!(
+            run(args(&[
+                "--file",
+                &file,
+                "-a",
+                "news",
+                "bbc",
+                "https://bbc.com"
+            ]))
+            .is_ok()
+        );
+        assert!(dir.join("demo.tap_index").exists());
+        let expected: Result<String, CommandError> =
+            Ok("Links of parent entity news:\n  bbc".to_string());
+        assert_eq!(
+            run(args(&["--file", &file, "-s", "news"])).map(|res| res.to_string()),
+            expected
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_run_file_and_index() {
+        let dir = temp_dir("file_and_index");
+        let (data_file, index_file) = (dir.join("links"), dir.join("links.idx"));
+        let (file, index) = (
+            data_file.display().to_string(),
+            index_file.display().to_string(),
+        );
+        let res = run(args(&[
+            "--index",
+            &index,
+            "--file",
+            &file,
+            "-a",
+            "news",
+            "bbc",
+            "https://bbc.com",
+        ]));
+        assert!(res.is_ok());
+        assert_eq!(
+            std::fs::read_to_string(&data_file).unwrap(),
+            "news->\n  bbc|https://bbc.com\n"
+        );
+        assert_eq!(std::fs::read_to_string(&index_file).unwrap(), "news|0\n");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_run_undo_names_the_command() {
+        let dir = temp_dir("undo");
+        let file = dir.join("demo.tap_data").display().to_string();
+        run(args(&[
+            "--file",
+            &file,
+            "-a",
+            "work",
+            "ci",
+            "https://ci.internal",
+        ]))
+        .unwrap();
+        run(args(&["--file", &file, "-d", "work", "--yes"])).unwrap();
+        let expected: Result<String, CommandError> = Ok(format!(
+            "{}\n  restored parent 'work' with 1 link(s)",
+            style::success("Undid tap -d work --yes")
+        ));
+        assert_eq!(
+            run(args(&["--file", &file, "--undo"])).map(|res| res.to_string()),
+            expected
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_run_read_only_flag() {
+        let dir = temp_dir("read_only");
+        let file = dir.join("demo.tap_data").display().to_string();
+        let add = ["-a", "work", "ci", "https://ci.internal"];
+        let err = run(args(
+            &[&["--read-only", "--file", &file], &add[..]].concat(),
+        ))
+        .unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::ReadOnly);
+        // The flag only applies to the run it was passed to
+        assert!(run(args(&[&["--file", &file], &add[..]].concat())).is_ok());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_context_global_flags() {
+        let (ctx, rest) = context(&args(&[
+            "--no-color",
+            "-q",
+            "--no-hooks",
+            "--profile",
+            "work",
+            "-s",
+            "news",
+        ]))
+        .unwrap();
+        assert_eq!(rest, args(&["-s", "news"]));
+        assert!(ctx.no_color);
+        assert_eq!(ctx.level, Level::Quiet);
+        assert!(ctx.store.options().no_hooks);
+        assert_eq!(ctx.store.options().profile.as_deref(), Some("work"));
+        assert_eq!(
+            ctx.store.options().operation.as_deref(),
+            Some("tap -s news")
+        );
+        let (ctx, _) = context(&args(&["-s"])).unwrap();
+        assert!(!ctx.no_color);
+        assert_eq!(ctx.store.options().profile, None);
+    }
+
+    #[test]
+    fn test_run_file_missing_path() {
+        let expected: Result<CommandResult, CommandError> = Err(CommandError::usage(
+            "expected a path after --file".to_string(),
+        ));
+        assert_eq!(run(args(&["--file"])), expected);
+    }
+
+    #[test]
+    fn test_run_index_without_file() {
+        let expected: Result<CommandResult, CommandError> = Err(CommandError::usage(
+            "--index can only be used together with --file".to_string(),
+        ));
+        assert_eq!(run(args(&["--index", "links.idx", "--show"])), expected);
+    }
+
+    #[test]
+    fn test_run_profile_with_file() {
+        let expected: Result<CommandResult, CommandError> = Err(CommandError::usage(
+            "--profile and --file can not be used together, --file picks the data file itself"
+                .to_string(),
+        ));
+        assert_eq!(
+            run(args(&["--profile", "work", "--file", "links", "--show"])),
+            expected
+        );
+        let err = run(args(&["--profile", "../work", "--show"])).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::Usage);
+    }
+
+    #[test]
+    fn test_run_log_level_flags() {
+        assert_eq!(run(args(&["-q", "-s", "--help"])), help(Show::default()));
+        assert_eq!(
+            run(args(&["--quiet", "-s", "--help"])),
+            help(Show::default())
+        );
+        assert_eq!(
+            run(args(&["--verbose", "-s", "--help"])),
+            help(Show::default())
+        );
+    }
+
+    #[test]
+    fn test_run_no_input_flag() {
+        assert_eq!(
+            run(args(&["--no-input", "-a", "--help"])),
+            help(Add::default())
+        );
+    }
+
+    #[test]
+    fn test_run_no_hooks_flag() {
+        assert_eq!(
+            run(args(&["--no-hooks", "-a", "--help"])),
+            help(Add::default())
+        );
+    }
+
+    #[test]
+    fn test_run_format_flag() {
+        assert_eq!(
+            run(args(&["--format", "yaml", "-s", "--help"])),
+            help(Show::default())
+        );
+        let expected: Result<CommandResult, CommandError> = Err(CommandError::usage(
+            "unknown format xml, the supported formats are: plain, json, yaml, table".to_string(),
+        ));
+        assert_eq!(run(args(&["--format", "xml", "--show"])), expected);
+        let expected: Result<CommandResult, CommandError> = Err(CommandError::usage(
+            "expected a format after --format".to_string(),
+        ));
+        assert_eq!(run(args(&["--format"])), expected);
+    }
+
+    #[test]
+    fn test_output_format() {
+        assert_eq!(output_format(&args(&["--show"])), Format::Plain);
+        assert_eq!(
+            output_format(&args(&["--format", "yaml", "--show"])),
+            Format::Yaml
+        );
+        assert_eq!(output_format(&args(&["--show", "--json"])), Format::Json);
+        assert_eq!(output_format(&args(&["--format", "xml"])), Format::Plain);
+    }
+
+    #[test]
+    fn test_run_no_color_flag() {
+        assert_eq!(
+            run(args(&["--no-color", "-s", "--help"])),
+            help(Show::default())
+        );
+    }
+
+    #[test]
+    fn test_run_hidden_commands() {
+        assert_eq!(
+            run(args(&["__complete", "--help"])),
+            help(Complete::default())
+        );
+        assert_eq!(
+            run(args(&["--generate-man", "--help"])),
+            help(Man::default())
+        );
+    }
+
+    #[test]
+    fn test_run_unknown_flag() {
+        let expected: Result<CommandResult, CommandError> = Err(CommandError::usage(
+            "unknown command --madeupflag, see the available commands with tap --help".to_string(),
+        ));
+        assert_eq!(run(args(&["--madeupflag"])), expected);
+        assert_eq!(run(args(&["--madeupflag", "link"])), expected);
+    }
+
+    #[test]
+    fn test_run_unknown_flag_suggests_commands() {
+        let expected: Result<CommandResult, CommandError> = Err(CommandError::usage(
+            "unknown command --sohw, did you mean --show?".to_string(),
+        ));
+        assert_eq!(run(args(&["--sohw", "work"])), expected);
+        let expected: Result<CommandResult, CommandError> = Err(CommandError::usage(
+            "unknown command --delte, did you mean --delete?".to_string(),
+        ));
+        assert_eq!(run(args(&["--delte"])), expected);
+    }
+
+    /// The words of an example command line after `tap` up to a redirect, e.g. `work spec -` for
+    /// `pbpaste | tap --add work spec -`
+    fn example_args(line: &str) -> Vec<String> {
+        line.split_whitespace()
+            .skip_while(|w| *w != "tap")
+            .skip(1)
+            .take_while(|w| *w != ">")
+            .map(|w| w.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_run_examples_select_their_command() {
+        for cmd in registry() {
+            for (line, _) in cmd.examples() {
+                let words = example_args(&line);
+                let mut rest = words.as_slice();
+                while let [flag, _, tail @ ..] = rest
+                    && (flag == "--file" || flag == "--index" || flag == "--format")
+                {
+                    rest = tail;
+                }
+                let (routed, _) = route(&rest[0]).unwrap_or_else(|e| panic!("{line}: {e}"));
+                assert_eq!(routed.help_message(), cmd.help_message(), "{line}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_run_examples_parse() {
+        // Commands writing outside the data file or not implemented yet are only routed above,
+        // `here` depends on the current directory, `--fzf` needs a terminal to pick in, `--serve`
+        // serves until stopped, and `--profile` writes the config file
+        let skipped = [
+            "-i, --init",
+            "--edit",
+            "--backup",
+            "--restore",
+            "--tui",
+            "--update",
+            "--migrate-data",
+            "--import",
+            "--export",
+            "here",
+            "--fzf",
+            "--serve",
+            "--profile",
+        ];
+        let dir = env::temp_dir().join(format!("tap_examples_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let data = dir.join("examples.tap_data");
+        for cmd in registry()
+            .iter()
+            .filter(|cmd| !skipped.contains(&cmd.name().as_str()))
+        {
+            // Values read from stdin would block the test
+            for (line, _) in cmd
+                .examples()
+                .iter()
+                .filter(|(line, _)| !line.contains('|'))
+            {
+                std::fs::write(
+                    &data,
+                    "search-engine->\n  google|https://google.com\nsearch-engines->\n  google|https://google.com\n  yahoo|https://yahoo.com\n",
+                )
+                .unwrap();
+                let _ = std::fs::remove_file(data.with_extension("tap_index"));
+                let mut words = args(&["--file", data.to_str().unwrap()]);
+                words.extend(example_args(line));
+                match run(words) {
+                    // The confirmation of `tap --delete <parent>` is refused without a terminal
+                    Err(e) if e.message.contains("pass --yes") => {}
+                    Err(e) => assert_ne!(e.kind, CommandErrorKind::Usage, "{line}: {e}"),
+                    Ok(_) => {}
+                }
+            }
+        }
     }
 }
@@ -1,37 +1,398 @@
-use super::utils::cli_usage_table::{Row, UsageTableBuilder};
+use super::utils::backup::ArchiveError;
+use super::utils::cli_usage_table::{DisplayCommandAsRow, Row, UsageTableBuilder};
+use super::utils::command::{Args, CommandUtilError, OpenLimit};
+use super::utils::config::{Config, default_config_path};
+use super::utils::context::Context;
+use super::utils::env_vars;
+use super::utils::format::Format;
+use super::utils::json::Json;
+use super::utils::link_type::LinkType;
+use super::utils::log;
+use super::utils::os_implementations::{
+    OsImplementationError, copy_to_clipboard, open_link, run_shell,
+};
+use super::utils::prompt::PromptError;
+use super::utils::style;
+use super::utils::suggest::{PrefixMatch, match_prefix, suggestions};
+use super::utils::tap_data_store::{
+    DataStoreHandle, LinkMeta, TapDataStoreError, TapDataStoreErrorKind,
+};
+use super::utils::template;
+use super::utils::yaml::Yaml;
 use std::fmt::{Display, Formatter};
 
 pub(crate) mod add;
+pub(crate) mod alias;
+pub(crate) mod archive;
+pub(crate) mod backup;
+pub(crate) mod broken;
+pub(crate) mod clean;
+pub(crate) mod compact;
+pub(crate) mod complete;
+pub(crate) mod completions;
+pub(crate) mod count;
+pub(crate) mod dedupe;
 pub(crate) mod delete;
+pub(crate) mod diff;
+pub(crate) mod doctor;
+pub(crate) mod edit;
+pub(crate) mod examples;
+pub(crate) mod exists;
 pub(crate) mod export;
+pub(crate) mod fzf;
+pub(crate) mod grep;
+pub(crate) mod group;
 pub(crate) mod help;
 pub(crate) mod here;
+pub(crate) mod history;
 pub(crate) mod import;
 pub(crate) mod init;
+pub(crate) mod last;
+pub(crate) mod list;
+pub(crate) mod man;
+pub(crate) mod migrate_data;
+pub(crate) mod note;
+pub(crate) mod open;
 pub(crate) mod parent_entity;
+pub(crate) mod paths;
+pub(crate) mod pin;
+pub(crate) mod profile;
+pub(crate) mod prune;
+pub(crate) mod random;
+pub(crate) mod restore;
+pub(crate) mod restore_trash;
+pub(crate) mod search;
+pub(crate) mod serve;
+pub(crate) mod session;
+pub(crate) mod set_default;
 pub(crate) mod show;
+pub(crate) mod sync;
+pub(crate) mod trash;
+pub(crate) mod tree;
 pub(crate) mod tui;
+pub(crate) mod unalias;
+pub(crate) mod unarchive;
+pub(crate) mod undo;
+pub(crate) mod unpin;
 pub(crate) mod update;
 pub(crate) mod upsert;
+pub(crate) mod verify;
 pub(crate) mod version;
+pub(crate) mod which;
 
 #[derive(Debug, PartialEq)]
 pub enum CommandResult {
+    /// Nothing is printed
+    Silent,
     Value(String),
+    /// Printed one item per line, e.g. names read by scripts
+    List(Vec<String>),
+    /// Printed one row per line with its columns separated by tabs, e.g. links and their values
+    /// for scripts to cut. `--format table` draws the columns as they are, whatever they hold.
+    Rows(Vec<Vec<String>>),
+    /// Printed as a title followed by one indented row per line
+    Table(Table),
+    /// Structured output of `--json`, serialized when printed
+    Json(Json),
+    /// Printed like the result it holds, but tap exits with 1, e.g. a report of broken links for
+    /// a cron job to alert on
+    Failure(Box<CommandResult>),
+}
+
+/// Rows printed under a title, with the columns of every row lined up
+#[derive(Debug, PartialEq)]
+pub struct Table {
+    pub title: String,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl CommandResult {
+    /// The text printed for the result in the given format. An empty string prints nothing.
+    pub fn render(&self, format: Format) -> String {
+        let structured = match format {
+            Format::Json | Format::Yaml => self.structured(),
+            Format::Plain | Format::Table => None,
+        };
+        match (structured, self) {
+            (Some(json), _) if format == Format::Yaml => Yaml(&json).to_string(),
+            (Some(json), _) => json.to_string(),
+            (None, CommandResult::Silent) => String::new(),
+            (None, CommandResult::Value(v)) => v.clone(),
+            (None, CommandResult::List(items)) if format == Format::Table => {
+                let rows: Vec<Vec<String>> = items.iter().map(|item| vec![item.clone()]).collect();
+                draw_table(&rows).join("\n")
+            }
+            (None, CommandResult::List(items)) => items.join("\n"),
+            (None, CommandResult::Rows(rows)) if format == Format::Table => {
+                draw_table(rows).join("\n")
+            }
+            (None, CommandResult::Rows(rows)) => rows
+                .iter()
+                .map(|row| row.join("\t"))
+                .collect::<Vec<String>>()
+                .join("\n"),
+            (None, CommandResult::Table(table)) if format == Format::Table => {
+                let mut lines = vec![table.title.clone()];
+                lines.extend(draw_table(&table.rows));
+                lines.join("\n")
+            }
+            (None, CommandResult::Table(table)) => table.to_string(),
+            (None, CommandResult::Json(j)) => j.to_string(),
+            (None, CommandResult::Failure(res)) => res.render(format),
+        }
+    }
+
+    /// The exit code of tap for the result, 0 unless it is a `Failure`
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CommandResult::Failure(_) => CommandErrorKind::Generic.exit_code(),
+            _ => 0,
+        }
+    }
+
+    /// The result as data for the structured formats, `None` for results printed as they are
+    fn structured(&self) -> Option<Json> {
+        match self {
+            CommandResult::Silent | CommandResult::Value(_) => None,
+            CommandResult::List(items) => Some(Json::Array(
+                items.iter().map(|i| Json::from(i.as_str())).collect(),
+            )),
+            CommandResult::Rows(rows) => Some(rows_json(rows)),
+            CommandResult::Table(table) => Some(Json::object([
+                ("title", Json::from(table.title.trim_end_matches(':'))),
+                ("rows", rows_json(&table.rows)),
+            ])),
+            CommandResult::Json(j) => Some(j.clone()),
+            CommandResult::Failure(res) => res.structured(),
+        }
+    }
+}
+
+/// `rows` as an array of arrays of strings
+fn rows_json(rows: &[Vec<String>]) -> Json {
+    Json::Array(
+        rows.iter()
+            .map(|row| Json::Array(row.iter().map(|c| Json::from(c.as_str())).collect()))
+            .collect(),
+    )
 }
 
 impl Display for CommandResult {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            CommandResult::Value(v) => v.fmt(f),
+        self.render(Format::Plain).fmt(f)
+    }
+}
+
+impl Display for Table {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.title)?;
+        for line in align_columns(&self.rows, "  ") {
+            write!(f, "\n{line}")?;
+        }
+        Ok(())
+    }
+}
+
+/// One line per row with the columns lined up, each column after `indent` and two spaces apart
+fn align_columns(rows: &[Vec<String>], indent: &str) -> Vec<String> {
+    let mut widths: Vec<usize> = vec![];
+    for row in rows {
+        for (i, cell) in row.iter().enumerate() {
+            match widths.get_mut(i) {
+                Some(width) => *width = (*width).max(cell.chars().count()),
+                None => widths.push(cell.chars().count()),
+            }
+        }
+    }
+    rows.iter()
+        .map(|row| {
+            let mut line = indent.to_string();
+            for (i, cell) in row.iter().enumerate() {
+                line.push_str(cell);
+                // The last column is not padded, so lines have no trailing spaces
+                if i + 1 < row.len() {
+                    line.push_str(&" ".repeat(widths[i] - cell.chars().count() + 2));
+                }
+            }
+            line
+        })
+        .collect()
+}
+
+/// The rows drawn with borders around every column, each as wide as its widest cell, for
+/// `--format table`. Rows with fewer cells than others are filled with empty ones.
+fn draw_table(rows: &[Vec<String>]) -> Vec<String> {
+    let mut widths: Vec<usize> = vec![];
+    for row in rows {
+        for (i, cell) in row.iter().enumerate() {
+            match widths.get_mut(i) {
+                Some(width) => *width = (*width).max(cell.chars().count()),
+                None => widths.push(cell.chars().count()),
+            }
         }
     }
+    if widths.is_empty() {
+        return vec![];
+    }
+    let border: String = widths
+        .iter()
+        .map(|width| format!("+{}", "-".repeat(width + 2)))
+        .chain(["+".to_string()])
+        .collect();
+    let mut lines = vec![border.clone()];
+    for row in rows {
+        let mut line = String::new();
+        for (i, width) in widths.iter().enumerate() {
+            let cell = row.get(i).map_or("", String::as_str);
+            line.push_str(&format!(
+                "| {cell}{} ",
+                " ".repeat(width - cell.chars().count())
+            ));
+        }
+        line.push('|');
+        lines.push(line);
+    }
+    lines.push(border);
+    lines
 }
 
 pub trait Command {
     fn error_message(&self) -> String;
     fn help_message(&self) -> String;
-    fn run(&self, parsed_args: Vec<String>) -> Result<CommandResult, String>;
+    fn run(
+        &self,
+        ctx: &mut Context,
+        parsed_args: Vec<String>,
+    ) -> Result<CommandResult, CommandError>;
+
+    /// The error returned when the command is given arguments it does not expect
+    fn usage_error(&self) -> CommandError {
+        CommandError::usage(self.error_message())
+    }
+}
+
+/// An error returned by a command. Its kind decides the exit code of tap, see
+/// `CommandErrorKind::exit_code`.
+#[derive(Debug, PartialEq)]
+pub struct CommandError {
+    pub kind: CommandErrorKind,
+    pub message: String,
+}
+
+impl CommandError {
+    pub fn usage(message: String) -> Self {
+        Self {
+            kind: CommandErrorKind::Usage,
+            message,
+        }
+    }
+
+    /// An error that prints nothing, its exit code is the answer, see `tap --exists`
+    pub fn silent(kind: CommandErrorKind) -> Self {
+        Self {
+            kind,
+            message: String::new(),
+        }
+    }
+
+    /// Adds a line to the message telling the user how to fix the error
+    pub fn with_hint(mut self, hint: &str) -> Self {
+        self.message.push('\n');
+        self.message.push_str(hint);
+        self
+    }
+}
+
+impl Display for CommandError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.message.fmt(f)
+    }
+}
+
+/// Any other error, e.g. of the OS utilities, is a generic error
+impl From<String> for CommandError {
+    fn from(message: String) -> Self {
+        Self {
+            kind: CommandErrorKind::Generic,
+            message,
+        }
+    }
+}
+
+/// A backup archive that can not be read is invalid data, like a data file that can not be parsed
+impl From<ArchiveError> for CommandError {
+    fn from(e: ArchiveError) -> Self {
+        Self {
+            kind: CommandErrorKind::InvalidData,
+            message: e.to_string(),
+        }
+    }
+}
+
+impl From<CommandUtilError> for CommandError {
+    fn from(e: CommandUtilError) -> Self {
+        e.to_string().into()
+    }
+}
+
+impl From<OsImplementationError> for CommandError {
+    fn from(e: OsImplementationError) -> Self {
+        e.to_string().into()
+    }
+}
+
+impl From<PromptError> for CommandError {
+    fn from(e: PromptError) -> Self {
+        e.to_string().into()
+    }
+}
+
+impl From<TapDataStoreError> for CommandError {
+    fn from(e: TapDataStoreError) -> Self {
+        let kind = match e.kind() {
+            TapDataStoreErrorKind::ParentEntityNotFound => CommandErrorKind::ParentNotFound,
+            TapDataStoreErrorKind::LinkNotFound => CommandErrorKind::LinkNotFound,
+            TapDataStoreErrorKind::ParseError => CommandErrorKind::InvalidData,
+            TapDataStoreErrorKind::ReadOnly => CommandErrorKind::ReadOnly,
+            TapDataStoreErrorKind::LinkAlreadyExists
+            | TapDataStoreErrorKind::DataFileChanged
+            | TapDataStoreErrorKind::ParentEntityAlreadyExists
+            | TapDataStoreErrorKind::ReservedKeyword
+            | TapDataStoreErrorKind::InvalidFileExtension
+            | TapDataStoreErrorKind::DataStoreAlreadyExists => CommandErrorKind::Generic,
+            _ => CommandErrorKind::DataStore,
+        };
+        Self {
+            kind,
+            message: e.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CommandErrorKind {
+    Generic,
+    Usage,
+    ParentNotFound,
+    LinkNotFound,
+    DataStore,
+    InvalidData,
+    ReadOnly,
+}
+
+impl CommandErrorKind {
+    /// The exit code of tap for the error, these are stable so scripts can rely on them
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CommandErrorKind::Generic => 1,
+            CommandErrorKind::Usage => 2,
+            CommandErrorKind::ParentNotFound => 3,
+            CommandErrorKind::LinkNotFound => 4,
+            CommandErrorKind::DataStore => 5,
+            CommandErrorKind::InvalidData => 6,
+            CommandErrorKind::ReadOnly => 7,
+        }
+    }
 }
 
 // Utility Messages used across commands
@@ -39,30 +400,831 @@ pub(in crate::commands) fn display_version() -> String {
     format!("{} v{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))
 }
 
+/// The Example Usage section of a help message, one `  - description: command line` per example
+pub(in crate::commands) fn display_examples(examples: &[(String, String)]) -> String {
+    let mut s = String::from("Example Usage: \n\n");
+    for (line, description) in examples {
+        s.push_str(&format!("  - {description}: {line}\n"));
+    }
+    s
+}
+
+/// A size in bytes, e.g. `12 B` or `1.2 KB`
+pub(in crate::commands) fn display_bytes(bytes: usize) -> String {
+    if bytes < 1024 {
+        format!("{bytes} B")
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
+/// `value` cut to `room` characters, ending with `...` when it was cut
+pub(in crate::commands) fn truncate(value: &str, room: Option<usize>) -> String {
+    match room {
+        Some(room) if value.chars().count() > room => {
+            let kept: String = value.chars().take(room.saturating_sub(3)).collect();
+            format!("{kept}...")
+        }
+        _ => value.to_string(),
+    }
+}
+
+/// A command listed in the usage table
+pub(crate) trait ListedCommand: Command + DisplayCommandAsRow {}
+
+impl<T: Command + DisplayCommandAsRow> ListedCommand for T {}
+
+/// Every command in the order of the usage table. Anything that needs to know the commands, like
+/// the suggestions for a mistyped one, goes through here so new commands are picked up for free.
+pub(crate) fn registry() -> Vec<Box<dyn ListedCommand>> {
+    vec![
+        // Opening Links:
+        Box::new(parent_entity::ParentEntity::default()),
+        Box::new(here::Here::default()),
+        Box::new(open::Open::default()),
+        Box::new(last::Last::default()),
+        Box::new(session::Session::default()),
+        Box::new(group::Group::default()),
+        Box::new(random::Random::default()),
+        Box::new(fzf::Fzf::default()),
+        // Adding, Updating, and Deleting Links:
+        Box::new(add::Add::default()),
+        Box::new(delete::Delete::default()),
+        Box::new(show::Show::default()),
+        Box::new(list::List::default()),
+        Box::new(tree::Tree::default()),
+        Box::new(upsert::Upsert::default()),
+        Box::new(pin::Pin::default()),
+        Box::new(unpin::Unpin::default()),
+        Box::new(note::Note::default()),
+        Box::new(set_default::SetDefault::default()),
+        Box::new(alias::Alias::default()),
+        Box::new(unalias::Unalias::default()),
+        Box::new(archive::Archive::default()),
+        Box::new(unarchive::Unarchive::default()),
+        Box::new(which::Which::default()),
+        Box::new(exists::Exists::default()),
+        Box::new(count::Count::default()),
+        Box::new(search::Search::default()),
+        Box::new(grep::Grep::default()),
+        // Utility Commands:
+        Box::new(init::Init::default()),
+        Box::new(completions::Completions::default()),
+        Box::new(doctor::Doctor::default()),
+        Box::new(clean::Clean::default()),
+        Box::new(dedupe::Dedupe::default()),
+        Box::new(verify::Verify::default()),
+        Box::new(broken::Broken::default()),
+        Box::new(edit::Edit::default()),
+        Box::new(paths::Paths::default()),
+        Box::new(profile::Profile::default()),
+        Box::new(compact::Compact::default()),
+        Box::new(prune::Prune::default()),
+        Box::new(backup::Backup::default()),
+        Box::new(restore::Restore::default()),
+        Box::new(undo::Undo::default()),
+        Box::new(history::History::default()),
+        Box::new(trash::Trash::default()),
+        Box::new(restore_trash::RestoreTrash::default()),
+        Box::new(migrate_data::MigrateData::default()),
+        Box::new(import::Import::default()),
+        Box::new(diff::Diff::default()),
+        Box::new(export::Export::default()),
+        Box::new(sync::GitSync::default()),
+        Box::new(serve::Serve::default()),
+        Box::new(tui::Tui::default()),
+        Box::new(update::Update::default()),
+        // Other Commands:
+        Box::new(help::Help::default()),
+        Box::new(examples::Examples::default()),
+        Box::new(version::Version::default()),
+    ]
+}
+
+/// The flags of every command, e.g. `-a` and `--add`
+pub(in crate::commands) fn command_flags() -> Vec<String> {
+    registry()
+        .iter()
+        .flat_map(|cmd| {
+            cmd.name()
+                .split(", ")
+                .filter(|name| name.starts_with('-'))
+                .map(|name| name.to_string())
+                .collect::<Vec<String>>()
+        })
+        .collect()
+}
+
+/// The error for a first argument that looks like a command but is not one, suggesting the
+/// closest commands if there are any
+pub(crate) fn unknown_command_error(flag: &str) -> CommandError {
+    let flags = command_flags();
+    let suggestions = suggestions(flag, &flags);
+    CommandError::usage(match suggestions.is_empty() {
+        true => format!("unknown command {flag}, see the available commands with tap --help"),
+        false => format!(
+            "unknown command {flag}, did you mean {}?",
+            suggestions.join(" or ")
+        ),
+    })
+}
+
 pub(in crate::commands) fn display_commands() -> String {
+    let rows = registry()
+        .iter()
+        .map(|cmd| Row::new(cmd.as_ref()))
+        .collect();
+    let open_options = open::OPTIONS
+        .iter()
+        .map(|(option, description)| Row::example(option, description))
+        .collect();
     let res = UsageTableBuilder::new("Usage:")
-        .add_section(
-            "Commands:",
-            vec![
-                // Opening Links:
-                Row::new(parent_entity::ParentEntity::default()),
-                Row::new(here::Here::default()),
-                // Adding, Updating, and Deleting Links:
-                Row::new(add::Add::default()),
-                Row::new(delete::Delete::default()),
-                Row::new(show::Show::default()),
-                Row::new(upsert::Upsert::default()),
-                // Utility Commands:
-                Row::new(init::Init::default()),
-                Row::new(import::Import::default()),
-                Row::new(export::Export::default()),
-                Row::new(tui::Tui::default()),
-                Row::new(update::Update::default()),
-                // Other Commands:
-                Row::new(help::Help::default()),
-                Row::new(version::Version::default()),
-            ],
-        )
+        .add_section("Commands:", rows)
+        .add_section("Open options:", open_options)
         .build();
     res.to_string()
 }
+
+pub(in crate::commands) fn display_exit_codes() -> String {
+    let mut s = String::from("Exit Codes:\n");
+    s.push_str("  0 success\n");
+    s.push_str("  1 generic error\n");
+    s.push_str("  2 usage error (unknown command or unexpected arguments)\n");
+    s.push_str("  3 parent entity not found\n");
+    s.push_str("  4 link not found\n");
+    s.push_str("  5 data store I/O error\n");
+    s.push_str("  6 data file can not be parsed\n");
+    s.push_str("  7 data file can not be changed in read-only mode");
+    s
+}
+
+// Name resolution used by the commands opening links
+/// The names directly under `parent` in the hierarchy of slash-separated parent entity names, e.g.
+/// `work/infra` under `work` when there is a `work/infra/grafana`. A child may be a parent entity,
+/// a group of them, or both.
+pub(in crate::commands) fn children_of(parents: &[String], parent: &str) -> Vec<String> {
+    let prefix = format!("{}/", parent.trim());
+    let mut children: Vec<String> = vec![];
+    for rest in parents.iter().filter_map(|p| p.strip_prefix(&prefix)) {
+        let child = format!("{prefix}{}", rest.split('/').next().unwrap_or_default());
+        if !children.contains(&child) {
+            children.push(child);
+        }
+    }
+    children
+}
+
+/// Resolves a parent entity typed as the start of its name, e.g. `sea` for `search-engines`.
+/// Names that are not the start of any parent are returned as they are, so reading them fails with
+/// the usual not found error.
+pub(in crate::commands) fn resolve_parent_prefix(
+    store: &DataStoreHandle,
+    parent: &str,
+) -> Result<String, CommandError> {
+    // Aliases are taken as they are, before any parent that starts the same way
+    if store.aliases()?.contains_key(parent.trim()) {
+        return Ok(parent.to_string());
+    }
+    // The start of a name only stands for names nested at the same depth, so `wo` is taken for
+    // the group `work` of `work/infra/grafana` but never for `work/infra/grafana` itself
+    let mut parents: Vec<String> = vec![];
+    for p in store.parents()? {
+        for (i, _) in p.match_indices('/').chain([(p.len(), "")]) {
+            let name = &p[..i];
+            if !name.get(parent.len()..).unwrap_or_default().contains('/')
+                && !parents.iter().any(|known| known == name)
+            {
+                parents.push(name.to_string());
+            }
+        }
+    }
+    match match_prefix(parent, &parents) {
+        PrefixMatch::Exact | PrefixMatch::NoMatch => Ok(parent.to_string()),
+        PrefixMatch::Unique(resolved) => {
+            log::verbose(format_args!("Resolved parent {parent} to {resolved}"));
+            Ok(resolved.to_string())
+        }
+        PrefixMatch::Ambiguous(matches) => Err(CommandError {
+            kind: CommandErrorKind::ParentNotFound,
+            message: format!(
+                "Parent '{parent}' is the start of several parent entities: {}",
+                matches.join(", ")
+            ),
+        }),
+    }
+}
+
+/// Resolves a link of `parent` typed as the start of its name, see `resolve_parent_prefix`
+pub(in crate::commands) fn resolve_link_prefix(
+    store: &DataStoreHandle,
+    parent: &str,
+    link: &str,
+) -> Result<String, CommandError> {
+    let links = store.links(parent)?;
+    match match_prefix(link, &links) {
+        PrefixMatch::Exact | PrefixMatch::NoMatch => Ok(link.to_string()),
+        PrefixMatch::Unique(resolved) => {
+            log::verbose(format_args!("Resolved link {link} to {resolved}"));
+            Ok(resolved.to_string())
+        }
+        PrefixMatch::Ambiguous(matches) => Err(CommandError {
+            kind: CommandErrorKind::LinkNotFound,
+            message: format!(
+                "Link '{link}' is the start of several links of parent '{parent}': {}",
+                matches.join(", ")
+            ),
+        }),
+    }
+}
+
+/// The links of `parent` with the pinned ones first, each with whether it is pinned
+pub(in crate::commands) fn links_pinned_first(
+    store: &DataStoreHandle,
+    parent: &str,
+) -> Result<Vec<(String, bool)>, CommandError> {
+    let meta = store.read_meta(parent)?;
+    let mut links: Vec<(String, bool)> = store
+        .links(parent)?
+        .into_iter()
+        .map(|link| {
+            let pinned = meta.get(&link).is_some_and(|meta| meta.pinned);
+            (link, pinned)
+        })
+        .collect();
+    // Sorting is stable, so the links stay in order within the pinned and unpinned ones
+    links.sort_by_key(|(_, pinned)| !pinned);
+    Ok(links)
+}
+
+/// The pinned links among the `links` of `parent`, failing when none of them is pinned
+pub(in crate::commands) fn only_pinned(
+    store: &DataStoreHandle,
+    parent: &str,
+    links: Vec<(String, String)>,
+) -> Result<Vec<(String, String)>, CommandError> {
+    let meta = store.read_meta(parent)?;
+    let pinned: Vec<(String, String)> = links
+        .into_iter()
+        .filter(|(link, _)| meta.get(link).is_some_and(|meta| meta.pinned))
+        .collect();
+    if pinned.is_empty() {
+        return Err(CommandError {
+            kind: CommandErrorKind::LinkNotFound,
+            message: format!("No links of parent '{parent}' are pinned"),
+        }
+        .with_hint(&format!("Pin one with tap --pin {parent} <Link Name>")));
+    }
+    Ok(pinned)
+}
+
+/// The link type named by `name`, failing with the names of the types when it names none
+pub(in crate::commands) fn parse_link_type(name: &str) -> Result<LinkType, CommandError> {
+    LinkType::parse(name).ok_or_else(|| {
+        let names: Vec<&str> = LinkType::ALL.iter().map(|t| t.name()).collect();
+        CommandError::usage(format!(
+            "unknown link type {name}, the types are: {}",
+            names.join(", ")
+        ))
+    })
+}
+
+/// The link types passed with `--type`, like `--type file,dir`, that `tap --show`,
+/// `tap <Parent Entity>`, and `tap here` keep the links of
+pub(in crate::commands) struct TypeFilter {
+    types: Vec<LinkType>,
+}
+
+impl TypeFilter {
+    /// Takes `--type <Types>` out of the arguments, `None` when it was not passed
+    pub fn take(args: &mut Args) -> Result<Option<Self>, CommandError> {
+        let Some(names) = args.take_option("--type") else {
+            return Ok(None);
+        };
+        let types = names
+            .split(',')
+            .map(parse_link_type)
+            .collect::<Result<Vec<LinkType>, CommandError>>()?;
+        Ok(Some(Self { types }))
+    }
+
+    /// Whether a link with `meta` and `value` is of one of the types. Links added before links had
+    /// types are told by their value, see `LinkType::detect`.
+    pub fn keeps(&self, meta: Option<&LinkMeta>, value: &str) -> bool {
+        meta.and_then(|meta| meta.link_type)
+            .or_else(|| LinkType::detect(value))
+            .is_some_and(|link_type| self.types.contains(&link_type))
+    }
+
+    /// The types for messages, like `file or dir`
+    pub fn names(&self) -> String {
+        let names: Vec<&str> = self.types.iter().map(|t| t.name()).collect();
+        names.join(" or ")
+    }
+}
+
+/// The links among the `links` of `parent` that `types` keeps, failing when it keeps none
+pub(in crate::commands) fn only_of_types(
+    store: &DataStoreHandle,
+    parent: &str,
+    links: Vec<(String, String)>,
+    types: &TypeFilter,
+) -> Result<Vec<(String, String)>, CommandError> {
+    let meta = store.read_meta(parent)?;
+    let kept: Vec<(String, String)> = links
+        .into_iter()
+        .filter(|(link, value)| types.keeps(meta.get(link), value))
+        .collect();
+    if kept.is_empty() {
+        return Err(CommandError {
+            kind: CommandErrorKind::LinkNotFound,
+            message: format!(
+                "No links of parent '{parent}' are of type {}",
+                types.names()
+            ),
+        });
+    }
+    Ok(kept)
+}
+
+/// The links `tap <Parent Entity>` opens: the pinned ones with `--pinned`, otherwise the default
+/// link of the parent unless `--all` is passed, and all of them when it has no default link.
+/// Expired links are left out unless `include_expired` is set.
+pub(in crate::commands) fn links_to_open(
+    store: &DataStoreHandle,
+    parent: &str,
+    pinned: bool,
+    all: bool,
+    include_expired: bool,
+) -> Result<Vec<(String, String)>, CommandError> {
+    let mut links = store.read_parent(parent)?;
+    let meta = store.read_meta(parent)?;
+    if !include_expired {
+        let (expired, current): (Vec<_>, Vec<_>) = links
+            .into_iter()
+            .partition(|(link, _)| meta.get(link).is_some_and(LinkMeta::is_expired));
+        if current.is_empty() {
+            return Err(CommandError {
+                kind: CommandErrorKind::LinkNotFound,
+                message: format!("Every link of parent '{parent}' has expired"),
+            }
+            .with_hint("Pass --include-expired to open them anyway, or remove them with tap --clean --expired"));
+        }
+        if !expired.is_empty() {
+            let names: Vec<&str> = expired.iter().map(|(link, _)| link.as_str()).collect();
+            log::note(format_args!(
+                "Skipped {} expired link(s): {}, pass --include-expired to open them",
+                names.len(),
+                names.join(", ")
+            ));
+        }
+        links = current;
+    }
+    if pinned {
+        return only_pinned(store, parent, links);
+    }
+    match links
+        .iter()
+        .find(|(link, _)| meta.get(link).is_some_and(|meta| meta.default))
+    {
+        Some(default) if !all => Ok(vec![default.clone()]),
+        _ => Ok(links),
+    }
+}
+
+/// Warns when the link about to be opened by name has expired, it is opened all the same
+pub(in crate::commands) fn note_if_expired(
+    store: &DataStoreHandle,
+    parent: &str,
+    link: &str,
+) -> Result<(), CommandError> {
+    let meta = store.read_meta(parent)?;
+    if let Some(date) = meta
+        .get(link)
+        .filter(|meta| meta.is_expired())
+        .and_then(|meta| meta.expires.as_deref())
+    {
+        log::note(format_args!(
+            "Link {link} of parent entity {parent} expired on {date}"
+        ));
+    }
+    Ok(())
+}
+
+/// The `--arg <name>=<value>` options, filling the placeholders of templated values
+pub(in crate::commands) fn take_template_args(
+    args: &mut Args,
+) -> Result<Vec<(String, String)>, CommandError> {
+    let mut pairs = vec![];
+    while let Some(arg) = args.take_option("--arg") {
+        match arg.split_once('=') {
+            Some((name, value)) if !name.trim().is_empty() => {
+                pairs.push((name.trim().to_string(), value.to_string()))
+            }
+            _ => {
+                return Err(CommandError::usage(format!(
+                    "expected --arg <name>=<value>, got --arg {arg}"
+                )));
+            }
+        }
+    }
+    Ok(pairs)
+}
+
+/// The value of `link` to open: its environment variables expanded, see `expand_env`, then its
+/// placeholders filled from `template_args`, see `template::fill`. Fails naming the placeholders no
+/// value was given for.
+pub(in crate::commands) fn value_to_open(
+    parent: &str,
+    link: &str,
+    value: &str,
+    template_args: &[(String, String)],
+) -> Result<String, CommandError> {
+    let value = expand_env(value)?;
+    template::fill(&value, template_args).map_err(|missing| {
+        CommandError::usage(format!(
+            "Link {link} of parent entity {parent} is a template, no value was given for: {}",
+            missing.join(", ")
+        ))
+        .with_hint(&format!(
+            "Pass them like tap {parent} {link} --arg {}=<value>",
+            missing[0]
+        ))
+    })
+}
+
+/// The config file key that lets links of type cmd run without asking first, see `open_value`
+const RUN_COMMANDS_CONFIG_KEY: &str = "run_commands";
+
+/// Opens `value`, the value of `link` of `parent` as returned by `value_to_open`, the way the type
+/// of the link says. Commands are run by the shell, and as a data file can hold any command, only
+/// after the user confirms unless the config file sets `run_commands = true`. Anything else is
+/// opened with `open_link`. Returns whether it was opened, `false` when a command was not confirmed.
+/// Opened links are recorded for `tap --last`, see `DataStoreHandle::record_opened`.
+pub(in crate::commands) fn open_value(
+    ctx: &Context,
+    parent: &str,
+    link: &str,
+    value: &str,
+) -> Result<bool, CommandError> {
+    let link_type = ctx
+        .store
+        .read_meta(parent)?
+        .get(link)
+        .and_then(|meta| meta.link_type);
+    if link_type != Some(LinkType::Cmd) {
+        open_link(value)?;
+        ctx.store.record_opened(parent, link);
+        return Ok(true);
+    }
+    if !run_commands_configured() {
+        if !ctx.prompt.is_interactive() {
+            return Err(CommandError::from(format!(
+                "Link {link} of parent entity {parent} runs the command {value}, which needs confirmation"
+            ))
+            .with_hint(&format!(
+                "Set {RUN_COMMANDS_CONFIG_KEY} = true in the config file to run commands without asking"
+            )));
+        }
+        if !ctx.prompt.confirm(&format!("Run {value}?"))? {
+            return Ok(false);
+        }
+    }
+    run_shell(value)?;
+    ctx.store.record_opened(parent, link);
+    Ok(true)
+}
+
+/// Puts `value`, the value of a link as returned by `value_to_open`, on the clipboard instead of
+/// opening it. The value is printed as well, so it can be copied by hand where there is no
+/// clipboard, e.g. over SSH. A clipboard that can not be written to is reported and fails the
+/// command, with the value still printed.
+pub(in crate::commands) fn copy_value(value: &str) -> CommandResult {
+    match copy_to_clipboard(value) {
+        Ok(()) => CommandResult::Value(value.to_string()),
+        Err(e) => {
+            log::note(format_args!(
+                "Could not copy the value to the clipboard: {e}"
+            ));
+            CommandResult::Failure(Box::new(CommandResult::Value(value.to_string())))
+        }
+    }
+}
+
+/// The parent entity and link named by `link`, written as `<Parent Entity>/<Link Name>`. Parent
+/// entities can nest with `/` and link names may hold one too, so every split is tried from the
+/// right and the first naming an existing link is used.
+pub(in crate::commands) fn split_link(
+    ctx: &Context,
+    link: &str,
+) -> Result<(String, String), CommandError> {
+    let splits: Vec<(&str, &str)> = link
+        .trim()
+        .rmatch_indices('/')
+        .map(|(i, _)| (&link.trim()[..i], &link.trim()[i + 1..]))
+        .filter(|(parent, link)| !parent.is_empty() && !link.is_empty())
+        .collect();
+    let Some(&(last_parent, last_link)) = splits.first() else {
+        return Err(CommandError::usage(format!(
+            "expected a link as <Parent Entity>/<Link Name>, got {link}"
+        )));
+    };
+    for &(parent, link) in &splits {
+        match ctx.store.read_link(parent, link) {
+            Ok(_) => return Ok((parent.to_string(), link.to_string())),
+            Err(e) if is_missing(e.kind()) => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+    // None of the splits exist, so the error names the most likely one
+    let e = ctx.store.read_link(last_parent, last_link).unwrap_err();
+    Err(ctx
+        .store
+        .with_suggestions(e, last_parent, Some(last_link))
+        .into())
+}
+
+/// Whether `kind` is the parent entity or link not being found
+pub(in crate::commands) fn is_missing(kind: &TapDataStoreErrorKind) -> bool {
+    matches!(
+        kind,
+        TapDataStoreErrorKind::ParentEntityNotFound | TapDataStoreErrorKind::LinkNotFound
+    )
+}
+
+/// Whether the config file sets `run_commands = true`. A config file that can not be read does
+/// not.
+fn run_commands_configured() -> bool {
+    default_config_path()
+        .and_then(|path| Config::load(Some(path)).ok())
+        .and_then(|config| config.get(RUN_COMMANDS_CONFIG_KEY)?.parse().ok())
+        .unwrap_or(false)
+}
+
+/// The config file key that turns off expanding environment variables in values, see `expand_env`
+const EXPAND_ENV_CONFIG_KEY: &str = "expand_env";
+
+/// `value` with the environment variables it names, like `$HOME` or `${JIRA_HOST}`, expanded as
+/// it is opened or shown. The data file keeps the value as it was written. Fails naming a variable
+/// that is not set, rather than opening a broken path. Setting `expand_env = false` in the config
+/// file leaves values as they are.
+pub(in crate::commands) fn expand_env(value: &str) -> Result<String, CommandError> {
+    if !value.contains('$') || !expand_env_configured() {
+        return Ok(value.to_string());
+    }
+    env_vars::expand(value, |name| std::env::var(name).ok()).map_err(|reason| {
+        CommandError::from(reason).with_hint(&format!(
+            "Set it, or write \\$ in the value for a literal $ (or set {EXPAND_ENV_CONFIG_KEY} = false in the config file)"
+        ))
+    })
+}
+
+/// Whether the config file leaves environment variables in values expanded, which it does
+/// unless it sets `expand_env = false`. A config file that can not be read does.
+fn expand_env_configured() -> bool {
+    default_config_path()
+        .and_then(|path| Config::load(Some(path)).ok())
+        .and_then(|config| config.get(EXPAND_ENV_CONFIG_KEY)?.parse().ok())
+        .unwrap_or(true)
+}
+
+/// The links out of `links` of `parent` to open at once. With `pick`, or when the config file sets
+/// `open_many = pick` and there are more links than the open limit, they are picked from a numbered
+/// list. Otherwise more links than the limit are confirmed first, see `OpenLimit`. Either way,
+/// more links than `max_open` in the config file are an error unless `force` is set. Returns no
+/// links when nothing is to be opened.
+pub(in crate::commands) fn choose_links_to_open(
+    ctx: &Context,
+    parent: &str,
+    links: Vec<(String, String)>,
+    pick: bool,
+    yes: bool,
+    force: bool,
+) -> Result<Vec<(String, String)>, CommandError> {
+    let limit = OpenLimit::load();
+    let too_many = links.len() > limit.limit && !yes;
+    if pick || (limit.pick && too_many && ctx.prompt.is_interactive()) {
+        let picked = pick_links(ctx, parent, links)?;
+        limit.check_max(picked.len(), force)?;
+        return Ok(picked);
+    }
+    limit.check_max(links.len(), force)?;
+    match limit.confirm(&ctx.prompt, links.len(), yes)? {
+        true => Ok(links),
+        false => Ok(vec![]),
+    }
+}
+
+/// Lists `links` numbered from 1, the way tap --show lists them with their values, and asks
+/// which of them to open
+pub(in crate::commands) fn pick_links(
+    ctx: &Context,
+    parent: &str,
+    links: Vec<(String, String)>,
+) -> Result<Vec<(String, String)>, CommandError> {
+    // Waiting for an answer that can never come would hang scripts
+    if !ctx.prompt.is_interactive() {
+        return Err(CommandError::usage(
+            "--pick asks which links to open and needs a terminal, name the links to open instead"
+                .to_string(),
+        ));
+    }
+    let meta = ctx.store.read_meta(parent)?;
+    let numbers: Vec<String> = (1..=links.len()).map(|i| format!("{i})")).collect();
+    let number_width = numbers.last().map_or(0, String::len);
+    let name_width = links
+        .iter()
+        .map(|(link, _)| link.chars().count())
+        .max()
+        .unwrap_or(0);
+    // The numbers take a column of their own in front of the rows of tap --show
+    let width = style::terminal_width().map(|w| w.saturating_sub(number_width + 2));
+    let rows = numbers
+        .into_iter()
+        .zip(show::link_rows(
+            links.clone(),
+            &meta,
+            true,
+            name_width,
+            width,
+        ))
+        .map(|(number, mut row)| {
+            row.insert(0, number);
+            row
+        })
+        .collect();
+    let list = Table {
+        title: format!("Links of parent entity {}:", style::parent(parent)),
+        rows,
+    };
+    let picked = ctx
+        .prompt
+        .pick("Links to open", &list.to_string(), links.len())?;
+    Ok(links
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| picked.contains(i))
+        .map(|(_, link)| link)
+        .collect())
+}
+
+/// The config file key that makes adding a value stored under another link an error, see
+/// `check_duplicates`
+const NO_DUPLICATES_CONFIG_KEY: &str = "no_duplicates";
+
+/// Warns about the values of `pairs` that are already stored under another link, compared
+/// normalized, see `dedupe::normalize_value`. With `strict`, or `no_duplicates = true` in the config
+/// file, that is an error instead.
+pub(in crate::commands) fn check_duplicates(
+    store: &DataStoreHandle,
+    parent: &str,
+    pairs: &[(String, String)],
+    strict: bool,
+) -> Result<(), CommandError> {
+    let normalize = dedupe::normalize_value;
+    let parent = store.resolve_alias(parent)?;
+    let all_links = store.all_links()?;
+    for (link, value) in pairs {
+        let value = normalize(value);
+        let places: Vec<String> = all_links
+            .iter()
+            .flat_map(|(p, links)| links.iter().map(move |(l, v)| (p, l, v)))
+            .filter(|&(p, l, v)| (p != &parent || l != link) && normalize(v) == value)
+            .map(|(p, l, _)| format!("{p}/{l}"))
+            .collect();
+        if places.is_empty() {
+            continue;
+        }
+        let message = format!("{value} is already stored as {}", places.join(", "));
+        if strict || no_duplicates_configured() {
+            return Err(CommandError {
+                kind: CommandErrorKind::Generic,
+                message,
+            }
+            .with_hint("Pass --force to store it anyway"));
+        }
+        log::note(format_args!("{message}"));
+    }
+    Ok(())
+}
+
+/// Whether the config file sets `no_duplicates = true`. A config file that can not be read does
+/// not.
+fn no_duplicates_configured() -> bool {
+    default_config_path()
+        .and_then(|path| Config::load(Some(path)).ok())
+        .and_then(|config| config.get(NO_DUPLICATES_CONFIG_KEY)?.parse().ok())
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn links_table() -> CommandResult {
+        CommandResult::Table(Table {
+            title: "Links:".to_string(),
+            rows: vec![
+                vec!["google".to_string(), "https://google.com".to_string()],
+                vec!["ci".to_string(), "https://ci.me".to_string()],
+            ],
+        })
+    }
+
+    #[test]
+    fn test_render_silent() {
+        for format in Format::ALL {
+            assert_eq!(CommandResult::Silent.render(format), "");
+        }
+    }
+
+    #[test]
+    fn test_render_value() {
+        let res = CommandResult::Value("https://google.com".to_string());
+        for format in Format::ALL {
+            assert_eq!(res.render(format), "https://google.com");
+        }
+    }
+
+    #[test]
+    fn test_render_list() {
+        let res = CommandResult::List(vec!["google".to_string(), "yahoo".to_string()]);
+        assert_eq!(res.render(Format::Plain), "google\nyahoo");
+        assert_eq!(res.render(Format::Json), r#"["google","yahoo"]"#);
+        assert_eq!(res.render(Format::Yaml), "- google\n- yahoo");
+        assert_eq!(
+            res.render(Format::Table),
+            "+--------+\n| google |\n| yahoo  |\n+--------+"
+        );
+        assert_eq!(CommandResult::List(vec![]).render(Format::Table), "");
+        assert_eq!(CommandResult::List(vec![]).render(Format::Plain), "");
+        assert_eq!(CommandResult::List(vec![]).render(Format::Json), "[]");
+    }
+
+    #[test]
+    fn test_render_rows() {
+        let res = CommandResult::Rows(vec![
+            vec!["google".to_string(), "https://google.com".to_string()],
+            vec!["ci".to_string(), "https://ci.me".to_string()],
+        ]);
+        assert_eq!(
+            res.render(Format::Plain),
+            "google\thttps://google.com\nci\thttps://ci.me"
+        );
+        assert_eq!(
+            res.render(Format::Table),
+            "+--------+--------------------+\n| google | https://google.com |\n| ci     | https://ci.me      |\n+--------+--------------------+"
+        );
+        assert_eq!(
+            res.render(Format::Json),
+            r#"[["google","https://google.com"],["ci","https://ci.me"]]"#
+        );
+        // A tab in a value stays in its column
+        let res = CommandResult::Rows(vec![vec!["a".to_string(), "b\tc".to_string()]]);
+        assert_eq!(
+            res.render(Format::Table),
+            "+---+-----+\n| a | b\tc |\n+---+-----+"
+        );
+        let res = CommandResult::List(vec!["b\tc".to_string()]);
+        assert_eq!(res.render(Format::Table), "+-----+\n| b\tc |\n+-----+");
+    }
+
+    #[test]
+    fn test_render_table() {
+        assert_eq!(
+            links_table().render(Format::Plain),
+            "Links:\n  google  https://google.com\n  ci      https://ci.me"
+        );
+        assert_eq!(
+            links_table().render(Format::Table),
+            "Links:\n+--------+--------------------+\n| google | https://google.com |\n| ci     | https://ci.me      |\n+--------+--------------------+"
+        );
+        assert_eq!(
+            links_table().render(Format::Json),
+            r#"{"title":"Links","rows":[["google","https://google.com"],["ci","https://ci.me"]]}"#
+        );
+        assert_eq!(
+            links_table().render(Format::Yaml),
+            "title: Links\nrows:\n  - - google\n    - https://google.com\n  - - ci\n    - https://ci.me"
+        );
+    }
+
+    #[test]
+    fn test_render_json() {
+        let res = CommandResult::Json(Json::object([(
+            "parents",
+            Json::Array(vec![Json::from("work")]),
+        )]));
+        assert_eq!(res.render(Format::Plain), r#"{"parents":["work"]}"#);
+        assert_eq!(res.render(Format::Json), r#"{"parents":["work"]}"#);
+        assert_eq!(res.render(Format::Yaml), "parents:\n  - work");
+    }
+
+    #[test]
+    fn test_display_bytes() {
+        assert_eq!(display_bytes(12), "12 B");
+        assert_eq!(display_bytes(1229), "1.2 KB");
+        assert_eq!(display_bytes(3 * 1024 * 1024), "3.0 MB");
+    }
+}
@@ -1,14 +1,26 @@
 use crate::{
-    commands::{Command, CommandResult},
+    commands::{
+        Command, CommandError, CommandResult, check_duplicates, display_examples, parse_link_type,
+    },
     utils::cli_usage_table::DisplayCommandAsRow,
-    utils::command::get_current_directory_name,
-    utils::tap_data_store::DataStore,
+    utils::command::{Args, link_value_pairs, parent_arg, resolve_value},
+    utils::context::Context,
+    utils::expiry,
+    utils::link_name,
+    utils::link_type::LinkType,
+    utils::log,
+    utils::os_implementations::http_get_html,
+    utils::style,
+    utils::tap_data_store::{validate_link, validate_parent},
 };
 
+/// How long fetching a page for its title may take, see `name_link`
+const TITLE_TIMEOUT_SECS: u64 = 5;
+
 pub(crate) struct Add {
     name: String,
     description: String,
-    args: [String; 3],
+    args: [String; 9],
 }
 
 impl Default for Add {
@@ -18,8 +30,14 @@ impl Default for Add {
             description: "Add a new link".to_string(),
             args: [
                 "<Parent|here>".to_string(),
-                "<Link>".to_string(),
-                "<Value>".to_string(),
+                "[Link]".to_string(),
+                "<Value|->".to_string(),
+                "[--expires <Date|Duration>]".to_string(),
+                "[--name <Link>]".to_string(),
+                "[--offline]".to_string(),
+                "[--no-duplicates]".to_string(),
+                "[--force]".to_string(),
+                "[--type <url|file|dir|cmd>]".to_string(),
             ],
         }
     }
@@ -27,61 +45,200 @@ impl Default for Add {
 
 impl Command for Add {
     fn error_message(&self) -> String {
-        "expected 3 arguments, see the Usage section with tap --add --help".to_string()
+        "expected a parent entity followed by one or more link and value pairs, see the Usage section with tap --add --help".to_string()
     }
 
     fn help_message(&self) -> String {
         let mut s = String::new();
         s.push_str("Tap --add command will add a new link to the Parent Entity\n\n");
-        s.push_str("Command Structure: tap --add <Parent Entity | here> <Link Name> <Value>\n");
-        s.push_str("Example Usage: \n\n");
-        s.push_str("  - Add a link to search-engines Parent Entity: tap --add search-engines google https://google.com\n");
-        s.push_str("  - Add a link to Parent Entity sharing name of current directory: tap --add here google https://google.com\n");
+        s.push_str("Command Structure: tap --add <Parent Entity | here> <Link Name> <Value> [<Link Name> <Value>...]\n");
+        s.push_str("Several links can be given at once, if one of them can not be added none of them are\n");
+        s.push_str(
+            "Missing arguments are asked for when run in a terminal, unless --no-input is passed\n",
+        );
+        s.push_str("Pass - as the Value to read it from stdin, the value must be a single line\n");
+        s.push_str("A link and its value can also be passed as one Link=Value argument, split on the first =. Write \\= for an = in the link name\n");
+        s.push_str("Pass --expires with a date like 2024-07-01 or a number of days or weeks like 30d or 2w for links that are only needed for a while. Expired links are marked in tap --show, left out when opening all links of the Parent Entity, and removed with tap --clean --expired\n");
+        s.push_str("Given a single URL, the link is named after the title of the page, or after the URL when the page can not be fetched, is not HTML, or --offline is passed. Pass --name to choose the name instead\n");
+        s.push_str("A value that is already stored under another link, ignoring surrounding spaces and trailing slashes, is reported with a note. Pass --no-duplicates, or set no_duplicates = true in the config file, to make that an error, and --force to skip the check\n");
+        s.push_str("Links have a type saying how they are opened: url, file, or dir, told from the value when it is a web address or a path that exists. Pass --type to set it, and --type cmd for a shell command that is run instead of opened, after confirming it unless run_commands = true is set in the config file\n");
+        s.push_str(&display_examples(&self.examples()));
         s
     }
 
-    fn run(&self, args: Vec<String>) -> Result<CommandResult, String> {
-        match args.len() {
-            1 => {
-                if args[0] == "--help" {
-                    Ok(CommandResult::Value(self.help_message()))
-                } else {
-                    Err(self.error_message())
-                }
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let mut args = Args::new(args);
+        let expires = args
+            .take_option("--expires")
+            .map(|when| expiry::parse(&when))
+            .transpose()
+            .map_err(CommandError::usage)?;
+        let name = args.take_option("--name");
+        let offline = args.take_flag(&["--offline"]);
+        let force = args.take_flag(&["--force"]);
+        let no_duplicates = args.take_flag(&["--no-duplicates"]);
+        let link_type = args
+            .take_option("--type")
+            .map(|name| parse_link_type(&name))
+            .transpose()?;
+        if args.len() == 1 && args.is_flag(0, "--help") {
+            return Ok(CommandResult::Value(self.help_message()));
+        }
+        if name.is_some() && args.len() != 2 {
+            return Err(CommandError::usage(
+                "--name names a single link, pass it with a parent entity and one value"
+                    .to_string(),
+            ));
+        }
+        let pairs = match args.len() {
+            0 => None,
+            2 if name.is_some() || link_name::is_web_url(&args[1]) => {
+                let link_name = match name {
+                    Some(name) => name,
+                    None => name_link(&args[1], offline)?,
+                };
+                Some(vec![(link_name, args[1].clone())])
             }
-            3 => match (args[0].as_str(), args[1].as_str(), args[2].as_str()) {
-                ("here", link_name, value) => {
-                    let mut ds = DataStore::new(None).map_err(|e| e.to_string())?;
-                    let current_dir_name =
-                        get_current_directory_name().map_err(|e| e.to_string())?;
-                    ds.add_link(
-                        current_dir_name.to_string(),
-                        link_name.to_string(),
-                        value.to_string(),
-                    )
-                    .map_err(|e| e.to_string())?;
-                    Ok(CommandResult::Value(format!(
-                        "Successfully added {link_name} with value {value} to parent entity {current_dir_name}"
-                    )))
+            _ => link_value_pairs(&args[1..])?,
+        };
+        match pairs {
+            Some(mut pairs) if !pairs.is_empty() => {
+                let parent_entity = args.parent(0)?;
+                for (_, value) in pairs.iter_mut() {
+                    *value = resolve_value(value, &ctx.input)?;
+                }
+                if !force {
+                    check_duplicates(&ctx.store, &parent_entity, &pairs, no_duplicates)?;
                 }
-                (parent_entity, link_name, value) => {
-                    let mut ds = DataStore::new(None).map_err(|e| e.to_string())?;
-                    ds.add_link(
-                        parent_entity.to_string(),
-                        link_name.to_string(),
-                        value.to_string(),
-                    )
-                    .map_err(|e| e.to_string())?;
-                    Ok(CommandResult::Value(format!(
+                // Every pair is saved at once, or none at all if one of them fails
+                ctx.store
+                    .write(|ds| {
+                        ds.batch(|tx| {
+                            for (link_name, value) in pairs.iter() {
+                                tx.add_link(&parent_entity, link_name, value)?;
+                                if let Some(date) = &expires {
+                                    tx.update_meta(&parent_entity, link_name, |meta| {
+                                        meta.expires = Some(date.clone())
+                                    })?;
+                                }
+                                if let Some(link_type) = link_type
+                                    .or_else(|| LinkType::detect(value))
+                                    .filter(|t| *t != LinkType::Url)
+                                {
+                                    tx.update_meta(&parent_entity, link_name, |meta| {
+                                        meta.link_type = Some(link_type)
+                                    })?;
+                                }
+                            }
+                            Ok(())
+                        })
+                    })
+                    .map_err(|e| match pairs.len() {
+                        1 => CommandError::from(e),
+                        _ => CommandError::from(e).with_hint("No links were added"),
+                    })?;
+                let mut message = match pairs.as_slice() {
+                    [(link_name, value)] => format!(
                         "Successfully added {link_name} with value {value} to parent entity {parent_entity}"
-                    )))
+                    ),
+                    pairs => format!(
+                        "Successfully added {} links to parent entity {parent_entity}: {}",
+                        pairs.len(),
+                        pairs
+                            .iter()
+                            .map(|(link_name, _)| link_name.as_str())
+                            .collect::<Vec<&str>>()
+                            .join(", ")
+                    ),
+                };
+                if let Some(date) = &expires {
+                    message.push_str(&format!(", expiring on {date}"));
                 }
-            },
-            _ => Err(self.error_message()),
+                Ok(CommandResult::Value(style::success(&message)))
+            }
+            _ if args.len() <= 2 && ctx.prompt.is_interactive() => {
+                match self.ask_missing_args(ctx, args)? {
+                    Some(args) => {
+                        let mut args = args.into_vec();
+                        // Put back in front, so it is never taken for an argument after `--`
+                        if let Some(date) = expires {
+                            args.splice(0..0, ["--expires".to_string(), date]);
+                        }
+                        if force {
+                            args.insert(0, "--force".to_string());
+                        }
+                        if no_duplicates {
+                            args.insert(0, "--no-duplicates".to_string());
+                        }
+                        if let Some(link_type) = link_type {
+                            args.splice(0..0, ["--type".to_string(), link_type.to_string()]);
+                        }
+                        self.run(ctx, args)
+                    }
+                    None => Ok(CommandResult::Value("Nothing was added".to_string())),
+                }
+            }
+            _ => Err(self.usage_error()),
         }
     }
 }
 
+impl Add {
+    /// Asks for the parent entity, link name, and value that were not passed, then confirms
+    /// before saving. Returns `None` if the user does not confirm.
+    fn ask_missing_args(
+        &self,
+        ctx: &Context,
+        mut args: Args,
+    ) -> Result<Option<Args>, CommandError> {
+        if args.is_empty() {
+            let literal = args.is_literal(0);
+            let parent_entity = ctx.prompt.ask("Parent entity", |parent_entity| {
+                parent_arg(parent_entity, literal).map_err(|e| e.to_string())?;
+                validate_parent(parent_entity).map_err(|e| e.to_string())
+            })?;
+            args.push(parent_entity);
+        }
+        let parent_entity = args.parent(0)?;
+        if args.len() == 1 {
+            args.push(ctx.prompt.ask("Link name", |link_name| {
+                validate_link(link_name).map_err(|e| e.to_string())?;
+                if ctx.store.read_link(&parent_entity, link_name).is_ok() {
+                    return Err(format!(
+                        "Link {link_name} already exists in parent entity {parent_entity}"
+                    ));
+                }
+                Ok(())
+            })?);
+        }
+        args.push(ctx.prompt.ask("Value", |_| Ok(()))?);
+        let confirmed = ctx.prompt.confirm(&format!(
+            "Add link {} with value {} to parent entity {parent_entity}?",
+            args[1], args[2]
+        ))?;
+        Ok(confirmed.then_some(args))
+    }
+}
+
+/// Names the link of `url` after the title of its page, or after the URL when the page can not be
+/// fetched or `offline` is set, see `link_name::derive`
+fn name_link(url: &str, offline: bool) -> Result<String, CommandError> {
+    let page = match offline {
+        true => None,
+        false => http_get_html(url, TITLE_TIMEOUT_SECS).unwrap_or_else(|e| {
+            log::verbose(format_args!("Could not fetch {url}: {e}"));
+            None
+        }),
+    };
+    let name = link_name::derive(url, page.as_deref()).ok_or_else(|| {
+        CommandError::usage(format!(
+            "Could not name the link of {url}, pass a name with --name"
+        ))
+    })?;
+    log::note(format_args!("Named the link {name}"));
+    Ok(name)
+}
+
 impl DisplayCommandAsRow for Add {
     fn args(&self) -> Vec<String> {
         self.args.to_vec()
@@ -91,6 +248,46 @@ impl DisplayCommandAsRow for Add {
         self.description.clone()
     }
 
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            (
+                "tap --add search-engines google https://google.com",
+                "Add a link to search-engines Parent Entity",
+            ),
+            (
+                "tap --add here google https://google.com",
+                "Add a link to Parent Entity sharing name of current directory",
+            ),
+            (
+                "tap --add proj repo https://github.com/me/proj ci https://ci.me/proj",
+                "Add several links at once",
+            ),
+            (
+                "tap --add work jira=https://jira.internal/ABC ci=https://ci.internal",
+                "Add links written as Link=Value",
+            ),
+            (
+                "pbpaste | tap --add work spec -",
+                "Add a link with the value read from stdin",
+            ),
+            (
+                "tap --add reading https://some.blog/long-post-slug-here",
+                "Add a link named after the title of the page",
+            ),
+            (
+                "tap --add sprint retro https://docs.internal/retro --expires 14d",
+                "Add a link that expires in two weeks",
+            ),
+            (
+                "tap --add dev top htop --type cmd",
+                "Add a shell command, run after confirming when the link is opened",
+            ),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
     fn name(&self) -> String {
         self.name.clone()
     }
@@ -99,13 +296,18 @@ impl DisplayCommandAsRow for Add {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::commands::CommandErrorKind;
+    use crate::utils::prompt::Prompt;
+    use crate::utils::tap_data_store::DataStoreHandle;
+    use std::cell::RefCell;
 
     #[test]
     fn test_add_run_expected_help_arg() {
         let args: Vec<String> = vec!["--help".to_string()];
         let cmd = Add::default();
-        let expected: Result<CommandResult, String> = Ok(CommandResult::Value(cmd.help_message()));
-        let res = cmd.run(args);
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        let res = cmd.run(&mut Context::default(), args);
         assert_eq!(res, expected);
     }
 
@@ -113,8 +315,8 @@ mod tests {
     fn test_add_run_unexpected_args() {
         let args: Vec<String> = vec!["random".to_string()];
         let cmd = Add::default();
-        let expected: Result<CommandResult, String> = Err(cmd.error_message());
-        let res = cmd.run(args);
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        let res = cmd.run(&mut Context::default(), args);
         assert_eq!(res, expected);
     }
 
@@ -128,10 +330,11 @@ mod tests {
         let current_dir = std::env::current_dir().unwrap();
         let current_dir_name = current_dir.file_name().unwrap().to_str().unwrap();
         let cmd = Add::default();
-        let expected: Result<CommandResult, String> = Ok(CommandResult::Value(format!(
+        let mut ctx = Context::in_memory(vec![]);
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Value(format!(
             "Successfully added google with value https://google.com to parent entity {current_dir_name}"
         )));
-        let res = cmd.run(args);
+        let res = cmd.run(&mut ctx, args);
         assert_eq!(res, expected);
     }
 
@@ -143,9 +346,408 @@ mod tests {
             "https://google.com".to_string(),
         ];
         let cmd = Add::default();
-        let expected: Result<CommandResult, String> =
+        let mut ctx = Context::in_memory(vec![]);
+        let expected: Result<CommandResult, CommandError> =
             Ok(CommandResult::Value("Successfully added google with value https://google.com to parent entity search-engines".to_string()));
-        let res = cmd.run(args);
+        let res = cmd.run(&mut ctx, args);
         assert_eq!(res, expected);
     }
+
+    #[test]
+    fn test_add_run_expires() {
+        let cmd = Add::default();
+        let mut ctx = Context::in_memory(vec![]);
+        let args = |when: &str| {
+            [
+                "sprint",
+                "retro",
+                "https://docs.internal/retro",
+                "--expires",
+                when,
+            ]
+            .map(String::from)
+            .to_vec()
+        };
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Value(
+            "Successfully added retro with value https://docs.internal/retro to parent entity sprint, expiring on 2024-07-01".to_string(),
+        ));
+        assert_eq!(cmd.run(&mut ctx, args("2024-07-01")), expected);
+        let meta = ctx.store.read_meta("sprint").unwrap().remove("retro");
+        assert_eq!(meta.unwrap().expires, Some("2024-07-01".to_string()));
+        let err = cmd.run(&mut ctx, args("next week")).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::Usage);
+        assert!(err.message.starts_with("'next week' is not a date"));
+    }
+
+    #[test]
+    fn test_add_run_link_type() {
+        let cmd = Add::default();
+        let mut ctx = Context::in_memory(vec![]);
+        let run = |ctx: &mut Context, args: &[&str]| {
+            cmd.run(ctx, args.iter().map(|a| a.to_string()).collect())
+        };
+        assert!(run(&mut ctx, &["vpn", "up", "wg-quick up wg0", "--type", "cmd"]).is_ok());
+        assert!(run(&mut ctx, &["vpn", "docs", "https://vpn.internal"]).is_ok());
+        assert!(run(&mut ctx, &["vpn", "notes", "not/a/path/that/exists"]).is_ok());
+        let meta = ctx.store.read_meta("vpn").unwrap();
+        assert_eq!(meta["up"].link_type, Some(LinkType::Cmd));
+        // URLs are the default type, which is not kept
+        assert!(!meta.contains_key("docs"));
+        assert!(!meta.contains_key("notes"));
+        let err = run(&mut ctx, &["vpn", "x", "y", "--type", "script"]).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::Usage);
+        assert_eq!(
+            err.message,
+            "unknown link type script, the types are: url, file, dir, cmd"
+        );
+    }
+
+    #[test]
+    fn test_add_run_names_link_after_url() {
+        let cmd = Add::default();
+        let mut ctx = Context::in_memory(vec![]);
+        let args = [
+            "reading",
+            "https://some.blog/long-post-slug-here",
+            "--offline",
+        ]
+        .map(String::from)
+        .to_vec();
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Value(
+            "Successfully added long-post-slug-here with value https://some.blog/long-post-slug-here to parent entity reading".to_string(),
+        ));
+        assert_eq!(cmd.run(&mut ctx, args), expected);
+        // Pages are never fetched under test, so naming falls back to the URL as well
+        let args = ["reading", "https://some.blog/posts/intro.html?page=2"]
+            .map(String::from)
+            .to_vec();
+        assert!(cmd.run(&mut ctx, args).is_ok());
+        assert_eq!(
+            ctx.store.links("reading").unwrap(),
+            vec!["intro".to_string(), "long-post-slug-here".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_add_run_name_option() {
+        let cmd = Add::default();
+        let mut ctx = Context::in_memory(vec![]);
+        let args = ["notes", "--name", "todo", "~/notes/todo.md"]
+            .map(String::from)
+            .to_vec();
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Value(
+            "Successfully added todo with value ~/notes/todo.md to parent entity notes".to_string(),
+        ));
+        assert_eq!(cmd.run(&mut ctx, args), expected);
+        let args = ["notes", "--name", "todo", "a", "https://a.com"]
+            .map(String::from)
+            .to_vec();
+        let err = cmd.run(&mut ctx, args).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::Usage);
+    }
+
+    #[test]
+    fn test_add_run_single_value_not_a_url() {
+        let cmd = Add::default();
+        let mut ctx = Context::in_memory(vec![]);
+        let args = ["notes", "~/notes/todo.md"].map(String::from).to_vec();
+        assert_eq!(cmd.run(&mut ctx, args), Err(cmd.usage_error()));
+    }
+
+    #[test]
+    fn test_add_run_duplicate_value() {
+        let cmd = Add::default();
+        let mut ctx = Context::in_memory(vec![(
+            "work".to_string(),
+            vec![("ci".to_string(), "https://ci.internal/".to_string())],
+        )]);
+        let args = |extra: &[&str]| {
+            let mut args = vec!["tools".to_string()];
+            args.extend(extra.iter().map(|a| a.to_string()));
+            args
+        };
+        // Only noted by default, so the link is still added
+        assert!(
+            cmd.run(&mut ctx, args(&["build", "https://ci.internal/"]))
+                .is_ok()
+        );
+        let expected: Result<CommandResult, CommandError> = Err(CommandError {
+            kind: CommandErrorKind::Generic,
+            message: "https://ci.internal is already stored as tools/build, work/ci".to_string(),
+        }
+        .with_hint("Pass --force to store it anyway"));
+        assert_eq!(
+            cmd.run(
+                &mut ctx,
+                args(&["--no-duplicates", "ci", " https://ci.internal//"])
+            ),
+            expected
+        );
+        assert!(
+            cmd.run(
+                &mut ctx,
+                args(&["--no-duplicates", "--force", "ci", "https://ci.internal"])
+            )
+            .is_ok()
+        );
+        assert_eq!(
+            ctx.store.links("tools").unwrap(),
+            vec!["build".to_string(), "ci".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_add_run_read_only() {
+        let args: Vec<String> = vec![
+            "search-engines".to_string(),
+            "google".to_string(),
+            "https://google.com".to_string(),
+        ];
+        let cmd = Add::default();
+        let mut ctx = Context {
+            store: DataStoreHandle::in_memory(vec![]).read_only(),
+            ..Context::default()
+        };
+        let res = cmd.run(&mut ctx, args);
+        let err = res.unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::ReadOnly);
+        assert!(err.message.contains("read-only mode"));
+    }
+
+    #[test]
+    fn test_add_run_value_from_stdin() {
+        let args: Vec<String> = vec![
+            "search-engines".to_string(),
+            "google".to_string(),
+            "-".to_string(),
+        ];
+        let cmd = Add::default();
+        let mut ctx = Context {
+            store: DataStoreHandle::in_memory(vec![]),
+            input: RefCell::new(Box::new("https://google.com\n".as_bytes())),
+            ..Context::default()
+        };
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Value(
+            "Successfully added google with value https://google.com to parent entity search-engines"
+                .to_string(),
+        ));
+        let res = cmd.run(&mut ctx, args);
+        assert_eq!(res, expected);
+        assert_eq!(
+            ctx.store.read_link("search-engines", "google").unwrap(),
+            ("google".to_string(), "https://google.com".to_string())
+        );
+    }
+
+    #[test]
+    fn test_add_run_value_from_stdin_multiple_lines() {
+        let args: Vec<String> = vec![
+            "search-engines".to_string(),
+            "google".to_string(),
+            "-".to_string(),
+        ];
+        let cmd = Add::default();
+        let mut ctx = Context {
+            store: DataStoreHandle::in_memory(vec![]),
+            input: RefCell::new(Box::new(
+                "https://google.com\nhttps://bing.com\n".as_bytes(),
+            )),
+            ..Context::default()
+        };
+        let res = cmd.run(&mut ctx, args);
+        assert!(res.unwrap_err().message.contains("single line"));
+        assert!(ctx.store.links("search-engines").is_err());
+    }
+
+    #[test]
+    fn test_add_run_several_pairs() {
+        let args: Vec<String> = vec![
+            "search-engines".to_string(),
+            "google".to_string(),
+            "https://google.com".to_string(),
+            "bing".to_string(),
+            "https://bing.com".to_string(),
+        ];
+        let cmd = Add::default();
+        let mut ctx = Context::in_memory(vec![]);
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Value(
+            "Successfully added 2 links to parent entity search-engines: google, bing".to_string(),
+        ));
+        let res = cmd.run(&mut ctx, args);
+        assert_eq!(res, expected);
+        assert_eq!(
+            ctx.store.links("search-engines").unwrap(),
+            vec!["bing".to_string(), "google".to_string()]
+        );
+        assert_eq!(ctx.store.saves(), 1);
+    }
+
+    #[test]
+    fn test_add_run_pair_missing_value() {
+        let args: Vec<String> = vec![
+            "search-engines".to_string(),
+            "google".to_string(),
+            "https://google.com".to_string(),
+            "bing".to_string(),
+        ];
+        let cmd = Add::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        let res = cmd.run(&mut Context::default(), args);
+        assert_eq!(res, expected);
+    }
+
+    #[test]
+    fn test_add_run_link_equals_value() {
+        let args: Vec<String> = vec![
+            "work".to_string(),
+            "jira=https://jira.internal/ABC?a=b".to_string(),
+            "ci".to_string(),
+            "https://ci.me".to_string(),
+            "a\\=b=https://ab.com".to_string(),
+        ];
+        let cmd = Add::default();
+        let mut ctx = Context::in_memory(vec![]);
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Value(
+            "Successfully added 3 links to parent entity work: jira, ci, a=b".to_string(),
+        ));
+        assert_eq!(cmd.run(&mut ctx, args), expected);
+        assert_eq!(
+            ctx.store.read_link("work", "jira").unwrap().1,
+            "https://jira.internal/ABC?a=b"
+        );
+    }
+
+    #[test]
+    fn test_add_run_link_equals_empty_value() {
+        let args: Vec<String> = vec!["work".to_string(), "jira=".to_string()];
+        let cmd = Add::default();
+        let mut ctx = Context::in_memory(vec![]);
+        let err = cmd.run(&mut ctx, args).unwrap_err();
+        assert!(err.message.starts_with("Link jira has no value after '='"));
+        assert_eq!(ctx.store.saves(), 0);
+    }
+
+    #[test]
+    fn test_add_run_several_pairs_invalid_pair_adds_nothing() {
+        let args: Vec<String> = vec![
+            "search-engines".to_string(),
+            "google".to_string(),
+            "https://google.com".to_string(),
+            "bing|search".to_string(),
+            "https://bing.com".to_string(),
+        ];
+        let cmd = Add::default();
+        let mut ctx = Context::in_memory(vec![]);
+        let err = cmd.run(&mut ctx, args).unwrap_err();
+        assert!(err.message.ends_with("No links were added"));
+        assert!(ctx.store.links("search-engines").is_err());
+        assert_eq!(ctx.store.saves(), 0);
+    }
+
+    #[test]
+    fn test_add_run_prompts_for_missing_args() {
+        let args: Vec<String> = vec!["search-engines".to_string()];
+        let cmd = Add::default();
+        let mut ctx = Context {
+            store: DataStoreHandle::in_memory(vec![]),
+            prompt: Prompt::scripted("google\nhttps://google.com\ny\n"),
+            ..Context::default()
+        };
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Value(
+            "Successfully added google with value https://google.com to parent entity search-engines"
+                .to_string(),
+        ));
+        assert_eq!(cmd.run(&mut ctx, args), expected);
+        assert_eq!(
+            ctx.store.read_link("search-engines", "google").unwrap(),
+            ("google".to_string(), "https://google.com".to_string())
+        );
+    }
+
+    #[test]
+    fn test_add_run_prompts_for_parent_entity() {
+        let cmd = Add::default();
+        let mut ctx = Context {
+            store: DataStoreHandle::in_memory(vec![]),
+            prompt: Prompt::scripted("--help\nsearch-engines\ngoogle\nhttps://google.com\nyes\n"),
+            ..Context::default()
+        };
+        assert!(cmd.run(&mut ctx, vec![]).is_ok());
+        assert_eq!(
+            ctx.store.links("search-engines").unwrap(),
+            vec!["google".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_add_run_prompt_not_confirmed() {
+        let args: Vec<String> = vec!["search-engines".to_string(), "google".to_string()];
+        let cmd = Add::default();
+        let mut ctx = Context {
+            store: DataStoreHandle::in_memory(vec![]),
+            prompt: Prompt::scripted("https://google.com\nn\n"),
+            ..Context::default()
+        };
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value("Nothing was added".to_string()));
+        assert_eq!(cmd.run(&mut ctx, args), expected);
+        assert_eq!(ctx.store.saves(), 0);
+    }
+
+    #[test]
+    fn test_add_run_prompts_again_for_existing_link() {
+        let args: Vec<String> = vec!["search-engines".to_string()];
+        let cmd = Add::default();
+        let mut ctx = Context {
+            store: DataStoreHandle::in_memory(vec![(
+                "search-engines".to_string(),
+                vec![("google".to_string(), "https://google.com".to_string())],
+            )]),
+            prompt: Prompt::scripted("google\nbing\nhttps://bing.com\ny\n"),
+            ..Context::default()
+        };
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Value(
+            "Successfully added bing with value https://bing.com to parent entity search-engines"
+                .to_string(),
+        ));
+        assert_eq!(cmd.run(&mut ctx, args), expected);
+    }
+
+    #[test]
+    fn test_add_run_end_of_options_literal_here() {
+        let args: Vec<String> = vec![
+            "--".to_string(),
+            "here".to_string(),
+            "google".to_string(),
+            "https://google.com".to_string(),
+        ];
+        let cmd = Add::default();
+        let mut ctx = Context::in_memory(vec![]);
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Value(
+            "Successfully added google with value https://google.com to parent entity here"
+                .to_string(),
+        ));
+        assert_eq!(cmd.run(&mut ctx, args), expected);
+        assert_eq!(ctx.store.parents().unwrap(), vec!["here".to_string()]);
+    }
+
+    #[test]
+    fn test_add_run_flag_like_parent_needs_end_of_options() {
+        let args: Vec<String> = vec![
+            "--work".to_string(),
+            "google".to_string(),
+            "https://google.com".to_string(),
+        ];
+        let cmd = Add::default();
+        let mut ctx = Context::in_memory(vec![]);
+        assert!(cmd.run(&mut ctx, args).is_err());
+        let args: Vec<String> = vec![
+            "--".to_string(),
+            "--work".to_string(),
+            "google".to_string(),
+            "https://google.com".to_string(),
+        ];
+        assert!(cmd.run(&mut ctx, args).is_ok());
+        assert_eq!(ctx.store.parents().unwrap(), vec!["--work".to_string()]);
+    }
 }
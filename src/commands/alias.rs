@@ -0,0 +1,250 @@
+use crate::{
+    commands::{Command, CommandError, CommandResult, Table, display_examples},
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::command::Args,
+    utils::context::Context,
+    utils::json::Json,
+    utils::style,
+};
+
+pub(crate) struct Alias {
+    name: String,
+    description: String,
+    args: [String; 2],
+}
+
+impl Default for Alias {
+    fn default() -> Self {
+        Self {
+            name: "--alias".to_string(),
+            description: "Give a Parent another name".to_string(),
+            args: ["[Alias]".to_string(), "[Parent|here]".to_string()],
+        }
+    }
+}
+
+impl Command for Alias {
+    fn error_message(&self) -> String {
+        "expected 0 or 2 arguments, see the Usage section with tap --alias --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --alias command gives a Parent Entity a shorter name to type, like k8s for kubernetes-production-cluster. Every command that reads or opens links, and shell completion, takes the alias in place of the Parent Entity, and tap --show <Alias> names the Parent Entity it stands for.\n\n");
+        s.push_str("An alias is a single word without path separators like / and it can not be the name of a Parent Entity or a reserved name like here. Setting an alias again points it to the new Parent Entity, tap --unalias removes it, and deleting a Parent Entity removes its aliases. Aliases are kept in .tap_aliases next to the data file.\n\n");
+        s.push_str("Command Structure(s):\n");
+        s.push_str("  - tap --alias                                   (List all aliases)\n");
+        s.push_str(
+            "  - tap --alias <Alias> <Parent Entity | here>    (Give a Parent Entity an alias)\n",
+        );
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let args = Args::new(args);
+        match args.len() {
+            0 => self.list(ctx),
+            1 if args.is_flag(0, "--help") => Ok(CommandResult::Value(self.help_message())),
+            2 => self.set(ctx, &args[0], &args.parent(1)?),
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+impl Alias {
+    fn list(&self, ctx: &Context) -> Result<CommandResult, CommandError> {
+        let aliases = ctx.store.aliases()?;
+        if ctx.format.is_structured() {
+            return Ok(CommandResult::Json(Json::Array(
+                aliases
+                    .iter()
+                    .map(|(alias, parent)| {
+                        Json::object([
+                            ("alias", Json::from(alias.as_str())),
+                            ("parent", Json::from(parent.as_str())),
+                        ])
+                    })
+                    .collect(),
+            )));
+        }
+        if aliases.is_empty() {
+            return Ok(CommandResult::Value(
+                "No aliases yet, add one with tap --alias <Alias> <Parent Entity>".to_string(),
+            ));
+        }
+        Ok(CommandResult::Table(Table {
+            title: "Aliases:".to_string(),
+            rows: aliases
+                .into_iter()
+                .map(|(alias, parent)| vec![alias, style::parent(&parent)])
+                .collect(),
+        }))
+    }
+
+    fn set(
+        &self,
+        ctx: &Context,
+        alias: &str,
+        parent_entity: &str,
+    ) -> Result<CommandResult, CommandError> {
+        let parent_entity = ctx.store.resolve_alias(parent_entity)?;
+        let old = ctx
+            .store
+            .write(|ds| ds.set_alias(alias, &parent_entity))
+            .map_err(|e| ctx.store.with_suggestions(e, &parent_entity, None))?;
+        let alias = alias.trim();
+        match old {
+            Some(old) if old == parent_entity.trim() => Ok(CommandResult::Value(format!(
+                "Alias '{alias}' already names parent '{old}'"
+            ))),
+            Some(old) => Ok(CommandResult::Value(style::success(&format!(
+                "Alias '{alias}' now names parent '{parent_entity}' instead of '{old}'"
+            )))),
+            None => Ok(CommandResult::Value(style::success(&format!(
+                "Alias '{alias}' now names parent '{parent_entity}'"
+            )))),
+        }
+    }
+}
+
+impl DisplayCommandAsRow for Alias {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            ("tap --alias", "List all aliases"),
+            (
+                "tap --alias k8s kubernetes-production-cluster",
+                "Type tap k8s grafana to open a link of kubernetes-production-cluster",
+            ),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::CommandErrorKind;
+    use crate::commands::{parent_entity::ParentEntity, show::Show};
+
+    fn ctx() -> Context {
+        Context::in_memory(vec![
+            (
+                "kubernetes-production-cluster".to_string(),
+                vec![("grafana".to_string(), "https://grafana.k8s".to_string())],
+            ),
+            (
+                "work".to_string(),
+                vec![("ci".to_string(), "https://ci.internal".to_string())],
+            ),
+        ])
+    }
+
+    fn alias(ctx: &mut Context, args: &[&str]) -> Result<CommandResult, CommandError> {
+        Alias::default().run(ctx, args.iter().map(|a| a.to_string()).collect())
+    }
+
+    #[test]
+    fn test_alias_run_expected_help_arg() {
+        let cmd = Alias::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_alias_run_unexpected_args() {
+        let cmd = Alias::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["k8s".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_alias_set_and_resolve() {
+        let mut ctx = ctx();
+        assert_eq!(
+            alias(&mut ctx, &["k8s", "kubernetes-production-cluster"]),
+            Ok(CommandResult::Value(style::success(
+                "Alias 'k8s' now names parent 'kubernetes-production-cluster'"
+            )))
+        );
+        assert_eq!(
+            ParentEntity::default().run(&mut ctx, vec!["k8s".to_string(), "graf".to_string()]),
+            Ok(CommandResult::Silent)
+        );
+        assert_eq!(
+            Show::default()
+                .run(&mut ctx, vec!["k8s".to_string()])
+                .map(|res| res.to_string()),
+            Ok(
+                "Links of parent entity kubernetes-production-cluster (alias k8s):\n  grafana"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            alias(&mut ctx, &["k8s", "k8s"]),
+            Ok(CommandResult::Value(
+                "Alias 'k8s' already names parent 'kubernetes-production-cluster'".to_string()
+            ))
+        );
+        assert_eq!(
+            alias(&mut ctx, &["k8s", "work"]),
+            Ok(CommandResult::Value(style::success(
+                "Alias 'k8s' now names parent 'work' instead of 'kubernetes-production-cluster'"
+            )))
+        );
+        assert_eq!(
+            alias(&mut ctx, &[]).map(|res| res.to_string()),
+            Ok("Aliases:\n  k8s  work".to_string())
+        );
+    }
+
+    #[test]
+    fn test_alias_collisions() {
+        let mut ctx = ctx();
+        let e = alias(&mut ctx, &["work", "kubernetes-production-cluster"]).unwrap_err();
+        assert!(
+            e.message
+                .starts_with("Alias work is already the name of a parent entity"),
+            "{}",
+            e.message
+        );
+        for reserved in ["here", "-k", "a->b", " ", "a/b", "a\\b", "k 8s", "k8s\t"] {
+            let e = alias(&mut ctx, &[reserved, "work"]).unwrap_err();
+            assert!(e.message.contains("is reserved"), "{}", e.message);
+        }
+        let e = alias(&mut ctx, &["k8s", "kubernetes"]).unwrap_err();
+        assert_eq!(e.kind, CommandErrorKind::ParentNotFound);
+    }
+
+    #[test]
+    fn test_alias_removed_with_its_parent() {
+        let mut ctx = ctx();
+        alias(&mut ctx, &["k8s", "kubernetes-production-cluster"]).unwrap();
+        ctx.store
+            .write(|ds| ds.batch(|tx| tx.delete("k8s", None)))
+            .unwrap();
+        assert!(ctx.store.aliases().unwrap().is_empty());
+        assert_eq!(ctx.store.parents().unwrap(), vec!["work".to_string()]);
+    }
+}
@@ -0,0 +1,195 @@
+use crate::{
+    commands::{Command, CommandError, CommandResult, display_examples},
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::command::Args,
+    utils::context::Context,
+    utils::style,
+};
+
+pub(crate) struct Archive {
+    name: String,
+    description: String,
+    args: [String; 1],
+}
+
+impl Default for Archive {
+    fn default() -> Self {
+        Self {
+            name: "--archive".to_string(),
+            description: "Move a Parent out of the way without deleting it".to_string(),
+            args: ["<Parent|here>".to_string()],
+        }
+    }
+}
+
+impl Command for Archive {
+    fn error_message(&self) -> String {
+        "expected 1 argument, see the Usage section with tap --archive --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --archive command retires a Parent Entity without losing its links. The Parent Entity is moved with its links, pins, and notes to .tap_archive next to the data file, in the same format, so it no longer shows up in tap --show, shell completion, or when opening links.\n\n");
+        s.push_str("tap --show --archived lists the archived Parent Entities and tap --unarchive brings one back. Aliases of an archived Parent Entity are kept, and work again once it is unarchived.\n\n");
+        s.push_str("Command Structure: tap --archive <Parent Entity | here>\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let args = Args::new(args);
+        match args.len() {
+            1 if args.is_flag(0, "--help") => Ok(CommandResult::Value(self.help_message())),
+            1 => {
+                let parent_entity = ctx.store.resolve_alias(&args.parent(0)?)?;
+                let links = ctx
+                    .store
+                    .write(|ds| ds.archive(&parent_entity))
+                    .map_err(|e| ctx.store.with_suggestions(e, &parent_entity, None))?;
+                Ok(CommandResult::Value(style::success(&format!(
+                    "Archived parent '{parent_entity}' with {links} link(s), bring it back with tap --unarchive {parent_entity}"
+                ))))
+            }
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+impl DisplayCommandAsRow for Archive {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [(
+            "tap --archive old-project",
+            "Move old-project and its links to the archive",
+        )]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::{CommandErrorKind, show::Show, unarchive::Unarchive};
+
+    fn ctx() -> Context {
+        Context::in_memory(vec![
+            (
+                "old-project".to_string(),
+                vec![
+                    ("ci".to_string(), "https://ci.old".to_string()),
+                    ("repo".to_string(), "https://repo.old".to_string()),
+                ],
+            ),
+            (
+                "work".to_string(),
+                vec![("ci".to_string(), "https://ci.internal".to_string())],
+            ),
+        ])
+    }
+
+    fn run(
+        cmd: &dyn Command,
+        ctx: &mut Context,
+        args: &[&str],
+    ) -> Result<CommandResult, CommandError> {
+        cmd.run(ctx, args.iter().map(|a| a.to_string()).collect())
+    }
+
+    #[test]
+    fn test_archive_run_expected_help_arg() {
+        let cmd = Archive::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_archive_run_unexpected_args() {
+        let cmd = Archive::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(cmd.run(&mut Context::default(), vec![]), expected);
+    }
+
+    #[test]
+    fn test_archive_and_unarchive() {
+        let mut ctx = ctx();
+        ctx.store
+            .write(|ds| ds.batch(|tx| tx.update_meta("old-project", "repo", |m| m.pinned = true)))
+            .unwrap();
+        assert_eq!(
+            run(&Archive::default(), &mut ctx, &["old-project"]),
+            Ok(CommandResult::Value(style::success(
+                "Archived parent 'old-project' with 2 link(s), bring it back with tap --unarchive old-project"
+            )))
+        );
+        assert_eq!(ctx.store.parents().unwrap(), vec!["work".to_string()]);
+        assert_eq!(
+            run(&Show::default(), &mut ctx, &["--archived"]).map(|res| res.to_string()),
+            Ok("Archived Parent Entities:\n  old-project".to_string())
+        );
+        let e = run(&Archive::default(), &mut ctx, &["old-project"]).unwrap_err();
+        assert_eq!(e.kind, CommandErrorKind::ParentNotFound);
+        assert_eq!(
+            run(&Unarchive::default(), &mut ctx, &["old-project"]),
+            Ok(CommandResult::Value(style::success(
+                "Brought back parent 'old-project' with 2 link(s) from the archive"
+            )))
+        );
+        assert!(ctx.store.read_meta("old-project").unwrap()["repo"].pinned);
+        assert!(ctx.store.archived().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_archive_keeps_aliases() {
+        let mut ctx = ctx();
+        ctx.store.write(|ds| ds.set_alias("w", "work")).unwrap();
+        run(&Archive::default(), &mut ctx, &["w"]).unwrap();
+        // Other changes do not remove the alias of the archived parent entity either
+        ctx.store
+            .write(|ds| ds.add_link("news".into(), "bbc".into(), "https://bbc.com".into()))
+            .unwrap();
+        assert_eq!(
+            ctx.store.aliases().unwrap().get("w"),
+            Some(&"work".to_string())
+        );
+        run(&Unarchive::default(), &mut ctx, &["w"]).unwrap();
+        assert_eq!(ctx.store.links("w").unwrap(), vec!["ci".to_string()]);
+    }
+
+    #[test]
+    fn test_unarchive_name_collision() {
+        let mut ctx = ctx();
+        run(&Archive::default(), &mut ctx, &["old-project"]).unwrap();
+        ctx.store
+            .write(|ds| ds.add_link("old-project".into(), "new".into(), "https://new".into()))
+            .unwrap();
+        let e = run(&Unarchive::default(), &mut ctx, &["old-project"]).unwrap_err();
+        assert!(
+            e.message.starts_with("Parent 'old-project' already exists"),
+            "{}",
+            e.message
+        );
+        // Nothing was merged, and the archived links are still there
+        assert_eq!(
+            ctx.store.links("old-project").unwrap(),
+            vec!["new".to_string()]
+        );
+        assert_eq!(ctx.store.archived().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,260 @@
+use crate::{
+    commands::{Command, CommandError, CommandResult, display_examples},
+    utils::backup::{Archive, EXTENSION, utc_timestamp},
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::command::Args,
+    utils::config::default_config_path,
+    utils::context::Context,
+    utils::tap_data_store::{StoreOptions, check_data_file, sidecar_paths},
+};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub(crate) struct Backup {
+    name: String,
+    description: String,
+    args: [String; 2],
+}
+
+impl Default for Backup {
+    fn default() -> Self {
+        Self {
+            name: "--backup".to_string(),
+            description: "Back up the data, index, and config files".to_string(),
+            args: ["[Directory]".to_string(), "[--force]".to_string()],
+        }
+    }
+}
+
+impl Command for Backup {
+    fn error_message(&self) -> String {
+        "expected 0-1 arguments, see the Usage section with tap --backup --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --backup command copies the data file, index file, and config file into a single timestamped .tapbackup archive and prints its path, along with the aliases, groups, sessions, recently opened links, history, archived links, and trashed links kept next to the data file. The archive lists the version of tap that wrote it and a hash of every file, so one file is all there is to keep in cloud storage. It is written to the given directory, or to a backups directory next to the data file. Restore it with tap --restore.\n\n");
+        s.push_str("A data file that can not be parsed is not backed up, so corruption is noticed instead of archived. Pass --force to back it up anyway.\n\n");
+        s.push_str("Command Structure: tap --backup [Directory] [--force]\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let mut args = Args::new(args);
+        let force = args.take_flag(&["--force"]);
+        let dir = match args.len() {
+            0 => None,
+            1 if args.is_flag(0, "--help") => {
+                return Ok(CommandResult::Value(self.help_message()));
+            }
+            1 => Some(PathBuf::from(&args[0])),
+            _ => return Err(self.usage_error()),
+        };
+        let path = write_backup(ctx.store.options(), dir, force)?;
+        Ok(CommandResult::Value(path.display().to_string()))
+    }
+}
+
+/// Backs up the data store picked by `options` to `dir`, or to the backups directory next to the
+/// data file, returning the path of the archive. See `archive` for `force`.
+pub(in crate::commands) fn write_backup(
+    options: &StoreOptions,
+    dir: Option<PathBuf>,
+    force: bool,
+) -> Result<PathBuf, CommandError> {
+    let [(data, _), (index, _)] = options.resolve_paths()?;
+    let archive = archive(&data, &index, force)?;
+    let dir = dir.unwrap_or_else(|| default_backup_dir(&data));
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Could not create backup directory {}: {e}", dir.display()))?;
+    let path = backup_path(&dir, &archive.created);
+    std::fs::write(&path, archive.to_string())
+        .map_err(|e| format!("Could not write backup {}: {e}", path.display()))?;
+    Ok(path)
+}
+
+/// The `backups` directory next to the data file
+pub(in crate::commands) fn default_backup_dir(data: &Path) -> PathBuf {
+    data.parent().unwrap_or(Path::new(".")).join("backups")
+}
+
+/// An archive of the data file, and of the index, config, and sidecar files when they exist, see
+/// `sidecar_paths`. The data file has to parse unless `force` is set.
+pub(in crate::commands) fn archive(
+    data: &Path,
+    index: &Path,
+    force: bool,
+) -> Result<Archive, CommandError> {
+    let read = |path: &Path| {
+        std::fs::read_to_string(path).map_err(|e| format!("Could not read {}: {e}", path.display()))
+    };
+    if !data.exists() {
+        return Err(format!("There is no data file to back up at {}", data.display()).into());
+    }
+    let contents = read(data)?;
+    if !force {
+        check_data_file(&contents).map_err(|e| {
+            CommandError::from(e)
+                .with_hint("The data file can not be parsed, pass --force to back it up anyway")
+        })?;
+    }
+    let mut files = vec![("data".to_string(), contents)];
+    if index.exists() {
+        files.push(("index".to_string(), read(index)?));
+    }
+    if let Some(config) = default_config_path().filter(|config| config.exists()) {
+        files.push(("config".to_string(), read(&config)?));
+    }
+    // The undo snapshot only takes back the change made before it, never a restored backup
+    for (name, path) in sidecar_paths(data)
+        .into_iter()
+        .filter(|(name, _)| *name != "undo")
+    {
+        if path.exists() {
+            files.push((name.to_string(), read(&path)?));
+        }
+    }
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    Ok(Archive {
+        created: utc_timestamp(now),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        files,
+    })
+}
+
+/// `tap-backup-2024-06-01T12-30-05.tapbackup` in `dir`, numbered if a backup was already taken
+/// that second
+pub(in crate::commands) fn backup_path(dir: &Path, created: &str) -> PathBuf {
+    let stem = format!(
+        "tap-backup-{}",
+        created.trim_end_matches('Z').replace(':', "-")
+    );
+    let mut path = dir.join(format!("{stem}.{EXTENSION}"));
+    let mut n = 1;
+    while path.exists() {
+        n += 1;
+        path = dir.join(format!("{stem}-{n}.{EXTENSION}"));
+    }
+    path
+}
+
+impl DisplayCommandAsRow for Backup {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            ("tap --backup", "Back up to the backups directory"),
+            ("tap --backup ~/Dropbox", "Back up to another directory"),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::CommandErrorKind;
+    use crate::utils::sessions::sessions_path_for;
+    use crate::utils::tap_data_store::{TestDataFile, index_path_for};
+
+    #[test]
+    fn test_backup_run_expected_help_arg() {
+        let cmd = Backup::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_backup_run_unexpected_args() {
+        let cmd = Backup::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(
+            cmd.run(
+                &mut Context::default(),
+                vec!["a".to_string(), "b".to_string()]
+            ),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_backup_run_writes_archive() {
+        let data = TestDataFile::new("work->\n  ci|https://ci.internal\n");
+        std::fs::write(index_path_for(&data), "work|0\n").unwrap();
+        let Ok(CommandResult::Value(path)) =
+            Backup::default().run(&mut Context::with_file(data.clone()), vec![])
+        else {
+            panic!("expected the path of the backup");
+        };
+        let path = PathBuf::from(path);
+        assert_eq!(path.parent(), Some(default_backup_dir(&data).as_path()));
+        assert!(path.to_string_lossy().ends_with(".tapbackup"));
+        let archive = std::fs::read_to_string(&path).unwrap();
+        assert!(archive.starts_with("tap-backup 2\ncreated "));
+        assert!(archive.contains("\nwork->\n  ci|https://ci.internal\n\nfile index 7 "));
+        // A second backup in the same second gets its own file
+        let Ok(CommandResult::Value(second)) =
+            Backup::default().run(&mut Context::with_file(data.clone()), vec![])
+        else {
+            panic!("expected the path of the backup");
+        };
+        assert_ne!(PathBuf::from(second), path);
+    }
+
+    #[test]
+    fn test_backup_run_sidecars() {
+        let data = TestDataFile::new("work->\n  ci|https://ci.internal\n");
+        std::fs::write(sessions_path_for(&data), "morning\twork\tci\n").unwrap();
+        let Ok(CommandResult::Value(path)) =
+            Backup::default().run(&mut Context::with_file(data.clone()), vec![])
+        else {
+            panic!("expected the path of the backup");
+        };
+        let archive = Archive::parse(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(
+            archive.file("data"),
+            Some("work->\n  ci|https://ci.internal\n")
+        );
+        assert_eq!(archive.file("sessions"), Some("morning\twork\tci\n"));
+        assert_eq!(archive.file("aliases"), None);
+    }
+
+    #[test]
+    fn test_backup_run_refuses_corrupt_data() {
+        let data = TestDataFile::new("not a parent entity\n");
+        let dir = data.with_file_name("elsewhere");
+        let args = vec![dir.display().to_string()];
+        let e = Backup::default()
+            .run(&mut Context::with_file(data.clone()), args.clone())
+            .unwrap_err();
+        assert_eq!(e.kind, CommandErrorKind::InvalidData);
+        assert!(!dir.exists());
+        let mut args = args;
+        args.push("--force".to_string());
+        assert!(
+            Backup::default()
+                .run(&mut Context::with_file(data.clone()), args)
+                .is_ok()
+        );
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+    }
+}
@@ -0,0 +1,416 @@
+use crate::{
+    commands::{
+        Command, CommandError, CommandResult, Table, clean::local_path, display_examples,
+        expand_env,
+    },
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::command::Args,
+    utils::context::Context,
+    utils::json::Json,
+    utils::link_type::LinkType,
+    utils::log,
+    utils::style,
+};
+use std::path::PathBuf;
+
+pub(crate) struct Broken {
+    name: String,
+    description: String,
+    args: [String; 3],
+}
+
+impl Default for Broken {
+    fn default() -> Self {
+        Self {
+            name: "--broken".to_string(),
+            description: "List links to paths that no longer exist".to_string(),
+            args: [
+                "[--parent <Parent Entity>]".to_string(),
+                "[--delete]".to_string(),
+                "[--yes]".to_string(),
+            ],
+        }
+    }
+}
+
+/// A link to a path that does not exist
+#[derive(Debug, PartialEq)]
+struct BrokenLink {
+    parent: String,
+    link: String,
+    value: String,
+    /// The value with `~` and environment variables expanded
+    path: PathBuf,
+}
+
+impl BrokenLink {
+    fn json(&self) -> Json {
+        Json::object([
+            ("parent", Json::from(self.parent.as_str())),
+            ("link", Json::from(self.link.as_str())),
+            ("value", Json::from(self.value.as_str())),
+            ("path", Json::from(self.path.to_string_lossy().as_ref())),
+        ])
+    }
+}
+
+impl Command for Broken {
+    fn error_message(&self) -> String {
+        "expected no arguments besides the flags, see the Usage section with tap --broken --help"
+            .to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --broken command lists the links to paths that no longer exist, like directories that were moved or checkouts that were deleted, with the path they point at. Values are expanded first, ~ to the home directory and environment variables like $HOME, see tap --help open. Paths start with /, ~/, ./, ../, a drive letter, or file://, and links added with --type file or --type dir are always paths. URLs and commands are skipped, so nothing is ever sent over the network, see tap --verify to check URLs too.\n\n");
+        s.push_str("Pass --parent to only check the links of one Parent Entity. With --delete the broken links are removed after confirmation, pass --yes to skip it (required when not run in a terminal).\n\n");
+        s.push_str("Tap exits with 1 when any broken link is listed, so it can run as a check when a shell starts.\n\n");
+        s.push_str(
+            "Command Structure: tap --broken [--parent <Parent Entity>] [--delete [-y | --yes]]\n",
+        );
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let mut args = Args::new(args);
+        let parent = args.take_option("--parent");
+        let delete = args.take_flag(&["--delete"]);
+        let yes = args.take_flag(&["-y", "--yes"]);
+        match args.len() {
+            0 => self.broken(ctx, parent.as_deref(), delete, yes),
+            1 if args.is_flag(0, "--help") => Ok(CommandResult::Value(self.help_message())),
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+impl Broken {
+    /// Lists the broken links of `parent`, or of every parent entity, deleting them when `delete`
+    /// is set
+    fn broken(
+        &self,
+        ctx: &Context,
+        parent: Option<&str>,
+        delete: bool,
+        yes: bool,
+    ) -> Result<CommandResult, CommandError> {
+        let links = match parent {
+            Some(parent) => {
+                let parent = ctx.store.resolve_alias(parent)?;
+                let links = ctx
+                    .store
+                    .read_parent(&parent)
+                    .map_err(|e| ctx.store.with_suggestions(e, &parent, None))?;
+                vec![(parent, links)]
+            }
+            None => ctx.store.all_links()?,
+        };
+        let meta = ctx.store.all_meta()?;
+        let mut checked = 0;
+        let mut broken = vec![];
+        for (parent, links) in &links {
+            for (link, value) in links {
+                let link_type = meta
+                    .get(&(parent.clone(), link.clone()))
+                    .and_then(|meta| meta.link_type);
+                let Some(path) = path_of(parent, link, value, link_type) else {
+                    continue;
+                };
+                checked += 1;
+                if !path.exists() {
+                    broken.push(BrokenLink {
+                        parent: parent.clone(),
+                        link: link.clone(),
+                        value: value.clone(),
+                        path,
+                    });
+                }
+            }
+        }
+        if broken.is_empty() {
+            return Ok(CommandResult::Value(format!(
+                "No broken links found among {checked} path(s)"
+            )));
+        }
+        if ctx.format.is_structured() && !delete {
+            return Ok(CommandResult::Failure(Box::new(CommandResult::Json(
+                Json::Array(broken.iter().map(BrokenLink::json).collect()),
+            ))));
+        }
+        let report = Table {
+            title: format!("Found {} broken link(s):", broken.len()),
+            rows: rows(&broken),
+        };
+        if !delete {
+            return Ok(CommandResult::Failure(Box::new(CommandResult::Table(
+                report,
+            ))));
+        }
+        if !yes {
+            if !ctx.prompt.is_interactive() {
+                return Err(CommandError::usage(format!(
+                    "Deleting removes {} broken link(s), pass --yes to delete them without confirmation",
+                    broken.len()
+                )));
+            }
+            if !ctx.prompt.confirm(&format!(
+                "{report}\nDelete the {} broken link(s)?",
+                broken.len()
+            ))? {
+                return Ok(CommandResult::Failure(Box::new(CommandResult::Value(
+                    "Nothing was deleted".to_string(),
+                ))));
+            }
+        }
+        ctx.store.write(|ds| {
+            ds.batch(|tx| {
+                broken
+                    .iter()
+                    .try_for_each(|b| tx.delete(&b.parent, Some(&b.link)))
+            })
+        })?;
+        Ok(CommandResult::Value(style::success(&format!(
+            "Removed {} broken link(s)",
+            broken.len()
+        ))))
+    }
+}
+
+/// The path the value of a link points at once expanded, `None` for URLs, commands, and anything
+/// else that is not a path. A value naming an environment variable that is not set is skipped
+/// with a note, as it can not be told where it points.
+fn path_of(parent: &str, link: &str, value: &str, link_type: Option<LinkType>) -> Option<PathBuf> {
+    let value = value.trim();
+    if matches!(link_type, Some(LinkType::Url | LinkType::Cmd))
+        || value.starts_with("http://")
+        || value.starts_with("https://")
+    {
+        return None;
+    }
+    let expanded = match expand_env(value) {
+        Ok(expanded) => expanded,
+        Err(e) => {
+            log::note(format_args!("Skipped {parent}/{link}: {}", e.message));
+            return None;
+        }
+    };
+    match link_type {
+        Some(LinkType::File | LinkType::Dir) => {
+            local_path(&expanded).or_else(|| Some(PathBuf::from(expanded)))
+        }
+        _ => local_path(&expanded),
+    }
+}
+
+/// The broken links with the parent entity only written on its first row, so they read as groups
+fn rows(broken: &[BrokenLink]) -> Vec<Vec<String>> {
+    broken
+        .iter()
+        .enumerate()
+        .map(|(i, b)| {
+            let first_of_parent = i == 0 || broken[i - 1].parent != b.parent;
+            vec![
+                if first_of_parent {
+                    b.parent.clone()
+                } else {
+                    String::new()
+                },
+                b.link.clone(),
+                b.path.to_string_lossy().to_string(),
+            ]
+        })
+        .collect()
+}
+
+impl DisplayCommandAsRow for Broken {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            ("tap --broken", "List the links to missing paths"),
+            (
+                "tap --broken --parent repos",
+                "List the links of repos to missing paths",
+            ),
+            (
+                "tap --broken --delete",
+                "Delete the links to missing paths after confirmation",
+            ),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::CommandErrorKind;
+    use crate::utils::prompt::Prompt;
+
+    /// A directory that exists for the whole test run
+    fn existing_dir() -> String {
+        env!("CARGO_MANIFEST_DIR").to_string()
+    }
+
+    fn ctx_with_paths() -> Context {
+        Context::in_memory(vec![
+            (
+                "repos".to_string(),
+                vec![
+                    ("gone".to_string(), "/no/such/tap/checkout".to_string()),
+                    ("tap".to_string(), existing_dir()),
+                ],
+            ),
+            (
+                "work".to_string(),
+                vec![
+                    ("ci".to_string(), "https://ci.internal/no/such".to_string()),
+                    ("notes".to_string(), "file:///no/such/tap/notes".to_string()),
+                    ("ticket".to_string(), "JIRA-42".to_string()),
+                ],
+            ),
+        ])
+    }
+
+    fn run(ctx: &mut Context, args: &[&str]) -> Result<CommandResult, CommandError> {
+        Broken::default().run(ctx, args.iter().map(|a| a.to_string()).collect())
+    }
+
+    #[test]
+    fn test_broken_run_expected_help_arg() {
+        let cmd = Broken::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_broken_run_unexpected_args() {
+        let cmd = Broken::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["work".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_path_of() {
+        assert_eq!(
+            path_of("p", "l", "/no/such/path", None),
+            Some(PathBuf::from("/no/such/path"))
+        );
+        assert_eq!(
+            path_of("p", "l", "file:///no/such/path", None),
+            Some(PathBuf::from("/no/such/path"))
+        );
+        // Environment variables are expanded before the path is checked
+        assert_eq!(
+            path_of("p", "l", "${CARGO_MANIFEST_DIR}/src", None),
+            Some(PathBuf::from(existing_dir()).join("src"))
+        );
+        assert_eq!(
+            path_of("p", "l", "notes", Some(LinkType::Dir)),
+            Some(PathBuf::from("notes"))
+        );
+        assert_eq!(path_of("p", "l", "notes", None), None);
+        assert_eq!(path_of("p", "l", "https://x.y/gone", None), None);
+        assert_eq!(path_of("p", "l", "/usr/bin/top", Some(LinkType::Cmd)), None);
+        assert_eq!(path_of("p", "l", "$TAP_NO_SUCH_VARIABLE/x", None), None);
+    }
+
+    #[test]
+    fn test_broken_lists_missing_paths() {
+        let mut ctx = ctx_with_paths();
+        let res = run(&mut ctx, &[]).unwrap();
+        // Broken links fail the command
+        assert_eq!(res.exit_code(), 1);
+        assert_eq!(
+            res,
+            CommandResult::Failure(Box::new(CommandResult::Table(Table {
+                title: "Found 2 broken link(s):".to_string(),
+                rows: vec![
+                    vec![
+                        "repos".to_string(),
+                        "gone".to_string(),
+                        "/no/such/tap/checkout".to_string()
+                    ],
+                    vec![
+                        "work".to_string(),
+                        "notes".to_string(),
+                        "/no/such/tap/notes".to_string()
+                    ],
+                ],
+            })))
+        );
+        let res = run(&mut ctx, &["--parent", "work"]).unwrap();
+        let CommandResult::Failure(res) = res else {
+            panic!("expected a failure, got {res:?}");
+        };
+        let CommandResult::Table(table) = *res else {
+            panic!("expected a table, got {res:?}");
+        };
+        assert_eq!(table.rows.len(), 1);
+        assert_eq!(ctx.store.saves(), 0);
+    }
+
+    #[test]
+    fn test_broken_nothing_broken() {
+        let mut ctx = Context::in_memory(vec![(
+            "repos".to_string(),
+            vec![
+                ("tap".to_string(), existing_dir()),
+                ("ci".to_string(), "https://ci.internal".to_string()),
+            ],
+        )]);
+        let res = run(&mut ctx, &["--delete"]).unwrap();
+        assert_eq!(res.exit_code(), 0);
+        assert_eq!(
+            res,
+            CommandResult::Value("No broken links found among 1 path(s)".to_string())
+        );
+    }
+
+    #[test]
+    fn test_broken_delete() {
+        let mut ctx = ctx_with_paths();
+        assert_eq!(
+            run(&mut ctx, &["--delete", "--yes"]),
+            Ok(CommandResult::Value(style::success(
+                "Removed 2 broken link(s)"
+            )))
+        );
+        assert_eq!(ctx.store.links("repos").unwrap(), vec!["tap"]);
+        assert_eq!(ctx.store.links("work").unwrap(), vec!["ci", "ticket"]);
+        assert_eq!(ctx.store.saves(), 1);
+    }
+
+    #[test]
+    fn test_broken_delete_asks_for_confirmation() {
+        let mut ctx = ctx_with_paths();
+        ctx.prompt = Prompt::scripted("n\n");
+        let res = run(&mut ctx, &["--delete"]).unwrap();
+        assert_eq!(res.exit_code(), 1);
+        assert_eq!(ctx.store.saves(), 0);
+        // Scripts must pass --yes
+        let mut ctx = ctx_with_paths();
+        let err = run(&mut ctx, &["--delete"]).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::Usage);
+    }
+}
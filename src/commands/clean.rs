@@ -0,0 +1,601 @@
+use crate::{
+    commands::{Command, CommandError, CommandResult, Table, display_examples},
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::command::Args,
+    utils::context::Context,
+    utils::json::Json,
+    utils::os_implementations::http_head_status,
+    utils::style,
+};
+use std::path::PathBuf;
+
+/// How long a URL gets to answer before it is reported as unreachable
+const HEAD_TIMEOUT_SECS: u64 = 10;
+
+/// How many URLs are checked at the same time
+const MAX_CONCURRENT_CHECKS: usize = 8;
+
+pub(crate) struct Clean {
+    name: String,
+    description: String,
+    args: [String; 4],
+}
+
+impl Default for Clean {
+    fn default() -> Self {
+        Self {
+            name: "--clean".to_string(),
+            description: "Find and remove dead links".to_string(),
+            args: [
+                "[--dry-run | --delete]".to_string(),
+                "[--yes]".to_string(),
+                "[--offline]".to_string(),
+                "[--expired]".to_string(),
+            ],
+        }
+    }
+}
+
+/// What checking the value of a link found
+#[derive(Clone, Debug, PartialEq)]
+enum Health {
+    Alive,
+    /// The path does not exist or the URL is gone, with the reason
+    Dead(String),
+    /// The URL did not answer, which may not be its fault, so it is never deleted
+    Unreachable(String),
+    /// Neither a path nor a URL, or a URL while offline
+    Unchecked,
+    /// The link was added with `tap --add --expires` and that day has passed, with the day
+    Expired(String),
+}
+
+impl Health {
+    fn status(&self) -> &'static str {
+        match self {
+            Health::Alive => "alive",
+            Health::Dead(_) => "dead",
+            Health::Unreachable(_) => "unreachable",
+            Health::Unchecked => "unchecked",
+            Health::Expired(_) => "expired",
+        }
+    }
+
+    fn reason(&self) -> &str {
+        match self {
+            Health::Dead(reason) | Health::Unreachable(reason) | Health::Expired(reason) => reason,
+            Health::Alive | Health::Unchecked => "",
+        }
+    }
+}
+
+/// A dead or unreachable link
+#[derive(Debug, PartialEq)]
+struct Finding {
+    parent: String,
+    link: String,
+    value: String,
+    health: Health,
+}
+
+impl Finding {
+    fn json(&self) -> Json {
+        Json::object([
+            ("parent", Json::from(self.parent.as_str())),
+            ("link", Json::from(self.link.as_str())),
+            ("value", Json::from(self.value.as_str())),
+            ("status", Json::from(self.health.status())),
+            ("reason", Json::from(self.health.reason())),
+        ])
+    }
+}
+
+impl Command for Clean {
+    fn error_message(&self) -> String {
+        "expected no arguments besides the flags, see the Usage section with tap --clean --help"
+            .to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --clean command checks every link value and reports the dead ones, grouped by parent entity. Paths (starting with /, ~/, ./, ../, a drive letter, or file://) are dead when they do not exist. URLs (starting with http:// or https://) are sent a HEAD request with curl and are dead when they answer 404 or 410. URLs that do not answer within 10 seconds are reported as unreachable, and are never deleted. Anything else is not checked.\n\n");
+        s.push_str("By default, or with --dry-run, the dead links are only reported. With --delete they are removed after confirmation, pass --yes to skip it (required when not run in a terminal). Pass --offline to only check paths.\n\n");
+        s.push_str("With --expired, the links whose expiry date set with tap --add --expires has passed are removed instead, after the same confirmation. Nothing is sent over the network. Pass --dry-run to only list them.\n\n");
+        s.push_str(
+            "Command Structure: tap --clean [--dry-run | --delete] [-y | --yes] [--offline] [--expired]\n",
+        );
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let mut args = Args::new(args);
+        let delete = args.take_flag(&["--delete"]);
+        let dry_run = args.take_flag(&["--dry-run"]);
+        let yes = args.take_flag(&["-y", "--yes"]);
+        let offline = args.take_flag(&["--offline"]);
+        let expired = args.take_flag(&["--expired"]);
+        match args.len() {
+            0 if delete && dry_run => Err(CommandError::usage(
+                "--delete and --dry-run can not be used together".to_string(),
+            )),
+            0 if expired => self.clean_expired(ctx, dry_run, yes),
+            0 => self.clean(ctx, delete, yes, &|value| check(value, offline, &head)),
+            1 if args.is_flag(0, "--help") => Ok(CommandResult::Value(self.help_message())),
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+impl Clean {
+    /// Checks every link with `check`, then reports the dead and unreachable ones, deleting the
+    /// dead ones when `delete` is set
+    fn clean(
+        &self,
+        ctx: &Context,
+        delete: bool,
+        yes: bool,
+        check: &(dyn Fn(&str) -> Health + Sync),
+    ) -> Result<CommandResult, CommandError> {
+        let links = ctx.store.all_links()?;
+        let findings = find_dead_links(&links, check);
+        let checked = links.iter().map(|(_, links)| links.len()).sum::<usize>();
+        if findings.is_empty() {
+            return Ok(CommandResult::Value(format!(
+                "No dead links found among {checked} link(s)"
+            )));
+        }
+        if ctx.format.is_structured() && !delete {
+            return Ok(CommandResult::Json(Json::Array(
+                findings.iter().map(Finding::json).collect(),
+            )));
+        }
+        let dead: Vec<&Finding> = findings
+            .iter()
+            .filter(|f| matches!(f.health, Health::Dead(_)))
+            .collect();
+        let report = report(&findings, dead.len());
+        if !delete {
+            return Ok(CommandResult::Table(report));
+        }
+        if dead.is_empty() {
+            return Ok(CommandResult::Value(format!(
+                "{report}\nNothing was deleted, unreachable links are kept"
+            )));
+        }
+        self.delete(ctx, &report, &dead, "dead", yes)
+    }
+
+    /// Lists the links that expired, see `tap --add --expires`, and deletes them unless `dry_run`
+    /// is set
+    fn clean_expired(
+        &self,
+        ctx: &Context,
+        dry_run: bool,
+        yes: bool,
+    ) -> Result<CommandResult, CommandError> {
+        let meta = ctx.store.all_meta()?;
+        let findings: Vec<Finding> = ctx
+            .store
+            .all_links()?
+            .into_iter()
+            .flat_map(|(parent, links)| {
+                let meta = &meta;
+                links.into_iter().filter_map(move |(link, value)| {
+                    let date = meta
+                        .get(&(parent.clone(), link.clone()))
+                        .filter(|meta| meta.is_expired())?
+                        .expires
+                        .clone()?;
+                    Some(Finding {
+                        parent: parent.clone(),
+                        link,
+                        value,
+                        health: Health::Expired(date),
+                    })
+                })
+            })
+            .collect();
+        if findings.is_empty() {
+            return Ok(CommandResult::Value("No expired links found".to_string()));
+        }
+        if ctx.format.is_structured() && dry_run {
+            return Ok(CommandResult::Json(Json::Array(
+                findings.iter().map(Finding::json).collect(),
+            )));
+        }
+        let report = Table {
+            title: format!("Found {} expired link(s):", findings.len()),
+            rows: rows(&findings),
+        };
+        if dry_run {
+            return Ok(CommandResult::Table(report));
+        }
+        self.delete(
+            ctx,
+            &report,
+            &findings.iter().collect::<Vec<_>>(),
+            "expired",
+            yes,
+        )
+    }
+
+    /// Deletes the links of the findings after confirmation, which `yes` skips. `kind` says what
+    /// is wrong with them, like `dead`.
+    fn delete(
+        &self,
+        ctx: &Context,
+        report: &Table,
+        findings: &[&Finding],
+        kind: &str,
+        yes: bool,
+    ) -> Result<CommandResult, CommandError> {
+        if !yes {
+            if !ctx.prompt.is_interactive() {
+                return Err(CommandError::usage(format!(
+                    "Deleting removes {} {kind} link(s), pass --yes to delete them without confirmation",
+                    findings.len()
+                )));
+            }
+            if !ctx.prompt.confirm(&format!(
+                "{report}\nDelete the {} {kind} link(s)?",
+                findings.len()
+            ))? {
+                return Ok(CommandResult::Value("Nothing was deleted".to_string()));
+            }
+        }
+        ctx.store.write(|ds| {
+            ds.batch(|tx| {
+                findings
+                    .iter()
+                    .try_for_each(|f| tx.delete(&f.parent, Some(&f.link)))
+            })
+        })?;
+        Ok(CommandResult::Value(style::success(&format!(
+            "Removed {} {kind} link(s)",
+            findings.len()
+        ))))
+    }
+}
+
+/// The dead and unreachable links, in the order of `links`. URLs are checked
+/// `MAX_CONCURRENT_CHECKS` at a time, as each may take up to `HEAD_TIMEOUT_SECS` to answer.
+fn find_dead_links(
+    links: &[(String, Vec<(String, String)>)],
+    check: &(dyn Fn(&str) -> Health + Sync),
+) -> Vec<Finding> {
+    let all: Vec<(&String, &String, &String)> = links
+        .iter()
+        .flat_map(|(parent, links)| links.iter().map(move |(link, value)| (parent, link, value)))
+        .collect();
+    let mut findings = vec![];
+    for chunk in all.chunks(MAX_CONCURRENT_CHECKS) {
+        let health: Vec<Health> = std::thread::scope(|s| {
+            let checks: Vec<_> = chunk
+                .iter()
+                .map(|(_, _, value)| s.spawn(move || check(value)))
+                .collect();
+            checks
+                .into_iter()
+                .map(|c| c.join().unwrap_or(Health::Unchecked))
+                .collect()
+        });
+        for ((parent, link, value), health) in chunk.iter().zip(health) {
+            if matches!(health, Health::Dead(_) | Health::Unreachable(_)) {
+                findings.push(Finding {
+                    parent: parent.to_string(),
+                    link: link.to_string(),
+                    value: value.to_string(),
+                    health,
+                });
+            }
+        }
+    }
+    findings
+}
+
+fn report(findings: &[Finding], dead: usize) -> Table {
+    Table {
+        title: format!(
+            "Found {dead} dead and {} unreachable link(s):",
+            findings.len() - dead
+        ),
+        rows: rows(findings),
+    }
+}
+
+/// The findings with the parent entity only written on its first row, so they read as groups
+fn rows(findings: &[Finding]) -> Vec<Vec<String>> {
+    findings
+        .iter()
+        .enumerate()
+        .map(|(i, f)| {
+            let first_of_parent = i == 0 || findings[i - 1].parent != f.parent;
+            vec![
+                if first_of_parent {
+                    f.parent.clone()
+                } else {
+                    String::new()
+                },
+                f.link.clone(),
+                format!("{} ({})", f.health.status(), f.health.reason()),
+                f.value.clone(),
+            ]
+        })
+        .collect()
+}
+
+/// Checks a path for existence and, unless `offline`, a URL with `head`
+fn check(value: &str, offline: bool, head: &dyn Fn(&str) -> Health) -> Health {
+    if value.starts_with("http://") || value.starts_with("https://") {
+        return match offline {
+            true => Health::Unchecked,
+            false => head(value),
+        };
+    }
+    match local_path(value) {
+        Some(path) if path.exists() => Health::Alive,
+        Some(_) => Health::Dead("missing".to_string()),
+        None => Health::Unchecked,
+    }
+}
+
+/// Sends a HEAD request to the URL, see `http_head_status`
+fn head(url: &str) -> Health {
+    match http_head_status(url, HEAD_TIMEOUT_SECS) {
+        Ok(Some(status @ (404 | 410))) => Health::Dead(format!("HTTP {status}")),
+        Ok(Some(_)) => Health::Alive,
+        Ok(None) => Health::Unreachable("no response".to_string()),
+        Err(e) => Health::Unreachable(e.to_string()),
+    }
+}
+
+/// The path a value points at, `None` when it does not look like a path
+pub(in crate::commands) fn local_path(value: &str) -> Option<PathBuf> {
+    let value = value.strip_prefix("file://").unwrap_or(value);
+    if let Some(rest) = value.strip_prefix("~/") {
+        return std::env::var_os("HOME").map(|home| PathBuf::from(home).join(rest));
+    }
+    let drive = value.as_bytes();
+    let windows_drive = drive.len() > 2
+        && drive[0].is_ascii_alphabetic()
+        && drive[1] == b':'
+        && matches!(drive[2], b'\\' | b'/');
+    match value.starts_with('/') || value.starts_with("./") || value.starts_with("../") {
+        true => Some(PathBuf::from(value)),
+        false => windows_drive.then(|| PathBuf::from(value)),
+    }
+}
+
+impl DisplayCommandAsRow for Clean {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            ("tap --clean", "Report the dead links"),
+            ("tap --clean --offline", "Report the missing paths only"),
+            (
+                "tap --clean --delete --yes",
+                "Delete the dead links without confirmation",
+            ),
+            ("tap --clean --expired", "Delete the expired links"),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::CommandErrorKind;
+    use crate::utils::prompt::Prompt;
+
+    /// A file that exists for the whole test run
+    fn existing_file() -> String {
+        env!("CARGO_MANIFEST_DIR").to_string() + "/Cargo.toml"
+    }
+
+    fn ctx_with_links() -> Context {
+        Context::in_memory(vec![
+            (
+                "docs".to_string(),
+                vec![
+                    ("manifest".to_string(), existing_file()),
+                    ("old".to_string(), "/no/such/tap/file".to_string()),
+                    ("wiki".to_string(), "https://wiki.internal/gone".to_string()),
+                ],
+            ),
+            (
+                "work".to_string(),
+                vec![
+                    ("ci".to_string(), "https://ci.internal".to_string()),
+                    ("ticket".to_string(), "JIRA-42".to_string()),
+                ],
+            ),
+        ])
+    }
+
+    /// Answers 404 for URLs ending with /gone and nothing for any other URL
+    fn fake_check(value: &str) -> Health {
+        check(value, false, &|url| match url.ends_with("/gone") {
+            true => Health::Dead("HTTP 404".to_string()),
+            false => Health::Unreachable("no response".to_string()),
+        })
+    }
+
+    #[test]
+    fn test_clean_run_expected_help_arg() {
+        let cmd = Clean::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_clean_run_unexpected_args() {
+        let cmd = Clean::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["work".to_string()]),
+            expected
+        );
+        let res = cmd.run(
+            &mut Context::default(),
+            vec!["--delete".to_string(), "--dry-run".to_string()],
+        );
+        assert_eq!(res.unwrap_err().kind, CommandErrorKind::Usage);
+    }
+
+    #[test]
+    fn test_check() {
+        assert_eq!(fake_check(&existing_file()), Health::Alive);
+        assert_eq!(
+            fake_check("file:///no/such/tap/file"),
+            Health::Dead("missing".to_string())
+        );
+        assert_eq!(
+            fake_check("https://x.y/gone"),
+            Health::Dead("HTTP 404".to_string())
+        );
+        assert_eq!(fake_check("JIRA-42"), Health::Unchecked);
+        assert_eq!(
+            check("https://x.y/gone", true, &|_| Health::Alive),
+            Health::Unchecked
+        );
+    }
+
+    #[test]
+    fn test_local_path() {
+        assert_eq!(local_path("./notes"), Some(PathBuf::from("./notes")));
+        assert_eq!(local_path("C:\\notes"), Some(PathBuf::from("C:\\notes")));
+        assert_eq!(local_path("notes"), None);
+        assert_eq!(local_path("mailto:a@b.c"), None);
+    }
+
+    #[test]
+    fn test_clean_dry_run_reports_by_parent() {
+        let ctx = ctx_with_links();
+        let res = Clean::default().clean(&ctx, false, false, &fake_check);
+        assert_eq!(
+            res.unwrap().to_string(),
+            "Found 2 dead and 1 unreachable link(s):\n  docs  old   dead (missing)             /no/such/tap/file\n        wiki  dead (HTTP 404)            https://wiki.internal/gone\n  work  ci    unreachable (no response)  https://ci.internal"
+        );
+        assert_eq!(ctx.store.saves(), 0);
+    }
+
+    #[test]
+    fn test_clean_offline_skips_urls() {
+        let ctx = ctx_with_links();
+        let res = Clean::default().clean(&ctx, false, false, &|v| check(v, true, &head));
+        let Ok(CommandResult::Table(table)) = res else {
+            panic!("expected a table, got {res:?}");
+        };
+        assert_eq!(table.rows.len(), 1);
+        assert_eq!(table.rows[0][1], "old");
+    }
+
+    #[test]
+    fn test_clean_delete_keeps_unreachable() {
+        let ctx = ctx_with_links();
+        let res = Clean::default().clean(&ctx, true, true, &fake_check);
+        assert_eq!(
+            res,
+            Ok(CommandResult::Value("Removed 2 dead link(s)".to_string()))
+        );
+        assert_eq!(ctx.store.links("docs").unwrap(), vec!["manifest"]);
+        assert_eq!(ctx.store.links("work").unwrap(), vec!["ci", "ticket"]);
+        assert_eq!(ctx.store.saves(), 1);
+    }
+
+    #[test]
+    fn test_clean_delete_asks_for_confirmation() {
+        let mut ctx = ctx_with_links();
+        ctx.prompt = Prompt::scripted("n\n");
+        let res = Clean::default().clean(&ctx, true, false, &fake_check);
+        assert_eq!(
+            res,
+            Ok(CommandResult::Value("Nothing was deleted".to_string()))
+        );
+        assert_eq!(ctx.store.saves(), 0);
+        // Scripts must pass --yes
+        let ctx = ctx_with_links();
+        let res = Clean::default().clean(&ctx, true, false, &fake_check);
+        assert_eq!(res.unwrap_err().kind, CommandErrorKind::Usage);
+    }
+
+    #[test]
+    fn test_clean_nothing_dead() {
+        let ctx = Context::in_memory(vec![(
+            "work".to_string(),
+            vec![("ticket".to_string(), "JIRA-42".to_string())],
+        )]);
+        assert_eq!(
+            Clean::default().clean(&ctx, true, false, &fake_check),
+            Ok(CommandResult::Value(
+                "No dead links found among 1 link(s)".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_clean_expired() {
+        let mut ctx = Context::in_memory(vec![(
+            "sprint".to_string(),
+            vec![
+                ("board".to_string(), "https://board.internal".to_string()),
+                (
+                    "retro".to_string(),
+                    "https://docs.internal/retro".to_string(),
+                ),
+            ],
+        )]);
+        let run = |ctx: &mut Context, args: &[&str]| {
+            Clean::default().run(ctx, args.iter().map(|a| a.to_string()).collect())
+        };
+        assert_eq!(
+            run(&mut ctx, &["--expired"]),
+            Ok(CommandResult::Value("No expired links found".to_string()))
+        );
+        ctx.store
+            .write(|ds| {
+                ds.batch(|tx| {
+                    tx.update_meta("sprint", "retro", |m| {
+                        m.expires = Some("2000-01-01".to_string())
+                    })
+                })
+            })
+            .unwrap();
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Table(Table {
+            title: "Found 1 expired link(s):".to_string(),
+            rows: vec![vec![
+                "sprint".to_string(),
+                "retro".to_string(),
+                "expired (2000-01-01)".to_string(),
+                "https://docs.internal/retro".to_string(),
+            ]],
+        }));
+        assert_eq!(run(&mut ctx, &["--expired", "--dry-run"]), expected);
+        assert_eq!(
+            run(&mut ctx, &["--expired", "--yes"]),
+            Ok(CommandResult::Value(
+                "Removed 1 expired link(s)".to_string()
+            ))
+        );
+        assert_eq!(ctx.store.links("sprint").unwrap(), vec!["board"]);
+    }
+}
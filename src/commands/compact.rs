@@ -0,0 +1,189 @@
+use crate::{
+    commands::{Command, CommandError, CommandResult, display_bytes, display_examples},
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::context::Context,
+    utils::tap_data_store::CompactSummary,
+};
+use std::path::Path;
+
+pub(crate) struct Compact {
+    name: String,
+    description: String,
+    args: [String; 1],
+}
+
+impl Default for Compact {
+    fn default() -> Self {
+        Self {
+            name: "--compact".to_string(),
+            description: "Clean up and rewrite the data file".to_string(),
+            args: ["[--salvage]".to_string()],
+        }
+    }
+}
+
+impl Command for Compact {
+    fn error_message(&self) -> String {
+        "expected 0-1 arguments, see the Usage section with tap --compact --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --compact command rewrites the data file after trimming and sorting every parent entity and link, merging parent entities that appear more than once, removing duplicate links, and dropping parent entities without links. The index file is rebuilt afterwards. Running it again on a compacted data file changes nothing.\n\n");
+        s.push_str("If the data file can not be fully parsed, nothing is rewritten unless --salvage is passed. With --salvage, every line that can not be parsed is skipped and written to .tap_data.rejected.\n\n");
+        s.push_str("Command Structure: tap --compact [--salvage]\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        match args.len() {
+            0 => {
+                let (summary, rejected_path) = ctx.store.compact(false).map_err(|e| {
+                    CommandError::from(e).with_hint(
+                        "Run tap --compact --salvage to skip the lines that can not be parsed",
+                    )
+                })?;
+                Ok(CommandResult::Value(display_summary(
+                    &summary,
+                    &rejected_path,
+                )))
+            }
+            1 => match args[0].as_str() {
+                "--help" => Ok(CommandResult::Value(self.help_message())),
+                "--salvage" => {
+                    let (summary, rejected_path) = ctx.store.compact(true)?;
+                    Ok(CommandResult::Value(display_summary(
+                        &summary,
+                        &rejected_path,
+                    )))
+                }
+                _ => Err(self.usage_error()),
+            },
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+impl DisplayCommandAsRow for Compact {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            (
+                "tap --compact",
+                "Clean up the data file after editing it by hand",
+            ),
+            (
+                "tap --compact --salvage",
+                "Clean up a data file with lines that can not be parsed",
+            ),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+fn display_summary(summary: &CompactSummary, rejected_path: &Path) -> String {
+    let size = match summary.bytes_before.cmp(&summary.bytes_after) {
+        std::cmp::Ordering::Greater => format!(
+            "file shrank {}",
+            display_bytes(summary.bytes_before - summary.bytes_after)
+        ),
+        std::cmp::Ordering::Less => format!(
+            "file grew {}",
+            display_bytes(summary.bytes_after - summary.bytes_before)
+        ),
+        std::cmp::Ordering::Equal => "file size unchanged".to_string(),
+    };
+    let mut s = format!(
+        "Compacted data file: removed {} duplicate(s), merged {} parent(s), dropped {} empty parent(s), {size}",
+        summary.duplicates_removed, summary.parents_merged, summary.empty_parents_removed
+    );
+    if !summary.rejected.is_empty() {
+        s.push_str(&format!(
+            "\nSkipped {} unparseable line(s), written to {}",
+            summary.rejected.len(),
+            rejected_path.display()
+        ));
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compact_run_expected_help_arg() {
+        let args: Vec<String> = vec!["--help".to_string()];
+        let cmd = Compact::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        let res = cmd.run(&mut Context::default(), args);
+        assert_eq!(res, expected);
+    }
+
+    #[test]
+    fn test_compact_run_unexpected_args() {
+        let args: Vec<String> = vec!["random".to_string()];
+        let cmd = Compact::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        let res = cmd.run(&mut Context::default(), args);
+        assert_eq!(res, expected);
+    }
+
+    #[test]
+    fn test_compact_run_merges_duplicate_parents() {
+        let cmd = Compact::default();
+        let mut ctx = Context::in_memory(vec![
+            (
+                "search-engines".to_string(),
+                vec![("google".to_string(), "https://google.com".to_string())],
+            ),
+            (
+                "search-engines".to_string(),
+                vec![
+                    ("google".to_string(), "https://google.com".to_string()),
+                    ("yahoo".to_string(), "https://yahoo.com".to_string()),
+                ],
+            ),
+        ]);
+        let res = cmd.run(&mut ctx, vec![]);
+        assert!(
+            res.as_ref()
+                .unwrap()
+                .to_string()
+                .starts_with("Compacted data file: removed 1 duplicate(s), merged 1 parent(s), dropped 0 empty parent(s), file shrank")
+        );
+        assert_eq!(
+            ctx.store.links("search-engines").unwrap(),
+            vec!["google".to_string(), "yahoo".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_compact_run_is_idempotent() {
+        let cmd = Compact::default();
+        let mut ctx = Context::in_memory(vec![(
+            "search-engines".to_string(),
+            vec![("google".to_string(), "https://google.com".to_string())],
+        )]);
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Value(
+            "Compacted data file: removed 0 duplicate(s), merged 0 parent(s), dropped 0 empty parent(s), file size unchanged".to_string(),
+        ));
+        assert_eq!(cmd.run(&mut ctx, vec![]), expected);
+        assert_eq!(cmd.run(&mut ctx, vec![]), expected);
+    }
+}
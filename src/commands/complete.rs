@@ -0,0 +1,539 @@
+use crate::{
+    commands::{Command, CommandError, CommandResult, command_flags, links_pinned_first},
+    utils::command::get_current_directory_name,
+    utils::context::Context,
+    utils::format::Format,
+    utils::groups::members,
+    utils::tap_data_store::{is_profile_name, profiles},
+};
+
+/// Global flags that can come before the command, see `cli::run`
+pub(in crate::commands) const GLOBAL_FLAGS: [&str; 7] = [
+    "--read-only",
+    "-q",
+    "--quiet",
+    "--verbose",
+    "--no-input",
+    "--no-color",
+    "--no-hooks",
+];
+
+/// Global flags that are followed by a path
+pub(in crate::commands) const GLOBAL_PATH_FLAGS: [&str; 2] = ["--file", "--index"];
+
+/// Global flag followed by one of the `Format` names
+pub(in crate::commands) const GLOBAL_FORMAT_FLAG: &str = "--format";
+
+/// Commands whose first argument is an existing parent entity
+const PARENT_COMMANDS: [&str; 16] = [
+    "-a",
+    "--add",
+    "-d",
+    "--delete",
+    "-s",
+    "--show",
+    "-u",
+    "--upsert",
+    "--list",
+    "--which",
+    "--exists",
+    "--pin",
+    "--unpin",
+    "--note",
+    "--set-default",
+    "--archive",
+];
+
+/// Commands whose second argument is an existing link of the parent entity
+const LINK_COMMANDS: [&str; 12] = [
+    "-d",
+    "--delete",
+    "-s",
+    "--show",
+    "-u",
+    "--upsert",
+    "--which",
+    "--exists",
+    "--pin",
+    "--unpin",
+    "--note",
+    "--set-default",
+];
+
+/// Hidden command shell completions ask for the candidates of the word being typed, so the
+/// completion scripts do not need to know tap's commands. It is not listed in the usage table.
+#[derive(Default)]
+pub(crate) struct Complete {}
+
+impl Command for Complete {
+    fn error_message(&self) -> String {
+        "expected the index of the word to complete followed by the words typed after tap"
+            .to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap __complete command prints the candidates for the word being completed, one per line. It is meant for shell completion scripts and only reads the data store.\n\n");
+        s.push_str("Command Structure: tap __complete <Index> [Word...]\n");
+        s.push_str("The words are the ones typed after tap, and Index is the position of the word being completed in them, starting at 0. The word at Index may be missing when nothing of it was typed yet.\n");
+        s.push_str("Example Usage: \n\n");
+        s.push_str("  - Complete a command: tap __complete 0 --sh\n");
+        s.push_str("  - Complete a link of search-engines: tap __complete 1 search-engines go\n");
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        if args.len() == 1 && args[0] == "--help" {
+            return Ok(CommandResult::Value(self.help_message()));
+        }
+        let Some(cursor) = args.first().and_then(|a| a.parse::<usize>().ok()) else {
+            return Err(self.usage_error());
+        };
+        let words = &args[1..];
+        let current = words.get(cursor).map_or("", |w| w.as_str());
+        Ok(CommandResult::List(
+            self.candidates(ctx, &words[..cursor.min(words.len())], current)
+                .into_iter()
+                .filter(|c| c.starts_with(current))
+                .collect(),
+        ))
+    }
+}
+
+impl Complete {
+    /// The candidates for the word following `before`. Completion must never get in the way of
+    /// typing, so a data store that can not be read just gives no candidates.
+    fn candidates(&self, ctx: &mut Context, before: &[String], current: &str) -> Vec<String> {
+        let mut before = before;
+        loop {
+            match before {
+                [flag, tail @ ..] if GLOBAL_FLAGS.contains(&flag.as_str()) => before = tail,
+                [flag, _, tail @ ..] if GLOBAL_PATH_FLAGS.contains(&flag.as_str()) => before = tail,
+                // The path itself is completed by the shell
+                [flag] if GLOBAL_PATH_FLAGS.contains(&flag.as_str()) => return vec![],
+                [flag, _, tail @ ..] if flag == GLOBAL_FORMAT_FLAG => before = tail,
+                // Completion reads the links of the profile given, like the command would
+                [flag, name, tail @ ..] if flag == "--profile" && is_profile_name(name) => {
+                    ctx.store.use_profile(name.to_string());
+                    before = tail;
+                }
+                [flag] if flag == "--profile" => {
+                    let mut candidates = profiles().unwrap_or_default();
+                    candidates.extend(["list", "use"].map(str::to_string));
+                    return candidates;
+                }
+                [flag, action] if flag == "--profile" && action == "use" => {
+                    return profiles().unwrap_or_default();
+                }
+                [flag] if flag == GLOBAL_FORMAT_FLAG => {
+                    return Format::ALL.map(|f| f.name().to_string()).to_vec();
+                }
+                _ => break,
+            }
+        }
+        match before {
+            [] => {
+                let mut candidates = command_flags();
+                candidates.extend(GLOBAL_FLAGS.iter().map(|f| f.to_string()));
+                candidates.extend(GLOBAL_PATH_FLAGS.iter().map(|f| f.to_string()));
+                candidates.push(GLOBAL_FORMAT_FLAG.to_string());
+                candidates.push("here".to_string());
+                candidates.extend(self.parents(ctx, current));
+                candidates.extend(ctx.store.aliases().unwrap_or_default().into_keys());
+                candidates
+            }
+            [command] if PARENT_COMMANDS.contains(&command.as_str()) => {
+                let mut candidates = vec!["here".to_string()];
+                candidates.extend(self.parents(ctx, current));
+                candidates.extend(ctx.store.aliases().unwrap_or_default().into_keys());
+                candidates
+            }
+            [command] if command == "--unarchive" => ctx
+                .store
+                .archived()
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(parent, _)| parent)
+                .collect(),
+            [command] if command == "--unalias" => ctx
+                .store
+                .aliases()
+                .unwrap_or_default()
+                .into_keys()
+                .collect(),
+            [command] if command == "--session" => ["save", "open", "list", "delete"]
+                .map(str::to_string)
+                .to_vec(),
+            [command, action]
+                if command == "--session" && (action == "open" || action == "delete") =>
+            {
+                ctx.store
+                    .sessions()
+                    .unwrap_or_default()
+                    .into_keys()
+                    .collect()
+            }
+            // Links of any parent entity can be saved, so they are offered as <Parent>/<Link>
+            [command, action, _, typed @ ..] if command == "--session" && action == "save" => {
+                self.parent_links(ctx, typed)
+            }
+            [command] if command == "--group" => ["add", "remove", "open", "show"]
+                .map(str::to_string)
+                .to_vec(),
+            [command, action]
+                if command == "--group"
+                    && ["remove", "open", "show"].contains(&action.as_str()) =>
+            {
+                ctx.store
+                    .groups()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|(name, _)| name)
+                    .collect()
+            }
+            [command, action, _, typed @ ..] if command == "--group" && action == "add" => {
+                self.parent_links(ctx, typed)
+            }
+            [command, action, group, typed @ ..] if command == "--group" && action == "remove" => {
+                let groups = ctx.store.groups().unwrap_or_default();
+                members(&groups, group)
+                    .iter()
+                    .map(|(parent, link)| format!("{parent}/{link}"))
+                    .filter(|link| !typed.contains(link))
+                    .collect()
+            }
+            [command, _] if command == "--alias" => {
+                let mut candidates = vec!["here".to_string()];
+                candidates.extend(self.parents(ctx, current));
+                candidates
+            }
+            [command, parent] if LINK_COMMANDS.contains(&command.as_str()) => {
+                self.links(ctx, parent)
+            }
+            // Several links can be opened at once, so the ones typed already are left out
+            [parent, typed @ ..] if !parent.starts_with('-') => {
+                let mut links = self.links(ctx, parent);
+                links.retain(|link| !typed.contains(link));
+                links
+            }
+            _ => vec![],
+        }
+    }
+
+    /// The parent entities, completed one segment of a nested name at a time: with `work/` typed,
+    /// `work/infra/grafana` is offered as `work/infra/`
+    fn parents(&self, ctx: &Context, current: &str) -> Vec<String> {
+        let mut parents: Vec<String> = vec![];
+        for parent in ctx.store.parents().unwrap_or_default() {
+            let segment = match parent.get(current.len()..).and_then(|rest| rest.find('/')) {
+                Some(i) if parent.starts_with(current) => {
+                    parent[..current.len() + i + 1].to_string()
+                }
+                _ => parent,
+            };
+            if !parents.contains(&segment) {
+                parents.push(segment);
+            }
+        }
+        parents
+    }
+
+    /// Every link as `<Parent>/<Link>`, leaving out the ones typed already
+    fn parent_links(&self, ctx: &Context, typed: &[String]) -> Vec<String> {
+        ctx.store
+            .all_links()
+            .unwrap_or_default()
+            .into_iter()
+            .flat_map(|(parent, links)| {
+                links
+                    .into_iter()
+                    .map(move |(link, _)| format!("{parent}/{link}"))
+            })
+            .filter(|link| !typed.contains(link))
+            .collect()
+    }
+
+    fn links(&self, ctx: &Context, parent: &str) -> Vec<String> {
+        let parent = match parent {
+            "here" => match get_current_directory_name() {
+                Ok(name) => name,
+                Err(_) => return vec![],
+            },
+            parent => parent.to_string(),
+        };
+        // Pinned links come first, so they are the ones shells offer first
+        links_pinned_first(&ctx.store, &parent)
+            .map(|links| links.into_iter().map(|(link, _)| link).collect())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::sessions::Sessions;
+
+    fn complete(args: &[&str]) -> Result<CommandResult, CommandError> {
+        let cmd = Complete::default();
+        let mut ctx = Context::in_memory(vec![
+            (
+                "search-engines".to_string(),
+                vec![
+                    ("google".to_string(), "https://google.com".to_string()),
+                    ("yahoo".to_string(), "https://yahoo.com".to_string()),
+                ],
+            ),
+            (
+                "security".to_string(),
+                vec![("vault".to_string(), "https://vault.io".to_string())],
+            ),
+        ]);
+        cmd.run(&mut ctx, args.iter().map(|a| a.to_string()).collect())
+    }
+
+    fn lines(lines: &[&str]) -> Result<CommandResult, CommandError> {
+        Ok(CommandResult::List(
+            lines.iter().map(|l| l.to_string()).collect(),
+        ))
+    }
+
+    #[test]
+    fn test_complete_run_unexpected_args() {
+        let cmd = Complete::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(cmd.run(&mut Context::default(), vec![]), expected);
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["first".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_complete_run_commands() {
+        assert_eq!(complete(&["0", "--sh"]), lines(&["--show"]));
+        assert_eq!(
+            complete(&["0", "--d"]),
+            lines(&["--delete", "--doctor", "--dedupe", "--diff"])
+        );
+    }
+
+    #[test]
+    fn test_complete_run_first_bare_word() {
+        assert_eq!(
+            complete(&["0", "se"]),
+            lines(&["search-engines", "security"])
+        );
+        assert_eq!(complete(&["0", "he"]), lines(&["here"]));
+    }
+
+    #[test]
+    fn test_complete_run_parent_after_command() {
+        assert_eq!(
+            complete(&["1", "-d"]),
+            lines(&["here", "search-engines", "security"])
+        );
+        assert_eq!(complete(&["1", "--add", "sec"]), lines(&["security"]));
+    }
+
+    #[test]
+    fn test_complete_run_links() {
+        assert_eq!(
+            complete(&["1", "search-engines"]),
+            lines(&["google", "yahoo"])
+        );
+        assert_eq!(
+            complete(&["2", "-s", "search-engines", "y"]),
+            lines(&["yahoo"])
+        );
+        // New links are not completed
+        assert_eq!(complete(&["2", "-a", "search-engines"]), lines(&[]));
+    }
+
+    #[test]
+    fn test_complete_run_more_links_to_open() {
+        assert_eq!(
+            complete(&["2", "search-engines", "google"]),
+            lines(&["yahoo"])
+        );
+        assert_eq!(
+            complete(&["3", "search-engines", "google", "yahoo"]),
+            lines(&[])
+        );
+    }
+
+    #[test]
+    fn test_complete_run_pinned_links_first() {
+        let mut ctx = Context::in_memory(vec![(
+            "work".to_string(),
+            vec![
+                ("ci".to_string(), "https://ci.internal".to_string()),
+                ("mr".to_string(), "https://mr.internal".to_string()),
+                ("wiki".to_string(), "https://wiki.internal".to_string()),
+            ],
+        )]);
+        ctx.store
+            .write(|ds| ds.batch(|tx| tx.update_meta("work", "wiki", |m| m.pinned = true)))
+            .unwrap();
+        let res = Complete::default().run(&mut ctx, vec!["1".to_string(), "work".to_string()]);
+        assert_eq!(res, lines(&["wiki", "ci", "mr"]));
+    }
+
+    #[test]
+    fn test_complete_run_aliases() {
+        let mut ctx = Context::in_memory(vec![(
+            "kubernetes-production-cluster".to_string(),
+            vec![("grafana".to_string(), "https://grafana.k8s".to_string())],
+        )]);
+        ctx.store
+            .write(|ds| ds.set_alias("k8s", "kubernetes-production-cluster"))
+            .unwrap();
+        let mut complete = |args: &[&str]| {
+            Complete::default().run(&mut ctx, args.iter().map(|a| a.to_string()).collect())
+        };
+        assert_eq!(
+            complete(&["0", "k"]),
+            lines(&["kubernetes-production-cluster", "k8s"])
+        );
+        assert_eq!(complete(&["1", "k8s", "g"]), lines(&["grafana"]));
+        assert_eq!(complete(&["1", "--unalias"]), lines(&["k8s"]));
+    }
+
+    #[test]
+    fn test_complete_run_sessions() {
+        let mut ctx = Context::in_memory(vec![(
+            "work".to_string(),
+            vec![
+                ("ci".to_string(), "https://ci.internal".to_string()),
+                ("mr".to_string(), "https://mr.internal".to_string()),
+            ],
+        )]);
+        ctx.store
+            .write_sessions(&Sessions::from([(
+                "morning".to_string(),
+                vec![("work".to_string(), "ci".to_string())],
+            )]))
+            .unwrap();
+        let mut complete = |args: &[&str]| {
+            Complete::default().run(&mut ctx, args.iter().map(|a| a.to_string()).collect())
+        };
+        assert_eq!(complete(&["1", "--session", "o"]), lines(&["open"]));
+        assert_eq!(complete(&["2", "--session", "open"]), lines(&["morning"]));
+        assert_eq!(
+            complete(&["4", "--session", "save", "evening", "work/ci"]),
+            lines(&["work/mr"])
+        );
+    }
+
+    #[test]
+    fn test_complete_run_groups() {
+        let mut ctx = Context::in_memory(vec![(
+            "work".to_string(),
+            vec![
+                ("ci".to_string(), "https://ci.internal".to_string()),
+                ("mr".to_string(), "https://mr.internal".to_string()),
+            ],
+        )]);
+        ctx.store
+            .write(|ds| {
+                ds.batch(|tx| {
+                    tx.update_meta("work", "ci", |m| m.groups = vec!["oncall".to_string()])
+                })
+            })
+            .unwrap();
+        let mut complete = |args: &[&str]| {
+            Complete::default().run(&mut ctx, args.iter().map(|a| a.to_string()).collect())
+        };
+        assert_eq!(complete(&["1", "--group", "o"]), lines(&["open"]));
+        assert_eq!(complete(&["2", "--group", "open"]), lines(&["oncall"]));
+        assert_eq!(
+            complete(&["3", "--group", "remove", "oncall"]),
+            lines(&["work/ci"])
+        );
+        assert_eq!(
+            complete(&["4", "--group", "add", "oncall", "work/ci"]),
+            lines(&["work/mr"])
+        );
+    }
+
+    #[test]
+    fn test_complete_run_archived_parents() {
+        let mut ctx = Context::in_memory(vec![
+            (
+                "old-project".to_string(),
+                vec![("ci".to_string(), "https://ci.old".to_string())],
+            ),
+            (
+                "work".to_string(),
+                vec![("ci".to_string(), "https://ci.internal".to_string())],
+            ),
+        ]);
+        ctx.store.write(|ds| ds.archive("old-project")).unwrap();
+        let mut complete = |args: &[&str]| {
+            Complete::default().run(&mut ctx, args.iter().map(|a| a.to_string()).collect())
+        };
+        // Archived parent entities are only offered to bring them back
+        assert_eq!(complete(&["0", "o"]), lines(&[]));
+        assert_eq!(complete(&["1", "--unarchive"]), lines(&["old-project"]));
+    }
+
+    #[test]
+    fn test_complete_run_nested_parents() {
+        let mut ctx = Context::in_memory(
+            [
+                "work/infra/grafana",
+                "work/infra/loki",
+                "work/tools",
+                "workshop",
+            ]
+            .map(|parent| {
+                (
+                    parent.to_string(),
+                    vec![("home".to_string(), "https://home.internal".to_string())],
+                )
+            })
+            .to_vec(),
+        );
+        let mut complete = |args: &[&str]| {
+            Complete::default().run(&mut ctx, args.iter().map(|a| a.to_string()).collect())
+        };
+        assert_eq!(
+            complete(&["1", "--show", "wo"]),
+            lines(&["work/", "workshop"])
+        );
+        assert_eq!(
+            complete(&["1", "--show", "work/"]),
+            lines(&["work/infra/", "work/tools"])
+        );
+        assert_eq!(
+            complete(&["0", "work/infra/"]),
+            lines(&["work/infra/grafana", "work/infra/loki"])
+        );
+    }
+
+    #[test]
+    fn test_complete_run_skips_profile() {
+        assert_eq!(
+            complete(&["3", "--profile", "work", "search-engines"]),
+            lines(&["google", "yahoo"])
+        );
+    }
+
+    #[test]
+    fn test_complete_run_skips_global_flags() {
+        assert_eq!(
+            complete(&["4", "--file", "x.tap_data", "-q", "security", "v"]),
+            lines(&["vault"])
+        );
+        assert_eq!(complete(&["1", "--file"]), lines(&[]));
+        assert_eq!(
+            complete(&["3", "--format", "yaml", "security", "v"]),
+            lines(&["vault"])
+        );
+        assert_eq!(complete(&["1", "--format", "y"]), lines(&["yaml"]));
+    }
+
+    #[test]
+    fn test_complete_run_unknown_parent() {
+        assert_eq!(complete(&["1", "personal"]), lines(&[]));
+    }
+}
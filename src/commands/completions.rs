@@ -0,0 +1,129 @@
+use crate::{
+    commands::{Command, CommandError, CommandResult, display_examples, init::Shell},
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::context::Context,
+};
+
+pub(crate) struct Completions {
+    name: String,
+    description: String,
+    args: [String; 1],
+}
+
+impl Default for Completions {
+    fn default() -> Self {
+        Self {
+            name: "--completions".to_string(),
+            description: "Print a shell completion script".to_string(),
+            args: ["<zsh|bash|fish>".to_string()],
+        }
+    }
+}
+
+impl Command for Completions {
+    fn error_message(&self) -> String {
+        "expected 1 argument, see the Usage section with tap --completions --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --completions command prints the completion script of a shell to stdout and nothing else, so packagers can install it at build time. It is the same script tap --init installs.\n\n");
+        s.push_str("Command Structure: tap --completions <zsh|bash|fish>\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, _ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        match args.as_slice() {
+            [flag] if flag == "--help" => Ok(CommandResult::Value(self.help_message())),
+            [shell] => Ok(CommandResult::Value(
+                Shell::parse(shell)?.script().to_string(),
+            )),
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+impl DisplayCommandAsRow for Completions {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            (
+                "tap --completions zsh > _tap",
+                "Write the zsh completion script to a file",
+            ),
+            (
+                "tap --completions bash > tap.bash",
+                "Write the bash completion script to a file",
+            ),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_completions_run_expected_help_arg() {
+        let cmd = Completions::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_completions_run_unexpected_args() {
+        let cmd = Completions::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(cmd.run(&mut Context::default(), vec![]), expected);
+        assert_eq!(
+            cmd.run(
+                &mut Context::default(),
+                vec!["zsh".to_string(), "bash".to_string()]
+            ),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_completions_run_prints_script() {
+        let cmd = Completions::default();
+        for shell in ["zsh", "bash", "fish"] {
+            assert_eq!(
+                cmd.run(&mut Context::default(), vec![shell.to_string()]),
+                Ok(CommandResult::Value(
+                    Shell::parse(shell).unwrap().script().to_string()
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn test_completions_run_unknown_shell() {
+        let cmd = Completions::default();
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["powershell".to_string()]),
+            Err(CommandError::usage(
+                "unknown shell powershell, the supported shells are: bash, fish, zsh".to_string()
+            ))
+        );
+    }
+}
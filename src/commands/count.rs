@@ -0,0 +1,163 @@
+use crate::{
+    commands::{Command, CommandError, CommandResult, display_examples},
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::command::Args,
+    utils::context::Context,
+};
+
+pub(crate) struct Count {
+    name: String,
+    description: String,
+    args: [String; 2],
+}
+
+impl Default for Count {
+    fn default() -> Self {
+        Self {
+            name: "--count".to_string(),
+            description: "Print the number of links or parents".to_string(),
+            args: ["[Parent|here]".to_string(), "[--parents]".to_string()],
+        }
+    }
+}
+
+impl Command for Count {
+    fn error_message(&self) -> String {
+        "expected 0-1 arguments, see the Usage section with tap --count --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --count command prints the number of links in the data file, or of one Parent Entity when it is given, as a bare number for scripts and shell prompts. A Parent Entity that is not found exits with 3.\n\n");
+        s.push_str("Command Structure: tap --count [Parent Entity | here] [--parents]\n");
+        s.push_str("Pass --parents to print the number of Parent Entities instead, read from the index only.\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let mut args = Args::new(args);
+        let parents = args.take_flag(&["--parents"]);
+        let count = match args.len() {
+            1 if args.is_flag(0, "--help") => {
+                return Ok(CommandResult::Value(self.help_message()));
+            }
+            0 if parents => ctx.store.parents()?.len(),
+            0 => ctx
+                .store
+                .all_links()?
+                .iter()
+                .map(|(_, links)| links.len())
+                .sum(),
+            1 if !parents => ctx.store.read_parent(&args.parent(0)?)?.len(),
+            _ => return Err(self.usage_error()),
+        };
+        Ok(CommandResult::Value(count.to_string()))
+    }
+}
+
+impl DisplayCommandAsRow for Count {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            ("tap --count", "Print the number of links"),
+            (
+                "tap --count search-engines",
+                "Print the number of links of search-engines",
+            ),
+            (
+                "tap --count --parents",
+                "Print the number of Parent Entities",
+            ),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::CommandErrorKind;
+
+    fn ctx_with_links() -> Context {
+        Context::in_memory(vec![
+            (
+                "search-engines".to_string(),
+                vec![
+                    ("google".to_string(), "https://google.com".to_string()),
+                    ("yahoo".to_string(), "https://yahoo.com".to_string()),
+                ],
+            ),
+            (
+                "work".to_string(),
+                vec![("ci".to_string(), "https://ci.internal".to_string())],
+            ),
+        ])
+    }
+
+    fn count(ctx: &mut Context, args: &[&str]) -> Result<CommandResult, CommandError> {
+        Count::default().run(ctx, args.iter().map(|a| a.to_string()).collect())
+    }
+
+    #[test]
+    fn test_count_run_expected_help_arg() {
+        let cmd = Count::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_count_run_unexpected_args() {
+        let expected: Result<CommandResult, CommandError> = Err(Count::default().usage_error());
+        assert_eq!(count(&mut ctx_with_links(), &["work", "ci"]), expected);
+        assert_eq!(
+            count(&mut ctx_with_links(), &["work", "--parents"]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_count_run() {
+        let mut ctx = ctx_with_links();
+        assert_eq!(
+            count(&mut ctx, &[]),
+            Ok(CommandResult::Value("3".to_string()))
+        );
+        assert_eq!(
+            count(&mut ctx, &["search-engines"]),
+            Ok(CommandResult::Value("2".to_string()))
+        );
+        assert_eq!(
+            count(&mut ctx, &["--parents"]),
+            Ok(CommandResult::Value("2".to_string()))
+        );
+        assert_eq!(
+            count(&mut Context::in_memory(vec![]), &[]),
+            Ok(CommandResult::Value("0".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_count_run_parent_not_found() {
+        let err = count(&mut ctx_with_links(), &["home"]).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::ParentNotFound);
+        assert_eq!(err.kind.exit_code(), 3);
+    }
+}
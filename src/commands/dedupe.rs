@@ -0,0 +1,471 @@
+use crate::{
+    commands::{Command, CommandError, CommandResult, Table, display_examples},
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::command::Args,
+    utils::context::Context,
+    utils::json::Json,
+    utils::style,
+};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+pub(crate) struct Dedupe {
+    name: String,
+    description: String,
+    args: [String; 2],
+}
+
+impl Default for Dedupe {
+    fn default() -> Self {
+        Self {
+            name: "--dedupe".to_string(),
+            description: "Find links that store the same value".to_string(),
+            args: ["[--fix]".to_string(), "[--keep-first]".to_string()],
+        }
+    }
+}
+
+/// The links of every parent entity that store the same value once normalized, see
+/// `normalize_value`, in the order they are stored
+#[derive(Debug, PartialEq)]
+struct Duplicates {
+    value: String,
+    /// Parent entity, link, and value as stored
+    links: Vec<(String, String, String)>,
+}
+
+impl Duplicates {
+    fn json(&self) -> Json {
+        Json::object([
+            ("value", Json::from(self.value.as_str())),
+            (
+                "links",
+                Json::Array(
+                    self.links
+                        .iter()
+                        .map(|(parent, link, value)| {
+                            Json::object([
+                                ("parent", Json::from(parent.as_str())),
+                                ("link", Json::from(link.as_str())),
+                                ("value", Json::from(value.as_str())),
+                            ])
+                        })
+                        .collect(),
+                ),
+            ),
+        ])
+    }
+}
+
+impl Command for Dedupe {
+    fn error_message(&self) -> String {
+        "expected no arguments besides the flags, see the Usage section with tap --dedupe --help"
+            .to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --dedupe command reports the links of every parent entity that store the same value, largest groups first. Values are compared normalized: surrounding whitespace and trailing slashes are ignored, and for URLs the scheme and host are compared in lowercase and default ports like :443 for https are dropped, so https://Example.com:443/docs/ and https://example.com/docs are the same value. Paths and the rest of a URL are compared as they are.\n\n");
+        s.push_str("With --fix, each group is listed and tap asks which link to keep, the others are removed together so tap --undo brings them all back. Pass --keep-first to keep the first link of each group without asking, which is required when not run in a terminal.\n\n");
+        s.push_str("Command Structure: tap --dedupe [--fix [--keep-first]]\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let mut args = Args::new(args);
+        let fix = args.take_flag(&["--fix"]);
+        let keep_first = args.take_flag(&["--keep-first"]);
+        match args.len() {
+            0 if keep_first && !fix => Err(CommandError::usage(
+                "--keep-first can only be used together with --fix".to_string(),
+            )),
+            0 => self.dedupe(ctx, fix, keep_first),
+            1 if args.is_flag(0, "--help") => Ok(CommandResult::Value(self.help_message())),
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+impl Dedupe {
+    /// Reports the duplicates, removing all but one link of each when `fix` is set
+    fn dedupe(
+        &self,
+        ctx: &Context,
+        fix: bool,
+        keep_first: bool,
+    ) -> Result<CommandResult, CommandError> {
+        let links = ctx.store.all_links()?;
+        let duplicates = find_duplicates(&links);
+        if duplicates.is_empty() {
+            let count = links.iter().map(|(_, links)| links.len()).sum::<usize>();
+            return Ok(CommandResult::Value(format!(
+                "No duplicate values found among {count} link(s)"
+            )));
+        }
+        if !fix {
+            if ctx.format.is_structured() {
+                return Ok(CommandResult::Json(Json::Array(
+                    duplicates.iter().map(Duplicates::json).collect(),
+                )));
+            }
+            return Ok(CommandResult::Table(report(&duplicates)));
+        }
+        if !keep_first && !ctx.prompt.is_interactive() {
+            return Err(CommandError::usage(format!(
+                "Found {} duplicate value(s), pass --keep-first to keep the first link of each without asking",
+                duplicates.len()
+            )));
+        }
+        let mut removed = vec![];
+        for group in &duplicates {
+            let keep = match keep_first {
+                true => 0,
+                false => {
+                    let mut options: Vec<String> = group
+                        .links
+                        .iter()
+                        .map(|(parent, link, value)| format!("{parent}/{link}  {value}"))
+                        .collect();
+                    options.push("Keep them all".to_string());
+                    ctx.prompt
+                        .choose(&format!("Keep which link of {}?", group.value), &options)?
+                }
+            };
+            if keep < group.links.len() {
+                removed.extend(
+                    group
+                        .links
+                        .iter()
+                        .enumerate()
+                        .filter(|&(i, _)| i != keep)
+                        .map(|(_, (parent, link, _))| (parent, link)),
+                );
+            }
+        }
+        if removed.is_empty() {
+            return Ok(CommandResult::Value("Nothing was deleted".to_string()));
+        }
+        ctx.store.write(|ds| {
+            ds.batch(|tx| {
+                removed
+                    .iter()
+                    .try_for_each(|(parent, link)| tx.delete(parent, Some(link)))
+            })
+        })?;
+        Ok(CommandResult::Value(style::success(&format!(
+            "Removed {} duplicate link(s)",
+            removed.len()
+        ))))
+    }
+}
+
+/// The groups of links storing the same normalized value, largest first and otherwise in the
+/// order their first link is stored
+fn find_duplicates(links: &[(String, Vec<(String, String)>)]) -> Vec<Duplicates> {
+    let mut groups: Vec<Duplicates> = vec![];
+    let mut by_value: HashMap<String, usize> = HashMap::new();
+    for (parent, links) in links {
+        for (link, value) in links {
+            let normalized = normalize_value(value);
+            let i = *by_value.entry(normalized.clone()).or_insert_with(|| {
+                groups.push(Duplicates {
+                    value: normalized,
+                    links: vec![],
+                });
+                groups.len() - 1
+            });
+            groups[i]
+                .links
+                .push((parent.clone(), link.clone(), value.clone()));
+        }
+    }
+    groups.retain(|group| group.links.len() > 1);
+    // A stable sort keeps groups of the same size in the order they were found
+    groups.sort_by_key(|group| Reverse(group.links.len()));
+    groups
+}
+
+/// The groups with their value only written on their first row, so they read as groups
+fn report(duplicates: &[Duplicates]) -> Table {
+    Table {
+        title: format!(
+            "Found {} value(s) stored under more than one link:",
+            duplicates.len()
+        ),
+        rows: duplicates
+            .iter()
+            .flat_map(|group| {
+                group
+                    .links
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (parent, link, _))| {
+                        vec![
+                            if i == 0 {
+                                group.value.clone()
+                            } else {
+                                String::new()
+                            },
+                            style::parent(parent),
+                            link.clone(),
+                        ]
+                    })
+            })
+            .collect(),
+    }
+}
+
+/// `value` in the form duplicates are compared in: trimmed and without trailing slashes, and for
+/// URLs (`scheme://...`) with the scheme and host in lowercase and without the default port of the
+/// scheme. The user name, path, query, and fragment of a URL keep their case, as do paths.
+pub(in crate::commands) fn normalize_value(value: &str) -> String {
+    let value = value.trim();
+    let Some((scheme, rest)) = value
+        .split_once("://")
+        .filter(|(scheme, _)| is_scheme(scheme))
+    else {
+        return strip_trailing_slashes(value).to_string();
+    };
+    let scheme = scheme.to_ascii_lowercase();
+    let (authority, tail) = rest.split_at(rest.find(['/', '?', '#']).unwrap_or(rest.len()));
+    let (user, host) = match authority.rsplit_once('@') {
+        Some((user, host)) => (format!("{user}@"), host),
+        None => (String::new(), authority),
+    };
+    let host = host.to_ascii_lowercase();
+    // The colon of an IPv6 host like [::1] is not followed by a port
+    let host = match host.rsplit_once(':') {
+        Some((name, port)) if !port.contains(']') && default_port(&scheme) == Some(port) => name,
+        _ => &host,
+    };
+    let (path, query) = tail.split_at(tail.find(['?', '#']).unwrap_or(tail.len()));
+    format!(
+        "{scheme}://{user}{host}{}{query}",
+        path.trim_end_matches('/')
+    )
+}
+
+/// Whether `scheme` can be the scheme of a URL: a letter followed by letters, digits, `+`, `-`,
+/// and `.`
+fn is_scheme(scheme: &str) -> bool {
+    scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+fn default_port(scheme: &str) -> Option<&'static str> {
+    match scheme {
+        "http" | "ws" => Some("80"),
+        "https" | "wss" => Some("443"),
+        "ftp" => Some("21"),
+        _ => None,
+    }
+}
+
+/// `value` without trailing slashes, unless it is nothing but slashes like the root path
+fn strip_trailing_slashes(value: &str) -> &str {
+    match value.trim_end_matches('/') {
+        "" => value,
+        stripped => stripped,
+    }
+}
+
+impl DisplayCommandAsRow for Dedupe {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            ("tap --dedupe", "Report the links that store the same value"),
+            (
+                "tap --dedupe --fix --keep-first",
+                "Keep only the first link of each value",
+            ),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::CommandErrorKind;
+    use crate::utils::prompt::Prompt;
+
+    fn ctx_with_duplicates() -> Context {
+        Context::in_memory(vec![
+            (
+                "docs".to_string(),
+                vec![
+                    ("rust".to_string(), "https://doc.rust-lang.org/".to_string()),
+                    ("wiki".to_string(), "https://wiki.internal".to_string()),
+                ],
+            ),
+            (
+                "work".to_string(),
+                vec![
+                    ("ci".to_string(), "https://ci.internal".to_string()),
+                    (
+                        "wiki".to_string(),
+                        " HTTPS://Wiki.Internal:443 ".to_string(),
+                    ),
+                    ("std".to_string(), "https://doc.rust-lang.org".to_string()),
+                    ("old-wiki".to_string(), "https://wiki.internal/".to_string()),
+                ],
+            ),
+        ])
+    }
+
+    fn run(ctx: &mut Context, args: &[&str]) -> Result<CommandResult, CommandError> {
+        Dedupe::default().run(ctx, args.iter().map(|a| a.to_string()).collect())
+    }
+
+    #[test]
+    fn test_dedupe_run_expected_help_arg() {
+        let cmd = Dedupe::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_dedupe_run_unexpected_args() {
+        let cmd = Dedupe::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["work".to_string()]),
+            expected
+        );
+        let err = run(&mut Context::default(), &["--keep-first"]).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::Usage);
+    }
+
+    #[test]
+    fn test_normalize_value_urls() {
+        for (value, expected) in [
+            ("  https://example.com/docs/  ", "https://example.com/docs"),
+            ("HTTPS://Example.COM/Docs", "https://example.com/Docs"),
+            ("https://example.com:443/", "https://example.com"),
+            ("http://example.com:80/a", "http://example.com/a"),
+            // Only the default port of the scheme is dropped
+            ("http://example.com:443/a", "http://example.com:443/a"),
+            ("https://example.com:8443", "https://example.com:8443"),
+            // The user name, query, and fragment keep their case and slashes
+            (
+                "https://Me@Example.com/a/?Q=1/",
+                "https://Me@example.com/a?Q=1/",
+            ),
+            ("https://example.com/#/Home/", "https://example.com#/Home/"),
+            ("https://[::1]:443/x", "https://[::1]/x"),
+            ("https://[::1]/x", "https://[::1]/x"),
+            ("file:///home/me/notes/", "file:///home/me/notes"),
+        ] {
+            assert_eq!(normalize_value(value), expected, "{value}");
+        }
+    }
+
+    #[test]
+    fn test_normalize_value_other_values() {
+        for (value, expected) in [
+            ("/home/me/Notes/", "/home/me/Notes"),
+            (" ~/code// ", "~/code"),
+            ("/", "/"),
+            ("mailto:Me@Example.com", "mailto:Me@Example.com"),
+            ("JIRA-42", "JIRA-42"),
+            // Not a scheme, so nothing is lowercased
+            ("1://Example.com", "1://Example.com"),
+        ] {
+            assert_eq!(normalize_value(value), expected, "{value}");
+        }
+    }
+
+    #[test]
+    fn test_dedupe_reports_largest_groups_first() {
+        let mut ctx = ctx_with_duplicates();
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Table(Table {
+            title: "Found 2 value(s) stored under more than one link:".to_string(),
+            rows: vec![
+                vec![
+                    "https://wiki.internal".to_string(),
+                    style::parent("docs"),
+                    "wiki".to_string(),
+                ],
+                vec![String::new(), style::parent("work"), "old-wiki".to_string()],
+                vec![String::new(), style::parent("work"), "wiki".to_string()],
+                vec![
+                    "https://doc.rust-lang.org".to_string(),
+                    style::parent("docs"),
+                    "rust".to_string(),
+                ],
+                vec![String::new(), style::parent("work"), "std".to_string()],
+            ],
+        }));
+        assert_eq!(run(&mut ctx, &[]), expected);
+        assert_eq!(ctx.store.saves(), 0);
+    }
+
+    #[test]
+    fn test_dedupe_nothing_to_report() {
+        let mut ctx = Context::in_memory(vec![(
+            "work".to_string(),
+            vec![("ci".to_string(), "https://ci.internal".to_string())],
+        )]);
+        assert_eq!(
+            run(&mut ctx, &["--fix"]),
+            Ok(CommandResult::Value(
+                "No duplicate values found among 1 link(s)".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_dedupe_fix_keep_first() {
+        let mut ctx = ctx_with_duplicates();
+        assert_eq!(
+            run(&mut ctx, &["--fix", "--keep-first"]),
+            Ok(CommandResult::Value(style::success(
+                "Removed 3 duplicate link(s)"
+            )))
+        );
+        assert_eq!(ctx.store.links("docs").unwrap(), vec!["rust", "wiki"]);
+        assert_eq!(ctx.store.links("work").unwrap(), vec!["ci"]);
+        // Everything is removed in one write
+        assert_eq!(ctx.store.saves(), 1);
+    }
+
+    #[test]
+    fn test_dedupe_fix_asks_which_link_to_keep() {
+        let mut ctx = ctx_with_duplicates();
+        // Keeps work/wiki, then every link of the rust docs
+        ctx.prompt = Prompt::scripted("3\n3\n");
+        assert_eq!(
+            run(&mut ctx, &["--fix"]),
+            Ok(CommandResult::Value(style::success(
+                "Removed 2 duplicate link(s)"
+            )))
+        );
+        assert_eq!(ctx.store.links("docs").unwrap(), vec!["rust"]);
+        assert_eq!(ctx.store.links("work").unwrap(), vec!["ci", "std", "wiki"]);
+        // Scripts must pass --keep-first
+        let mut ctx = ctx_with_duplicates();
+        let err = run(&mut ctx, &["--fix"]).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::Usage);
+        assert_eq!(ctx.store.saves(), 0);
+    }
+}
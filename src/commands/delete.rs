@@ -1,14 +1,17 @@
 use crate::{
-    commands::{Command, CommandResult},
+    commands::{Command, CommandError, CommandErrorKind, CommandResult, display_examples},
     utils::cli_usage_table::DisplayCommandAsRow,
-    utils::command::get_current_directory_name,
-    utils::tap_data_store::DataStore,
+    utils::command::{Args, END_OF_OPTIONS},
+    utils::context::Context,
+    utils::glob::Glob,
+    utils::style,
+    utils::tap_data_store::Batch,
 };
 
 pub(crate) struct Delete {
     name: String,
     description: String,
-    args: [String; 2],
+    args: [String; 6],
 }
 
 impl Default for Delete {
@@ -16,75 +19,267 @@ impl Default for Delete {
         Self {
             name: "-d, --delete".to_string(),
             description: "Deletes a link".to_string(),
-            args: ["<Parent|here>".to_string(), "[Link]".to_string()],
+            args: [
+                "<Parent|here>".to_string(),
+                "[Link...]".to_string(),
+                "[--yes]".to_string(),
+                "[--purge]".to_string(),
+                "[--glob <Pattern>]".to_string(),
+                "[--dry-run]".to_string(),
+            ],
         }
     }
 }
 
 impl Command for Delete {
     fn error_message(&self) -> String {
-        "expected 1-2 arguments, see the Usage section with tap --delete --help".to_string()
+        "expected 1 or more arguments, see the Usage section with tap --delete --help".to_string()
     }
 
     fn help_message(&self) -> String {
         let mut s = String::new();
-        s.push_str("Tap --delete command will delete either a specific link or all links of a Parent Entity\n\n");
-        s.push_str("Command Structure: tap --delete <Parent Entity | here> [Link Name]\n");
-        s.push_str("Example Usage: \n\n");
-        s.push_str("  - Delete all links: tap --delete search-engines\n");
-        s.push_str("  - Delete specific link: tap --delete search-engines google\n");
-        s.push_str("  - Delete all links associated to parent entity sharing name of current directory: tap --delete here\n");
+        s.push_str("Tap --delete command will delete either specific links or all links of a Parent Entity\n\n");
+        s.push_str(
+            "Command Structure: tap --delete <Parent Entity | here> [Link Name...] [--glob <Pattern>] [-y | --yes] [--purge] [--dry-run]\n",
+        );
+        s.push_str("Pass --glob with a pattern instead of link names to delete the links whose names match it, where * stands for any characters, ? for one, and [...] for one of a class like [0-9]. The matching links are listed and confirmed first, pass --yes to skip it\n");
+        s.push_str("Pass --dry-run to see what would be removed without changing any file. Parent Entities and links are looked up as usual and nothing is confirmed, the output is the one of a real delete prefixed with DRY RUN:\n");
+        s.push_str("Several links of a Parent Entity are removed at once, links that do not exist are listed and skipped. It fails only when none of them exist\n");
+        s.push_str("Deleted links are kept in the trash for a while, see tap --trash, and tap --restore-trash puts them back. Pass --purge to delete them for good instead\n");
+        s.push_str("Deleting all links of a Parent Entity asks for confirmation, pass --yes to skip it (required when not run in a terminal)\n");
+        s.push_str("Without arguments, the Parent Entity and Link are picked from numbered lists when run in a terminal, unless --no-input is passed\n");
+        s.push_str(&display_examples(&self.examples()));
         s
     }
 
-    fn run(&self, args: Vec<String>) -> Result<CommandResult, String> {
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let mut args = Args::new(args);
+        let yes = args.take_flag(&["-y", "--yes"]);
+        let purge = args.take_flag(&["--purge"]);
+        let glob = args.take_option("--glob");
+        let dry_run = args.take_flag(&["--dry-run"]);
+        // Everything is removed in one batch, so the data file is written once. A dry run goes
+        // through the same batch and then rolls it back.
+        let delete = |parent_entity: &str, links: &[Option<&str>]| {
+            let remove = |tx: &mut Batch| {
+                links.iter().try_for_each(|&link| match purge {
+                    true => tx.purge(parent_entity, link),
+                    false => tx.delete(parent_entity, link),
+                })
+            };
+            match dry_run {
+                true => ctx.store.dry_run(remove),
+                false => ctx.store.write(|ds| ds.batch(remove)),
+            }
+        };
+        let done = |message: String| match dry_run {
+            true => format!("DRY RUN: {message}"),
+            false => style::success(&message),
+        };
+        if let Some(pattern) = glob {
+            if args.len() != 1 {
+                return Err(self.usage_error());
+            }
+            let parent_entity = args.parent(0)?;
+            let Some(links) = self.links_to_delete(ctx, &parent_entity, &pattern, yes, dry_run)?
+            else {
+                return Ok(CommandResult::Value("Nothing was deleted".to_string()));
+            };
+            let removed: Vec<Option<&str>> = links.iter().map(|link| Some(link.as_str())).collect();
+            delete(&parent_entity, &removed)?;
+            return Ok(CommandResult::Value(done(removed_message(
+                &parent_entity,
+                &links,
+            ))));
+        }
         match args.len() {
-            1 => match args[0].as_str() {
-                "--help" => Ok(CommandResult::Value(self.help_message())),
-                "here" => {
-                    let mut ds = DataStore::new(None).map_err(|e| e.to_string())?;
-                    let current_dir_name =
-                        get_current_directory_name().map_err(|e| e.to_string())?;
-                    ds.delete(current_dir_name.to_string(), None)
-                        .map_err(|e| e.to_string())?;
-                    Ok(CommandResult::Value(format!(
-                        "Successfully removed all links of parent '{current_dir_name}'"
-                    )))
-                }
-                parent_entity => {
-                    let mut ds = DataStore::new(None).map_err(|e| e.to_string())?;
-                    ds.delete(parent_entity.to_string(), None)
-                        .map_err(|e| e.to_string())?;
-                    Ok(CommandResult::Value(format!(
-                        "Successfully removed all links of parent '{parent_entity}'"
-                    )))
+            0 if ctx.prompt.is_interactive() => match self.ask_what_to_delete(ctx)? {
+                Some(mut args) => {
+                    if purge {
+                        args.insert(0, "--purge".to_string());
+                    }
+                    if dry_run {
+                        args.insert(0, "--dry-run".to_string());
+                    }
+                    self.run(ctx, args)
                 }
+                None => Ok(CommandResult::Value("Nothing was deleted".to_string())),
             },
-            2 => match (args[0].as_str(), args[1].as_str()) {
-                ("here", link_name) => {
-                    let mut ds = DataStore::new(None).map_err(|e| e.to_string())?;
-                    let current_dir_name =
-                        get_current_directory_name().map_err(|e| e.to_string())?;
-                    ds.delete(current_dir_name.to_string(), Some(link_name.to_string()))
-                        .map_err(|e| e.to_string())?;
-                    Ok(CommandResult::Value(format!(
-                        "Successfully removed link '{link_name}' from parent '{current_dir_name}'"
-                    )))
+            1 if args.is_flag(0, "--help") => Ok(CommandResult::Value(self.help_message())),
+            1 => {
+                let parent_entity = args.parent(0)?;
+                if !yes && !dry_run && !self.confirm_delete_parent(ctx, &parent_entity)? {
+                    return Ok(CommandResult::Value("Nothing was deleted".to_string()));
                 }
-                (parent_entity, link_name) => {
-                    let mut ds = DataStore::new(None).map_err(|e| e.to_string())?;
-                    ds.delete(parent_entity.to_string(), Some(link_name.to_string()))
-                        .map_err(|e| e.to_string())?;
-                    Ok(CommandResult::Value(format!(
-                        "Successfully removed link '{link_name}' from parent '{parent_entity}'"
-                    )))
+                delete(&parent_entity, &[None])
+                    .map_err(|e| ctx.store.with_suggestions(e, &parent_entity, None))?;
+                Ok(CommandResult::Value(done(format!(
+                    "Successfully removed all links of parent '{parent_entity}'"
+                ))))
+            }
+            2 => {
+                let parent_entity = args.parent(0)?;
+                let link_name = args[1].as_str();
+                delete(&parent_entity, &[Some(link_name)]).map_err(|e| {
+                    ctx.store
+                        .with_suggestions(e, &parent_entity, Some(link_name))
+                })?;
+                Ok(CommandResult::Value(done(format!(
+                    "Successfully removed link '{link_name}' from parent '{parent_entity}'"
+                ))))
+            }
+            n if n > 2 => {
+                let parent_entity = args.parent(0)?;
+                let existing = ctx
+                    .store
+                    .links(&parent_entity)
+                    .map_err(|e| ctx.store.with_suggestions(e, &parent_entity, None))?;
+                let mut found: Vec<&str> = Vec::new();
+                let mut missing: Vec<&str> = Vec::new();
+                for link in args[1..].iter().map(String::as_str) {
+                    if found.contains(&link) || missing.contains(&link) {
+                        continue;
+                    }
+                    match existing.iter().any(|l| l == link) {
+                        true => found.push(link),
+                        false => missing.push(link),
+                    }
                 }
-            },
-            _ => Err(self.error_message()),
+                if found.is_empty() {
+                    // Fails with the suggestions for the first name, as none of them exist
+                    ctx.store.read_link(&parent_entity, missing[0])?;
+                }
+                let links: Vec<Option<&str>> = found.iter().map(|&link| Some(link)).collect();
+                delete(&parent_entity, &links)?;
+                let mut s = done(removed_message(&parent_entity, &found));
+                if !missing.is_empty() {
+                    s.push_str(&format!(
+                        "\nNot found in parent '{parent_entity}': {}",
+                        missing.join(", ")
+                    ));
+                }
+                Ok(CommandResult::Value(s))
+            }
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+impl Delete {
+    /// The links of the parent entity whose names match the glob `pattern`, see `Glob`.
+    /// They are listed and confirmed first, which `yes` and `dry_run` skip. Returns `None` if
+    /// the user does not confirm.
+    fn links_to_delete(
+        &self,
+        ctx: &Context,
+        parent_entity: &str,
+        pattern: &str,
+        yes: bool,
+        dry_run: bool,
+    ) -> Result<Option<Vec<String>>, CommandError> {
+        let glob = Glob::new(pattern).map_err(|e| CommandError::usage(e.to_string()))?;
+        let links: Vec<String> = ctx
+            .store
+            .links(parent_entity)
+            .map_err(|e| ctx.store.with_suggestions(e, parent_entity, None))?
+            .into_iter()
+            .filter(|link| glob.matches(link))
+            .collect();
+        if links.is_empty() {
+            return Err(CommandError {
+                kind: CommandErrorKind::LinkNotFound,
+                message: format!("No links of parent '{parent_entity}' match '{pattern}'"),
+            });
+        }
+        if yes || dry_run {
+            return Ok(Some(links));
+        }
+        if !ctx.prompt.is_interactive() {
+            return Err(CommandError::usage(format!(
+                "'{pattern}' matches {} link(s) of parent entity {parent_entity}, pass --yes to delete them without confirmation",
+                links.len()
+            )));
+        }
+        let question = format!(
+            "Delete {} link(s) of parent entity {parent_entity}: {}?",
+            links.len(),
+            links.join(", ")
+        );
+        Ok(ctx.prompt.confirm(&question)?.then_some(links))
+    }
+
+    /// Lets the user pick a parent entity and then one of its links (or all of them) from
+    /// numbered lists, and confirms before deleting. Returns `None` if the user does not confirm.
+    fn ask_what_to_delete(&self, ctx: &Context) -> Result<Option<Vec<String>>, CommandError> {
+        let parents = ctx.store.parents()?;
+        if parents.is_empty() {
+            return Err("There are no parent entities to delete from"
+                .to_string()
+                .into());
+        }
+        let parent_entity = parents[ctx.prompt.choose("Parent entity", &parents)?].clone();
+        let mut options = ctx.store.links(&parent_entity)?;
+        options.push(format!("All links of {parent_entity}"));
+        let choice = ctx.prompt.choose("Link to delete", &options)?;
+        // The chosen names are passed after `--`, so they are never taken as `here` or a flag
+        let (args, question) = if choice == options.len() - 1 {
+            (
+                // Confirmed below, so deleting the parent entity does not ask again
+                vec![
+                    "--yes".to_string(),
+                    END_OF_OPTIONS.to_string(),
+                    parent_entity.clone(),
+                ],
+                format!(
+                    "Delete parent entity {parent_entity} and its {} link(s)?",
+                    options.len() - 1
+                ),
+            )
+        } else {
+            let link_name = options[choice].clone();
+            (
+                vec![
+                    END_OF_OPTIONS.to_string(),
+                    parent_entity.clone(),
+                    link_name.clone(),
+                ],
+                format!("Delete link {link_name} from parent entity {parent_entity}?"),
+            )
+        };
+        Ok(ctx.prompt.confirm(&question)?.then_some(args))
+    }
+
+    /// Deleting a whole parent entity needs confirmation, which is asked for in a terminal.
+    /// Otherwise it fails, as `--yes` must be passed to delete without asking.
+    fn confirm_delete_parent(
+        &self,
+        ctx: &Context,
+        parent_entity: &str,
+    ) -> Result<bool, CommandError> {
+        let links = ctx.store.links(parent_entity)?;
+        if !ctx.prompt.is_interactive() {
+            return Err(CommandError::usage(format!(
+                "Deleting parent entity {parent_entity} removes its {} link(s), pass --yes to delete it without confirmation",
+                links.len()
+            )));
         }
+        Ok(ctx.prompt.confirm(&format!(
+            "Delete parent entity {parent_entity} and its {} link(s)?",
+            links.len()
+        ))?)
     }
 }
 
+/// `Successfully removed 2 links from parent 'work': jira, ci`
+fn removed_message(parent_entity: &str, links: &[impl AsRef<str>]) -> String {
+    let names: Vec<&str> = links.iter().map(AsRef::as_ref).collect();
+    format!(
+        "Successfully removed {} links from parent '{parent_entity}': {}",
+        names.len(),
+        names.join(", ")
+    )
+}
+
 impl DisplayCommandAsRow for Delete {
     fn args(&self) -> Vec<String> {
         self.args.to_vec()
@@ -94,6 +289,36 @@ impl DisplayCommandAsRow for Delete {
         self.description.clone()
     }
 
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            ("tap --delete search-engines", "Delete all links"),
+            (
+                "tap --delete search-engines --yes",
+                "Delete all links without confirmation",
+            ),
+            ("tap --delete search-engines google", "Delete specific link"),
+            (
+                "tap --delete work jira ci old-dashboard",
+                "Delete several links at once",
+            ),
+            (
+                "tap --delete work --glob 'imported-*' --dry-run",
+                "List the links whose names start with imported-",
+            ),
+            (
+                "tap --delete search-engines --dry-run",
+                "Show what deleting all links would remove",
+            ),
+            (
+                "tap --delete here",
+                "Delete all links associated to parent entity sharing name of current directory",
+            ),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
     fn name(&self) -> String {
         self.name.clone()
     }
@@ -102,82 +327,425 @@ impl DisplayCommandAsRow for Delete {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::command::get_current_directory_name;
+    use crate::utils::prompt::Prompt;
+    use crate::utils::tap_data_store::{TestDataFile, index_path_for};
+    use crate::utils::trash::trash_path_for;
+    use std::path::PathBuf;
+
+    fn ctx_with_links(parent: &str) -> Context {
+        Context::in_memory(vec![(
+            parent.to_string(),
+            vec![
+                ("google".to_string(), "https://google.com".to_string()),
+                ("yahoo".to_string(), "https://yahoo.com".to_string()),
+            ],
+        )])
+    }
 
     #[test]
     fn test_delete_run_expected_help_arg() {
         let args: Vec<String> = vec!["--help".to_string()];
         let cmd = Delete::default();
-        let expected: Result<CommandResult, String> = Ok(CommandResult::Value(cmd.help_message()));
-        let res = cmd.run(args);
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        let res = cmd.run(&mut Context::default(), args);
         assert_eq!(res, expected);
     }
 
     #[test]
     fn test_delete_run_unexpected_args() {
-        let args: Vec<String> = vec![
-            "random".to_string(),
-            "random2".to_string(),
-            "random3".to_string(),
-        ];
+        let args: Vec<String> = vec!["--yes".to_string()];
         let cmd = Delete::default();
-        let expected: Result<CommandResult, String> = Err(cmd.error_message());
-        let res = cmd.run(args);
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        let res = cmd.run(&mut Context::default(), args);
         assert_eq!(res, expected);
     }
 
-    // TODO: GH-45 Move the following to an integration test
     #[test]
-    #[ignore = "GH-45: Should be an integration test due to DataStore dependency"]
     fn test_delete_run_expected_here_arg() {
-        let args: Vec<String> = vec!["here".to_string()];
-        let current_dir = std::env::current_dir().unwrap();
-        let current_dir_name = current_dir.file_name().unwrap().to_str().unwrap();
+        let args: Vec<String> = vec!["here".to_string(), "--yes".to_string()];
+        let current_dir_name = get_current_directory_name().unwrap();
         let cmd = Delete::default();
-        let expected: Result<CommandResult, String> = Ok(CommandResult::Value(format!(
+        let mut ctx = ctx_with_links(&current_dir_name);
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Value(format!(
             "Successfully removed all links of parent '{current_dir_name}'"
         )));
-        let res = cmd.run(args);
+        let res = cmd.run(&mut ctx, args);
         assert_eq!(res, expected);
+        assert!(ctx.store.parents().unwrap().is_empty());
     }
 
-    // TODO: GH-45 Move the following to an integration test
     #[test]
-    #[ignore = "GH-45: Should be an integration test due to DataStore dependency"]
     fn test_delete_run_expected_here_and_link_args() {
         let args: Vec<String> = vec!["here".to_string(), "google".to_string()];
-        let current_dir = std::env::current_dir().unwrap();
-        let current_dir_name = current_dir.file_name().unwrap().to_str().unwrap();
+        let current_dir_name = get_current_directory_name().unwrap();
         let cmd = Delete::default();
-        let expected: Result<CommandResult, String> = Ok(CommandResult::Value(format!(
+        let mut ctx = ctx_with_links(&current_dir_name);
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Value(format!(
             "Successfully removed link 'google' from parent '{current_dir_name}'"
         )));
-        let res = cmd.run(args);
+        let res = cmd.run(&mut ctx, args);
         assert_eq!(res, expected);
+        assert_eq!(
+            ctx.store.links(&current_dir_name).unwrap(),
+            vec!["yahoo".to_string()]
+        );
     }
 
-    // TODO: GH-45 Move the following to an integration test
     #[test]
-    #[ignore = "GH-45: Should be an integration test due to DataStore dependency"]
     fn test_delete_run_expected_parent_entity_arg() {
-        let args: Vec<String> = vec!["search-engines".to_string()];
+        let args: Vec<String> = vec!["search-engines".to_string(), "-y".to_string()];
         let cmd = Delete::default();
-        let expected: Result<CommandResult, String> = Ok(CommandResult::Value(
+        let mut ctx = ctx_with_links("search-engines");
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Value(
             "Successfully removed all links of parent 'search-engines'".to_string(),
         ));
-        let res = cmd.run(args);
+        let res = cmd.run(&mut ctx, args);
         assert_eq!(res, expected);
+        assert!(ctx.store.parents().unwrap().is_empty());
     }
 
-    // TODO: GH-45 Move the following to an integration test
     #[test]
-    #[ignore = "GH-45: Should be an integration test due to DataStore dependency"]
     fn test_delete_run_expected_parent_entity_and_link_args() {
         let args: Vec<String> = vec!["search-engines".to_string(), "google".to_string()];
         let cmd = Delete::default();
-        let expected: Result<CommandResult, String> = Ok(CommandResult::Value(
+        let mut ctx = ctx_with_links("search-engines");
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Value(
             "Successfully removed link 'google' from parent 'search-engines'".to_string(),
         ));
-        let res = cmd.run(args);
+        let res = cmd.run(&mut ctx, args);
         assert_eq!(res, expected);
+        assert_eq!(
+            ctx.store.links("search-engines").unwrap(),
+            vec!["yahoo".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_delete_run_parent_entity_not_found() {
+        let args: Vec<String> = vec!["bing".to_string()];
+        let cmd = Delete::default();
+        let mut ctx = ctx_with_links("search-engines");
+        assert!(cmd.run(&mut ctx, args).is_err());
+        assert_eq!(
+            ctx.store.parents().unwrap(),
+            vec!["search-engines".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_delete_run_prompts_for_link() {
+        let cmd = Delete::default();
+        let mut ctx = Context {
+            prompt: Prompt::scripted("1\n2\ny\n"),
+            ..ctx_with_links("search-engines")
+        };
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Value(
+            "Successfully removed link 'yahoo' from parent 'search-engines'".to_string(),
+        ));
+        assert_eq!(cmd.run(&mut ctx, vec![]), expected);
+        assert_eq!(
+            ctx.store.links("search-engines").unwrap(),
+            vec!["google".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_delete_run_prompts_for_all_links() {
+        let cmd = Delete::default();
+        let mut ctx = Context {
+            prompt: Prompt::scripted("1\n3\ny\n"),
+            ..ctx_with_links("search-engines")
+        };
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Value(
+            "Successfully removed all links of parent 'search-engines'".to_string(),
+        ));
+        assert_eq!(cmd.run(&mut ctx, vec![]), expected);
+    }
+
+    #[test]
+    fn test_delete_run_prompt_not_confirmed() {
+        let cmd = Delete::default();
+        let mut ctx = Context {
+            prompt: Prompt::scripted("1\n1\n\n"),
+            ..ctx_with_links("search-engines")
+        };
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value("Nothing was deleted".to_string()));
+        assert_eq!(cmd.run(&mut ctx, vec![]), expected);
+        assert_eq!(ctx.store.links("search-engines").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_delete_run_no_args_not_interactive() {
+        let cmd = Delete::default();
+        let mut ctx = ctx_with_links("search-engines");
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(cmd.run(&mut ctx, vec![]), expected);
+    }
+
+    #[test]
+    fn test_delete_run_parent_entity_needs_yes() {
+        let args: Vec<String> = vec!["search-engines".to_string()];
+        let cmd = Delete::default();
+        let mut ctx = ctx_with_links("search-engines");
+        let expected: Result<CommandResult, CommandError> = Err(CommandError::usage(
+            "Deleting parent entity search-engines removes its 2 link(s), pass --yes to delete it without confirmation".to_string(),
+        ));
+        assert_eq!(cmd.run(&mut ctx, args), expected);
+        assert_eq!(ctx.store.links("search-engines").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_delete_run_parent_entity_confirmed() {
+        let args: Vec<String> = vec!["search-engines".to_string()];
+        let cmd = Delete::default();
+        let mut ctx = Context {
+            prompt: Prompt::scripted("y\n"),
+            ..ctx_with_links("search-engines")
+        };
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Value(
+            "Successfully removed all links of parent 'search-engines'".to_string(),
+        ));
+        assert_eq!(cmd.run(&mut ctx, args), expected);
+        assert!(ctx.store.parents().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_delete_run_parent_entity_not_confirmed() {
+        let args: Vec<String> = vec!["search-engines".to_string()];
+        let cmd = Delete::default();
+        let mut ctx = Context {
+            prompt: Prompt::scripted("n\n"),
+            ..ctx_with_links("search-engines")
+        };
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value("Nothing was deleted".to_string()));
+        assert_eq!(cmd.run(&mut ctx, args), expected);
+        assert_eq!(ctx.store.links("search-engines").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_delete_run_link_needs_no_confirmation() {
+        let args: Vec<String> = vec!["search-engines".to_string(), "google".to_string()];
+        let cmd = Delete::default();
+        let mut ctx = Context {
+            prompt: Prompt::scripted(""),
+            ..ctx_with_links("search-engines")
+        };
+        assert!(cmd.run(&mut ctx, args).is_ok());
+    }
+
+    #[test]
+    fn test_delete_run_link_not_found_suggests_links() {
+        let args: Vec<String> = vec!["search-engines".to_string(), "yahooo".to_string()];
+        let cmd = Delete::default();
+        let mut ctx = ctx_with_links("search-engines");
+        let err = cmd.run(&mut ctx, args).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::LinkNotFound);
+        assert!(err.message.contains("Did you mean: yahoo?"));
+    }
+
+    #[test]
+    fn test_delete_run_end_of_options_literal_here() {
+        let args: Vec<String> = vec!["--yes".to_string(), "--".to_string(), "here".to_string()];
+        let cmd = Delete::default();
+        let mut ctx = ctx_with_links("here");
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Value(
+            "Successfully removed all links of parent 'here'".to_string(),
+        ));
+        assert_eq!(cmd.run(&mut ctx, args), expected);
+        assert!(ctx.store.parents().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_delete_run_several_links() {
+        let args: Vec<String> = vec![
+            "search-engines".to_string(),
+            "google".to_string(),
+            "bing".to_string(),
+            "yahoo".to_string(),
+        ];
+        let cmd = Delete::default();
+        let mut ctx = ctx_with_links("search-engines");
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Value(
+            "Successfully removed 2 links from parent 'search-engines': google, yahoo\nNot found in parent 'search-engines': bing".to_string(),
+        ));
+        assert_eq!(cmd.run(&mut ctx, args), expected);
+        assert!(ctx.store.parents().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_delete_run_several_links_none_found() {
+        let args: Vec<String> = vec![
+            "search-engines".to_string(),
+            "yahooo".to_string(),
+            "bing".to_string(),
+        ];
+        let cmd = Delete::default();
+        let mut ctx = ctx_with_links("search-engines");
+        let err = cmd.run(&mut ctx, args).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::LinkNotFound);
+        assert!(err.message.contains("Did you mean: yahoo?"));
+        assert_eq!(ctx.store.links("search-engines").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_delete_run_glob() {
+        let args: Vec<String> = vec![
+            "work".to_string(),
+            "--glob".to_string(),
+            "imported-?*".to_string(),
+            "--yes".to_string(),
+        ];
+        let cmd = Delete::default();
+        let mut ctx = Context::in_memory(vec![(
+            "work".to_string(),
+            vec![
+                ("imported-1".to_string(), "https://a.com".to_string()),
+                ("ci".to_string(), "https://ci.internal".to_string()),
+                ("imported-22".to_string(), "https://b.com".to_string()),
+                ("imported-".to_string(), "https://c.com".to_string()),
+            ],
+        )]);
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Value(
+            "Successfully removed 2 links from parent 'work': imported-1, imported-22".to_string(),
+        ));
+        assert_eq!(cmd.run(&mut ctx, args), expected);
+        assert_eq!(
+            ctx.store.links("work").unwrap(),
+            vec!["ci".to_string(), "imported-".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_delete_run_glob_dry_run() {
+        let args: Vec<String> = vec![
+            "search-engines".to_string(),
+            "--glob".to_string(),
+            "*oo*".to_string(),
+            "--dry-run".to_string(),
+        ];
+        let cmd = Delete::default();
+        let mut ctx = ctx_with_links("search-engines");
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Value(
+            "DRY RUN: Successfully removed 2 links from parent 'search-engines': google, yahoo"
+                .to_string(),
+        ));
+        assert_eq!(cmd.run(&mut ctx, args), expected);
+        assert_eq!(ctx.store.links("search-engines").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_delete_run_glob_needs_confirmation() {
+        let args = || -> Vec<String> {
+            vec![
+                "search-engines".to_string(),
+                "--glob".to_string(),
+                "g*".to_string(),
+            ]
+        };
+        let cmd = Delete::default();
+        let mut ctx = ctx_with_links("search-engines");
+        let err = cmd.run(&mut ctx, args()).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::Usage);
+        let mut ctx = Context {
+            prompt: Prompt::scripted("n\n"),
+            ..ctx_with_links("search-engines")
+        };
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value("Nothing was deleted".to_string()));
+        assert_eq!(cmd.run(&mut ctx, args()), expected);
+        assert_eq!(ctx.store.links("search-engines").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_delete_run_glob_no_matches() {
+        let args: Vec<String> = vec![
+            "search-engines".to_string(),
+            "--glob".to_string(),
+            "bing*".to_string(),
+            "--yes".to_string(),
+        ];
+        let cmd = Delete::default();
+        let mut ctx = ctx_with_links("search-engines");
+        let expected: Result<CommandResult, CommandError> = Err(CommandError {
+            kind: CommandErrorKind::LinkNotFound,
+            message: "No links of parent 'search-engines' match 'bing*'".to_string(),
+        });
+        assert_eq!(cmd.run(&mut ctx, args), expected);
+    }
+
+    #[test]
+    fn test_delete_run_glob_invalid_pattern() {
+        let args: Vec<String> = vec![
+            "search-engines".to_string(),
+            "--glob".to_string(),
+            "[".to_string(),
+            "--yes".to_string(),
+        ];
+        let cmd = Delete::default();
+        let mut ctx = ctx_with_links("search-engines");
+        let expected: Result<CommandResult, CommandError> = Err(CommandError::usage(
+            "Invalid pattern '[', a [ is not closed, write [[] for a literal [".to_string(),
+        ));
+        assert_eq!(cmd.run(&mut ctx, args), expected);
+        assert_eq!(ctx.store.links("search-engines").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_delete_run_dry_run() {
+        let cmd = Delete::default();
+        let mut ctx = ctx_with_links("search-engines");
+        let run = |ctx: &mut Context, args: &[&str]| {
+            let args = args.iter().map(|a| a.to_string()).collect();
+            cmd.run(ctx, args)
+        };
+        assert_eq!(
+            run(&mut ctx, &["search-engines", "--dry-run"]),
+            Ok(CommandResult::Value(
+                "DRY RUN: Successfully removed all links of parent 'search-engines'".to_string()
+            ))
+        );
+        assert_eq!(
+            run(&mut ctx, &["--dry-run", "search-engines", "google"]),
+            Ok(CommandResult::Value(
+                "DRY RUN: Successfully removed link 'google' from parent 'search-engines'"
+                    .to_string()
+            ))
+        );
+        let err = run(&mut ctx, &["search-engines", "yahooo", "--dry-run"]).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::LinkNotFound);
+        assert_eq!(ctx.store.links("search-engines").unwrap().len(), 2);
+        assert_eq!(ctx.store.saves(), 0);
+    }
+
+    #[test]
+    fn test_delete_run_dry_run_leaves_files_untouched() {
+        let data =
+            TestDataFile::new("work->\n  ci|https://ci.internal\n  mr|https://mr.internal\n");
+        let index = index_path_for(&data);
+        Context::with_file(data.clone())
+            .store
+            .rebuild_index()
+            .unwrap();
+        let modified = |path: &PathBuf| std::fs::metadata(path).unwrap().modified().unwrap();
+        let before = (modified(&data), modified(&index));
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let args = vec![
+            "work".to_string(),
+            "ci".to_string(),
+            "--dry-run".to_string(),
+        ];
+        assert_eq!(
+            Delete::default().run(&mut Context::with_file(data.clone()), args),
+            Ok(CommandResult::Value(
+                "DRY RUN: Successfully removed link 'ci' from parent 'work'".to_string()
+            ))
+        );
+        assert_eq!((modified(&data), modified(&index)), before);
+        assert!(std::fs::read_to_string(&data).unwrap().contains("ci|"));
+        assert!(!trash_path_for(&data).exists());
     }
 }
@@ -0,0 +1,341 @@
+use crate::{
+    commands::{Command, CommandError, CommandResult, display_examples},
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::command::Args,
+    utils::context::Context,
+    utils::json::Json,
+    utils::tap_data_store::read_tap_file,
+};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+pub(crate) struct Diff {
+    name: String,
+    description: String,
+    args: [String; 1],
+}
+
+impl Default for Diff {
+    fn default() -> Self {
+        Self {
+            name: "--diff".to_string(),
+            description: "Compares the links with a tap file".to_string(),
+            args: ["<tap file>".to_string()],
+        }
+    }
+}
+
+/// A link that differs between the data file and the tap file
+#[derive(Debug, PartialEq)]
+enum Change {
+    /// Only in the data file, with its value
+    OnlyInStore(String),
+    /// Only in the tap file, with its value
+    OnlyInFile(String),
+    /// In both with different values, the one of the data file first
+    Changed(String, String),
+}
+
+impl Change {
+    fn prefix(&self) -> char {
+        match self {
+            Change::OnlyInStore(_) => '-',
+            Change::OnlyInFile(_) => '+',
+            Change::Changed(_, _) => '~',
+        }
+    }
+}
+
+impl Command for Diff {
+    fn error_message(&self) -> String {
+        "expected 1 argument, see the Usage section with tap --diff --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --diff command compares the links of the data file with the links of a tap file, like one exported by a teammate, without changing anything. The file is read the same way tap --import Tap reads it, so this shows what importing it would change. Any file written like a data file can be compared, such as another .tap_data file, whatever its extension.\n\n");
+        s.push_str("One line is printed per link that differs, ordered by Parent Entity and then link name:\n");
+        s.push_str("  - <Parent Entity> <Link> <Value>   (only in the data file)\n");
+        s.push_str("  + <Parent Entity> <Link> <Value>   (only in the tap file)\n");
+        s.push_str(
+            "  ~ <Parent Entity> <Link> <Value in the data file> -> <Value in the tap file>\n\n",
+        );
+        s.push_str("Importing adds the + links and replaces the values of the ~ links, the - links are kept. Pass --format json to print the differences as JSON.\n\n");
+        s.push_str("Command Structure: tap --diff <tap file>\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let args = Args::new(args);
+        match args.len() {
+            1 if args.is_flag(0, "--help") => Ok(CommandResult::Value(self.help_message())),
+            1 => self.diff(ctx, Path::new(&args[0])),
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+impl Diff {
+    fn diff(&self, ctx: &Context, path: &Path) -> Result<CommandResult, CommandError> {
+        let (file_links, _) = read_tap_file(path)?;
+        let changes = changes(ctx.store.all_links()?, file_links);
+        if ctx.format.is_structured() {
+            return Ok(CommandResult::Json(changes_json(&changes)));
+        }
+        if changes.is_empty() {
+            return Ok(CommandResult::Value(format!(
+                "No differences between the data file and {}",
+                path.display()
+            )));
+        }
+        Ok(CommandResult::List(
+            changes
+                .iter()
+                .map(|((parent, link), change)| {
+                    let value = match change {
+                        Change::OnlyInStore(value) | Change::OnlyInFile(value) => value.clone(),
+                        Change::Changed(store, file) => format!("{store} -> {file}"),
+                    };
+                    format!("{} {parent} {link} {value}", change.prefix())
+                })
+                .collect(),
+        ))
+    }
+}
+
+/// The links that differ between `store` and `file`, by parent entity and link name. A link
+/// written more than once in the file has the last value, the one an import would keep.
+fn changes(
+    store: Vec<(String, Vec<(String, String)>)>,
+    file: Vec<(String, Vec<(String, String)>)>,
+) -> BTreeMap<(String, String), Change> {
+    let by_link = |links: Vec<(String, Vec<(String, String)>)>| -> BTreeMap<_, _> {
+        links
+            .into_iter()
+            .flat_map(|(parent, links)| {
+                links
+                    .into_iter()
+                    .map(move |(link, value)| ((parent.clone(), link), value))
+            })
+            .collect()
+    };
+    let mut store = by_link(store);
+    let mut changes = BTreeMap::new();
+    for (key, file_value) in by_link(file) {
+        match store.remove(&key) {
+            Some(value) if value == file_value => {}
+            Some(value) => {
+                changes.insert(key, Change::Changed(value, file_value));
+            }
+            None => {
+                changes.insert(key, Change::OnlyInFile(file_value));
+            }
+        }
+    }
+    for (key, value) in store {
+        changes.insert(key, Change::OnlyInStore(value));
+    }
+    changes
+}
+
+/// `{"only_in_store":[...],"only_in_file":[...],"changed":[...]}`, each link with its parent,
+/// name, and value, or both values when it changed
+fn changes_json(changes: &BTreeMap<(String, String), Change>) -> Json {
+    let mut only_in_store = vec![];
+    let mut only_in_file = vec![];
+    let mut changed = vec![];
+    for ((parent, link), change) in changes {
+        let (parent, link) = (Json::from(parent.as_str()), Json::from(link.as_str()));
+        match change {
+            Change::OnlyInStore(value) => only_in_store.push(Json::object([
+                ("parent", parent),
+                ("link", link),
+                ("value", Json::from(value.as_str())),
+            ])),
+            Change::OnlyInFile(value) => only_in_file.push(Json::object([
+                ("parent", parent),
+                ("link", link),
+                ("value", Json::from(value.as_str())),
+            ])),
+            Change::Changed(store, file) => changed.push(Json::object([
+                ("parent", parent),
+                ("link", link),
+                ("store_value", Json::from(store.as_str())),
+                ("file_value", Json::from(file.as_str())),
+            ])),
+        }
+    }
+    Json::object([
+        ("only_in_store", Json::Array(only_in_store)),
+        ("only_in_file", Json::Array(only_in_file)),
+        ("changed", Json::Array(changed)),
+    ])
+}
+
+impl DisplayCommandAsRow for Diff {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            (
+                "tap --diff ~/Downloads/team.tap",
+                "Show what importing team.tap would change",
+            ),
+            (
+                "tap --format json --diff ~/Downloads/team.tap",
+                "Show the differences as JSON",
+            ),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::CommandErrorKind;
+    use crate::utils::format::Format;
+    use std::path::PathBuf;
+
+    fn ctx_with_links() -> Context {
+        Context::in_memory(vec![
+            (
+                "search-engines".to_string(),
+                vec![
+                    ("bing".to_string(), "https://bing.com".to_string()),
+                    ("google".to_string(), "https://google.com".to_string()),
+                ],
+            ),
+            (
+                "work".to_string(),
+                vec![
+                    ("ci".to_string(), "https://ci.internal".to_string()),
+                    ("jira".to_string(), "https://old.jira.internal".to_string()),
+                ],
+            ),
+        ])
+    }
+
+    /// A tap file in the temporary directory with `contents`, removed by the caller
+    fn tap_file(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("tap_diff_{name}_{}.tap", std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    const TEAM_FILE: &str = "search-engines->\n  google|https://google.com\nwork->\n  jira|https://jira.internal\n  wiki|https://wiki.internal\n";
+
+    #[test]
+    fn test_diff_run_expected_help_arg() {
+        let cmd = Diff::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_diff_run_unexpected_args() {
+        let cmd = Diff::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(cmd.run(&mut Context::default(), vec![]), expected);
+    }
+
+    #[test]
+    fn test_diff_run() {
+        let path = tap_file("run", TEAM_FILE);
+        let mut ctx = ctx_with_links();
+        let res = Diff::default().run(&mut ctx, vec![path.to_str().unwrap().to_string()]);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            res,
+            Ok(CommandResult::List(vec![
+                "- search-engines bing https://bing.com".to_string(),
+                "- work ci https://ci.internal".to_string(),
+                "~ work jira https://old.jira.internal -> https://jira.internal".to_string(),
+                "+ work wiki https://wiki.internal".to_string(),
+            ]))
+        );
+        assert_eq!(ctx.store.saves(), 0);
+    }
+
+    #[test]
+    fn test_diff_run_json() {
+        let path = tap_file("json", TEAM_FILE);
+        let mut ctx = ctx_with_links();
+        ctx.format = Format::Json;
+        let res = Diff::default().run(&mut ctx, vec![path.to_str().unwrap().to_string()]);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            res.unwrap().render(Format::Json),
+            r#"{"only_in_store":[{"parent":"search-engines","link":"bing","value":"https://bing.com"},{"parent":"work","link":"ci","value":"https://ci.internal"}],"only_in_file":[{"parent":"work","link":"wiki","value":"https://wiki.internal"}],"changed":[{"parent":"work","link":"jira","store_value":"https://old.jira.internal","file_value":"https://jira.internal"}]}"#
+        );
+    }
+
+    #[test]
+    fn test_diff_run_same_links() {
+        let path = tap_file(
+            "same",
+            "work->\n  jira|https://old.jira.internal\n  ci|https://ci.internal\nsearch-engines->\n  google|https://google.com\n  bing|https://bing.com\n",
+        );
+        let res = Diff::default().run(
+            &mut ctx_with_links(),
+            vec![path.to_str().unwrap().to_string()],
+        );
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            res,
+            Ok(CommandResult::Value(format!(
+                "No differences between the data file and {}",
+                path.display()
+            )))
+        );
+    }
+
+    #[test]
+    fn test_diff_run_data_file() {
+        let path = std::env::temp_dir().join(format!(
+            "tap_diff_data_file_{}.tap_data",
+            std::process::id()
+        ));
+        std::fs::write(&path, TEAM_FILE).unwrap();
+        let res = Diff::default().run(
+            &mut ctx_with_links(),
+            vec![path.to_str().unwrap().to_string()],
+        );
+        std::fs::remove_file(&path).unwrap();
+        let Ok(CommandResult::List(lines)) = res else {
+            panic!("expected the differences, got {res:?}");
+        };
+        assert!(lines.contains(&"+ work wiki https://wiki.internal".to_string()));
+    }
+
+    #[test]
+    fn test_diff_run_rejects_what_import_rejects() {
+        let cmd = Diff::default();
+        let res = cmd.run(&mut ctx_with_links(), vec!["/no/such/team.tap".to_string()]);
+        assert!(res.unwrap_err().message.contains("does not exist"));
+        let path = tap_file("invalid", "  google|https://google.com\n");
+        let res = cmd.run(
+            &mut ctx_with_links(),
+            vec![path.to_str().unwrap().to_string()],
+        );
+        std::fs::remove_file(&path).unwrap();
+        assert_ne!(res.unwrap_err().kind, CommandErrorKind::Usage);
+    }
+}
@@ -0,0 +1,318 @@
+use crate::{
+    commands::{Command, CommandError, CommandErrorKind, CommandResult, Table, display_examples},
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::context::Context,
+    utils::json::Json,
+    utils::tap_data_store::{Finding, Repair, Severity},
+};
+
+pub(crate) struct Doctor {
+    name: String,
+    description: String,
+    args: [String; 2],
+}
+
+impl Default for Doctor {
+    fn default() -> Self {
+        Self {
+            name: "--doctor".to_string(),
+            description: "Check the data and index files for problems".to_string(),
+            args: ["[--fix]".to_string(), "[--salvage]".to_string()],
+        }
+    }
+}
+
+impl Command for Doctor {
+    fn error_message(&self) -> String {
+        "expected 0-1 arguments, see the Usage section with tap --doctor --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --doctor command checks the data and index files for problems and reports each one as an error or a warning, with how to fix it. It checks that:\n\n");
+        s.push_str("  - every line of the data file can be parsed\n");
+        s.push_str("  - no parent entity or link appears more than once\n");
+        s.push_str("  - every parent entity has at least one link\n");
+        s.push_str("  - no parent entity or link value contains the separator '|'\n");
+        s.push_str("  - the index file can be parsed, lists every parent entity of the data file, and points at the line of each\n\n");
+        s.push_str("Tap exits with a non-zero code when an error is found, so this is the command to run before filing a bug. With --fix, the problems that are safe to fix are fixed: the index file is rebuilt, parent entities without links are removed, and repeated parent entities and links are merged. Everything else has to be fixed by hand, e.g. with tap --edit.\n\n");
+        s.push_str("With --salvage, every line that can not be parsed is skipped and written to .tap_data.rejected so the remaining links can be used again.\n\n");
+        s.push_str("Command Structure: tap --doctor [--fix | --salvage]\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        match args.len() {
+            0 => self.report(ctx, ctx.store.diagnose()?, 0),
+            1 => match args[0].as_str() {
+                "--help" => Ok(CommandResult::Value(self.help_message())),
+                "--fix" => {
+                    let findings = ctx.store.diagnose()?;
+                    let fixed = self.fix(ctx, &findings)?;
+                    self.report(ctx, ctx.store.diagnose()?, fixed)
+                }
+                "--salvage" => {
+                    let (rejected, rejected_path) = ctx.store.salvage()?;
+                    if rejected.is_empty() {
+                        return Ok(CommandResult::Value(
+                            "No unparseable lines found".to_string(),
+                        ));
+                    }
+                    let mut s = format!(
+                        "Skipped {} unparseable line(s), written to {}:",
+                        rejected.len(),
+                        rejected_path.display()
+                    );
+                    for r in rejected.iter() {
+                        s.push_str(&format!(
+                            "\n  line {}: {} ({})",
+                            r.line_number, r.line, r.reason
+                        ));
+                    }
+                    Ok(CommandResult::Value(s))
+                }
+                _ => Err(self.usage_error()),
+            },
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+impl Doctor {
+    /// Applies the repairs of the findings, returning how many findings they fixed. Nothing is
+    /// repaired while the data file can not be parsed, as that would drop the broken lines.
+    fn fix(&self, ctx: &Context, findings: &[Finding]) -> Result<usize, CommandError> {
+        if findings
+            .iter()
+            .any(|f| f.severity == Severity::Error && f.repair.is_none())
+        {
+            return Ok(0);
+        }
+        let repairs = |repair: &Repair| findings.iter().any(|f| f.repair.as_ref() == Some(repair));
+        if repairs(&Repair::MergeDuplicates) {
+            // Compacting rebuilds the index as well
+            ctx.store.compact(false)?;
+        } else if repairs(&Repair::Prune) {
+            // Saving the pruned data file writes a fresh index file as well
+            ctx.store.write(|ds| ds.prune())?;
+        } else if repairs(&Repair::RebuildIndex) {
+            ctx.store.rebuild_index()?;
+        } else {
+            return Ok(0);
+        }
+        Ok(findings.iter().filter(|f| f.repair.is_some()).count())
+    }
+
+    /// Lists the findings, failing when any of them is an error
+    fn report(
+        &self,
+        ctx: &Context,
+        findings: Vec<Finding>,
+        fixed: usize,
+    ) -> Result<CommandResult, CommandError> {
+        let fixed_line = match fixed {
+            0 => String::new(),
+            fixed => format!("Fixed {fixed} problem(s)\n"),
+        };
+        if findings.is_empty() {
+            return Ok(CommandResult::Value(format!(
+                "{fixed_line}No problems found"
+            )));
+        }
+        let has_errors = findings.iter().any(|f| f.severity == Severity::Error);
+        if ctx.format.is_structured() && !has_errors {
+            return Ok(CommandResult::Json(findings_json(&findings, fixed)));
+        }
+        let table = Table {
+            title: format!("{fixed_line}Found {} problem(s):", findings.len()),
+            rows: findings
+                .iter()
+                .flat_map(|f| {
+                    [
+                        vec![f.severity.to_string(), f.message.clone()],
+                        vec![String::new(), format!("fix: {}", f.fix)],
+                    ]
+                })
+                .collect(),
+        };
+        match has_errors {
+            true => Err(CommandError {
+                kind: CommandErrorKind::InvalidData,
+                message: table.to_string(),
+            }),
+            false => Ok(CommandResult::Table(table)),
+        }
+    }
+}
+
+/// `{"fixed":1,"findings":[{"severity":"warning","message":"...","fix":"...","fixable":true}]}`
+fn findings_json(findings: &[Finding], fixed: usize) -> Json {
+    Json::object([
+        ("fixed", Json::Number(fixed as u64)),
+        (
+            "findings",
+            Json::Array(
+                findings
+                    .iter()
+                    .map(|f| {
+                        Json::object([
+                            ("severity", Json::from(f.severity.to_string().as_str())),
+                            ("message", Json::from(f.message.as_str())),
+                            ("fix", Json::from(f.fix.as_str())),
+                            ("fixable", Json::Bool(f.repair.is_some())),
+                        ])
+                    })
+                    .collect(),
+            ),
+        ),
+    ])
+}
+
+impl DisplayCommandAsRow for Doctor {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            ("tap --doctor", "Check the data and index files"),
+            (
+                "tap --doctor --fix",
+                "Fix the problems that are safe to fix",
+            ),
+            (
+                "tap --doctor --salvage",
+                "Recover the links that can still be parsed",
+            ),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::tap_data_store::{TestDataFile, index_path_for};
+
+    fn ctx_in_memory() -> Context {
+        Context::in_memory(vec![(
+            "search-engines".to_string(),
+            vec![("google".to_string(), "https://google.com".to_string())],
+        )])
+    }
+
+    #[test]
+    fn test_doctor_run_expected_help_arg() {
+        let args: Vec<String> = vec!["--help".to_string()];
+        let cmd = Doctor::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        let res = cmd.run(&mut Context::default(), args);
+        assert_eq!(res, expected);
+    }
+
+    #[test]
+    fn test_doctor_run_unexpected_args() {
+        let args: Vec<String> = vec!["random".to_string()];
+        let cmd = Doctor::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        let res = cmd.run(&mut Context::default(), args);
+        assert_eq!(res, expected);
+    }
+
+    #[test]
+    fn test_doctor_run_no_args() {
+        let cmd = Doctor::default();
+        let mut ctx = ctx_in_memory();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value("No problems found".to_string()));
+        let res = cmd.run(&mut ctx, vec![]);
+        assert_eq!(res, expected);
+    }
+
+    #[test]
+    fn test_doctor_run_salvage_nothing_rejected() {
+        let cmd = Doctor::default();
+        let mut ctx = ctx_in_memory();
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Value(
+            "No unparseable lines found".to_string(),
+        ));
+        let res = cmd.run(&mut ctx, vec!["--salvage".to_string()]);
+        assert_eq!(res, expected);
+    }
+
+    #[test]
+    fn test_doctor_run_reports_errors() {
+        let data = TestDataFile::new("work->\n  ci|https://ci.internal\n");
+        std::fs::write(index_path_for(&data), "work|3\n").unwrap();
+        let e = Doctor::default()
+            .run(&mut Context::with_file(data.clone()), vec![])
+            .unwrap_err();
+        assert_eq!(e.kind, CommandErrorKind::InvalidData);
+        assert_eq!(
+            e.message,
+            "Found 1 problem(s):\n  error  The index entry of parent entity work points at byte 3, which is not the start of its work-> line\n         fix: Rebuild the index with tap --doctor --fix"
+        );
+    }
+
+    #[test]
+    fn test_doctor_run_warnings_succeed() {
+        let data = TestDataFile::new("work->\n  ci|https://ci.internal\n");
+        let res = Doctor::default().run(&mut Context::with_file(data.clone()), vec![]);
+        let Ok(CommandResult::Table(table)) = res else {
+            panic!("expected a table, got {res:?}");
+        };
+        assert_eq!(table.rows[0], ["warning", "The index file is missing"]);
+    }
+
+    #[test]
+    fn test_doctor_run_fix() {
+        let data = TestDataFile::new(
+            "work->\n  ci|https://ci.internal\nwork->\n  ci|https://ci.internal\n  mr|https://mr.internal\n",
+        );
+        std::fs::write(index_path_for(&data), "gone|0\n").unwrap();
+        let res = Doctor::default().run(
+            &mut Context::with_file(data.clone()),
+            vec!["--fix".to_string()],
+        );
+        assert_eq!(
+            res,
+            Ok(CommandResult::Value(
+                "Fixed 4 problem(s)\nNo problems found".to_string()
+            ))
+        );
+        assert_eq!(
+            std::fs::read_to_string(&data).unwrap(),
+            "work->\n  ci|https://ci.internal\n  mr|https://mr.internal\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(index_path_for(&data)).unwrap(),
+            "work|0\n"
+        );
+    }
+
+    #[test]
+    fn test_doctor_run_fix_leaves_parse_errors() {
+        let contents = "work->\n  broken\n";
+        let data = TestDataFile::new(contents);
+        let e = Doctor::default()
+            .run(
+                &mut Context::with_file(data.clone()),
+                vec!["--fix".to_string()],
+            )
+            .unwrap_err();
+        assert_eq!(e.kind, CommandErrorKind::InvalidData);
+        assert_eq!(std::fs::read_to_string(&data).unwrap(), contents);
+    }
+}
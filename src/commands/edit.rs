@@ -0,0 +1,332 @@
+use crate::{
+    commands::{Command, CommandError, CommandResult, display_examples},
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::context::Context,
+    utils::log,
+    utils::style,
+    utils::tap_data_store::{TapDataStoreErrorKind, check_data_file},
+};
+use std::{env, fs, io::ErrorKind, path::Path, process};
+
+pub(crate) struct Edit {
+    name: String,
+    description: String,
+    args: [String; 0],
+}
+
+impl Default for Edit {
+    fn default() -> Self {
+        Self {
+            name: "--edit".to_string(),
+            description: "Edit the data file in your editor".to_string(),
+            args: [],
+        }
+    }
+}
+
+impl Command for Edit {
+    fn error_message(&self) -> String {
+        "expected no arguments, see the Usage section with tap --edit --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --edit command opens a copy of the data file in your editor, taken from $EDITOR, then $VISUAL, then vi. Once the editor exits, the copy replaces the data file and the index is rebuilt, but only if every line of it can be parsed. Otherwise the lines that can not be parsed are listed, and you can edit the copy again or give up without changing anything.\n\n");
+        s.push_str("When something else changes the data file while it is being edited, it is not replaced, and the edits are saved next to it in a .edited file instead.\n\n");
+        s.push_str("Command Structure: tap --edit\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        match args.as_slice() {
+            [] => self.edit(ctx, &editor_command()),
+            [flag] if flag == "--help" => Ok(CommandResult::Value(self.help_message())),
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+impl Edit {
+    /// Edits a copy of the data file with `editor`, a program followed by its arguments
+    fn edit(&self, ctx: &Context, editor: &[String]) -> Result<CommandResult, CommandError> {
+        ctx.store.options().check_writable()?;
+        let [(data, _), _] = ctx.store.options().resolve_paths()?;
+        let original = match fs::read_to_string(&data) {
+            Ok(original) => Some(original),
+            // A missing data file is edited as an empty one
+            Err(e) if e.kind() == ErrorKind::NotFound => None,
+            Err(e) => {
+                return Err(format!("Could not read data file {}: {e}", data.display()).into());
+            }
+        };
+        let copy = env::temp_dir().join(format!("tap_edit_{}.tap_data", process::id()));
+        fs::write(&copy, original.as_deref().unwrap_or_default())
+            .map_err(|e| format!("Could not write {}: {e}", copy.display()))?;
+        let res = self.edit_copy(ctx, editor, &copy, &data, original.as_deref());
+        let _ = fs::remove_file(&copy);
+        res
+    }
+
+    /// Edits `copy` until it parses or the user gives up, then replaces the `data` file with it
+    /// unless the data file no longer holds `original`
+    fn edit_copy(
+        &self,
+        ctx: &Context,
+        editor: &[String],
+        copy: &Path,
+        data: &Path,
+        original: Option<&str>,
+    ) -> Result<CommandResult, CommandError> {
+        loop {
+            open_editor(editor, copy)?;
+            let edited = fs::read_to_string(copy)
+                .map_err(|e| format!("Could not read {}: {e}", copy.display()))?;
+            if edited == original.unwrap_or_default() {
+                return Ok(CommandResult::Value("No changes were made".to_string()));
+            }
+            let e = match check_data_file(&edited) {
+                Ok(_) => {
+                    return match ctx.store.replace_unchanged_data_file(original, &edited) {
+                        Ok(()) => Ok(CommandResult::Value(style::success("Data file updated"))),
+                        Err(e) if *e.kind() == TapDataStoreErrorKind::DataFileChanged => {
+                            Err(keep_edits(data, &edited, e.into()))
+                        }
+                        Err(e) => Err(e.into()),
+                    };
+                }
+                Err(e) => e,
+            };
+            if !ctx.prompt.is_interactive()
+                || !ctx.prompt.confirm(&format!(
+                    "The edited data file can not be parsed:\n{e}\nEdit it again?"
+                ))?
+            {
+                return Err(CommandError::from(e).with_hint(
+                    "Nothing was changed, fix the lines above and run tap --edit again",
+                ));
+            }
+        }
+    }
+}
+
+/// `e` with a hint to where the `edited` data file was saved, next to the `data` file, when it
+/// could not replace it
+fn keep_edits(data: &Path, edited: &str, e: CommandError) -> CommandError {
+    let mut file_name = data.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".edited");
+    let kept = data.with_file_name(file_name);
+    match fs::write(&kept, edited) {
+        Ok(()) => e.with_hint(&format!(
+            "Your edits were saved to {}, compare them with the data file and run tap --edit again",
+            kept.display()
+        )),
+        Err(_) => e.with_hint("Your edits could not be saved, run tap --edit again"),
+    }
+}
+
+/// The editor to use, `$EDITOR`, then `$VISUAL`, then `vi`. The variables may hold arguments
+/// after the program, like `code --wait`.
+fn editor_command() -> Vec<String> {
+    let editor = ["EDITOR", "VISUAL"]
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string());
+    editor.split_whitespace().map(str::to_string).collect()
+}
+
+/// Runs the editor on `file` and waits for it to exit
+fn open_editor(editor: &[String], file: &Path) -> Result<(), CommandError> {
+    let (program, args) = editor
+        .split_first()
+        .expect("The editor command has a program");
+    log::verbose(format_args!(
+        "Running {} {}",
+        editor.join(" "),
+        file.display()
+    ));
+    let status = process::Command::new(program)
+        .args(args)
+        .arg(file)
+        .status()
+        .map_err(|e| format!("Could not start the editor {program}: {e}"))?;
+    match status.success() {
+        true => Ok(()),
+        false => {
+            Err(format!("The editor {program} exited with {status}, nothing was changed").into())
+        }
+    }
+}
+
+impl DisplayCommandAsRow for Edit {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            ("tap --edit", "Edit the data file in $EDITOR"),
+            ("EDITOR=nano tap --edit", "Edit the data file in nano"),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::CommandErrorKind;
+    use crate::utils::prompt::Prompt;
+    use crate::utils::tap_data_store::{TestDataFile, index_path_for};
+
+    const LINKS: &str = "work->\n  ci|https://ci.internal\n";
+
+    /// An editor writing `contents` to the file it is given
+    fn editor(contents: &str) -> Vec<String> {
+        vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            format!("printf '{contents}' > \"$0\""),
+        ]
+    }
+
+    #[test]
+    fn test_edit_run_expected_help_arg() {
+        let cmd = Edit::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_edit_run_unexpected_args() {
+        let cmd = Edit::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["work".to_string()]),
+            expected
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_edit_replaces_data_file() {
+        let data = TestDataFile::new(LINKS);
+        let res = Edit::default().edit(
+            &Context::with_file(data.clone()),
+            &editor("work->\\n  ci|https://ci.internal\\npersonal->\\n  mail|https://mail.me\\n"),
+        );
+        assert_eq!(
+            res,
+            Ok(CommandResult::Value("Data file updated".to_string()))
+        );
+        assert_eq!(
+            fs::read_to_string(&data).unwrap(),
+            "personal->\n  mail|https://mail.me\nwork->\n  ci|https://ci.internal\n"
+        );
+        assert!(index_path_for(&data).exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_edit_unchanged() {
+        let data = TestDataFile::new(LINKS);
+        let res = Edit::default().edit(&Context::with_file(data.clone()), &["true".to_string()]);
+        assert_eq!(
+            res,
+            Ok(CommandResult::Value("No changes were made".to_string()))
+        );
+        assert!(!index_path_for(&data).exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_edit_parse_error_aborts() {
+        let data = TestDataFile::new(LINKS);
+        let ctx = Context {
+            prompt: Prompt::scripted("n\n"),
+            ..Context::with_file(data.clone())
+        };
+        let e = Edit::default()
+            .edit(&ctx, &editor("work->\\n  broken\\n"))
+            .unwrap_err();
+        assert_eq!(e.kind, CommandErrorKind::InvalidData);
+        assert!(e.message.starts_with("line 2: broken"), "{}", e.message);
+        assert_eq!(
+            fs::read_to_string(&data).unwrap(),
+            "work->\n  ci|https://ci.internal\n"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_edit_data_file_changed_meanwhile() {
+        let data = TestDataFile::new(LINKS);
+        // The editor saves the copy while something else adds a link to the data file
+        let editor = vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            format!(
+                "printf 'news->\\n  bbc|https://bbc.com\\n' > \"$0\"; printf 'work->\\n  ci|https://ci.internal\\n  mr|https://mr.internal\\n' > '{}'",
+                data.display()
+            ),
+        ];
+        let e = Edit::default()
+            .edit(&Context::with_file(data.clone()), &editor)
+            .unwrap_err();
+        assert!(
+            e.message.contains("was changed by something else"),
+            "{}",
+            e.message
+        );
+        assert_eq!(
+            fs::read_to_string(&data).unwrap(),
+            "work->\n  ci|https://ci.internal\n  mr|https://mr.internal\n"
+        );
+        assert_eq!(
+            fs::read_to_string(data.with_file_name("demo.tap_data.edited")).unwrap(),
+            "news->\n  bbc|https://bbc.com\n"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_edit_unreadable_data_file() {
+        let data = TestDataFile::new(LINKS);
+        // A directory can not be read as the data file, which is not edited as an empty one
+        fs::remove_file(&data).unwrap();
+        fs::create_dir(&data).unwrap();
+        let e = Edit::default()
+            .edit(&Context::with_file(data.clone()), &["true".to_string()])
+            .unwrap_err();
+        assert!(
+            e.message.starts_with("Could not read data file"),
+            "{}",
+            e.message
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_edit_editor_fails() {
+        let data = TestDataFile::new(LINKS);
+        let e = Edit::default()
+            .edit(&Context::with_file(data.clone()), &["false".to_string()])
+            .unwrap_err();
+        assert!(e.message.contains("nothing was changed"), "{}", e.message);
+    }
+}
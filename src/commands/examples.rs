@@ -0,0 +1,166 @@
+use crate::{
+    commands::{
+        Command, CommandError, CommandResult, ListedCommand, display_examples, help::topics,
+        registry,
+    },
+    utils::cli_usage_table::{DisplayCommandAsRow, Row, UsageTableBuilder},
+    utils::context::Context,
+};
+
+pub(crate) struct Examples {
+    name: String,
+    description: String,
+    args: [String; 1],
+}
+
+impl Default for Examples {
+    fn default() -> Self {
+        Self {
+            name: "--examples".to_string(),
+            description: "Show example invocations".to_string(),
+            args: ["[Command]".to_string()],
+        }
+    }
+}
+
+impl Command for Examples {
+    fn error_message(&self) -> String {
+        "expected 0-1 arguments, see the Usage section with tap --examples --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --examples command shows example invocations of every command, grouped by command. Pass a command to only show its examples.\n\n");
+        s.push_str("Command Structure: tap --examples [Command]\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, _ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let commands = registry();
+        match args.as_slice() {
+            [] => Ok(CommandResult::Value(display_command_examples(&commands))),
+            [flag] if flag == "--help" => Ok(CommandResult::Value(self.help_message())),
+            [topic] => match commands
+                .into_iter()
+                .find(|cmd| topics(cmd.as_ref()).contains(topic))
+            {
+                Some(cmd) => Ok(CommandResult::Value(display_command_examples(&[cmd]))),
+                None => Err(CommandError::usage(format!(
+                    "unknown command {topic}, see the available commands with tap --help"
+                ))),
+            },
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+impl DisplayCommandAsRow for Examples {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            ("tap --examples", "Show the examples of every command"),
+            ("tap --examples add", "Show the examples of the add command"),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+/// A section per command, titled with its name and holding one row per example
+fn display_command_examples(commands: &[Box<dyn ListedCommand>]) -> String {
+    commands
+        .iter()
+        .filter(|cmd| !cmd.examples().is_empty())
+        .fold(
+            UsageTableBuilder::new("Examples:").without_synopsis(),
+            |table, cmd| {
+                let rows = cmd
+                    .examples()
+                    .iter()
+                    .map(|(line, description)| Row::example(line, description))
+                    .collect();
+                table.add_section(format!("{}:", cmd.name()), rows)
+            },
+        )
+        .build()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::add::Add;
+
+    #[test]
+    fn test_examples_run_help_arg() {
+        let cmd = Examples::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_examples_run_unexpected_args() {
+        let cmd = Examples::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(
+            cmd.run(
+                &mut Context::default(),
+                vec!["add".to_string(), "show".to_string()]
+            ),
+            expected
+        );
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["nope".to_string()]),
+            Err(CommandError::usage(
+                "unknown command nope, see the available commands with tap --help".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_examples_run_every_command() {
+        let res = Examples::default()
+            .run(&mut Context::default(), vec![])
+            .unwrap()
+            .to_string();
+        for cmd in registry() {
+            assert!(res.contains(&format!("{}:", cmd.name())), "{}", cmd.name());
+            for (line, _) in cmd.examples() {
+                assert!(res.contains(&line), "{line}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_examples_run_one_command() {
+        let res = Examples::default()
+            .run(&mut Context::default(), vec!["add".to_string()])
+            .unwrap()
+            .to_string();
+        assert!(res.starts_with("Examples:\n-a, --add:\n"));
+        assert_eq!(
+            res.lines()
+                .filter(|line| line.starts_with(' ') && !line.starts_with("  "))
+                .count(),
+            Add::default().examples().len()
+        );
+        assert!(!res.contains("--upsert"));
+    }
+}
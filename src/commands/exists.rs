@@ -0,0 +1,195 @@
+use crate::{
+    commands::{Command, CommandError, CommandErrorKind, CommandResult, display_examples},
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::command::Args,
+    utils::context::Context,
+};
+
+pub(crate) struct Exists {
+    name: String,
+    description: String,
+    args: [String; 3],
+}
+
+impl Default for Exists {
+    fn default() -> Self {
+        Self {
+            name: "--exists".to_string(),
+            description: "Check if a parent entity or link exists".to_string(),
+            args: [
+                "<Parent|here>".to_string(),
+                "[Link]".to_string(),
+                "[--print]".to_string(),
+            ],
+        }
+    }
+}
+
+impl Command for Exists {
+    fn error_message(&self) -> String {
+        "expected 1-2 arguments, see the Usage section with tap --exists --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --exists command checks if a Parent Entity, or a link of it, exists. It prints nothing, the answer is its exit code: 0 when it exists, 3 when the Parent Entity is not found, and 4 when the link is not found. Names are not shortened, they must be typed in full.\n\n");
+        s.push_str(
+            "Command Structure: tap --exists <Parent Entity | here> [Link Name] [--print]\n",
+        );
+        s.push_str("Pass --print to print yes or no instead, for use in a terminal. The exit code is then 0 either way.\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let mut args = Args::new(args);
+        let print = args.take_flag(&["--print"]);
+        let found = match args.len() {
+            1 if args.is_flag(0, "--help") => {
+                return Ok(CommandResult::Value(self.help_message()));
+            }
+            // Only the index is read to find a parent entity
+            1 => match ctx.store.parents()?.contains(&args.parent(0)?) {
+                true => Ok(()),
+                false => Err(CommandErrorKind::ParentNotFound),
+            },
+            2 => match ctx
+                .store
+                .read_link(&args.parent(0)?, &args[1])
+                .map_err(CommandError::from)
+            {
+                Ok(_) => Ok(()),
+                Err(e)
+                    if matches!(
+                        e.kind,
+                        CommandErrorKind::ParentNotFound | CommandErrorKind::LinkNotFound
+                    ) =>
+                {
+                    Err(e.kind)
+                }
+                // A data store that can not be read is not an answer
+                Err(e) => return Err(e),
+            },
+            _ => return Err(self.usage_error()),
+        };
+        match (found, print) {
+            (Ok(()), true) => Ok(CommandResult::Value("yes".to_string())),
+            (Err(_), true) => Ok(CommandResult::Value("no".to_string())),
+            (Ok(()), false) => Ok(CommandResult::Silent),
+            (Err(kind), false) => Err(CommandError::silent(kind)),
+        }
+    }
+}
+
+impl DisplayCommandAsRow for Exists {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            (
+                "tap --exists search-engines google",
+                "Check if a link exists",
+            ),
+            (
+                "tap --exists here || tap --add here repo .",
+                "Add a link to the current directory unless it has links already",
+            ),
+            ("tap --exists search-engines --print", "Print yes or no"),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::tap_data_store::TestDataFile;
+    use std::path::Path;
+
+    const LINKS: &str =
+        "search-engines->\n  google|https://google.com\n  yahoo|https://yahoo.com\n";
+
+    fn exists(data: &Path, args: &[&str]) -> Result<CommandResult, CommandError> {
+        Exists::default().run(
+            &mut Context::with_file(data.to_path_buf()),
+            args.iter().map(|a| a.to_string()).collect(),
+        )
+    }
+
+    fn exit_code(res: Result<CommandResult, CommandError>) -> i32 {
+        res.map_or_else(|e| e.kind.exit_code(), |_| 0)
+    }
+
+    #[test]
+    fn test_exists_run_expected_help_arg() {
+        let cmd = Exists::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_exists_run_unexpected_args() {
+        let cmd = Exists::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(cmd.run(&mut Context::default(), vec![]), expected);
+        assert_eq!(
+            cmd.run(
+                &mut Context::default(),
+                vec!["a".to_string(), "b".to_string(), "c".to_string()]
+            ),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_exists_run_exit_codes() {
+        let data = TestDataFile::new(LINKS);
+        assert_eq!(
+            exists(&data, &["search-engines"]),
+            Ok(CommandResult::Silent)
+        );
+        assert_eq!(exit_code(exists(&data, &["search-engines", "google"])), 0);
+        assert_eq!(exit_code(exists(&data, &["work"])), 3);
+        assert_eq!(exit_code(exists(&data, &["work", "ci"])), 3);
+        assert_eq!(exit_code(exists(&data, &["search-engines", "bing"])), 4);
+        // Names are not resolved from their start
+        assert_eq!(exit_code(exists(&data, &["search"])), 3);
+    }
+
+    #[test]
+    fn test_exists_run_silent_error() {
+        let data = TestDataFile::new(LINKS);
+        let e = exists(&data, &["search-engines", "bing"]).unwrap_err();
+        assert_eq!(e.kind, CommandErrorKind::LinkNotFound);
+        assert!(e.message.is_empty());
+    }
+
+    #[test]
+    fn test_exists_run_print() {
+        let data = TestDataFile::new(LINKS);
+        assert_eq!(
+            exists(&data, &["search-engines", "--print"]),
+            Ok(CommandResult::Value("yes".to_string()))
+        );
+        assert_eq!(
+            exists(&data, &["search-engines", "bing", "--print"]),
+            Ok(CommandResult::Value("no".to_string()))
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{
-    commands::{Command, CommandResult},
+    commands::{Command, CommandError, CommandResult, display_examples},
     utils::cli_usage_table::DisplayCommandAsRow,
+    utils::context::Context,
 };
 
 pub(crate) struct Export {
@@ -32,20 +33,21 @@ impl Command for Export {
 
     fn help_message(&self) -> String {
         format!(
-            "Tap export exports all links from Tap to a bookmark file compatible with the following browsers:\n{}\n\nExample Usage: {}",
+            "Tap export exports all links from Tap to a bookmark file compatible with the following browsers:\n{}\n\nCommand Structure: {}\n{}",
             "Chrome, Edge, Firefox, Opera, Safari, Tap",
-            "tap --export <Chrome | Edge | Firefox | Opera | Safari | Tap> <destination folder>"
+            "tap --export <Chrome | Edge | Firefox | Opera | Safari | Tap> <destination folder>",
+            display_examples(&self.examples())
         )
     }
 
-    fn run(&self, args: Vec<String>) -> Result<CommandResult, String> {
+    fn run(&self, _ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
         match args.len() {
-            0 => Err(self.error_message()),
+            0 => Err(self.usage_error()),
             1 => {
                 if args[0] == "--help" {
                     Ok(CommandResult::Value(self.help_message()))
                 } else {
-                    Err(self.error_message())
+                    Err(self.usage_error())
                 }
             }
             2 => match (args[0].as_str(), args[1].as_str()) {
@@ -67,9 +69,9 @@ impl Command for Export {
                 ("Tap", f) => Ok(CommandResult::Value(format!(
                     "TODO: Implement export functionality to Tap: {f}"
                 ))),
-                (bad_browser, _) => Err(self.bad_browser_message(bad_browser)),
+                (bad_browser, _) => Err(CommandError::usage(self.bad_browser_message(bad_browser))),
             },
-            _ => Err(self.error_message()),
+            _ => Err(self.usage_error()),
         }
     }
 }
@@ -83,6 +85,22 @@ impl DisplayCommandAsRow for Export {
         self.description.clone()
     }
 
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            (
+                "tap --export Chrome ~/Desktop",
+                "Export all links to a Chrome bookmark file in ~/Desktop",
+            ),
+            (
+                "tap --export Tap ~/backups",
+                "Export all links to a tap file in ~/backups",
+            ),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
     fn name(&self) -> String {
         self.name.clone()
     }
@@ -96,8 +114,9 @@ mod tests {
     fn test_export_run_expected_help_arg() {
         let args: Vec<String> = vec!["--help".to_string()];
         let cmd = Export::default();
-        let expected: Result<CommandResult, String> = Ok(CommandResult::Value(cmd.help_message()));
-        let res = cmd.run(args);
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        let res = cmd.run(&mut Context::default(), args);
         assert_eq!(res, expected);
     }
 
@@ -105,8 +124,8 @@ mod tests {
     fn test_export_run_unexpected_args() {
         let args: Vec<String> = vec!["random".to_string()];
         let cmd = Export::default();
-        let expected: Result<CommandResult, String> = Err(cmd.error_message());
-        let res = cmd.run(args);
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        let res = cmd.run(&mut Context::default(), args);
         assert_eq!(res, expected);
     }
 
@@ -114,92 +133,105 @@ mod tests {
     fn test_export_run_bad_browser() {
         let args: Vec<String> = vec!["bad browser".to_string(), "path".to_string()];
         let cmd = Export::default();
-        let expected: Result<CommandResult, String> = Err(cmd.bad_browser_message("bad browser"));
-        let res = cmd.run(args);
+        let expected: Result<CommandResult, CommandError> =
+            Err(CommandError::usage(cmd.bad_browser_message("bad browser")));
+        let res = cmd.run(&mut Context::default(), args);
         assert_eq!(res, expected);
     }
 
     #[test]
     fn test_export_run_chrome() {
         let cmd = Export::default();
-        let args = vec!["Chrome", "./test.json"]
+        let args = ["Chrome", "./test.json"]
             .iter()
             .map(|s| s.to_string())
             .collect();
         let expected = CommandResult::Value(
             "TODO: Implement export functionality to Chrome: ./test.json".to_string(),
         );
-        let res = cmd.run(args).expect("Could not display export");
+        let res = cmd
+            .run(&mut Context::default(), args)
+            .expect("Could not display export");
         assert_eq!(res, expected);
     }
 
     #[test]
     fn test_export_run_edge() {
         let cmd = Export::default();
-        let args = vec!["Edge", "./test.json"]
+        let args = ["Edge", "./test.json"]
             .iter()
             .map(|s| s.to_string())
             .collect();
         let expected = CommandResult::Value(
             "TODO: Implement export functionality to Edge: ./test.json".to_string(),
         );
-        let res = cmd.run(args).expect("Could not display export");
+        let res = cmd
+            .run(&mut Context::default(), args)
+            .expect("Could not display export");
         assert_eq!(res, expected);
     }
 
     #[test]
     fn test_export_run_firefox() {
         let cmd = Export::default();
-        let args = vec!["Firefox", "./test.json"]
+        let args = ["Firefox", "./test.json"]
             .iter()
             .map(|s| s.to_string())
             .collect();
         let expected = CommandResult::Value(
             "TODO: Implement export functionality to Firefox: ./test.json".to_string(),
         );
-        let res = cmd.run(args).expect("Could not display export");
+        let res = cmd
+            .run(&mut Context::default(), args)
+            .expect("Could not display export");
         assert_eq!(res, expected);
     }
 
     #[test]
     fn test_export_run_opera() {
         let cmd = Export::default();
-        let args = vec!["Opera", "./test.json"]
+        let args = ["Opera", "./test.json"]
             .iter()
             .map(|s| s.to_string())
             .collect();
         let expected = CommandResult::Value(
             "TODO: Implement export functionality to Opera: ./test.json".to_string(),
         );
-        let res = cmd.run(args).expect("Could not display export");
+        let res = cmd
+            .run(&mut Context::default(), args)
+            .expect("Could not display export");
         assert_eq!(res, expected);
     }
 
     #[test]
     fn test_export_run_safari() {
         let cmd = Export::default();
-        let args = vec!["Safari", "./test.json"]
+        let args = ["Safari", "./test.json"]
             .iter()
             .map(|s| s.to_string())
             .collect();
         let expected = CommandResult::Value(
             "TODO: Implement export functionality to Safari: ./test.json".to_string(),
         );
-        let res = cmd.run(args).expect("Could not display export");
+        let res = cmd
+            .run(&mut Context::default(), args)
+            .expect("Could not display export");
         assert_eq!(res, expected);
     }
 
     #[test]
     fn test_export_run_tap() {
         let cmd = Export::default();
-        let args = vec!["Tap", "./test.tap"]
+        let args = ["Tap", "./test.tap"]
             .iter()
             .map(|s| s.to_string())
             .collect();
         let expected = CommandResult::Value(
             "TODO: Implement export functionality to Tap: ./test.tap".to_string(),
         );
-        let res = cmd.run(args).expect("Could not display export");
+        let res = cmd
+            .run(&mut Context::default(), args)
+            .expect("Could not display export");
         assert_eq!(res, expected);
     }
 }
@@ -0,0 +1,266 @@
+use crate::{
+    commands::{
+        Command, CommandError, CommandResult, Table, display_examples, open_value, pick_links,
+        resolve_parent_prefix, value_to_open,
+    },
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::command::{Args, OpenLimit},
+    utils::context::Context,
+    utils::log,
+    utils::os_implementations::{fzf_select, is_on_path},
+};
+
+pub(crate) struct Fzf {
+    name: String,
+    description: String,
+    args: [String; 2],
+}
+
+impl Default for Fzf {
+    fn default() -> Self {
+        Self {
+            name: "--fzf".to_string(),
+            description: "Pick links to open with fzf".to_string(),
+            args: ["[Parent Entity]".to_string(), "[--force]".to_string()],
+        }
+    }
+}
+
+impl Command for Fzf {
+    fn error_message(&self) -> String {
+        "expected 0-1 arguments, see the Usage section with tap --fzf --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --fzf command picks a Parent Entity and then the links to open with fzf, the fuzzy finder, showing the value of a link next to the list. Press Tab to pick several links, and Esc to leave without opening anything. Give a Parent Entity to go straight to its links, it can be shortened to the start of its name like when opening links.\n\n");
+        s.push_str("fzf is looked up on the PATH each time. When it is not installed, the Parent Entities and links are listed numbered to pick from instead, like with tap <Parent Entity> --pick.\n\n");
+        s.push_str("More links picked than max_open = <count> in the config file allows are not opened, pass --force to open them anyway.\n\n");
+        s.push_str("Command Structure: tap --fzf [Parent Entity] [--force]\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let mut args = Args::new(args);
+        let force = args.take_flag(&["--force"]);
+        let fzf = is_on_path("fzf");
+        if !fzf {
+            log::verbose(format_args!(
+                "fzf was not found on the PATH, listing the choices numbered"
+            ));
+        }
+        let parent = match args.len() {
+            0 => match self.pick_parent(ctx, fzf)? {
+                Some(parent) => parent,
+                None => return Ok(CommandResult::Silent),
+            },
+            1 if args.is_flag(0, "--help") => return Ok(CommandResult::Value(self.help_message())),
+            1 => resolve_parent_prefix(&ctx.store, &args.parent(0)?)?,
+            _ => return Err(self.usage_error()),
+        };
+        let links = ctx
+            .store
+            .read_parent(&parent)
+            .map_err(|e| ctx.store.with_suggestions(e, &parent, None))?;
+        if links.is_empty() {
+            return Err(CommandError::from(format!(
+                "Parent entity {parent} has no links to pick from"
+            )));
+        }
+        let picked = match fzf {
+            true => self.fzf_links(&parent, links)?,
+            false => pick_links(ctx, &parent, links)?,
+        };
+        // Leaving fzf without picking anything is not an error
+        if picked.is_empty() {
+            return Ok(CommandResult::Silent);
+        }
+        OpenLimit::load().check_max(picked.len(), force)?;
+        let picked = picked
+            .into_iter()
+            .map(|(link, value)| {
+                let value = value_to_open(&parent, &link, &value, &[])?;
+                Ok((link, value))
+            })
+            .collect::<Result<Vec<_>, CommandError>>()?;
+        let mut rows = vec![];
+        for (link, value) in picked {
+            if open_value(ctx, &parent, &link, &value)? {
+                rows.push(vec![link]);
+            }
+        }
+        Ok(CommandResult::Table(Table {
+            title: "Opening links:".to_string(),
+            rows,
+        }))
+    }
+}
+
+impl Fzf {
+    /// The parent entity to pick links from, `None` when none was picked
+    fn pick_parent(&self, ctx: &Context, fzf: bool) -> Result<Option<String>, CommandError> {
+        // The index lists the parent entities without reading the data file
+        let parents = ctx.store.parents()?;
+        if parents.is_empty() {
+            return Err(CommandError::from(
+                "There are no parent entities to pick from".to_string(),
+            )
+            .with_hint("Add one with tap -a <Parent Entity> <Link Name> <Value>"));
+        }
+        if fzf {
+            let picked = fzf_select(&parents, &["--prompt", "Parent Entity> "])?;
+            return Ok(picked.and_then(|mut picked| picked.pop()));
+        }
+        if !ctx.prompt.is_interactive() {
+            return Err(CommandError::usage(
+                "tap --fzf asks which links to open and needs a terminal, name the links to open instead"
+                    .to_string(),
+            ));
+        }
+        let i = ctx.prompt.choose("Parent entity", &parents)?;
+        Ok(Some(parents[i].clone()))
+    }
+
+    /// The links of `parent` picked with fzf. Each is given to fzf as `link<Tab>value`, only the
+    /// name is listed and the value is previewed.
+    fn fzf_links(
+        &self,
+        parent: &str,
+        links: Vec<(String, String)>,
+    ) -> Result<Vec<(String, String)>, CommandError> {
+        let candidates: Vec<String> = links
+            .iter()
+            .map(|(link, value)| format!("{link}\t{value}"))
+            .collect();
+        let prompt = format!("{parent}> ");
+        let args = [
+            "--multi",
+            "--delimiter",
+            "\t",
+            "--with-nth",
+            "1",
+            "--preview",
+            "echo {2}",
+            "--preview-window",
+            "down:3:wrap",
+            "--prompt",
+            &prompt,
+        ];
+        let picked = fzf_select(&candidates, &args)?.unwrap_or_default();
+        Ok(links
+            .into_iter()
+            .filter(|(link, value)| picked.contains(&format!("{link}\t{value}")))
+            .collect())
+    }
+}
+
+impl DisplayCommandAsRow for Fzf {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            ("tap --fzf", "Pick a Parent Entity, then the Links to open"),
+            (
+                "tap --fzf search-engines",
+                "Pick the Links of search-engines to open",
+            ),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::CommandErrorKind;
+    use crate::utils::prompt::Prompt;
+
+    fn ctx_with_answers(answers: &'static str) -> Context {
+        Context {
+            prompt: Prompt::scripted(answers),
+            ..Context::in_memory(vec![
+                (
+                    "search-engines".to_string(),
+                    vec![
+                        ("google".to_string(), "https://google.com".to_string()),
+                        ("yahoo".to_string(), "https://yahoo.com".to_string()),
+                    ],
+                ),
+                (
+                    "work".to_string(),
+                    vec![("ci".to_string(), "https://ci.internal".to_string())],
+                ),
+            ])
+        }
+    }
+
+    #[test]
+    fn test_fzf_run_expected_help_arg() {
+        let cmd = Fzf::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_fzf_run_unexpected_args() {
+        let cmd = Fzf::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(
+            cmd.run(
+                &mut Context::default(),
+                vec!["work".to_string(), "ci".to_string()]
+            ),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_fzf_run_falls_back_to_numbered_lists() {
+        // fzf is never found under test
+        let cmd = Fzf::default();
+        let mut ctx = ctx_with_answers("1\n2\n");
+        assert_eq!(
+            cmd.run(&mut ctx, vec![]),
+            Ok(CommandResult::Table(Table {
+                title: "Opening links:".to_string(),
+                rows: vec![vec!["yahoo".to_string()]],
+            }))
+        );
+        let mut ctx = ctx_with_answers("1\n");
+        assert_eq!(
+            cmd.run(&mut ctx, vec!["wo".to_string()]),
+            Ok(CommandResult::Table(Table {
+                title: "Opening links:".to_string(),
+                rows: vec![vec!["ci".to_string()]],
+            }))
+        );
+    }
+
+    #[test]
+    fn test_fzf_run_needs_a_terminal() {
+        let cmd = Fzf::default();
+        let mut ctx = Context {
+            prompt: Prompt::stdin(false),
+            ..ctx_with_answers("")
+        };
+        let err = cmd.run(&mut ctx, vec![]).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::Usage);
+    }
+}
@@ -0,0 +1,224 @@
+use crate::{
+    commands::{Command, CommandError, CommandErrorKind, CommandResult, display_examples},
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::command::Args,
+    utils::context::Context,
+    utils::json::Json,
+    utils::regex::Regex,
+};
+
+pub(crate) struct Grep {
+    name: String,
+    description: String,
+    args: [String; 3],
+}
+
+impl Default for Grep {
+    fn default() -> Self {
+        Self {
+            name: "--grep".to_string(),
+            description: "Find links whose value matches a regex".to_string(),
+            args: [
+                "<Pattern>".to_string(),
+                "[-i]".to_string(),
+                "[-v]".to_string(),
+            ],
+        }
+    }
+}
+
+impl Command for Grep {
+    fn error_message(&self) -> String {
+        "expected 1 argument, see the Usage section with tap --grep --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --grep command prints every link whose value matches a regular expression, as Parent<TAB>Link<TAB>Value lines. Like grep, it prints nothing and exits with 1 when no value matches.\n\n");
+        s.push_str("Command Structure: tap --grep <Pattern> [-i] [-v]\n");
+        s.push_str("Pass -i to ignore case, and -v to print the links whose value does not match instead. Patterns support . [a-z] [^a-z] \\d \\w \\s ^ $ \\b (a|b) (?:a) (?<name>a), the * + ? {n} {n,} {n,m} repetitions, and a leading (?i). Other syntax, like [[:alpha:]] or \\p{L}, is refused. Put -- before a pattern starting with a dash.\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let mut args = Args::new(args);
+        let ignore_case = args.take_flag(&["-i"]);
+        let invert = args.take_flag(&["-v"]);
+        match args.len() {
+            1 if args.is_flag(0, "--help") => Ok(CommandResult::Value(self.help_message())),
+            1 => {
+                let regex = Regex::new(&args[0]).map_err(|e| CommandError::usage(e.to_string()))?;
+                let regex = match ignore_case {
+                    true => regex.case_insensitive(),
+                    false => regex,
+                };
+                let mut matches = vec![];
+                for (parent, links) in ctx.store.all_links()? {
+                    for (link, value) in links {
+                        if regex.is_match(&value) != invert {
+                            matches.push((parent.clone(), link, value));
+                        }
+                    }
+                }
+                if matches.is_empty() {
+                    return Err(CommandError::silent(CommandErrorKind::Generic));
+                }
+                if ctx.format.is_structured() {
+                    return Ok(CommandResult::Json(Json::Array(
+                        matches
+                            .iter()
+                            .map(|(parent, link, value)| {
+                                Json::object([
+                                    ("parent", Json::from(parent.as_str())),
+                                    ("link", Json::from(link.as_str())),
+                                    ("value", Json::from(value.as_str())),
+                                ])
+                            })
+                            .collect(),
+                    )));
+                }
+                Ok(CommandResult::Rows(
+                    matches
+                        .into_iter()
+                        .map(|(parent, link, value)| vec![parent, link, value])
+                        .collect(),
+                ))
+            }
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+impl DisplayCommandAsRow for Grep {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            (
+                r"tap --grep 'jira\.internal/[A-Z]+-\d+'",
+                "Find the links to Jira issues",
+            ),
+            (
+                "tap --grep -i old-host.example.com",
+                "Find the links to a host, ignoring case",
+            ),
+            (
+                "tap --grep -v '^https://'",
+                "Find the links not using HTTPS",
+            ),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::format::Format;
+
+    fn ctx() -> Context {
+        Context::in_memory(vec![
+            (
+                "search-engines".to_string(),
+                vec![
+                    ("google".to_string(), "https://google.com".to_string()),
+                    ("yahoo".to_string(), "http://yahoo.com".to_string()),
+                ],
+            ),
+            (
+                "work".to_string(),
+                vec![(
+                    "bug".to_string(),
+                    "https://jira.internal/ENG-42".to_string(),
+                )],
+            ),
+        ])
+    }
+
+    fn grep(args: &[&str]) -> Result<CommandResult, CommandError> {
+        Grep::default().run(&mut ctx(), args.iter().map(|a| a.to_string()).collect())
+    }
+
+    fn rows(rows: &[&[&str]]) -> Result<CommandResult, CommandError> {
+        Ok(CommandResult::Rows(
+            rows.iter()
+                .map(|row| row.iter().map(|c| c.to_string()).collect())
+                .collect(),
+        ))
+    }
+
+    #[test]
+    fn test_grep_run_expected_help_arg() {
+        let cmd = Grep::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_grep_run_unexpected_args() {
+        let cmd = Grep::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(cmd.run(&mut Context::default(), vec![]), expected);
+        assert_eq!(grep(&["a", "b"]), expected);
+    }
+
+    #[test]
+    fn test_grep_run_matches() {
+        assert_eq!(
+            grep(&[r"jira\.internal/[A-Z]+-\d+"]),
+            rows(&[&["work", "bug", "https://jira.internal/ENG-42"]])
+        );
+        assert_eq!(
+            grep(&["^https://", "-v"]),
+            rows(&[&["search-engines", "yahoo", "http://yahoo.com"]])
+        );
+        assert_eq!(
+            grep(&["-i", "GOOGLE"]),
+            rows(&[&["search-engines", "google", "https://google.com"]])
+        );
+    }
+
+    #[test]
+    fn test_grep_run_only_values() {
+        // The link name matches, its value does not
+        assert_eq!(
+            grep(&["bug"]),
+            Err(CommandError::silent(CommandErrorKind::Generic))
+        );
+    }
+
+    #[test]
+    fn test_grep_run_invalid_pattern() {
+        let e = grep(&["[A-Z"]).unwrap_err();
+        assert_eq!(e.kind, CommandErrorKind::Usage);
+        assert_eq!(e.message, Regex::new("[A-Z").unwrap_err().to_string());
+    }
+
+    #[test]
+    fn test_grep_run_structured_format() {
+        let mut ctx = ctx();
+        ctx.format = Format::Json;
+        let res = Grep::default().run(&mut ctx, vec!["ENG".to_string()]);
+        assert_eq!(
+            res.unwrap().render(Format::Json),
+            r#"[{"parent":"work","link":"bug","value":"https://jira.internal/ENG-42"}]"#
+        );
+    }
+}
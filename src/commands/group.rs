@@ -0,0 +1,510 @@
+use crate::{
+    commands::{
+        Command, CommandError, CommandResult, Table, display_examples, open_value, split_link,
+        value_to_open,
+    },
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::command::{Args, OpenLimit},
+    utils::context::Context,
+    utils::groups::{Groups, members},
+    utils::json::Json,
+    utils::style,
+};
+
+pub(crate) struct Group {
+    name: String,
+    description: String,
+    args: [String; 3],
+}
+
+impl Default for Group {
+    fn default() -> Self {
+        Self {
+            name: "--group".to_string(),
+            description: "Keep links of any Parents in a named group".to_string(),
+            args: [
+                "<add|remove|open|show>".to_string(),
+                "[Group]".to_string(),
+                "[Parent/Link...]".to_string(),
+            ],
+        }
+    }
+}
+
+impl Command for Group {
+    fn error_message(&self) -> String {
+        "expected add, remove, open, or show, see the Usage section with tap --group --help"
+            .to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --group command keeps links of any Parent Entities in a named group, like the dashboards to watch when on call, to open or show all of them at once. Each link is given as <Parent Entity>/<Link Name> and a group lists its links in the order they were added. A link can be in any number of groups.\n\n");
+        s.push_str("Unlike a session, a group is part of its links: it is kept in their metadata, with the order of the groups kept in .tap_groups next to the data file. Deleting a link drops it from its groups with a note, and a group is gone once it has no links left. Pass --force to open more links than max_open in the config file allows.\n\n");
+        s.push_str("Command Structure(s):\n");
+        s.push_str("  - tap --group add <Group> <Parent/Link...>       (Add links to a group)\n");
+        s.push_str(
+            "  - tap --group remove <Group> <Parent/Link...>    (Take links out of a group)\n",
+        );
+        s.push_str(
+            "  - tap --group open <Group> [--force]             (Open the links of a group)\n",
+        );
+        s.push_str("  - tap --group show [Group]                       (Show the links of a group, or list all groups)\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let mut args = Args::new(args);
+        let force = args.take_flag(&["--force"]);
+        let (action, rest) = match args.split_first() {
+            Some((action, rest)) => (action.as_str(), rest),
+            None => return Err(self.usage_error()),
+        };
+        match (action, rest) {
+            ("--help", []) => Ok(CommandResult::Value(self.help_message())),
+            ("add", [name, links @ ..]) if !links.is_empty() && !force => {
+                self.add(ctx, name, links)
+            }
+            ("remove", [name, links @ ..]) if !links.is_empty() && !force => {
+                self.remove(ctx, name, links)
+            }
+            ("open", [name]) => self.open(ctx, name, force),
+            ("show", []) if !force => self.list(ctx),
+            ("show", [name]) if !force => self.show(ctx, name),
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+impl Group {
+    fn add(
+        &self,
+        ctx: &Context,
+        name: &str,
+        links: &[String],
+    ) -> Result<CommandResult, CommandError> {
+        validate_name(name)?;
+        let name = name.trim();
+        let mut groups = ctx.store.groups()?;
+        let mut added: Vec<(String, String)> = vec![];
+        for link in resolve(ctx, links)? {
+            if !members(&groups, name).contains(&link) && !added.contains(&link) {
+                added.push(link);
+            }
+        }
+        if added.is_empty() {
+            return Ok(CommandResult::Value(format!(
+                "Every link is in group '{name}' already"
+            )));
+        }
+        match groups.iter_mut().find(|(group, _)| group == name) {
+            Some((_, links)) => links.extend(added.iter().cloned()),
+            None => groups.push((name.to_string(), added.clone())),
+        }
+        // The order is written first, so the links are in place once their metadata names the
+        // group
+        ctx.store.write_groups(&groups)?;
+        ctx.store.write(|ds| {
+            ds.batch(|tx| {
+                for (parent, link) in &added {
+                    tx.update_meta(parent, link, |meta| meta.groups.push(name.to_string()))?;
+                }
+                Ok(())
+            })
+        })?;
+        Ok(CommandResult::Value(style::success(&format!(
+            "Added {} link(s) to group '{name}'",
+            added.len()
+        ))))
+    }
+
+    fn remove(
+        &self,
+        ctx: &Context,
+        name: &str,
+        links: &[String],
+    ) -> Result<CommandResult, CommandError> {
+        let name = name.trim();
+        let mut groups = ctx.store.groups()?;
+        find(&groups, name)?;
+        let removed = resolve(ctx, links)?;
+        if let Some((parent, link)) = removed
+            .iter()
+            .find(|link| !members(&groups, name).contains(link))
+        {
+            return Err(
+                CommandError::from(format!("Link {parent}/{link} is not in group {name}"))
+                    .with_hint(&format!("Show its links with tap --group show {name}")),
+            );
+        }
+        for (_, links) in groups.iter_mut().filter(|(group, _)| group == name) {
+            links.retain(|link| !removed.contains(link));
+        }
+        groups.retain(|(_, links)| !links.is_empty());
+        ctx.store.write_groups(&groups)?;
+        ctx.store.write(|ds| {
+            ds.batch(|tx| {
+                for (parent, link) in &removed {
+                    tx.update_meta(parent, link, |meta| meta.groups.retain(|g| g != name))?;
+                }
+                Ok(())
+            })
+        })?;
+        Ok(CommandResult::Value(style::success(&format!(
+            "Took {} link(s) out of group '{name}'",
+            removed.len()
+        ))))
+    }
+
+    fn open(&self, ctx: &Context, name: &str, force: bool) -> Result<CommandResult, CommandError> {
+        let name = name.trim();
+        let links = values(ctx, find(&ctx.store.groups()?, name)?)?;
+        OpenLimit::load().check_max(links.len(), force)?;
+        let mut rows = vec![];
+        for (parent, link, value) in links {
+            let value = value_to_open(&parent, &link, &value, &[])?;
+            if open_value(ctx, &parent, &link, &value)? {
+                rows.push(vec![format!("{parent}/{link}")]);
+            }
+        }
+        Ok(CommandResult::Table(Table {
+            title: format!("Opening group {name}:"),
+            rows,
+        }))
+    }
+
+    fn show(&self, ctx: &Context, name: &str) -> Result<CommandResult, CommandError> {
+        let name = name.trim();
+        let links = values(ctx, find(&ctx.store.groups()?, name)?)?;
+        if ctx.format.is_structured() {
+            return Ok(CommandResult::Json(Json::Array(
+                links
+                    .iter()
+                    .map(|(parent, link, value)| {
+                        Json::object([
+                            ("parent", Json::from(parent.as_str())),
+                            ("link", Json::from(link.as_str())),
+                            ("value", Json::from(value.as_str())),
+                        ])
+                    })
+                    .collect(),
+            )));
+        }
+        Ok(CommandResult::Table(Table {
+            title: format!("Group {name}:"),
+            rows: links
+                .into_iter()
+                .map(|(parent, link, value)| vec![format!("{parent}/{link}"), value])
+                .collect(),
+        }))
+    }
+
+    fn list(&self, ctx: &Context) -> Result<CommandResult, CommandError> {
+        let groups = ctx.store.groups()?;
+        if ctx.format.is_structured() {
+            return Ok(CommandResult::Json(Json::Array(
+                groups
+                    .iter()
+                    .map(|(name, links)| {
+                        Json::object([
+                            ("name", Json::from(name.as_str())),
+                            (
+                                "links",
+                                Json::Array(
+                                    links
+                                        .iter()
+                                        .map(|(parent, link)| {
+                                            Json::object([
+                                                ("parent", Json::from(parent.as_str())),
+                                                ("link", Json::from(link.as_str())),
+                                            ])
+                                        })
+                                        .collect(),
+                                ),
+                            ),
+                        ])
+                    })
+                    .collect(),
+            )));
+        }
+        if groups.is_empty() {
+            return Ok(CommandResult::Value(
+                "No groups yet, add links to one with tap --group add <Group> <Parent/Link...>"
+                    .to_string(),
+            ));
+        }
+        Ok(CommandResult::Table(Table {
+            title: "Groups:".to_string(),
+            rows: groups
+                .into_iter()
+                .map(|(name, links)| {
+                    let links: Vec<String> = links
+                        .iter()
+                        .map(|(parent, link)| format!("{parent}/{link}"))
+                        .collect();
+                    vec![name, links.join(" ")]
+                })
+                .collect(),
+        }))
+    }
+}
+
+/// The links of group `name`, which must exist
+fn find<'a>(groups: &'a Groups, name: &str) -> Result<&'a [(String, String)], CommandError> {
+    match members(groups, name) {
+        [] => Err(CommandError::from(format!("Group {name} not found"))
+            .with_hint("List the groups with tap --group show")),
+        links => Ok(links),
+    }
+}
+
+/// The parent entity and name of each `<Parent Entity>/<Link Name>` of `links`. Metadata is kept
+/// by parent entity, so aliases are resolved.
+fn resolve(ctx: &Context, links: &[String]) -> Result<Vec<(String, String)>, CommandError> {
+    links
+        .iter()
+        .map(|link| {
+            let (parent, link) = split_link(ctx, link)?;
+            Ok((ctx.store.resolve_alias(&parent)?.trim().to_string(), link))
+        })
+        .collect()
+}
+
+/// `links` with their values
+fn values(
+    ctx: &Context,
+    links: &[(String, String)],
+) -> Result<Vec<(String, String, String)>, CommandError> {
+    links
+        .iter()
+        .map(|(parent, link)| {
+            let (_, value) = ctx.store.read_link(parent, link)?;
+            Ok((parent.clone(), link.clone(), value))
+        })
+        .collect()
+}
+
+/// A group name is a single word that does not look like a flag
+fn validate_name(name: &str) -> Result<(), CommandError> {
+    let name = name.trim();
+    if name.is_empty() || name.starts_with('-') || name.contains(char::is_whitespace) {
+        return Err(CommandError::usage(format!(
+            "Group name '{name}' must be a single word that does not start with -"
+        )));
+    }
+    Ok(())
+}
+
+impl DisplayCommandAsRow for Group {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            (
+                "tap --group add oncall work/pagerduty infra/grafana-alerts",
+                "Add two links to the group oncall",
+            ),
+            (
+                "tap --group open oncall",
+                "Open the links of the group oncall",
+            ),
+            (
+                "tap --group show oncall",
+                "Show the links of the group oncall",
+            ),
+            (
+                "tap --group remove oncall infra/grafana-alerts",
+                "Take a link out of the group oncall",
+            ),
+            ("tap --group show", "List all groups"),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::CommandErrorKind;
+
+    fn ctx_with_links() -> Context {
+        Context::in_memory(vec![
+            (
+                "work".to_string(),
+                vec![
+                    ("pagerduty".to_string(), "https://pagerduty.com".to_string()),
+                    ("ci".to_string(), "https://ci.internal".to_string()),
+                ],
+            ),
+            (
+                "infra".to_string(),
+                vec![(
+                    "grafana-alerts".to_string(),
+                    "https://grafana.internal/alerts".to_string(),
+                )],
+            ),
+        ])
+    }
+
+    fn group(ctx: &mut Context, args: &[&str]) -> Result<CommandResult, CommandError> {
+        Group::default().run(ctx, args.iter().map(|a| a.to_string()).collect())
+    }
+
+    fn row(link: &str, value: &str) -> Vec<String> {
+        vec![link.to_string(), value.to_string()]
+    }
+
+    #[test]
+    fn test_group_run_expected_help_arg() {
+        let cmd = Group::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_group_run_unexpected_args() {
+        let expected: Result<CommandResult, CommandError> = Err(Group::default().usage_error());
+        for args in [
+            &[][..],
+            &["add", "oncall"],
+            &["remove", "oncall"],
+            &["open"],
+            &["show", "oncall", "work/ci"],
+            &["tag", "oncall"],
+        ] {
+            assert_eq!(group(&mut ctx_with_links(), args), expected, "{args:?}");
+        }
+    }
+
+    #[test]
+    fn test_group_add_and_show() {
+        let mut ctx = ctx_with_links();
+        group(
+            &mut ctx,
+            &["add", "oncall", "work/pagerduty", "infra/grafana-alerts"],
+        )
+        .unwrap();
+        // Links in the group already are not added again
+        group(&mut ctx, &["add", "oncall", "work/ci", "work/pagerduty"]).unwrap();
+        assert_eq!(
+            ctx.store.read_meta("work").unwrap()["ci"].groups,
+            ["oncall"]
+        );
+        assert_eq!(
+            group(&mut ctx, &["show", "oncall"]),
+            Ok(CommandResult::Table(Table {
+                title: "Group oncall:".to_string(),
+                rows: vec![
+                    row("work/pagerduty", "https://pagerduty.com"),
+                    row("infra/grafana-alerts", "https://grafana.internal/alerts"),
+                    row("work/ci", "https://ci.internal"),
+                ],
+            }))
+        );
+        assert_eq!(
+            group(&mut ctx, &["open", "oncall"]),
+            Ok(CommandResult::Table(Table {
+                title: "Opening group oncall:".to_string(),
+                rows: vec![
+                    vec!["work/pagerduty".to_string()],
+                    vec!["infra/grafana-alerts".to_string()],
+                    vec!["work/ci".to_string()],
+                ],
+            }))
+        );
+    }
+
+    #[test]
+    fn test_group_add_errors() {
+        let mut ctx = ctx_with_links();
+        let err = group(&mut ctx, &["add", "on call", "work/ci"]).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::Usage);
+        let err = group(&mut ctx, &["add", "oncall", "work/cj"]).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::LinkNotFound);
+        assert_eq!(ctx.store.groups().unwrap(), Groups::new());
+    }
+
+    #[test]
+    fn test_group_remove() {
+        let mut ctx = ctx_with_links();
+        group(&mut ctx, &["add", "oncall", "work/pagerduty", "work/ci"]).unwrap();
+        assert!(group(&mut ctx, &["remove", "oncall", "infra/grafana-alerts"]).is_err());
+        group(&mut ctx, &["remove", "oncall", "work/ci"]).unwrap();
+        assert!(
+            ctx.store.read_meta("work").unwrap()["pagerduty"].groups == ["oncall"]
+                && !ctx.store.read_meta("work").unwrap().contains_key("ci")
+        );
+        group(&mut ctx, &["remove", "oncall", "work/pagerduty"]).unwrap();
+        // A group without links is gone
+        assert_eq!(ctx.store.groups().unwrap(), Groups::new());
+        assert!(group(&mut ctx, &["open", "oncall"]).is_err());
+    }
+
+    #[test]
+    fn test_group_drops_deleted_links() {
+        let mut ctx = ctx_with_links();
+        group(
+            &mut ctx,
+            &["add", "oncall", "work/pagerduty", "infra/grafana-alerts"],
+        )
+        .unwrap();
+        ctx.store
+            .write(|ds| ds.delete("work".to_string(), Some("pagerduty".to_string())))
+            .unwrap();
+        assert_eq!(
+            ctx.store.groups().unwrap(),
+            [(
+                "oncall".to_string(),
+                vec![("infra".to_string(), "grafana-alerts".to_string())]
+            )]
+        );
+        assert_eq!(
+            group(&mut ctx, &["show", "oncall"]),
+            Ok(CommandResult::Table(Table {
+                title: "Group oncall:".to_string(),
+                rows: vec![row(
+                    "infra/grafana-alerts",
+                    "https://grafana.internal/alerts"
+                )],
+            }))
+        );
+    }
+
+    #[test]
+    fn test_group_list() {
+        let mut ctx = ctx_with_links();
+        assert!(matches!(
+            group(&mut ctx, &["show"]),
+            Ok(CommandResult::Value(_))
+        ));
+        group(&mut ctx, &["add", "oncall", "work/pagerduty"]).unwrap();
+        group(&mut ctx, &["add", "builds", "work/ci"]).unwrap();
+        assert_eq!(
+            group(&mut ctx, &["show"]),
+            Ok(CommandResult::Table(Table {
+                title: "Groups:".to_string(),
+                rows: vec![
+                    vec!["oncall".to_string(), "work/pagerduty".to_string()],
+                    vec!["builds".to_string(), "work/ci".to_string()],
+                ],
+            }))
+        );
+    }
+}
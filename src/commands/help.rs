@@ -1,12 +1,16 @@
 use crate::{
-    commands::{Command, CommandResult, display_commands, display_version},
+    commands::{
+        Command, CommandError, CommandResult, ListedCommand, display_commands, display_exit_codes,
+        display_version, registry,
+    },
     utils::cli_usage_table::DisplayCommandAsRow,
+    utils::context::Context,
 };
 
 pub(crate) struct Help {
     name: String,
     description: String,
-    args: [String; 0],
+    args: [String; 1],
 }
 
 impl Default for Help {
@@ -14,7 +18,7 @@ impl Default for Help {
         Self {
             name: "--help".to_string(),
             description: "Display this help message".to_string(),
-            args: [],
+            args: ["[Command]".to_string()],
         }
     }
 }
@@ -26,18 +30,35 @@ impl Command for Help {
 
     fn help_message(&self) -> String {
         format!(
-            "{}\n{}\n\n{}",
+            "{}\n{}\n\n{}\nSee the details of a command with tap --help <command>, e.g. tap --help add\n\n{}",
             display_version(),
             env!("CARGO_PKG_DESCRIPTION"),
             display_commands(),
+            display_exit_codes(),
         )
     }
 
-    fn run(&self, args: Vec<String>) -> Result<CommandResult, String> {
-        if !args.is_empty() {
-            Err(self.error_message())
-        } else {
-            Ok(CommandResult::Value(self.help_message()))
+    fn run(&self, _ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        match args.as_slice() {
+            [] => Ok(CommandResult::Value(self.help_message())),
+            [topic] => {
+                let commands = registry();
+                match commands
+                    .iter()
+                    .find(|cmd| topics(cmd.as_ref()).contains(topic))
+                {
+                    Some(cmd) => Ok(CommandResult::Value(cmd.help_message())),
+                    None => Err(CommandError::usage(format!(
+                        "unknown help topic {topic}, the topics are: {}",
+                        commands
+                            .iter()
+                            .filter_map(|cmd| topics(cmd.as_ref()).last().cloned())
+                            .collect::<Vec<String>>()
+                            .join(", ")
+                    ))),
+                }
+            }
+            _ => Err(self.usage_error()),
         }
     }
 }
@@ -51,21 +72,47 @@ impl DisplayCommandAsRow for Help {
         self.description.clone()
     }
 
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            ("tap --help", "Show every command"),
+            ("tap --help add", "Show the details of the add command"),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
     fn name(&self) -> String {
         self.name.clone()
     }
 }
 
+/// The ways a command can be named as a help topic, e.g. `-a`, `--add`, and `a` or `add` for
+/// `-a, --add`. The last one is the plain long name, like `add`.
+pub(in crate::commands) fn topics(cmd: &dyn ListedCommand) -> Vec<String> {
+    let mut topics = vec![];
+    for name in cmd.name().split(", ") {
+        let plain = name
+            .trim_start_matches('-')
+            .trim_matches(['<', '>'])
+            .to_lowercase();
+        topics.push(name.to_string());
+        topics.push(plain);
+    }
+    topics
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::commands::{add::Add, here::Here, parent_entity::ParentEntity};
 
     #[test]
     fn test_help_unexpected_args() {
         let args = vec!["--help".to_string(), "me".to_string()];
         let cmd = Help::default();
-        let expected: Result<CommandResult, String> = Err(cmd.error_message());
-        let res = cmd.run(args);
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        let res = cmd.run(&mut Context::default(), args);
         assert_eq!(res, expected);
     }
 
@@ -73,8 +120,52 @@ mod tests {
     fn test_help_run() {
         let args = vec![];
         let cmd = Help::default();
-        let expected: Result<CommandResult, String> = Ok(CommandResult::Value(cmd.help_message()));
-        let res = cmd.run(args);
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        let res = cmd.run(&mut Context::default(), args);
         assert_eq!(res, expected);
     }
+
+    #[test]
+    fn test_help_run_topic() {
+        let cmd = Help::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(Add::default().help_message()));
+        for topic in ["add", "--add", "-a"] {
+            assert_eq!(
+                cmd.run(&mut Context::default(), vec![topic.to_string()]),
+                expected
+            );
+        }
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["here".to_string()]),
+            Ok(CommandResult::Value(Here::default().help_message()))
+        );
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["parent".to_string()]),
+            Ok(CommandResult::Value(ParentEntity::default().help_message()))
+        );
+    }
+
+    #[test]
+    fn test_help_run_unknown_topic() {
+        let cmd = Help::default();
+        let err = cmd
+            .run(&mut Context::default(), vec!["nope".to_string()])
+            .unwrap_err();
+        assert_eq!(err.kind, crate::commands::CommandErrorKind::Usage);
+        assert!(err.message.starts_with(
+            "unknown help topic nope, the topics are: parent, here, open, last, session, group, random, fzf, add, delete, show"
+        ));
+    }
+
+    #[test]
+    fn test_help_fits_100_columns() {
+        let help = Help::default().help_message();
+        for line in help.lines() {
+            assert!(line.chars().count() <= 100, "too wide: {line}");
+        }
+        assert!(help.contains("[Link...] [Open options]"));
+        assert!(help.contains("Open options:\n [--exact]"));
+    }
 }
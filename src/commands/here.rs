@@ -1,9 +1,9 @@
-use crate::utils::command::get_current_directory_name;
-use crate::utils::os_implementations::open_link;
-use crate::utils::tap_data_store::ReadDataStore;
+use crate::utils::command::{Args, get_current_directory_name};
 use crate::{
-    commands::{Command, CommandResult},
+    commands::open::{OPTIONS_PLACEHOLDER, OpenOptions, open, options_help, options_usage},
+    commands::{Command, CommandError, CommandResult, display_examples},
     utils::cli_usage_table::DisplayCommandAsRow,
+    utils::context::Context,
 };
 
 pub(crate) struct Here {
@@ -17,68 +17,75 @@ impl Default for Here {
         Self {
             name: "here".to_string(),
             description: "Open 1+ links (uses folder name)".to_string(),
-            args: ["[Link]".to_string()],
+            args: ["[Link...]".to_string()],
         }
     }
 }
 
 impl Command for Here {
     fn error_message(&self) -> String {
-        "expected 0-1 arguments, see the Usage section with tap here --help".to_string()
+        "expected 0 or more arguments, see the Usage section with tap here --help".to_string()
     }
 
     fn help_message(&self) -> String {
         let mut s: String = "".to_string();
-        s.push_str("Tap here uses the current working directory as the Parent Entity and will open either all or a specific link.\n\n");
-        s.push_str("Command Structure: tap here [Link Name]\n");
-        s.push_str("Example Usage: \n\n");
-        s.push_str("  - Open all Links: tap here\n");
-        s.push_str("  - Open specific Link: tap here google\n");
+        s.push_str("Tap here uses the current working directory as the Parent Entity and will open either all, some, or a specific link.\n\n");
+        s.push_str(&format!(
+            "Command Structure: tap here [Link Name...] {}\n",
+            options_usage()
+        ));
+        s.push_str("The Link Name can be shortened to the start of its name, as long as only one link starts that way. Pass --exact to only open the link as typed.\n");
+        s.push_str(&options_help());
+        s.push_str("The same links are opened by tap --open here [Link Name...], which can be clearer in scripts.\n");
+        s.push_str(&display_examples(&self.examples()));
         s
     }
 
-    fn run(&self, args: Vec<String>) -> Result<CommandResult, String> {
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let mut args = Args::new(args);
+        let opts = OpenOptions::take(&mut args)?.in_current_directory();
         match args.len() {
-            0 => {
-                let parent_entity = get_current_directory_name().map_err(|e| e.to_string())?;
-                let ds =
-                    ReadDataStore::new(None, parent_entity.clone()).map_err(|e| e.to_string())?;
-                let res = ds.read_parent(&parent_entity).map_err(|e| e.to_string())?;
-                let mut res_str = "Opening links: [".to_string();
-                for (link, val) in res.iter() {
-                    open_link(val).map_err(|e| e.to_string())?;
-                    res_str.push_str(format!("{link},").as_str());
-                }
-                res_str.push(']');
-                Ok(CommandResult::Value(res_str))
+            1 if args.is_flag(0, "--help") => Ok(CommandResult::Value(self.help_message())),
+            n if n == 0 || opts.names_only() => {
+                open(ctx, &get_current_directory_name()?, &args, &opts)
             }
-            1 => match args[0].as_str() {
-                "--help" => Ok(CommandResult::Value(self.help_message())),
-                link => {
-                    let parent_entity = get_current_directory_name().map_err(|e| e.to_string())?;
-                    let ds = ReadDataStore::new(None, parent_entity.to_string())
-                        .map_err(|e| e.to_string())?;
-                    let (_, val) = ds
-                        .read_link(&parent_entity, link)
-                        .map_err(|e| e.to_string())?;
-                    open_link(&val).map_err(|e| e.to_string())?;
-                    Ok(CommandResult::Value("Opening link...".to_string()))
-                }
-            },
-            _ => Err(self.error_message()),
+            _ => Err(self.usage_error()),
         }
     }
 }
 
 impl DisplayCommandAsRow for Here {
     fn args(&self) -> Vec<String> {
-        self.args.to_vec()
+        let mut args = self.args.to_vec();
+        args.push(OPTIONS_PLACEHOLDER.to_string());
+        args
     }
 
     fn description(&self) -> String {
         self.description.clone()
     }
 
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            ("tap here", "Open all Links"),
+            ("tap here google", "Open specific Link"),
+            ("tap here google yahoo", "Open several Links"),
+            ("tap here --pinned", "Open the pinned Links"),
+            (
+                "tap here google --copy",
+                "Put the value of a Link on the clipboard",
+            ),
+            ("tap here --all", "Open all Links, even with a default Link"),
+            (
+                "tap here --pick",
+                "Pick the Links to open from a numbered list",
+            ),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
     fn name(&self) -> String {
         self.name.clone()
     }
@@ -87,46 +94,116 @@ impl DisplayCommandAsRow for Here {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::commands::{CommandErrorKind, Table};
+
+    fn ctx_with_links(parent: &str) -> Context {
+        Context::in_memory(vec![(
+            parent.to_string(),
+            vec![
+                ("google".to_string(), "https://google.com".to_string()),
+                ("yahoo".to_string(), "https://yahoo.com".to_string()),
+            ],
+        )])
+    }
 
     #[test]
     fn test_here_run_expected_help_arg() {
         let args: Vec<String> = vec!["--help".to_string()];
         let cmd = Here::default();
-        let expected: Result<CommandResult, String> = Ok(CommandResult::Value(cmd.help_message()));
-        let res = cmd.run(args);
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        let res = cmd.run(&mut Context::default(), args);
         assert_eq!(res, expected);
     }
 
     #[test]
     fn test_here_run_unexpected_args() {
-        let args: Vec<String> = vec!["random".to_string(), "random2".to_string()];
+        let args: Vec<String> = vec!["random".to_string(), "--all".to_string()];
         let cmd = Here::default();
-        let expected: Result<CommandResult, String> = Err(cmd.error_message());
-        let res = cmd.run(args);
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        let res = cmd.run(&mut Context::default(), args);
         assert_eq!(res, expected);
     }
 
     #[test]
-    #[ignore = "GH-45: Should be an integration test due to DataStore dependency & os dependency"]
     fn test_here_run_all_links() {
         let args: Vec<String> = vec![];
         let cmd = Here::default();
-        let expected: Result<CommandResult, String> = Ok(CommandResult::Value(
-            "TODO: Implement here functionality".to_string(),
-        ));
-        let res = cmd.run(args);
+        let mut ctx = ctx_with_links(&get_current_directory_name().unwrap());
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Table(Table {
+            title: "Opening links:".to_string(),
+            rows: vec![vec!["google".to_string()], vec!["yahoo".to_string()]],
+        }));
+        let res = cmd.run(&mut ctx, args);
         assert_eq!(res, expected);
     }
 
     #[test]
-    #[ignore = "GH-45: Should be an integration test due to DataStore dependency & os dependency"]
     fn test_here_run_specific_link() {
         let args: Vec<String> = vec!["google".to_string()];
         let cmd = Here::default();
-        let expected: Result<CommandResult, String> = Ok(CommandResult::Value(
-            "TODO: Implement open functionality for here with Link Name google".to_string(),
-        ));
-        let res = cmd.run(args);
+        let mut ctx = ctx_with_links(&get_current_directory_name().unwrap());
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Silent);
+        let res = cmd.run(&mut ctx, args);
         assert_eq!(res, expected);
     }
+
+    #[test]
+    fn test_here_run_copy() {
+        let cmd = Here::default();
+        let mut ctx = ctx_with_links(&get_current_directory_name().unwrap());
+        assert_eq!(
+            cmd.run(&mut ctx, vec!["google".to_string(), "--copy".to_string()]),
+            Ok(CommandResult::Value("https://google.com".to_string()))
+        );
+        // Every link is copied with its name, one per line
+        assert_eq!(
+            cmd.run(&mut ctx, vec!["--copy".to_string()]),
+            Ok(CommandResult::Value(
+                "google\thttps://google.com\nyahoo\thttps://yahoo.com".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_here_run_parent_entity_not_found() {
+        let args: Vec<String> = vec![];
+        let cmd = Here::default();
+        let mut ctx = ctx_with_links("not-the-current-directory");
+        assert!(cmd.run(&mut ctx, args).is_err());
+    }
+
+    #[test]
+    fn test_here_run_unique_link_prefix() {
+        let args: Vec<String> = vec!["goo".to_string()];
+        let cmd = Here::default();
+        let mut ctx = ctx_with_links(&get_current_directory_name().unwrap());
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Silent);
+        assert_eq!(cmd.run(&mut ctx, args), expected);
+    }
+
+    #[test]
+    fn test_here_run_exact_flag_disables_prefix() {
+        let args: Vec<String> = vec!["goo".to_string(), "--exact".to_string()];
+        let cmd = Here::default();
+        let mut ctx = ctx_with_links(&get_current_directory_name().unwrap());
+        let err = cmd.run(&mut ctx, args).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::LinkNotFound);
+    }
+
+    #[test]
+    fn test_here_run_several_links() {
+        let args: Vec<String> = vec!["google".to_string(), "yahoo".to_string()];
+        let current_dir_name = get_current_directory_name().unwrap();
+        let cmd = Here::default();
+        let mut ctx = ctx_with_links(&current_dir_name);
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Table(Table {
+            title: "Opened 2 of 2 links:".to_string(),
+            rows: vec![
+                vec!["google".to_string(), "opened".to_string()],
+                vec!["yahoo".to_string(), "opened".to_string()],
+            ],
+        }));
+        assert_eq!(cmd.run(&mut ctx, args), expected);
+    }
 }
@@ -0,0 +1,295 @@
+use crate::{
+    commands::{Command, CommandError, CommandResult, Table, display_examples},
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::command::Args,
+    utils::context::Context,
+    utils::history::{self, Entry, history_path_for},
+    utils::json::Json,
+};
+
+/// The entries shown when no count is given
+const DEFAULT_COUNT: usize = 20;
+
+pub(crate) struct History {
+    name: String,
+    description: String,
+    args: [String; 2],
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self {
+            name: "--history".to_string(),
+            description: "Show the latest changes to the links".to_string(),
+            args: [
+                "[Count]".to_string(),
+                "[--parent <Parent Entity>]".to_string(),
+            ],
+        }
+    }
+}
+
+impl Command for History {
+    fn error_message(&self) -> String {
+        "expected 0-1 arguments, see the Usage section with tap --history --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --history command shows the latest changes to the links, newest last. Every link that is added, upserted, deleted, or imported is recorded in .tap_history next to the data file, with the time of the change and the old and new value. Once the history file passes 1 MB it is moved to .tap_history.1, replacing the previous one, so older changes are eventually forgotten.\n\n");
+        s.push_str(&format!("Pass a count to show that many changes instead of {DEFAULT_COUNT}, and --parent to only show the changes to the links of one Parent Entity.\n\n"));
+        s.push_str("Command Structure: tap --history [Count] [--parent <Parent Entity>]\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let mut args = Args::new(args);
+        if args.len() == 1 && args.is_flag(0, "--help") {
+            return Ok(CommandResult::Value(self.help_message()));
+        }
+        let parent = args.take_option("--parent");
+        let count = match args.len() {
+            0 => DEFAULT_COUNT,
+            1 => args[0].parse::<usize>().map_err(|_| {
+                CommandError::usage(format!(
+                    "expected the number of changes to show, got {}",
+                    args[0]
+                ))
+            })?,
+            _ => return Err(self.usage_error()),
+        };
+        let [(data, _), _] = ctx.store.options().resolve_paths()?;
+        let path = history_path_for(&data);
+        let mut entries = history::read(&path)
+            .map_err(|e| format!("Could not read history {}: {e}", path.display()))?;
+        if let Some(parent) = &parent {
+            entries.retain(|entry| entry.parent == parent.trim());
+        }
+        let entries = &entries[entries.len().saturating_sub(count)..];
+        if ctx.format.is_structured() {
+            return Ok(CommandResult::Json(history_json(entries)));
+        }
+        if entries.is_empty() {
+            return Ok(CommandResult::Value(match parent {
+                Some(parent) => format!("No changes to the links of {parent} were recorded"),
+                None => "No changes were recorded yet".to_string(),
+            }));
+        }
+        Ok(CommandResult::Table(Table {
+            title: format!("Last {} change(s):", entries.len()),
+            rows: entries
+                .iter()
+                .map(|entry| {
+                    vec![
+                        entry.time.clone(),
+                        entry.operation.clone(),
+                        entry.parent.clone(),
+                        entry.link.clone(),
+                        display_change(entry),
+                    ]
+                })
+                .collect(),
+        }))
+    }
+}
+
+/// The value that was added or deleted, or `old -> new` when a value was replaced
+fn display_change(entry: &Entry) -> String {
+    match (entry.old.as_str(), entry.new.as_str()) {
+        ("", new) => new.to_string(),
+        (old, "") => old.to_string(),
+        (old, new) => format!("{old} -> {new}"),
+    }
+}
+
+/// `[{"time":"2024-06-01T12:30:05Z","operation":"add","parent":"work","link":"ci","old":"","new":"..."}]`
+fn history_json(entries: &[Entry]) -> Json {
+    Json::Array(
+        entries
+            .iter()
+            .map(|entry| {
+                Json::object([
+                    ("time", Json::from(entry.time.as_str())),
+                    ("operation", Json::from(entry.operation.as_str())),
+                    ("parent", Json::from(entry.parent.as_str())),
+                    ("link", Json::from(entry.link.as_str())),
+                    ("old", Json::from(entry.old.as_str())),
+                    ("new", Json::from(entry.new.as_str())),
+                ])
+            })
+            .collect(),
+    )
+}
+
+impl DisplayCommandAsRow for History {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            ("tap --history", "Show the latest changes"),
+            ("tap --history 5", "Show the last 5 changes"),
+            (
+                "tap --history --parent work",
+                "Show the changes to the links of work",
+            ),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::CommandErrorKind;
+    use crate::utils::format::Format;
+    use crate::utils::tap_data_store::TestDataFile;
+    use std::path::Path;
+
+    const LINKS: &str = "work->\n  ci|https://ci.internal\n";
+
+    fn history(data: &Path, args: &[&str]) -> Result<CommandResult, CommandError> {
+        History::default().run(
+            &mut Context::with_file(data.to_path_buf()),
+            args.iter().map(|a| a.to_string()).collect(),
+        )
+    }
+
+    /// Adds a link, replaces the value of another and deletes a parent entity
+    fn make_changes(data: &Path) {
+        let ctx = Context::with_file(data.to_path_buf());
+        ctx.store
+            .write(|ds| {
+                ds.batch(|tx| {
+                    tx.add_link("news", "bbc", "https://bbc.com")?;
+                    tx.upsert_link("work", "ci", "https://ci.example.com")
+                })
+            })
+            .unwrap();
+        ctx.store
+            .write(|ds| ds.delete("news".to_string(), None))
+            .unwrap();
+    }
+
+    fn rows(res: Result<CommandResult, CommandError>) -> Vec<Vec<String>> {
+        match res {
+            // The time of each change varies, so it is left out
+            Ok(CommandResult::Table(table)) => table
+                .rows
+                .into_iter()
+                .map(|row| row[1..].to_vec())
+                .collect(),
+            res => panic!("expected a table, got {res:?}"),
+        }
+    }
+
+    #[test]
+    fn test_history_run_expected_help_arg() {
+        let cmd = History::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_history_run_unexpected_args() {
+        let cmd = History::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(
+            cmd.run(
+                &mut Context::default(),
+                vec!["1".to_string(), "2".to_string()]
+            ),
+            expected
+        );
+        let e = cmd
+            .run(&mut Context::default(), vec!["many".to_string()])
+            .unwrap_err();
+        assert_eq!(e.kind, CommandErrorKind::Usage);
+    }
+
+    #[test]
+    fn test_history_records_changes() {
+        let data = TestDataFile::new(LINKS);
+        assert_eq!(
+            history(&data, &[]),
+            Ok(CommandResult::Value(
+                "No changes were recorded yet".to_string()
+            ))
+        );
+        make_changes(&data);
+        assert_eq!(
+            rows(history(&data, &[])),
+            [
+                ["add", "news", "bbc", "https://bbc.com"],
+                [
+                    "upsert",
+                    "work",
+                    "ci",
+                    "https://ci.internal -> https://ci.example.com"
+                ],
+                ["delete", "news", "bbc", "https://bbc.com"],
+            ]
+        );
+        assert_eq!(
+            rows(history(&data, &["1"])),
+            [["delete", "news", "bbc", "https://bbc.com"]]
+        );
+        assert_eq!(
+            rows(history(&data, &["--parent", "work"])),
+            [[
+                "upsert",
+                "work",
+                "ci",
+                "https://ci.internal -> https://ci.example.com"
+            ]]
+        );
+    }
+
+    #[test]
+    fn test_history_skips_failed_changes() {
+        let data = TestDataFile::new(LINKS);
+        let ctx = Context::with_file(data.clone());
+        assert!(
+            ctx.store
+                .write(|ds| ds.add_link(
+                    "work".to_string(),
+                    "ci".to_string(),
+                    "https://ci.example.com".to_string()
+                ))
+                .is_err()
+        );
+        assert!(!history_path_for(&data).exists());
+    }
+
+    #[test]
+    fn test_history_json() {
+        let data = TestDataFile::new(LINKS);
+        make_changes(&data);
+        let mut ctx = Context {
+            format: Format::Json,
+            ..Context::with_file(data.clone())
+        };
+        let Ok(CommandResult::Json(Json::Array(entries))) =
+            History::default().run(&mut ctx, vec!["--parent".to_string(), "news".to_string()])
+        else {
+            panic!("expected a JSON array");
+        };
+        assert_eq!(entries.len(), 2);
+    }
+}
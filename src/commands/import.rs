@@ -1,7 +1,8 @@
 use crate::{
-    commands::{Command, CommandResult},
+    commands::{Command, CommandError, CommandResult, display_examples},
     utils::cli_usage_table::DisplayCommandAsRow,
-    utils::tap_data_store::{DataStore, ImportType},
+    utils::context::Context,
+    utils::tap_data_store::ImportType,
 };
 use std::path::PathBuf;
 
@@ -34,20 +35,21 @@ impl Command for Import {
 
     fn help_message(&self) -> String {
         format!(
-            "Tap import imports a bookmark file from one of the following browsers into Tap. Import will overwrite existing links:\n{}\n\nExample Usage: {}",
+            "Tap import imports a bookmark file from one of the following browsers into Tap. Import will overwrite existing links:\n{}\n\nCommand Structure: {}\n{}",
             "Chrome, Edge, Firefox, Opera, Safari, Tap",
-            "tap --import <Chrome | Edge | Firefox | Opera | Safari | Tap> <bookmark file>"
+            "tap --import <Chrome | Edge | Firefox | Opera | Safari | Tap> <bookmark file>",
+            display_examples(&self.examples())
         )
     }
 
-    fn run(&self, args: Vec<String>) -> Result<CommandResult, String> {
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
         match args.len() {
-            0 => Err(self.error_message()),
+            0 => Err(self.usage_error()),
             1 => {
                 if args[0] == "--help" {
                     Ok(CommandResult::Value(self.help_message()))
                 } else {
-                    Err(self.error_message())
+                    Err(self.usage_error())
                 }
             }
             2 => match (args[0].as_str(), args[1].as_str()) {
@@ -67,14 +69,13 @@ impl Command for Import {
                     "TODO: Implement import functionality from Safari: {f}"
                 ))),
                 ("Tap", f) => {
-                    let mut ds = DataStore::new(None).map_err(|e| e.to_string())?;
-                    ds.import(PathBuf::from(f), ImportType::Tap)
-                        .map_err(|e| e.to_string())?;
+                    ctx.store
+                        .write(|ds| ds.import(PathBuf::from(f), ImportType::Tap))?;
                     Ok(CommandResult::Value("Import complete".to_string()))
                 }
-                (bad_browser, _) => Err(self.bad_browser_message(bad_browser)),
+                (bad_browser, _) => Err(CommandError::usage(self.bad_browser_message(bad_browser))),
             },
-            _ => Err(self.error_message()),
+            _ => Err(self.usage_error()),
         }
     }
 }
@@ -88,6 +89,22 @@ impl DisplayCommandAsRow for Import {
         self.description.clone()
     }
 
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            (
+                "tap --import Firefox ~/Downloads/bookmarks.html",
+                "Import the bookmarks exported from Firefox",
+            ),
+            (
+                "tap --import Tap ~/links.tap",
+                "Import the links of a tap file",
+            ),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
     fn name(&self) -> String {
         self.name.clone()
     }
@@ -101,8 +118,9 @@ mod tests {
     fn test_import_expected_help_arg() {
         let args: Vec<String> = vec!["--help".to_string()];
         let cmd = Import::default();
-        let expected: Result<CommandResult, String> = Ok(CommandResult::Value(cmd.help_message()));
-        let res = cmd.run(args);
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        let res = cmd.run(&mut Context::default(), args);
         assert_eq!(res, expected);
     }
 
@@ -110,8 +128,8 @@ mod tests {
     fn test_import_unexpected_args() {
         let args: Vec<String> = vec!["random".to_string()];
         let cmd = Import::default();
-        let expected: Result<CommandResult, String> = Err(cmd.error_message());
-        let res = cmd.run(args);
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        let res = cmd.run(&mut Context::default(), args);
         assert_eq!(res, expected);
     }
 
@@ -119,91 +137,110 @@ mod tests {
     fn test_import_run_bad_browser() {
         let args: Vec<String> = vec!["bad browser".to_string(), "path".to_string()];
         let cmd = Import::default();
-        let expected: Result<CommandResult, String> = Err(cmd.bad_browser_message("bad browser"));
-        let res = cmd.run(args);
+        let expected: Result<CommandResult, CommandError> =
+            Err(CommandError::usage(cmd.bad_browser_message("bad browser")));
+        let res = cmd.run(&mut Context::default(), args);
         assert_eq!(res, expected);
     }
 
     #[test]
     fn test_import_run_chrome() {
         let cmd = Import::default();
-        let args = vec!["Chrome", "./test.json"]
+        let args = ["Chrome", "./test.json"]
             .iter()
             .map(|s| s.to_string())
             .collect();
         let expected = CommandResult::Value(
             "TODO: Implement import functionality from Chrome: ./test.json".to_string(),
         );
-        let res = cmd.run(args).expect("Could not display import");
+        let res = cmd
+            .run(&mut Context::default(), args)
+            .expect("Could not display import");
         assert_eq!(res, expected);
     }
 
     #[test]
     fn test_import_run_edge() {
         let cmd = Import::default();
-        let args = vec!["Edge", "./test.json"]
+        let args = ["Edge", "./test.json"]
             .iter()
             .map(|s| s.to_string())
             .collect();
         let expected = CommandResult::Value(
             "TODO: Implement import functionality from Edge: ./test.json".to_string(),
         );
-        let res = cmd.run(args).expect("Could not display import");
+        let res = cmd
+            .run(&mut Context::default(), args)
+            .expect("Could not display import");
         assert_eq!(res, expected);
     }
 
     #[test]
     fn test_import_run_firefox() {
         let cmd = Import::default();
-        let args = vec!["Firefox", "./test.json"]
+        let args = ["Firefox", "./test.json"]
             .iter()
             .map(|s| s.to_string())
             .collect();
         let expected = CommandResult::Value(
             "TODO: Implement import functionality from Firefox: ./test.json".to_string(),
         );
-        let res = cmd.run(args).expect("Could not display import");
+        let res = cmd
+            .run(&mut Context::default(), args)
+            .expect("Could not display import");
         assert_eq!(res, expected);
     }
 
     #[test]
     fn test_import_run_opera() {
         let cmd = Import::default();
-        let args = vec!["Opera", "./test.json"]
+        let args = ["Opera", "./test.json"]
             .iter()
             .map(|s| s.to_string())
             .collect();
         let expected = CommandResult::Value(
             "TODO: Implement import functionality from Opera: ./test.json".to_string(),
         );
-        let res = cmd.run(args).expect("Could not display import");
+        let res = cmd
+            .run(&mut Context::default(), args)
+            .expect("Could not display import");
         assert_eq!(res, expected);
     }
 
     #[test]
     fn test_import_run_safari() {
         let cmd = Import::default();
-        let args = vec!["Safari", "./test.json"]
+        let args = ["Safari", "./test.json"]
             .iter()
             .map(|s| s.to_string())
             .collect();
         let expected = CommandResult::Value(
             "TODO: Implement import functionality from Safari: ./test.json".to_string(),
         );
-        let res = cmd.run(args).expect("Could not display import");
+        let res = cmd
+            .run(&mut Context::default(), args)
+            .expect("Could not display import");
         assert_eq!(res, expected);
     }
 
     #[test]
-    #[ignore = "GH-45: Should be an integration test due to DataStore dependency"]
     fn test_import_run_tap() {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let path = std::env::temp_dir().join(format!("tap_import_{timestamp}.tap"));
+        std::fs::write(&path, "search-engines->\n  google|https://google.com\n").unwrap();
         let cmd = Import::default();
-        let args = vec!["Tap", "./test.tap"]
-            .iter()
-            .map(|s| s.to_string())
-            .collect();
+        let mut ctx = Context::in_memory(vec![]);
+        let args = vec!["Tap".to_string(), path.display().to_string()];
         let expected = CommandResult::Value("Import complete".to_string());
-        let res = cmd.run(args).expect("Could not display import");
+        let res = cmd.run(&mut ctx, args).expect("Could not import");
         assert_eq!(res, expected);
+        assert_eq!(
+            ctx.store.links("search-engines").unwrap(),
+            vec!["google".to_string()]
+        );
+        std::fs::remove_file(&path).unwrap();
     }
 }
@@ -1,12 +1,17 @@
 use crate::{
-    commands::{Command, CommandResult},
+    commands::{Command, CommandError, CommandResult, display_examples},
     utils::cli_usage_table::DisplayCommandAsRow,
+    utils::context::Context,
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
 };
 
 pub(crate) struct Init {
     name: String,
     description: String,
-    args: [String; 0],
+    args: [String; 1],
 }
 
 impl Default for Init {
@@ -14,7 +19,7 @@ impl Default for Init {
         Self {
             name: "-i, --init".to_string(),
             description: "Setup Tap and shell completions".to_string(),
-            args: [],
+            args: ["[zsh|bash|fish]".to_string()],
         }
     }
 }
@@ -27,23 +32,161 @@ impl Command for Init {
     fn help_message(&self) -> String {
         let mut s = String::new();
         s.push_str("Initializes Tap (Shell Auto-Completion, etc.).\n\n");
-        s.push_str("Example Usage: tap --init");
+        s.push_str("The completion script of the shell in $SHELL, or of the shell passed, is installed where the shell looks for completions:\n");
+        s.push_str("  - bash  $XDG_DATA_HOME/bash-completion/completions/tap (or ~/.local/share/bash-completion/completions/tap)\n");
+        s.push_str("  - fish  $XDG_CONFIG_HOME/fish/completions/tap.fish (or ~/.config/fish/completions/tap.fish)\n");
+        s.push_str("  - zsh   ~/.zsh/completions/_tap, add ~/.zsh/completions to fpath before compinit runs\n");
+        s.push_str("To print the script instead, for example when packaging tap, use tap --completions <shell>.\n\n");
+        s.push_str("Command Structure: tap --init [zsh|bash|fish]\n");
+        s.push_str(&display_examples(&self.examples()));
         s
     }
 
-    fn run(&self, args: Vec<String>) -> Result<CommandResult, String> {
-        match args.len() {
-            0 => todo!("Implement init Functionality"),
-            1 => {
-                if args[0] == "--help" {
-                    Ok(CommandResult::Value(self.help_message()))
-                } else {
-                    Err(self.error_message())
-                }
+    fn run(&self, _ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let shell = match args.as_slice() {
+            [] => Shell::from_env()?,
+            [flag] if flag == "--help" => return Ok(CommandResult::Value(self.help_message())),
+            [shell] => Shell::parse(shell)?,
+            _ => return Err(self.usage_error()),
+        };
+        let home = std::env::var_os("HOME")
+            .map(PathBuf::from)
+            .ok_or(CommandError::from(
+                "Could not find the home directory to install completions in, set HOME".to_string(),
+            ))?;
+        let path = shell.install(&home)?;
+        let mut s = format!(
+            "Installed {} completions to {}",
+            shell.name(),
+            path.display()
+        );
+        if shell == Shell::Zsh {
+            s.push_str("\nAdd fpath=(~/.zsh/completions $fpath) to ~/.zshrc before compinit runs, if it is not there yet");
+        }
+        Ok(CommandResult::Value(s))
+    }
+}
+
+/// A shell tap has a completion script for. `tap --completions` prints the script and
+/// `tap --init` installs it, both through `Shell::script`, so they never drift apart.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(in crate::commands) enum Shell {
+    Bash,
+    Fish,
+    Zsh,
+}
+
+impl Shell {
+    const ALL: [Shell; 3] = [Shell::Bash, Shell::Fish, Shell::Zsh];
+
+    /// ## Errors
+    /// - `CommandErrorKind::Usage` - if tap has no completion script for the shell
+    pub fn parse(name: &str) -> Result<Self, CommandError> {
+        Shell::ALL
+            .into_iter()
+            .find(|shell| shell.name() == name)
+            .ok_or_else(|| {
+                CommandError::usage(format!(
+                    "unknown shell {name}, the supported shells are: {}",
+                    Shell::ALL.map(|shell| shell.name()).join(", ")
+                ))
+            })
+    }
+
+    /// The shell of the user, from the `SHELL` environment variable
+    fn from_env() -> Result<Self, CommandError> {
+        let shell = std::env::var("SHELL").unwrap_or_default();
+        let name = Path::new(&shell)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        Shell::parse(&name).map_err(|e| e.with_hint("Pass the shell to use, e.g. tap --init zsh"))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Shell::Bash => "bash",
+            Shell::Fish => "fish",
+            Shell::Zsh => "zsh",
+        }
+    }
+
+    /// The completion script, which asks the hidden `tap __complete` command for the candidates
+    pub fn script(&self) -> &'static str {
+        match self {
+            Shell::Bash => {
+                r#"# bash completion for tap
+_tap() {
+    local IFS=$'\n'
+    COMPREPLY=($(tap __complete "$((COMP_CWORD - 1))" "${COMP_WORDS[@]:1}" 2>/dev/null))
+    # Nested parent entities are completed one segment at a time
+    [[ ${#COMPREPLY[@]} -eq 1 && ${COMPREPLY[0]} == */ ]] && compopt -o nospace
+}
+complete -o default -F _tap tap"#
+            }
+            Shell::Fish => {
+                r#"# fish completion for tap
+function __tap_complete
+    set -l words (commandline -opc)
+    set -e words[1]
+    tap __complete (count $words) $words (commandline -ct) 2>/dev/null
+end
+complete -c tap -f -a '(__tap_complete)'"#
+            }
+            Shell::Zsh => {
+                r#"#compdef tap
+# zsh completion for tap
+local -a candidates
+candidates=(${(f)"$(tap __complete $((CURRENT - 2)) ${words[2,-1]} 2>/dev/null)"})
+local -a groups
+groups=(${(M)candidates:#*/})
+compadd -S '' -a groups
+candidates=(${candidates:#*/})
+compadd -a candidates"#
             }
-            _ => Err(self.error_message()),
         }
     }
+
+    /// Where the shell looks for the completion script of tap
+    fn install_path(&self, home: &Path) -> PathBuf {
+        let xdg_home = |var: &str, default: &[&str]| {
+            std::env::var_os(var)
+                .map(PathBuf::from)
+                .filter(|p| p.is_absolute())
+                .unwrap_or_else(|| default.iter().fold(home.to_path_buf(), |p, d| p.join(d)))
+        };
+        match self {
+            Shell::Bash => xdg_home("XDG_DATA_HOME", &[".local", "share"])
+                .join("bash-completion")
+                .join("completions")
+                .join("tap"),
+            Shell::Fish => xdg_home("XDG_CONFIG_HOME", &[".config"])
+                .join("fish")
+                .join("completions")
+                .join("tap.fish"),
+            Shell::Zsh => home.join(".zsh").join("completions").join("_tap"),
+        }
+    }
+
+    /// Writes the completion script to `install_path`, replacing an older one
+    fn install(&self, home: &Path) -> Result<PathBuf, CommandError> {
+        let path = self.install_path(home);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| {
+                CommandError::from(format!(
+                    "Could not create completions directory {}: {e}",
+                    dir.display()
+                ))
+            })?;
+        }
+        fs::write(&path, format!("{}\n", self.script())).map_err(|e| {
+            CommandError::from(format!(
+                "Could not write completions to {}: {e}",
+                path.display()
+            ))
+        })?;
+        Ok(path)
+    }
 }
 
 impl DisplayCommandAsRow for Init {
@@ -55,6 +198,19 @@ impl DisplayCommandAsRow for Init {
         self.description.clone()
     }
 
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            (
+                "tap --init",
+                "Set up shell auto-completion for the shell in $SHELL",
+            ),
+            ("tap --init fish", "Set up shell auto-completion for fish"),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
     fn name(&self) -> String {
         self.name.clone()
     }
@@ -64,29 +220,58 @@ impl DisplayCommandAsRow for Init {
 mod tests {
     use super::*;
 
-    #[test]
-    #[should_panic] // TODO: remove after implementing init functionality
-    fn test_init_run_expected_args() {
-        let cmd = Init::default();
-        let args: Vec<String> = vec![];
-        let res = cmd.run(args);
-    }
-
     #[test]
     fn test_init_run_expected_help_arg() {
         let args: Vec<String> = vec!["--help".to_string()];
         let cmd = Init::default();
-        let expected: Result<CommandResult, String> = Ok(CommandResult::Value(cmd.help_message()));
-        let res = cmd.run(args);
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        let res = cmd.run(&mut Context::default(), args);
         assert_eq!(res, expected);
     }
 
     #[test]
     fn test_init_run_unexpected_args() {
-        let args: Vec<String> = vec!["random".to_string()];
+        let args: Vec<String> = vec!["zsh".to_string(), "random".to_string()];
         let cmd = Init::default();
-        let expected: Result<CommandResult, String> = Err(cmd.error_message());
-        let res = cmd.run(args);
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        let res = cmd.run(&mut Context::default(), args);
         assert_eq!(res, expected);
     }
+
+    #[test]
+    fn test_init_run_unknown_shell() {
+        let res = Init::default().run(&mut Context::default(), vec!["tcsh".to_string()]);
+        assert_eq!(
+            res,
+            Err(CommandError::usage(
+                "unknown shell tcsh, the supported shells are: bash, fish, zsh".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_shell_install() {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let home = std::env::temp_dir().join(format!("tap_init_home_{timestamp}"));
+        let path = Shell::Zsh.install(&home).unwrap();
+        assert_eq!(path, home.join(".zsh").join("completions").join("_tap"));
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            format!("{}\n", Shell::Zsh.script())
+        );
+        // Installing again replaces the script
+        assert_eq!(Shell::Zsh.install(&home).unwrap(), path);
+        fs::remove_dir_all(&home).unwrap();
+    }
+
+    #[test]
+    fn test_shell_scripts_use_complete() {
+        for shell in Shell::ALL {
+            assert!(shell.script().contains("tap __complete"), "{shell:?}");
+        }
+    }
 }
@@ -0,0 +1,294 @@
+use crate::{
+    commands::{
+        Command, CommandError, CommandErrorKind, CommandResult, Table, copy_value,
+        display_examples, open_value, value_to_open,
+    },
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::command::{Args, OpenLimit},
+    utils::context::Context,
+    utils::log,
+};
+
+pub(crate) struct Last {
+    name: String,
+    description: String,
+    args: [String; 4],
+}
+
+impl Default for Last {
+    fn default() -> Self {
+        Self {
+            name: "--last".to_string(),
+            description: "Reopen the links opened last".to_string(),
+            args: [
+                "[Count]".to_string(),
+                "[--print]".to_string(),
+                "[--copy]".to_string(),
+                "[--force]".to_string(),
+            ],
+        }
+    }
+}
+
+impl Command for Last {
+    fn error_message(&self) -> String {
+        "expected 0-1 arguments, see the Usage section with tap --last --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --last command opens the link tap opened most recently again, like after closing its tab by accident. Give a Count to open that many of the links opened last, the most recent first. Every link opened by tap is recorded, up to the last 50, in a recent file next to the data file. A recorded link that was deleted since is skipped with a note.\n\n");
+        s.push_str("Pass --print to print the values instead of opening them, and --copy to put them on the clipboard, like when opening links. Several links are printed as <Parent Entity>/<Link Name><TAB><Value>, one per line. Pass --force to open more links than max_open in the config file allows.\n\n");
+        s.push_str("Command Structure: tap --last [Count] [--print] [--copy] [--force]\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let mut args = Args::new(args);
+        let print = args.take_flag(&["--print"]);
+        let copy = args.take_flag(&["--copy"]);
+        let force = args.take_flag(&["--force"]);
+        if print && copy {
+            return Err(CommandError::usage(
+                "--print and --copy can not be used together".to_string(),
+            ));
+        }
+        let count = match args.len() {
+            0 => 1,
+            1 if args.is_flag(0, "--help") => return Ok(CommandResult::Value(self.help_message())),
+            1 => match args[0].parse() {
+                Ok(count) if count > 0 => count,
+                _ => {
+                    return Err(CommandError::usage(format!(
+                        "expected a number of links above 0, got {}",
+                        args[0]
+                    )));
+                }
+            },
+            _ => return Err(self.usage_error()),
+        };
+        let links = last_opened(ctx, count)?;
+        if print || copy {
+            let res = match links.as_slice() {
+                [(_, _, value)] => CommandResult::List(vec![value.clone()]),
+                links => CommandResult::Rows(
+                    links
+                        .iter()
+                        .map(|(parent, link, value)| {
+                            vec![format!("{parent}/{link}"), value.clone()]
+                        })
+                        .collect(),
+                ),
+            };
+            return Ok(match copy {
+                true => copy_value(&res.to_string()),
+                false => res,
+            });
+        }
+        OpenLimit::load().check_max(links.len(), force)?;
+        let mut rows = vec![];
+        for (parent, link, value) in links {
+            if open_value(ctx, &parent, &link, &value)? {
+                rows.push(vec![format!("{parent}/{link}")]);
+            }
+        }
+        Ok(CommandResult::Table(Table {
+            title: "Opening links:".to_string(),
+            rows,
+        }))
+    }
+}
+
+/// The parent entity, name, and value to open of up to `count` of the links opened last, the
+/// most recent first. Links deleted since they were opened are skipped.
+fn last_opened(ctx: &Context, count: usize) -> Result<Vec<(String, String, String)>, CommandError> {
+    let entries = ctx.store.recently_opened()?;
+    if entries.is_empty() {
+        return Err(CommandError {
+            kind: CommandErrorKind::LinkNotFound,
+            message: "No link was opened yet".to_string(),
+        });
+    }
+    let mut links = vec![];
+    for entry in entries.iter().rev() {
+        if links.len() == count {
+            break;
+        }
+        let value = match ctx.store.read_link(&entry.parent, &entry.link) {
+            Ok((_, value)) => value,
+            Err(e) => match CommandError::from(e) {
+                e if matches!(
+                    e.kind,
+                    CommandErrorKind::ParentNotFound | CommandErrorKind::LinkNotFound
+                ) =>
+                {
+                    log::note(format_args!(
+                        "Skipped {}/{}, it was deleted since it was opened",
+                        entry.parent, entry.link
+                    ));
+                    continue;
+                }
+                e => return Err(e),
+            },
+        };
+        let value = value_to_open(&entry.parent, &entry.link, &value, &[])?;
+        links.push((entry.parent.clone(), entry.link.clone(), value));
+    }
+    if links.is_empty() {
+        return Err(CommandError {
+            kind: CommandErrorKind::LinkNotFound,
+            message: "Every link opened recently was deleted since".to_string(),
+        });
+    }
+    Ok(links)
+}
+
+impl DisplayCommandAsRow for Last {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            ("tap --last", "Open the link opened last again"),
+            ("tap --last 3", "Open the last three links opened again"),
+            (
+                "tap --last --copy",
+                "Put the value of the link opened last on the clipboard",
+            ),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::open::Open;
+
+    fn ctx_with_links() -> Context {
+        Context::in_memory(vec![
+            (
+                "search-engines".to_string(),
+                vec![
+                    ("google".to_string(), "https://google.com".to_string()),
+                    ("yahoo".to_string(), "https://yahoo.com".to_string()),
+                ],
+            ),
+            (
+                "work".to_string(),
+                vec![("ci".to_string(), "https://ci.internal".to_string())],
+            ),
+        ])
+    }
+
+    fn run(ctx: &mut Context, cmd: &dyn Command, args: &[&str]) -> CommandResult {
+        cmd.run(ctx, args.iter().map(|a| a.to_string()).collect())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_last_run_expected_help_arg() {
+        let cmd = Last::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_last_run_unexpected_args() {
+        let cmd = Last::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(
+            cmd.run(
+                &mut ctx_with_links(),
+                vec!["1".to_string(), "2".to_string()]
+            ),
+            expected
+        );
+        for arg in ["0", "many"] {
+            let err = cmd
+                .run(&mut ctx_with_links(), vec![arg.to_string()])
+                .unwrap_err();
+            assert_eq!(err.kind, CommandErrorKind::Usage);
+        }
+    }
+
+    #[test]
+    fn test_last_run_nothing_opened() {
+        let err = Last::default()
+            .run(&mut ctx_with_links(), vec![])
+            .unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::LinkNotFound);
+    }
+
+    #[test]
+    fn test_last_run() {
+        let mut ctx = ctx_with_links();
+        run(&mut ctx, &Open::default(), &["work", "ci"]);
+        run(&mut ctx, &Open::default(), &["search-engines", "--all"]);
+        // Only links that were opened are recorded
+        run(&mut ctx, &Open::default(), &["work", "ci", "--print"]);
+        assert_eq!(
+            run(&mut ctx, &Last::default(), &["--print"]),
+            CommandResult::List(vec!["https://yahoo.com".to_string()])
+        );
+        assert_eq!(
+            run(&mut ctx, &Last::default(), &["5", "--print"]),
+            CommandResult::Rows(vec![
+                vec![
+                    "search-engines/yahoo".to_string(),
+                    "https://yahoo.com".to_string()
+                ],
+                vec![
+                    "search-engines/google".to_string(),
+                    "https://google.com".to_string()
+                ],
+                vec!["work/ci".to_string(), "https://ci.internal".to_string()],
+            ])
+        );
+        assert_eq!(
+            run(&mut ctx, &Last::default(), &["2"]),
+            CommandResult::Table(Table {
+                title: "Opening links:".to_string(),
+                rows: vec![
+                    vec!["search-engines/yahoo".to_string()],
+                    vec!["search-engines/google".to_string()],
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn test_last_run_skips_deleted_links() {
+        let mut ctx = ctx_with_links();
+        run(&mut ctx, &Open::default(), &["work", "ci"]);
+        run(&mut ctx, &Open::default(), &["search-engines", "google"]);
+        ctx.store
+            .write(|ds| ds.delete("search-engines".to_string(), Some("google".to_string())))
+            .unwrap();
+        assert_eq!(
+            run(&mut ctx, &Last::default(), &["--copy"]),
+            CommandResult::Value("https://ci.internal".to_string())
+        );
+        ctx.store
+            .write(|ds| ds.delete("work".to_string(), Some("ci".to_string())))
+            .unwrap();
+        let err = Last::default().run(&mut ctx, vec![]).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::LinkNotFound);
+    }
+}
@@ -0,0 +1,170 @@
+use crate::{
+    commands::{Command, CommandError, CommandErrorKind, CommandResult, display_examples},
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::command::Args,
+    utils::context::Context,
+};
+
+pub(crate) struct List {
+    name: String,
+    description: String,
+    args: [String; 1],
+}
+
+impl Default for List {
+    fn default() -> Self {
+        Self {
+            name: "--list".to_string(),
+            description: "List names, one per line for scripts".to_string(),
+            args: ["[Parent|here]".to_string()],
+        }
+    }
+}
+
+impl Command for List {
+    fn error_message(&self) -> String {
+        "expected 0-1 arguments, see the Usage section with tap --list --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --list command prints the names of all parent entities, or the link names of a parent entity, one per line without a header. Unlike --show, its output is stable so scripts like shell completions can rely on it. An empty data store prints nothing.\n\n");
+        s.push_str("Command Structure: tap --list [Parent Entity | here]\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let args = Args::new(args);
+        match args.len() {
+            0 => Ok(CommandResult::List(ctx.store.parents()?)),
+            1 if args.is_flag(0, "--help") => Ok(CommandResult::Value(self.help_message())),
+            1 => {
+                let parent_entity = args.parent(0)?;
+                match ctx.store.links(&parent_entity) {
+                    Ok(links) => Ok(CommandResult::List(links)),
+                    Err(e) => {
+                        let e = CommandError::from(e);
+                        // Nothing to list in an empty data store is not an error
+                        if e.kind == CommandErrorKind::ParentNotFound
+                            && ctx.store.parents()?.is_empty()
+                        {
+                            return Ok(CommandResult::List(vec![]));
+                        }
+                        Err(e)
+                    }
+                }
+            }
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+impl DisplayCommandAsRow for List {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            ("tap --list", "List all parent entities"),
+            (
+                "tap --list search-engines",
+                "List the links of a parent entity",
+            ),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx_with_links() -> Context {
+        Context::in_memory(vec![
+            (
+                "search-engines".to_string(),
+                vec![
+                    ("google".to_string(), "https://google.com".to_string()),
+                    ("yahoo".to_string(), "https://yahoo.com".to_string()),
+                ],
+            ),
+            (
+                "work".to_string(),
+                vec![("mail".to_string(), "https://mail.com".to_string())],
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_list_run_expected_help_arg() {
+        let args: Vec<String> = vec!["--help".to_string()];
+        let cmd = List::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(cmd.run(&mut Context::default(), args), expected);
+    }
+
+    #[test]
+    fn test_list_run_unexpected_args() {
+        let args: Vec<String> = vec!["work".to_string(), "mail".to_string()];
+        let cmd = List::default();
+        let mut ctx = ctx_with_links();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(cmd.run(&mut ctx, args), expected);
+    }
+
+    #[test]
+    fn test_list_run_parents() {
+        let cmd = List::default();
+        let mut ctx = ctx_with_links();
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::List(vec![
+            "search-engines".to_string(),
+            "work".to_string(),
+        ]));
+        assert_eq!(cmd.run(&mut ctx, vec![]), expected);
+    }
+
+    #[test]
+    fn test_list_run_links() {
+        let args: Vec<String> = vec!["search-engines".to_string()];
+        let cmd = List::default();
+        let mut ctx = ctx_with_links();
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::List(vec![
+            "google".to_string(),
+            "yahoo".to_string(),
+        ]));
+        assert_eq!(cmd.run(&mut ctx, args), expected);
+    }
+
+    #[test]
+    fn test_list_run_empty_store() {
+        let cmd = List::default();
+        let mut ctx = Context::in_memory(vec![]);
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::List(vec![]));
+        assert_eq!(cmd.run(&mut ctx, vec![]), expected);
+        assert_eq!(cmd.run(&mut ctx, vec!["work".to_string()]), expected);
+    }
+
+    #[test]
+    fn test_list_run_parent_not_found() {
+        let args: Vec<String> = vec!["personal".to_string()];
+        let cmd = List::default();
+        let mut ctx = ctx_with_links();
+        assert_eq!(
+            cmd.run(&mut ctx, args).unwrap_err().kind,
+            CommandErrorKind::ParentNotFound
+        );
+    }
+}
@@ -0,0 +1,165 @@
+use crate::{
+    commands::{
+        Command, CommandError, CommandResult, complete::GLOBAL_FLAGS, complete::GLOBAL_FORMAT_FLAG,
+        complete::GLOBAL_PATH_FLAGS, display_exit_codes, registry,
+    },
+    utils::context::Context,
+    utils::format::Format,
+};
+
+/// Hidden command printing the man page, built from the same registry as the usage table so it
+/// never falls behind the commands. Packagers install its output as `tap.1`.
+#[derive(Default)]
+pub(crate) struct Man {}
+
+impl Command for Man {
+    fn error_message(&self) -> String {
+        "expected no arguments, see tap --generate-man --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --generate-man command prints the man page of tap in roff format, for packagers to install as tap.1.\n\n");
+        s.push_str("Command Structure: tap --generate-man\n");
+        s.push_str("Example Usage: \n\n");
+        s.push_str("  - Write the man page: tap --generate-man > tap.1\n");
+        s
+    }
+
+    fn run(&self, _ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        match args.as_slice() {
+            [] => Ok(CommandResult::Value(man_page())),
+            [flag] if flag == "--help" => Ok(CommandResult::Value(self.help_message())),
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+/// The whole man page: NAME, SYNOPSIS, the global options, a subsection per command, and the exit
+/// codes
+fn man_page() -> String {
+    let mut s = String::new();
+    s.push_str(&format!(
+        ".TH TAP 1 \"\" \"{} {}\" \"User Commands\"\n",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION")
+    ));
+    s.push_str(".SH NAME\n");
+    s.push_str(&format!(
+        "tap \\- {}\n",
+        escape(env!("CARGO_PKG_DESCRIPTION"))
+    ));
+    s.push_str(".SH SYNOPSIS\n");
+    s.push_str(".B tap\n[\\fIoptions\\fR] \\fIcommand\\fR [\\fIargs\\fR]\n");
+    s.push_str(".br\n.B tap\n[\\fIoptions\\fR] \\fIparent\\fR [\\fIlink\\fR]\n");
+    s.push_str(".SH OPTIONS\n");
+    s.push_str("Global options come before the command, in any order.\n");
+    for flag in GLOBAL_FLAGS {
+        s.push_str(&format!(".TP\n.B {}\n", escape(flag)));
+    }
+    for flag in GLOBAL_PATH_FLAGS {
+        s.push_str(&format!(".TP\n.B {} \\fIpath\\fR\n", escape(flag)));
+    }
+    s.push_str(&format!(
+        ".TP\n.B {} \\fI{}\\fR\n",
+        escape(GLOBAL_FORMAT_FLAG),
+        Format::ALL.map(Format::name).join("|")
+    ));
+    s.push_str(".SH COMMANDS\n");
+    for cmd in registry() {
+        s.push_str(&format!(".SS \"{}\"\n", escape(&cmd.name())));
+        if !cmd.args().is_empty() {
+            s.push_str(&format!(".B {}\n", escape(&cmd.args().join(" "))));
+            s.push_str(".br\n");
+        }
+        s.push_str(&format!("{}\n", escape(&cmd.description())));
+        s.push_str(".PP\n");
+        // The help of --help is the usage table this page already covers
+        if cmd.name() != "--help" {
+            let help = cmd.help_message();
+            // The examples are listed below
+            let help = help.split("Example Usage:").next().unwrap_or_default();
+            s.push_str(&paragraphs(help));
+        }
+        for (line, description) in cmd.examples() {
+            s.push_str(&format!(
+                ".TP\n.B {}\n{}\n",
+                escape(&line),
+                escape(&description)
+            ));
+        }
+    }
+    s.push_str(".SH EXIT STATUS\n");
+    for line in display_exit_codes().lines().skip(1) {
+        if let Some((code, meaning)) = line.trim().split_once(' ') {
+            s.push_str(&format!(".TP\n.B {code}\n{}\n", escape(meaning)));
+        }
+    }
+    s
+}
+
+/// Help text as roff, keeping its line breaks, with blank lines starting new paragraphs
+fn paragraphs(text: &str) -> String {
+    let mut s = String::new();
+    for line in text.lines() {
+        match line.trim() {
+            "" => s.push_str(".PP\n"),
+            line => {
+                s.push_str(&escape(line));
+                s.push_str("\n.br\n");
+            }
+        }
+    }
+    s
+}
+
+/// Escapes text for roff: backslashes and dashes, and lines starting with a control character
+fn escape(text: &str) -> String {
+    let s = text.replace('\\', "\\e").replace('-', "\\-");
+    match s.starts_with(['.', '\'']) {
+        true => format!("\\&{s}"),
+        false => s,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_man_run_unexpected_args() {
+        let cmd = Man::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["random".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_man_run_every_command() {
+        let Ok(CommandResult::Value(page)) = Man::default().run(&mut Context::default(), vec![])
+        else {
+            panic!("expected the man page");
+        };
+        assert!(page.starts_with(".TH TAP 1"));
+        for cmd in registry() {
+            assert!(
+                page.contains(&format!(".SS \"{}\"\n", escape(&cmd.name()))),
+                "{}",
+                cmd.name()
+            );
+            for (line, _) in cmd.examples() {
+                assert!(page.contains(&escape(&line)), "{line}");
+            }
+        }
+        assert!(page.contains(".TP\n.B 7\n"));
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("-a, --add"), "\\-a, \\-\\-add");
+        assert_eq!(escape("a \\= b"), "a \\e= b");
+        assert_eq!(escape(".tap_data"), "\\&.tap_data");
+    }
+}
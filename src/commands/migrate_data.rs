@@ -0,0 +1,139 @@
+use crate::{
+    commands::{Command, CommandError, CommandResult, display_examples},
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::context::Context,
+    utils::tap_data_store::migrate_data,
+};
+use std::path::PathBuf;
+
+pub(crate) struct MigrateData {
+    name: String,
+    description: String,
+    args: [String; 2],
+}
+
+impl Default for MigrateData {
+    fn default() -> Self {
+        Self {
+            name: "--migrate-data".to_string(),
+            description: "Move the data files to another directory".to_string(),
+            args: [
+                "[Destination Directory]".to_string(),
+                "[--force]".to_string(),
+            ],
+        }
+    }
+}
+
+impl Command for MigrateData {
+    fn error_message(&self) -> String {
+        "expected 0-2 arguments, see the Usage section with tap --migrate-data --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --migrate-data command moves the data files to the destination directory: .tap_data and .tap_index with the files kept next to them, like .tap_aliases, .tap_trash, and .tap_archive, for every profile. The destination defaults to $XDG_DATA_HOME/tap (or ~/.local/share/tap). The copies are checked before the destination is saved to the config file ($XDG_CONFIG_HOME/tap/config or ~/.config/tap/config) as data_dir, and the original files are only removed after that. If any step fails, the original files are left as they were.\n\n");
+        s.push_str("A destination that already holds a data file is not overwritten unless --force is passed.\n\n");
+        s.push_str("Command Structure: tap --migrate-data [Destination Directory] [--force]\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        if args.len() == 1 && args[0] == "--help" {
+            return Ok(CommandResult::Value(self.help_message()));
+        }
+        let force = args.iter().any(|a| a == "--force");
+        let rest: Vec<&String> = args.iter().filter(|a| *a != "--force").collect();
+        match rest.len() {
+            0 => self.migrate(ctx, None, force),
+            1 if !rest[0].starts_with('-') => {
+                self.migrate(ctx, Some(PathBuf::from(rest[0])), force)
+            }
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+impl MigrateData {
+    fn migrate(
+        &self,
+        ctx: &Context,
+        dest_dir: Option<PathBuf>,
+        force: bool,
+    ) -> Result<CommandResult, CommandError> {
+        let (src_dir, dest_dir) = migrate_data(ctx.store.options(), dest_dir, force)?;
+        Ok(CommandResult::Value(format!(
+            "Moved data files from {} to {}",
+            src_dir.display(),
+            dest_dir.display()
+        )))
+    }
+}
+
+impl DisplayCommandAsRow for MigrateData {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            (
+                "tap --migrate-data",
+                "Move the data files to the per-user data directory",
+            ),
+            (
+                "tap --migrate-data ~/dotfiles/tap",
+                "Move the data files to a synced directory",
+            ),
+            (
+                "tap --migrate-data ~/dotfiles/tap --force",
+                "Replace the data files in a directory",
+            ),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_data_run_expected_help_arg() {
+        let args: Vec<String> = vec!["--help".to_string()];
+        let cmd = MigrateData::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        let res = cmd.run(&mut Context::default(), args);
+        assert_eq!(res, expected);
+    }
+
+    #[test]
+    fn test_migrate_data_run_unexpected_flag() {
+        let args: Vec<String> = vec!["--random".to_string()];
+        let cmd = MigrateData::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        let res = cmd.run(&mut Context::default(), args);
+        assert_eq!(res, expected);
+    }
+
+    #[test]
+    fn test_migrate_data_run_too_many_args() {
+        let args: Vec<String> = vec!["a".to_string(), "b".to_string(), "--force".to_string()];
+        let cmd = MigrateData::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        let res = cmd.run(&mut Context::default(), args);
+        assert_eq!(res, expected);
+    }
+}
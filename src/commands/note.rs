@@ -0,0 +1,207 @@
+use crate::{
+    commands::{Command, CommandError, CommandResult, display_examples},
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::command::Args,
+    utils::context::Context,
+    utils::style,
+};
+
+pub(crate) struct Note {
+    name: String,
+    description: String,
+    args: [String; 3],
+}
+
+impl Default for Note {
+    fn default() -> Self {
+        Self {
+            name: "--note".to_string(),
+            description: "Attach a note to a link".to_string(),
+            args: [
+                "<Parent|here>".to_string(),
+                "<Link>".to_string(),
+                "[Text]".to_string(),
+            ],
+        }
+    }
+}
+
+impl Command for Note {
+    fn error_message(&self) -> String {
+        "expected 2-3 arguments, see the Usage section with tap --note --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --note command attaches a note to a link, for what the value alone does not say, like where the credentials of a staging server are kept. The note replaces the one the link had, and is printed under the value by tap --show <Parent Entity> <Link Name>. tap --search looks through notes as well.\n\n");
+        s.push_str("Leave out the text to remove the note of the link. The note is kept on the line of the link in the data file, with any '|' and line breaks escaped like in values.\n\n");
+        s.push_str("Command Structure: tap --note <Parent Entity | here> <Link Name> [Text]\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let args = Args::new(args);
+        match args.len() {
+            1 if args.is_flag(0, "--help") => Ok(CommandResult::Value(self.help_message())),
+            2 => self.set_note(ctx, &args.parent(0)?, &args[1], ""),
+            3 => self.set_note(ctx, &args.parent(0)?, &args[1], &args[2]),
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+impl Note {
+    /// Replaces the note of a link, an empty note removes it
+    fn set_note(
+        &self,
+        ctx: &Context,
+        parent_entity: &str,
+        link: &str,
+        note: &str,
+    ) -> Result<CommandResult, CommandError> {
+        let note = note.trim();
+        let old = ctx
+            .store
+            .write(|ds| {
+                ds.batch(|tx| {
+                    tx.update_meta(parent_entity, link, |meta| {
+                        meta.note = (!note.is_empty()).then(|| note.to_string());
+                    })
+                })
+            })
+            .map_err(|e| ctx.store.with_suggestions(e, parent_entity, Some(link)))?;
+        let done = match (old.note, note.is_empty()) {
+            (None, true) => {
+                return Ok(CommandResult::Value(format!(
+                    "Link '{link}' of parent '{parent_entity}' has no note"
+                )));
+            }
+            (Some(_), true) => "Removed the note of",
+            (None, false) => "Added a note to",
+            (Some(_), false) => "Replaced the note of",
+        };
+        Ok(CommandResult::Value(style::success(&format!(
+            "{done} link '{link}' of parent '{parent_entity}'"
+        ))))
+    }
+}
+
+impl DisplayCommandAsRow for Note {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            (
+                "tap --note work staging 1Password/Staging",
+                "Note where the credentials of a link are kept",
+            ),
+            ("tap --note work staging", "Remove the note of a link"),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::CommandErrorKind;
+
+    fn ctx() -> Context {
+        Context::in_memory(vec![(
+            "work".to_string(),
+            vec![(
+                "staging".to_string(),
+                "https://staging.internal".to_string(),
+            )],
+        )])
+    }
+
+    fn note(ctx: &mut Context, args: &[&str]) -> Result<CommandResult, CommandError> {
+        Note::default().run(ctx, args.iter().map(|a| a.to_string()).collect())
+    }
+
+    fn note_of(ctx: &Context) -> Option<String> {
+        ctx.store
+            .read_meta("work")
+            .unwrap()
+            .get("staging")
+            .and_then(|meta| meta.note.clone())
+    }
+
+    #[test]
+    fn test_note_run_expected_help_arg() {
+        let cmd = Note::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_note_run_unexpected_args() {
+        let cmd = Note::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["work".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_note_set_replace_clear() {
+        let mut ctx = ctx();
+        assert_eq!(
+            note(
+                &mut ctx,
+                &["work", "staging", "creds | in 1Password\nunder X"]
+            ),
+            Ok(CommandResult::Value(style::success(
+                "Added a note to link 'staging' of parent 'work'"
+            )))
+        );
+        assert_eq!(
+            note_of(&ctx).as_deref(),
+            Some("creds | in 1Password\nunder X")
+        );
+        assert_eq!(
+            note(&mut ctx, &["work", "staging", "ask the team"]),
+            Ok(CommandResult::Value(style::success(
+                "Replaced the note of link 'staging' of parent 'work'"
+            )))
+        );
+        assert_eq!(
+            note(&mut ctx, &["work", "staging"]),
+            Ok(CommandResult::Value(style::success(
+                "Removed the note of link 'staging' of parent 'work'"
+            )))
+        );
+        assert_eq!(note_of(&ctx), None);
+        assert_eq!(
+            note(&mut ctx, &["work", "staging", " "]),
+            Ok(CommandResult::Value(
+                "Link 'staging' of parent 'work' has no note".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_note_link_not_found() {
+        let e = note(&mut ctx(), &["work", "prod", "text"]).unwrap_err();
+        assert_eq!(e.kind, CommandErrorKind::LinkNotFound);
+    }
+}
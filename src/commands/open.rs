@@ -0,0 +1,786 @@
+use crate::{
+    commands::{
+        Command, CommandError, CommandResult, Table, TypeFilter, children_of, choose_links_to_open,
+        copy_value, display_examples, links_to_open, note_if_expired, only_of_types, open_value,
+        resolve_link_prefix, resolve_parent_prefix, take_template_args, value_to_open,
+    },
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::command::{Args, OpenLimit, get_current_directory_name},
+    utils::context::Context,
+    utils::link_type::LinkType,
+    utils::log,
+    utils::os_implementations::open_in_browser,
+    utils::style,
+};
+
+pub(crate) struct Open {
+    name: String,
+    description: String,
+    args: [String; 2],
+}
+
+impl Default for Open {
+    fn default() -> Self {
+        Self {
+            name: "--open".to_string(),
+            description: "Open 1/all Links of Parent, with options".to_string(),
+            args: ["<Parent|here>".to_string(), "[Link...]".to_string()],
+        }
+    }
+}
+
+impl Command for Open {
+    fn error_message(&self) -> String {
+        "expected 1 or more arguments, see the Usage section with tap --open --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --open command opens links like tap <Parent Entity> [Link Name...] and tap here [Link Name...] do, spelled out so the options after it are never taken for a link name. Both forms open links the same way and take the same options.\n\n");
+        s.push_str(&format!(
+            "Command Structure: tap --open <Parent Entity | here> [Link Name...] {}\n",
+            options_usage()
+        ));
+        s.push_str("The Parent Entity and Link Name can be shortened to the start of their names, as long as only one name starts that way. Pass --exact to only open names as typed.\n");
+        s.push_str("Parent Entity names can nest with '/', like work/infra. A name that only groups others, like work, lists the Parent Entities under it instead of opening all of their links.\n");
+        s.push_str(&options_help());
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let mut args = Args::new(args);
+        let opts = OpenOptions::take(&mut args)?;
+        match args.len() {
+            1 if args.is_flag(0, "--help") => Ok(CommandResult::Value(self.help_message())),
+            n if n == 1 || (n > 1 && opts.names_only()) => match args.is_flag(0, "here") {
+                true => open(
+                    ctx,
+                    &get_current_directory_name()?,
+                    &args[1..],
+                    &opts.in_current_directory(),
+                ),
+                false => open_parent(ctx, &args[0], &args[1..], &opts),
+            },
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+/// The options of every command opening the links of a parent entity, with a short description
+/// for the Open options section of tap --help
+pub(in crate::commands) const OPTIONS: [(&str, &str); 13] = [
+    ("[--exact]", "Only open names as typed"),
+    ("[--pinned]", "Only open pinned links"),
+    ("[--all]", "Open all links, not just the default one"),
+    ("[--include-expired]", "Open expired links too"),
+    ("[-y | --yes]", "Open many links without asking"),
+    ("[--force]", "Open more links than max_open"),
+    ("[--pick]", "Pick the links to open from a list"),
+    (
+        "[--arg <Name>=<Value>...]",
+        "Fill the placeholders of templates",
+    ),
+    ("[--type <Types>]", "Only open links of these types"),
+    ("[--browser <Name>]", "Open in another browser"),
+    ("[--private]", "Open in a private window"),
+    ("[--print]", "Print the values instead"),
+    ("[--copy]", "Copy the values instead"),
+];
+
+/// Stands for the options of `OPTIONS` in the usage of a command opening links
+pub(in crate::commands) const OPTIONS_PLACEHOLDER: &str = "[Open options]";
+
+/// The options of `OPTIONS` as they are written in a command structure
+pub(in crate::commands) fn options_usage() -> String {
+    OPTIONS.map(|(option, _)| option).join(" ")
+}
+
+/// What the options of `OPTIONS` do, for the help of every command opening links
+pub(in crate::commands) fn options_help() -> String {
+    let mut s = String::new();
+    s.push_str("Several Link Names open just those links. A link that can not be found does not stop the others, and what happened to each one is listed at the end.\n");
+    s.push_str("Pass --pinned to only open the links that were pinned with tap --pin.\n");
+    s.push_str("When a default link was picked with tap --set-default, only that link is opened. Pass --all to open all links anyway.\n");
+    s.push_str("Set open_all_default = false in the config file to list the links of a Parent Entity with several of them and no default link instead of opening them all, to name one or pass --all.\n");
+    s.push_str("Links that expired, see tap --add --expires, are not opened unless --include-expired is passed. An expired link is still opened when it is named, with a warning.\n");
+    s.push_str("Opening more than 5 links at once asks for confirmation first, pass --yes to skip it. Set open_limit = <count> in the config file to change the number, and open_unconfirmed = refuse to fail instead of opening them when not run in a terminal.\n");
+    s.push_str("Set max_open = <count> in the config file to never open more links than that at once, which fails instead of asking. Pass --force to open them anyway, like tap work --all --force.\n");
+    s.push_str("A value can be a template with {name} placeholders, like https://github.com/me/{repo}/issues?q={query}. Each one is filled with the URL encoded value passed as --arg name=value when the link is opened, and {{ and }} stand for literal braces.\n");
+    s.push_str("Pass --type to only open the links of the types given, like --type file,dir for local files and directories but no web pages. See tap --add --type for the types.\n");
+    s.push_str("Pass --pick to list the links numbered and type the ones to open, like 1,3-5. Set open_many = pick in the config file to be asked this way instead of yes or no when there are more links than the limit.\n");
+    s.push_str("Pass --browser to open the links in another browser than the default one, like --browser firefox, and --private as well to open them in a private window. Tap knows chrome, chromium, brave, edge, firefox, and safari, any other name is started as a program.\n");
+    s.push_str("Pass --print to print the values instead of opening them, and --copy to put them on the clipboard, with pbcopy on macOS, clip on Windows, and wl-copy, xclip, xsel, or clip.exe on Linux. The values are printed with --copy too, for when there is no clipboard, like over SSH. Several links are printed as <Link Name><TAB><Value>, one per line.\n");
+    s
+}
+
+/// What is done with the links picked, see `OpenOptions`
+#[derive(Debug, PartialEq)]
+enum Action {
+    /// Opened, in the default program of their type unless a browser is named
+    Open {
+        browser: Option<String>,
+        private: bool,
+    },
+    /// Printed instead of opened
+    Print,
+    /// Put on the clipboard instead of opened
+    Copy,
+}
+
+/// The options of `OPTIONS`, taken out of the arguments of a command opening links
+pub(in crate::commands) struct OpenOptions {
+    exact: bool,
+    pinned: bool,
+    all: bool,
+    include_expired: bool,
+    yes: bool,
+    force: bool,
+    pick: bool,
+    template_args: Vec<(String, String)>,
+    types: Option<TypeFilter>,
+    action: Action,
+    /// The parent entity is the current directory, named `here` in the commands suggested
+    here: bool,
+}
+
+impl OpenOptions {
+    pub fn take(args: &mut Args) -> Result<Self, CommandError> {
+        let exact = args.take_flag(&["--exact"]);
+        let pinned = args.take_flag(&["--pinned"]);
+        let all = args.take_flag(&["--all"]);
+        let include_expired = args.take_flag(&["--include-expired"]);
+        let yes = args.take_flag(&["-y", "--yes"]);
+        let force = args.take_flag(&["--force"]);
+        let pick = args.take_flag(&["--pick"]);
+        let template_args = take_template_args(args)?;
+        let types = TypeFilter::take(args)?;
+        let browser = args.take_option("--browser");
+        let private = args.take_flag(&["--private"]);
+        let print = args.take_flag(&["--print"]);
+        let copy = args.take_flag(&["--copy"]);
+        let action = match (print, copy) {
+            (true, true) => {
+                return Err(CommandError::usage(
+                    "--print and --copy can not be used together".to_string(),
+                ));
+            }
+            _ if (print || copy) && (browser.is_some() || private) => {
+                return Err(CommandError::usage(
+                    "--browser and --private open links, they can not be used with --print or --copy"
+                        .to_string(),
+                ));
+            }
+            (true, false) => Action::Print,
+            (false, true) => Action::Copy,
+            (false, false) if private && browser.is_none() => {
+                return Err(CommandError::usage(
+                    "--private needs the browser to open, like --browser firefox --private"
+                        .to_string(),
+                ));
+            }
+            (false, false) => Action::Open { browser, private },
+        };
+        Ok(Self {
+            exact,
+            pinned,
+            all,
+            include_expired,
+            yes,
+            force,
+            pick,
+            template_args,
+            types,
+            action,
+            here: false,
+        })
+    }
+
+    /// The options for the links of the current directory
+    pub fn in_current_directory(self) -> Self {
+        Self { here: true, ..self }
+    }
+
+    /// Whether every link of a parent entity is to be opened without naming one of the `count`
+    /// links: always with `--all`, or when they were chosen some other way, and otherwise unless
+    /// the config file sets `open_all_default = false`. Printing or copying them is no surprise.
+    fn opens_all(&self, count: usize, limit: &OpenLimit) -> bool {
+        limit.open_all
+            || count <= 1
+            || self.all
+            || self.pick
+            || self.pinned
+            || self.types.is_some()
+            || !matches!(self.action, Action::Open { .. })
+    }
+
+    /// Whether none of the options choosing among all the links of a parent entity were passed,
+    /// which they can not be along with link names
+    pub fn names_only(&self) -> bool {
+        !self.pinned && !self.all && !self.include_expired && !self.pick && self.types.is_none()
+    }
+}
+
+/// Opens the links named `names` of the parent entity named `parent`, or all of them, see `open`.
+/// Unless `--exact` is passed, `parent` can be the start of a name. A name that only groups other
+/// parent entities, like `work` for `work/infra`, lists them instead.
+pub(in crate::commands) fn open_parent(
+    ctx: &Context,
+    parent: &str,
+    names: &[String],
+    opts: &OpenOptions,
+) -> Result<CommandResult, CommandError> {
+    let parent = match opts.exact {
+        true => parent.to_string(),
+        false => resolve_parent_prefix(&ctx.store, parent)?,
+    };
+    // Opening every link nested under a group would open far too many
+    if names.is_empty()
+        && let Some(children) = group_children(ctx, &parent)?
+    {
+        return Ok(CommandResult::Table(Table {
+            title: format!("Parent entities under {parent}:"),
+            rows: children.iter().map(|c| vec![style::parent(c)]).collect(),
+        }));
+    }
+    open(ctx, &parent, names, opts)
+}
+
+/// The names under `parent` when it only groups other parent entities, like `work` for
+/// `work/infra`, and has no links of its own
+fn group_children(ctx: &Context, parent: &str) -> Result<Option<Vec<String>>, CommandError> {
+    let parent = ctx.store.resolve_alias(parent)?;
+    let parents = ctx.store.parents()?;
+    if parents.iter().any(|p| p == parent.trim()) {
+        return Ok(None);
+    }
+    let children = children_of(&parents, &parent);
+    Ok((!children.is_empty()).then_some(children))
+}
+
+/// Opens the links of `parent` named by `names`, or the ones `links_to_open` picks when no names
+/// are given, the way `opts` say
+pub(in crate::commands) fn open(
+    ctx: &Context,
+    parent: &str,
+    names: &[String],
+    opts: &OpenOptions,
+) -> Result<CommandResult, CommandError> {
+    match names {
+        [] => open_all(ctx, parent, opts),
+        [name] => open_one(ctx, parent, name, opts),
+        names => open_several(ctx, parent, names, opts),
+    }
+}
+
+fn open_all(
+    ctx: &Context,
+    parent: &str,
+    opts: &OpenOptions,
+) -> Result<CommandResult, CommandError> {
+    // Every link is listed to pick from, the default link too, and every link of the types
+    // asked for is opened
+    let res = links_to_open(
+        &ctx.store,
+        parent,
+        opts.pinned,
+        opts.all || opts.pick || opts.types.is_some(),
+        opts.include_expired,
+    )?;
+    let res = match &opts.types {
+        Some(types) => only_of_types(&ctx.store, parent, res, types)?,
+        None => res,
+    };
+    if !opts.opens_all(res.len(), &OpenLimit::load()) {
+        return Ok(name_one(parent, opts, res));
+    }
+    // Printing many links needs no confirmation, picking them still does
+    let res = match (&opts.action, opts.pick) {
+        (Action::Open { .. }, _) | (_, true) => {
+            choose_links_to_open(ctx, parent, res, opts.pick, opts.yes, opts.force)?
+        }
+        _ => res,
+    };
+    if res.is_empty() {
+        return Ok(CommandResult::Value("Nothing was opened".to_string()));
+    }
+    // Every template is filled before any link is opened
+    let res = res
+        .into_iter()
+        .map(|(link, val)| {
+            let val = value_to_open(parent, &link, &val, &opts.template_args)?;
+            Ok((link, val))
+        })
+        .collect::<Result<Vec<_>, CommandError>>()?;
+    let (browser, private) = match &opts.action {
+        Action::Open { browser, private } => (browser.as_deref(), *private),
+        _ => return Ok(print_or_copy(&opts.action, res)),
+    };
+    let mut rows = vec![];
+    for (link, val) in res.into_iter() {
+        if open_in(ctx, parent, &link, &val, browser, private)? {
+            rows.push(vec![link]);
+        }
+    }
+    Ok(CommandResult::Table(Table {
+        title: "Opening links:".to_string(),
+        rows,
+    }))
+}
+
+/// The `links` of `parent` listed rather than opened, see `OpenOptions::opens_all`, with the commands
+/// opening one or all of them
+fn name_one(parent: &str, opts: &OpenOptions, links: Vec<(String, String)>) -> CommandResult {
+    let typed = match opts.here {
+        true => "here",
+        false => parent,
+    };
+    let first = links
+        .first()
+        .map_or("<Link Name>", |(link, _)| link.as_str());
+    CommandResult::Table(Table {
+        title: format!(
+            "{} has {} links, open one with tap {typed} {first} or all of them with tap {typed} --all:",
+            style::parent(parent),
+            links.len()
+        ),
+        rows: links.into_iter().map(|(link, _)| vec![link]).collect(),
+    })
+}
+
+/// The link of `parent` named `name`, shortened to the start of it unless `--exact` is passed,
+/// with its value to open
+fn resolve(
+    ctx: &Context,
+    parent: &str,
+    name: &str,
+    opts: &OpenOptions,
+) -> Result<(String, String), CommandError> {
+    let link = match opts.exact {
+        true => name.to_string(),
+        false => resolve_link_prefix(&ctx.store, parent, name)?,
+    };
+    let (_, val) = ctx.store.read_link(parent, &link)?;
+    let val = value_to_open(parent, &link, &val, &opts.template_args)?;
+    note_if_expired(&ctx.store, parent, &link)?;
+    Ok((link, val))
+}
+
+fn open_one(
+    ctx: &Context,
+    parent: &str,
+    name: &str,
+    opts: &OpenOptions,
+) -> Result<CommandResult, CommandError> {
+    let (link, val) = resolve(ctx, parent, name, opts)?;
+    let (browser, private) = match &opts.action {
+        Action::Open { browser, private } => (browser.as_deref(), *private),
+        Action::Print => return Ok(CommandResult::Value(val)),
+        Action::Copy => return Ok(copy_value(&val)),
+    };
+    log::verbose(format_args!("Opening link {link}: {val}"));
+    match open_in(ctx, parent, &link, &val, browser, private)? {
+        // The opened link is all the user needs to see
+        true => Ok(CommandResult::Silent),
+        false => Ok(CommandResult::Value("Nothing was run".to_string())),
+    }
+}
+
+/// Opens the links of `parent` named by `names`, each resolved like a single named link. A link
+/// that can not be found or opened does not stop the others, the outcome of each one is listed
+/// instead. Fails only when none of them could be opened. Links printed or copied must all be
+/// found, as a partial list to paste would go unnoticed.
+fn open_several(
+    ctx: &Context,
+    parent: &str,
+    names: &[String],
+    opts: &OpenOptions,
+) -> Result<CommandResult, CommandError> {
+    let (browser, private) = match &opts.action {
+        Action::Open { browser, private } => (browser.as_deref(), *private),
+        action => {
+            let res = names
+                .iter()
+                .map(|name| resolve(ctx, parent, name, opts))
+                .collect::<Result<Vec<_>, CommandError>>()?;
+            return Ok(print_or_copy(action, res));
+        }
+    };
+    OpenLimit::load().check_max(names.len(), opts.force)?;
+    let open = |name: &str| -> Result<String, CommandError> {
+        let (link, val) = resolve(ctx, parent, name, opts)?;
+        log::verbose(format_args!("Opening link {link}: {val}"));
+        match open_in(ctx, parent, &link, &val, browser, private)? {
+            true => Ok(link),
+            false => Err(CommandError::from(format!("{link} was not run"))),
+        }
+    };
+    let mut rows = vec![];
+    let mut errors = vec![];
+    for name in names {
+        match open(name) {
+            Ok(link) => rows.push(vec![link, style::success("opened")]),
+            Err(e) => {
+                rows.push(vec![name.clone(), style::error(&e.message)]);
+                errors.push(e);
+            }
+        }
+    }
+    if errors.len() == names.len() {
+        let message = errors
+            .iter()
+            .map(|e| e.message.as_str())
+            .collect::<Vec<&str>>()
+            .join("\n");
+        return Err(CommandError {
+            kind: errors.remove(0).kind,
+            message,
+        });
+    }
+    Ok(CommandResult::Table(Table {
+        title: format!(
+            "Opened {} of {} links:",
+            names.len() - errors.len(),
+            names.len()
+        ),
+        rows,
+    }))
+}
+
+/// Opens `links` of any parent entities, each a parent entity, link name, and value, in that order
+/// the way `opts` say, like the links of a session. Only the options for links named one by one
+/// apply, see `OpenOptions::names_only`. The links opened are listed under `title`.
+pub(in crate::commands) fn open_links(
+    ctx: &Context,
+    title: String,
+    links: Vec<(String, String, String)>,
+    opts: &OpenOptions,
+) -> Result<CommandResult, CommandError> {
+    if !opts.names_only() {
+        return Err(CommandError::usage(
+            "--pinned, --all, --include-expired, --pick, and --type choose among the links of a Parent Entity, they can not be used here"
+                .to_string(),
+        ));
+    }
+    // Every template is filled before any link is opened
+    let links = links
+        .into_iter()
+        .map(|(parent, link, val)| {
+            let val = value_to_open(&parent, &link, &val, &opts.template_args)?;
+            Ok((parent, link, val))
+        })
+        .collect::<Result<Vec<_>, CommandError>>()?;
+    let (browser, private) = match &opts.action {
+        Action::Open { browser, private } => (browser.as_deref(), *private),
+        action => {
+            let links = links
+                .into_iter()
+                .map(|(parent, link, val)| (format!("{parent}/{link}"), val))
+                .collect();
+            return Ok(print_or_copy(action, links));
+        }
+    };
+    OpenLimit::load().check_max(links.len(), opts.force)?;
+    let mut rows = vec![];
+    for (parent, link, val) in links {
+        if open_in(ctx, &parent, &link, &val, browser, private)? {
+            rows.push(vec![format!("{parent}/{link}")]);
+        }
+    }
+    Ok(CommandResult::Table(Table { title, rows }))
+}
+
+/// Opens `value` of `link` like `open_value`, or in `browser` when one is named. Commands are
+/// run, never opened in a browser.
+fn open_in(
+    ctx: &Context,
+    parent: &str,
+    link: &str,
+    value: &str,
+    browser: Option<&str>,
+    private: bool,
+) -> Result<bool, CommandError> {
+    let Some(browser) = browser else {
+        return open_value(ctx, parent, link, value);
+    };
+    let link_type = ctx
+        .store
+        .read_meta(parent)?
+        .get(link)
+        .and_then(|meta| meta.link_type);
+    if link_type == Some(LinkType::Cmd) {
+        return Err(CommandError::usage(format!(
+            "Link {link} of parent entity {parent} runs a command, it can not be opened in {browser}"
+        )));
+    }
+    open_in_browser(value, browser, private)?;
+    ctx.store.record_opened(parent, link);
+    Ok(true)
+}
+
+/// The `links` printed, or put on the clipboard and printed, as `<link>\t<value>` lines. A single
+/// link, like the default link of a parent entity, is only its value.
+fn print_or_copy(action: &Action, links: Vec<(String, String)>) -> CommandResult {
+    let res = match links.as_slice() {
+        [(_, val)] => CommandResult::List(vec![val.clone()]),
+        _ => CommandResult::Rows(
+            links
+                .into_iter()
+                .map(|(link, val)| vec![link, val])
+                .collect(),
+        ),
+    };
+    match action {
+        Action::Copy => copy_value(&res.to_string()),
+        _ => res,
+    }
+}
+
+impl DisplayCommandAsRow for Open {
+    fn args(&self) -> Vec<String> {
+        let mut args = self.args.to_vec();
+        args.push(OPTIONS_PLACEHOLDER.to_string());
+        args
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            (
+                "tap --open work ci --browser firefox --private",
+                "Open the Link ci of work in a private Firefox window",
+            ),
+            (
+                "tap --open here --all --print",
+                "Print the values of all Links of the current directory",
+            ),
+            (
+                "tap --open work --pick --copy",
+                "Pick Links of work and put their values on the clipboard",
+            ),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::CommandErrorKind;
+    use crate::utils::link_type::LinkType;
+
+    fn ctx_with_links(parent: &str) -> Context {
+        Context::in_memory(vec![(
+            parent.to_string(),
+            vec![
+                ("google".to_string(), "https://google.com".to_string()),
+                ("yahoo".to_string(), "https://yahoo.com".to_string()),
+            ],
+        )])
+    }
+
+    fn run(ctx: &mut Context, args: &[&str]) -> Result<CommandResult, CommandError> {
+        Open::default().run(ctx, args.iter().map(|a| a.to_string()).collect())
+    }
+
+    #[test]
+    fn test_open_run_expected_help_arg() {
+        let cmd = Open::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_open_run_unexpected_args() {
+        let cmd = Open::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(cmd.run(&mut Context::default(), vec![]), expected);
+        assert_eq!(
+            run(
+                &mut ctx_with_links("search"),
+                &["search", "google", "--all"]
+            ),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_open_run_like_bare_form() {
+        let mut ctx = ctx_with_links("search-engines");
+        assert_eq!(run(&mut ctx, &["search", "goo"]), Ok(CommandResult::Silent));
+        assert_eq!(
+            run(&mut ctx, &["search-engines"]),
+            Ok(CommandResult::Table(Table {
+                title: "Opening links:".to_string(),
+                rows: vec![vec!["google".to_string()], vec!["yahoo".to_string()]],
+            }))
+        );
+        let err = run(&mut ctx, &["search-engines", "bing"]).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::LinkNotFound);
+    }
+
+    #[test]
+    fn test_open_run_here() {
+        let mut ctx = ctx_with_links(&get_current_directory_name().unwrap());
+        assert_eq!(
+            run(&mut ctx, &["here", "yahoo", "--print"]),
+            Ok(CommandResult::Value("https://yahoo.com".to_string()))
+        );
+        // here is the current directory, never the start of a parent entity name
+        let mut ctx = ctx_with_links("here-and-there");
+        let err = run(&mut ctx, &["here"]).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::ParentNotFound);
+    }
+
+    #[test]
+    fn test_open_run_print() {
+        let mut ctx = ctx_with_links("search-engines");
+        assert_eq!(
+            run(&mut ctx, &["search-engines", "--print"]),
+            Ok(CommandResult::Rows(vec![
+                vec!["google".to_string(), "https://google.com".to_string()],
+                vec!["yahoo".to_string(), "https://yahoo.com".to_string()],
+            ]))
+        );
+        assert_eq!(
+            run(&mut ctx, &["search-engines", "yahoo", "google", "--print"]),
+            Ok(CommandResult::Rows(vec![
+                vec!["yahoo".to_string(), "https://yahoo.com".to_string()],
+                vec!["google".to_string(), "https://google.com".to_string()],
+            ]))
+        );
+        // A partial list is never printed
+        let err = run(&mut ctx, &["search-engines", "yahoo", "bing", "--print"]).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::LinkNotFound);
+    }
+
+    #[test]
+    fn test_open_run_copy() {
+        let mut ctx = ctx_with_links("search-engines");
+        assert_eq!(
+            run(&mut ctx, &["search-engines", "google", "--copy"]),
+            Ok(CommandResult::Value("https://google.com".to_string()))
+        );
+        assert_eq!(
+            run(&mut ctx, &["search-engines", "--copy"]),
+            Ok(CommandResult::Value(
+                "google\thttps://google.com\nyahoo\thttps://yahoo.com".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_open_run_browser() {
+        let mut ctx = ctx_with_links("search-engines");
+        assert_eq!(
+            run(
+                &mut ctx,
+                &[
+                    "search-engines",
+                    "google",
+                    "--browser",
+                    "firefox",
+                    "--private"
+                ]
+            ),
+            Ok(CommandResult::Silent)
+        );
+        // Safari has no flag for a private window
+        let err = run(
+            &mut ctx,
+            &[
+                "search-engines",
+                "google",
+                "--browser",
+                "safari",
+                "--private",
+            ],
+        )
+        .unwrap_err();
+        assert!(
+            err.message
+                .contains("Can not open a private window of safari")
+        );
+        ctx.store
+            .write(|ds| {
+                ds.batch(|tx| {
+                    tx.update_meta("search-engines", "yahoo", |m| {
+                        m.link_type = Some(LinkType::Cmd)
+                    })
+                })
+            })
+            .unwrap();
+        let err = run(
+            &mut ctx,
+            &["search-engines", "yahoo", "--browser", "firefox"],
+        )
+        .unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::Usage);
+    }
+
+    #[test]
+    fn test_open_options_opens_all() {
+        let options = |args: &[&str]| {
+            OpenOptions::take(&mut Args::new(args.iter().map(|a| a.to_string()).collect())).unwrap()
+        };
+        let limit = OpenLimit {
+            open_all: false,
+            ..OpenLimit::default()
+        };
+        assert!(!options(&[]).opens_all(2, &limit));
+        assert!(options(&[]).opens_all(2, &OpenLimit::default()));
+        // A single link is always opened, and so are links chosen another way than by name
+        assert!(options(&[]).opens_all(1, &limit));
+        for args in [
+            &["--all"][..],
+            &["--pick"],
+            &["--pinned"],
+            &["--type", "url"],
+            &["--print"],
+        ] {
+            assert!(options(args).opens_all(2, &limit), "{args:?}");
+        }
+    }
+
+    #[test]
+    fn test_name_one() {
+        let links = vec![
+            ("google".to_string(), "https://google.com".to_string()),
+            ("yahoo".to_string(), "https://yahoo.com".to_string()),
+        ];
+        let opts = OpenOptions::take(&mut Args::new(vec![])).unwrap();
+        assert_eq!(
+            name_one("search-engines", &opts, links.clone()).to_string(),
+            "search-engines has 2 links, open one with tap search-engines google or all of them with tap search-engines --all:\n  google\n  yahoo"
+        );
+        let res = name_one("tap", &opts.in_current_directory(), links).to_string();
+        assert!(res.contains("open one with tap here google or all of them with tap here --all"));
+    }
+
+    #[test]
+    fn test_open_options_conflicts() {
+        let mut ctx = ctx_with_links("search-engines");
+        for args in [
+            &["search-engines", "--print", "--copy"][..],
+            &["search-engines", "--print", "--browser", "firefox"],
+            &["search-engines", "--copy", "--private"],
+            &["search-engines", "--private"],
+        ] {
+            let err = run(&mut ctx, args).unwrap_err();
+            assert_eq!(err.kind, CommandErrorKind::Usage, "{args:?}");
+        }
+    }
+}
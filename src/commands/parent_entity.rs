@@ -1,8 +1,9 @@
 use crate::{
-    commands::{Command, CommandResult},
+    commands::open::{OPTIONS_PLACEHOLDER, OpenOptions, open_parent, options_help, options_usage},
+    commands::{Command, CommandError, CommandResult, display_examples},
     utils::cli_usage_table::DisplayCommandAsRow,
-    utils::os_implementations::open_link,
-    utils::tap_data_store::ReadDataStore,
+    utils::command::Args,
+    utils::context::Context,
 };
 
 pub(crate) struct ParentEntity {
@@ -16,67 +17,100 @@ impl Default for ParentEntity {
         Self {
             name: "<Parent>".to_string(),
             description: "Open 1/all Links of Parent".to_string(),
-            args: ["[Link]".to_string()],
+            args: ["[Link...]".to_string()],
         }
     }
 }
 
 impl Command for ParentEntity {
     fn error_message(&self) -> String {
-        "expected 1-2 arguments, see the Usage section with tap --parent-entity --help".to_string()
+        "expected 1 or more arguments, see the Usage section with tap --parent-entity --help"
+            .to_string()
     }
 
     fn help_message(&self) -> String {
         let mut s: String = "".to_string();
-        s.push_str("Tap's core functionality is to open links. Tap Parent Entity command enables you to specify a Parent Entity and open either all or a specific link.\n\n");
-        s.push_str("Command Structure: tap <Parent Entity> [Link Name]\n");
-        s.push_str("Example Usage: \n\n");
-        s.push_str("  - Open all Links of Parent Entity named search-engine: tap search-engine\n");
-        s.push_str("  - Open specific Link named google in Parent Entity named search-engine: tap search-engine google\n");
+        s.push_str("Tap's core functionality is to open links. Tap Parent Entity command enables you to specify a Parent Entity and open either all, some, or a specific link.\n\n");
+        s.push_str(&format!(
+            "Command Structure: tap <Parent Entity> [Link Name...] {}\n",
+            options_usage()
+        ));
+        s.push_str("The Parent Entity and Link Name can be shortened to the start of their names, as long as only one name starts that way. Pass --exact to only open names as typed.\n");
+        s.push_str("Parent Entity names can nest with '/', like work/infra. A name that only groups others, like work, lists the Parent Entities under it instead of opening all of their links.\n");
+        s.push_str(&options_help());
+        s.push_str("The same links are opened by tap --open <Parent Entity> [Link Name...], which can be clearer in scripts.\n");
+        s.push_str(&display_examples(&self.examples()));
         s
     }
 
-    fn run(&self, args: Vec<String>) -> Result<CommandResult, String> {
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let mut args = Args::new(args);
+        let opts = OpenOptions::take(&mut args)?;
         match args.len() {
-            1 => {
-                let parent_entity = args[0].as_str();
-                let ds = ReadDataStore::new(None, parent_entity.to_string())
-                    .map_err(|e| e.to_string())?;
-                let res = ds.read_parent(parent_entity).map_err(|e| e.to_string())?;
-                let mut res_str = "Opening links: [".to_string();
-                for (link, val) in res.iter() {
-                    open_link(val).map_err(|e| e.to_string())?;
-                    res_str.push_str(format!("{link},").as_str());
-                }
-                res_str.push(']');
-                Ok(CommandResult::Value(res_str))
+            2 if args.is_flag(0, "--parent-entity") && args.is_flag(1, "--help") => {
+                Ok(CommandResult::Value(self.help_message()))
+            }
+            n if n == 1 || (n > 1 && opts.names_only()) => {
+                open_parent(ctx, &args[0], &args[1..], &opts)
             }
-            2 => match (args[0].as_str(), args[1].as_str()) {
-                ("--parent-entity", "--help") => Ok(CommandResult::Value(self.help_message())),
-                (parent_entity, link) => {
-                    let ds = ReadDataStore::new(None, parent_entity.to_string())
-                        .map_err(|e| e.to_string())?;
-                    let (_, val) = ds
-                        .read_link(parent_entity, link)
-                        .map_err(|e| e.to_string())?;
-                    open_link(&val).map_err(|e| e.to_string())?;
-                    Ok(CommandResult::Value("Opening link...".to_string()))
-                }
-            },
-            _ => Err(self.error_message()),
+            _ => Err(self.usage_error()),
         }
     }
 }
 
 impl DisplayCommandAsRow for ParentEntity {
     fn args(&self) -> Vec<String> {
-        self.args.to_vec()
+        let mut args = self.args.to_vec();
+        args.push(OPTIONS_PLACEHOLDER.to_string());
+        args
     }
 
     fn description(&self) -> String {
         self.description.clone()
     }
 
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            (
+                "tap search-engine",
+                "Open all Links of Parent Entity named search-engine",
+            ),
+            (
+                "tap search-engine google",
+                "Open specific Link named google in Parent Entity named search-engine",
+            ),
+            ("tap sea goo", "Open the same Link with shortened names"),
+            (
+                "tap work jira ci",
+                "Open the Links named jira and ci of work",
+            ),
+            ("tap work --pinned", "Open the pinned Links of work"),
+            (
+                "tap work --all",
+                "Open all Links of work, even when it has a default Link",
+            ),
+            (
+                "tap gh issues --arg repo=tap --arg query=crash",
+                "Open the Link issues of gh with its {repo} and {query} placeholders filled",
+            ),
+            (
+                "tap work --type file,dir",
+                "Open the Links of work that are files or directories",
+            ),
+            (
+                "tap work ci --copy",
+                "Put the value of the Link ci of work on the clipboard instead of opening it",
+            ),
+            (
+                "tap work --pick",
+                "List the Links of work numbered and open the ones picked, like 1,3-5",
+            ),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
     fn name(&self) -> String {
         self.name.clone()
     }
@@ -85,13 +119,41 @@ impl DisplayCommandAsRow for ParentEntity {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::commands::{CommandErrorKind, Table};
+    use crate::utils::link_type::LinkType;
+    use crate::utils::prompt::Prompt;
+
+    fn ctx_with_parents(parents: &[&str]) -> Context {
+        Context::in_memory(
+            parents
+                .iter()
+                .map(|parent| {
+                    (
+                        parent.to_string(),
+                        vec![("google".to_string(), "https://google.com".to_string())],
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    fn ctx_with_links(parent: &str) -> Context {
+        Context::in_memory(vec![(
+            parent.to_string(),
+            vec![
+                ("google".to_string(), "https://google.com".to_string()),
+                ("yahoo".to_string(), "https://yahoo.com".to_string()),
+            ],
+        )])
+    }
 
     #[test]
     fn test_parent_entity_run_expected_help_arg() {
         let args: Vec<String> = vec!["--parent-entity".to_string(), "--help".to_string()];
         let cmd = ParentEntity::default();
-        let expected: Result<CommandResult, String> = Ok(CommandResult::Value(cmd.help_message()));
-        let res = cmd.run(args);
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        let res = cmd.run(&mut Context::default(), args);
         assert_eq!(res, expected);
     }
 
@@ -100,33 +162,523 @@ mod tests {
         let args: Vec<String> = vec![
             "random".to_string(),
             "random2".to_string(),
-            "random3".to_string(),
+            "--pinned".to_string(),
         ];
         let cmd = ParentEntity::default();
-        let expected: Result<CommandResult, String> = Err(cmd.error_message());
-        let res = cmd.run(args);
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        let res = cmd.run(&mut Context::default(), args);
         assert_eq!(res, expected);
     }
 
     #[test]
-    #[ignore = "GH-45: Should be an integration test due to DataStore dependency & os dependency"]
     fn test_parent_entity_run_all_links() {
         let args: Vec<String> = vec!["search-engine".to_string()];
         let cmd = ParentEntity::default();
-        let expected: Result<CommandResult, String> = Ok(CommandResult::Value(
-            "TODO: Implement open functionality for Parent Entity: search-engine".to_string(),
-        ));
-        let res = cmd.run(args);
+        let mut ctx = ctx_with_links("search-engine");
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Table(Table {
+            title: "Opening links:".to_string(),
+            rows: vec![vec!["google".to_string()], vec!["yahoo".to_string()]],
+        }));
+        let res = cmd.run(&mut ctx, args);
         assert_eq!(res, expected);
     }
 
     #[test]
-    #[ignore = "GH-45: Should be an integration test due to DataStore dependency & os dependency"]
+    fn test_parent_entity_run_pinned_links() {
+        let cmd = ParentEntity::default();
+        let mut ctx = ctx_with_links("search-engine");
+        let pinned = vec!["search-engine".to_string(), "--pinned".to_string()];
+        let e = cmd.run(&mut ctx, pinned.clone()).unwrap_err();
+        assert_eq!(e.kind, CommandErrorKind::LinkNotFound);
+        ctx.store
+            .write(|ds| {
+                ds.batch(|tx| tx.update_meta("search-engine", "yahoo", |m| m.pinned = true))
+            })
+            .unwrap();
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Table(Table {
+            title: "Opening links:".to_string(),
+            rows: vec![vec!["yahoo".to_string()]],
+        }));
+        assert_eq!(cmd.run(&mut ctx, pinned), expected);
+    }
+
+    #[test]
+    fn test_parent_entity_run_default_link() {
+        let cmd = ParentEntity::default();
+        let mut ctx = ctx_with_links("search-engine");
+        let table = |links: &[&str]| {
+            Ok(CommandResult::Table(Table {
+                title: "Opening links:".to_string(),
+                rows: links.iter().map(|l| vec![l.to_string()]).collect(),
+            }))
+        };
+        ctx.store
+            .write(|ds| ds.batch(|tx| tx.set_default("search-engine", Some("yahoo"))))
+            .unwrap();
+        assert_eq!(
+            cmd.run(&mut ctx, vec!["search-engine".to_string()]),
+            table(&["yahoo"])
+        );
+        assert_eq!(
+            cmd.run(
+                &mut ctx,
+                vec!["search-engine".to_string(), "--all".to_string()]
+            ),
+            table(&["google", "yahoo"])
+        );
+        // Deleting the default link leaves no dangling default behind
+        ctx.store
+            .write(|ds| ds.batch(|tx| tx.delete("search-engine", Some("yahoo"))))
+            .unwrap();
+        ctx.store
+            .write(|ds| ds.batch(|tx| tx.add_link("search-engine", "yahoo", "https://yahoo.com")))
+            .unwrap();
+        assert_eq!(
+            cmd.run(&mut ctx, vec!["search-engine".to_string()]),
+            table(&["google", "yahoo"])
+        );
+    }
+
+    #[test]
+    fn test_parent_entity_run_skips_expired_links() {
+        let cmd = ParentEntity::default();
+        let mut ctx = ctx_with_links("search-engine");
+        let table = |links: &[&str]| {
+            Ok(CommandResult::Table(Table {
+                title: "Opening links:".to_string(),
+                rows: links.iter().map(|l| vec![l.to_string()]).collect(),
+            }))
+        };
+        let expire = |ctx: &mut Context, link: &str| {
+            ctx.store
+                .write(|ds| {
+                    ds.batch(|tx| {
+                        tx.update_meta("search-engine", link, |m| {
+                            m.expires = Some("2000-01-01".to_string())
+                        })
+                    })
+                })
+                .unwrap();
+        };
+        expire(&mut ctx, "yahoo");
+        assert_eq!(
+            cmd.run(&mut ctx, vec!["search-engine".to_string()]),
+            table(&["google"])
+        );
+        assert_eq!(
+            cmd.run(
+                &mut ctx,
+                vec!["search-engine".to_string(), "--include-expired".to_string()]
+            ),
+            table(&["google", "yahoo"])
+        );
+        // Naming an expired link still opens it
+        assert_eq!(
+            cmd.run(
+                &mut ctx,
+                vec!["search-engine".to_string(), "yahoo".to_string()]
+            ),
+            Ok(CommandResult::Silent)
+        );
+        expire(&mut ctx, "google");
+        let e = cmd
+            .run(&mut ctx, vec!["search-engine".to_string()])
+            .unwrap_err();
+        assert_eq!(e.kind, CommandErrorKind::LinkNotFound);
+        assert!(
+            e.message
+                .starts_with("Every link of parent 'search-engine' has expired")
+        );
+    }
+
+    #[test]
+    fn test_parent_entity_run_group_lists_children() {
+        let cmd = ParentEntity::default();
+        let mut ctx = Context::in_memory(
+            [
+                "work/infra/grafana",
+                "work/infra/loki",
+                "work/tools",
+                "workshop",
+            ]
+            .iter()
+            .map(|parent| {
+                (
+                    parent.to_string(),
+                    vec![("home".to_string(), "https://home.internal".to_string())],
+                )
+            })
+            .collect(),
+        );
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Table(Table {
+            title: "Parent entities under work:".to_string(),
+            rows: vec![
+                vec!["work/infra".to_string()],
+                vec!["work/tools".to_string()],
+            ],
+        }));
+        assert_eq!(cmd.run(&mut ctx, vec!["work".to_string()]), expected);
+        // The start of a name does not reach into nested names
+        assert_eq!(
+            cmd.run(&mut ctx, vec!["work/inf".to_string()]),
+            Ok(CommandResult::Table(Table {
+                title: "Parent entities under work/infra:".to_string(),
+                rows: vec![
+                    vec!["work/infra/grafana".to_string()],
+                    vec!["work/infra/loki".to_string()]
+                ],
+            }))
+        );
+        assert_eq!(
+            cmd.run(&mut ctx, vec!["work/too".to_string()]),
+            Ok(CommandResult::Table(Table {
+                title: "Opening links:".to_string(),
+                rows: vec![vec!["home".to_string()]],
+            }))
+        );
+    }
+
+    #[test]
     fn test_parent_entity_run_specific_link() {
         let args: Vec<String> = vec!["search-engine".to_string(), "google".to_string()];
         let cmd = ParentEntity::default();
-        let expected: Result<CommandResult, String> = Ok(CommandResult::Value("TODO: Implement open functionality for Parent Entity search-engine with Link Name google".to_string()));
-        let res = cmd.run(args);
+        let mut ctx = ctx_with_links("search-engine");
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Silent);
+        let res = cmd.run(&mut ctx, args);
         assert_eq!(res, expected);
     }
+
+    #[test]
+    fn test_parent_entity_run_link_not_found() {
+        let args: Vec<String> = vec!["search-engine".to_string(), "bing".to_string()];
+        let cmd = ParentEntity::default();
+        let mut ctx = ctx_with_links("search-engine");
+        assert!(cmd.run(&mut ctx, args).is_err());
+    }
+
+    #[test]
+    fn test_parent_entity_run_parent_not_found_suggests_parents() {
+        let args: Vec<String> = vec!["serach-engine".to_string()];
+        let cmd = ParentEntity::default();
+        let mut ctx = ctx_with_links("search-engine");
+        let err = cmd.run(&mut ctx, args).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::ParentNotFound);
+        assert!(err.message.contains("Did you mean: search-engine?"));
+    }
+
+    #[test]
+    fn test_parent_entity_run_unique_prefix() {
+        let args: Vec<String> = vec!["sea".to_string(), "goo".to_string()];
+        let cmd = ParentEntity::default();
+        let mut ctx = ctx_with_parents(&["search-engine", "work"]);
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Silent);
+        assert_eq!(cmd.run(&mut ctx, args), expected);
+    }
+
+    #[test]
+    fn test_parent_entity_run_ambiguous_prefix() {
+        let args: Vec<String> = vec!["se".to_string()];
+        let cmd = ParentEntity::default();
+        let mut ctx = ctx_with_parents(&["search-engine", "security"]);
+        let expected: Result<CommandResult, CommandError> = Err(CommandError {
+            kind: CommandErrorKind::ParentNotFound,
+            message: "Parent 'se' is the start of several parent entities: search-engine, security"
+                .to_string(),
+        });
+        assert_eq!(cmd.run(&mut ctx, args), expected);
+    }
+
+    #[test]
+    fn test_parent_entity_run_ambiguous_link_prefix() {
+        let args: Vec<String> = vec!["search-engine".to_string(), "g".to_string()];
+        let cmd = ParentEntity::default();
+        let mut ctx = Context::in_memory(vec![(
+            "search-engine".to_string(),
+            vec![
+                ("github".to_string(), "https://github.com".to_string()),
+                ("google".to_string(), "https://google.com".to_string()),
+            ],
+        )]);
+        let err = cmd.run(&mut ctx, args).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::LinkNotFound);
+        assert_eq!(
+            err.message,
+            "Link 'g' is the start of several links of parent 'search-engine': github, google"
+        );
+    }
+
+    #[test]
+    fn test_parent_entity_run_exact_name_that_is_also_a_prefix() {
+        let args: Vec<String> = vec!["work".to_string()];
+        let cmd = ParentEntity::default();
+        let mut ctx = ctx_with_parents(&["work", "work-docs"]);
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Table(Table {
+            title: "Opening links:".to_string(),
+            rows: vec![vec!["google".to_string()]],
+        }));
+        assert_eq!(cmd.run(&mut ctx, args), expected);
+    }
+
+    #[test]
+    fn test_parent_entity_run_exact_flag_disables_prefix() {
+        let args: Vec<String> = vec!["sea".to_string(), "--exact".to_string()];
+        let cmd = ParentEntity::default();
+        let mut ctx = ctx_with_parents(&["search-engine"]);
+        let err = cmd.run(&mut ctx, args).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::ParentNotFound);
+    }
+
+    #[test]
+    fn test_parent_entity_run_several_links() {
+        let args: Vec<String> = ["search-engine", "goo", "bing", "yahoo"]
+            .map(String::from)
+            .to_vec();
+        let cmd = ParentEntity::default();
+        let mut ctx = ctx_with_links("search-engine");
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Table(Table {
+            title: "Opened 2 of 3 links:".to_string(),
+            rows: vec![
+                vec!["google".to_string(), "opened".to_string()],
+                vec![
+                    "bing".to_string(),
+                    "Link 'bing' not found in parent 'search-engine' (tap data store error: Link not found)".to_string(),
+                ],
+                vec!["yahoo".to_string(), "opened".to_string()],
+            ],
+        }));
+        assert_eq!(cmd.run(&mut ctx, args), expected);
+    }
+
+    #[test]
+    fn test_parent_entity_run_several_links_none_found() {
+        let args: Vec<String> = ["search-engine", "bing", "yahooo"]
+            .map(String::from)
+            .to_vec();
+        let cmd = ParentEntity::default();
+        let mut ctx = ctx_with_links("search-engine");
+        let err = cmd.run(&mut ctx, args).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::LinkNotFound);
+        assert_eq!(err.message.lines().count(), 2);
+        assert!(err.message.contains("Did you mean: yahoo?"));
+    }
+
+    #[test]
+    fn test_parent_entity_run_confirms_many_links() {
+        let links: Vec<(String, String)> = (1..=6)
+            .map(|i| (format!("tab{i}"), format!("https://tab{i}.com")))
+            .collect();
+        let cmd = ParentEntity::default();
+        let mut ctx = Context {
+            prompt: Prompt::scripted("n\ny\n"),
+            ..Context::in_memory(vec![("big".to_string(), links)])
+        };
+        assert_eq!(
+            cmd.run(&mut ctx, vec!["big".to_string()]),
+            Ok(CommandResult::Value("Nothing was opened".to_string()))
+        );
+        let res = cmd.run(&mut ctx, vec!["big".to_string()]).unwrap();
+        assert!(res.to_string().starts_with("Opening links:"));
+        // --yes does not ask, the script has no answers left
+        assert!(
+            cmd.run(&mut ctx, vec!["big".to_string(), "--yes".to_string()])
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_parent_entity_run_pick() {
+        let links: Vec<(String, String)> = (1..=4)
+            .map(|i| (format!("tab{i}"), format!("https://tab{i}.com")))
+            .collect();
+        let cmd = ParentEntity::default();
+        let mut ctx = Context {
+            prompt: Prompt::scripted("everything\n2-3\n"),
+            ..Context::in_memory(vec![("big".to_string(), links.clone())])
+        };
+        let args = vec!["big".to_string(), "--pick".to_string()];
+        assert_eq!(
+            cmd.run(&mut ctx, args.clone()),
+            Ok(CommandResult::Table(Table {
+                title: "Opening links:".to_string(),
+                rows: vec![vec!["tab2".to_string()], vec!["tab3".to_string()]],
+            }))
+        );
+        // Without a terminal to ask in, --pick fails instead of waiting
+        let mut ctx = Context::in_memory(vec![("big".to_string(), links)]);
+        let err = cmd.run(&mut ctx, args).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::Usage);
+        assert!(
+            cmd.run(
+                &mut ctx,
+                vec!["big".to_string(), "tab1".to_string(), "--pick".to_string()]
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_parent_entity_run_type_filter() {
+        let cmd = ParentEntity::default();
+        let mut ctx = Context::in_memory(vec![(
+            "work".to_string(),
+            vec![
+                ("ci".to_string(), "https://ci.internal".to_string()),
+                ("notes".to_string(), "/home/me/notes.md".to_string()),
+                ("repo".to_string(), "/home/me/tap".to_string()),
+            ],
+        )]);
+        ctx.store
+            .write(|ds| {
+                ds.batch(|tx| {
+                    tx.update_meta("work", "ci", |m| m.default = true)?;
+                    tx.update_meta("work", "notes", |m| m.link_type = Some(LinkType::File))?;
+                    tx.update_meta("work", "repo", |m| m.link_type = Some(LinkType::Dir))
+                })
+            })
+            .unwrap();
+        let args = |types: &str| vec!["work".to_string(), "--type".to_string(), types.to_string()];
+        // Every link of the types is opened, not only the default link
+        assert_eq!(
+            cmd.run(&mut ctx, args("file,dir")),
+            Ok(CommandResult::Table(Table {
+                title: "Opening links:".to_string(),
+                rows: vec![vec!["notes".to_string()], vec!["repo".to_string()]],
+            }))
+        );
+        let err = cmd.run(&mut ctx, args("cmd")).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::LinkNotFound);
+        let err = cmd.run(&mut ctx, args("page")).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::Usage);
+        assert!(err.message.contains("url, file, dir, cmd"));
+    }
+
+    #[test]
+    fn test_parent_entity_run_template() {
+        let cmd = ParentEntity::default();
+        let mut ctx = Context::in_memory(vec![(
+            "gh".to_string(),
+            vec![(
+                "issues".to_string(),
+                "https://github.com/me/{repo}/issues?q={query}".to_string(),
+            )],
+        )]);
+        let args = |extra: &[&str]| {
+            let mut args = vec!["gh".to_string(), "issues".to_string()];
+            args.extend(extra.iter().map(|a| a.to_string()));
+            args
+        };
+        assert_eq!(
+            cmd.run(
+                &mut ctx,
+                args(&["--arg", "repo=tap", "--arg", "query=crash on open"])
+            ),
+            Ok(CommandResult::Silent)
+        );
+        let err = cmd.run(&mut ctx, args(&["--arg", "repo=tap"])).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::Usage);
+        assert!(err.message.starts_with(
+            "Link issues of parent entity gh is a template, no value was given for: query"
+        ));
+        let err = cmd.run(&mut ctx, vec!["gh".to_string()]).unwrap_err();
+        assert!(err.message.contains("no value was given for: repo, query"));
+        let err = cmd.run(&mut ctx, args(&["--arg", "repo"])).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::Usage);
+    }
+
+    #[test]
+    fn test_parent_entity_run_copy() {
+        let cmd = ParentEntity::default();
+        let mut ctx = Context::in_memory(vec![(
+            "gh".to_string(),
+            vec![(
+                "repo".to_string(),
+                "https://github.com/me/{repo}".to_string(),
+            )],
+        )]);
+        // The value is filled like it is to be opened, and printed
+        let args = |extra: &[&str]| {
+            let mut args = vec!["gh".to_string()];
+            args.extend(extra.iter().map(|a| a.to_string()));
+            args
+        };
+        assert_eq!(
+            cmd.run(&mut ctx, args(&["re", "--copy", "--arg", "repo=tap"])),
+            Ok(CommandResult::Value(
+                "https://github.com/me/tap".to_string()
+            ))
+        );
+        // The only link of the parent entity is copied without its name
+        assert_eq!(
+            cmd.run(&mut ctx, args(&["--copy", "--arg", "repo=tap"])),
+            Ok(CommandResult::Value(
+                "https://github.com/me/tap".to_string()
+            ))
+        );
+        let err = cmd
+            .run(&mut ctx, args(&["repo", "--copy", "--print"]))
+            .unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::Usage);
+    }
+
+    #[test]
+    fn test_parent_entity_run_unset_environment_variable() {
+        let cmd = ParentEntity::default();
+        let mut ctx = Context::in_memory(vec![(
+            "docs".to_string(),
+            vec![(
+                "index".to_string(),
+                "$TAP_TEST_NEVER_SET/docs/index.html".to_string(),
+            )],
+        )]);
+        let err = cmd
+            .run(&mut ctx, vec!["docs".to_string(), "index".to_string()])
+            .unwrap_err();
+        assert!(
+            err.message
+                .starts_with("Environment variable TAP_TEST_NEVER_SET is not set")
+        );
+        // The stored value is left as it is
+        assert_eq!(
+            ctx.store.read_link("docs", "index").unwrap().1,
+            "$TAP_TEST_NEVER_SET/docs/index.html"
+        );
+    }
+
+    #[test]
+    fn test_parent_entity_run_command_link() {
+        let cmd = ParentEntity::default();
+        let ctx_with_answers = |answers: &'static str| {
+            let ctx = Context {
+                prompt: Prompt::scripted(answers),
+                ..Context::in_memory(vec![(
+                    "vpn".to_string(),
+                    vec![("up".to_string(), "wg-quick up wg0".to_string())],
+                )])
+            };
+            ctx.store
+                .write(|ds| {
+                    ds.batch(|tx| {
+                        tx.update_meta("vpn", "up", |m| m.link_type = Some(LinkType::Cmd))
+                    })
+                })
+                .unwrap();
+            ctx
+        };
+        let args = vec!["vpn".to_string(), "up".to_string()];
+        assert_eq!(
+            cmd.run(&mut ctx_with_answers("n\n"), args.clone()),
+            Ok(CommandResult::Value("Nothing was run".to_string()))
+        );
+        assert_eq!(
+            cmd.run(&mut ctx_with_answers("y\n"), args.clone()),
+            Ok(CommandResult::Silent)
+        );
+        // Without a terminal to confirm in, commands are only run when the config file allows it
+        let mut ctx = Context {
+            prompt: Prompt::stdin(false),
+            ..ctx_with_answers("")
+        };
+        let err = cmd.run(&mut ctx, args).unwrap_err();
+        assert!(err.message.contains("run_commands = true"));
+    }
 }
@@ -0,0 +1,259 @@
+use crate::{
+    commands::{Command, CommandError, CommandResult, Table, display_bytes, display_examples},
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::command::Args,
+    utils::config::default_config_path,
+    utils::context::Context,
+    utils::json::Json,
+    utils::tap_data_store::{PathSource, xdg_source},
+};
+use std::path::{Path, PathBuf};
+
+pub(crate) struct Paths {
+    name: String,
+    description: String,
+    args: [String; 1],
+}
+
+impl Default for Paths {
+    fn default() -> Self {
+        Self {
+            name: "--where".to_string(),
+            description: "Show the data, index, and config files in use".to_string(),
+            args: ["[--json]".to_string()],
+        }
+    }
+}
+
+impl Command for Paths {
+    fn error_message(&self) -> String {
+        "expected 0-1 arguments, see the Usage section with tap --where --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --where command shows the data file, index file, and config file tap uses, whether each exists and its size, and the rule that picked it:\n\n");
+        s.push_str("  - flag     the --file or --index flag\n");
+        s.push_str("  - config   the data_dir of the config file, set by tap --migrate-data\n");
+        s.push_str("  - env      the XDG_DATA_HOME or XDG_CONFIG_HOME environment variable\n");
+        s.push_str("  - XDG      the XDG default under the home directory\n");
+        s.push_str("  - exe-dir  the directory of the tap executable\n\n");
+        s.push_str("The profile in use is shown as well, see tap --profile --help. Nothing is created, so it is safe to run before tap was ever used. Pass --json to print JSON instead.\n\n");
+        s.push_str("Command Structure: tap --where [--json]\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let mut args = Args::new(args);
+        let json = args.take_flag(&["--json"]) || ctx.format.is_structured();
+        match args.len() {
+            0 => {
+                let [data, index] = ctx.store.options().resolve_paths()?;
+                // The --file flag picks the data file itself, so no profile is in use
+                let profile = match data.1 {
+                    PathSource::Flag => None,
+                    _ => Some(ctx.store.options().active_profile()?),
+                };
+                let mut files = vec![("data", data), ("index", index)];
+                if let Some(config) = default_config_path() {
+                    files.push(("config", (config, xdg_source("XDG_CONFIG_HOME"))));
+                }
+                if json {
+                    let mut entries = vec![];
+                    if let Some(profile) = &profile {
+                        entries.push(("profile".to_string(), Json::from(profile.as_str())));
+                    }
+                    entries.push((
+                        "files".to_string(),
+                        Json::Array(
+                            files
+                                .iter()
+                                .map(|(name, file)| file_json(name, file))
+                                .collect(),
+                        ),
+                    ));
+                    return Ok(CommandResult::Json(Json::Object(entries)));
+                }
+                Ok(CommandResult::Table(Table {
+                    title: match &profile {
+                        Some(profile) => format!("Files in use by profile {profile}:"),
+                        None => "Files in use:".to_string(),
+                    },
+                    rows: files
+                        .iter()
+                        .map(|(name, (path, source))| {
+                            vec![
+                                name.to_string(),
+                                path.display().to_string(),
+                                match file_size(path) {
+                                    Some(size) => format!("exists, {}", display_bytes(size)),
+                                    None => "missing".to_string(),
+                                },
+                                source.to_string(),
+                            ]
+                        })
+                        .collect(),
+                }))
+            }
+            1 if args.is_flag(0, "--help") => Ok(CommandResult::Value(self.help_message())),
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+impl DisplayCommandAsRow for Paths {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            ("tap --where", "Show the files in use"),
+            (
+                "tap --file ./demo.tap_data --where",
+                "Show the files an alternate data file uses",
+            ),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+/// The size of the file at `path`, `None` when it does not exist
+fn file_size(path: &Path) -> Option<usize> {
+    path.metadata().ok().map(|m| m.len() as usize)
+}
+
+/// `{"name":"data","path":"...","exists":true,"size":42,"source":"flag"}`, the size is left out
+/// for a missing file
+fn file_json(name: &str, (path, source): &(PathBuf, PathSource)) -> Json {
+    let size = file_size(path);
+    let mut entries = vec![
+        ("name".to_string(), Json::from(name)),
+        (
+            "path".to_string(),
+            Json::from(path.display().to_string().as_str()),
+        ),
+        ("exists".to_string(), Json::Bool(size.is_some())),
+    ];
+    if let Some(size) = size {
+        entries.push(("size".to_string(), Json::Number(size as u64)));
+    }
+    entries.push((
+        "source".to_string(),
+        Json::from(source.to_string().as_str()),
+    ));
+    Json::Object(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::tap_data_store::TestDataFile;
+
+    #[test]
+    fn test_where_run_expected_help_arg() {
+        let cmd = Paths::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_where_run_unexpected_args() {
+        let cmd = Paths::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["random".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_where_run_file_flag() {
+        let data = TestDataFile::missing();
+        std::fs::write(&data, "work->\n  ci|https://ci.internal\n").unwrap();
+        let res = Paths::default().run(&mut Context::with_file(data.clone()), vec![]);
+        let Ok(CommandResult::Table(table)) = res else {
+            panic!("expected a table, got {res:?}");
+        };
+        assert_eq!(
+            table.rows[..2],
+            [
+                vec![
+                    "data".to_string(),
+                    data.display().to_string(),
+                    "exists, 32 B".to_string(),
+                    "flag".to_string(),
+                ],
+                vec![
+                    "index".to_string(),
+                    data.with_extension("tap_index").display().to_string(),
+                    "missing".to_string(),
+                    "flag".to_string(),
+                ],
+            ]
+        );
+        // Looking the files up never creates them
+        assert!(!data.with_extension("tap_index").exists());
+    }
+
+    #[test]
+    fn test_where_run_json() {
+        let data = TestDataFile::missing();
+        let res = Paths::default().run(
+            &mut Context::with_file(data.clone()),
+            vec!["--json".to_string()],
+        );
+        let Ok(CommandResult::Json(Json::Object(entries))) = res else {
+            panic!("expected a JSON object, got {res:?}");
+        };
+        let Json::Array(files) = &entries[0].1 else {
+            panic!("expected an array of files");
+        };
+        assert_eq!(
+            files[0],
+            Json::object([
+                ("name", Json::from("data")),
+                ("path", Json::from(data.display().to_string().as_str())),
+                ("exists", Json::Bool(false)),
+                ("source", Json::from("flag")),
+            ])
+        );
+        assert!(!data.exists());
+    }
+
+    #[test]
+    fn test_where_run_profile() {
+        let mut ctx = Context::default();
+        ctx.store.use_profile("work".to_string());
+        let res = Paths::default().run(&mut ctx, vec!["--json".to_string()]);
+        let Ok(CommandResult::Json(Json::Object(entries))) = res else {
+            panic!("expected a JSON object, got {res:?}");
+        };
+        assert_eq!(entries[0], ("profile".to_string(), Json::from("work")));
+        let Json::Array(files) = &entries[1].1 else {
+            panic!("expected an array of files");
+        };
+        let Json::Object(data) = &files[0] else {
+            panic!("expected the data file");
+        };
+        let Json::String(path) = &data[1].1 else {
+            panic!("expected the path of the data file");
+        };
+        assert!(PathBuf::from(path).ends_with("work/.tap_data"), "{path}");
+    }
+}
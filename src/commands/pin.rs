@@ -0,0 +1,166 @@
+use crate::{
+    commands::{Command, CommandError, CommandResult, display_examples},
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::command::Args,
+    utils::context::Context,
+    utils::style,
+};
+
+pub(crate) struct Pin {
+    name: String,
+    description: String,
+    args: [String; 2],
+}
+
+impl Default for Pin {
+    fn default() -> Self {
+        Self {
+            name: "--pin".to_string(),
+            description: "Pin a link so it is listed first".to_string(),
+            args: ["<Parent|here>".to_string(), "<Link>".to_string()],
+        }
+    }
+}
+
+impl Command for Pin {
+    fn error_message(&self) -> String {
+        "expected 2 arguments, see the Usage section with tap --pin --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --pin command marks a link as pinned. Pinned links are listed first by tap --show and by shell completion, marked with a star, and tap <Parent Entity> --pinned opens only the pinned links of a Parent Entity instead of all of them.\n\n");
+        s.push_str("The pin is kept on the line of the link in the data file, written as |pinned after its value. Unpin a link with tap --unpin.\n\n");
+        s.push_str("Command Structure: tap --pin <Parent Entity | here> <Link Name>\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let args = Args::new(args);
+        match args.len() {
+            1 if args.is_flag(0, "--help") => Ok(CommandResult::Value(self.help_message())),
+            2 => set_pinned(ctx, &args.parent(0)?, &args[1], true),
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+/// Pins or unpins a link, saying so when it already was
+pub(in crate::commands) fn set_pinned(
+    ctx: &Context,
+    parent_entity: &str,
+    link: &str,
+    pinned: bool,
+) -> Result<CommandResult, CommandError> {
+    let old = ctx
+        .store
+        .write(|ds| ds.batch(|tx| tx.update_meta(parent_entity, link, |meta| meta.pinned = pinned)))
+        .map_err(|e| ctx.store.with_suggestions(e, parent_entity, Some(link)))?;
+    let done = if pinned { "pinned" } else { "unpinned" };
+    if old.pinned == pinned {
+        return Ok(CommandResult::Value(format!(
+            "Link '{link}' of parent '{parent_entity}' is already {done}"
+        )));
+    }
+    Ok(CommandResult::Value(style::success(&format!(
+        "Link '{link}' of parent '{parent_entity}' is now {done}"
+    ))))
+}
+
+impl DisplayCommandAsRow for Pin {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            ("tap --pin work ci", "Pin the ci link of work"),
+            ("tap --pin here repo", "Pin a link of the current directory"),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::CommandErrorKind;
+
+    fn ctx() -> Context {
+        Context::in_memory(vec![(
+            "work".to_string(),
+            vec![
+                ("ci".to_string(), "https://ci.internal".to_string()),
+                ("mr".to_string(), "https://mr.internal".to_string()),
+            ],
+        )])
+    }
+
+    fn pin(ctx: &mut Context, args: &[&str]) -> Result<CommandResult, CommandError> {
+        Pin::default().run(ctx, args.iter().map(|a| a.to_string()).collect())
+    }
+
+    #[test]
+    fn test_pin_run_expected_help_arg() {
+        let cmd = Pin::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_pin_run_unexpected_args() {
+        let cmd = Pin::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["work".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_pin_link() {
+        let mut ctx = ctx();
+        assert_eq!(
+            pin(&mut ctx, &["work", "ci"]),
+            Ok(CommandResult::Value(style::success(
+                "Link 'ci' of parent 'work' is now pinned"
+            )))
+        );
+        assert!(ctx.store.read_meta("work").unwrap()["ci"].pinned);
+        assert_eq!(
+            pin(&mut ctx, &["work", "ci"]),
+            Ok(CommandResult::Value(
+                "Link 'ci' of parent 'work' is already pinned".to_string()
+            ))
+        );
+        assert_eq!(
+            set_pinned(&ctx, "work", "ci", false),
+            Ok(CommandResult::Value(style::success(
+                "Link 'ci' of parent 'work' is now unpinned"
+            )))
+        );
+        assert!(ctx.store.read_meta("work").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_pin_link_not_found() {
+        let e = pin(&mut ctx(), &["work", "cj"]).unwrap_err();
+        assert_eq!(e.kind, CommandErrorKind::LinkNotFound);
+        assert!(e.message.contains("Did you mean: ci?"), "{}", e.message);
+    }
+}
@@ -0,0 +1,181 @@
+use crate::{
+    commands::{Command, CommandError, CommandResult, Table, display_examples},
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::config::Config,
+    utils::context::Context,
+    utils::json::Json,
+    utils::style,
+    utils::tap_data_store::{DEFAULT_PROFILE, PROFILE_CONFIG_KEY, is_profile_name, profiles},
+};
+
+pub(crate) struct Profile {
+    name: String,
+    description: String,
+    args: [String; 2],
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            name: "--profile".to_string(),
+            description: "Use or list separate stores of links".to_string(),
+            args: ["<Name|list|use>".to_string(), "[Name]".to_string()],
+        }
+    }
+}
+
+impl Command for Profile {
+    fn error_message(&self) -> String {
+        "expected list or use <Name>, see the Usage section with tap --profile --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --profile keeps separate stores of links, like one for work and one for personal links, each with its own data files, completion, sessions, and groups. Put --profile <Name> before any command to run it against that profile, or set the TAP_PROFILE environment variable. Otherwise the profile set with tap --profile use is used, and the default profile when none was set.\n\n");
+        s.push_str(&format!("The {DEFAULT_PROFILE} profile keeps its files right in the data directory, so the links stored before there were profiles are its links. Any other profile keeps them in a directory of its own named after it, created the first time the profile is used. tap --where shows the profile in use and its files.\n\n"));
+        s.push_str("Command Structure(s):\n");
+        s.push_str("  - tap --profile <Name> <Command...>    (Run a command against a profile)\n");
+        s.push_str(
+            "  - tap --profile list                   (List the profiles that have links)\n",
+        );
+        s.push_str("  - tap --profile use <Name>             (Use a profile when none is given)\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        match args.as_slice() {
+            ["--help"] => Ok(CommandResult::Value(self.help_message())),
+            ["list"] => self.list(ctx),
+            ["use", name] => self.set_default(name),
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+impl Profile {
+    fn list(&self, ctx: &Context) -> Result<CommandResult, CommandError> {
+        let active = ctx.store.options().active_profile()?;
+        let mut profiles = profiles()?;
+        // The profile in use is listed even before it has links
+        if !profiles.contains(&active) {
+            profiles.push(active.clone());
+            profiles.sort();
+        }
+        if ctx.format.is_structured() {
+            return Ok(CommandResult::Json(Json::Array(
+                profiles
+                    .iter()
+                    .map(|profile| {
+                        Json::object([
+                            ("name", Json::from(profile.as_str())),
+                            ("active", Json::Bool(*profile == active)),
+                        ])
+                    })
+                    .collect(),
+            )));
+        }
+        Ok(CommandResult::Table(Table {
+            title: "Profiles:".to_string(),
+            rows: profiles
+                .into_iter()
+                .map(|profile| match profile == active {
+                    true => vec![style::highlight(&profile), "in use".to_string()],
+                    false => vec![profile, String::new()],
+                })
+                .collect(),
+        }))
+    }
+
+    fn set_default(&self, name: &str) -> Result<CommandResult, CommandError> {
+        if !is_profile_name(name) {
+            return Err(CommandError::usage(format!(
+                "{name} is not a valid profile name, use letters, digits, -, _, and ."
+            )));
+        }
+        let mut config = Config::load(None).map_err(|e| CommandError::from(e.to_string()))?;
+        config.set(PROFILE_CONFIG_KEY, name);
+        config
+            .save()
+            .map_err(|e| CommandError::from(e.to_string()))?;
+        Ok(CommandResult::Value(style::success(&format!(
+            "Profile {name} is now used when no profile is given"
+        ))))
+    }
+}
+
+impl DisplayCommandAsRow for Profile {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            (
+                "tap --profile work --add ci build https://ci.internal",
+                "Add a link to the work profile",
+            ),
+            (
+                "tap --profile work ci",
+                "Open the links of ci in the work profile",
+            ),
+            ("tap --profile list", "List the profiles"),
+            (
+                "tap --profile use personal",
+                "Use the personal profile from now on",
+            ),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::CommandErrorKind;
+
+    #[test]
+    fn test_profile_run_expected_help_arg() {
+        let cmd = Profile::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_profile_run_unexpected_args() {
+        let cmd = Profile::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        for args in [&[][..], &["use"], &["list", "work"], &["work"]] {
+            assert_eq!(
+                cmd.run(
+                    &mut Context::default(),
+                    args.iter().map(|a| a.to_string()).collect()
+                ),
+                expected,
+                "{args:?}"
+            );
+        }
+        let err = cmd
+            .run(
+                &mut Context::default(),
+                vec!["use".to_string(), "../work".to_string()],
+            )
+            .unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::Usage);
+    }
+}
@@ -0,0 +1,170 @@
+use crate::{
+    commands::{Command, CommandError, CommandResult, Table, display_examples},
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::context::Context,
+    utils::json::Json,
+    utils::style,
+    utils::tap_data_store::PruneSummary,
+};
+
+pub(crate) struct Prune {
+    name: String,
+    description: String,
+    args: [String; 0],
+}
+
+impl Default for Prune {
+    fn default() -> Self {
+        Self {
+            name: "--prune".to_string(),
+            description: "Remove parent entities without links and stale index entries".to_string(),
+            args: [],
+        }
+    }
+}
+
+impl Command for Prune {
+    fn error_message(&self) -> String {
+        "expected 0 arguments, see the Usage section with tap --prune --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --prune command removes the parent entities that have no links left, e.g. after deleting every link of one by hand, and the index file entries of parent entities that are no longer in the data file. Everything else in the data file is left as it is, use tap --compact to also sort it and merge duplicates.\n\n");
+        s.push_str("The data file is only rewritten when there is something to remove, and tap --undo brings the removed parent entities back.\n\n");
+        s.push_str("Command Structure: tap --prune\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        match args.len() {
+            0 => {
+                let summary = ctx.store.write(|ds| ds.prune())?;
+                if ctx.format.is_structured() {
+                    return Ok(CommandResult::Json(prune_json(&summary)));
+                }
+                if summary.is_empty() {
+                    return Ok(CommandResult::Value("Nothing to prune".to_string()));
+                }
+                let rows: Vec<Vec<String>> = summary
+                    .empty_parents
+                    .iter()
+                    .map(|parent| vec!["empty parent entity".to_string(), style::parent(parent)])
+                    .chain(summary.orphaned_index_entries.iter().map(|parent| {
+                        vec![
+                            "index entry without data".to_string(),
+                            style::parent(parent),
+                        ]
+                    }))
+                    .collect();
+                Ok(CommandResult::Table(Table {
+                    title: format!("Pruned {} entries:", rows.len()),
+                    rows,
+                }))
+            }
+            1 if args[0] == "--help" => Ok(CommandResult::Value(self.help_message())),
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+/// `{"empty_parents":["old"],"orphaned_index_entries":["gone"]}`
+fn prune_json(summary: &PruneSummary) -> Json {
+    let names = |parents: &[String]| {
+        Json::Array(
+            parents
+                .iter()
+                .map(|parent| Json::from(parent.as_str()))
+                .collect(),
+        )
+    };
+    Json::object([
+        ("empty_parents", names(&summary.empty_parents)),
+        (
+            "orphaned_index_entries",
+            names(&summary.orphaned_index_entries),
+        ),
+    ])
+}
+
+impl DisplayCommandAsRow for Prune {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [(
+            "tap --prune",
+            "Remove parent entities left without links after editing the data file",
+        )]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prune_run_expected_help_arg() {
+        let cmd = Prune::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_prune_run_unexpected_args() {
+        let cmd = Prune::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["work".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_prune_run_nothing_to_prune() {
+        let cmd = Prune::default();
+        let mut ctx = Context::in_memory(vec![(
+            "work".to_string(),
+            vec![("ci".to_string(), "https://ci.internal".to_string())],
+        )]);
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value("Nothing to prune".to_string()));
+        assert_eq!(cmd.run(&mut ctx, vec![]), expected);
+    }
+
+    #[test]
+    fn test_prune_run_removes_empty_parents() {
+        let cmd = Prune::default();
+        let mut ctx = Context::in_memory(vec![
+            ("old".to_string(), vec![]),
+            (
+                "work".to_string(),
+                vec![("ci".to_string(), "https://ci.internal".to_string())],
+            ),
+        ]);
+        let res = cmd.run(&mut ctx, vec![]).unwrap();
+        assert!(res.to_string().starts_with("Pruned 1 entries:"));
+        assert_eq!(ctx.store.parents().unwrap(), vec!["work".to_string()]);
+        assert_eq!(
+            cmd.run(&mut ctx, vec![]),
+            Ok(CommandResult::Value("Nothing to prune".to_string()))
+        );
+    }
+}
@@ -0,0 +1,248 @@
+use crate::{
+    commands::{
+        Command, CommandError, CommandResult, display_examples, open_value, resolve_parent_prefix,
+        value_to_open,
+    },
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::command::Args,
+    utils::context::Context,
+    utils::style,
+};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub(crate) struct Random {
+    name: String,
+    description: String,
+    args: [String; 2],
+}
+
+impl Default for Random {
+    fn default() -> Self {
+        Self {
+            name: "--random".to_string(),
+            description: "Open a random link".to_string(),
+            args: ["[Parent Entity]".to_string(), "[--print]".to_string()],
+        }
+    }
+}
+
+impl Command for Random {
+    fn error_message(&self) -> String {
+        "expected 0-1 arguments, see the Usage section with tap --random --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --random command opens a link picked at random, every link being as likely as any other. Give a Parent Entity to only pick among its links, it can be shortened to the start of its name like when opening links. The picked link is printed as <Parent Entity>/<Link Name>.\n\n");
+        s.push_str(
+            "Pass --print to only print the picked link and its value without opening it.\n\n",
+        );
+        s.push_str("Command Structure: tap --random [Parent Entity] [--print]\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let mut args = Args::new(args);
+        let print = args.take_flag(&["--print"]);
+        match args.len() {
+            0 => self.open_random(ctx, None, print, seed()),
+            1 if args.is_flag(0, "--help") => Ok(CommandResult::Value(self.help_message())),
+            1 => self.open_random(ctx, Some(&args.parent(0)?), print, seed()),
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+impl Random {
+    /// Opens the link picked by `seed` among every link, or the links of `parent` when given
+    fn open_random(
+        &self,
+        ctx: &Context,
+        parent: Option<&str>,
+        print: bool,
+        seed: u64,
+    ) -> Result<CommandResult, CommandError> {
+        let links: Vec<(String, String, String)> = match parent {
+            Some(parent) => {
+                let parent = resolve_parent_prefix(&ctx.store, parent)?;
+                ctx.store
+                    .read_parent(&parent)
+                    .map_err(|e| ctx.store.with_suggestions(e, &parent, None))?
+                    .into_iter()
+                    .map(|(link, value)| (parent.clone(), link, value))
+                    .collect()
+            }
+            None => ctx
+                .store
+                .all_links()?
+                .into_iter()
+                .flat_map(|(parent, links)| {
+                    links
+                        .into_iter()
+                        .map(move |(link, value)| (parent.clone(), link, value))
+                })
+                .collect(),
+        };
+        if links.is_empty() {
+            return Err(
+                CommandError::from("There are no links to pick from".to_string())
+                    .with_hint("Add one with tap -a <Parent Entity> <Link Name> <Value>"),
+            );
+        }
+        let (parent, link, value) = &links[pick(seed, links.len())];
+        if print {
+            return Ok(CommandResult::Value(format!("{parent}/{link} {value}")));
+        }
+        let value = value_to_open(parent, link, value, &[])?;
+        if !open_value(ctx, parent, link, &value)? {
+            return Ok(CommandResult::Value("Nothing was run".to_string()));
+        }
+        Ok(CommandResult::Value(format!(
+            "Opening {}",
+            style::highlight(&format!("{parent}/{link}"))
+        )))
+    }
+}
+
+/// A seed that differs between runs
+fn seed() -> u64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64);
+    nanos ^ u64::from(std::process::id()).rotate_left(32)
+}
+
+/// An index below `len` picked by `seed`, every index being as likely. The seed is mixed with
+/// SplitMix64, then mapped to the range by multiplying, see https://arxiv.org/abs/1805.10941
+fn pick(seed: u64, len: usize) -> usize {
+    let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^= z >> 31;
+    ((u128::from(z) * len as u128) >> 64) as usize
+}
+
+impl DisplayCommandAsRow for Random {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            ("tap --random", "Open a random link"),
+            ("tap --random search-engines", "Open a random search engine"),
+            (
+                "tap --random --print",
+                "Print a random link without opening it",
+            ),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::CommandErrorKind;
+
+    fn ctx() -> Context {
+        Context::in_memory(vec![
+            (
+                "search-engines".to_string(),
+                vec![
+                    ("google".to_string(), "https://google.com".to_string()),
+                    ("yahoo".to_string(), "https://yahoo.com".to_string()),
+                ],
+            ),
+            (
+                "work".to_string(),
+                vec![(
+                    "retro-board".to_string(),
+                    "https://retro.internal".to_string(),
+                )],
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_random_run_expected_help_arg() {
+        let cmd = Random::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_random_run_unexpected_args() {
+        let cmd = Random::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(
+            cmd.run(
+                &mut Context::default(),
+                vec!["work".to_string(), "ci".to_string()]
+            ),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_random_seeded() {
+        let cmd = Random::default();
+        let picked =
+            |parent: Option<&str>, seed: u64| match cmd.open_random(&ctx(), parent, true, seed) {
+                Ok(CommandResult::Value(s)) => s,
+                res => panic!("expected a value, got {res:?}"),
+            };
+        assert_eq!(picked(None, 3), "search-engines/google https://google.com");
+        assert_eq!(picked(None, 1), "search-engines/yahoo https://yahoo.com");
+        assert_eq!(picked(None, 6), "work/retro-board https://retro.internal");
+        assert_eq!(
+            picked(Some("search"), 1),
+            "search-engines/yahoo https://yahoo.com"
+        );
+        assert_eq!(
+            cmd.open_random(&ctx(), Some("work"), false, 1),
+            Ok(CommandResult::Value(format!(
+                "Opening {}",
+                style::highlight("work/retro-board")
+            )))
+        );
+    }
+
+    #[test]
+    fn test_random_pick_is_uniform() {
+        let mut counts = [0; 3];
+        for seed in 0..3_000 {
+            counts[pick(seed, 3)] += 1;
+        }
+        assert!(
+            counts.iter().all(|&n| (900..1_100).contains(&n)),
+            "{counts:?}"
+        );
+    }
+
+    #[test]
+    fn test_random_empty() {
+        let cmd = Random::default();
+        let e = cmd
+            .open_random(&Context::in_memory(vec![]), None, true, 1)
+            .unwrap_err();
+        assert_eq!(e.kind, CommandErrorKind::Generic);
+        let e = cmd.open_random(&ctx(), Some("news"), true, 1).unwrap_err();
+        assert_eq!(e.kind, CommandErrorKind::ParentNotFound);
+    }
+}
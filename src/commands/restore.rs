@@ -0,0 +1,433 @@
+use crate::{
+    commands::{
+        Command, CommandError, CommandResult, Table, backup::write_backup, display_examples,
+    },
+    utils::backup::Archive,
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::command::Args,
+    utils::context::Context,
+    utils::style,
+    utils::tap_data_store::{check_data_file, sidecar_paths},
+};
+use std::path::Path;
+
+/// The files kept next to the data file that are restored with it: those naming its links, the
+/// links moved out of it to the archive and the trash, and its history. The recently opened links
+/// in an archive are left out, they record what was opened since.
+const RESTORED_SIDECARS: [&str; 7] = [
+    "aliases",
+    "groups",
+    "sessions",
+    "history.1",
+    "history",
+    "trash",
+    "archive",
+];
+
+pub(crate) struct Restore {
+    name: String,
+    description: String,
+    args: [String; 3],
+}
+
+impl Default for Restore {
+    fn default() -> Self {
+        Self {
+            name: "--restore".to_string(),
+            description: "Restore the data file from a backup".to_string(),
+            args: [
+                "<Archive>".to_string(),
+                "[--list]".to_string(),
+                "[--yes]".to_string(),
+            ],
+        }
+    }
+}
+
+impl Command for Restore {
+    fn error_message(&self) -> String {
+        "expected 1 argument, see the Usage section with tap --restore --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --restore command replaces the data file with the one in a .tapbackup archive written by tap --backup, and rebuilds the index. The aliases, groups, sessions, history, archived links, and trashed links in the archive are restored with it. The archive is checked against the hashes it lists and its data file parsed before anything is changed, and the current data file is backed up first to the backups directory next to it. Archives written by a newer version of tap in a format this one does not know are refused. The config file of the archive is not restored.\n\n");
+        s.push_str("Restoring over a data file with links asks for confirmation, pass --yes to skip it (required when not run in a terminal). Pass --list to only show what is in the archive.\n\n");
+        s.push_str("Command Structure: tap --restore <Archive> [--list] [-y | --yes]\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let mut args = Args::new(args);
+        let list = args.take_flag(&["--list"]);
+        let yes = args.take_flag(&["-y", "--yes"]);
+        match args.len() {
+            1 if args.is_flag(0, "--help") => Ok(CommandResult::Value(self.help_message())),
+            1 => {
+                let path = Path::new(&args[0]);
+                let contents = std::fs::read_to_string(path)
+                    .map_err(|e| format!("Could not read backup {}: {e}", path.display()))?;
+                let archive = Archive::parse(&contents)?;
+                let Some(data) = archive.file("data") else {
+                    return Err(format!("The backup {} has no data file", path.display()).into());
+                };
+                let links = check_data_file(data).map_err(|e| {
+                    CommandError::from(e).with_hint("The data file of the backup can not be parsed")
+                })?;
+                let parents = links.len();
+                let links = links.iter().map(|(_, links)| links.len()).sum::<usize>();
+                if list {
+                    return Ok(CommandResult::Table(Table {
+                        title: format!("Backup {}:", path.display()),
+                        rows: [
+                            ("created", archive.created.clone()),
+                            ("version", archive.version.clone()),
+                            (
+                                "files",
+                                archive
+                                    .files
+                                    .iter()
+                                    .map(|(name, _)| name.as_str())
+                                    .collect::<Vec<&str>>()
+                                    .join(", "),
+                            ),
+                            ("parent entities", parents.to_string()),
+                            ("links", links.to_string()),
+                        ]
+                        .into_iter()
+                        .map(|(key, value)| vec![key.to_string(), value])
+                        .collect(),
+                    }));
+                }
+                ctx.store.options().check_writable()?;
+                if !yes && !self.confirm_restore(ctx, path)? {
+                    return Ok(CommandResult::Value("Nothing was restored".to_string()));
+                }
+                let mut s = String::new();
+                if let Some(previous) = self.backup_current(ctx)? {
+                    s.push_str(&format!(
+                        "Backed up the current data file to {}\n",
+                        previous.display()
+                    ));
+                }
+                ctx.store.replace_data_file(data)?;
+                self.restore_sidecars(ctx, &archive)?;
+                s.push_str(&style::success(&format!(
+                    "Restored {parents} parent entities and {links} links from the backup of {}",
+                    archive.created
+                )));
+                Ok(CommandResult::Value(s))
+            }
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+impl Restore {
+    /// Restoring over links asks for confirmation in a terminal, and fails otherwise as `--yes`
+    /// must be passed to restore without asking
+    fn confirm_restore(&self, ctx: &Context, path: &Path) -> Result<bool, CommandError> {
+        let parents = ctx.store.parents()?;
+        if parents.is_empty() {
+            return Ok(true);
+        }
+        if !ctx.prompt.is_interactive() {
+            return Err(CommandError::usage(format!(
+                "Restoring replaces the {} parent entities of the current data file, pass --yes to restore without confirmation",
+                parents.len()
+            )));
+        }
+        Ok(ctx.prompt.confirm(&format!(
+            "Replace the {} parent entities of the current data file with the backup {}?",
+            parents.len(),
+            path.display()
+        ))?)
+    }
+
+    /// Backs up the current data file, whether it parses or not, returning the path of the backup.
+    /// `None` when there is no data file yet.
+    fn backup_current(&self, ctx: &Context) -> Result<Option<std::path::PathBuf>, CommandError> {
+        let [(data, _), _] = ctx.store.options().resolve_paths()?;
+        if !data.exists() {
+            return Ok(None);
+        }
+        write_backup(ctx.store.options(), None, true).map(Some)
+    }
+
+    /// Writes the files of `RESTORED_SIDECARS` the archive holds next to the data file. Those it
+    /// does not hold are left as they are.
+    fn restore_sidecars(&self, ctx: &Context, archive: &Archive) -> Result<(), CommandError> {
+        let [(data, _), _] = ctx.store.options().resolve_paths()?;
+        for (name, path) in sidecar_paths(&data) {
+            if let Some(contents) = archive
+                .file(name)
+                .filter(|_| RESTORED_SIDECARS.contains(&name))
+            {
+                std::fs::write(&path, contents)
+                    .map_err(|e| format!("Could not restore {}: {e}", path.display()))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl DisplayCommandAsRow for Restore {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            (
+                "tap --restore backups/tap-backup-2024-06-01T12-30-05.tapbackup",
+                "Restore a backup",
+            ),
+            (
+                "tap --restore backups/tap-backup-2024-06-01T12-30-05.tapbackup --list",
+                "Show what is in a backup",
+            ),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::{
+        CommandErrorKind,
+        backup::{Backup, default_backup_dir},
+    };
+    use crate::utils::history::{history_path_for, rotated_path};
+    use crate::utils::prompt::Prompt;
+    use crate::utils::sessions::sessions_path_for;
+    use crate::utils::tap_data_store::{TestDataFile, archive_path_for, index_path_for};
+    use crate::utils::trash::trash_path_for;
+
+    const LINKS: &str = "work->\n  ci|https://ci.internal\n  mr|https://mr.internal\n";
+
+    /// Backs up the data file, returning the path of the archive
+    fn backup(data: &Path) -> String {
+        match Backup::default().run(&mut Context::with_file(data.to_path_buf()), vec![]) {
+            Ok(CommandResult::Value(path)) => path,
+            res => panic!("expected the path of the backup, got {res:?}"),
+        }
+    }
+
+    fn restore(data: &Path, args: &[&str]) -> Result<CommandResult, CommandError> {
+        Restore::default().run(
+            &mut Context::with_file(data.to_path_buf()),
+            args.iter().map(|a| a.to_string()).collect(),
+        )
+    }
+
+    #[test]
+    fn test_restore_run_expected_help_arg() {
+        let cmd = Restore::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_restore_run_unexpected_args() {
+        let cmd = Restore::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(cmd.run(&mut Context::default(), vec![]), expected);
+    }
+
+    #[test]
+    fn test_restore_round_trip() {
+        let data = TestDataFile::new(LINKS);
+        let archive = backup(&data);
+        let ctx = Context::with_file(data.clone());
+        ctx.store
+            .write(|ds| ds.delete("work".to_string(), Some("ci".to_string())))
+            .unwrap();
+        ctx.store
+            .write(|ds| {
+                ds.add_link(
+                    "news".to_string(),
+                    "bbc".to_string(),
+                    "https://bbc.com".to_string(),
+                )
+            })
+            .unwrap();
+        let Ok(CommandResult::Value(res)) = restore(&data, &[&archive, "--yes"]) else {
+            panic!("expected the restore to succeed");
+        };
+        assert!(
+            res.contains("Restored 1 parent entities and 2 links"),
+            "{res}"
+        );
+        assert_eq!(std::fs::read_to_string(&data).unwrap(), LINKS);
+        assert_eq!(
+            std::fs::read_to_string(index_path_for(&data)).unwrap(),
+            "work|0\n"
+        );
+        // The mutated state was backed up before being replaced
+        let previous = res
+            .lines()
+            .next()
+            .and_then(|line| line.strip_prefix("Backed up the current data file to "))
+            .unwrap();
+        let previous = Archive::parse(&std::fs::read_to_string(previous).unwrap()).unwrap();
+        assert!(
+            previous
+                .file("data")
+                .unwrap()
+                .contains("bbc|https://bbc.com")
+        );
+    }
+
+    #[test]
+    fn test_restore_list() {
+        let data = TestDataFile::new(LINKS);
+        let archive = backup(&data);
+        let Ok(CommandResult::Table(table)) = restore(&data, &[&archive, "--list"]) else {
+            panic!("expected a table");
+        };
+        assert_eq!(table.rows[1], ["version", env!("CARGO_PKG_VERSION")]);
+        assert_eq!(table.rows[3], ["parent entities", "1"]);
+        assert_eq!(table.rows[4], ["links", "2"]);
+    }
+
+    #[test]
+    fn test_restore_needs_confirmation() {
+        let data = TestDataFile::new(LINKS);
+        let archive = backup(&data);
+        std::fs::write(&data, "news->\n  bbc|https://bbc.com\n").unwrap();
+        let e = restore(&data, &[&archive]).unwrap_err();
+        assert_eq!(e.kind, CommandErrorKind::Usage);
+        let mut ctx = Context {
+            prompt: Prompt::scripted("n\n"),
+            ..Context::with_file(data.clone())
+        };
+        let res = Restore::default().run(&mut ctx, vec![archive.clone()]);
+        assert_eq!(
+            res,
+            Ok(CommandResult::Value("Nothing was restored".to_string()))
+        );
+        assert!(
+            std::fs::read_to_string(&data)
+                .unwrap()
+                .starts_with("news->")
+        );
+    }
+
+    #[test]
+    fn test_restore_sidecars_round_trip() {
+        let data = TestDataFile::new(LINKS);
+        std::fs::write(sessions_path_for(&data), "morning\twork\tci\n").unwrap();
+        let archive = backup(&data);
+        std::fs::write(&data, "news->\n  bbc|https://bbc.com\n").unwrap();
+        std::fs::write(sessions_path_for(&data), "evening\tnews\tbbc\n").unwrap();
+        let Ok(CommandResult::Value(res)) = restore(&data, &[&archive, "--yes"]) else {
+            panic!("expected the restore to succeed");
+        };
+        assert!(
+            res.contains("Restored 1 parent entities and 2 links"),
+            "{res}"
+        );
+        assert_eq!(std::fs::read_to_string(&data).unwrap(), LINKS);
+        assert_eq!(
+            std::fs::read_to_string(sessions_path_for(&data)).unwrap(),
+            "morning\twork\tci\n"
+        );
+    }
+
+    #[test]
+    fn test_restore_archive_trash_and_history_round_trip() {
+        let data = TestDataFile::new(LINKS);
+        let archived = "work->\n  wiki|https://wiki.example.com\n";
+        let trashed = "2024-06-01T12:30:05Z\twork\tdocs\thttps://docs.example.com\n";
+        let rotated = "2024-06-01T12:30:05Z\tdelete\twork\twiki\thttps://wiki.example.com\t\n";
+        std::fs::write(archive_path_for(&data), archived).unwrap();
+        std::fs::write(trash_path_for(&data), trashed).unwrap();
+        std::fs::write(rotated_path(&history_path_for(&data)), rotated).unwrap();
+        let archive = backup(&data);
+        std::fs::remove_file(archive_path_for(&data)).unwrap();
+        std::fs::write(trash_path_for(&data), "").unwrap();
+        std::fs::remove_file(rotated_path(&history_path_for(&data))).unwrap();
+        assert!(restore(&data, &[&archive, "--yes"]).is_ok());
+        assert_eq!(
+            std::fs::read_to_string(archive_path_for(&data)).unwrap(),
+            archived
+        );
+        assert_eq!(
+            std::fs::read_to_string(trash_path_for(&data)).unwrap(),
+            trashed
+        );
+        assert_eq!(
+            std::fs::read_to_string(rotated_path(&history_path_for(&data))).unwrap(),
+            rotated
+        );
+    }
+
+    #[test]
+    fn test_restore_rejects_corrupted_archive() {
+        let data = TestDataFile::new(LINKS);
+        let archive = data.with_file_name("corrupted.tapbackup");
+        let text = std::fs::read_to_string(backup(&data))
+            .unwrap()
+            .replace("https://", "http://x");
+        std::fs::write(&archive, text).unwrap();
+        std::fs::write(&data, "news->\n  bbc|https://bbc.com\n").unwrap();
+        let e = restore(&data, &[archive.to_str().unwrap(), "--yes"]).unwrap_err();
+        assert_eq!(e.kind, CommandErrorKind::InvalidData);
+        // Nothing was touched, not even backed up
+        assert!(
+            std::fs::read_to_string(&data)
+                .unwrap()
+                .starts_with("news->")
+        );
+        assert_eq!(
+            std::fs::read_dir(default_backup_dir(&data))
+                .unwrap()
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_restore_rejects_newer_format() {
+        let data = TestDataFile::new(LINKS);
+        let archive = backup(&data);
+        let text = std::fs::read_to_string(&archive)
+            .unwrap()
+            .replace("tap-backup 2", "tap-backup 3");
+        std::fs::write(&archive, text).unwrap();
+        let e = restore(&data, &[&archive, "--yes"]).unwrap_err();
+        assert_eq!(e.kind, CommandErrorKind::InvalidData);
+        assert!(e.message.contains("newer version of tap"), "{}", e.message);
+        assert_eq!(std::fs::read_to_string(&data).unwrap(), LINKS);
+    }
+
+    #[test]
+    fn test_restore_rejects_invalid_archive() {
+        let data = TestDataFile::new(LINKS);
+        let archive = data.with_file_name("broken.tapbackup");
+        std::fs::write(
+            &archive,
+            "tap-backup 1\ncreated x\nversion 0.1.0\nfile data 99\nwork->\n",
+        )
+        .unwrap();
+        let e = restore(&data, &[archive.to_str().unwrap(), "--yes"]).unwrap_err();
+        assert_eq!(e.kind, CommandErrorKind::InvalidData);
+        assert_eq!(std::fs::read_to_string(&data).unwrap(), LINKS);
+    }
+}
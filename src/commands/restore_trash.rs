@@ -0,0 +1,179 @@
+use crate::{
+    commands::{Command, CommandError, CommandErrorKind, CommandResult, display_examples},
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::command::Args,
+    utils::context::Context,
+    utils::style,
+};
+
+pub(crate) struct RestoreTrash {
+    name: String,
+    description: String,
+    args: [String; 2],
+}
+
+impl Default for RestoreTrash {
+    fn default() -> Self {
+        Self {
+            name: "--restore-trash".to_string(),
+            description: "Put deleted links back".to_string(),
+            args: ["<Parent|here>".to_string(), "[Link]".to_string()],
+        }
+    }
+}
+
+impl Command for RestoreTrash {
+    fn error_message(&self) -> String {
+        "expected 1-2 arguments, see the Usage section with tap --restore-trash --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --restore-trash command puts links removed with tap --delete back from the trash, see tap --trash. Leave out the Link Name to restore every deleted link of the Parent Entity. A link deleted more than once gets the value it had when it was deleted last.\n\n");
+        s.push_str("A link that was added again since it was deleted is never replaced, delete or rename it first.\n\n");
+        s.push_str("Command Structure: tap --restore-trash <Parent Entity | here> [Link Name]\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let args = Args::new(args);
+        let (parent_entity, link) = match args.len() {
+            1 if args.is_flag(0, "--help") => {
+                return Ok(CommandResult::Value(self.help_message()));
+            }
+            1 => (args.parent(0)?, None),
+            2 => (args.parent(0)?, Some(args[1].trim())),
+            _ => return Err(self.usage_error()),
+        };
+        let restored = ctx
+            .store
+            .write(|ds| ds.restore_trash(&parent_entity, link))
+            .map_err(|e| match CommandError::from(e) {
+                e if e.kind == CommandErrorKind::ParentNotFound
+                    || e.kind == CommandErrorKind::LinkNotFound =>
+                {
+                    e.with_hint("List the deleted links with tap --trash")
+                }
+                e => e,
+            })?;
+        let message = match restored.as_slice() {
+            [(link, _)] => format!("Restored link '{link}' of parent '{parent_entity}'"),
+            links => format!("Restored {} links of parent '{parent_entity}'", links.len()),
+        };
+        Ok(CommandResult::Value(style::success(&message)))
+    }
+}
+
+impl DisplayCommandAsRow for RestoreTrash {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            (
+                "tap --restore-trash work ci",
+                "Put the deleted link ci of work back",
+            ),
+            (
+                "tap --restore-trash work",
+                "Put every deleted link of work back",
+            ),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx() -> Context {
+        Context::in_memory(vec![(
+            "work".to_string(),
+            vec![
+                ("ci".to_string(), "https://ci.internal".to_string()),
+                ("mr".to_string(), "https://mr.internal".to_string()),
+            ],
+        )])
+    }
+
+    fn restore(ctx: &mut Context, args: &[&str]) -> Result<CommandResult, CommandError> {
+        RestoreTrash::default().run(ctx, args.iter().map(|a| a.to_string()).collect())
+    }
+
+    #[test]
+    fn test_restore_trash_run_expected_help_arg() {
+        let cmd = RestoreTrash::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_restore_trash_run_unexpected_args() {
+        let cmd = RestoreTrash::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(cmd.run(&mut Context::default(), vec![]), expected);
+    }
+
+    #[test]
+    fn test_restore_trash_parent_and_link() {
+        let mut ctx = ctx();
+        ctx.store
+            .write(|ds| ds.delete("work".to_string(), None))
+            .unwrap();
+        assert!(ctx.store.parents().unwrap().is_empty());
+        assert_eq!(
+            restore(&mut ctx, &["work", "mr"]),
+            Ok(CommandResult::Value(style::success(
+                "Restored link 'mr' of parent 'work'"
+            )))
+        );
+        // Only the links still in the trash are restored
+        assert_eq!(
+            restore(&mut ctx, &["work"]),
+            Ok(CommandResult::Value(style::success(
+                "Restored link 'ci' of parent 'work'"
+            )))
+        );
+        assert_eq!(ctx.store.links("work").unwrap(), ["ci", "mr"]);
+        assert!(ctx.store.trashed().unwrap().is_empty());
+        let e = restore(&mut ctx, &["work"]).unwrap_err();
+        assert_eq!(e.kind, CommandErrorKind::ParentNotFound);
+    }
+
+    #[test]
+    fn test_restore_trash_collision() {
+        let mut ctx = ctx();
+        ctx.store
+            .write(|ds| ds.delete("work".to_string(), Some("ci".to_string())))
+            .unwrap();
+        ctx.store
+            .write(|ds| ds.add_link("work".into(), "ci".into(), "https://new-ci".into()))
+            .unwrap();
+        let e = restore(&mut ctx, &["work", "ci"]).unwrap_err();
+        assert!(
+            e.message
+                .starts_with("Link 'ci' already exists in parent 'work'"),
+            "{}",
+            e.message
+        );
+        // The deleted link stays in the trash
+        assert_eq!(ctx.store.trashed().unwrap().len(), 1);
+    }
+}
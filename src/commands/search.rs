@@ -0,0 +1,475 @@
+use crate::{
+    commands::{Command, CommandError, CommandResult, display_examples, open_value, value_to_open},
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::command::Args,
+    utils::context::Context,
+    utils::json::Json,
+    utils::log,
+    utils::style,
+};
+use std::ops::Range;
+
+pub(crate) struct Search {
+    name: String,
+    description: String,
+    args: [String; 3],
+}
+
+impl Default for Search {
+    fn default() -> Self {
+        Self {
+            name: "--search".to_string(),
+            description: "Find links by name, value, or note".to_string(),
+            args: [
+                "<Term>".to_string(),
+                "[--in parents|links|values|notes]".to_string(),
+                "[--open]".to_string(),
+            ],
+        }
+    }
+}
+
+/// The parts of a link a search looks at
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Field {
+    Parent,
+    Link,
+    Value,
+    Note,
+}
+
+impl Field {
+    const ALL: [Field; 4] = [Field::Parent, Field::Link, Field::Value, Field::Note];
+
+    /// The name passed to `--in`
+    fn scope(self) -> &'static str {
+        match self {
+            Field::Parent => "parents",
+            Field::Link => "links",
+            Field::Value => "values",
+            Field::Note => "notes",
+        }
+    }
+
+    /// The name of the field in structured output
+    fn key(self) -> &'static str {
+        match self {
+            Field::Parent => "parent",
+            Field::Link => "link",
+            Field::Value => "value",
+            Field::Note => "note",
+        }
+    }
+}
+
+/// A link with the part of each searched field that matched the term
+struct Match {
+    parent: String,
+    link: String,
+    value: String,
+    /// The note of the link, empty when it has none
+    note: String,
+    ranges: Vec<(Field, Range<usize>)>,
+}
+
+impl Match {
+    fn text(&self, field: Field) -> &str {
+        match field {
+            Field::Parent => &self.parent,
+            Field::Link => &self.link,
+            Field::Value => &self.value,
+            Field::Note => &self.note,
+        }
+    }
+
+    /// The field with the matching part highlighted
+    fn display(&self, field: Field) -> String {
+        let text = self.text(field);
+        match self.ranges.iter().find(|(f, _)| *f == field) {
+            Some((_, range)) => format!(
+                "{}{}{}",
+                &text[..range.start],
+                style::highlight(&text[range.clone()]),
+                &text[range.end..]
+            ),
+            None => text.to_string(),
+        }
+    }
+
+    /// `parent / link -> value`, followed by `(note)` when the note matched
+    fn line(&self) -> String {
+        let mut line = format!(
+            "{} / {} -> {}",
+            self.display(Field::Parent),
+            self.display(Field::Link),
+            self.display(Field::Value)
+        );
+        if self.ranges.iter().any(|(f, _)| *f == Field::Note) {
+            // Notes can span lines, a match is printed on one
+            line.push_str(&format!(
+                " ({})",
+                self.display(Field::Note).replace('\n', " ")
+            ));
+        }
+        line
+    }
+
+    fn json(&self) -> Json {
+        let mut json = Json::object([
+            ("parent", Json::from(self.parent.as_str())),
+            ("link", Json::from(self.link.as_str())),
+            ("value", Json::from(self.value.as_str())),
+        ]);
+        if let Json::Object(entries) = &mut json {
+            if !self.note.is_empty() {
+                entries.push(("note".to_string(), Json::from(self.note.as_str())));
+            }
+            entries.push((
+                "matched".to_string(),
+                Json::Array(
+                    self.ranges
+                        .iter()
+                        .map(|(f, _)| Json::from(f.key()))
+                        .collect(),
+                ),
+            ));
+        }
+        json
+    }
+}
+
+impl Command for Search {
+    fn error_message(&self) -> String {
+        "expected 1 argument, see the Usage section with tap --search --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --search command finds every link whose Parent Entity, name, value, or note contains the search term, ignoring case. Matches are printed as Parent / Link -> Value with the matching part highlighted, followed by the note of the link when the term is in it. When nothing matches, tap exits with 1.\n\n");
+        s.push_str(
+            "Command Structure: tap --search <Term> [--in parents|links|values|notes] [--open]\n",
+        );
+        s.push_str("Pass --in to only search Parent Entity names, link names, values, or notes added with tap --note, and --open to open the link when it is the only match.\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let mut args = Args::new(args);
+        let open = args.take_flag(&["--open"]);
+        let fields = match args.take_option("--in") {
+            Some(scope) => vec![self.parse_scope(&scope)?],
+            None => Field::ALL.to_vec(),
+        };
+        match args.len() {
+            1 if args.is_flag(0, "--help") => Ok(CommandResult::Value(self.help_message())),
+            1 if args[0].is_empty() => Err(CommandError::usage(
+                "expected a search term that is not empty".to_string(),
+            )),
+            1 => {
+                let matches = self.search(ctx, &args[0], &fields)?;
+                if matches.is_empty() {
+                    return Err(format!("no links match {}", args[0]).into());
+                }
+                if open {
+                    if let [found] = matches.as_slice() {
+                        log::verbose(format_args!("Opening link {}: {}", found.link, found.value));
+                        let value = value_to_open(&found.parent, &found.link, &found.value, &[])?;
+                        if !open_value(ctx, &found.parent, &found.link, &value)? {
+                            return Ok(CommandResult::Value("Nothing was run".to_string()));
+                        }
+                        return Ok(CommandResult::Silent);
+                    }
+                    log::note(format_args!(
+                        "{} links match, nothing was opened",
+                        matches.len()
+                    ));
+                }
+                if ctx.format.is_structured() {
+                    return Ok(CommandResult::Json(Json::Array(
+                        matches.iter().map(Match::json).collect(),
+                    )));
+                }
+                Ok(CommandResult::List(
+                    matches.iter().map(Match::line).collect(),
+                ))
+            }
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+impl Search {
+    fn parse_scope(&self, scope: &str) -> Result<Field, CommandError> {
+        Field::ALL
+            .into_iter()
+            .find(|f| f.scope() == scope)
+            .ok_or_else(|| {
+                CommandError::usage(format!(
+                    "unknown scope {scope}, the supported scopes are: {}",
+                    Field::ALL.map(Field::scope).join(", ")
+                ))
+            })
+    }
+
+    /// Every link with a match in one of `fields`, in the order of the data file
+    fn search(
+        &self,
+        ctx: &Context,
+        term: &str,
+        fields: &[Field],
+    ) -> Result<Vec<Match>, CommandError> {
+        let mut matches = vec![];
+        let mut meta = ctx.store.all_meta()?;
+        for (parent, links) in ctx.store.all_links()? {
+            for (link, value) in links {
+                let note = meta
+                    .remove(&(parent.clone(), link.clone()))
+                    .and_then(|meta| meta.note)
+                    .unwrap_or_default();
+                let mut found = Match {
+                    parent: parent.clone(),
+                    link,
+                    value,
+                    note,
+                    ranges: vec![],
+                };
+                for &field in fields {
+                    if let Some(range) = find_ignore_case(found.text(field), term) {
+                        found.ranges.push((field, range));
+                    }
+                }
+                if !found.ranges.is_empty() {
+                    matches.push(found);
+                }
+            }
+        }
+        Ok(matches)
+    }
+}
+
+/// The byte range of the first part of `haystack` equal to `needle` when ignoring case
+fn find_ignore_case(haystack: &str, needle: &str) -> Option<Range<usize>> {
+    let needle: Vec<char> = needle.chars().flat_map(char::to_lowercase).collect();
+    haystack.char_indices().find_map(|(start, _)| {
+        let mut rest = needle.as_slice();
+        for (i, c) in haystack[start..].char_indices() {
+            for lower in c.to_lowercase() {
+                match rest.split_first() {
+                    Some((first, tail)) if *first == lower => rest = tail,
+                    _ => return None,
+                }
+            }
+            if rest.is_empty() {
+                return Some(start..start + i + c.len_utf8());
+            }
+        }
+        None
+    })
+}
+
+impl DisplayCommandAsRow for Search {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            ("tap --search goo", "Find every link mentioning goo"),
+            (
+                "tap --search jira.internal --in values",
+                "Only search the values of links",
+            ),
+            (
+                "tap --search yahoo --open",
+                "Open the link when it is the only match",
+            ),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::CommandErrorKind;
+    use crate::utils::format::Format;
+
+    fn search(args: &[&str]) -> Result<CommandResult, CommandError> {
+        let mut ctx = Context::in_memory(vec![
+            (
+                "search-engines".to_string(),
+                vec![
+                    ("google".to_string(), "https://google.com".to_string()),
+                    ("yahoo".to_string(), "https://yahoo.com".to_string()),
+                ],
+            ),
+            (
+                "work".to_string(),
+                vec![("jira".to_string(), "https://jira.internal/ENG".to_string())],
+            ),
+        ]);
+        Search::default().run(&mut ctx, args.iter().map(|a| a.to_string()).collect())
+    }
+
+    fn lines(lines: &[&str]) -> Result<CommandResult, CommandError> {
+        Ok(CommandResult::List(
+            lines.iter().map(|l| l.to_string()).collect(),
+        ))
+    }
+
+    #[test]
+    fn test_search_run_expected_help_arg() {
+        let cmd = Search::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_search_run_unexpected_args() {
+        let cmd = Search::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(cmd.run(&mut Context::default(), vec![]), expected);
+        assert_eq!(search(&["goo", "yah"]), expected);
+        assert_eq!(search(&["goo", "--in"]), expected);
+        assert_eq!(
+            search(&["goo", "--in", "names"]),
+            Err(CommandError::usage(
+                "unknown scope names, the supported scopes are: parents, links, values, notes"
+                    .to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_search_run_every_field() {
+        assert_eq!(
+            search(&["GOO"]),
+            lines(&["search-engines / google -> https://google.com"])
+        );
+        assert_eq!(
+            search(&["search"]),
+            lines(&[
+                "search-engines / google -> https://google.com",
+                "search-engines / yahoo -> https://yahoo.com",
+            ])
+        );
+        assert_eq!(
+            search(&["internal"]),
+            lines(&["work / jira -> https://jira.internal/ENG"])
+        );
+    }
+
+    #[test]
+    fn test_search_run_in_scope() {
+        assert_eq!(
+            search(&["jira", "--in", "values"]),
+            lines(&["work / jira -> https://jira.internal/ENG"])
+        );
+        assert_eq!(
+            search(&["https", "--in", "links"]).unwrap_err().kind,
+            CommandErrorKind::Generic
+        );
+        assert_eq!(
+            search(&["work", "--in", "parents"]),
+            lines(&["work / jira -> https://jira.internal/ENG"])
+        );
+    }
+
+    #[test]
+    fn test_search_run_notes() {
+        let mut ctx = Context::in_memory(vec![(
+            "work".to_string(),
+            vec![
+                ("jira".to_string(), "https://jira.internal".to_string()),
+                (
+                    "staging".to_string(),
+                    "https://staging.internal".to_string(),
+                ),
+            ],
+        )]);
+        ctx.store
+            .write(|ds| {
+                ds.batch(|tx| {
+                    tx.update_meta("work", "staging", |meta| {
+                        meta.note = Some("creds are in\n1Password".to_string())
+                    })
+                })
+            })
+            .unwrap();
+        let mut run = |args: &[&str]| {
+            Search::default().run(&mut ctx, args.iter().map(|a| a.to_string()).collect())
+        };
+        assert_eq!(
+            run(&["1password"]),
+            lines(&["work / staging -> https://staging.internal (creds are in 1Password)"])
+        );
+        assert_eq!(
+            run(&["internal", "--in", "notes"]).unwrap_err().kind,
+            CommandErrorKind::Generic
+        );
+        // The note is only printed when the term is in it
+        assert_eq!(
+            run(&["staging"]),
+            lines(&["work / staging -> https://staging.internal"])
+        );
+    }
+
+    #[test]
+    fn test_search_run_no_match() {
+        let e = search(&["bing"]).unwrap_err();
+        assert_eq!(e.kind.exit_code(), 1);
+        assert_eq!(e.message, "no links match bing");
+    }
+
+    #[test]
+    fn test_search_run_open() {
+        assert_eq!(search(&["yahoo", "--open"]), Ok(CommandResult::Silent));
+        // Nothing is opened when more than one link matches
+        assert_eq!(
+            search(&["search", "--open"])
+                .unwrap()
+                .to_string()
+                .lines()
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_search_run_structured_format() {
+        let mut ctx = Context::in_memory(vec![(
+            "work".to_string(),
+            vec![("jira".to_string(), "https://jira.internal".to_string())],
+        )]);
+        ctx.format = Format::Json;
+        let res = Search::default().run(&mut ctx, vec!["jira".to_string()]);
+        assert_eq!(
+            res.unwrap().render(Format::Json),
+            r#"[{"parent":"work","link":"jira","value":"https://jira.internal","matched":["link","value"]}]"#
+        );
+    }
+
+    #[test]
+    fn test_find_ignore_case() {
+        assert_eq!(find_ignore_case("Search-Engines", "engine"), Some(7..13));
+        assert_eq!(find_ignore_case("google", "GOO"), Some(0..3));
+        assert_eq!(find_ignore_case("Straße", "SSE"), None);
+        assert_eq!(find_ignore_case("ÉCOLE", "école"), Some(0..6));
+        assert_eq!(find_ignore_case("google", "yahoo"), None);
+    }
+}
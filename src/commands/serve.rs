@@ -0,0 +1,361 @@
+use crate::{
+    commands::{Command, CommandError, CommandResult, display_examples},
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::command::Args,
+    utils::context::Context,
+    utils::log,
+    utils::os_implementations::{catch_interrupt, interrupted, open_link},
+};
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+/// The port served on when none is given
+const DEFAULT_PORT: u16 = 7878;
+
+pub(crate) struct Serve {
+    name: String,
+    description: String,
+    args: [String; 2],
+}
+
+impl Default for Serve {
+    fn default() -> Self {
+        Self {
+            name: "--serve".to_string(),
+            description: "Browse the links on a local web page".to_string(),
+            args: ["[Port]".to_string(), "[--open]".to_string()],
+        }
+    }
+}
+
+impl Command for Serve {
+    fn error_message(&self) -> String {
+        "expected 0-1 arguments, see the Usage section with tap --serve --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str(&format!("Tap --serve command serves a web page listing every Parent Entity with its links, to browse them with a mouse. The links are clickable, their values are shown when hovering them, and the filter box at the top hides the links whose name or value does not match. The page is made again for every request, so changes to the links show up when it is reloaded. It is served on 127.0.0.1, port {DEFAULT_PORT} unless another one is given, so only this computer can reach it.\n\n"));
+        s.push_str("Tap serves the page until it is stopped with Ctrl-C, which lets the request being answered finish, closes the port, and exits with 0. Pass --open to open the page in the browser once it is served.\n\n");
+        s.push_str("Command Structure: tap --serve [Port] [--open]\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let mut args = Args::new(args);
+        let open = args.take_flag(&["--open"]);
+        let port = match args.len() {
+            0 => DEFAULT_PORT,
+            1 if args.is_flag(0, "--help") => return Ok(CommandResult::Value(self.help_message())),
+            1 => args[0].trim().parse().map_err(|_| {
+                CommandError::usage(format!(
+                    "expected a port like {DEFAULT_PORT}, got {}",
+                    args[0]
+                ))
+            })?,
+            _ => return Err(self.usage_error()),
+        };
+        // Only this computer can reach the page
+        let listener = TcpListener::bind(("127.0.0.1", port)).map_err(|e| {
+            CommandError::from(format!("Could not serve on 127.0.0.1:{port}: {e}"))
+                .with_hint("Pass another port, like tap --serve 8080")
+        })?;
+        let port = listener.local_addr().map_or(port, |addr| addr.port());
+        let url = format!("http://127.0.0.1:{port}/");
+        catch_interrupt();
+        log::note(format_args!(
+            "Serving the links at {url}, press Ctrl-C to stop"
+        ));
+        if open {
+            open_link(&url)?;
+        }
+        serve_until(ctx, &listener, interrupted)
+            .map_err(|e| format!("Could not serve on 127.0.0.1:{port}: {e}"))?;
+        log::note(format_args!("Stopped serving the links at {url}"));
+        Ok(CommandResult::Silent)
+    }
+}
+
+/// How long the server waits between two checks for a request or for being stopped
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Answers the requests coming to `listener` until `stop` is true, which is checked between
+/// requests
+fn serve_until(
+    ctx: &Context,
+    listener: &TcpListener,
+    stop: impl Fn() -> bool,
+) -> std::io::Result<()> {
+    // Waiting for a request without blocking leaves a chance to stop between requests
+    listener.set_nonblocking(true)?;
+    while !stop() {
+        let served = match listener.accept() {
+            Ok((stream, _)) => stream
+                .set_nonblocking(false)
+                .and_then(|_| serve(ctx, stream)),
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                thread::sleep(POLL_INTERVAL);
+                continue;
+            }
+            Err(e) => Err(e),
+        };
+        // A browser that goes away mid request does not stop the server
+        if let Err(e) = served {
+            log::verbose(format_args!("Could not answer a request: {e}"));
+        }
+    }
+    Ok(())
+}
+
+/// Answers the request read from `stream`
+fn serve(ctx: &Context, mut stream: TcpStream) -> std::io::Result<()> {
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+    log::verbose(format_args!("Serving {}", request_line.trim()));
+    let (status, content_type, body) = respond(ctx, &request_line);
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
+
+/// The status, content type, and body answering the request starting with `request_line`, like
+/// `GET / HTTP/1.1`. Only the page itself is served.
+fn respond(ctx: &Context, request_line: &str) -> (&'static str, &'static str, String) {
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    if method != "GET" {
+        return (
+            "405 Method Not Allowed",
+            "text/plain",
+            "Only GET is served\n".to_string(),
+        );
+    }
+    if path != "/" && path != "/index.html" {
+        return ("404 Not Found", "text/plain", "Not found\n".to_string());
+    }
+    // The data file is read again for every request, so changes show up on reload
+    ctx.store.reload();
+    match ctx.store.all_links() {
+        Ok(links) => ("200 OK", "text/html; charset=utf-8", page(&links)),
+        Err(e) => (
+            "500 Internal Server Error",
+            "text/plain; charset=utf-8",
+            format!("{}\n", CommandError::from(e)),
+        ),
+    }
+}
+
+/// The HTML page listing every parent entity as a section of links
+fn page(links: &[(String, Vec<(String, String)>)]) -> String {
+    let mut s = String::new();
+    s.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>Tap</title>\n");
+    s.push_str("<style>body{font-family:sans-serif;max-width:50em;margin:2em auto;padding:0 1em}input{width:100%;font-size:1.1em;padding:.3em}h2{margin-bottom:.2em}ul{margin-top:0}</style>\n");
+    s.push_str("</head>\n<body>\n<input id=\"filter\" type=\"search\" placeholder=\"Filter links\" autofocus>\n");
+    if links.is_empty() {
+        s.push_str("<p>There are no links yet, add one with tap --add.</p>\n");
+    }
+    for (parent, links) in links {
+        s.push_str(&format!("<section>\n<h2>{}</h2>\n<ul>\n", escape(parent)));
+        for (link, value) in links {
+            let (link, value) = (escape(link), escape(value));
+            // Only web pages can be opened from a web page, anything else is shown
+            match value.starts_with("http://") || value.starts_with("https://") {
+                true => s.push_str(&format!(
+                    "<li><a href=\"{value}\" title=\"{value}\">{link}</a></li>\n"
+                )),
+                false => s.push_str(&format!("<li title=\"{value}\">{link}</li>\n")),
+            }
+        }
+        s.push_str("</ul>\n</section>\n");
+    }
+    s.push_str("<script>\ndocument.getElementById('filter').addEventListener('input', e => {\n  const q = e.target.value.toLowerCase();\n  for (const section of document.querySelectorAll('section')) {\n    let shown = 0;\n    for (const li of section.querySelectorAll('li')) {\n      const match = (li.textContent + ' ' + li.title + ' ' + section.querySelector('h2').textContent).toLowerCase().includes(q);\n      li.hidden = !match;\n      shown += match;\n    }\n    section.hidden = shown === 0;\n  }\n});\n</script>\n");
+    s.push_str("</body>\n</html>\n");
+    s
+}
+
+/// `s` with the characters that mean something in HTML escaped
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl DisplayCommandAsRow for Serve {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            ("tap --serve", "Serve the links on port 7878"),
+            (
+                "tap --serve 8080 --open",
+                "Serve the links on port 8080 and open them in the browser",
+            ),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::CommandErrorKind;
+
+    fn ctx_with_links() -> Context {
+        Context::in_memory(vec![
+            (
+                "search-engines".to_string(),
+                vec![("google".to_string(), "https://google.com".to_string())],
+            ),
+            (
+                "work".to_string(),
+                vec![
+                    ("notes".to_string(), "/home/me/notes & todo.md".to_string()),
+                    (
+                        "<ci>".to_string(),
+                        "https://ci.internal/?a=1&b=2".to_string(),
+                    ),
+                ],
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_serve_run_expected_help_arg() {
+        let cmd = Serve::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_serve_run_unexpected_args() {
+        let cmd = Serve::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(
+            cmd.run(
+                &mut Context::default(),
+                vec!["80".to_string(), "81".to_string()]
+            ),
+            expected
+        );
+        let err = cmd
+            .run(&mut Context::default(), vec!["http".to_string()])
+            .unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::Usage);
+    }
+
+    #[test]
+    fn test_serve_until_answers_then_stops() {
+        use std::io::Read;
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let ctx = ctx_with_links();
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let done = Arc::new(AtomicBool::new(false));
+        let client = {
+            let done = Arc::clone(&done);
+            thread::spawn(move || {
+                let mut stream = TcpStream::connect(addr).unwrap();
+                stream.write_all(b"GET / HTTP/1.1\r\n\r\n").unwrap();
+                let mut response = String::new();
+                stream.read_to_string(&mut response).unwrap();
+                done.store(true, Ordering::SeqCst);
+                response
+            })
+        };
+        serve_until(&ctx, &listener, || done.load(Ordering::SeqCst)).unwrap();
+        let response = client.join().unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("<h2>work</h2>"));
+    }
+
+    #[test]
+    fn test_serve_until_stopped_before_any_request() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        serve_until(&ctx_with_links(), &listener, || true).unwrap();
+    }
+
+    #[test]
+    fn test_respond_page() {
+        let ctx = ctx_with_links();
+        let (status, content_type, body) = respond(&ctx, "GET / HTTP/1.1\r\n");
+        assert_eq!(status, "200 OK");
+        assert_eq!(content_type, "text/html; charset=utf-8");
+        assert!(body.contains("<h2>search-engines</h2>"));
+        assert!(body.contains(
+            "<li><a href=\"https://google.com\" title=\"https://google.com\">google</a></li>"
+        ));
+        // Names and values are escaped, and values that are not web pages are not links
+        assert!(body.contains("<li title=\"/home/me/notes &amp; todo.md\">notes</li>"));
+        assert!(body.contains(
+            "<a href=\"https://ci.internal/?a=1&amp;b=2\" title=\"https://ci.internal/?a=1&amp;b=2\">&lt;ci&gt;</a>"
+        ));
+        assert!(body.contains("id=\"filter\""));
+    }
+
+    #[test]
+    fn test_respond_reads_changes() {
+        let ctx = ctx_with_links();
+        ctx.store
+            .write(|ds| {
+                ds.add_link(
+                    "home".to_string(),
+                    "nas".to_string(),
+                    "https://nas.local".to_string(),
+                )
+            })
+            .unwrap();
+        let (_, _, body) = respond(&ctx, "GET / HTTP/1.1\r\n");
+        assert!(body.contains("<h2>home</h2>"));
+    }
+
+    #[test]
+    fn test_respond_other_requests() {
+        let ctx = ctx_with_links();
+        assert_eq!(
+            respond(&ctx, "GET /favicon.ico HTTP/1.1\r\n").0,
+            "404 Not Found"
+        );
+        assert_eq!(
+            respond(&ctx, "POST / HTTP/1.1\r\n").0,
+            "405 Method Not Allowed"
+        );
+    }
+
+    #[test]
+    fn test_page_without_links() {
+        assert!(page(&[]).contains("There are no links yet"));
+    }
+}
@@ -0,0 +1,459 @@
+use crate::{
+    commands::{
+        Command, CommandError, CommandErrorKind, CommandResult, Table, display_examples,
+        is_missing,
+        open::{OpenOptions, open_links},
+        split_link,
+    },
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::command::Args,
+    utils::context::Context,
+    utils::json::Json,
+    utils::log,
+    utils::style,
+};
+
+pub(crate) struct Session {
+    name: String,
+    description: String,
+    args: [String; 3],
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self {
+            name: "--session".to_string(),
+            description: "Save links to open them together later".to_string(),
+            args: [
+                "<save|open|list|delete>".to_string(),
+                "[Name]".to_string(),
+                "[Parent/Link...]".to_string(),
+            ],
+        }
+    }
+}
+
+impl Command for Session {
+    fn error_message(&self) -> String {
+        "expected save, open, list, or delete, see the Usage section with tap --session --help"
+            .to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --session command saves links of any Parent Entities under a name, to open all of them at once later, like the links of a morning routine. Each link is given as <Parent Entity>/<Link Name> and they are opened in the order they were saved. Sessions are kept in .tap_sessions next to the data file.\n\n");
+        s.push_str("Links are kept by name and looked up again when the session is opened, so aliases can be used and a link that was deleted since is skipped with a note. Saving a session again replaces its links. Pass --force to open more links than max_open in the config file allows.\n\n");
+        s.push_str("Opening a session takes the options of tap --open for links named one by one: --print and --copy print or copy the values as <Parent Entity>/<Link Name><TAB><Value> lines instead of opening them, --browser and --private open them in another browser, and --arg fills the templates.\n\n");
+        s.push_str("Command Structure(s):\n");
+        s.push_str("  - tap --session save <Name> <Parent/Link...>    (Save links as a session)\n");
+        s.push_str(
+            "  - tap --session open <Name> [Open Options]     (Open the links of a session)\n",
+        );
+        s.push_str("  - tap --session list                           (List all sessions)\n");
+        s.push_str("  - tap --session delete <Name>                  (Delete a session)\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let mut args = Args::new(args);
+        let opts = match args.first().map(String::as_str) {
+            Some("open") => Some(OpenOptions::take(&mut args)?),
+            _ => None,
+        };
+        let force = args.take_flag(&["--force"]);
+        let (action, rest) = match args.split_first() {
+            Some((action, rest)) => (action.as_str(), rest),
+            None => return Err(self.usage_error()),
+        };
+        match (action, rest) {
+            ("--help", []) => Ok(CommandResult::Value(self.help_message())),
+            ("save", [name, links @ ..]) if !links.is_empty() && !force => {
+                self.save(ctx, name, links)
+            }
+            ("open", [name]) if !force => match &opts {
+                Some(opts) => self.open(ctx, name, opts),
+                None => Err(self.usage_error()),
+            },
+            ("list", []) if !force => self.list(ctx),
+            ("delete", [name]) if !force => self.delete(ctx, name),
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+impl Session {
+    fn save(
+        &self,
+        ctx: &Context,
+        name: &str,
+        links: &[String],
+    ) -> Result<CommandResult, CommandError> {
+        validate_name(name)?;
+        let name = name.trim();
+        let links = links
+            .iter()
+            .map(|link| split_link(ctx, link))
+            .collect::<Result<Vec<_>, _>>()?;
+        let count = links.len();
+        let mut sessions = ctx.store.sessions()?;
+        let replaced = sessions.insert(name.to_string(), links).is_some();
+        ctx.store.write_sessions(&sessions)?;
+        Ok(CommandResult::Value(style::success(&match replaced {
+            true => format!("Replaced the links of session '{name}' with {count} link(s)"),
+            false => format!("Saved {count} link(s) as session '{name}'"),
+        })))
+    }
+
+    fn open(
+        &self,
+        ctx: &Context,
+        name: &str,
+        opts: &OpenOptions,
+    ) -> Result<CommandResult, CommandError> {
+        let links = find(ctx, name)?;
+        let mut found = vec![];
+        for (parent, link) in links {
+            let value = match ctx.store.read_link(&parent, &link) {
+                Ok((_, value)) => value,
+                Err(e) if is_missing(e.kind()) => {
+                    log::note(format_args!(
+                        "Skipped {parent}/{link} of session '{name}', it no longer exists"
+                    ));
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+            found.push((parent, link, value));
+        }
+        if found.is_empty() {
+            return Err(CommandError {
+                kind: CommandErrorKind::LinkNotFound,
+                message: format!("None of the links of session '{name}' exist anymore"),
+            }
+            .with_hint(&format!(
+                "Save it again with tap --session save {name} <Parent/Link...>"
+            )));
+        }
+        open_links(ctx, format!("Opening session {name}:"), found, opts)
+    }
+
+    fn list(&self, ctx: &Context) -> Result<CommandResult, CommandError> {
+        let sessions = ctx.store.sessions()?;
+        if ctx.format.is_structured() {
+            return Ok(CommandResult::Json(Json::Array(
+                sessions
+                    .iter()
+                    .map(|(name, links)| {
+                        Json::object([
+                            ("name", Json::from(name.as_str())),
+                            (
+                                "links",
+                                Json::Array(
+                                    links
+                                        .iter()
+                                        .map(|(parent, link)| {
+                                            Json::object([
+                                                ("parent", Json::from(parent.as_str())),
+                                                ("link", Json::from(link.as_str())),
+                                            ])
+                                        })
+                                        .collect(),
+                                ),
+                            ),
+                        ])
+                    })
+                    .collect(),
+            )));
+        }
+        if sessions.is_empty() {
+            return Ok(CommandResult::Value(
+                "No sessions yet, save one with tap --session save <Name> <Parent/Link...>"
+                    .to_string(),
+            ));
+        }
+        Ok(CommandResult::Table(Table {
+            title: "Sessions:".to_string(),
+            rows: sessions
+                .into_iter()
+                .map(|(name, links)| {
+                    let links: Vec<String> = links
+                        .iter()
+                        .map(|(parent, link)| format!("{parent}/{link}"))
+                        .collect();
+                    vec![name, links.join(" ")]
+                })
+                .collect(),
+        }))
+    }
+
+    fn delete(&self, ctx: &Context, name: &str) -> Result<CommandResult, CommandError> {
+        find(ctx, name)?;
+        let mut sessions = ctx.store.sessions()?;
+        sessions.remove(name.trim());
+        ctx.store.write_sessions(&sessions)?;
+        Ok(CommandResult::Value(style::success(&format!(
+            "Deleted session '{}'",
+            name.trim()
+        ))))
+    }
+}
+
+/// The links of session `name`, which must exist
+fn find(ctx: &Context, name: &str) -> Result<Vec<(String, String)>, CommandError> {
+    ctx.store.sessions()?.remove(name.trim()).ok_or_else(|| {
+        CommandError::from(format!("Session {} not found", name.trim()))
+            .with_hint("List the sessions with tap --session list")
+    })
+}
+
+/// A session name is a single word that does not look like a flag
+fn validate_name(name: &str) -> Result<(), CommandError> {
+    let name = name.trim();
+    if name.is_empty() || name.starts_with('-') || name.contains(char::is_whitespace) {
+        return Err(CommandError::usage(format!(
+            "Session name '{name}' must be a single word that does not start with -"
+        )));
+    }
+    Ok(())
+}
+
+impl DisplayCommandAsRow for Session {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            (
+                "tap --session save morning work/ci work/standup personal/calendar",
+                "Save three links as the session morning",
+            ),
+            (
+                "tap --session open morning",
+                "Open the links of the session morning",
+            ),
+            (
+                "tap --session open morning --print",
+                "Print the links of the session morning",
+            ),
+            ("tap --session list", "List all sessions"),
+            ("tap --session delete morning", "Delete the session morning"),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx_with_links() -> Context {
+        Context::in_memory(vec![
+            (
+                "work".to_string(),
+                vec![
+                    ("ci".to_string(), "https://ci.internal".to_string()),
+                    ("standup".to_string(), "https://meet.internal".to_string()),
+                ],
+            ),
+            (
+                "personal".to_string(),
+                vec![("calendar".to_string(), "https://calendar.com".to_string())],
+            ),
+            (
+                "work/infra".to_string(),
+                vec![("a/b".to_string(), "https://grafana.internal".to_string())],
+            ),
+        ])
+    }
+
+    fn session(ctx: &mut Context, args: &[&str]) -> Result<CommandResult, CommandError> {
+        Session::default().run(ctx, args.iter().map(|a| a.to_string()).collect())
+    }
+
+    #[test]
+    fn test_session_run_expected_help_arg() {
+        let cmd = Session::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_session_run_unexpected_args() {
+        let expected: Result<CommandResult, CommandError> = Err(Session::default().usage_error());
+        for args in [
+            &[][..],
+            &["save", "morning"],
+            &["open"],
+            &["list", "morning"],
+            &["delete", "morning", "evening"],
+            &["rename", "morning"],
+        ] {
+            assert_eq!(session(&mut ctx_with_links(), args), expected, "{args:?}");
+        }
+    }
+
+    #[test]
+    fn test_session_save_and_open() {
+        let mut ctx = ctx_with_links();
+        session(
+            &mut ctx,
+            &[
+                "save",
+                "morning",
+                "work/standup",
+                "personal/calendar",
+                "work/ci",
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            ctx.store.sessions().unwrap()["morning"],
+            [
+                ("work".to_string(), "standup".to_string()),
+                ("personal".to_string(), "calendar".to_string()),
+                ("work".to_string(), "ci".to_string()),
+            ]
+        );
+        assert_eq!(
+            session(&mut ctx, &["open", "morning"]),
+            Ok(CommandResult::Table(Table {
+                title: "Opening session morning:".to_string(),
+                rows: vec![
+                    vec!["work/standup".to_string()],
+                    vec!["personal/calendar".to_string()],
+                    vec!["work/ci".to_string()],
+                ],
+            }))
+        );
+        // Opened links count as opened for tap --last
+        assert_eq!(ctx.store.recently_opened().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_session_open_options() {
+        let mut ctx = ctx_with_links();
+        session(
+            &mut ctx,
+            &["save", "morning", "work/ci", "personal/calendar"],
+        )
+        .unwrap();
+        assert_eq!(
+            session(&mut ctx, &["open", "morning", "--print"]),
+            Ok(CommandResult::Rows(vec![
+                vec!["work/ci".to_string(), "https://ci.internal".to_string()],
+                vec![
+                    "personal/calendar".to_string(),
+                    "https://calendar.com".to_string()
+                ],
+            ]))
+        );
+        assert_eq!(
+            session(&mut ctx, &["open", "morning", "--copy"]),
+            Ok(CommandResult::Value(
+                "work/ci\thttps://ci.internal\npersonal/calendar\thttps://calendar.com".to_string()
+            ))
+        );
+        // Printing is not opening
+        assert!(ctx.store.recently_opened().unwrap().is_empty());
+        let err = session(&mut ctx, &["open", "morning", "--print", "--copy"]).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::Usage);
+        let err = session(&mut ctx, &["open", "morning", "--pinned"]).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::Usage);
+        // The options only belong to open
+        let err = session(&mut ctx, &["list", "--print"]).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::Usage);
+    }
+
+    #[test]
+    fn test_session_save_nested_names() {
+        let mut ctx = ctx_with_links();
+        session(&mut ctx, &["save", "infra", "work/infra/a/b"]).unwrap();
+        assert_eq!(
+            ctx.store.sessions().unwrap()["infra"],
+            [("work/infra".to_string(), "a/b".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_session_save_errors() {
+        let mut ctx = ctx_with_links();
+        let err = session(&mut ctx, &["save", "morning", "work"]).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::Usage);
+        let err = session(&mut ctx, &["save", "--morning", "work/ci"]).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::Usage);
+        let err = session(&mut ctx, &["save", "morning", "work/cj"]).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::LinkNotFound);
+        let err = session(&mut ctx, &["save", "morning", "home/nas"]).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::ParentNotFound);
+        assert!(ctx.store.sessions().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_session_open_skips_deleted_links() {
+        let mut ctx = ctx_with_links();
+        session(
+            &mut ctx,
+            &["save", "morning", "work/ci", "personal/calendar"],
+        )
+        .unwrap();
+        ctx.store
+            .write(|ds| ds.delete("work".to_string(), Some("ci".to_string())))
+            .unwrap();
+        assert_eq!(
+            session(&mut ctx, &["open", "morning"]),
+            Ok(CommandResult::Table(Table {
+                title: "Opening session morning:".to_string(),
+                rows: vec![vec!["personal/calendar".to_string()]],
+            }))
+        );
+        ctx.store
+            .write(|ds| ds.delete("personal".to_string(), None))
+            .unwrap();
+        let err = session(&mut ctx, &["open", "morning"]).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::LinkNotFound);
+    }
+
+    #[test]
+    fn test_session_list_and_delete() {
+        let mut ctx = ctx_with_links();
+        assert!(matches!(
+            session(&mut ctx, &["list"]),
+            Ok(CommandResult::Value(_))
+        ));
+        session(
+            &mut ctx,
+            &["save", "morning", "work/ci", "personal/calendar"],
+        )
+        .unwrap();
+        assert_eq!(
+            session(&mut ctx, &["list"]),
+            Ok(CommandResult::Table(Table {
+                title: "Sessions:".to_string(),
+                rows: vec![vec![
+                    "morning".to_string(),
+                    "work/ci personal/calendar".to_string()
+                ]],
+            }))
+        );
+        session(&mut ctx, &["delete", "morning"]).unwrap();
+        assert!(ctx.store.sessions().unwrap().is_empty());
+        assert!(session(&mut ctx, &["delete", "morning"]).is_err());
+        assert!(session(&mut ctx, &["open", "morning"]).is_err());
+    }
+}
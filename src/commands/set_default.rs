@@ -0,0 +1,196 @@
+use crate::{
+    commands::{Command, CommandError, CommandResult, display_examples},
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::command::Args,
+    utils::context::Context,
+    utils::style,
+};
+
+pub(crate) struct SetDefault {
+    name: String,
+    description: String,
+    args: [String; 2],
+}
+
+impl Default for SetDefault {
+    fn default() -> Self {
+        Self {
+            name: "--set-default".to_string(),
+            description: "Pick the link tap <Parent> opens".to_string(),
+            args: ["<Parent|here>".to_string(), "[Link]".to_string()],
+        }
+    }
+}
+
+impl Command for SetDefault {
+    fn error_message(&self) -> String {
+        "expected 1-2 arguments, see the Usage section with tap --set-default --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --set-default command makes a link the default link of its Parent Entity. Once a Parent Entity has a default link, tap <Parent Entity> opens only that link, and tap <Parent Entity> --all opens all of them like before. tap --show marks the default link.\n\n");
+        s.push_str("A Parent Entity has at most one default link, so setting another one replaces it. Leave out the Link Name to go back to opening all links. Deleting the default link also removes the setting.\n\n");
+        s.push_str("Command Structure: tap --set-default <Parent Entity | here> [Link Name]\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let args = Args::new(args);
+        match args.len() {
+            1 if args.is_flag(0, "--help") => Ok(CommandResult::Value(self.help_message())),
+            1 => self.set_default(ctx, &args.parent(0)?, None),
+            2 => self.set_default(ctx, &args.parent(0)?, Some(&args[1])),
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+impl SetDefault {
+    /// Replaces the default link of a parent, `None` removes it
+    fn set_default(
+        &self,
+        ctx: &Context,
+        parent_entity: &str,
+        link: Option<&str>,
+    ) -> Result<CommandResult, CommandError> {
+        let old = ctx
+            .store
+            .write(|ds| ds.batch(|tx| tx.set_default(parent_entity, link)))
+            .map_err(|e| ctx.store.with_suggestions(e, parent_entity, link))?;
+        let message = match (old, link) {
+            (None, None) => {
+                return Ok(CommandResult::Value(format!(
+                    "Parent '{parent_entity}' has no default link"
+                )));
+            }
+            (Some(old), Some(link)) if old == link.trim() => {
+                return Ok(CommandResult::Value(format!(
+                    "Link '{old}' is already the default link of parent '{parent_entity}'"
+                )));
+            }
+            (Some(_), None) => format!("Parent '{parent_entity}' no longer has a default link"),
+            (_, Some(link)) => {
+                format!("Link '{link}' is now the default link of parent '{parent_entity}'")
+            }
+        };
+        Ok(CommandResult::Value(style::success(&message)))
+    }
+}
+
+impl DisplayCommandAsRow for SetDefault {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            ("tap --set-default work ci", "Make tap work open only ci"),
+            (
+                "tap --set-default work",
+                "Make tap work open all of its links again",
+            ),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::CommandErrorKind;
+
+    fn ctx() -> Context {
+        Context::in_memory(vec![(
+            "work".to_string(),
+            vec![
+                ("ci".to_string(), "https://ci.internal".to_string()),
+                ("mr".to_string(), "https://mr.internal".to_string()),
+            ],
+        )])
+    }
+
+    fn set_default(ctx: &mut Context, args: &[&str]) -> Result<CommandResult, CommandError> {
+        SetDefault::default().run(ctx, args.iter().map(|a| a.to_string()).collect())
+    }
+
+    fn default_of(ctx: &Context) -> Vec<String> {
+        ctx.store
+            .read_meta("work")
+            .unwrap()
+            .into_iter()
+            .filter(|(_, meta)| meta.default)
+            .map(|(link, _)| link)
+            .collect()
+    }
+
+    #[test]
+    fn test_set_default_run_expected_help_arg() {
+        let cmd = SetDefault::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_set_default_run_unexpected_args() {
+        let cmd = SetDefault::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(cmd.run(&mut Context::default(), vec![]), expected);
+    }
+
+    #[test]
+    fn test_set_default_replace_clear() {
+        let mut ctx = ctx();
+        assert_eq!(
+            set_default(&mut ctx, &["work", "ci"]),
+            Ok(CommandResult::Value(style::success(
+                "Link 'ci' is now the default link of parent 'work'"
+            )))
+        );
+        assert_eq!(
+            set_default(&mut ctx, &["work", "ci"]),
+            Ok(CommandResult::Value(
+                "Link 'ci' is already the default link of parent 'work'".to_string()
+            ))
+        );
+        // There is only ever one default link
+        set_default(&mut ctx, &["work", "mr"]).unwrap();
+        assert_eq!(default_of(&ctx), vec!["mr".to_string()]);
+        assert_eq!(
+            set_default(&mut ctx, &["work"]),
+            Ok(CommandResult::Value(style::success(
+                "Parent 'work' no longer has a default link"
+            )))
+        );
+        assert!(default_of(&ctx).is_empty());
+        assert_eq!(
+            set_default(&mut ctx, &["work"]),
+            Ok(CommandResult::Value(
+                "Parent 'work' has no default link".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_set_default_not_found() {
+        let e = set_default(&mut ctx(), &["work", "cj"]).unwrap_err();
+        assert_eq!(e.kind, CommandErrorKind::LinkNotFound);
+        let e = set_default(&mut ctx(), &["home"]).unwrap_err();
+        assert_eq!(e.kind, CommandErrorKind::ParentNotFound);
+    }
+}
@@ -1,14 +1,27 @@
 use crate::{
-    commands::{Command, CommandResult},
+    commands::{
+        Command, CommandError, CommandErrorKind, CommandResult, Table, TypeFilter, children_of,
+        display_examples, expand_env, truncate,
+    },
     utils::cli_usage_table::DisplayCommandAsRow,
-    utils::command::get_current_directory_name,
-    utils::tap_data_store::{Index, ReadDataStore},
+    utils::command::Args,
+    utils::context::Context,
+    utils::history::Entry,
+    utils::json::Json,
+    utils::link_type::LinkType,
+    utils::log,
+    utils::regex::Regex,
+    utils::style,
+    utils::tap_data_store::LinkMeta,
+    utils::template,
 };
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
 
 pub(crate) struct Show {
     name: String,
     description: String,
-    args: [String; 2],
+    args: [String; 12],
 }
 
 impl Default for Show {
@@ -16,7 +29,20 @@ impl Default for Show {
         Self {
             name: "-s, --show".to_string(),
             description: "Shows links".to_string(),
-            args: ["<Parent|here>".to_string(), "[Link]".to_string()],
+            args: [
+                "<Parent|here>".to_string(),
+                "[Link]".to_string(),
+                "[--archived]".to_string(),
+                "[--sort <Key>]".to_string(),
+                "[--reverse]".to_string(),
+                "[--values]".to_string(),
+                "[--full]".to_string(),
+                "[--plain]".to_string(),
+                "[--filter <Pattern>]".to_string(),
+                "[--regex]".to_string(),
+                "[--type <Types>]".to_string(),
+                "[--json]".to_string(),
+            ],
         }
     }
 }
@@ -34,79 +60,545 @@ impl Command for Show {
             "  - tap --show                                     (Show all Parent Entity values)\n",
         );
         s.push_str("  - tap --show <Parent Entity | here> [Link Name]  (Show specific/all Link values)\n\n");
-        s.push_str("Example Usage: \n");
-        s.push_str("  - tap --show search-engines        (Show all Link values)\n");
-        s.push_str("  - tap --show search-engines google (Show specific Link value)\n");
-        s.push_str("  - tap --show here                  (Show all Link values of Parent Entity - uses name of current directory)\n");
+        s.push_str("Parent Entity names can nest with '/', like work/infra/grafana, and tap --show work then lists the names nested under work, ending with '/', before the links of work itself. An alias given with tap --alias shows the links of the Parent Entity it names. Links pinned with tap --pin are listed first and marked with a star, the link picked with tap --set-default is marked [default], and links added with tap --add --expires are marked with the day they expire, or expired, on. Links that are files, directories, or commands are marked with their type, see tap --add --type, and values with {name} placeholders are marked with the names to pass with --arg when opening them. Environment variables in values, like $HOME or ${JIRA_HOST}, are shown expanded, the way the link is opened. The note of a link, added with tap --note, is printed under its value.\n\n");
+        s.push_str("Pass --archived to list the Parent Entities archived with tap --archive instead, or the links of one of them.\n\n");
+        s.push_str("Pass --sort to order what is listed, without changing the order of the data file. Links sort by name, value, or recent (the most recently added or changed first, going by the history file), and Parent Entities by name or count (the most links first). Pass --reverse to flip the order.\n\n");
+        s.push_str("Parent Entities are listed with how many links they have, like search-engines (4). Pass --plain to list the bare names.\n\n");
+        s.push_str("Pass --filter to only list the links whose name or value contains the pattern, ignoring case, or with no Parent Entity given, the Parent Entities whose name contains it. With --regex, the pattern is a regular expression instead, still ignoring case. When nothing matches, tap prints no matches and exits with 1.\n\n");
+        s.push_str("Pass --type to only list the links of the types given, like --type url for web pages or --type file,dir for local files and directories. See tap --add --type for the types. It composes with --filter, listing the links that pass both.\n\n");
+        s.push_str("Pass --values to list the value of each link next to its name. Values are cut to the width of the terminal, ending with ..., unless --full is passed.\n\n");
+        s.push_str("Pass --json to print JSON instead, for scripts and tools like jq.\n\n");
+        s.push_str(&display_examples(&self.examples()));
         s
     }
 
-    fn run(&self, args: Vec<String>) -> Result<CommandResult, String> {
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let mut args = Args::new(args);
+        let opts = Options::take(&mut args, ctx)?;
+        // Parent entities have no type, and a single link is shown whatever its type
+        if opts.types.is_some() && (opts.archived || args.len() != 1) {
+            return Err(CommandError::usage(
+                "--type lists the links of one Parent Entity, like tap --show work --type url"
+                    .to_string(),
+            ));
+        }
+        if opts.archived {
+            return self.show_archived(ctx, args, opts.json);
+        }
         match args.len() {
-            0 => {
-                // Use Index parents
-                let index = Index::new(None).unwrap();
-                let parents = index.parents();
-                let parent_entities: String = parents.iter().map(|s| format!("  {s}\n")).collect();
-                Ok(CommandResult::Value(format!(
-                    "Parent Entities:\n{}",
-                    parent_entities.trim_end_matches('\n')
-                )))
-            }
-            1 => match args[0].as_str() {
-                "--help" => Ok(CommandResult::Value(self.help_message())),
-                "here" => {
-                    let parent_entity = get_current_directory_name().map_err(|e| e.to_string())?;
-                    let ds = ReadDataStore::new(None, parent_entity.to_string())
-                        .map_err(|e| e.to_string())?;
-                    let links = ds.links(&parent_entity).map_err(|e| e.to_string())?;
-                    let links_string: String = links.iter().map(|s| format!("  {s}\n")).collect();
-                    Ok(CommandResult::Value(format!(
-                        "Links of parent entity {parent_entity}:\n{}",
-                        links_string.trim_end_matches('\n')
-                    )))
+            0 => self.show_parents(ctx, &opts),
+            1 if args.is_flag(0, "--help") => Ok(CommandResult::Value(self.help_message())),
+            1 => self.show_links(ctx, &args.parent(0)?, &opts),
+            2 => {
+                let parent_entity = ctx.store.resolve_alias(&args.parent(0)?)?;
+                let link_value = ctx.store.read_link(&parent_entity, &args[1])?;
+                if opts.json {
+                    return Ok(CommandResult::Json(links_json(
+                        &parent_entity,
+                        &[link_value],
+                    )));
                 }
-                parent_entity => {
-                    let ds = ReadDataStore::new(None, parent_entity.to_string())
-                        .map_err(|e| e.to_string())?;
-                    let links = ds.links(parent_entity).map_err(|e| e.to_string())?;
-                    let links_string: String = links.iter().map(|s| format!("  {s}\n")).collect();
-                    Ok(CommandResult::Value(format!(
-                        "Links of parent entity {parent_entity}:\n{}",
-                        links_string.trim_end_matches('\n')
-                    )))
+                let mut s = format!("{}: {}", link_value.0, shown_value(&link_value.1));
+                let meta = ctx
+                    .store
+                    .read_meta(&parent_entity)?
+                    .remove(&link_value.0)
+                    .unwrap_or_default();
+                let markers = markers(&meta, &link_value.1);
+                if !markers.is_empty() {
+                    s.push_str(&format!(" {markers}"));
                 }
-            },
-            2 => match (args[0].as_str(), args[1].as_str()) {
-                ("here", link_name) => {
-                    let parent_entity = get_current_directory_name().map_err(|e| e.to_string())?;
-                    let ds = ReadDataStore::new(None, parent_entity.to_string())
-                        .map_err(|e| e.to_string())?;
-                    let link_value = ds
-                        .read_link(&parent_entity, link_name)
-                        .map_err(|e| e.to_string())?;
-                    Ok(CommandResult::Value(format!(
-                        "{}: {}",
-                        link_value.0, link_value.1
-                    )))
+                // The note goes under the value, indented so it is not taken for another link
+                for line in meta.note.iter().flat_map(|note| note.lines()) {
+                    s.push_str(&format!("\n  {line}"));
                 }
-                (parent_entity, link_name) => {
-                    let ds = ReadDataStore::new(None, parent_entity.to_string())
-                        .map_err(|e| e.to_string())?;
-                    let link_value = ds
-                        .read_link(parent_entity, link_name)
-                        .map_err(|e| e.to_string())?;
-                    Ok(CommandResult::Value(format!(
-                        "{}: {}",
-                        link_value.0, link_value.1
-                    )))
+                Ok(CommandResult::Value(s))
+            }
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+/// The options of `tap --show`, taken out of the arguments before the names are read
+struct Options {
+    json: bool,
+    archived: bool,
+    sort: Option<String>,
+    reverse: bool,
+    values: bool,
+    full: bool,
+    plain: bool,
+    filter: Option<Filter>,
+    types: Option<TypeFilter>,
+}
+
+impl Options {
+    fn take(args: &mut Args, ctx: &Context) -> Result<Self, CommandError> {
+        let regex = args.take_flag(&["--regex"]);
+        let filter = match args.take_option("--filter") {
+            Some(pattern) => Some(Filter::new(pattern, regex)?),
+            None if args.has_flag("--filter") => {
+                return Err(CommandError::usage(
+                    "expected a pattern after --filter".to_string(),
+                ));
+            }
+            None if regex => {
+                return Err(CommandError::usage(
+                    "--regex changes how --filter matches, pass it with --filter <Pattern>"
+                        .to_string(),
+                ));
+            }
+            None => None,
+        };
+        Ok(Self {
+            json: args.take_flag(&["--json"]) || ctx.format.is_structured(),
+            archived: args.take_flag(&["--archived"]),
+            sort: args.take_option("--sort"),
+            reverse: args.take_flag(&["--reverse"]),
+            values: args.take_flag(&["--values"]),
+            full: args.take_flag(&["--full"]),
+            plain: args.take_flag(&["--plain"]),
+            filter,
+            types: TypeFilter::take(args)?,
+        })
+    }
+
+    /// Whether `names` passes the filter, which it does when any of them matches
+    fn keeps(&self, names: &[&str]) -> bool {
+        self.filter
+            .as_ref()
+            .is_none_or(|filter| names.iter().any(|name| filter.matches(name)))
+    }
+
+    /// The error for a filter that kept nothing, `None` when nothing was filtered
+    fn no_matches(&self) -> Option<CommandError> {
+        match (&self.filter, &self.types) {
+            (Some(filter), Some(types)) => Some(
+                format!(
+                    "no matches for {} of type {}",
+                    filter.pattern,
+                    types.names()
+                )
+                .into(),
+            ),
+            (Some(filter), None) => Some(format!("no matches for {}", filter.pattern).into()),
+            (None, Some(types)) => Some(format!("no links of type {}", types.names()).into()),
+            (None, None) => None,
+        }
+    }
+}
+
+/// What `tap --show --filter` keeps: the names and values that contain the pattern, ignoring case,
+/// or that match it as a regular expression with `--regex`
+struct Filter {
+    pattern: String,
+    regex: Option<Regex>,
+}
+
+impl Filter {
+    fn new(pattern: String, regex: bool) -> Result<Self, CommandError> {
+        let regex = match regex {
+            true => Some(
+                Regex::new(&pattern)
+                    .map_err(|e| CommandError::usage(e.to_string()))?
+                    .case_insensitive(),
+            ),
+            false => None,
+        };
+        Ok(Self { pattern, regex })
+    }
+
+    fn matches(&self, text: &str) -> bool {
+        match &self.regex {
+            Some(regex) => regex.is_match(text),
+            None => text.to_lowercase().contains(&self.pattern.to_lowercase()),
+        }
+    }
+}
+
+impl Show {
+    /// Lists the parent entities, with how many links they have unless `--plain` is passed
+    fn show_parents(&self, ctx: &Context, opts: &Options) -> Result<CommandResult, CommandError> {
+        // Use Index parents
+        let mut parents = match &opts.sort {
+            Some(key) => self.sorted_parents(ctx, key)?,
+            None => ctx.store.parents()?,
+        };
+        parents.retain(|parent| opts.keeps(&[parent]));
+        if parents.is_empty()
+            && let Some(e) = opts.no_matches()
+        {
+            return Err(e);
+        }
+        if opts.reverse {
+            parents.reverse();
+        }
+        if opts.json {
+            return Ok(CommandResult::Json(Json::object([(
+                "parents",
+                Json::Array(parents.iter().map(|p| Json::from(p.as_str())).collect()),
+            )])));
+        }
+        let counts = match opts.plain {
+            true => BTreeMap::new(),
+            false => self.link_counts(ctx)?,
+        };
+        Ok(CommandResult::Table(Table {
+            title: "Parent Entities:".to_string(),
+            rows: parents
+                .iter()
+                .map(|p| match counts.get(p) {
+                    Some(count) => vec![format!("{} ({count})", style::parent(p))],
+                    None => vec![style::parent(p)],
+                })
+                .collect(),
+        }))
+    }
+
+    /// Lists the links of the parent entity `typed` names, after the names nested under it
+    fn show_links(
+        &self,
+        ctx: &Context,
+        typed: &str,
+        opts: &Options,
+    ) -> Result<CommandResult, CommandError> {
+        let parent_entity = ctx.store.resolve_alias(typed)?;
+        let mut children = children_of(&ctx.store.parents()?, &parent_entity);
+        // A name that only groups other parent entities has no links of its own
+        let only_group =
+            |e: &CommandError| e.kind == CommandErrorKind::ParentNotFound && !children.is_empty();
+        let links = match self.listed_links(ctx, &parent_entity, opts) {
+            Err(e) if only_group(&e) => vec![],
+            links => links?,
+        };
+        children.retain(|child| opts.keeps(&[child]));
+        if links.is_empty()
+            && children.is_empty()
+            && let Some(e) = opts.no_matches()
+        {
+            return Err(e);
+        }
+        if opts.json {
+            let mut json = links_json(&parent_entity, &links);
+            if let (Json::Object(entries), false) = (&mut json, children.is_empty()) {
+                let groups = children.iter().map(|c| Json::from(c.as_str())).collect();
+                entries.push(("children".to_string(), Json::Array(groups)));
+            }
+            return Ok(CommandResult::Json(json));
+        }
+        let meta = ctx.store.read_meta(&parent_entity)?;
+        let mut title = format!("Links of parent entity {}", style::parent(&parent_entity));
+        if typed.trim() != parent_entity.trim() {
+            title.push_str(&format!(" (alias {})", typed.trim()));
+        }
+        // The names nested under the parent entity come first, ending with `/`
+        let mut rows: Vec<Vec<String>> = children
+            .iter()
+            .map(|child| {
+                let segment = &child[parent_entity.trim().len() + 1..];
+                vec![style::parent(&format!("{segment}/"))]
+            })
+            .collect();
+        let name_width = rows
+            .iter()
+            .map(|row| &row[0])
+            .chain(links.iter().map(|(link, _)| link))
+            .map(|name| name.chars().count())
+            .max()
+            .unwrap_or(0);
+        let width = (opts.values && !opts.full)
+            .then(style::terminal_width)
+            .flatten();
+        let links = match opts.values {
+            true => links
+                .into_iter()
+                .map(|(link, value)| (link, shown_value(&value)))
+                .collect(),
+            false => links,
+        };
+        rows.extend(link_rows(links, &meta, opts.values, name_width, width));
+        Ok(CommandResult::Table(Table {
+            title: format!("{title}:"),
+            rows,
+        }))
+    }
+
+    /// The links of `parent_entity` as they are listed: in the order of `--sort`, otherwise in
+    /// the order of the data file with the pinned links first for people to read, then filtered
+    /// and reversed as asked
+    fn listed_links(
+        &self,
+        ctx: &Context,
+        parent_entity: &str,
+        opts: &Options,
+    ) -> Result<Vec<(String, String)>, CommandError> {
+        let mut links = match &opts.sort {
+            Some(key) => self.sorted_links(ctx, parent_entity, key)?,
+            None => ctx.store.read_parent(parent_entity)?,
+        };
+        let meta = ctx.store.read_meta(parent_entity)?;
+        if opts.sort.is_none() && !opts.json {
+            // Sorting is stable, so the links stay in order within the pinned and unpinned ones
+            links.sort_by_key(|(link, _)| !meta.get(link).is_some_and(|meta| meta.pinned));
+        }
+        links.retain(|(link, value)| {
+            opts.keeps(&[link, value])
+                && opts
+                    .types
+                    .as_ref()
+                    .is_none_or(|types| types.keeps(meta.get(link), value))
+        });
+        if opts.reverse {
+            links.reverse();
+        }
+        Ok(links)
+    }
+
+    /// The parent entities ordered by `key`: names A to Z, or the most links first
+    fn sorted_parents(&self, ctx: &Context, key: &str) -> Result<Vec<String>, CommandError> {
+        let key = SortKey::parse(key, &[SortKey::Name, SortKey::Count])?;
+        let mut parents = ctx.store.parents()?;
+        match key {
+            SortKey::Count => {
+                let counts = self.link_counts(ctx)?;
+                // Sorting is stable, so parent entities with as many links stay sorted by name
+                parents.sort_by_key(|parent| Reverse(counts.get(parent).copied().unwrap_or(0)));
+            }
+            _ => parents.sort(),
+        }
+        Ok(parents)
+    }
+
+    /// How many links each parent entity has
+    fn link_counts(&self, ctx: &Context) -> Result<BTreeMap<String, usize>, CommandError> {
+        Ok(ctx
+            .store
+            .all_links()?
+            .into_iter()
+            .map(|(parent, links)| (parent, links.len()))
+            .collect())
+    }
+
+    /// The links of `parent_entity` ordered by `key`: names or values A to Z, or the most recently
+    /// added or changed first
+    fn sorted_links(
+        &self,
+        ctx: &Context,
+        parent_entity: &str,
+        key: &str,
+    ) -> Result<Vec<(String, String)>, CommandError> {
+        let key = SortKey::parse(key, &[SortKey::Name, SortKey::Value, SortKey::Recent])?;
+        let mut links = ctx.store.read_parent(parent_entity)?;
+        match key {
+            SortKey::Value => links.sort_by(|(_, a), (_, b)| a.cmp(b)),
+            SortKey::Recent => sort_by_recent(&mut links, parent_entity, &ctx.store.history()?),
+            _ => links.sort_by(|(a, _), (b, _)| a.cmp(b)),
+        }
+        Ok(links)
+    }
+
+    /// Lists the archived parent entities, or the links of one of them
+    fn show_archived(
+        &self,
+        ctx: &Context,
+        args: Args,
+        json: bool,
+    ) -> Result<CommandResult, CommandError> {
+        let archived = ctx.store.archived()?;
+        match args.len() {
+            0 if json => Ok(CommandResult::Json(Json::object([(
+                "archived",
+                Json::Array(
+                    archived
+                        .iter()
+                        .map(|(parent, _)| Json::from(parent.as_str()))
+                        .collect(),
+                ),
+            )]))),
+            0 if archived.is_empty() => Ok(CommandResult::Value(
+                "No archived parent entities, archive one with tap --archive <Parent Entity>"
+                    .to_string(),
+            )),
+            0 => Ok(CommandResult::Table(Table {
+                title: "Archived Parent Entities:".to_string(),
+                rows: archived
+                    .iter()
+                    .map(|(parent, _)| vec![style::parent(parent)])
+                    .collect(),
+            })),
+            1 => {
+                let parent_entity = args[0].trim();
+                let Some((_, links)) = archived.iter().find(|(p, _)| p == parent_entity) else {
+                    return Err(CommandError {
+                        kind: CommandErrorKind::ParentNotFound,
+                        message: format!("Parent '{parent_entity}' is not archived"),
+                    }
+                    .with_hint("List the archived parent entities with tap --show --archived"));
+                };
+                if json {
+                    return Ok(CommandResult::Json(links_json(parent_entity, links)));
                 }
-            },
-            _ => Err(self.error_message()),
+                Ok(CommandResult::Table(Table {
+                    title: format!(
+                        "Links of archived parent entity {}:",
+                        style::parent(parent_entity)
+                    ),
+                    rows: links.iter().map(|(link, _)| vec![link.clone()]).collect(),
+                }))
+            }
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+/// What `tap --show --sort` orders by
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SortKey {
+    Name,
+    Value,
+    Recent,
+    Count,
+}
+
+impl SortKey {
+    fn name(self) -> &'static str {
+        match self {
+            SortKey::Name => "name",
+            SortKey::Value => "value",
+            SortKey::Recent => "recent",
+            SortKey::Count => "count",
+        }
+    }
+
+    /// The key named `key`, failing when it is not one of `valid`
+    fn parse(key: &str, valid: &[SortKey]) -> Result<SortKey, CommandError> {
+        let names: Vec<&str> = valid.iter().map(|key| key.name()).collect();
+        if let Some(key) = valid.iter().find(|valid| valid.name() == key.trim()) {
+            return Ok(*key);
         }
+        let message = match key.trim() {
+            "usage" => {
+                "Links can not be sorted by usage, tap does not record how often they are opened"
+                    .to_string()
+            }
+            key => format!("Unknown sort key '{key}'"),
+        };
+        Err(CommandError::usage(format!(
+            "{message}, expected one of: {}",
+            names.join(", ")
+        )))
     }
 }
 
+/// Sorts the links of `parent` by when they were last added or changed, newest first. Links the
+/// history file does not know, like those added before it was kept, go last.
+fn sort_by_recent(links: &mut [(String, String)], parent: &str, history: &[Entry]) {
+    let mut changed: BTreeMap<&str, &str> = BTreeMap::new();
+    // Later entries are newer, and deletions leave no value behind
+    for entry in history
+        .iter()
+        .filter(|entry| entry.parent == parent.trim() && !entry.new.is_empty())
+    {
+        changed.insert(&entry.link, &entry.time);
+    }
+    links.sort_by_key(|(link, _)| Reverse(changed.get(link.as_str()).copied()));
+}
+
+/// The rows listing `links`, each with its markers and, when `values` is set, its value. Values
+/// are cut so that rows fit in `width` columns, after a first column `name_width` wide.
+pub(in crate::commands) fn link_rows(
+    links: Vec<(String, String)>,
+    meta: &BTreeMap<String, LinkMeta>,
+    values: bool,
+    name_width: usize,
+    width: Option<usize>,
+) -> Vec<Vec<String>> {
+    let markers: Vec<String> = links
+        .iter()
+        .map(|(link, value)| markers(meta.get(link).unwrap_or(&LinkMeta::default()), value))
+        .collect();
+    // Rows are indented by 2 and their columns are 2 apart, see `Table`
+    let markers_width = match markers.iter().map(|m| m.chars().count()).max() {
+        Some(0) | None => 0,
+        Some(widest) => widest + 2,
+    };
+    let room = width.map(|w| w.saturating_sub(2 + name_width + 2 + markers_width));
+    links
+        .into_iter()
+        .zip(markers)
+        .map(|((link, value), markers)| {
+            let mut row = vec![link];
+            if values {
+                row.push(truncate(&value, room));
+            }
+            if !markers.is_empty() {
+                row.push(markers);
+            }
+            row
+        })
+        .collect()
+}
+
+/// `value` as it is opened, with its environment variables expanded, see `expand_env`. When that
+/// fails, e.g. because a variable is not set, the reason is noted and the value is shown as stored.
+fn shown_value(value: &str) -> String {
+    expand_env(value).unwrap_or_else(|e| {
+        log::note(format_args!(
+            "{}",
+            e.message.lines().next().unwrap_or_default()
+        ));
+        value.to_string()
+    })
+}
+
+/// The markers shown next to a link: whether it is pinned, whether it is the default link, when
+/// it expires, its type, and the placeholders to fill when its `value` is a template
+fn markers(meta: &LinkMeta, value: &str) -> String {
+    let mut markers = vec![];
+    if meta.pinned {
+        markers.push(style::pin_marker().to_string());
+    }
+    if meta.default {
+        markers.push("[default]".to_string());
+    }
+    match &meta.expires {
+        Some(date) if meta.is_expired() => markers.push(style::error(&format!("[expired {date}]"))),
+        Some(date) => markers.push(format!("[expires {date}]")),
+        None => {}
+    }
+    // Most links are web pages, only the others are marked so they stand out
+    if let Some(link_type) = meta.link_type.filter(|t| *t != LinkType::Url) {
+        markers.push(format!("[{link_type}]"));
+    }
+    // Templates can only be opened with --arg, so the names to pass are listed
+    let placeholders = template::placeholders(value);
+    if !placeholders.is_empty() {
+        markers.push(format!("[template: {}]", placeholders.join(", ")));
+    }
+    markers.join(" ")
+}
+
+/// `{"parent":"x","links":[{"name":"...","value":"..."}]}`
+pub(in crate::commands) fn links_json(parent_entity: &str, links: &[(String, String)]) -> Json {
+    Json::object([
+        ("parent", Json::from(parent_entity)),
+        (
+            "links",
+            Json::Array(
+                links
+                    .iter()
+                    .map(|(name, value)| {
+                        Json::object([
+                            ("name", Json::from(name.as_str())),
+                            ("value", Json::from(value.as_str())),
+                        ])
+                    })
+                    .collect(),
+            ),
+        ),
+    ])
+}
+
 impl DisplayCommandAsRow for Show {
     fn args(&self) -> Vec<String> {
         self.args.to_vec()
@@ -116,6 +608,49 @@ impl DisplayCommandAsRow for Show {
         self.description.clone()
     }
 
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            ("tap --show", "Show all Parent Entities"),
+            ("tap --show search-engines", "Show all Link values"),
+            (
+                "tap --show search-engines google",
+                "Show specific Link value",
+            ),
+            (
+                "tap --show here",
+                "Show all Link values of Parent Entity - uses name of current directory",
+            ),
+            ("tap --show --archived", "Show all archived Parent Entities"),
+            (
+                "tap --show work --sort recent",
+                "Show the most recently added Links first",
+            ),
+            (
+                "tap --show --sort count",
+                "Show the Parent Entities with the most Links first",
+            ),
+            (
+                "tap --show work --filter grafana",
+                "Show the Links of work with grafana in their name or value",
+            ),
+            (
+                "tap --show work --type url",
+                "Show the Links of work that are web pages",
+            ),
+            (
+                "tap --show search-engines --values",
+                "Show all Links with their values",
+            ),
+            (
+                "tap --show search-engines --json",
+                "Show all Link values as JSON",
+            ),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
     fn name(&self) -> String {
         self.name.clone()
     }
@@ -124,13 +659,26 @@ impl DisplayCommandAsRow for Show {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::commands::CommandErrorKind;
+    use crate::utils::command::get_current_directory_name;
+
+    fn ctx_with_links(parent: &str) -> Context {
+        Context::in_memory(vec![(
+            parent.to_string(),
+            vec![
+                ("google".to_string(), "https://google.com".to_string()),
+                ("yahoo".to_string(), "https://yahoo.com".to_string()),
+            ],
+        )])
+    }
 
     #[test]
     fn test_show_run_expected_help_arg() {
         let args: Vec<String> = vec!["--help".to_string()];
         let cmd = Show::default();
-        let expected: Result<CommandResult, String> = Ok(CommandResult::Value(cmd.help_message()));
-        let res = cmd.run(args);
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        let res = cmd.run(&mut Context::default(), args);
         assert_eq!(res, expected);
     }
 
@@ -142,56 +690,589 @@ mod tests {
             "random3".to_string(),
         ];
         let cmd = Show::default();
-        let expected: Result<CommandResult, String> = Err(cmd.error_message());
-        let res = cmd.run(args);
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        let res = cmd.run(&mut Context::default(), args);
         assert_eq!(res, expected);
     }
 
     #[test]
-    #[ignore = "GH-45: Should really be an integration test - move this out"]
+    fn test_show_run_no_args() {
+        let args: Vec<String> = vec![];
+        let cmd = Show::default();
+        let mut ctx = ctx_with_links("search-engines");
+        let expected: Result<String, CommandError> =
+            Ok("Parent Entities:\n  search-engines (2)".to_string());
+        let res = cmd.run(&mut ctx, args).map(|res| res.to_string());
+        assert_eq!(res, expected);
+        let res = cmd.run(&mut ctx, vec!["--plain".to_string()]);
+        assert_eq!(
+            res.map(|res| res.to_string()),
+            Ok("Parent Entities:\n  search-engines".to_string())
+        );
+    }
+
+    #[test]
     fn test_show_run_expected_here_arg() {
         let args: Vec<String> = vec!["here".to_string()];
+        let current_dir_name = get_current_directory_name().unwrap();
         let cmd = Show::default();
-        let expected: Result<CommandResult, String> = Ok(CommandResult::Value(
-            "TODO: Implement show functionality for here".to_string(),
+        let mut ctx = ctx_with_links(&current_dir_name);
+        let expected: Result<String, CommandError> = Ok(format!(
+            "Links of parent entity {current_dir_name}:\n  google\n  yahoo"
         ));
-        let res = cmd.run(args);
+        let res = cmd.run(&mut ctx, args).map(|res| res.to_string());
         assert_eq!(res, expected);
     }
 
     #[test]
-    #[ignore = "GH-45: Should really be an integration test - move this out"]
     fn test_show_run_expected_here_and_link_args() {
         let args: Vec<String> = vec!["here".to_string(), "google".to_string()];
+        let current_dir_name = get_current_directory_name().unwrap();
         let cmd = Show::default();
-        let expected: Result<CommandResult, String> = Ok(CommandResult::Value(
-            "TODO: Implement show functionality for here with Link Name google".to_string(),
+        let mut ctx = ctx_with_links(&current_dir_name);
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Value(
+            "google: https://google.com".to_string(),
         ));
-        let res = cmd.run(args);
+        let res = cmd.run(&mut ctx, args);
         assert_eq!(res, expected);
     }
 
     #[test]
-    #[ignore = "GH-45: Should really be an integration test - move this out"]
     fn test_show_run_expected_parent_entity_arg() {
         let args: Vec<String> = vec!["search-engines".to_string()];
         let cmd = Show::default();
-        let expected: Result<CommandResult, String> = Ok(CommandResult::Value(
-            "TODO: Implement show functionality for Parent Entity: search-engines".to_string(),
-        ));
-        let res = cmd.run(args);
+        let mut ctx = ctx_with_links("search-engines");
+        let expected: Result<String, CommandError> =
+            Ok("Links of parent entity search-engines:\n  google\n  yahoo".to_string());
+        let res = cmd.run(&mut ctx, args).map(|res| res.to_string());
         assert_eq!(res, expected);
     }
 
     #[test]
-    #[ignore = "GH-45: Should really be an integration test - move this out"]
     fn test_show_run_expected_parent_entity_and_link_args() {
         let args: Vec<String> = vec!["search-engines".to_string(), "google".to_string()];
         let cmd = Show::default();
-        let expected: Result<CommandResult, String> = Ok(CommandResult::Value(
-            "TODO: Implement show functionality for Parent Entity search-engines with Link Name google".to_string()
+        let mut ctx = ctx_with_links("search-engines");
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Value(
+            "google: https://google.com".to_string(),
         ));
-        let res = cmd.run(args);
+        let res = cmd.run(&mut ctx, args);
         assert_eq!(res, expected);
     }
+
+    #[test]
+    fn test_show_run_pinned_first() {
+        let cmd = Show::default();
+        let mut ctx = ctx_with_links("search-engines");
+        ctx.store
+            .write(|ds| {
+                ds.batch(|tx| tx.update_meta("search-engines", "yahoo", |m| m.pinned = true))
+            })
+            .unwrap();
+        let res = cmd
+            .run(&mut ctx, vec!["search-engines".to_string()])
+            .map(|res| res.to_string());
+        assert_eq!(
+            res,
+            Ok("Links of parent entity search-engines:\n  yahoo   [pinned]\n  google".to_string())
+        );
+        let res = cmd.run(
+            &mut ctx,
+            vec!["search-engines".to_string(), "yahoo".to_string()],
+        );
+        assert_eq!(
+            res,
+            Ok(CommandResult::Value(
+                "yahoo: https://yahoo.com [pinned]".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_show_run_default_link() {
+        let mut ctx = ctx_with_links("search-engines");
+        ctx.store
+            .write(|ds| {
+                ds.batch(|tx| {
+                    tx.update_meta("search-engines", "google", |m| m.pinned = true)?;
+                    tx.set_default("search-engines", Some("google"))
+                })
+            })
+            .unwrap();
+        let res = Show::default()
+            .run(&mut ctx, vec!["search-engines".to_string()])
+            .map(|res| res.to_string());
+        assert_eq!(
+            res,
+            Ok(
+                "Links of parent entity search-engines:\n  google  [pinned] [default]\n  yahoo"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_show_run_nested_parents() {
+        let mut ctx = Context::in_memory(
+            ["work", "work/infra/grafana", "work/tools"]
+                .iter()
+                .map(|parent| {
+                    (
+                        parent.to_string(),
+                        vec![("home".to_string(), "https://home.internal".to_string())],
+                    )
+                })
+                .collect(),
+        );
+        let mut show = |parent: &str| {
+            Show::default()
+                .run(&mut ctx, vec![parent.to_string()])
+                .map(|res| res.to_string())
+        };
+        assert_eq!(
+            show("work"),
+            Ok("Links of parent entity work:\n  infra/\n  tools/\n  home".to_string())
+        );
+        // A group without links of its own only lists what is nested under it
+        assert_eq!(
+            show("work/infra"),
+            Ok("Links of parent entity work/infra:\n  grafana/".to_string())
+        );
+        assert_eq!(
+            show("work/infra/grafana"),
+            Ok("Links of parent entity work/infra/grafana:\n  home".to_string())
+        );
+        assert_eq!(
+            show("work/inf").unwrap_err().kind,
+            CommandErrorKind::ParentNotFound
+        );
+    }
+
+    #[test]
+    fn test_show_run_note() {
+        let mut ctx = ctx_with_links("search-engines");
+        ctx.store
+            .write(|ds| {
+                ds.batch(|tx| {
+                    tx.update_meta("search-engines", "google", |m| {
+                        m.note = Some("the default\nuse with care".to_string())
+                    })
+                })
+            })
+            .unwrap();
+        let res = Show::default().run(
+            &mut ctx,
+            vec!["search-engines".to_string(), "google".to_string()],
+        );
+        assert_eq!(
+            res,
+            Ok(CommandResult::Value(
+                "google: https://google.com\n  the default\n  use with care".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_show_run_expiring_links() {
+        let mut ctx = ctx_with_links("search-engines");
+        ctx.store
+            .write(|ds| {
+                ds.batch(|tx| {
+                    tx.update_meta("search-engines", "google", |m| {
+                        m.expires = Some("2000-01-01".to_string())
+                    })?;
+                    tx.update_meta("search-engines", "yahoo", |m| {
+                        m.expires = Some("2999-01-01".to_string())
+                    })
+                })
+            })
+            .unwrap();
+        let res = Show::default().run(&mut ctx, vec!["search-engines".to_string()]);
+        assert_eq!(
+            res.map(|r| r.to_string()),
+            Ok("Links of parent entity search-engines:\n  google  [expired 2000-01-01]\n  yahoo   [expires 2999-01-01]".to_string())
+        );
+    }
+
+    #[test]
+    fn test_show_run_sort_links() {
+        let mut ctx = Context::in_memory(vec![(
+            "search-engines".to_string(),
+            vec![
+                ("yahoo".to_string(), "https://a.yahoo.com".to_string()),
+                ("google".to_string(), "https://google.com".to_string()),
+            ],
+        )]);
+        let mut show = |args: &[&str]| {
+            Show::default()
+                .run(&mut ctx, args.iter().map(|a| a.to_string()).collect())
+                .map(|r| r.to_string())
+        };
+        assert_eq!(
+            show(&["search-engines", "--sort", "name"]),
+            Ok("Links of parent entity search-engines:\n  google\n  yahoo".to_string())
+        );
+        assert_eq!(
+            show(&["search-engines", "--sort", "value", "--reverse"]),
+            Ok("Links of parent entity search-engines:\n  google\n  yahoo".to_string())
+        );
+        assert_eq!(
+            show(&["search-engines", "--sort", "size"]),
+            Err(CommandError::usage(
+                "Unknown sort key 'size', expected one of: name, value, recent".to_string()
+            ))
+        );
+        assert_eq!(
+            show(&["search-engines", "--sort", "usage"])
+                .unwrap_err()
+                .kind,
+            CommandErrorKind::Usage
+        );
+    }
+
+    #[test]
+    fn test_show_run_sort_parents_by_count() {
+        let mut ctx = Context::in_memory(vec![
+            (
+                "docs".to_string(),
+                vec![("rust".to_string(), "https://doc.rust-lang.org".to_string())],
+            ),
+            (
+                "search-engines".to_string(),
+                vec![
+                    ("google".to_string(), "https://google.com".to_string()),
+                    ("yahoo".to_string(), "https://yahoo.com".to_string()),
+                ],
+            ),
+        ]);
+        let mut show = |args: &[&str]| {
+            Show::default()
+                .run(&mut ctx, args.iter().map(|a| a.to_string()).collect())
+                .map(|r| r.to_string())
+        };
+        assert_eq!(
+            show(&["--sort", "count"]),
+            Ok("Parent Entities:\n  search-engines (2)\n  docs (1)".to_string())
+        );
+        assert_eq!(
+            show(&["--sort", "count", "--reverse"]),
+            Ok("Parent Entities:\n  docs (1)\n  search-engines (2)".to_string())
+        );
+        assert_eq!(
+            show(&["--sort", "value"]),
+            Err(CommandError::usage(
+                "Unknown sort key 'value', expected one of: name, count".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_sort_by_recent() {
+        let entry = |time: &str, operation: &str, link: &str, new: &str| Entry {
+            time: time.to_string(),
+            operation: operation.to_string(),
+            parent: "work".to_string(),
+            link: link.to_string(),
+            old: String::new(),
+            new: new.to_string(),
+        };
+        let history = [
+            entry("2024-06-01T12:00:00Z", "add", "ci", "https://ci.internal"),
+            entry("2024-06-02T12:00:00Z", "add", "mr", "https://mr.internal"),
+            entry(
+                "2024-06-03T12:00:00Z",
+                "upsert",
+                "ci",
+                "https://ci.example.com",
+            ),
+            entry("2024-06-04T12:00:00Z", "delete", "mr", ""),
+        ];
+        let mut links: Vec<(String, String)> = ["jira", "mr", "ci"]
+            .iter()
+            .map(|link| (link.to_string(), String::new()))
+            .collect();
+        sort_by_recent(&mut links, "work", &history);
+        let names: Vec<&str> = links.iter().map(|(link, _)| link.as_str()).collect();
+        assert_eq!(names, vec!["ci", "mr", "jira"]);
+    }
+
+    #[test]
+    fn test_show_run_values() {
+        let mut ctx = ctx_with_links("search-engines");
+        ctx.store
+            .write(|ds| {
+                ds.batch(|tx| tx.update_meta("search-engines", "yahoo", |m| m.pinned = true))
+            })
+            .unwrap();
+        let res = Show::default().run(
+            &mut ctx,
+            vec!["search-engines".to_string(), "--values".to_string()],
+        );
+        assert_eq!(
+            res.map(|r| r.to_string()),
+            Ok("Links of parent entity search-engines:\n  yahoo   https://yahoo.com   [pinned]\n  google  https://google.com".to_string())
+        );
+    }
+
+    #[test]
+    fn test_show_run_templates() {
+        let mut ctx = Context::in_memory(vec![(
+            "gh".to_string(),
+            vec![
+                (
+                    "issues".to_string(),
+                    "https://github.com/me/{repo}/issues?q={query}".to_string(),
+                ),
+                ("home".to_string(), "https://github.com/{{me}}".to_string()),
+            ],
+        )]);
+        let res = Show::default().run(&mut ctx, vec!["gh".to_string()]);
+        assert_eq!(
+            res.map(|r| r.to_string()),
+            Ok("Links of parent entity gh:\n  home\n  issues  [template: repo, query]".to_string())
+        );
+        let res = Show::default().run(&mut ctx, vec!["gh".to_string(), "issues".to_string()]);
+        assert_eq!(
+            res,
+            Ok(CommandResult::Value(
+                "issues: https://github.com/me/{repo}/issues?q={query} [template: repo, query]"
+                    .to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_show_run_expands_environment_variables() {
+        // PATH is set wherever the tests run
+        let path = std::env::var("PATH").unwrap();
+        let mut ctx = Context::in_memory(vec![(
+            "env".to_string(),
+            vec![
+                ("path".to_string(), "${PATH}/x".to_string()),
+                ("unset".to_string(), "$TAP_TEST_NEVER_SET/x".to_string()),
+            ],
+        )]);
+        let res = Show::default().run(&mut ctx, vec!["env".to_string(), "path".to_string()]);
+        assert_eq!(res, Ok(CommandResult::Value(format!("path: {path}/x"))));
+        let res = Show::default().run(&mut ctx, vec!["env".to_string(), "unset".to_string()]);
+        assert_eq!(
+            res,
+            Ok(CommandResult::Value(
+                "unset: $TAP_TEST_NEVER_SET/x".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_show_run_link_types() {
+        let mut ctx = ctx_with_links("search-engines");
+        ctx.store
+            .write(|ds| {
+                ds.batch(|tx| {
+                    tx.update_meta("search-engines", "google", |m| {
+                        m.link_type = Some(LinkType::Url)
+                    })?;
+                    tx.update_meta("search-engines", "yahoo", |m| {
+                        m.link_type = Some(LinkType::Cmd)
+                    })
+                })
+            })
+            .unwrap();
+        let res = Show::default().run(&mut ctx, vec!["search-engines".to_string()]);
+        assert_eq!(
+            res.map(|r| r.to_string()),
+            Ok("Links of parent entity search-engines:\n  google\n  yahoo   [cmd]".to_string())
+        );
+    }
+
+    #[test]
+    fn test_show_run_type_filter() {
+        let mut ctx = Context::in_memory(vec![(
+            "work".to_string(),
+            vec![
+                ("ci".to_string(), "https://ci.internal".to_string()),
+                ("notes".to_string(), "/home/me/notes.md".to_string()),
+                ("vpn".to_string(), "wg-quick up wg0".to_string()),
+            ],
+        )]);
+        ctx.store
+            .write(|ds| {
+                ds.batch(|tx| {
+                    tx.update_meta("work", "notes", |m| m.link_type = Some(LinkType::File))?;
+                    tx.update_meta("work", "vpn", |m| m.link_type = Some(LinkType::Cmd))
+                })
+            })
+            .unwrap();
+        let show = |ctx: &mut Context, extra: &[&str]| {
+            let mut args = vec!["work".to_string()];
+            args.extend(extra.iter().map(|s| s.to_string()));
+            Show::default().run(ctx, args).map(|r| r.to_string())
+        };
+        // Links without a type are told by their value
+        assert_eq!(
+            show(&mut ctx, &["--type", "url"]),
+            Ok("Links of parent entity work:\n  ci".to_string())
+        );
+        assert_eq!(
+            show(&mut ctx, &["--type", "file,cmd"]),
+            Ok("Links of parent entity work:\n  notes  [file]\n  vpn    [cmd]".to_string())
+        );
+        assert_eq!(
+            show(&mut ctx, &["--type", "cmd", "--filter", "notes"]),
+            Err(CommandError::from(
+                "no matches for notes of type cmd".to_string()
+            ))
+        );
+        assert_eq!(
+            show(&mut ctx, &["--type", "dir"]),
+            Err(CommandError::from("no links of type dir".to_string()))
+        );
+        let err = show(&mut ctx, &["--type", "script"]).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::Usage);
+        assert!(err.message.contains("url, file, dir, cmd"));
+        let err = Show::default()
+            .run(&mut ctx, vec!["--type".to_string(), "url".to_string()])
+            .unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::Usage);
+    }
+
+    #[test]
+    fn test_link_rows_cut_values_to_width() {
+        let links = vec![
+            ("ci".to_string(), "https://ci.internal".to_string()),
+            (
+                "jira".to_string(),
+                "https://jira.internal/ENG-42".to_string(),
+            ),
+        ];
+        let meta = BTreeMap::from([(
+            "ci".to_string(),
+            LinkMeta {
+                default: true,
+                ..LinkMeta::default()
+            },
+        )]);
+        // 2 + 4 + 2 columns go to the name and 2 + 9 to the markers, leaving 11 for the value
+        assert_eq!(
+            link_rows(links.clone(), &meta, true, 4, Some(30)),
+            vec![
+                vec![
+                    "ci".to_string(),
+                    "https://...".to_string(),
+                    "[default]".to_string()
+                ],
+                vec!["jira".to_string(), "https://...".to_string()],
+            ]
+        );
+        assert_eq!(
+            link_rows(links, &meta, true, 4, None)[1],
+            vec![
+                "jira".to_string(),
+                "https://jira.internal/ENG-42".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_show_run_filter() {
+        let mut ctx = Context::in_memory(vec![
+            (
+                "search-engines".to_string(),
+                vec![
+                    ("google".to_string(), "https://google.com".to_string()),
+                    ("yahoo".to_string(), "https://yahoo.com".to_string()),
+                ],
+            ),
+            (
+                "work".to_string(),
+                vec![(
+                    "dashboards".to_string(),
+                    "https://Grafana.internal".to_string(),
+                )],
+            ),
+        ]);
+        let mut show = |args: &[&str]| {
+            Show::default()
+                .run(&mut ctx, args.iter().map(|a| a.to_string()).collect())
+                .map(|r| r.to_string())
+        };
+        assert_eq!(
+            show(&["work", "--filter", "grafana"]),
+            Ok("Links of parent entity work:\n  dashboards".to_string())
+        );
+        assert_eq!(
+            show(&["search-engines", "--filter", "^y", "--regex"]),
+            Ok("Links of parent entity search-engines:\n  yahoo".to_string())
+        );
+        assert_eq!(
+            show(&["--filter", "WORK", "--plain"]),
+            Ok("Parent Entities:\n  work".to_string())
+        );
+        let e = show(&["search-engines", "--filter", "bing"]).unwrap_err();
+        assert_eq!(e.kind, CommandErrorKind::Generic);
+        assert_eq!(e.message, "no matches for bing");
+        let e = show(&["work", "--filter"]).unwrap_err();
+        assert_eq!(e.kind, CommandErrorKind::Usage);
+        assert_eq!(e.message, "expected a pattern after --filter");
+        assert_eq!(
+            show(&["work", "--regex"]).unwrap_err().kind,
+            CommandErrorKind::Usage
+        );
+    }
+
+    #[test]
+    fn test_show_run_json_no_parents() {
+        let args: Vec<String> = vec!["--json".to_string()];
+        let cmd = Show::default();
+        let mut ctx = Context::in_memory(vec![]);
+        let res = cmd.run(&mut ctx, args).unwrap();
+        assert_eq!(res.to_string(), r#"{"parents":[]}"#);
+    }
+
+    #[test]
+    fn test_show_run_json_single_parent() {
+        let args: Vec<String> = vec!["--json".to_string()];
+        let cmd = Show::default();
+        let mut ctx = ctx_with_links("search-engines");
+        let res = cmd.run(&mut ctx, args).unwrap();
+        assert_eq!(res.to_string(), r#"{"parents":["search-engines"]}"#);
+    }
+
+    #[test]
+    fn test_show_run_json_multiple_links() {
+        let args: Vec<String> = vec!["search-engines".to_string(), "--json".to_string()];
+        let cmd = Show::default();
+        let mut ctx = ctx_with_links("search-engines");
+        let res = cmd.run(&mut ctx, args).unwrap();
+        assert_eq!(
+            res.to_string(),
+            r#"{"parent":"search-engines","links":[{"name":"google","value":"https://google.com"},{"name":"yahoo","value":"https://yahoo.com"}]}"#
+        );
+    }
+
+    #[test]
+    fn test_show_run_json_single_link() {
+        let args: Vec<String> = vec![
+            "--json".to_string(),
+            "search-engines".to_string(),
+            "yahoo".to_string(),
+        ];
+        let cmd = Show::default();
+        let mut ctx = ctx_with_links("search-engines");
+        let res = cmd.run(&mut ctx, args).unwrap();
+        assert_eq!(
+            res.to_string(),
+            r#"{"parent":"search-engines","links":[{"name":"yahoo","value":"https://yahoo.com"}]}"#
+        );
+    }
+
+    #[test]
+    fn test_show_run_parent_not_found_suggests_parents() {
+        let args: Vec<String> = vec!["search".to_string()];
+        let cmd = Show::default();
+        let mut ctx = ctx_with_links("search-engines");
+        let err = cmd.run(&mut ctx, args).unwrap_err();
+        assert_eq!(err.kind, CommandErrorKind::ParentNotFound);
+        assert!(err.message.contains("Did you mean: search-engines?"));
+    }
 }
@@ -0,0 +1,487 @@
+use crate::{
+    commands::{Command, CommandError, CommandResult, Table, display_examples},
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::command::Args,
+    utils::config::{Config, default_config_path},
+    utils::context::Context,
+    utils::json::Json,
+    utils::os_implementations::run_git,
+    utils::style,
+};
+use std::path::{Path, PathBuf};
+
+/// The config file key naming the git repository the data file is synced with, for data files
+/// that are not inside the repository they are synced with, e.g. symlinked into it
+const SYNC_REPO_CONFIG_KEY: &str = "sync_repo";
+
+/// Runs git with the arguments in a directory, see `run_git`
+type Git<'a> = &'a dyn Fn(&Path, &[&str]) -> Result<String, CommandError>;
+
+pub(crate) struct GitSync {
+    name: String,
+    description: String,
+    args: [String; 1],
+}
+
+impl Default for GitSync {
+    fn default() -> Self {
+        Self {
+            name: "--sync".to_string(),
+            description: "Sync the data file with its git repository".to_string(),
+            args: ["[--status]".to_string()],
+        }
+    }
+}
+
+/// Where the branch of the repository stands, read from `git status --porcelain=v2 --branch`
+#[derive(Debug, Default, PartialEq)]
+struct RepoStatus {
+    branch: String,
+    upstream: Option<String>,
+    ahead: usize,
+    behind: usize,
+    /// How many files have changes that are not committed
+    changed_files: usize,
+}
+
+impl RepoStatus {
+    fn parse(porcelain: &str) -> Self {
+        let mut status = Self::default();
+        for line in porcelain.lines() {
+            if let Some(head) = line.strip_prefix("# branch.head ") {
+                status.branch = head.to_string();
+            } else if let Some(upstream) = line.strip_prefix("# branch.upstream ") {
+                status.upstream = Some(upstream.to_string());
+            } else if let Some(ab) = line.strip_prefix("# branch.ab ") {
+                for count in ab.split_whitespace() {
+                    let (sign, n) = count.split_at(1);
+                    let n = n.parse().unwrap_or(0);
+                    match sign {
+                        "+" => status.ahead = n,
+                        _ => status.behind = n,
+                    }
+                }
+            } else if !line.starts_with('#') && !line.is_empty() {
+                status.changed_files += 1;
+            }
+        }
+        status
+    }
+}
+
+impl Command for GitSync {
+    fn error_message(&self) -> String {
+        "expected 0-1 arguments, see the Usage section with tap --sync --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --sync command syncs the data file with the git repository it is in, like a dotfiles repository, by running git. The changes to the data file are committed first with a generated message, then git pull --rebase brings in the changes from the remote, the data file is checked to still parse and the index file is rebuilt from it, and the commits are pushed. Other files of the repository are never committed.\n\n");
+        s.push_str("When pulling conflicts with the changes to the data file, the pull is undone and nothing is pushed, so the data file is never left half merged. The changes stay committed, to be merged by hand with git pull --rebase in the repository. When the pulled data file does not parse, nothing is pushed either.\n\n");
+        s.push_str(&format!("The repository is the one the data file is in. Set {SYNC_REPO_CONFIG_KEY} = <path> in the config file to use another one, e.g. when the data file is a symlink into it.\n\n"));
+        s.push_str("Pass --status to show the branch, how many commits it is ahead of and behind its upstream as of the last fetch, and the files with changes, without running anything that changes the repository.\n\n");
+        s.push_str("Command Structure: tap --sync [--status]\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let mut args = Args::new(args);
+        let status = args.take_flag(&["--status"]);
+        let git = |dir: &Path, args: &[&str]| run_git(dir, args).map_err(CommandError::from);
+        match args.len() {
+            0 => {
+                let [(data, _), _] = ctx.store.options().resolve_paths()?;
+                let repo = self.repo(&data, &git)?;
+                match status {
+                    true => self.status(ctx, &repo, &data, &git),
+                    false => self.sync(ctx, &repo, &data, &git),
+                }
+            }
+            1 if args.is_flag(0, "--help") => Ok(CommandResult::Value(self.help_message())),
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+impl GitSync {
+    /// The repository the data file is synced with: the one set in the config file, otherwise the
+    /// one the data file is in
+    fn repo(&self, data: &Path, git: Git) -> Result<PathBuf, CommandError> {
+        if let Some(repo) = sync_repo_configured() {
+            return Ok(repo);
+        }
+        let dir = data.parent().unwrap_or(Path::new("."));
+        match git(dir, &["rev-parse", "--show-toplevel"]) {
+            Ok(top) => Ok(PathBuf::from(top.trim())),
+            Err(e) => Err(CommandError::from(format!(
+                "The data file {} is not in a git repository: {}",
+                data.display(),
+                e.message
+            ))
+            .with_hint(&format!(
+                "Move it into one with tap --migrate-data, or set {SYNC_REPO_CONFIG_KEY} = <path> in the config file"
+            ))),
+        }
+    }
+
+    /// Reports where the branch stands and what has changed, without changing anything
+    fn status(
+        &self,
+        ctx: &Context,
+        repo: &Path,
+        data: &Path,
+        git: Git,
+    ) -> Result<CommandResult, CommandError> {
+        let status = RepoStatus::parse(&git(repo, &["status", "--porcelain=v2", "--branch"])?);
+        let data_changed = !git(repo, &data_status_args(data))?.trim().is_empty();
+        if ctx.format.is_structured() {
+            return Ok(CommandResult::Json(Json::object([
+                (
+                    "repository",
+                    Json::from(repo.display().to_string().as_str()),
+                ),
+                ("branch", Json::from(status.branch.as_str())),
+                (
+                    "upstream",
+                    Json::from(status.upstream.as_deref().unwrap_or_default()),
+                ),
+                ("ahead", Json::Number(status.ahead as u64)),
+                ("behind", Json::Number(status.behind as u64)),
+                ("changed_files", Json::Number(status.changed_files as u64)),
+                ("data_file_changed", Json::Bool(data_changed)),
+            ])));
+        }
+        let dirty = match (status.changed_files, data_changed) {
+            (0, _) => "no".to_string(),
+            (n, true) => format!("yes, {n} file(s) including the data file"),
+            (n, false) => format!("yes, {n} file(s) but not the data file"),
+        };
+        Ok(CommandResult::Table(Table {
+            title: format!("Sync status of {}:", repo.display()),
+            rows: vec![
+                vec!["branch".to_string(), status.branch],
+                vec![
+                    "upstream".to_string(),
+                    status.upstream.unwrap_or_else(|| "none".to_string()),
+                ],
+                vec!["ahead".to_string(), status.ahead.to_string()],
+                vec!["behind".to_string(), status.behind.to_string()],
+                vec!["dirty".to_string(), dirty],
+            ],
+        }))
+    }
+
+    /// Commits the changes to the data file, pulls, checks the data file, and pushes
+    fn sync(
+        &self,
+        ctx: &Context,
+        repo: &Path,
+        data: &Path,
+        git: Git,
+    ) -> Result<CommandResult, CommandError> {
+        let mut done = vec![];
+        let data_arg = data.to_string_lossy();
+        if !git(repo, &data_status_args(data))?.trim().is_empty() {
+            let file_name = data
+                .file_name()
+                .map_or(data_arg.clone(), |n| n.to_string_lossy());
+            let message = format!("Update {file_name} with tap --sync");
+            git(repo, &["add", "--", &data_arg])?;
+            git(repo, &["commit", "--message", &message, "--", &data_arg])?;
+            done.push(format!("Committed the changes to {}", data.display()));
+        }
+        // Other files of the repository may have changes too, which git pull would refuse
+        if let Err(e) = git(repo, &["pull", "--rebase", "--autostash"]) {
+            let conflicts = git(repo, &["diff", "--name-only", "--diff-filter=U"])?;
+            if conflicts.trim().is_empty() {
+                return Err(e);
+            }
+            git(repo, &["rebase", "--abort"])?;
+            return Err(CommandError::from(format!(
+                "Pulling conflicts with the changes in {}, the pull was undone and nothing was pushed",
+                conflicts.trim().lines().collect::<Vec<_>>().join(", ")
+            ))
+            .with_hint(&format!(
+                "Merge by hand: run git pull --rebase in {}, fix the conflicts, run git rebase --continue, then tap --doctor and tap --sync",
+                repo.display()
+            )));
+        }
+        done.push(format!("Pulled {}", repo.display()));
+        ctx.store.rebuild_index().map_err(|e| {
+            CommandError::from(format!(
+                "The data file does not parse after pulling, nothing was pushed: {}",
+                CommandError::from(e).message
+            ))
+            .with_hint(&format!(
+                "Fix it with tap --doctor or tap --edit, or go back with git reset --hard ORIG_HEAD in {}, then run tap --sync again",
+                repo.display()
+            ))
+        })?;
+        let status = RepoStatus::parse(&git(repo, &["status", "--porcelain=v2", "--branch"])?);
+        match status.ahead {
+            0 => done.push("Nothing to push".to_string()),
+            ahead => {
+                git(repo, &["push"])?;
+                done.push(format!("Pushed {ahead} commit(s)"));
+            }
+        }
+        Ok(CommandResult::Value(style::success(&done.join("\n"))))
+    }
+}
+
+/// The arguments of `git status` listing the changes to the data file, nothing when there are none
+fn data_status_args(data: &Path) -> [&str; 4] {
+    [
+        "status",
+        "--porcelain",
+        "--",
+        data.to_str().unwrap_or_default(),
+    ]
+}
+
+/// The repository set with `sync_repo` in the config file, `None` when there is none or the
+/// config file can not be read
+fn sync_repo_configured() -> Option<PathBuf> {
+    default_config_path()
+        .and_then(|path| Config::load(Some(path)).ok())
+        .and_then(|config| config.get(SYNC_REPO_CONFIG_KEY).map(PathBuf::from))
+}
+
+impl DisplayCommandAsRow for GitSync {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            (
+                "tap --sync",
+                "Commit the changes to the links, pull, and push",
+            ),
+            (
+                "tap --sync --status",
+                "Show how the links stand against the remote",
+            ),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::format::Format;
+    use std::cell::RefCell;
+
+    const REPO: &str = "/home/me/dotfiles";
+    const DATA: &str = "/home/me/dotfiles/tap/.tap_data";
+
+    /// A fake git that answers from `answers`, the first whose arguments start like the ones run,
+    /// and records what was run
+    fn fake_git<'a>(
+        answers: &'a [(&'a str, Result<&'a str, &'a str>)],
+        ran: &'a RefCell<Vec<String>>,
+    ) -> impl Fn(&Path, &[&str]) -> Result<String, CommandError> + 'a {
+        move |_, args| {
+            let line = args.join(" ");
+            ran.borrow_mut().push(line.clone());
+            match answers.iter().find(|(start, _)| line.starts_with(start)) {
+                Some((_, Ok(out))) => Ok(out.to_string()),
+                Some((_, Err(e))) => Err(CommandError::from(e.to_string())),
+                None => Ok(String::new()),
+            }
+        }
+    }
+
+    fn sync(
+        ctx: &Context,
+        answers: &[(&str, Result<&str, &str>)],
+    ) -> (Result<CommandResult, CommandError>, Vec<String>) {
+        let ran = RefCell::new(vec![]);
+        let git = fake_git(answers, &ran);
+        let res = GitSync::default().sync(ctx, Path::new(REPO), Path::new(DATA), &git);
+        (res, ran.take())
+    }
+
+    #[test]
+    fn test_sync_run_expected_help_arg() {
+        let cmd = GitSync::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_sync_run_unexpected_args() {
+        let cmd = GitSync::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["origin".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_repo_status_parse() {
+        let status = RepoStatus::parse(
+            "# branch.oid 1234\n# branch.head main\n# branch.upstream origin/main\n# branch.ab +2 -1\n1 .M N... 100644 100644 100644 1 2 tap/.tap_data\n? notes.txt\n",
+        );
+        assert_eq!(
+            status,
+            RepoStatus {
+                branch: "main".to_string(),
+                upstream: Some("origin/main".to_string()),
+                ahead: 2,
+                behind: 1,
+                changed_files: 2,
+            }
+        );
+        assert_eq!(RepoStatus::parse("# branch.head main\n").upstream, None);
+    }
+
+    #[test]
+    fn test_sync_commits_pulls_and_pushes() {
+        let ctx = Context::in_memory(vec![]);
+        let (res, ran) = sync(
+            &ctx,
+            &[
+                (
+                    "status --porcelain=v2",
+                    Ok("# branch.head main\n# branch.ab +1 -0\n"),
+                ),
+                ("status --porcelain", Ok(" M tap/.tap_data\n")),
+            ],
+        );
+        assert_eq!(
+            res,
+            Ok(CommandResult::Value(format!(
+                "Committed the changes to {DATA}\nPulled {REPO}\nPushed 1 commit(s)"
+            )))
+        );
+        assert_eq!(
+            ran,
+            vec![
+                format!("status --porcelain -- {DATA}"),
+                format!("add -- {DATA}"),
+                format!("commit --message Update .tap_data with tap --sync -- {DATA}"),
+                "pull --rebase --autostash".to_string(),
+                "status --porcelain=v2 --branch".to_string(),
+                "push".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sync_nothing_to_commit_or_push() {
+        let ctx = Context::in_memory(vec![]);
+        let (res, ran) = sync(&ctx, &[]);
+        assert_eq!(
+            res,
+            Ok(CommandResult::Value(format!(
+                "Pulled {REPO}\nNothing to push"
+            )))
+        );
+        assert!(
+            !ran.iter()
+                .any(|line| line.starts_with("commit") || line == "push")
+        );
+    }
+
+    #[test]
+    fn test_sync_conflict_undoes_the_pull() {
+        let ctx = Context::in_memory(vec![]);
+        let (res, ran) = sync(
+            &ctx,
+            &[
+                (
+                    "pull",
+                    Err("git pull --rebase --autostash failed: CONFLICT"),
+                ),
+                ("diff --name-only", Ok("tap/.tap_data\n")),
+            ],
+        );
+        let err = res.unwrap_err();
+        assert!(
+            err.message
+                .contains("conflicts with the changes in tap/.tap_data")
+        );
+        assert!(err.message.contains("git rebase --continue"));
+        assert_eq!(ran.last().unwrap(), "rebase --abort");
+        assert!(!ran.iter().any(|line| line == "push"));
+    }
+
+    #[test]
+    fn test_sync_pull_failure() {
+        let ctx = Context::in_memory(vec![]);
+        let (res, ran) = sync(
+            &ctx,
+            &[("pull", Err("git pull failed: Could not resolve host"))],
+        );
+        assert_eq!(
+            res,
+            Err(CommandError::from(
+                "git pull failed: Could not resolve host".to_string()
+            ))
+        );
+        assert!(
+            !ran.iter()
+                .any(|line| line == "rebase --abort" || line == "push")
+        );
+    }
+
+    #[test]
+    fn test_sync_status() {
+        let mut ctx = Context::in_memory(vec![]);
+        let ran = RefCell::new(vec![]);
+        let answers = [
+            (
+                "status --porcelain=v2",
+                Ok(
+                    "# branch.head main\n# branch.upstream origin/main\n# branch.ab +0 -3\n? notes.txt\n",
+                ),
+            ),
+            ("status --porcelain", Ok("")),
+        ];
+        let git = fake_git(&answers, &ran);
+        let res = GitSync::default().status(&ctx, Path::new(REPO), Path::new(DATA), &git);
+        assert_eq!(
+            res.unwrap().to_string(),
+            format!(
+                "Sync status of {REPO}:\n  branch    main\n  upstream  origin/main\n  ahead     0\n  behind    3\n  dirty     yes, 1 file(s) but not the data file"
+            )
+        );
+        ctx.format = Format::Json;
+        let res = GitSync::default().status(&ctx, Path::new(REPO), Path::new(DATA), &git);
+        assert_eq!(
+            res.unwrap().render(Format::Json),
+            format!(
+                r#"{{"repository":"{REPO}","branch":"main","upstream":"origin/main","ahead":0,"behind":3,"changed_files":1,"data_file_changed":false}}"#
+            )
+        );
+        // Nothing that changes the repository is run
+        assert!(ran.borrow().iter().all(|line| line.starts_with("status")));
+    }
+
+    #[test]
+    fn test_sync_repo_outside_git() {
+        let ran = RefCell::new(vec![]);
+        let answers = [("rev-parse", Err("fatal: not a git repository"))];
+        let git = fake_git(&answers, &ran);
+        let err = GitSync::default().repo(Path::new(DATA), &git).unwrap_err();
+        assert!(err.message.contains("not in a git repository"));
+    }
+}
@@ -0,0 +1,160 @@
+use crate::{
+    commands::{Command, CommandError, CommandResult, Table, display_examples},
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::context::Context,
+    utils::json::Json,
+    utils::style,
+    utils::trash::{DEFAULT_RETENTION_DAYS, Entry, TRASH_DAYS_CONFIG_KEY},
+};
+
+pub(crate) struct Trash {
+    name: String,
+    description: String,
+    args: [String; 0],
+}
+
+impl Default for Trash {
+    fn default() -> Self {
+        Self {
+            name: "--trash".to_string(),
+            description: "Show the deleted links that can be restored".to_string(),
+            args: [],
+        }
+    }
+}
+
+impl Command for Trash {
+    fn error_message(&self) -> String {
+        "expected 0 arguments, see the Usage section with tap --trash --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --trash command shows the links removed with tap --delete, oldest first, with the time they were deleted. Deleted links are kept in .tap_trash next to the data file, and tap --restore-trash puts them back.\n\n");
+        s.push_str(&format!("Links are dropped from the trash {DEFAULT_RETENTION_DAYS} days after they were deleted, set {TRASH_DAYS_CONFIG_KEY} = <days> in the config file to keep them longer or shorter. Pass --purge to tap --delete to skip the trash.\n\n"));
+        s.push_str("Command Structure: tap --trash\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        match args.len() {
+            0 => {
+                let entries = ctx.store.trashed()?;
+                if ctx.format.is_structured() {
+                    return Ok(CommandResult::Json(trash_json(&entries)));
+                }
+                if entries.is_empty() {
+                    return Ok(CommandResult::Value("The trash is empty".to_string()));
+                }
+                Ok(CommandResult::Table(Table {
+                    title: format!("{} deleted link(s):", entries.len()),
+                    rows: entries
+                        .iter()
+                        .map(|entry| {
+                            vec![
+                                entry.time.clone(),
+                                style::parent(&entry.parent),
+                                entry.link.clone(),
+                                entry.value.clone(),
+                            ]
+                        })
+                        .collect(),
+                }))
+            }
+            1 if args[0] == "--help" => Ok(CommandResult::Value(self.help_message())),
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+/// `[{"time":"2024-06-01T12:30:05Z","parent":"work","link":"ci","value":"..."}]`
+fn trash_json(entries: &[Entry]) -> Json {
+    Json::Array(
+        entries
+            .iter()
+            .map(|entry| {
+                Json::object([
+                    ("time", Json::from(entry.time.as_str())),
+                    ("parent", Json::from(entry.parent.as_str())),
+                    ("link", Json::from(entry.link.as_str())),
+                    ("value", Json::from(entry.value.as_str())),
+                ])
+            })
+            .collect(),
+    )
+}
+
+impl DisplayCommandAsRow for Trash {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [("tap --trash", "Show the deleted links")]
+            .into_iter()
+            .map(|(line, description)| (line.to_string(), description.to_string()))
+            .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trash_run_expected_help_arg() {
+        let cmd = Trash::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_trash_run_unexpected_args() {
+        let cmd = Trash::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["work".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_trash_run_lists_deleted_links() {
+        let mut ctx = Context::in_memory(vec![(
+            "work".to_string(),
+            vec![
+                ("ci".to_string(), "https://ci.internal".to_string()),
+                ("mr".to_string(), "https://mr.internal".to_string()),
+            ],
+        )]);
+        assert_eq!(
+            Trash::default().run(&mut ctx, vec![]),
+            Ok(CommandResult::Value("The trash is empty".to_string()))
+        );
+        ctx.store
+            .write(|ds| ds.delete("work".to_string(), Some("ci".to_string())))
+            .unwrap();
+        ctx.store
+            .write(|ds| ds.batch(|tx| tx.purge("work", Some("mr"))))
+            .unwrap();
+        let Ok(CommandResult::Table(table)) = Trash::default().run(&mut ctx, vec![]) else {
+            panic!("expected a table");
+        };
+        // Purged links skip the trash
+        assert_eq!(table.title, "1 deleted link(s):");
+        assert_eq!(table.rows[0][1..], ["work", "ci", "https://ci.internal"]);
+    }
+}
@@ -0,0 +1,394 @@
+use crate::{
+    commands::{Command, CommandError, CommandResult, display_examples, truncate},
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::command::Args,
+    utils::context::Context,
+    utils::json::Json,
+    utils::regex::Regex,
+    utils::style,
+};
+
+pub(crate) struct Tree {
+    name: String,
+    description: String,
+    args: [String; 3],
+}
+
+impl Default for Tree {
+    fn default() -> Self {
+        Self {
+            name: "--tree".to_string(),
+            description: "Show every parent entity with its links".to_string(),
+            args: [
+                "[--values]".to_string(),
+                "[--filter <Pattern>]".to_string(),
+                "[--ascii]".to_string(),
+            ],
+        }
+    }
+}
+
+/// The characters drawing the branches of the tree
+struct Glyphs {
+    branch: &'static str,
+    last: &'static str,
+    /// Drawn below a branch that has more branches after the one nested under it
+    pipe: &'static str,
+}
+
+const UNICODE: Glyphs = Glyphs {
+    branch: "├── ",
+    last: "└── ",
+    pipe: "│   ",
+};
+
+const ASCII: Glyphs = Glyphs {
+    branch: "|-- ",
+    last: "`-- ",
+    pipe: "|   ",
+};
+
+/// A segment of the slash-separated parent entity names, with the links of the parent entity
+/// named up to it and the segments nested under it
+struct Node<'a> {
+    segment: &'a str,
+    links: &'a [(String, String)],
+    children: Vec<Node<'a>>,
+}
+
+type Parents = Vec<(String, Vec<(String, String)>)>;
+
+impl Command for Tree {
+    fn error_message(&self) -> String {
+        "unexpected arguments, see the Usage section with tap --tree --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --tree command shows every Parent Entity with its links nested underneath, in the order of the data file. Parent Entity names that nest with '/', like work/infra, are drawn one segment per level, with the nested names before the links.\n\n");
+        s.push_str("Command Structure: tap --tree [--values] [--filter <Pattern>] [--ascii]\n");
+        s.push_str("Pass --values to show the value of each link, cut to the width of the terminal, and --filter to only show the branches matching a regular expression, ignoring case: a Parent Entity whose name matches keeps all of its links, otherwise only the links whose name or value matches are kept. The branches are drawn with plain ASCII when not printing to a terminal, with --no-color, or with --ascii.\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let mut args = Args::new(args);
+        if args.len() == 1 && args.is_flag(0, "--help") {
+            return Ok(CommandResult::Value(self.help_message()));
+        }
+        let values = args.take_flag(&["--values"]);
+        let ascii = args.take_flag(&["--ascii"]);
+        let pattern = args.take_option("--filter");
+        if pattern.is_none() && args.has_flag("--filter") {
+            return Err(CommandError::usage(
+                "expected a pattern after --filter".to_string(),
+            ));
+        }
+        let filter = match &pattern {
+            Some(pattern) => Some(
+                Regex::new(pattern)
+                    .map_err(|e| CommandError::usage(e.to_string()))?
+                    .case_insensitive(),
+            ),
+            None => None,
+        };
+        if !args.is_empty() {
+            return Err(self.usage_error());
+        }
+        let mut parents = ctx.store.all_links()?;
+        if let (Some(filter), Some(pattern)) = (&filter, &pattern) {
+            parents = prune(parents, filter);
+            if parents.is_empty() {
+                return Err(format!("no matches for {pattern}").into());
+            }
+        }
+        if ctx.format.is_structured() {
+            return Ok(CommandResult::Json(tree_json(&parents)));
+        }
+        let glyphs = match !ascii && style::fancy() {
+            true => &UNICODE,
+            false => &ASCII,
+        };
+        let width = values.then(style::terminal_width).flatten();
+        Ok(CommandResult::Value(draw(&parents, glyphs, values, width)))
+    }
+}
+
+/// Keeps the parent entities whose name matches `filter` with all of their links, and of the
+/// others only the links whose name or value matches
+fn prune(parents: Parents, filter: &Regex) -> Parents {
+    parents
+        .into_iter()
+        .filter_map(|(parent, links)| {
+            if filter.is_match(&parent) {
+                return Some((parent, links));
+            }
+            let links: Vec<_> = links
+                .into_iter()
+                .filter(|(link, value)| filter.is_match(link) || filter.is_match(value))
+                .collect();
+            (!links.is_empty()).then_some((parent, links))
+        })
+        .collect()
+}
+
+/// Nests the parent entities by the segments of their names, so `work/infra` is drawn under `work`
+fn nest(parents: &Parents) -> Vec<Node<'_>> {
+    let mut roots: Vec<Node> = vec![];
+    for (parent, links) in parents {
+        let mut nodes = &mut roots;
+        let mut segments = parent.split('/').peekable();
+        while let Some(segment) = segments.next() {
+            let i = match nodes.iter().position(|node| node.segment == segment) {
+                Some(i) => i,
+                None => {
+                    nodes.push(Node {
+                        segment,
+                        links: &[],
+                        children: vec![],
+                    });
+                    nodes.len() - 1
+                }
+            };
+            if segments.peek().is_none() {
+                nodes[i].links = links;
+            }
+            nodes = &mut nodes[i].children;
+        }
+    }
+    roots
+}
+
+/// The tree as text, with values cut to `width` columns when given
+fn draw(parents: &Parents, glyphs: &Glyphs, values: bool, width: Option<usize>) -> String {
+    let mut lines = vec![];
+    for node in nest(parents) {
+        lines.push(style::parent(node.segment));
+        draw_branches(&node, "", glyphs, values, width, &mut lines);
+    }
+    lines.join("\n")
+}
+
+/// Draws the segments nested under `node`, then its links, each line starting with `indent`
+fn draw_branches(
+    node: &Node,
+    indent: &str,
+    glyphs: &Glyphs,
+    values: bool,
+    width: Option<usize>,
+    lines: &mut Vec<String>,
+) {
+    let count = node.children.len() + node.links.len();
+    let glyph = |i: usize| match i + 1 == count {
+        true => glyphs.last,
+        false => glyphs.branch,
+    };
+    for (i, child) in node.children.iter().enumerate() {
+        lines.push(format!(
+            "{indent}{}{}",
+            glyph(i),
+            style::parent(child.segment)
+        ));
+        let nested = match i + 1 == count {
+            true => " ".repeat(glyphs.pipe.chars().count()),
+            false => glyphs.pipe.to_string(),
+        };
+        draw_branches(
+            child,
+            &format!("{indent}{nested}"),
+            glyphs,
+            values,
+            width,
+            lines,
+        );
+    }
+    for (i, (link, value)) in node.links.iter().enumerate() {
+        let mut line = format!("{indent}{}{link}", glyph(node.children.len() + i));
+        if values {
+            line.push_str(": ");
+            let room = width.map(|w| w.saturating_sub(line.chars().count()));
+            line.push_str(&truncate(value, room));
+        }
+        lines.push(line);
+    }
+}
+
+/// `{"parents":[{"name":"...","links":[{"name":"...","value":"..."}]}]}`
+fn tree_json(parents: &Parents) -> Json {
+    Json::object([(
+        "parents",
+        Json::Array(
+            parents
+                .iter()
+                .map(|(parent, links)| {
+                    Json::object([
+                        ("name", Json::from(parent.as_str())),
+                        (
+                            "links",
+                            Json::Array(
+                                links
+                                    .iter()
+                                    .map(|(link, value)| {
+                                        Json::object([
+                                            ("name", Json::from(link.as_str())),
+                                            ("value", Json::from(value.as_str())),
+                                        ])
+                                    })
+                                    .collect(),
+                            ),
+                        ),
+                    ])
+                })
+                .collect(),
+        ),
+    )])
+}
+
+impl DisplayCommandAsRow for Tree {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            ("tap --tree", "Show every parent entity with its links"),
+            ("tap --tree --values", "Show the values of the links too"),
+            (
+                "tap --tree --filter jira",
+                "Only show the branches mentioning jira",
+            ),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::CommandErrorKind;
+
+    fn ctx() -> Context {
+        Context::in_memory(vec![
+            (
+                "work".to_string(),
+                vec![("jira".to_string(), "https://jira.internal".to_string())],
+            ),
+            (
+                "search-engines".to_string(),
+                vec![
+                    ("yahoo".to_string(), "https://yahoo.com".to_string()),
+                    ("google".to_string(), "https://google.com".to_string()),
+                ],
+            ),
+        ])
+    }
+
+    fn tree(args: &[&str]) -> Result<CommandResult, CommandError> {
+        Tree::default().run(&mut ctx(), args.iter().map(|a| a.to_string()).collect())
+    }
+
+    #[test]
+    fn test_tree_run_expected_help_arg() {
+        let cmd = Tree::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_tree_run_unexpected_args() {
+        let cmd = Tree::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(tree(&["work"]), expected);
+        let e = tree(&["--filter"]).unwrap_err();
+        assert_eq!(e.kind, CommandErrorKind::Usage);
+        assert_eq!(e.message, "expected a pattern after --filter");
+    }
+
+    #[test]
+    fn test_tree_run() {
+        // Not printing to a terminal, so the branches are ASCII
+        assert_eq!(
+            tree(&[]),
+            Ok(CommandResult::Value(
+                "search-engines\n|-- google\n`-- yahoo\nwork\n`-- jira".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_tree_run_values_and_filter() {
+        assert_eq!(
+            tree(&["--values", "--filter", "YAHOO|internal"]),
+            Ok(CommandResult::Value(
+                "search-engines\n`-- yahoo: https://yahoo.com\nwork\n`-- jira: https://jira.internal"
+                    .to_string()
+            ))
+        );
+        assert_eq!(
+            tree(&["--filter", "^search"]),
+            Ok(CommandResult::Value(
+                "search-engines\n|-- google\n`-- yahoo".to_string()
+            ))
+        );
+        let e = tree(&["--filter", "bing"]).unwrap_err();
+        assert_eq!(e.kind, CommandErrorKind::Generic);
+        assert_eq!(e.message, "no matches for bing");
+    }
+
+    #[test]
+    fn test_tree_run_invalid_filter() {
+        let e = tree(&["--filter", "(work"]).unwrap_err();
+        assert!(e.message.starts_with("Invalid pattern, unclosed group:"));
+    }
+
+    #[test]
+    fn test_draw_nested_parents() {
+        let link = |name: &str| vec![(name.to_string(), format!("https://{name}.internal"))];
+        let parents = vec![
+            ("work".to_string(), link("ci")),
+            ("work/infra/grafana".to_string(), link("dashboards")),
+            ("work/tools".to_string(), link("wiki")),
+        ];
+        assert_eq!(
+            draw(&parents, &ASCII, false, None),
+            "work\n|-- infra\n|   `-- grafana\n|       `-- dashboards\n|-- tools\n|   `-- wiki\n`-- ci"
+        );
+    }
+
+    #[test]
+    fn test_draw_unicode_and_width() {
+        let parents = vec![(
+            "work".to_string(),
+            vec![
+                ("ci".to_string(), "https://ci.internal".to_string()),
+                (
+                    "jira".to_string(),
+                    "https://jira.internal/ENG-42".to_string(),
+                ),
+            ],
+        )];
+        assert_eq!(
+            draw(&parents, &UNICODE, true, Some(24)),
+            "work\n├── ci: https://ci.in...\n└── jira: https://jir..."
+        );
+        assert_eq!(
+            draw(&parents, &UNICODE, false, None),
+            "work\n├── ci\n└── jira"
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{
-    commands::{Command, CommandResult},
+    commands::{Command, CommandError, CommandResult, display_examples},
     utils::cli_usage_table::DisplayCommandAsRow,
+    utils::context::Context,
 };
 
 pub(crate) struct Tui {
@@ -27,21 +28,21 @@ impl Command for Tui {
     fn help_message(&self) -> String {
         let mut s = String::new();
         s.push_str("Opens a terminal user interface to facilitate adding, updating, and deleting links.\n\n");
-        s.push_str("Example Usage: tap --tui");
+        s.push_str(&display_examples(&self.examples()));
         s
     }
 
-    fn run(&self, args: Vec<String>) -> Result<CommandResult, String> {
+    fn run(&self, _ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
         match args.len() {
             0 => todo!("Implement TUI Functionality"),
             1 => {
                 if args[0] == "--help" {
                     Ok(CommandResult::Value(self.help_message()))
                 } else {
-                    Err(self.error_message())
+                    Err(self.usage_error())
                 }
             }
-            _ => Err(self.error_message()),
+            _ => Err(self.usage_error()),
         }
     }
 }
@@ -55,6 +56,13 @@ impl DisplayCommandAsRow for Tui {
         self.description.clone()
     }
 
+    fn examples(&self) -> Vec<(String, String)> {
+        [("tap --tui", "Open the terminal user interface")]
+            .into_iter()
+            .map(|(line, description)| (line.to_string(), description.to_string()))
+            .collect()
+    }
+
     fn name(&self) -> String {
         self.name.clone()
     }
@@ -69,15 +77,16 @@ mod tests {
     fn test_tui_run_expected_args() {
         let args: Vec<String> = vec![];
         let cmd = Tui::default();
-        let res = cmd.run(args);
+        let _ = cmd.run(&mut Context::default(), args);
     }
 
     #[test]
     fn test_tui_run_expected_help_arg() {
         let args: Vec<String> = vec!["--help".to_string()];
         let cmd = Tui::default();
-        let expected: Result<CommandResult, String> = Ok(CommandResult::Value(cmd.help_message()));
-        let res = cmd.run(args);
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        let res = cmd.run(&mut Context::default(), args);
         assert_eq!(res, expected);
     }
 
@@ -85,8 +94,8 @@ mod tests {
     fn test_tui_run_unexpected_args() {
         let args: Vec<String> = vec!["random".to_string()];
         let cmd = Tui::default();
-        let expected: Result<CommandResult, String> = Err(cmd.error_message());
-        let res = cmd.run(args);
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        let res = cmd.run(&mut Context::default(), args);
         assert_eq!(res, expected);
     }
 }
@@ -0,0 +1,123 @@
+use crate::{
+    commands::{Command, CommandError, CommandErrorKind, CommandResult, display_examples},
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::command::Args,
+    utils::context::Context,
+    utils::style,
+};
+
+pub(crate) struct Unalias {
+    name: String,
+    description: String,
+    args: [String; 1],
+}
+
+impl Default for Unalias {
+    fn default() -> Self {
+        Self {
+            name: "--unalias".to_string(),
+            description: "Remove an alias".to_string(),
+            args: ["<Alias>".to_string()],
+        }
+    }
+}
+
+impl Command for Unalias {
+    fn error_message(&self) -> String {
+        "expected 1 argument, see the Usage section with tap --unalias --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --unalias command removes an alias given with tap --alias. The Parent Entity it named and its links are left as they are.\n\n");
+        s.push_str("Command Structure: tap --unalias <Alias>\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let args = Args::new(args);
+        match args.len() {
+            1 if args.is_flag(0, "--help") => Ok(CommandResult::Value(self.help_message())),
+            1 => {
+                let alias = args[0].trim();
+                match ctx.store.write(|ds| ds.remove_alias(alias))? {
+                    Some(parent) => Ok(CommandResult::Value(style::success(&format!(
+                        "Removed alias '{alias}' of parent '{parent}'"
+                    )))),
+                    None => Err(CommandError {
+                        kind: CommandErrorKind::ParentNotFound,
+                        message: format!("Alias '{alias}' not found"),
+                    }
+                    .with_hint("List the aliases with tap --alias")),
+                }
+            }
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+impl DisplayCommandAsRow for Unalias {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [("tap --unalias k8s", "Remove the alias k8s")]
+            .into_iter()
+            .map(|(line, description)| (line.to_string(), description.to_string()))
+            .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unalias_run_expected_help_arg() {
+        let cmd = Unalias::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_unalias_run_unexpected_args() {
+        let cmd = Unalias::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(cmd.run(&mut Context::default(), vec![]), expected);
+    }
+
+    #[test]
+    fn test_unalias_removes_alias() {
+        let mut ctx = Context::in_memory(vec![(
+            "work".to_string(),
+            vec![("ci".to_string(), "https://ci.internal".to_string())],
+        )]);
+        ctx.store.write(|ds| ds.set_alias("w", "work")).unwrap();
+        assert_eq!(
+            Unalias::default().run(&mut ctx, vec!["w".to_string()]),
+            Ok(CommandResult::Value(style::success(
+                "Removed alias 'w' of parent 'work'"
+            )))
+        );
+        let e = Unalias::default()
+            .run(&mut ctx, vec!["w".to_string()])
+            .unwrap_err();
+        assert_eq!(e.kind, CommandErrorKind::ParentNotFound);
+        // The parent itself is untouched
+        assert!(ctx.store.read_parent("work").is_ok());
+    }
+}
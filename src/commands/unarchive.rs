@@ -0,0 +1,120 @@
+use crate::{
+    commands::{Command, CommandError, CommandErrorKind, CommandResult, display_examples},
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::command::Args,
+    utils::context::Context,
+    utils::style,
+};
+
+pub(crate) struct Unarchive {
+    name: String,
+    description: String,
+    args: [String; 1],
+}
+
+impl Default for Unarchive {
+    fn default() -> Self {
+        Self {
+            name: "--unarchive".to_string(),
+            description: "Bring back an archived Parent".to_string(),
+            args: ["<Parent>".to_string()],
+        }
+    }
+}
+
+impl Command for Unarchive {
+    fn error_message(&self) -> String {
+        "expected 1 argument, see the Usage section with tap --unarchive --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --unarchive command moves a Parent Entity archived with tap --archive back to the data file, with its links, pins, and notes. A Parent Entity of the same name that was added since is never merged into, rename or delete it first.\n\n");
+        s.push_str("Command Structure: tap --unarchive <Parent Entity>\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let args = Args::new(args);
+        match args.len() {
+            1 if args.is_flag(0, "--help") => Ok(CommandResult::Value(self.help_message())),
+            1 => {
+                let parent_entity = args[0].trim();
+                let links = ctx
+                    .store
+                    .write(|ds| ds.unarchive(parent_entity))
+                    .map_err(|e| match CommandError::from(e) {
+                        e if e.kind == CommandErrorKind::ParentNotFound => e.with_hint(
+                            "List the archived parent entities with tap --show --archived",
+                        ),
+                        e => e,
+                    })?;
+                Ok(CommandResult::Value(style::success(&format!(
+                    "Brought back parent '{parent_entity}' with {links} link(s) from the archive"
+                ))))
+            }
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+impl DisplayCommandAsRow for Unarchive {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [(
+            "tap --unarchive old-project",
+            "Move old-project and its links back from the archive",
+        )]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unarchive_run_expected_help_arg() {
+        let cmd = Unarchive::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_unarchive_run_unexpected_args() {
+        let cmd = Unarchive::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(cmd.run(&mut Context::default(), vec![]), expected);
+    }
+
+    #[test]
+    fn test_unarchive_not_archived() {
+        let mut ctx = Context::in_memory(vec![]);
+        let e = Unarchive::default()
+            .run(&mut ctx, vec!["old-project".to_string()])
+            .unwrap_err();
+        assert_eq!(e.kind, CommandErrorKind::ParentNotFound);
+        assert!(
+            e.message
+                .starts_with("Parent 'old-project' is not archived")
+        );
+    }
+}
@@ -0,0 +1,359 @@
+use crate::{
+    commands::{Command, CommandError, CommandResult, display_examples},
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::context::Context,
+    utils::style,
+    utils::tap_data_store::check_data_file,
+};
+use std::collections::BTreeMap;
+
+/// The changes listed after undoing, the rest are counted
+const MAX_CHANGES_SHOWN: usize = 10;
+
+pub(crate) struct Undo {
+    name: String,
+    description: String,
+    args: [String; 0],
+}
+
+impl Default for Undo {
+    fn default() -> Self {
+        Self {
+            name: "--undo".to_string(),
+            description: "Revert the last change to the data file".to_string(),
+            args: [],
+        }
+    }
+}
+
+impl Command for Undo {
+    fn error_message(&self) -> String {
+        "expected 0 arguments, see the Usage section with tap --undo --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --undo command puts the data file back the way it was before the last change and rebuilds the index. Every command that changes the data file (add, upsert, delete, import, compact, edit, restore, ...) first saves a snapshot of it next to the data file, replacing the previous one, so only the last change can be undone.\n\n");
+        s.push_str("Undoing removes the snapshot, running tap --undo again reports that there is nothing further to undo. Undoing an undo is not supported. Deleted links that undoing brings back are taken out of the trash, see tap --trash.\n\n");
+        s.push_str("Command Structure: tap --undo\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        match args.len() {
+            0 => {
+                let Some(undone) = ctx.store.undo()? else {
+                    return Ok(CommandResult::Value(
+                        "Nothing to undo, only the last change can be undone".to_string(),
+                    ));
+                };
+                let mut s = style::success(&match undone.operation {
+                    Some(operation) => format!("Undid {operation}"),
+                    None => "Undid the last change".to_string(),
+                });
+                // The replaced file may have been edited by hand into something that doesn't parse
+                if let (Ok(restored), Ok(replaced)) = (
+                    check_data_file(&undone.restored),
+                    check_data_file(&undone.replaced),
+                ) {
+                    let changes = describe_changes(restored, replaced);
+                    for change in changes.iter().take(MAX_CHANGES_SHOWN) {
+                        s.push_str(&format!("\n  {change}"));
+                    }
+                    if changes.len() > MAX_CHANGES_SHOWN {
+                        s.push_str(&format!(
+                            "\n  and {} more change(s)",
+                            changes.len() - MAX_CHANGES_SHOWN
+                        ));
+                    }
+                }
+                Ok(CommandResult::Value(s))
+            }
+            1 if args[0] == "--help" => Ok(CommandResult::Value(self.help_message())),
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+/// What undoing changed, going from the `replaced` parent entities and links to the `restored` ones
+fn describe_changes(
+    restored: Vec<(String, Vec<(String, String)>)>,
+    replaced: Vec<(String, Vec<(String, String)>)>,
+) -> Vec<String> {
+    let links = |state: Vec<(String, Vec<(String, String)>)>| {
+        state
+            .into_iter()
+            .map(|(parent, links)| (parent, links.into_iter().collect::<BTreeMap<_, _>>()))
+            .collect::<BTreeMap<_, _>>()
+    };
+    let (restored, mut replaced) = (links(restored), links(replaced));
+    let mut changes = vec![];
+    for (parent, links) in restored {
+        let Some(before) = replaced.remove(&parent) else {
+            changes.push(format!(
+                "restored parent '{parent}' with {} link(s)",
+                links.len()
+            ));
+            continue;
+        };
+        for (link, value) in &links {
+            match before.get(link) {
+                None => changes.push(format!("restored link '{link}' in '{parent}'")),
+                Some(old) if old != value => changes.push(format!(
+                    "changed link '{link}' in '{parent}' back to {value}"
+                )),
+                Some(_) => {}
+            }
+        }
+        for link in before.keys().filter(|link| !links.contains_key(*link)) {
+            changes.push(format!("removed link '{link}' from '{parent}'"));
+        }
+    }
+    for (parent, links) in replaced {
+        changes.push(format!(
+            "removed parent '{parent}' with {} link(s)",
+            links.len()
+        ));
+    }
+    changes
+}
+
+impl DisplayCommandAsRow for Undo {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [("tap --undo", "Bring back what the last command changed")]
+            .into_iter()
+            .map(|(line, description)| (line.to_string(), description.to_string()))
+            .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::{compact::Compact, delete::Delete, restore_trash::RestoreTrash};
+    use crate::utils::tap_data_store::{TestDataFile, index_path_for, undo_path_for};
+    use std::path::Path;
+
+    const LINKS: &str = "news->\n  bbc|https://bbc.com\nwork->\n  ci|https://ci.internal\n  mr|https://mr.internal\n";
+
+    fn undo(data: &Path) -> String {
+        match Undo::default().run(&mut Context::with_file(data.to_path_buf()), vec![]) {
+            Ok(CommandResult::Value(s)) => s,
+            res => panic!("expected a value, got {res:?}"),
+        }
+    }
+
+    #[test]
+    fn test_undo_run_expected_help_arg() {
+        let cmd = Undo::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_undo_run_unexpected_args() {
+        let cmd = Undo::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["work".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_undo_delete_parent() {
+        let data = TestDataFile::new(LINKS);
+        Delete::default()
+            .run(
+                &mut Context::with_file(data.clone()),
+                vec!["work".to_string(), "--yes".to_string()],
+            )
+            .unwrap();
+        assert!(!std::fs::read_to_string(&data).unwrap().contains("work->"));
+        assert_eq!(
+            undo(&data),
+            format!(
+                "{}\n  restored parent 'work' with 2 link(s)",
+                style::success("Undid the last change")
+            )
+        );
+        assert_eq!(std::fs::read_to_string(&data).unwrap(), LINKS);
+        assert!(!undo_path_for(&data).exists());
+        // Nothing further to undo, instead of flipping back to the deleted state
+        assert_eq!(
+            undo(&data),
+            "Nothing to undo, only the last change can be undone"
+        );
+        assert_eq!(std::fs::read_to_string(&data).unwrap(), LINKS);
+    }
+
+    #[test]
+    fn test_undo_delete_takes_links_out_of_the_trash() {
+        let data = TestDataFile::new(LINKS);
+        let ctx = Context::with_file(data.clone());
+        Delete::default()
+            .run(
+                &mut Context::with_file(data.clone()),
+                vec!["news".to_string(), "--yes".to_string()],
+            )
+            .unwrap();
+        Delete::default()
+            .run(
+                &mut Context::with_file(data.clone()),
+                vec!["work".to_string(), "ci".to_string(), "--yes".to_string()],
+            )
+            .unwrap();
+        assert_eq!(ctx.store.trashed().unwrap().len(), 2);
+        undo(&data);
+        assert!(std::fs::read_to_string(&data).unwrap().contains("ci|"));
+        // Only the link put back leaves the trash
+        let trashed = ctx.store.trashed().unwrap();
+        assert_eq!(trashed.len(), 1);
+        assert_eq!(trashed[0].parent, "news");
+    }
+
+    #[test]
+    fn test_undo_restore_trash_puts_links_back_in_the_trash() {
+        let data = TestDataFile::new(LINKS);
+        Delete::default()
+            .run(
+                &mut Context::with_file(data.clone()),
+                vec!["work".to_string(), "--yes".to_string()],
+            )
+            .unwrap();
+        RestoreTrash::default()
+            .run(
+                &mut Context::with_file(data.clone()),
+                vec!["work".to_string()],
+            )
+            .unwrap();
+        assert!(
+            Context::with_file(data.clone())
+                .store
+                .trashed()
+                .unwrap()
+                .is_empty()
+        );
+        undo(&data);
+        assert!(!std::fs::read_to_string(&data).unwrap().contains("work->"));
+        let trashed = Context::with_file(data.clone()).store.trashed().unwrap();
+        let mut links: Vec<&str> = trashed.iter().map(|e| e.link.as_str()).collect();
+        links.sort();
+        assert_eq!(links, ["ci", "mr"]);
+        // The links can be restored again
+        RestoreTrash::default()
+            .run(
+                &mut Context::with_file(data.clone()),
+                vec!["work".to_string()],
+            )
+            .unwrap();
+        assert!(std::fs::read_to_string(&data).unwrap().contains("ci|"));
+    }
+
+    #[test]
+    fn test_undo_compact() {
+        let messy = "work->\n  mr|https://mr.internal\n  ci|https://ci.internal\nwork->\n  ci|https://ci.internal\nempty->\n";
+        let data = TestDataFile::new(messy);
+        Compact::default()
+            .run(&mut Context::with_file(data.clone()), vec![])
+            .unwrap();
+        assert_ne!(std::fs::read_to_string(&data).unwrap(), messy);
+        undo(&data);
+        // Put back as it was, not in the form saving the data store would write it in
+        assert_eq!(std::fs::read_to_string(&data).unwrap(), messy);
+        assert_eq!(
+            std::fs::read_to_string(index_path_for(&data)).unwrap(),
+            "work|0\n"
+        );
+    }
+
+    #[test]
+    fn test_undo_records_only_changes() {
+        let data = TestDataFile::new(LINKS);
+        let ctx = Context::with_file(data.clone());
+        ctx.store.parents().unwrap();
+        ctx.store
+            .write(|ds| ds.read_link_slow("work", "ci"))
+            .unwrap();
+        assert!(!undo_path_for(&data).exists());
+        ctx.store
+            .write(|ds| {
+                ds.upsert_link(
+                    "work".to_string(),
+                    "ci".to_string(),
+                    "https://ci.example.com".to_string(),
+                )
+            })
+            .unwrap();
+        ctx.store
+            .write(|ds| {
+                ds.add_link(
+                    "work".to_string(),
+                    "docs".to_string(),
+                    "https://docs.internal".to_string(),
+                )
+            })
+            .unwrap();
+        // Only the last change is undone
+        assert_eq!(
+            undo(&data),
+            format!(
+                "{}\n  removed link 'docs' from 'work'",
+                style::success("Undid the last change")
+            )
+        );
+        assert!(
+            std::fs::read_to_string(&data)
+                .unwrap()
+                .contains("ci|https://ci.example.com")
+        );
+    }
+
+    #[test]
+    fn test_describe_changes() {
+        let state = |links: &[(&str, &str, &str)]| {
+            let mut state: Vec<(String, Vec<(String, String)>)> = vec![];
+            for (parent, link, value) in links {
+                let entry = (link.to_string(), value.to_string());
+                match state.iter_mut().find(|(p, _)| p == parent) {
+                    Some((_, links)) => links.push(entry),
+                    None => state.push((parent.to_string(), vec![entry])),
+                }
+            }
+            state
+        };
+        assert_eq!(
+            describe_changes(
+                state(&[("work", "ci", "https://ci"), ("work", "mr", "https://mr")]),
+                state(&[
+                    ("work", "ci", "https://new-ci"),
+                    ("work", "wiki", "https://wiki"),
+                    ("news", "bbc", "https://bbc.com")
+                ]),
+            ),
+            [
+                "changed link 'ci' in 'work' back to https://ci",
+                "restored link 'mr' in 'work'",
+                "removed link 'wiki' from 'work'",
+                "removed parent 'news' with 1 link(s)",
+            ]
+        );
+    }
+}
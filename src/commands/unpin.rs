@@ -0,0 +1,95 @@
+use crate::{
+    commands::{Command, CommandError, CommandResult, display_examples, pin::set_pinned},
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::command::Args,
+    utils::context::Context,
+};
+
+pub(crate) struct Unpin {
+    name: String,
+    description: String,
+    args: [String; 2],
+}
+
+impl Default for Unpin {
+    fn default() -> Self {
+        Self {
+            name: "--unpin".to_string(),
+            description: "Unpin a link".to_string(),
+            args: ["<Parent|here>".to_string(), "<Link>".to_string()],
+        }
+    }
+}
+
+impl Command for Unpin {
+    fn error_message(&self) -> String {
+        "expected 2 arguments, see the Usage section with tap --unpin --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --unpin command removes the pin tap --pin put on a link, so it is listed in order with the other links again.\n\n");
+        s.push_str("Command Structure: tap --unpin <Parent Entity | here> <Link Name>\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let args = Args::new(args);
+        match args.len() {
+            1 if args.is_flag(0, "--help") => Ok(CommandResult::Value(self.help_message())),
+            2 => set_pinned(ctx, &args.parent(0)?, &args[1], false),
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+impl DisplayCommandAsRow for Unpin {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [("tap --unpin work ci", "Unpin the ci link of work")]
+            .into_iter()
+            .map(|(line, description)| (line.to_string(), description.to_string()))
+            .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unpin_run_expected_help_arg() {
+        let cmd = Unpin::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_unpin_run_unexpected_args() {
+        let cmd = Unpin::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(
+            cmd.run(
+                &mut Context::default(),
+                vec!["work".to_string(), "ci".to_string(), "mr".to_string()]
+            ),
+            expected
+        );
+    }
+}
@@ -1,14 +1,19 @@
 use crate::{
-    commands::{Command, CommandResult},
+    commands::{
+        Command, CommandError, CommandErrorKind, CommandResult, check_duplicates, display_examples,
+    },
     utils::cli_usage_table::DisplayCommandAsRow,
-    utils::command::get_current_directory_name,
-    utils::tap_data_store::DataStore,
+    utils::command::{Args, link_value_pairs, parent_arg, resolve_value},
+    utils::context::Context,
+    utils::style,
+    utils::tap_data_store::{parse_snippet, validate_link, validate_parent},
 };
+use std::io::Read;
 
 pub(crate) struct Upsert {
     name: String,
     description: String,
-    args: [String; 3],
+    args: [String; 6],
 }
 
 impl Default for Upsert {
@@ -19,7 +24,10 @@ impl Default for Upsert {
             args: [
                 "<Parent|here>".to_string(),
                 "<Link>".to_string(),
-                "<Value>".to_string(),
+                "<Value|->".to_string(),
+                "[--no-duplicates]".to_string(),
+                "[--force]".to_string(),
+                "[--from <File|->]".to_string(),
             ],
         }
     }
@@ -27,58 +35,213 @@ impl Default for Upsert {
 
 impl Command for Upsert {
     fn error_message(&self) -> String {
-        "expected 3 arguments, see the Usage section with tap --upsert --help".to_string()
+        "expected a parent entity followed by one or more link and value pairs, see the Usage section with tap --upsert --help".to_string()
     }
 
     fn help_message(&self) -> String {
         let mut s = String::new();
         s.push_str("Tap --upsert command will create/update a Link in the Parent Entity\n\n");
-        s.push_str("Command Structure: tap --upsert <Parent Entity | here> <Link Name> <Value>\n");
-        s.push_str("Example Usage: \n\n");
-        s.push_str("  - Create/Update a link in search-engines Parent Entity: tap --upsert search-engines google https://google.com\n");
-        s.push_str("  - Create/Update a link in Parent Entity sharing name of current directory: tap --upsert here google https://google.com\n");
+        s.push_str("Command Structure: tap --upsert <Parent Entity | here> <Link Name> <Value> [<Link Name> <Value>...]\n");
+        s.push_str("Several links can be given at once, if one of them can not be upserted none of them are\n");
+        s.push_str(
+            "Missing arguments are asked for when run in a terminal, unless --no-input is passed\n",
+        );
+        s.push_str("Pass - as the Value to read it from stdin, the value must be a single line\n");
+        s.push_str("A link and its value can also be passed as one Link=Value argument, split on the first =. Write \\= for an = in the link name\n");
+        s.push_str("A value that is already stored under another link, ignoring surrounding spaces and trailing slashes, is reported with a note. Pass --no-duplicates, or set no_duplicates = true in the config file, to make that an error, and --force to skip the check\n");
+        s.push_str("Pass --from with a file, or - for stdin, instead of the arguments to upsert every link in it. Links are written like in the data file, parent-> lines each followed by indented link|value lines, or as one parent|link|value per line. The whole file is checked first and saved at once, nothing is saved if a line is invalid\n");
+        s.push_str(&display_examples(&self.examples()));
         s
     }
 
-    fn run(&self, args: Vec<String>) -> Result<CommandResult, String> {
-        match args.len() {
-            1 => {
-                if args[0] == "--help" {
-                    Ok(CommandResult::Value(self.help_message()))
-                } else {
-                    Err(self.error_message())
-                }
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let mut args = Args::new(args);
+        let force = args.take_flag(&["--force"]);
+        let no_duplicates = args.take_flag(&["--no-duplicates"]);
+        if args.len() == 1 && args.is_flag(0, "--help") {
+            return Ok(CommandResult::Value(self.help_message()));
+        }
+        if let Some(from) = args.take_option("--from") {
+            if !args.is_empty() {
+                return Err(self.usage_error());
             }
-            3 => match (args[0].as_str(), args[1].as_str(), args[2].as_str()) {
-                ("here", link_name, value) => {
-                    let mut ds = DataStore::new(None).map_err(|e| e.to_string())?;
-                    let current_dir_name =
-                        get_current_directory_name().map_err(|e| e.to_string())?;
-                    ds.upsert_link(
-                        current_dir_name.to_string(),
-                        link_name.to_string(),
-                        value.to_string(),
-                    )
-                    .map_err(|e| e.to_string())?;
-                    Ok(CommandResult::Value(format!(
-                        "Successfully upserted {link_name} with value {value} to parent entity {current_dir_name}"
-                    )))
+            return self.upsert_from(ctx, &from, force, no_duplicates);
+        }
+        let pairs = match args.len() {
+            0 => None,
+            _ => link_value_pairs(&args[1..])?,
+        };
+        match pairs {
+            Some(mut pairs) if !pairs.is_empty() => {
+                let parent_entity = args.parent(0)?;
+                for (_, value) in pairs.iter_mut() {
+                    *value = resolve_value(value, &ctx.input)?;
+                }
+                if !force {
+                    check_duplicates(&ctx.store, &parent_entity, &pairs, no_duplicates)?;
                 }
-                (parent_entity, link_name, value) => {
-                    let mut ds = DataStore::new(None).map_err(|e| e.to_string())?;
-                    ds.upsert_link(
-                        parent_entity.to_string(),
-                        link_name.to_string(),
-                        value.to_string(),
-                    )
-                    .map_err(|e| e.to_string())?;
-                    Ok(CommandResult::Value(format!(
+                // Every pair is saved at once, or none at all if one of them fails
+                ctx.store
+                    .write(|ds| {
+                        ds.batch(|tx| {
+                            for (link_name, value) in pairs.iter() {
+                                tx.upsert_link(&parent_entity, link_name, value)?;
+                            }
+                            Ok(())
+                        })
+                    })
+                    .map_err(|e| match pairs.len() {
+                        1 => CommandError::from(e),
+                        _ => CommandError::from(e).with_hint("No links were upserted"),
+                    })?;
+                match pairs.as_slice() {
+                    [(link_name, value)] => Ok(CommandResult::Value(style::success(&format!(
                         "Successfully upserted {link_name} with value {value} to parent entity {parent_entity}"
-                    )))
+                    )))),
+                    pairs => Ok(CommandResult::Value(style::success(&format!(
+                        "Successfully upserted {} links to parent entity {parent_entity}: {}",
+                        pairs.len(),
+                        pairs
+                            .iter()
+                            .map(|(link_name, _)| link_name.as_str())
+                            .collect::<Vec<&str>>()
+                            .join(", ")
+                    )))),
+                }
+            }
+            _ if args.len() <= 2 && ctx.prompt.is_interactive() => {
+                match self.ask_missing_args(ctx, args)? {
+                    Some(args) => {
+                        let mut args = args.into_vec();
+                        // Put back in front, so they are never taken for arguments after `--`
+                        if force {
+                            args.insert(0, "--force".to_string());
+                        }
+                        if no_duplicates {
+                            args.insert(0, "--no-duplicates".to_string());
+                        }
+                        self.run(ctx, args)
+                    }
+                    None => Ok(CommandResult::Value("Nothing was upserted".to_string())),
+                }
+            }
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+impl Upsert {
+    /// Upserts every link of the file (or stdin when `from` is `-`) in one batch, see
+    /// `parse_snippet`, and counts the links created, updated, and left as they were
+    fn upsert_from(
+        &self,
+        ctx: &Context,
+        from: &str,
+        force: bool,
+        no_duplicates: bool,
+    ) -> Result<CommandResult, CommandError> {
+        let (text, source) = match from {
+            "-" => {
+                let mut text = String::new();
+                ctx.input
+                    .borrow_mut()
+                    .read_to_string(&mut text)
+                    .map_err(|e| format!("Could not read links from stdin: {e}"))?;
+                (text, "stdin".to_string())
+            }
+            path => (
+                std::fs::read_to_string(path)
+                    .map_err(|e| format!("Could not read links from {path}: {e}"))?,
+                path.to_string(),
+            ),
+        };
+        let links = parse_snippet(&text).map_err(|rejected| {
+            let mut message = format!(
+                "Found {} invalid line(s) in {source}, nothing was upserted:",
+                rejected.len()
+            );
+            for r in rejected.iter() {
+                message.push_str(&format!(
+                    "\n  line {}: {} ({})",
+                    r.line_number, r.line, r.reason
+                ));
+            }
+            CommandError {
+                kind: CommandErrorKind::InvalidData,
+                message,
+            }
+        })?;
+        if links.is_empty() {
+            return Err(format!("No links found in {source}").into());
+        }
+        if !force {
+            let mut parents: Vec<&str> = vec![];
+            for (parent, _, _) in links.iter() {
+                if !parents.contains(&parent.as_str()) {
+                    parents.push(parent);
                 }
-            },
-            _ => Err(self.error_message()),
+            }
+            for parent in parents {
+                let pairs: Vec<(String, String)> = links
+                    .iter()
+                    .filter(|(p, _, _)| p == parent)
+                    .map(|(_, l, v)| (l.clone(), v.clone()))
+                    .collect();
+                check_duplicates(&ctx.store, parent, &pairs, no_duplicates)?;
+            }
+        }
+        let (mut created, mut updated) = (0, 0);
+        for (parent, link, value) in links.iter() {
+            match ctx.store.read_link(parent, link) {
+                Ok((_, old)) if old == *value => {}
+                Ok(_) => updated += 1,
+                Err(_) => created += 1,
+            }
+        }
+        ctx.store
+            .write(|ds| {
+                ds.batch(|tx| {
+                    links
+                        .iter()
+                        .try_for_each(|(parent, link, value)| tx.upsert_link(parent, link, value))
+                })
+            })
+            .map_err(|e| CommandError::from(e).with_hint("No links were upserted"))?;
+        Ok(CommandResult::Value(style::success(&format!(
+            "Upserted {} links from {source}: {created} created, {updated} updated, {} unchanged",
+            links.len(),
+            links.len() - created - updated
+        ))))
+    }
+
+    /// Asks for the parent entity, link name, and value that were not passed, then confirms
+    /// before saving. Returns `None` if the user does not confirm.
+    fn ask_missing_args(
+        &self,
+        ctx: &Context,
+        mut args: Args,
+    ) -> Result<Option<Args>, CommandError> {
+        if args.is_empty() {
+            let literal = args.is_literal(0);
+            let parent_entity = ctx.prompt.ask("Parent entity", |parent_entity| {
+                parent_arg(parent_entity, literal).map_err(|e| e.to_string())?;
+                validate_parent(parent_entity).map_err(|e| e.to_string())
+            })?;
+            args.push(parent_entity);
         }
+        let parent_entity = args.parent(0)?;
+        if args.len() == 1 {
+            args.push(ctx.prompt.ask("Link name", |link_name| {
+                validate_link(link_name).map_err(|e| e.to_string())?;
+                Ok(())
+            })?);
+        }
+        args.push(ctx.prompt.ask("Value", |_| Ok(()))?);
+        let confirmed = ctx.prompt.confirm(&format!(
+            "Upsert link {} with value {} to parent entity {parent_entity}?",
+            args[1], args[2]
+        ))?;
+        Ok(confirmed.then_some(args))
     }
 }
 
@@ -91,6 +254,38 @@ impl DisplayCommandAsRow for Upsert {
         self.description.clone()
     }
 
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            (
+                "tap --upsert search-engines google https://google.com",
+                "Create/Update a link in search-engines Parent Entity",
+            ),
+            (
+                "tap --upsert here google https://google.com",
+                "Create/Update a link in Parent Entity sharing name of current directory",
+            ),
+            (
+                "tap --upsert proj repo https://github.com/me/proj ci https://ci.me/proj",
+                "Create/Update several links at once",
+            ),
+            (
+                "tap --upsert work jira=https://jira.internal/ABC ci=https://ci.internal",
+                "Create/Update links written as Link=Value",
+            ),
+            (
+                "pbpaste | tap --upsert work spec -",
+                "Create/Update a link with the value read from stdin",
+            ),
+            (
+                "tap --upsert --from onboarding.tap",
+                "Create/Update every link written in a file",
+            ),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
     fn name(&self) -> String {
         self.name.clone()
     }
@@ -99,13 +294,17 @@ impl DisplayCommandAsRow for Upsert {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::prompt::Prompt;
+    use crate::utils::tap_data_store::DataStoreHandle;
+    use std::cell::RefCell;
 
     #[test]
     fn test_upsert_run_expected_help_arg() {
         let args: Vec<String> = vec!["--help".to_string()];
         let cmd = Upsert::default();
-        let expected: Result<CommandResult, String> = Ok(CommandResult::Value(cmd.help_message()));
-        let res = cmd.run(args);
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        let res = cmd.run(&mut Context::default(), args);
         assert_eq!(res, expected);
     }
 
@@ -113,11 +312,33 @@ mod tests {
     fn test_upsert_run_unexpected_args() {
         let args: Vec<String> = vec!["random".to_string()];
         let cmd = Upsert::default();
-        let expected: Result<CommandResult, String> = Err(cmd.error_message());
-        let res = cmd.run(args);
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        let res = cmd.run(&mut Context::default(), args);
         assert_eq!(res, expected);
     }
 
+    #[test]
+    fn test_upsert_run_duplicate_value() {
+        let cmd = Upsert::default();
+        let mut ctx = Context::in_memory(vec![(
+            "work".to_string(),
+            vec![("ci".to_string(), "https://ci.internal".to_string())],
+        )]);
+        let args = |link: &str| -> Vec<String> {
+            ["--no-duplicates", "work", link, "https://ci.internal/"]
+                .map(String::from)
+                .to_vec()
+        };
+        // Upserting a link with its own value is no duplicate
+        assert!(cmd.run(&mut ctx, args("ci")).is_ok());
+        let err = cmd.run(&mut ctx, args("build")).unwrap_err();
+        assert!(
+            err.message
+                .starts_with("https://ci.internal is already stored as work/ci")
+        );
+        assert_eq!(ctx.store.links("work").unwrap(), vec!["ci".to_string()]);
+    }
+
     #[test]
     fn test_upsert_run_expected_three_args_here() {
         let args: Vec<String> = vec![
@@ -128,10 +349,11 @@ mod tests {
         let current_dir = std::env::current_dir().unwrap();
         let current_dir_name = current_dir.file_name().unwrap().to_str().unwrap();
         let cmd = Upsert::default();
-        let expected: Result<CommandResult, String> = Ok(CommandResult::Value(format!(
+        let mut ctx = Context::in_memory(vec![]);
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Value(format!(
             "Successfully upserted google with value https://google.com to parent entity {current_dir_name}"
         )));
-        let res = cmd.run(args);
+        let res = cmd.run(&mut ctx, args);
         assert_eq!(res, expected);
     }
 
@@ -143,9 +365,244 @@ mod tests {
             "https://google.com".to_string(),
         ];
         let cmd = Upsert::default();
-        let expected: Result<CommandResult, String> =
+        let mut ctx = Context::in_memory(vec![]);
+        let expected: Result<CommandResult, CommandError> =
             Ok(CommandResult::Value("Successfully upserted google with value https://google.com to parent entity search-engines".to_string()));
-        let res = cmd.run(args);
+        let res = cmd.run(&mut ctx, args);
+        assert_eq!(res, expected);
+    }
+
+    #[test]
+    fn test_upsert_run_value_from_stdin() {
+        let args: Vec<String> = vec![
+            "search-engines".to_string(),
+            "google".to_string(),
+            "-".to_string(),
+        ];
+        let cmd = Upsert::default();
+        let mut ctx = Context {
+            store: DataStoreHandle::in_memory(vec![]),
+            input: RefCell::new(Box::new("https://google.com\n".as_bytes())),
+            ..Context::default()
+        };
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Value(
+            "Successfully upserted google with value https://google.com to parent entity search-engines"
+                .to_string(),
+        ));
+        let res = cmd.run(&mut ctx, args);
         assert_eq!(res, expected);
+        assert_eq!(
+            ctx.store.read_link("search-engines", "google").unwrap(),
+            ("google".to_string(), "https://google.com".to_string())
+        );
+    }
+
+    #[test]
+    fn test_upsert_run_value_from_stdin_multiple_lines() {
+        let args: Vec<String> = vec![
+            "search-engines".to_string(),
+            "google".to_string(),
+            "-".to_string(),
+        ];
+        let cmd = Upsert::default();
+        let mut ctx = Context {
+            store: DataStoreHandle::in_memory(vec![]),
+            input: RefCell::new(Box::new(
+                "https://google.com\nhttps://bing.com\n".as_bytes(),
+            )),
+            ..Context::default()
+        };
+        let res = cmd.run(&mut ctx, args);
+        assert!(res.unwrap_err().message.contains("single line"));
+        assert!(ctx.store.links("search-engines").is_err());
+    }
+
+    #[test]
+    fn test_upsert_run_several_pairs() {
+        let args: Vec<String> = vec![
+            "search-engines".to_string(),
+            "google".to_string(),
+            "https://google.com".to_string(),
+            "bing".to_string(),
+            "https://bing.com".to_string(),
+        ];
+        let cmd = Upsert::default();
+        let mut ctx = Context::in_memory(vec![]);
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Value(
+            "Successfully upserted 2 links to parent entity search-engines: google, bing"
+                .to_string(),
+        ));
+        let res = cmd.run(&mut ctx, args);
+        assert_eq!(res, expected);
+        assert_eq!(
+            ctx.store.links("search-engines").unwrap(),
+            vec!["bing".to_string(), "google".to_string()]
+        );
+        assert_eq!(ctx.store.saves(), 1);
+    }
+
+    #[test]
+    fn test_upsert_run_pair_missing_value() {
+        let args: Vec<String> = vec![
+            "search-engines".to_string(),
+            "google".to_string(),
+            "https://google.com".to_string(),
+            "bing".to_string(),
+        ];
+        let cmd = Upsert::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        let res = cmd.run(&mut Context::default(), args);
+        assert_eq!(res, expected);
+    }
+
+    #[test]
+    fn test_upsert_run_link_equals_value() {
+        let args: Vec<String> = vec![
+            "work".to_string(),
+            "jira=https://jira.internal/ABC?a=b".to_string(),
+            "ci".to_string(),
+            "https://ci.me".to_string(),
+            "a\\=b=https://ab.com".to_string(),
+        ];
+        let cmd = Upsert::default();
+        let mut ctx = Context::in_memory(vec![]);
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Value(
+            "Successfully upserted 3 links to parent entity work: jira, ci, a=b".to_string(),
+        ));
+        assert_eq!(cmd.run(&mut ctx, args), expected);
+        assert_eq!(
+            ctx.store.read_link("work", "jira").unwrap().1,
+            "https://jira.internal/ABC?a=b"
+        );
+    }
+
+    #[test]
+    fn test_upsert_run_link_equals_empty_value() {
+        let args: Vec<String> = vec!["work".to_string(), "jira=".to_string()];
+        let cmd = Upsert::default();
+        let mut ctx = Context::in_memory(vec![]);
+        let err = cmd.run(&mut ctx, args).unwrap_err();
+        assert!(err.message.starts_with("Link jira has no value after '='"));
+        assert_eq!(ctx.store.saves(), 0);
+    }
+
+    #[test]
+    fn test_upsert_run_several_pairs_invalid_pair_adds_nothing() {
+        let args: Vec<String> = vec![
+            "search-engines".to_string(),
+            "google".to_string(),
+            "https://google.com".to_string(),
+            "bing|search".to_string(),
+            "https://bing.com".to_string(),
+        ];
+        let cmd = Upsert::default();
+        let mut ctx = Context::in_memory(vec![]);
+        let err = cmd.run(&mut ctx, args).unwrap_err();
+        assert!(err.message.ends_with("No links were upserted"));
+        assert!(ctx.store.links("search-engines").is_err());
+        assert_eq!(ctx.store.saves(), 0);
+    }
+
+    #[test]
+    fn test_upsert_run_prompts_for_missing_args() {
+        let args: Vec<String> = vec!["search-engines".to_string()];
+        let cmd = Upsert::default();
+        let mut ctx = Context {
+            store: DataStoreHandle::in_memory(vec![]),
+            prompt: Prompt::scripted("google\nhttps://google.com\ny\n"),
+            ..Context::default()
+        };
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Value(
+            "Successfully upserted google with value https://google.com to parent entity search-engines"
+                .to_string(),
+        ));
+        assert_eq!(cmd.run(&mut ctx, args), expected);
+        assert_eq!(
+            ctx.store.read_link("search-engines", "google").unwrap(),
+            ("google".to_string(), "https://google.com".to_string())
+        );
+    }
+
+    #[test]
+    fn test_upsert_run_prompts_for_parent_entity() {
+        let cmd = Upsert::default();
+        let mut ctx = Context {
+            store: DataStoreHandle::in_memory(vec![]),
+            prompt: Prompt::scripted("--help\nsearch-engines\ngoogle\nhttps://google.com\nyes\n"),
+            ..Context::default()
+        };
+        assert!(cmd.run(&mut ctx, vec![]).is_ok());
+        assert_eq!(
+            ctx.store.links("search-engines").unwrap(),
+            vec!["google".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_upsert_run_prompt_not_confirmed() {
+        let args: Vec<String> = vec!["search-engines".to_string(), "google".to_string()];
+        let cmd = Upsert::default();
+        let mut ctx = Context {
+            store: DataStoreHandle::in_memory(vec![]),
+            prompt: Prompt::scripted("https://google.com\nn\n"),
+            ..Context::default()
+        };
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value("Nothing was upserted".to_string()));
+        assert_eq!(cmd.run(&mut ctx, args), expected);
+        assert_eq!(ctx.store.saves(), 0);
+    }
+
+    fn ctx_with_input(input: &'static str) -> Context {
+        Context {
+            input: RefCell::new(Box::new(input.as_bytes())),
+            ..Context::in_memory(vec![(
+                "work".to_string(),
+                vec![
+                    ("ci".to_string(), "https://ci.internal".to_string()),
+                    ("mr".to_string(), "https://mr.internal".to_string()),
+                ],
+            )])
+        }
+    }
+
+    #[test]
+    fn test_upsert_run_from_stdin() {
+        let cmd = Upsert::default();
+        let input = "# Links for new teammates\nwork->\n  ci|https://ci.internal\n  mr|https://mr.internal/all\n  wiki|https://wiki.internal\nnews|bbc|https://bbc.com\n";
+        let args = || vec!["--from".to_string(), "-".to_string()];
+        let mut ctx = ctx_with_input(input);
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Value(
+            "Upserted 4 links from stdin: 2 created, 1 updated, 1 unchanged".to_string(),
+        ));
+        assert_eq!(cmd.run(&mut ctx, args()), expected);
+        assert_eq!(
+            ctx.store.read_link("work", "mr").unwrap().1,
+            "https://mr.internal/all"
+        );
+        assert_eq!(ctx.store.links("news").unwrap(), vec!["bbc".to_string()]);
+        // Upserting the same links again changes nothing
+        ctx.input = RefCell::new(Box::new(input.as_bytes()));
+        let expected: Result<CommandResult, CommandError> = Ok(CommandResult::Value(
+            "Upserted 4 links from stdin: 0 created, 0 updated, 4 unchanged".to_string(),
+        ));
+        assert_eq!(cmd.run(&mut ctx, args()), expected);
+    }
+
+    #[test]
+    fn test_upsert_run_from_rejects_invalid_lines() {
+        let cmd = Upsert::default();
+        let mut ctx = ctx_with_input(
+            "wiki|https://wiki.internal\nwork->\n  wiki|https://wiki.internal\n  #bad|x\n  docs\n",
+        );
+        let expected: Result<CommandResult, CommandError> = Err(CommandError {
+            kind: CommandErrorKind::InvalidData,
+            message: "Found 2 invalid line(s) in stdin, nothing was upserted:\n  line 1: wiki|https://wiki.internal (Link does not have a parent, add a parent-> line above it)\n  line 5:   docs (Expected parent->, link|value, or parent|link|value)".to_string(),
+        });
+        assert_eq!(
+            cmd.run(&mut ctx, vec!["--from".to_string(), "-".to_string()]),
+            expected
+        );
+        assert_eq!(ctx.store.links("work").unwrap().len(), 2);
     }
 }
@@ -0,0 +1,482 @@
+use crate::{
+    commands::{Command, CommandError, CommandResult, Table, clean::local_path, display_examples},
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::command::Args,
+    utils::context::Context,
+    utils::json::Json,
+    utils::link_type::LinkType,
+    utils::os_implementations::{HttpResponse, OsImplementationError, http_status},
+};
+use std::path::Path;
+
+/// How long a URL gets to answer before it is reported as timed out
+const REQUEST_TIMEOUT_SECS: u64 = 10;
+
+/// How many URLs are checked at the same time
+const MAX_CONCURRENT_CHECKS: usize = 8;
+
+pub(crate) struct Verify {
+    name: String,
+    description: String,
+    args: [String; 1],
+}
+
+impl Default for Verify {
+    fn default() -> Self {
+        Self {
+            name: "--verify".to_string(),
+            description: "Report which links still work".to_string(),
+            args: ["[--parent <Parent Entity>]".to_string()],
+        }
+    }
+}
+
+/// What checking the value of a link found
+#[derive(Clone, Debug, PartialEq)]
+enum Status {
+    /// The path exists
+    Ok,
+    /// The path does not exist
+    Missing,
+    /// The status code the URL answered with, after following redirects
+    Http(u16),
+    /// The URL did not answer in time
+    Timeout,
+    /// The request failed before any answer, with the reason
+    NetworkError(String),
+    /// A command, or a value that is neither a path nor a URL
+    Unchecked,
+}
+
+impl Status {
+    fn label(&self) -> String {
+        match self {
+            Status::Ok => "ok".to_string(),
+            Status::Missing => "missing".to_string(),
+            Status::Http(status) => status.to_string(),
+            Status::Timeout => "timeout".to_string(),
+            Status::NetworkError(_) => "network error".to_string(),
+            Status::Unchecked => "unchecked".to_string(),
+        }
+    }
+
+    fn reason(&self) -> &str {
+        match self {
+            Status::NetworkError(reason) => reason,
+            _ => "",
+        }
+    }
+
+    /// Whether the link is broken, or could not be reached to tell
+    fn failed(&self) -> bool {
+        match self {
+            Status::Ok | Status::Unchecked => false,
+            Status::Http(status) => *status >= 400,
+            Status::Missing | Status::Timeout | Status::NetworkError(_) => true,
+        }
+    }
+}
+
+/// The status of one link
+#[derive(Debug, PartialEq)]
+struct Report {
+    parent: String,
+    link: String,
+    value: String,
+    status: Status,
+}
+
+impl Report {
+    fn json(&self) -> Json {
+        Json::object([
+            ("parent", Json::from(self.parent.as_str())),
+            ("link", Json::from(self.link.as_str())),
+            ("value", Json::from(self.value.as_str())),
+            ("status", Json::from(self.status.label().as_str())),
+            ("reason", Json::from(self.status.reason())),
+            ("failed", Json::Bool(self.status.failed())),
+        ])
+    }
+}
+
+impl Command for Verify {
+    fn error_message(&self) -> String {
+        "expected no arguments besides the flags, see the Usage section with tap --verify --help"
+            .to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --verify command checks every link and reports its status, changing nothing. URLs are sent a HEAD request with curl, or a GET request when the server does not allow HEAD (405), and are listed with the status code they answer with after following redirects. URLs that do not answer within 10 seconds are listed as timeout, and requests that fail before any answer, like for a host that can not be resolved, as network error with the reason. Paths are listed as ok or missing. Commands and anything else are listed as unchecked. To remove dead links instead, see tap --clean.\n\n");
+        s.push_str("Pass --parent to only check the links of one Parent Entity. Pass --format json to print the report as JSON, for CI jobs and scripts.\n\n");
+        s.push_str("Tap exits with 1 when any link failed: a status code of 400 or more, a timeout, a network error, or a missing path. The report is printed either way, so a cron job can alert on it.\n\n");
+        s.push_str("Command Structure: tap --verify [--parent <Parent Entity>]\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let mut args = Args::new(args);
+        let parent = args.take_option("--parent");
+        match args.len() {
+            0 => self.verify(ctx, parent.as_deref(), &check_url),
+            1 if args.is_flag(0, "--help") => Ok(CommandResult::Value(self.help_message())),
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+impl Verify {
+    /// Checks the links of `parent`, or of every parent entity, with URLs checked by `url_status`,
+    /// and reports the status of each
+    fn verify(
+        &self,
+        ctx: &Context,
+        parent: Option<&str>,
+        url_status: &(dyn Fn(&str) -> Status + Sync),
+    ) -> Result<CommandResult, CommandError> {
+        let links = match parent {
+            Some(parent) => {
+                let parent = ctx.store.resolve_alias(parent)?;
+                let links = ctx
+                    .store
+                    .read_parent(&parent)
+                    .map_err(|e| ctx.store.with_suggestions(e, &parent, None))?;
+                vec![(parent, links)]
+            }
+            None => ctx.store.all_links()?,
+        };
+        let meta = ctx.store.all_meta()?;
+        let all: Vec<(&String, &String, &String, Option<LinkType>)> = links
+            .iter()
+            .flat_map(|(parent, links)| {
+                let meta = &meta;
+                links.iter().map(move |(link, value)| {
+                    let link_type = meta
+                        .get(&(parent.clone(), link.clone()))
+                        .and_then(|meta| meta.link_type);
+                    (parent, link, value, link_type)
+                })
+            })
+            .collect();
+        if all.is_empty() {
+            return Ok(CommandResult::Value("No links to verify".to_string()));
+        }
+        let mut reports = vec![];
+        // Each URL may take up to REQUEST_TIMEOUT_SECS to answer, so a few are checked at a time
+        for chunk in all.chunks(MAX_CONCURRENT_CHECKS) {
+            let statuses: Vec<Status> = std::thread::scope(|s| {
+                let checks: Vec<_> = chunk
+                    .iter()
+                    .map(|(_, _, value, link_type)| {
+                        s.spawn(move || check(value, *link_type, url_status))
+                    })
+                    .collect();
+                checks
+                    .into_iter()
+                    .map(|c| c.join().unwrap_or(Status::Unchecked))
+                    .collect()
+            });
+            for ((parent, link, value, _), status) in chunk.iter().zip(statuses) {
+                reports.push(Report {
+                    parent: parent.to_string(),
+                    link: link.to_string(),
+                    value: value.to_string(),
+                    status,
+                });
+            }
+        }
+        let failed = reports.iter().filter(|r| r.status.failed()).count();
+        let res = match ctx.format.is_structured() {
+            true => CommandResult::Json(Json::object([
+                ("checked", Json::Number(reports.len() as u64)),
+                ("failed", Json::Number(failed as u64)),
+                (
+                    "links",
+                    Json::Array(reports.iter().map(Report::json).collect()),
+                ),
+            ])),
+            false => CommandResult::Table(Table {
+                title: format!("Checked {} link(s), {failed} failed:", reports.len()),
+                rows: rows(&reports),
+            }),
+        };
+        match failed {
+            0 => Ok(res),
+            _ => Ok(CommandResult::Failure(Box::new(res))),
+        }
+    }
+}
+
+/// The reports with the parent entity only written on its first row, so they read as groups
+fn rows(reports: &[Report]) -> Vec<Vec<String>> {
+    reports
+        .iter()
+        .enumerate()
+        .map(|(i, r)| {
+            let first_of_parent = i == 0 || reports[i - 1].parent != r.parent;
+            let status = match r.status.reason() {
+                "" => r.status.label(),
+                reason => format!("{} ({reason})", r.status.label()),
+            };
+            vec![
+                if first_of_parent {
+                    r.parent.clone()
+                } else {
+                    String::new()
+                },
+                r.link.clone(),
+                status,
+                r.value.clone(),
+            ]
+        })
+        .collect()
+}
+
+/// Checks a path for existence and a URL with `url_status`. The type of the link, see
+/// `tap --add --type`, decides when it is known, otherwise the value does.
+fn check(value: &str, link_type: Option<LinkType>, url_status: &dyn Fn(&str) -> Status) -> Status {
+    let value = value.trim();
+    let path = match link_type {
+        Some(LinkType::Cmd) => return Status::Unchecked,
+        Some(LinkType::Url) => return url_status(value),
+        Some(LinkType::File | LinkType::Dir) => {
+            local_path(value).or_else(|| Some(Path::new(value).to_path_buf()))
+        }
+        None if value.starts_with("http://") || value.starts_with("https://") => {
+            return url_status(value);
+        }
+        None => local_path(value),
+    };
+    match path {
+        Some(path) if path.exists() => Status::Ok,
+        Some(_) => Status::Missing,
+        None => Status::Unchecked,
+    }
+}
+
+/// Sends a HEAD request to the URL, and a GET request when HEAD is not allowed, see `http_status`
+fn check_url(url: &str) -> Status {
+    let status = |res: Result<HttpResponse, OsImplementationError>| match res {
+        Ok(HttpResponse::Status(status)) => Status::Http(status),
+        Ok(HttpResponse::TimedOut) => Status::Timeout,
+        Ok(HttpResponse::Failed(reason)) => Status::NetworkError(reason),
+        Err(e) => Status::NetworkError(e.to_string()),
+    };
+    match status(http_status(url, true, REQUEST_TIMEOUT_SECS)) {
+        Status::Http(405) => status(http_status(url, false, REQUEST_TIMEOUT_SECS)),
+        head => head,
+    }
+}
+
+impl DisplayCommandAsRow for Verify {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            ("tap --verify", "Report the status of every link"),
+            (
+                "tap --verify --parent work",
+                "Report the status of the links of work",
+            ),
+            (
+                "tap --format json --verify",
+                "Report the status of every link as JSON",
+            ),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::format::Format;
+
+    /// A file that exists for the whole test run
+    fn existing_file() -> String {
+        env!("CARGO_MANIFEST_DIR").to_string() + "/Cargo.toml"
+    }
+
+    fn ctx_with_links() -> Context {
+        let ctx = Context::in_memory(vec![
+            (
+                "docs".to_string(),
+                vec![
+                    ("manifest".to_string(), existing_file()),
+                    ("old".to_string(), "/no/such/tap/file".to_string()),
+                    ("wiki".to_string(), "https://wiki.internal/gone".to_string()),
+                ],
+            ),
+            (
+                "work".to_string(),
+                vec![
+                    ("ci".to_string(), "https://ci.internal".to_string()),
+                    ("home".to_string(), "https://home.internal".to_string()),
+                    ("vpn".to_string(), "wg-quick up wg0".to_string()),
+                ],
+            ),
+        ]);
+        ctx.store
+            .write(|ds| {
+                ds.batch(|tx| tx.update_meta("work", "vpn", |m| m.link_type = Some(LinkType::Cmd)))
+            })
+            .unwrap();
+        ctx
+    }
+
+    /// Answers 404 for URLs ending with /gone, 200 for home, and times out for any other URL
+    fn fake_url_status(url: &str) -> Status {
+        match url {
+            url if url.ends_with("/gone") => Status::Http(404),
+            url if url.contains("home") => Status::Http(200),
+            _ => Status::Timeout,
+        }
+    }
+
+    #[test]
+    fn test_verify_run_expected_help_arg() {
+        let cmd = Verify::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_verify_run_unexpected_args() {
+        let cmd = Verify::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["work".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_check() {
+        assert_eq!(check(&existing_file(), None, &fake_url_status), Status::Ok);
+        assert_eq!(
+            check("file:///no/such/tap/file", None, &fake_url_status),
+            Status::Missing
+        );
+        assert_eq!(
+            check("https://x.y/gone", None, &fake_url_status),
+            Status::Http(404)
+        );
+        assert_eq!(
+            check("ls ~", Some(LinkType::Cmd), &fake_url_status),
+            Status::Unchecked
+        );
+        assert_eq!(
+            check("no/such/tap/file", Some(LinkType::File), &fake_url_status),
+            Status::Missing
+        );
+        assert_eq!(check("JIRA-42", None, &fake_url_status), Status::Unchecked);
+    }
+
+    #[test]
+    fn test_status_failed() {
+        assert!(!Status::Http(200).failed());
+        assert!(!Status::Http(301).failed());
+        assert!(Status::Http(404).failed());
+        assert!(Status::Http(503).failed());
+        assert!(Status::Timeout.failed());
+        assert!(Status::NetworkError("Could not resolve host".to_string()).failed());
+        assert!(!Status::Unchecked.failed());
+    }
+
+    #[test]
+    fn test_verify_reports_every_link() {
+        let ctx = ctx_with_links();
+        let saves = ctx.store.saves();
+        let res = Verify::default()
+            .verify(&ctx, None, &fake_url_status)
+            .unwrap();
+        assert_eq!(res.exit_code(), 1);
+        assert_eq!(
+            res.to_string(),
+            format!(
+                "Checked 6 link(s), 3 failed:\n  docs  manifest  ok         {}\n        old       missing    /no/such/tap/file\n        wiki      404        https://wiki.internal/gone\n  work  ci        timeout    https://ci.internal\n        home      200        https://home.internal\n        vpn       unchecked  wg-quick up wg0",
+                existing_file()
+            )
+        );
+        // A report changes nothing
+        assert_eq!(ctx.store.saves(), saves);
+    }
+
+    #[test]
+    fn test_verify_parent() {
+        let ctx = ctx_with_links();
+        let res = Verify::default()
+            .verify(&ctx, Some("work"), &|url| match url.contains("home") {
+                true => Status::Http(200),
+                false => Status::NetworkError("Could not resolve host: ci.internal".to_string()),
+            })
+            .unwrap();
+        assert_eq!(
+            res,
+            CommandResult::Failure(Box::new(CommandResult::Table(Table {
+                title: "Checked 3 link(s), 1 failed:".to_string(),
+                rows: vec![
+                    vec![
+                        "work".to_string(),
+                        "ci".to_string(),
+                        "network error (Could not resolve host: ci.internal)".to_string(),
+                        "https://ci.internal".to_string(),
+                    ],
+                    vec![
+                        String::new(),
+                        "home".to_string(),
+                        "200".to_string(),
+                        "https://home.internal".to_string(),
+                    ],
+                    vec![
+                        String::new(),
+                        "vpn".to_string(),
+                        "unchecked".to_string(),
+                        "wg-quick up wg0".to_string(),
+                    ],
+                ],
+            })))
+        );
+        let res = Verify::default().verify(&ctx, Some("work"), &|_| Status::Http(200));
+        assert_eq!(res.unwrap().exit_code(), 0);
+        assert!(
+            Verify::default()
+                .verify(&ctx, Some("nope"), &fake_url_status)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_verify_json() {
+        let mut ctx = ctx_with_links();
+        ctx.format = Format::Json;
+        let res = Verify::default()
+            .verify(&ctx, Some("docs"), &fake_url_status)
+            .unwrap();
+        assert_eq!(res.exit_code(), 1);
+        assert_eq!(
+            res.render(Format::Json),
+            format!(
+                r#"{{"checked":3,"failed":2,"links":[{{"parent":"docs","link":"manifest","value":"{}","status":"ok","reason":"","failed":false}},{{"parent":"docs","link":"old","value":"/no/such/tap/file","status":"missing","reason":"","failed":true}},{{"parent":"docs","link":"wiki","value":"https://wiki.internal/gone","status":"404","reason":"","failed":true}}]}}"#,
+                existing_file()
+            )
+        );
+    }
+}
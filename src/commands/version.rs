@@ -1,12 +1,21 @@
 use crate::{
-    commands::{Command, CommandResult, display_version},
+    commands::{Command, CommandError, CommandResult, display_examples, display_version},
     utils::cli_usage_table::DisplayCommandAsRow,
+    utils::command::Args,
+    utils::context::Context,
+    utils::json::Json,
+    utils::log::{self, Level},
 };
 
+/// Build metadata captured by `build.rs`, unset when it could not be found
+const COMMIT: Option<&str> = option_env!("TAP_BUILD_COMMIT");
+const BUILT: Option<&str> = option_env!("TAP_BUILD_DATE");
+const TARGET: Option<&str> = option_env!("TAP_BUILD_TARGET");
+
 pub(crate) struct Version {
     name: String,
     description: String,
-    args: [String; 0],
+    args: [String; 1],
 }
 
 impl Default for Version {
@@ -14,7 +23,7 @@ impl Default for Version {
         Self {
             name: "-v, --version".to_string(),
             description: "Show tap version".to_string(),
-            args: [],
+            args: ["[--json]".to_string()],
         }
     }
 }
@@ -26,24 +35,44 @@ impl Command for Version {
 
     fn help_message(&self) -> String {
         let mut s = String::new();
-        s.push_str("The version command shows the current version.\n\n");
-        s.push_str("Example Usage: tap --version");
+        s.push_str("The version command shows the current version. With --verbose, the commit tap was built from is shown on a second line.\n\n");
+        s.push_str("Pass --json to print the name, version, commit, build date, and target of tap as JSON, for scripts. The commit, build date, and target are left out when they were not known at build time.\n\n");
+        s.push_str(&display_examples(&self.examples()));
         s
     }
 
-    fn run(&self, args: Vec<String>) -> Result<CommandResult, String> {
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let mut args = Args::new(args);
+        let json = args.take_flag(&["--json"]) || ctx.format.is_structured();
         match args.len() {
-            0 => Ok(CommandResult::Value(display_version())),
-            1 => {
-                if args[0] == "--help" {
-                    Ok(CommandResult::Value(self.help_message()))
-                } else {
-                    Err(self.error_message())
+            0 if json => Ok(CommandResult::Json(version_json())),
+            0 => {
+                let mut version = display_version();
+                if log::level() >= Level::Verbose
+                    && let Some(commit) = COMMIT
+                {
+                    version.push_str(&format!("\ncommit {commit}"));
                 }
+                Ok(CommandResult::Value(version))
             }
-            _ => Err(self.error_message()),
+            1 if args.is_flag(0, "--help") => Ok(CommandResult::Value(self.help_message())),
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+/// `{"name":"tap","version":"0.1.0","commit":"...","built":"...","target":"..."}`
+fn version_json() -> Json {
+    let mut entries = vec![
+        ("name".to_string(), Json::from(env!("CARGO_PKG_NAME"))),
+        ("version".to_string(), Json::from(env!("CARGO_PKG_VERSION"))),
+    ];
+    for (key, value) in [("commit", COMMIT), ("built", BUILT), ("target", TARGET)] {
+        if let Some(value) = value {
+            entries.push((key.to_string(), Json::from(value)));
         }
     }
+    Json::Object(entries)
 }
 
 impl DisplayCommandAsRow for Version {
@@ -55,6 +84,19 @@ impl DisplayCommandAsRow for Version {
         self.description.clone()
     }
 
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            ("tap --version", "Show the current version"),
+            (
+                "tap --version --json",
+                "Show the version and build metadata as JSON",
+            ),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
     fn name(&self) -> String {
         self.name.clone()
     }
@@ -68,8 +110,9 @@ mod tests {
     fn test_version_run_expected_args() {
         let args: Vec<String> = vec![];
         let cmd = Version::default();
-        let expected: Result<CommandResult, String> = Ok(CommandResult::Value(display_version()));
-        let res = cmd.run(args);
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(display_version()));
+        let res = cmd.run(&mut Context::default(), args);
         assert_eq!(res, expected);
     }
 
@@ -77,8 +120,9 @@ mod tests {
     fn test_version_run_help_arg() {
         let args: Vec<String> = vec!["--help".to_string()];
         let cmd = Version::default();
-        let expected: Result<CommandResult, String> = Ok(CommandResult::Value(cmd.help_message()));
-        let res = cmd.run(args);
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        let res = cmd.run(&mut Context::default(), args);
         assert_eq!(res, expected);
     }
 
@@ -86,8 +130,28 @@ mod tests {
     fn test_version_run_unexpected_args() {
         let args: Vec<String> = vec!["random".to_string()];
         let cmd = Version::default();
-        let expected: Result<CommandResult, String> = Err(cmd.error_message());
-        let res = cmd.run(args);
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        let res = cmd.run(&mut Context::default(), args);
         assert_eq!(res, expected);
     }
+
+    #[test]
+    fn test_version_run_json() {
+        let cmd = Version::default();
+        let res = cmd.run(&mut Context::default(), vec!["--json".to_string()]);
+        let Ok(CommandResult::Json(Json::Object(entries))) = res else {
+            panic!("expected a JSON object, got {res:?}");
+        };
+        assert_eq!(entries[0], ("name".to_string(), Json::from("tap")));
+        assert_eq!(
+            entries[1],
+            ("version".to_string(), Json::from(env!("CARGO_PKG_VERSION")))
+        );
+        let keys: Vec<&str> = entries.iter().map(|(key, _)| key.as_str()).collect();
+        for key in &keys[2..] {
+            assert!(["commit", "built", "target"].contains(key), "{key}");
+        }
+        // Cargo always tells build scripts the target
+        assert!(keys.contains(&"target"));
+    }
 }
@@ -0,0 +1,242 @@
+use crate::{
+    commands::{
+        Command, CommandError, CommandResult, display_examples, resolve_link_prefix,
+        resolve_parent_prefix, show::links_json,
+    },
+    utils::cli_usage_table::DisplayCommandAsRow,
+    utils::command::Args,
+    utils::context::Context,
+};
+
+pub(crate) struct Which {
+    name: String,
+    description: String,
+    args: [String; 3],
+}
+
+impl Default for Which {
+    fn default() -> Self {
+        Self {
+            name: "--which".to_string(),
+            description: "Print link values without opening them".to_string(),
+            args: [
+                "<Parent|here>".to_string(),
+                "[Link]".to_string(),
+                "[--exact]".to_string(),
+            ],
+        }
+    }
+}
+
+impl Command for Which {
+    fn error_message(&self) -> String {
+        "expected 1-2 arguments, see the Usage section with tap --which --help".to_string()
+    }
+
+    fn help_message(&self) -> String {
+        let mut s = String::new();
+        s.push_str("Tap --which command prints the value of a link as it is stored, without opening it, so it can be pasted or passed to another tool. Without a link, every link of the Parent Entity is printed as Name<TAB>Value, one per line.\n\n");
+        s.push_str("Command Structure: tap --which <Parent Entity | here> [Link Name] [--exact]\n");
+        s.push_str("Names are resolved like opening a link does: the Parent Entity and Link Name can be shortened to the start of their names, as long as only one name starts that way. Pass --exact to only use names as typed.\n");
+        s.push_str(&display_examples(&self.examples()));
+        s
+    }
+
+    fn run(&self, ctx: &mut Context, args: Vec<String>) -> Result<CommandResult, CommandError> {
+        let mut args = Args::new(args);
+        let exact = args.take_flag(&["--exact"]);
+        match args.len() {
+            1 if args.is_flag(0, "--help") => Ok(CommandResult::Value(self.help_message())),
+            1 => {
+                let parent_entity = self.resolve_parent(ctx, &args, exact)?;
+                let links = ctx.store.read_parent(&parent_entity)?;
+                if ctx.format.is_structured() {
+                    return Ok(CommandResult::Json(links_json(&parent_entity, &links)));
+                }
+                Ok(CommandResult::Rows(
+                    links
+                        .into_iter()
+                        .map(|(link, val)| vec![link, val])
+                        .collect(),
+                ))
+            }
+            2 => {
+                let parent_entity = self.resolve_parent(ctx, &args, exact)?;
+                let link = match exact {
+                    true => args[1].to_string(),
+                    false => resolve_link_prefix(&ctx.store, &parent_entity, &args[1])?,
+                };
+                let link_value = ctx.store.read_link(&parent_entity, &link)?;
+                if ctx.format.is_structured() {
+                    return Ok(CommandResult::Json(links_json(
+                        &parent_entity,
+                        &[link_value],
+                    )));
+                }
+                Ok(CommandResult::Value(link_value.1))
+            }
+            _ => Err(self.usage_error()),
+        }
+    }
+}
+
+impl Which {
+    /// The parent entity named by the first argument, `here` being the current directory
+    fn resolve_parent(
+        &self,
+        ctx: &Context,
+        args: &Args,
+        exact: bool,
+    ) -> Result<String, CommandError> {
+        match exact || args.is_flag(0, "here") {
+            true => Ok(args.parent(0)?),
+            false => resolve_parent_prefix(&ctx.store, &args[0]),
+        }
+    }
+}
+
+impl DisplayCommandAsRow for Which {
+    fn args(&self) -> Vec<String> {
+        self.args.to_vec()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn examples(&self) -> Vec<(String, String)> {
+        [
+            (
+                "tap --which search-engines google",
+                "Print the value of a link",
+            ),
+            (
+                "tap --which search-engines",
+                "Print every link of a Parent Entity as Name<TAB>Value",
+            ),
+            (
+                "tap --which here repo",
+                "Print a link of the current directory",
+            ),
+        ]
+        .into_iter()
+        .map(|(line, description)| (line.to_string(), description.to_string()))
+        .collect()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::CommandErrorKind;
+    use crate::utils::format::Format;
+
+    fn ctx_with_links(parent: &str) -> Context {
+        Context::in_memory(vec![(
+            parent.to_string(),
+            vec![
+                ("google".to_string(), "https://google.com".to_string()),
+                ("yahoo".to_string(), "https://yahoo.com".to_string()),
+            ],
+        )])
+    }
+
+    #[test]
+    fn test_which_run_expected_help_arg() {
+        let cmd = Which::default();
+        let expected: Result<CommandResult, CommandError> =
+            Ok(CommandResult::Value(cmd.help_message()));
+        assert_eq!(
+            cmd.run(&mut Context::default(), vec!["--help".to_string()]),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_which_run_unexpected_args() {
+        let cmd = Which::default();
+        let expected: Result<CommandResult, CommandError> = Err(cmd.usage_error());
+        assert_eq!(cmd.run(&mut Context::default(), vec![]), expected);
+        assert_eq!(
+            cmd.run(
+                &mut Context::default(),
+                vec!["a".to_string(), "b".to_string(), "c".to_string()]
+            ),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_which_run_link_value() {
+        let cmd = Which::default();
+        let mut ctx = ctx_with_links("search-engines");
+        let res = cmd.run(
+            &mut ctx,
+            vec!["search-engines".to_string(), "google".to_string()],
+        );
+        assert_eq!(
+            res.map(|res| res.to_string()),
+            Ok("https://google.com".to_string())
+        );
+    }
+
+    #[test]
+    fn test_which_run_prefixes() {
+        let cmd = Which::default();
+        let mut ctx = ctx_with_links("search-engines");
+        let res = cmd.run(&mut ctx, vec!["sea".to_string(), "ya".to_string()]);
+        assert_eq!(
+            res,
+            Ok(CommandResult::Value("https://yahoo.com".to_string()))
+        );
+        let res = cmd.run(
+            &mut ctx,
+            vec!["sea".to_string(), "ya".to_string(), "--exact".to_string()],
+        );
+        assert_eq!(res.unwrap_err().kind, CommandErrorKind::ParentNotFound);
+    }
+
+    #[test]
+    fn test_which_run_all_links() {
+        let cmd = Which::default();
+        let mut ctx = ctx_with_links("search-engines");
+        let res = cmd.run(&mut ctx, vec!["search-engines".to_string()]);
+        assert_eq!(
+            res.map(|res| res.to_string()),
+            Ok("google\thttps://google.com\nyahoo\thttps://yahoo.com".to_string())
+        );
+    }
+
+    #[test]
+    fn test_which_run_link_not_found() {
+        let cmd = Which::default();
+        let mut ctx = ctx_with_links("search-engines");
+        let res = cmd.run(
+            &mut ctx,
+            vec!["search-engines".to_string(), "bing".to_string()],
+        );
+        assert_eq!(res.unwrap_err().kind, CommandErrorKind::LinkNotFound);
+    }
+
+    #[test]
+    fn test_which_run_structured_format() {
+        let cmd = Which::default();
+        let mut ctx = ctx_with_links("search-engines");
+        ctx.format = Format::Yaml;
+        let res = cmd.run(
+            &mut ctx,
+            vec!["search-engines".to_string(), "google".to_string()],
+        );
+        assert_eq!(
+            res.map(|res| res.render(Format::Yaml)),
+            Ok(
+                "parent: search-engines\nlinks:\n  - name: google\n    value: https://google.com"
+                    .to_string()
+            )
+        );
+    }
+}
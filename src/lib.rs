@@ -0,0 +1,28 @@
+//! Tap stores links (URLs or file paths) under parent entities, such as repositories or
+//! projects, and opens them by name. This crate is what the `tap` command line tool is built on,
+//! and the same data files can be read and changed from Rust through [`Store`].
+//!
+//! ```
+//! let path = std::env::temp_dir().join(format!("tap_doc_{}.tap_data", std::process::id()));
+//! # let _ = std::fs::remove_file(&path);
+//! # let _ = std::fs::remove_file(path.with_extension("tap_index"));
+//! let mut store = tap::Store::open(&path)?;
+//! store.add("search-engines", "google", "https://google.com")?;
+//! assert_eq!(store.link("search-engines", "google")?, "https://google.com");
+//! assert_eq!(store.parents(), vec!["search-engines".to_string()]);
+//! // Opening the same file again sees the link, like the tap command does
+//! let store = tap::Store::open(&path)?;
+//! assert_eq!(
+//!     store.links("search-engines")?,
+//!     vec![("google".to_string(), "https://google.com".to_string())]
+//! );
+//! # Ok::<(), tap::Error>(())
+//! ```
+
+#[doc(hidden)]
+pub mod cli;
+mod commands;
+mod store;
+mod utils;
+
+pub use store::{Error, ErrorKind, Store, open_link, validate_link, validate_parent};
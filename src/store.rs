@@ -0,0 +1,325 @@
+use crate::utils::os_implementations::{self, OsImplementationError};
+use crate::utils::tap_data_store::{
+    self, DataStore, StoreOptions, TapDataStoreError, TapDataStoreErrorKind, index_path_for,
+};
+use std::{fmt, path::Path};
+
+/// A tap data store: parent entities, each with named links. Every change is written to the data
+/// and index files before the method returns, the same way the `tap` commands write them.
+pub struct Store {
+    inner: DataStore,
+}
+
+impl Store {
+    /// Opens the data file at `path`, creating it when missing. The index file is kept next to
+    /// it, `demo.tap_data` is indexed by `demo.tap_index` and any other file gets `.tap_index`
+    /// appended to its name.
+    ///
+    /// # Errors
+    ///
+    /// - `ErrorKind::InvalidData` if the data or index file can not be parsed
+    /// - `ErrorKind::Io` if a file can not be created or read
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let data = path.as_ref().to_path_buf();
+        let index = index_path_for(&data);
+        Ok(Self {
+            inner: DataStore::open_files(data, index)?,
+        })
+    }
+
+    /// Opens the data file `tap` uses when no `--file` flag is passed, see the README for where
+    /// it is stored.
+    ///
+    /// # Errors
+    ///
+    /// Same as `Store::open`, plus `ErrorKind::Io` if the data directory can not be found.
+    pub fn open_default() -> Result<Self, Error> {
+        Ok(Self {
+            inner: {
+                let (data, index) = StoreOptions::default().paths()?;
+                DataStore::new(data, index)?
+            },
+        })
+    }
+
+    /// The names of every parent entity, sorted
+    pub fn parents(&self) -> Vec<String> {
+        self.inner.parents()
+    }
+
+    /// The links of a parent entity as (name, value) pairs, sorted by name
+    ///
+    /// # Errors
+    ///
+    /// - `ErrorKind::ParentNotFound` if there is no parent entity named `parent`
+    pub fn links(&self, parent: &str) -> Result<Vec<(String, String)>, Error> {
+        Ok(self.inner.read_parent_slow(parent)?)
+    }
+
+    /// The value of a single link
+    ///
+    /// # Errors
+    ///
+    /// - `ErrorKind::ParentNotFound` if there is no parent entity named `parent`
+    /// - `ErrorKind::LinkNotFound` if the parent entity has no link named `link`
+    pub fn link(&self, parent: &str, link: &str) -> Result<String, Error> {
+        match self.inner.read_link_slow(parent, link)? {
+            Some((_, value)) => Ok(value),
+            None => Err(Error {
+                kind: ErrorKind::LinkNotFound,
+                message: format!("Link '{link}' not found in parent '{parent}'"),
+            }),
+        }
+    }
+
+    /// Adds a link, creating the parent entity if needed
+    ///
+    /// # Errors
+    ///
+    /// - `ErrorKind::InvalidName` if the parent entity or link name is reserved
+    /// - `ErrorKind::LinkAlreadyExists` if the parent entity already has a link named `link`
+    /// - `ErrorKind::Io` if the files can not be written
+    pub fn add(&mut self, parent: &str, link: &str, value: &str) -> Result<(), Error> {
+        Ok(self
+            .inner
+            .add_link(parent.to_string(), link.to_string(), value.to_string())?)
+    }
+
+    /// Adds a link, or replaces the value of an existing one
+    ///
+    /// # Errors
+    ///
+    /// - `ErrorKind::InvalidName` if the parent entity or link name is reserved
+    /// - `ErrorKind::Io` if the files can not be written
+    pub fn upsert(&mut self, parent: &str, link: &str, value: &str) -> Result<(), Error> {
+        Ok(self
+            .inner
+            .upsert_link(parent.to_string(), link.to_string(), value.to_string())?)
+    }
+
+    /// Removes a single link. A parent entity left without links is removed as well. The link is
+    /// kept in the trash file next to the data file, like `tap --delete` does.
+    ///
+    /// # Errors
+    ///
+    /// - `ErrorKind::ParentNotFound` if there is no parent entity named `parent`
+    /// - `ErrorKind::LinkNotFound` if the parent entity has no link named `link`
+    /// - `ErrorKind::Io` if the files can not be written
+    pub fn remove_link(&mut self, parent: &str, link: &str) -> Result<(), Error> {
+        Ok(self
+            .inner
+            .delete(parent.to_string(), Some(link.to_string()))?)
+    }
+
+    /// Removes a parent entity with all of its links, keeping them in the trash file
+    ///
+    /// # Errors
+    ///
+    /// - `ErrorKind::ParentNotFound` if there is no parent entity named `parent`
+    /// - `ErrorKind::Io` if the files can not be written
+    pub fn remove_parent(&mut self, parent: &str) -> Result<(), Error> {
+        Ok(self.inner.delete(parent.to_string(), None)?)
+    }
+}
+
+/// Checks that `parent` can be used as a parent entity name
+///
+/// # Errors
+///
+/// - `ErrorKind::InvalidName` if the name is `|` or starts with `#`
+pub fn validate_parent(parent: &str) -> Result<(), Error> {
+    Ok(tap_data_store::validate_parent(parent)?)
+}
+
+/// Checks that `link` can be used as a link name
+///
+/// # Errors
+///
+/// - `ErrorKind::InvalidName` if the name contains `|` or starts with `#`
+pub fn validate_link(link: &str) -> Result<(), Error> {
+    Ok(tap_data_store::validate_link(link)?)
+}
+
+/// Opens a link value (a URL or a path) with the default application of the OS, like `tap <parent>
+/// <link>` does
+///
+/// # Errors
+///
+/// - `ErrorKind::Open` if the OS is not supported or the opener could not be started
+pub fn open_link(value: &str) -> Result<(), Error> {
+    Ok(os_implementations::open_link(value)?)
+}
+
+/// What went wrong, see `Error::kind`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// There is no parent entity with the given name
+    ParentNotFound,
+    /// The parent entity has no link with the given name
+    LinkNotFound,
+    /// The parent entity already has a link with the given name
+    LinkAlreadyExists,
+    /// The parent entity or link name is reserved, see `validate_parent` and `validate_link`
+    InvalidName,
+    /// The data or index file can not be parsed
+    InvalidData,
+    /// The data files can not be changed because tap is in read-only mode
+    ReadOnly,
+    /// A file could not be created, read or written
+    Io,
+    /// A link could not be opened
+    Open,
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ErrorKind::ParentNotFound => write!(f, "Parent entity not found"),
+            ErrorKind::LinkNotFound => write!(f, "Link not found"),
+            ErrorKind::LinkAlreadyExists => write!(f, "Link already exists"),
+            ErrorKind::InvalidName => write!(f, "Invalid name"),
+            ErrorKind::InvalidData => write!(f, "Invalid data"),
+            ErrorKind::ReadOnly => write!(f, "Read only"),
+            ErrorKind::Io => write!(f, "I/O error"),
+            ErrorKind::Open => write!(f, "Open failed"),
+        }
+    }
+}
+
+/// The error returned by every fallible function of the library
+#[derive(Debug)]
+pub struct Error {
+    kind: ErrorKind,
+    message: String,
+}
+
+impl Error {
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<TapDataStoreError> for Error {
+    fn from(e: TapDataStoreError) -> Self {
+        let kind = match e.kind() {
+            TapDataStoreErrorKind::ParentEntityNotFound => ErrorKind::ParentNotFound,
+            TapDataStoreErrorKind::LinkNotFound => ErrorKind::LinkNotFound,
+            TapDataStoreErrorKind::LinkAlreadyExists => ErrorKind::LinkAlreadyExists,
+            TapDataStoreErrorKind::ReservedKeyword => ErrorKind::InvalidName,
+            TapDataStoreErrorKind::ParseError => ErrorKind::InvalidData,
+            TapDataStoreErrorKind::ReadOnly => ErrorKind::ReadOnly,
+            _ => ErrorKind::Io,
+        };
+        Self {
+            kind,
+            message: e.to_string(),
+        }
+    }
+}
+
+impl From<OsImplementationError> for Error {
+    fn from(e: OsImplementationError) -> Self {
+        Self {
+            kind: ErrorKind::Open,
+            message: e.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::tap_data_store::TestDataFile;
+
+    #[test]
+    fn test_store_add_and_read() {
+        let path = TestDataFile::missing();
+        let mut store = Store::open(&path).unwrap();
+        store
+            .add("search-engines", "google", "https://google.com")
+            .unwrap();
+        store
+            .add("search-engines", "bing", "https://bing.com")
+            .unwrap();
+        assert_eq!(store.parents(), vec!["search-engines".to_string()]);
+        assert_eq!(
+            store.link("search-engines", "google").unwrap(),
+            "https://google.com"
+        );
+
+        // The index is written next to the data file, so a reopened store finds the parents
+        let store = Store::open(&path).unwrap();
+        assert!(path.with_file_name("demo.tap_index").exists());
+        assert_eq!(
+            store.links("search-engines").unwrap(),
+            vec![
+                ("bing".to_string(), "https://bing.com".to_string()),
+                ("google".to_string(), "https://google.com".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_store_upsert_and_remove() {
+        let path = TestDataFile::missing();
+        let mut store = Store::open(&path).unwrap();
+        store.upsert("work", "ci", "https://ci.internal").unwrap();
+        store
+            .upsert("work", "ci", "https://ci.example.com")
+            .unwrap();
+        store.add("work", "jira", "https://jira.internal").unwrap();
+        assert_eq!(store.link("work", "ci").unwrap(), "https://ci.example.com");
+
+        store.remove_link("work", "ci").unwrap();
+        assert_eq!(
+            store.link("work", "ci").unwrap_err().kind(),
+            ErrorKind::LinkNotFound
+        );
+        store.remove_parent("work").unwrap();
+        assert_eq!(
+            store.links("work").unwrap_err().kind(),
+            ErrorKind::ParentNotFound
+        );
+        assert!(store.parents().is_empty());
+    }
+
+    #[test]
+    fn test_store_rebuilds_missing_index() {
+        let path = TestDataFile::missing();
+        std::fs::write(&path, "work->\n  ci|https://ci.internal\n").unwrap();
+        let store = Store::open(&path).unwrap();
+        assert_eq!(store.parents(), vec!["work".to_string()]);
+    }
+
+    #[test]
+    fn test_store_errors() {
+        let path = TestDataFile::missing();
+        let mut store = Store::open(&path).unwrap();
+        store.add("work", "ci", "https://ci.internal").unwrap();
+        assert_eq!(
+            store
+                .add("work", "ci", "https://ci.internal")
+                .unwrap_err()
+                .kind(),
+            ErrorKind::LinkAlreadyExists
+        );
+        assert_eq!(
+            store.add("#work", "ci", "x").unwrap_err().kind(),
+            ErrorKind::InvalidName
+        );
+        assert_eq!(
+            validate_link("a|b").unwrap_err().kind(),
+            ErrorKind::InvalidName
+        );
+        assert!(validate_parent("work").is_ok());
+    }
+}
@@ -1,4 +1,29 @@
+pub(crate) mod aliases;
+pub(crate) mod backup;
 pub(crate) mod cli_usage_table;
 pub(crate) mod command;
+pub(crate) mod config;
+pub(crate) mod context;
+pub(crate) mod env_vars;
+pub(crate) mod expiry;
+pub(crate) mod format;
+pub(crate) mod glob;
+pub(crate) mod groups;
+pub(crate) mod history;
+pub(crate) mod hooks;
+pub(crate) mod json;
+pub(crate) mod link_name;
+pub(crate) mod link_type;
+pub(crate) mod log;
 pub(crate) mod os_implementations;
+pub(crate) mod prompt;
+pub(crate) mod recent;
+pub(crate) mod regex;
+pub(crate) mod selection;
+pub(crate) mod sessions;
+pub(crate) mod style;
+pub(crate) mod suggest;
 pub(crate) mod tap_data_store;
+pub(crate) mod template;
+pub(crate) mod trash;
+pub(crate) mod yaml;
@@ -0,0 +1,93 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// Other names of parent entities, by alias. Aliases are set with `tap --alias` and kept in their
+/// own file next to the data file, one per line:
+///
+/// ```text
+/// k8s->kubernetes-production-cluster
+/// ```
+pub(crate) type Aliases = BTreeMap<String, String>;
+
+/// The aliases file kept next to a data file: `.tap_data` keeps its aliases in `.tap_aliases`,
+/// `demo.tap_data` in `demo.tap_aliases`, and any other file name gets `.tap_aliases` appended
+pub(crate) fn aliases_path_for(data: &Path) -> PathBuf {
+    let file_name = data.file_name().unwrap_or_default().to_string_lossy();
+    match file_name.strip_suffix("tap_data") {
+        Some(stem) => data.with_file_name(format!("{stem}tap_aliases")),
+        None => data.with_file_name(format!("{file_name}.tap_aliases")),
+    }
+}
+
+/// The aliases in the file at `path`, none when there is no such file. Lines that are not an
+/// alias are skipped.
+pub(crate) fn read(path: &Path) -> std::io::Result<Aliases> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Aliases::new()),
+        Err(e) => return Err(e),
+    };
+    Ok(contents
+        .lines()
+        .filter_map(|line| line.split_once("->"))
+        .map(|(alias, parent)| (alias.trim().to_string(), parent.trim().to_string()))
+        .filter(|(alias, parent)| !alias.is_empty() && !parent.is_empty())
+        .collect())
+}
+
+/// Replaces the file at `path` with the aliases, removing it when there are none
+pub(crate) fn write(path: &Path, aliases: &Aliases) -> std::io::Result<()> {
+    if aliases.is_empty() {
+        return match fs::remove_file(path) {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        };
+    }
+    let lines: String = aliases
+        .iter()
+        .map(|(alias, parent)| format!("{alias}->{parent}\n"))
+        .collect();
+    fs::write(path, lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aliases_path_for() {
+        assert_eq!(
+            aliases_path_for(Path::new("/data/.tap_data")),
+            Path::new("/data/.tap_aliases")
+        );
+        assert_eq!(
+            aliases_path_for(Path::new("/data/links")),
+            Path::new("/data/links.tap_aliases")
+        );
+    }
+
+    #[test]
+    fn test_aliases_round_trip() {
+        let dir = std::env::temp_dir().join(format!("tap_aliases_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(".tap_aliases");
+        assert_eq!(read(&path).unwrap(), Aliases::new());
+        let aliases = Aliases::from([(
+            "k8s".to_string(),
+            "kubernetes-production-cluster".to_string(),
+        )]);
+        write(&path, &aliases).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "k8s->kubernetes-production-cluster\n"
+        );
+        assert_eq!(read(&path).unwrap(), aliases);
+        // Removing the last alias removes the file
+        write(&path, &Aliases::new()).unwrap();
+        assert!(!path.exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
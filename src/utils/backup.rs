@@ -0,0 +1,292 @@
+use std::fmt;
+
+/// The extension of the archives written by `tap --backup`
+pub(crate) const EXTENSION: &str = "tapbackup";
+
+/// The first word of every archive, followed by the version of the format
+const MAGIC: &str = "tap-backup";
+
+/// The version of the format written, bumped whenever it changes. Archives of a newer format are
+/// refused rather than restored in part.
+const FORMAT: u32 = 2;
+
+/// A backup of the files of a data store, written by `tap --backup` and read by `tap --restore`.
+/// The archive is plain text, so it can be inspected with any pager:
+///
+/// ```text
+/// tap-backup 2
+/// created 2024-06-01T12:30:05Z
+/// version 0.1.0
+/// file data 32 e2b105f168193fe5
+/// work->
+///   ci|https://ci.internal
+///
+/// file index 7 ac202402a91b1ab2
+/// work|0
+///
+/// end
+/// ```
+///
+/// Every `file <name> <length> <hash>` line is followed by exactly `length` bytes of contents and
+/// a line break, so the contents may hold any text. The hash is the `checksum` of the contents,
+/// and a file that does not match it fails the whole archive. Archives of format 1 have no hashes.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Archive {
+    /// When the backup was taken, see `utc_timestamp`
+    pub created: String,
+    /// The version of tap that took the backup
+    pub version: String,
+    /// The name (`data`, `index`, `config`, then the files kept next to the data file like
+    /// `aliases`) and contents of every file, in that order. The snapshots kept for `tap --undo`
+    /// hold the data file and the command that changed it (`operation`).
+    pub files: Vec<(String, String)>,
+}
+
+impl Archive {
+    /// The contents of the file with the given name
+    pub fn file(&self, name: &str) -> Option<&str> {
+        self.files
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, contents)| contents.as_str())
+    }
+
+    pub fn parse(archive: &str) -> Result<Self, ArchiveError> {
+        let mut rest = archive;
+        let header = next_line(&mut rest, "its header")?;
+        let format = header
+            .strip_prefix(MAGIC)
+            .and_then(|format| format.strip_prefix(' '))
+            .and_then(|format| format.parse::<u32>().ok())
+            .filter(|&format| format > 0)
+            .ok_or_else(|| {
+                ArchiveError::new(format!("the archive does not start with {MAGIC} {FORMAT}"))
+            })?;
+        if format > FORMAT {
+            return Err(ArchiveError::new(format!(
+                "the archive is of format {format}, written by a newer version of tap, and this one reads up to format {FORMAT}. Update tap to restore it"
+            )));
+        }
+        let field = |line: &str, key: &str| {
+            line.strip_prefix(key)
+                .and_then(|value| value.strip_prefix(' '))
+                .map(str::to_string)
+                .ok_or_else(|| ArchiveError::new(format!("expected the {key} line, got {line}")))
+        };
+        let created = field(next_line(&mut rest, "the created line")?, "created")?;
+        let version = field(next_line(&mut rest, "the version line")?, "version")?;
+        let mut files = vec![];
+        loop {
+            let line = next_line(&mut rest, "the end line")?;
+            if line == "end" {
+                break;
+            }
+            let fields = field(line, "file")?;
+            let fields: Vec<&str> = fields.split(' ').collect();
+            // Only archives of format 1 have no hashes
+            let (name, length, hash) = match (format, fields.as_slice()) {
+                (1, [name, length]) => (name, length, None),
+                (_, [name, length, hash]) if format > 1 => (name, length, Some(*hash)),
+                _ => (&"", &"", None),
+            };
+            let (name, Ok(length)) = (name.to_string(), length.parse::<usize>()) else {
+                return Err(ArchiveError::new(format!(
+                    "expected a file line like file data 32 <hash>, got {line}"
+                )));
+            };
+            let contents = rest
+                .get(..length)
+                .filter(|_| rest[length..].starts_with('\n'))
+                .ok_or_else(|| {
+                    ArchiveError::new(format!(
+                        "the {name} file is cut short, the archive may be truncated"
+                    ))
+                })?;
+            if hash.is_some_and(|hash| hash != checksum(contents)) {
+                return Err(ArchiveError::new(format!(
+                    "the {name} file does not match its hash, the archive is corrupted"
+                )));
+            }
+            files.push((name, contents.to_string()));
+            rest = &rest[length + 1..];
+        }
+        Ok(Self {
+            created,
+            version,
+            files,
+        })
+    }
+}
+
+/// Takes the next line off `rest`
+fn next_line<'a>(rest: &mut &'a str, what: &str) -> Result<&'a str, ArchiveError> {
+    let (line, tail) = rest
+        .split_once('\n')
+        .ok_or_else(|| ArchiveError::new(format!("the archive ends before {what}")))?;
+    *rest = tail;
+    Ok(line)
+}
+
+impl fmt::Display for Archive {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{MAGIC} {FORMAT}")?;
+        writeln!(f, "created {}", self.created)?;
+        writeln!(f, "version {}", self.version)?;
+        for (name, contents) in &self.files {
+            writeln!(f, "file {name} {} {}", contents.len(), checksum(contents))?;
+            writeln!(f, "{contents}")?;
+        }
+        writeln!(f, "end")
+    }
+}
+
+/// The 64-bit FNV-1a hash of `contents` as 16 hex digits. It is not a cryptographic hash, it only
+/// has to notice a file that was cut short or changed since the backup.
+fn checksum(contents: &str) -> String {
+    format!("{:016x}", fnv1a(FNV_OFFSET_BASIS, contents.as_bytes()))
+}
+
+/// The hash `fnv1a` starts from
+pub(crate) const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// Continues the 64-bit FNV-1a hash `hash` over `bytes`, so a file can be hashed a chunk at a time
+pub(crate) fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct ArchiveError {
+    pub message: String,
+}
+
+impl ArchiveError {
+    fn new(message: String) -> Self {
+        Self { message }
+    }
+}
+
+impl fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Not a valid tap backup: {}", self.message)
+    }
+}
+
+/// The UTC time as `YYYY-MM-DDTHH:MM:SSZ`, given in seconds since 1970-01-01
+pub(crate) fn utc_timestamp(secs: u64) -> String {
+    // Days since 1970-01-01 to a civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let days = (secs / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    let time = secs % 86_400;
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        time / 3_600,
+        time % 3_600 / 60,
+        time % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn archive() -> Archive {
+        Archive {
+            created: "2024-06-01T12:30:05Z".to_string(),
+            version: "0.1.0".to_string(),
+            files: vec![
+                (
+                    "data".to_string(),
+                    "work->\n  ci|https://ci.internal\n".to_string(),
+                ),
+                ("index".to_string(), "work|0\n".to_string()),
+                ("config".to_string(), String::new()),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_archive_display() {
+        assert_eq!(
+            archive().to_string(),
+            "tap-backup 2\ncreated 2024-06-01T12:30:05Z\nversion 0.1.0\nfile data 32 e2b105f168193fe5\nwork->\n  ci|https://ci.internal\n\nfile index 7 ac202402a91b1ab2\nwork|0\n\nfile config 0 cbf29ce484222325\n\nend\n"
+        );
+    }
+
+    #[test]
+    fn test_archive_round_trip() {
+        let text = archive().to_string();
+        assert!(text.starts_with("tap-backup 2\ncreated 2024-06-01T12:30:05Z\n"));
+        assert_eq!(Archive::parse(&text), Ok(archive()));
+        assert_eq!(archive().file("index"), Some("work|0\n"));
+        assert_eq!(archive().file("missing"), None);
+    }
+
+    #[test]
+    fn test_archive_parse_errors() {
+        let text = archive().to_string();
+        assert!(Archive::parse("tap-data\n").is_err());
+        // Cut off in the middle of the data file
+        let e = Archive::parse(&text[..100]).unwrap_err();
+        assert_eq!(
+            e.message,
+            "the data file is cut short, the archive may be truncated"
+        );
+        assert!(Archive::parse(text.trim_end_matches("end\n")).is_err());
+        assert!(Archive::parse(&text.replace("file index 7", "file index seven")).is_err());
+        // Format 2 archives need the hashes
+        assert!(Archive::parse(&text.replace(&format!(" {}", checksum("")), "")).is_err());
+    }
+
+    #[test]
+    fn test_archive_parse_format_1() {
+        let text = "tap-backup 1\ncreated 2024-06-01T12:30:05Z\nversion 0.1.0\nfile data 32\nwork->\n  ci|https://ci.internal\n\nfile index 7\nwork|0\n\nfile config 0\n\nend\n";
+        assert_eq!(Archive::parse(text), Ok(archive()));
+    }
+
+    #[test]
+    fn test_archive_parse_newer_format() {
+        let text = archive()
+            .to_string()
+            .replace("tap-backup 2", "tap-backup 3");
+        let e = Archive::parse(&text).unwrap_err();
+        assert_eq!(
+            e.message,
+            "the archive is of format 3, written by a newer version of tap, and this one reads up to format 2. Update tap to restore it"
+        );
+    }
+
+    #[test]
+    fn test_archive_parse_corrupted() {
+        // A link changed after the backup was taken
+        let text = archive().to_string().replace("ci.internal", "ci.external");
+        let e = Archive::parse(&text).unwrap_err();
+        assert_eq!(
+            e.message,
+            "the data file does not match its hash, the archive is corrupted"
+        );
+    }
+
+    #[test]
+    fn test_checksum() {
+        assert_eq!(checksum(""), "cbf29ce484222325");
+        assert_eq!(checksum("a"), "af63dc4c8601ec8c");
+        assert_ne!(checksum("work|0\n"), checksum("work|1\n"));
+    }
+
+    #[test]
+    fn test_utc_timestamp() {
+        assert_eq!(utc_timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(utc_timestamp(1_717_245_005), "2024-06-01T12:30:05Z");
+        assert_eq!(utc_timestamp(951_782_400), "2000-02-29T00:00:00Z");
+    }
+}
@@ -1,8 +1,12 @@
+use crate::utils::style;
 use std::fmt::Display;
 
 pub(crate) trait DisplayCommandAsRow {
     fn args(&self) -> Vec<String>;
     fn description(&self) -> String;
+    /// Example invocations as (command line, description) pairs, shown by `tap --examples` and in
+    /// the help message of the command
+    fn examples(&self) -> Vec<(String, String)>;
     fn name(&self) -> String;
 }
 
@@ -26,7 +30,7 @@ impl Ord for Row {
 }
 
 impl Row {
-    pub(crate) fn new(cmd: impl DisplayCommandAsRow) -> Self {
+    pub(crate) fn new(cmd: &(impl DisplayCommandAsRow + ?Sized)) -> Self {
         Self {
             args: cmd.args(),
             description: cmd.description(),
@@ -34,6 +38,15 @@ impl Row {
         }
     }
 
+    /// A row showing an example command line in the name column
+    pub(crate) fn example(line: &str, description: &str) -> Self {
+        Self {
+            args: vec![],
+            description: description.to_string(),
+            name: line.to_string(),
+        }
+    }
+
     fn size_by_param(&self) -> Vec<(String, usize)> {
         vec![
             ("name".to_string(), self.name.len()),
@@ -65,24 +78,63 @@ impl Section {
 
     fn pad(&self, s: &str, param_size_idx: usize) -> String {
         let mut res = s.to_string();
-        let pad_len = self.max_size_by_param[param_size_idx].1 - s.len();
-        res += &" ".repeat(pad_len);
+        let width = self.max_size_by_param[param_size_idx].1;
+        let width = match param_size_idx {
+            1 => width.min(ARGS_WIDTH),
+            _ => width,
+        };
+        res += &" ".repeat(width.saturating_sub(s.len()));
         res
     }
 }
 
+/// The widest the args column gets before its arguments wrap onto the next lines, which keeps
+/// the table of tap --help under 100 columns
+const ARGS_WIDTH: usize = 34;
+
+/// The widest the description column gets before its words wrap onto the next lines
+const DESCRIPTION_WIDTH: usize = 46;
+
+/// Joins `words` with spaces into lines of at most `width` characters. A word longer than
+/// `width` gets a line of its own.
+fn wrap<'a>(words: impl IntoIterator<Item = &'a str>, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    let mut line = String::new();
+    for word in words {
+        if !line.is_empty() && line.len() + 1 + word.len() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
 impl Display for Section {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         {
-            writeln!(f, "{}", self.title)?;
+            writeln!(f, "{}", style::title(&self.title))?;
             for element in &self.elements {
-                writeln!(
-                    f,
-                    " {} {} {}",
-                    self.pad(element.name.as_str(), 0),
-                    self.pad(element.args.join(" ").as_str(), 1),
-                    self.pad(element.description.as_str(), 2)
-                )?;
+                let args = wrap(element.args.iter().map(String::as_str), ARGS_WIDTH);
+                let description = wrap(element.description.split_whitespace(), DESCRIPTION_WIDTH);
+                for idx in 0..args.len().max(description.len()) {
+                    let name = match idx {
+                        0 => style::command(&self.pad(element.name.as_str(), 0)),
+                        _ => self.pad("", 0),
+                    };
+                    let line = format!(
+                        " {} {} {}",
+                        name,
+                        self.pad(args.get(idx).map_or("", String::as_str), 1),
+                        description.get(idx).map_or("", String::as_str)
+                    );
+                    writeln!(f, "{}", line.trim_end())?;
+                }
             }
         }
         Ok(())
@@ -91,17 +143,23 @@ impl Display for Section {
 
 pub(crate) struct UsageTable {
     title: String,
+    synopsis: bool,
     sections: Vec<Section>,
 }
 
 impl UsageTable {
-    fn new(title: String, sections: Vec<Section>) -> Self {
-        Self { title, sections }
+    fn new(title: String, synopsis: bool, sections: Vec<Section>) -> Self {
+        Self {
+            title,
+            synopsis,
+            sections,
+        }
     }
 }
 
 pub(crate) struct UsageTableBuilder {
     title: String,
+    synopsis: bool,
     sections: Option<Vec<Section>>,
 }
 
@@ -109,10 +167,17 @@ impl UsageTableBuilder {
     pub(crate) fn new(title: impl ToString) -> Self {
         Self {
             title: title.to_string(),
+            synopsis: true,
             sections: None,
         }
     }
 
+    /// Leaves out the `tap <command> <args> [options]` line under the title
+    pub(crate) fn without_synopsis(mut self) -> Self {
+        self.synopsis = false;
+        self
+    }
+
     pub(crate) fn add_section(mut self, title: impl ToString, elements: Vec<Row>) -> Self {
         let section = Section::new(title, elements);
         if let Some(sections) = &mut self.sections {
@@ -124,15 +189,17 @@ impl UsageTableBuilder {
     }
 
     pub(crate) fn build(self) -> UsageTable {
-        UsageTable::new(self.title, self.sections.unwrap_or_default())
+        UsageTable::new(self.title, self.synopsis, self.sections.unwrap_or_default())
     }
 }
 
 impl Display for UsageTable {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         {
-            writeln!(f, "{}", self.title)?;
-            writeln!(f, "  tap <command> <args> [options]\n")?;
+            writeln!(f, "{}", style::title(&self.title))?;
+            if self.synopsis {
+                writeln!(f, "  tap <command> <args> [options]\n")?;
+            }
             for section in &self.sections {
                 section.fmt(f)?
             }
@@ -158,6 +225,19 @@ fn max_of_params(a: &Vec<(String, usize)>, b: &Vec<(String, usize)>) -> Vec<(Str
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_wrap() {
+        assert_eq!(
+            wrap(["[--a]", "[--bb]", "[--ccc]"], 12),
+            vec!["[--a] [--bb]", "[--ccc]"]
+        );
+        assert_eq!(
+            wrap(["[--longer-than-width]"], 5),
+            vec!["[--longer-than-width]"]
+        );
+        assert_eq!(wrap([], 5), vec![""]);
+    }
+
     #[test]
     fn test_max_of_params() {
         let a = vec![
@@ -1,5 +1,21 @@
+use crate::utils::config::{Config, default_config_path};
+use crate::utils::log;
+use crate::utils::prompt::Prompt;
+use std::cell::RefCell;
+use std::io::{IsTerminal, Read};
+use std::ops::{Deref, DerefMut};
 use std::{env, fmt};
 
+/// The conventional end of options marker, see `Args`
+pub(crate) const END_OF_OPTIONS: &str = "--";
+
+/// Where a value given as `-` is read from. Commands use stdin, tests inject their own reader.
+pub(crate) type ValueInput = RefCell<Box<dyn Read>>;
+
+pub(crate) fn stdin_input() -> ValueInput {
+    RefCell::new(Box::new(std::io::stdin()))
+}
+
 pub(crate) fn get_current_directory_name() -> Result<String, CommandUtilError> {
     let current_dir = env::current_dir().map_err(|e| CommandUtilError {
         kind: CommandUtilErrorKind::CurrentDirectoryNotFound,
@@ -18,11 +34,499 @@ pub(crate) fn get_current_directory_name() -> Result<String, CommandUtilError> {
     Ok(current_dir_name.to_string())
 }
 
+/// The arguments of a command with the `--` end of options marker taken out. Arguments given after
+/// the marker are names as typed: they are never taken as flags, and `here` is not the current
+/// directory. Without a marker, every argument is parsed as before.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Args {
+    values: Vec<String>,
+    /// Index of the first argument given after `--`, if it was passed
+    literal_from: Option<usize>,
+}
+
+impl Args {
+    pub fn new(mut values: Vec<String>) -> Self {
+        let literal_from = values.iter().position(|a| a == END_OF_OPTIONS);
+        if let Some(i) = literal_from {
+            values.remove(i);
+        }
+        Self {
+            values,
+            literal_from,
+        }
+    }
+
+    /// Whether the argument at `i` was given after `--`
+    pub fn is_literal(&self, i: usize) -> bool {
+        self.literal_from.is_some_and(|from| i >= from)
+    }
+
+    /// Whether the argument at `i` is the flag `name`, which it never is after `--`
+    pub fn is_flag(&self, i: usize, name: &str) -> bool {
+        !self.is_literal(i) && self.values.get(i).is_some_and(|a| a == name)
+    }
+
+    /// Whether the `name` flag is given before `--`
+    pub fn has_flag(&self, name: &str) -> bool {
+        (0..self.values.len()).any(|i| self.is_flag(i, name))
+    }
+
+    /// Takes every one of the `names` flags given before `--` out of the arguments, returning
+    /// whether any was passed
+    pub fn take_flag(&mut self, names: &[&str]) -> bool {
+        let mut taken = false;
+        let mut i = 0;
+        while i < self.values.len() {
+            if !self.is_literal(i) && names.contains(&self.values[i].as_str()) {
+                self.values.remove(i);
+                if let Some(from) = self.literal_from.as_mut() {
+                    *from -= 1;
+                }
+                taken = true;
+            } else {
+                i += 1;
+            }
+        }
+        taken
+    }
+
+    /// Takes the `name` flag given before `--` out of the arguments along with the value after
+    /// it, returning the value. A flag given as the last argument has no value, so it is left in
+    /// place for the command to report as an unexpected argument.
+    pub fn take_option(&mut self, name: &str) -> Option<String> {
+        let i = (0..self.values.len().saturating_sub(1))
+            .find(|&i| self.is_flag(i, name) && !self.is_literal(i + 1))?;
+        let value = self.values.remove(i + 1);
+        self.values.remove(i);
+        if let Some(from) = self.literal_from.as_mut() {
+            *from -= 2;
+        }
+        Some(value)
+    }
+
+    /// The parent entity named by the argument at `i`, see `parent_arg`
+    pub fn parent(&self, i: usize) -> Result<String, CommandUtilError> {
+        parent_arg(&self.values[i], self.is_literal(i))
+    }
+
+    /// The arguments as they were passed, with `--` put back
+    pub fn into_vec(mut self) -> Vec<String> {
+        if let Some(i) = self.literal_from {
+            self.values.insert(i, END_OF_OPTIONS.to_string());
+        }
+        self.values
+    }
+}
+
+impl Deref for Args {
+    type Target = Vec<String>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.values
+    }
+}
+
+impl DerefMut for Args {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.values
+    }
+}
+
+/// The parent entity named by `arg`. Unless it is `literal` (given after `--`), `here` is the name
+/// of the current directory, and names starting with `-` are rejected since they look like flags.
+pub(crate) fn parent_arg(arg: &str, literal: bool) -> Result<String, CommandUtilError> {
+    match arg {
+        _ if literal => Ok(arg.to_string()),
+        "here" => get_current_directory_name(),
+        _ if arg.starts_with('-') => Err(CommandUtilError {
+            kind: CommandUtilErrorKind::ReservedName,
+            message: format!(
+                "Parent entity name {arg} starts with '-' which is reserved for commands, pass it after -- to use it as a name"
+            ),
+        }),
+        _ => Ok(arg.to_string()),
+    }
+}
+
+/// Returns `value`, unless it is `-`, in which case the value is read from `input` until EOF with
+/// the trailing newline removed. The value read must be a single, non-empty line.
+pub(crate) fn resolve_value(value: &str, input: &ValueInput) -> Result<String, CommandUtilError> {
+    if value != "-" {
+        return Ok(value.to_string());
+    }
+    if std::io::stdin().is_terminal() {
+        log::note(format_args!(
+            "reading value from stdin, press Ctrl-D to finish"
+        ));
+    }
+    let mut value = String::new();
+    input
+        .borrow_mut()
+        .read_to_string(&mut value)
+        .map_err(|e| CommandUtilError {
+            kind: CommandUtilErrorKind::StdinReadFailed,
+            message: format!("Could not read value from stdin: {e}"),
+        })?;
+    let value = value
+        .strip_suffix('\n')
+        .map(|v| v.strip_suffix('\r').unwrap_or(v))
+        .unwrap_or(&value);
+    if value.is_empty() {
+        return Err(CommandUtilError {
+            kind: CommandUtilErrorKind::InvalidValue,
+            message: "No value was read from stdin".to_string(),
+        });
+    }
+    if value.contains('\n') {
+        return Err(CommandUtilError {
+            kind: CommandUtilErrorKind::InvalidValue,
+            message: "The value read from stdin must be a single line".to_string(),
+        });
+    }
+    Ok(value.to_string())
+}
+
+/// The config file key setting how many links are opened at once without asking, see `OpenLimit`
+const OPEN_LIMIT_CONFIG_KEY: &str = "open_limit";
+
+/// The config file key saying what to do with more links than the limit when there is no
+/// terminal to ask in: `open` them (the default) or `refuse`
+const OPEN_UNCONFIRMED_CONFIG_KEY: &str = "open_unconfirmed";
+
+/// The config file key saying how to ask about more links than the limit: `confirm` them all
+/// (the default) or `pick` some of them from a numbered list
+const OPEN_MANY_CONFIG_KEY: &str = "open_many";
+
+/// The config file key saying whether `tap <Parent Entity>` opens every link of a parent entity
+/// without a default link (`true`, the default), or lists them to name one or pass `--all`
+const OPEN_ALL_DEFAULT_CONFIG_KEY: &str = "open_all_default";
+
+/// The config file key capping how many links one command opens, see `OpenLimit::check_max`
+const MAX_OPEN_CONFIG_KEY: &str = "max_open";
+
+/// How many links are opened at once before asking, so `tap <Parent Entity>` does not suddenly
+/// open dozens of browser tabs
+#[derive(Debug, PartialEq)]
+pub(crate) struct OpenLimit {
+    /// Opening more links than this asks for confirmation
+    pub limit: usize,
+    /// Without a terminal, more links than the limit are an error instead of being opened
+    pub refuse_unconfirmed: bool,
+    /// In a terminal, more links than the limit are picked from a list instead of confirmed
+    pub pick: bool,
+    /// All links of a parent entity are opened when none is named, otherwise only with `--all`
+    pub open_all: bool,
+    /// Opening more links than this is an error rather than a question
+    pub max_open: Option<usize>,
+}
+
+impl Default for OpenLimit {
+    fn default() -> Self {
+        Self {
+            limit: 5,
+            refuse_unconfirmed: false,
+            pick: false,
+            open_all: true,
+            max_open: None,
+        }
+    }
+}
+
+impl OpenLimit {
+    /// The limit set in the config file. Anything it does not set, or a config file that can not
+    /// be read, keeps the default.
+    pub fn load() -> Self {
+        let config = default_config_path().and_then(|path| Config::load(Some(path)).ok());
+        let get = |key: &str| config.as_ref().and_then(|c| c.get(key).map(str::trim));
+        let default = Self::default();
+        Self {
+            limit: get(OPEN_LIMIT_CONFIG_KEY)
+                .and_then(|limit| limit.parse().ok())
+                .unwrap_or(default.limit),
+            refuse_unconfirmed: get(OPEN_UNCONFIRMED_CONFIG_KEY)
+                .map_or(default.refuse_unconfirmed, |v| v == "refuse"),
+            pick: get(OPEN_MANY_CONFIG_KEY).map_or(default.pick, |v| v == "pick"),
+            open_all: get(OPEN_ALL_DEFAULT_CONFIG_KEY)
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default.open_all),
+            // A single link can always be opened
+            max_open: get(MAX_OPEN_CONFIG_KEY)
+                .and_then(|max| max.parse().ok())
+                .filter(|&max| max > 0)
+                .or(default.max_open),
+        }
+    }
+
+    /// Fails when opening `count` links goes over `max_open`, unless `force` lifts it for this run.
+    /// Unlike `confirm`, nothing is asked, so scripts never hang on it.
+    pub fn check_max(&self, count: usize, force: bool) -> Result<(), CommandUtilError> {
+        match self.max_open {
+            Some(max) if count > max && !force => Err(CommandUtilError {
+                kind: CommandUtilErrorKind::TooManyLinks,
+                message: format!(
+                    "Opening {count} links is more than the {max} allowed by {MAX_OPEN_CONFIG_KEY} in the config file, pass --force to open them anyway"
+                ),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether `count` links are to be opened. Up to the limit, or with `yes`, they always are.
+    /// Otherwise the user is asked when run in a terminal, and without one they are opened unless
+    /// `refuse_unconfirmed` is set.
+    pub fn confirm(
+        &self,
+        prompt: &Prompt,
+        count: usize,
+        yes: bool,
+    ) -> Result<bool, CommandUtilError> {
+        if count <= self.limit || yes {
+            return Ok(true);
+        }
+        if !prompt.is_interactive() {
+            return match self.refuse_unconfirmed {
+                true => Err(CommandUtilError {
+                    kind: CommandUtilErrorKind::ConfirmationRequired,
+                    message: format!(
+                        "Opening {count} links at once needs confirmation, pass --yes to open them"
+                    ),
+                }),
+                false => Ok(true),
+            };
+        }
+        prompt
+            .confirm(&format!("Open {count} links at once?"))
+            .map_err(|e| CommandUtilError {
+                kind: CommandUtilErrorKind::StdinReadFailed,
+                message: e.to_string(),
+            })
+    }
+}
+
+/// Splits the arguments after the parent entity into link and value pairs. A pair is either one
+/// `link=value` argument, split on the first `=` that is not escaped as `\=`, or a link followed by
+/// its value. Returns `None` if the last link has no value.
+pub(crate) fn link_value_pairs(
+    args: &[String],
+) -> Result<Option<Vec<(String, String)>>, CommandUtilError> {
+    let mut pairs = vec![];
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match split_link_value(arg) {
+            Some((link, "")) => {
+                return Err(CommandUtilError {
+                    kind: CommandUtilErrorKind::InvalidValue,
+                    message: format!(
+                        "Link {link} has no value after '=', pass it as {link}=<Value>"
+                    ),
+                });
+            }
+            Some((link, value)) => pairs.push((link, value.to_string())),
+            None => match args.next() {
+                Some(value) => pairs.push((arg.replace("\\=", "="), value.clone())),
+                None => return Ok(None),
+            },
+        }
+    }
+    Ok(Some(pairs))
+}
+
+/// The link name and value of a `link=value` argument, with `\=` in the link name unescaped
+fn split_link_value(arg: &str) -> Option<(String, &str)> {
+    let mut escaped = false;
+    for (i, c) in arg.char_indices() {
+        match c {
+            '=' if !escaped => return Some((arg[..i].replace("\\=", "="), &arg[i + 1..])),
+            _ => escaped = c == '\\' && !escaped,
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(s: &'static str) -> ValueInput {
+        RefCell::new(Box::new(s.as_bytes()))
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn test_open_limit_confirm() {
+        let limit = OpenLimit::default();
+        // Up to the limit, and with --yes, nothing is asked
+        assert!(limit.confirm(&Prompt::scripted(""), 5, false).unwrap());
+        assert!(limit.confirm(&Prompt::scripted(""), 40, true).unwrap());
+        assert!(limit.confirm(&Prompt::scripted("y\n"), 6, false).unwrap());
+        assert!(!limit.confirm(&Prompt::scripted("n\n"), 6, false).unwrap());
+        // Without a terminal, the links are opened unless the config file says otherwise
+        assert!(limit.confirm(&Prompt::stdin(false), 6, false).unwrap());
+        let limit = OpenLimit {
+            refuse_unconfirmed: true,
+            ..OpenLimit::default()
+        };
+        let err = limit.confirm(&Prompt::stdin(false), 6, false).unwrap_err();
+        assert_eq!(err.kind, CommandUtilErrorKind::ConfirmationRequired);
+        assert!(limit.confirm(&Prompt::stdin(false), 6, true).unwrap());
+    }
+
+    #[test]
+    fn test_open_limit_check_max() {
+        assert!(OpenLimit::default().check_max(1000, false).is_ok());
+        let limit = OpenLimit {
+            max_open: Some(3),
+            ..OpenLimit::default()
+        };
+        assert!(limit.check_max(1, false).is_ok());
+        assert!(limit.check_max(3, false).is_ok());
+        let err = limit.check_max(4, false).unwrap_err();
+        assert_eq!(err.kind, CommandUtilErrorKind::TooManyLinks);
+        assert!(
+            err.message
+                .starts_with("Opening 4 links is more than the 3 allowed by max_open")
+        );
+        assert!(limit.check_max(4, true).is_ok());
+    }
+
+    #[test]
+    fn test_args_without_end_of_options() {
+        let mut parsed = Args::new(args(&["here", "--yes"]));
+        assert!(parsed.take_flag(&["-y", "--yes"]));
+        assert_eq!(*parsed, args(&["here"]));
+        assert!(!parsed.is_literal(0));
+        assert_eq!(
+            parsed.parent(0).unwrap(),
+            get_current_directory_name().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_args_after_end_of_options() {
+        let mut parsed = Args::new(args(&["-y", "--", "here", "--yes"]));
+        assert!(parsed.take_flag(&["-y", "--yes"]));
+        assert_eq!(*parsed, args(&["here", "--yes"]));
+        assert!(parsed.is_literal(0));
+        assert!(!parsed.is_flag(1, "--yes"));
+        assert_eq!(parsed.parent(0).unwrap(), "here");
+        assert_eq!(parsed.into_vec(), args(&["--", "here", "--yes"]));
+    }
+
+    #[test]
+    fn test_args_take_option() {
+        let mut parsed = Args::new(args(&["jira", "--in", "values", "--", "--in", "x"]));
+        assert_eq!(parsed.take_option("--in"), Some("values".to_string()));
+        assert_eq!(parsed.take_option("--in"), None);
+        assert_eq!(parsed.into_vec(), args(&["jira", "--", "--in", "x"]));
+        let mut parsed = Args::new(args(&["jira", "--in"]));
+        assert_eq!(parsed.take_option("--in"), None);
+        assert_eq!(*parsed, args(&["jira", "--in"]));
+    }
+
+    #[test]
+    fn test_parent_arg() {
+        assert_eq!(parent_arg("work", false).unwrap(), "work");
+        assert_eq!(parent_arg("--show", true).unwrap(), "--show");
+        assert_eq!(
+            parent_arg("--show", false).unwrap_err().kind,
+            CommandUtilErrorKind::ReservedName
+        );
+    }
+
+    #[test]
+    fn test_resolve_value_not_dash() {
+        let res = resolve_value("https://google.com", &input("ignored\n"));
+        assert_eq!(res.unwrap(), "https://google.com");
+    }
+
+    #[test]
+    fn test_resolve_value_trims_trailing_newline() {
+        let res = resolve_value("-", &input("https://google.com\n"));
+        assert_eq!(res.unwrap(), "https://google.com");
+        let res = resolve_value("-", &input("https://google.com\r\n"));
+        assert_eq!(res.unwrap(), "https://google.com");
+        let res = resolve_value("-", &input("https://google.com"));
+        assert_eq!(res.unwrap(), "https://google.com");
+    }
+
+    #[test]
+    fn test_resolve_value_multiple_lines() {
+        let res = resolve_value("-", &input("https://google.com\nhttps://bing.com\n"));
+        assert_eq!(res.unwrap_err().kind, CommandUtilErrorKind::InvalidValue);
+    }
+
+    #[test]
+    fn test_resolve_value_empty() {
+        let res = resolve_value("-", &input(""));
+        assert_eq!(res.unwrap_err().kind, CommandUtilErrorKind::InvalidValue);
+        let res = resolve_value("-", &input("\n"));
+        assert_eq!(res.unwrap_err().kind, CommandUtilErrorKind::InvalidValue);
+    }
+
+    fn pairs(pairs: &[(&str, &str)]) -> Option<Vec<(String, String)>> {
+        Some(
+            pairs
+                .iter()
+                .map(|(l, v)| (l.to_string(), v.to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_link_value_pairs_separate() {
+        let res = link_value_pairs(&args(&["google", "https://google.com"])).unwrap();
+        assert_eq!(res, pairs(&[("google", "https://google.com")]));
+        assert_eq!(link_value_pairs(&args(&["google"])).unwrap(), None);
+    }
+
+    #[test]
+    fn test_link_value_pairs_key_value() {
+        let res = link_value_pairs(&args(&[
+            "jira=https://jira.internal/ABC?a=b",
+            "ci",
+            "https://ci.me?x=1",
+            "docs=-",
+        ]))
+        .unwrap();
+        assert_eq!(
+            res,
+            pairs(&[
+                ("jira", "https://jira.internal/ABC?a=b"),
+                ("ci", "https://ci.me?x=1"),
+                ("docs", "-"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_link_value_pairs_escaped_equals() {
+        let res = link_value_pairs(&args(&["a\\=b=https://c.com", "x\\=y", "https://z.com"]));
+        assert_eq!(
+            res.unwrap(),
+            pairs(&[("a=b", "https://c.com"), ("x=y", "https://z.com")])
+        );
+    }
+
+    #[test]
+    fn test_link_value_pairs_empty_value() {
+        let res = link_value_pairs(&args(&["jira="]));
+        assert_eq!(res.unwrap_err().kind, CommandUtilErrorKind::InvalidValue);
+    }
+}
+
 // Errors
 #[derive(Debug, PartialEq)]
 pub enum CommandUtilErrorKind {
     CastError,
+    ConfirmationRequired,
     CurrentDirectoryNotFound,
+    InvalidValue,
+    ReservedName,
+    StdinReadFailed,
+    TooManyLinks,
     UnableToGetCurrentDirectoryName,
 }
 
@@ -42,9 +546,14 @@ impl fmt::Display for CommandUtilErrorKind {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             CommandUtilErrorKind::CastError => write!(f, "Cast error"),
+            CommandUtilErrorKind::ConfirmationRequired => write!(f, "Confirmation required"),
             CommandUtilErrorKind::CurrentDirectoryNotFound => {
                 write!(f, "Current directory not found")
             }
+            CommandUtilErrorKind::InvalidValue => write!(f, "Invalid value"),
+            CommandUtilErrorKind::ReservedName => write!(f, "Reserved name"),
+            CommandUtilErrorKind::StdinReadFailed => write!(f, "Stdin read failed"),
+            CommandUtilErrorKind::TooManyLinks => write!(f, "Too many links"),
             CommandUtilErrorKind::UnableToGetCurrentDirectoryName => {
                 write!(f, "Unable to get current directory name")
             }
@@ -0,0 +1,184 @@
+use std::{fmt, fs, path::PathBuf};
+
+/// The user's tap config file, made of `key = value` lines. Lines starting with `#` are comments.
+/// By default it is stored in `$XDG_CONFIG_HOME/tap/config` (or `~/.config/tap/config`).
+#[derive(Debug, PartialEq)]
+pub(crate) struct Config {
+    path: PathBuf,
+    entries: Vec<(String, String)>,
+}
+
+impl Config {
+    /// Loads the config file at the given (or default) path. A missing file is an empty config.
+    pub fn load(path: Option<PathBuf>) -> Result<Self, ConfigError> {
+        let path = match path {
+            Some(path) => path,
+            None => default_config_path().ok_or(ConfigError {
+                kind: ConfigErrorKind::ConfigPathNotFound,
+                message: "Could not find the config directory, set XDG_CONFIG_HOME or HOME"
+                    .to_string(),
+            })?,
+        };
+        let entries = if path.exists() {
+            let file_as_str = fs::read_to_string(&path).map_err(|e| ConfigError {
+                kind: ConfigErrorKind::FileReadFailed,
+                message: format!("Could not read config file at {}: {e}", path.display()),
+            })?;
+            Config::parse(&file_as_str)?
+        } else {
+            vec![]
+        };
+        Ok(Self { path, entries })
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn set(&mut self, key: &str, value: &str) {
+        match self.entries.iter_mut().find(|(k, _)| k == key) {
+            Some((_, v)) => *v = value.to_string(),
+            None => self.entries.push((key.to_string(), value.to_string())),
+        }
+    }
+
+    /// Writes the config file, creating its directory if needed
+    pub fn save(&self) -> Result<(), ConfigError> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(|e| ConfigError {
+                kind: ConfigErrorKind::FileWriteFailed,
+                message: format!("Could not create config directory {}: {e}", dir.display()),
+            })?;
+        }
+        let file_as_str: String = self
+            .entries
+            .iter()
+            .map(|(k, v)| format!("{k} = {v}\n"))
+            .collect();
+        fs::write(&self.path, file_as_str).map_err(|e| ConfigError {
+            kind: ConfigErrorKind::FileWriteFailed,
+            message: format!(
+                "Could not write config file at {}: {e}",
+                self.path.display()
+            ),
+        })
+    }
+
+    fn parse(file_as_str: &str) -> Result<Vec<(String, String)>, ConfigError> {
+        let mut entries = vec![];
+        for line in file_as_str.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line.split_once('=').ok_or(ConfigError {
+                kind: ConfigErrorKind::ParseError,
+                message: format!(
+                    "Line '{line}' of config file does not match expected format of key = value"
+                ),
+            })?;
+            entries.push((key.trim().to_string(), value.trim().to_string()));
+        }
+        Ok(entries)
+    }
+}
+
+/// `$XDG_CONFIG_HOME/tap/config` or `~/.config/tap/config`
+pub(crate) fn default_config_path() -> Option<PathBuf> {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_home.join("tap").join("config"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config_path() -> PathBuf {
+        let thread = std::thread::current();
+        let test_name = thread.name().expect("Could not get thread name");
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        std::env::temp_dir()
+            .join(format!("tap_config_{test_name}_{timestamp}"))
+            .join("config")
+    }
+
+    #[test]
+    fn test_parse() {
+        let entries =
+            Config::parse("# where the links live\ndata_dir = /home/me/tap\n\nreadonly=true\n")
+                .unwrap();
+        assert_eq!(
+            entries,
+            vec![
+                ("data_dir".to_string(), "/home/me/tap".to_string()),
+                ("readonly".to_string(), "true".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_line() {
+        let res = Config::parse("data_dir /home/me/tap\n");
+        assert_eq!(res.unwrap_err().kind, ConfigErrorKind::ParseError);
+    }
+
+    #[test]
+    fn test_load_missing_file() {
+        let config = Config::load(Some(test_config_path())).unwrap();
+        assert_eq!(config.get("data_dir"), None);
+    }
+
+    #[test]
+    fn test_set_save_load() {
+        let path = test_config_path();
+        let mut config = Config::load(Some(path.clone())).unwrap();
+        config.set("data_dir", "/tmp/a");
+        config.set("data_dir", "/tmp/b");
+        config.save().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "data_dir = /tmp/b\n");
+        let config = Config::load(Some(path.clone())).unwrap();
+        assert_eq!(config.get("data_dir"), Some("/tmp/b"));
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
+
+// Errors
+#[derive(Debug, PartialEq)]
+pub enum ConfigErrorKind {
+    ConfigPathNotFound,
+    FileReadFailed,
+    FileWriteFailed,
+    ParseError,
+}
+
+#[derive(Debug)]
+pub struct ConfigError {
+    kind: ConfigErrorKind,
+    message: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (config error: {})", self.message, self.kind)
+    }
+}
+
+impl fmt::Display for ConfigErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigErrorKind::ConfigPathNotFound => write!(f, "Config path not found"),
+            ConfigErrorKind::FileReadFailed => write!(f, "File read failed"),
+            ConfigErrorKind::FileWriteFailed => write!(f, "File write failed"),
+            ConfigErrorKind::ParseError => write!(f, "Parse error"),
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use crate::utils::command::{ValueInput, stdin_input};
+use crate::utils::format::Format;
+use crate::utils::log::Level;
+use crate::utils::prompt::Prompt;
+use crate::utils::tap_data_store::{DataStoreHandle, StoreOptions};
+
+/// What a command runs against, built once by `cli::run` after the global flags are read and
+/// passed to `Command::run`. Commands get the data store, prompts, and stdin from here instead of
+/// opening them themselves, so tests can run any command against an in memory store.
+pub(crate) struct Context {
+    /// Opened the first time a command reads or writes it, from the files picked by the global
+    /// `--file`, `--index` and `--profile` flags, see `StoreOptions`
+    pub store: DataStoreHandle,
+    /// Asks for missing arguments, never when `--no-input` is set
+    pub prompt: Prompt,
+    /// Read when a value is `-`
+    pub input: ValueInput,
+    /// Set by the global `--format` flag
+    pub format: Format,
+    /// Set by the global `-q/--quiet` and `--verbose` flags
+    pub level: Level,
+    /// Set by the global `--no-color` flag
+    pub no_color: bool,
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        Self {
+            store: DataStoreHandle::new(StoreOptions::default()),
+            prompt: Prompt::stdin(false),
+            input: stdin_input(),
+            format: Format::default(),
+            level: Level::Normal,
+            no_color: false,
+        }
+    }
+}
+
+#[cfg(test)]
+impl Context {
+    /// A context around an in memory store holding `state`, prompts are not interactive
+    pub fn in_memory(state: Vec<(String, Vec<(String, String)>)>) -> Self {
+        Self {
+            store: DataStoreHandle::in_memory(state),
+            ..Self::default()
+        }
+    }
+
+    /// A context around the data file `data`, indexed by its sibling, like `tap --file <data>`
+    pub fn with_file(data: std::path::PathBuf) -> Self {
+        Self {
+            store: DataStoreHandle::new(StoreOptions::default().with_files(data, None)),
+            ..Self::default()
+        }
+    }
+}
@@ -0,0 +1,138 @@
+/// `value` with every `$NAME` and `${NAME}` replaced by the value `lookup` gives for the variable
+/// `NAME`, and `\$` by a literal `$`. A `$` not followed by a name is kept as it is. Fails with the
+/// reason, naming the variable, when a variable is not set or a `${` is not a `${NAME}`.
+pub(crate) fn expand(
+    value: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<String, String> {
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(i) = rest.find(['\\', '$']) {
+        expanded.push_str(&rest[..i]);
+        rest = &rest[i..];
+        if let Some(after) = rest.strip_prefix("\\$") {
+            expanded.push('$');
+            rest = after;
+            continue;
+        }
+        if let Some(after) = rest.strip_prefix('\\') {
+            expanded.push('\\');
+            rest = after;
+            continue;
+        }
+        let after = &rest[1..];
+        let (name, len) = match after.strip_prefix('{') {
+            Some(braced) => match braced.split_once('}') {
+                Some((name, _)) if is_name(name) => (name, name.len() + 3),
+                _ => {
+                    let reference: String = rest.chars().take(20).collect();
+                    return Err(format!(
+                        "Expected a variable name like ${{HOME}} at {reference}, write \\$ for a literal $"
+                    ));
+                }
+            },
+            None => {
+                let len = name_len(after);
+                (&after[..len], len + 1)
+            }
+        };
+        if name.is_empty() {
+            expanded.push('$');
+            rest = after;
+            continue;
+        }
+        match lookup(name) {
+            Some(value) => expanded.push_str(&value),
+            None => return Err(format!("Environment variable {name} is not set")),
+        }
+        rest = &rest[len..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// Whether `name` is a variable name: a letter or `_`, then letters, digits, and `_`
+fn is_name(name: &str) -> bool {
+    !name.is_empty() && name_len(name) == name.len()
+}
+
+/// The length of the variable name `s` starts with, 0 when it does not start with one
+fn name_len(s: &str) -> usize {
+    let mut chars = s.char_indices();
+    match chars.next() {
+        Some((_, c)) if c.is_ascii_alphabetic() || c == '_' => chars
+            .find(|(_, c)| !(c.is_ascii_alphanumeric() || *c == '_'))
+            .map_or(s.len(), |(i, _)| i),
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "REPO_ROOT" => Some("/home/me/tap".to_string()),
+            "JIRA_HOST" => Some("jira.internal".to_string()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_expand_set_variables() {
+        assert_eq!(
+            expand("$REPO_ROOT/docs/index.html", lookup),
+            Ok("/home/me/tap/docs/index.html".to_string())
+        );
+        assert_eq!(
+            expand("https://${JIRA_HOST}/browse/ABC-1", lookup),
+            Ok("https://jira.internal/browse/ABC-1".to_string())
+        );
+        assert_eq!(
+            expand("${REPO_ROOT}_old/$EMPTY.", lookup),
+            Ok("/home/me/tap_old/.".to_string())
+        );
+    }
+
+    #[test]
+    fn test_expand_unset_variable() {
+        assert_eq!(
+            expand("https://$NOT_SET/x", lookup),
+            Err("Environment variable NOT_SET is not set".to_string())
+        );
+        assert_eq!(
+            expand("${NOT_SET}", lookup),
+            Err("Environment variable NOT_SET is not set".to_string())
+        );
+    }
+
+    #[test]
+    fn test_expand_escaped_and_literal_dollars() {
+        assert_eq!(
+            expand("price \\$5 and \\$REPO_ROOT", lookup),
+            Ok("price $5 and $REPO_ROOT".to_string())
+        );
+        assert_eq!(
+            expand("costs 5$ or $1, C:\\dir", lookup),
+            Ok("costs 5$ or $1, C:\\dir".to_string())
+        );
+    }
+
+    #[test]
+    fn test_expand_braces() {
+        // Template placeholders are left for `template::fill`
+        assert_eq!(
+            expand("https://${JIRA_HOST}/{query}", lookup),
+            Ok("https://jira.internal/{query}".to_string())
+        );
+        assert_eq!(
+            expand("${JIRA_HOST}}", lookup),
+            Ok("jira.internal}".to_string())
+        );
+        assert!(expand("${JIRA_${EMPTY}}", lookup).is_err());
+        assert!(expand("${}", lookup).is_err());
+        assert!(expand("${JIRA_HOST", lookup).is_err());
+    }
+}
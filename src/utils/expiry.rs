@@ -0,0 +1,97 @@
+use crate::utils::backup::utc_timestamp;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The day an expiring link expires on, given as a date like `2024-07-01` or as a number of
+/// days (`30d`) or weeks (`2w`) from today. Dates are UTC and written as `YYYY-MM-DD`.
+pub(crate) fn parse(when: &str) -> Result<String, String> {
+    let when = when.trim();
+    if is_date(when) {
+        return Ok(when.to_string());
+    }
+    let days = match when.split_at(when.len().saturating_sub(1)) {
+        (n, "d") => n.parse::<u64>().ok(),
+        (n, "w") => n.parse::<u64>().ok().and_then(|n| n.checked_mul(7)),
+        _ => None,
+    };
+    match days {
+        Some(days) if days > 0 && days < 365_000 => Ok(date_of(now() + days * 86_400)),
+        _ => Err(format!(
+            "'{when}' is not a date like 2024-07-01 or a number of days or weeks like 30d or 2w"
+        )),
+    }
+}
+
+/// Whether `s` is a valid date written as `YYYY-MM-DD`
+pub(crate) fn is_date(s: &str) -> bool {
+    let parts: Vec<&str> = s.split('-').collect();
+    let [year, month, day] = parts.as_slice() else {
+        return false;
+    };
+    if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return false;
+    }
+    let (Ok(year), Ok(month), Ok(day)) = (year.parse::<u32>(), month.parse(), day.parse()) else {
+        return false;
+    };
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days_in_month = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        1..=12 => 31,
+        _ => return false,
+    };
+    (1..=days_in_month).contains(&day)
+}
+
+/// Whether a link expiring on `date` has expired. Links expire at the start of that day (UTC).
+pub(crate) fn is_expired(date: &str) -> bool {
+    // Dates sort like the days they stand for, so they are compared as they are
+    date <= today().as_str()
+}
+
+/// Today's date (UTC) as `YYYY-MM-DD`
+pub(crate) fn today() -> String {
+    date_of(now())
+}
+
+fn date_of(secs: u64) -> String {
+    utc_timestamp(secs)[..10].to_string()
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(parse("2024-07-01"), Ok("2024-07-01".to_string()));
+        assert_eq!(parse(" 2024-02-29 "), Ok("2024-02-29".to_string()));
+        assert!(parse("2023-02-29").is_err());
+        assert!(parse("2024-13-01").is_err());
+        assert!(parse("2024-7-1").is_err());
+        assert!(parse("tomorrow").is_err());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse("30d"), Ok(date_of(now() + 30 * 86_400)));
+        assert_eq!(parse("2w"), Ok(date_of(now() + 14 * 86_400)));
+        assert!(parse("0d").is_err());
+        assert!(parse("d").is_err());
+        assert!(parse("3m").is_err());
+    }
+
+    #[test]
+    fn test_is_expired() {
+        assert!(is_expired("2000-01-01"));
+        assert!(is_expired(&today()));
+        assert!(!is_expired(&parse("1d").unwrap()));
+    }
+}
@@ -0,0 +1,44 @@
+use std::fmt;
+
+/// How results are printed, set by the global `--format` flag. Only results with structure, the
+/// lists and tables of the read commands, are affected: plain values like help messages are
+/// printed as they are in every format.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) enum Format {
+    /// The output meant for people, and the one completion scripts read
+    #[default]
+    Plain,
+    Json,
+    Yaml,
+    /// Lists, rows, and tables are drawn with borders around their columns
+    Table,
+}
+
+impl Format {
+    pub const ALL: [Format; 4] = [Format::Plain, Format::Json, Format::Yaml, Format::Table];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|format| format.name() == name)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Format::Plain => "plain",
+            Format::Json => "json",
+            Format::Yaml => "yaml",
+            Format::Table => "table",
+        }
+    }
+
+    /// Whether results are printed as data for other programs, commands with a `--json` flag
+    /// build the same data for these formats
+    pub fn is_structured(self) -> bool {
+        matches!(self, Format::Json | Format::Yaml)
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
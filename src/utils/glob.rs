@@ -0,0 +1,198 @@
+use std::fmt;
+
+/// A glob matching link names as a whole, where `*` stands for any run of characters (also
+/// none), `?` for exactly one character, and `[...]` for one character of a class like `[abc]`,
+/// `[0-9]`, or `[!-]` for any character but those. Every other character matches itself, case
+/// sensitively like link names. A `[` that is not closed is refused rather than matched as
+/// itself, write `[[]` for a literal `[`.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Glob {
+    tokens: Vec<Token>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Star,
+    Question,
+    Class {
+        ranges: Vec<(char, char)>,
+        negated: bool,
+    },
+    Char(char),
+}
+
+impl Token {
+    /// Whether the token consumes `c`, `*` being handled by `Glob::matches`
+    fn matches(&self, c: char) -> bool {
+        match self {
+            Token::Star => false,
+            Token::Question => true,
+            Token::Class { ranges, negated } => {
+                ranges.iter().any(|&(lo, hi)| lo <= c && c <= hi) != *negated
+            }
+            Token::Char(t) => *t == c,
+        }
+    }
+}
+
+impl Glob {
+    pub fn new(pattern: &str) -> Result<Self, GlobError> {
+        let chars: Vec<char> = pattern.chars().collect();
+        let mut tokens = vec![];
+        let mut pos = 0;
+        while pos < chars.len() {
+            let token = match chars[pos] {
+                '*' => Token::Star,
+                '?' => Token::Question,
+                '[' => {
+                    let (token, end) = class(&chars, pos).ok_or_else(|| GlobError {
+                        pattern: pattern.to_string(),
+                        message: "a [ is not closed, write [[] for a literal [".to_string(),
+                    })?;
+                    pos = end;
+                    token
+                }
+                c => Token::Char(c),
+            };
+            tokens.push(token);
+            pos += 1;
+        }
+        Ok(Self { tokens })
+    }
+
+    /// Whether `name` matches the glob as a whole
+    pub fn matches(&self, name: &str) -> bool {
+        let name: Vec<char> = name.chars().collect();
+        let (mut p, mut n) = (0, 0);
+        // Where the last `*` was seen and the name position it was tried at, to backtrack to
+        // when the rest of the pattern fails to match
+        let mut star: Option<(usize, usize)> = None;
+        while n < name.len() {
+            match self.tokens.get(p) {
+                Some(Token::Star) => {
+                    star = Some((p, n));
+                    p += 1;
+                }
+                Some(token) if token.matches(name[n]) => {
+                    p += 1;
+                    n += 1;
+                }
+                _ => match star {
+                    Some((star_p, star_n)) => {
+                        // Let the `*` take one more character and try again after it
+                        p = star_p + 1;
+                        n = star_n + 1;
+                        star = Some((star_p, star_n + 1));
+                    }
+                    None => return false,
+                },
+            }
+        }
+        self.tokens[p..].iter().all(|t| *t == Token::Star)
+    }
+}
+
+/// The class starting with the `[` at `start` and the position of its `]`, `None` when it is not
+/// closed. A `]` right after the `[` (or `[!`) is part of the class.
+fn class(chars: &[char], start: usize) -> Option<(Token, usize)> {
+    let mut pos = start + 1;
+    let negated = chars.get(pos) == Some(&'!');
+    if negated {
+        pos += 1;
+    }
+    let first = pos;
+    let mut ranges = vec![];
+    loop {
+        let c = *chars.get(pos)?;
+        if c == ']' && pos != first {
+            return Some((Token::Class { ranges, negated }, pos));
+        }
+        // A `-` before the closing `]` is a literal dash
+        match (chars.get(pos + 1), chars.get(pos + 2)) {
+            (Some('-'), Some(&hi)) if hi != ']' => {
+                ranges.push((c.min(hi), c.max(hi)));
+                pos += 3;
+            }
+            _ => {
+                ranges.push((c, c));
+                pos += 1;
+            }
+        }
+    }
+}
+
+/// A glob that can not be parsed
+#[derive(Debug, PartialEq)]
+pub(crate) struct GlobError {
+    pattern: String,
+    message: String,
+}
+
+impl fmt::Display for GlobError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid pattern '{}', {}", self.pattern, self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, name: &str) -> bool {
+        Glob::new(pattern).unwrap().matches(name)
+    }
+
+    #[test]
+    fn test_matches_literal() {
+        assert!(matches("jira", "jira"));
+        assert!(!matches("jira", "jira2"));
+        assert!(!matches("jira", "Jira"));
+        assert!(matches("", ""));
+    }
+
+    #[test]
+    fn test_matches_star() {
+        assert!(matches("imported-*", "imported-"));
+        assert!(matches("imported-*", "imported-42"));
+        assert!(!matches("imported-*", "old-imported-42"));
+        assert!(matches("*-dashboard", "old-dashboard"));
+        assert!(matches("a*b*c", "aXbYbZc"));
+        assert!(!matches("a*b*c", "aXbYbZ"));
+        assert!(matches("*", ""));
+        assert!(matches("**", "anything"));
+    }
+
+    #[test]
+    fn test_matches_question_mark() {
+        assert!(matches("ci?", "ci2"));
+        assert!(!matches("ci?", "ci"));
+        assert!(!matches("ci?", "ci22"));
+        assert!(matches("?*?", "ab"));
+        assert!(matches("prod-??", "prod-éu"));
+    }
+
+    #[test]
+    fn test_matches_class() {
+        assert!(matches("ci[0-9]", "ci2"));
+        assert!(!matches("ci[0-9]", "cix"));
+        assert!(matches("ci[!0-9]", "cix"));
+        assert!(!matches("ci[!0-9]", "ci2"));
+        assert!(matches("[ab]*", "beta"));
+        assert!(matches("a[-_]b", "a_b"));
+        assert!(matches("a[_-]b", "a-b"));
+        assert!(matches("[]]", "]"));
+        assert!(matches("[[]*", "[draft] notes"));
+        assert!(matches("*]", "x]"));
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        let e = Glob::new("[").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "Invalid pattern '[', a [ is not closed, write [[] for a literal ["
+        );
+        assert!(Glob::new("imported-[0-9*").is_err());
+        assert!(Glob::new("[!]").is_err());
+    }
+}
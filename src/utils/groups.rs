@@ -0,0 +1,206 @@
+use crate::utils::tap_data_store::LinkMeta;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// Named groups of links of any parent entities, in the order the groups were made, each with
+/// its links as parent entity and name in the order they were added. A link is in a group when
+/// its metadata says so, see `LinkMeta::groups`, and this registry only keeps the order. It is
+/// kept in its own file next to the data file, one link per line with the fields separated by
+/// tabs, written as `\t` here:
+///
+/// ```text
+/// oncall\twork\tpagerduty
+/// oncall\tinfra\tgrafana-alerts
+/// ```
+pub(crate) type Groups = Vec<(String, Vec<(String, String)>)>;
+
+/// The groups file kept next to a data file: `.tap_data` keeps its groups in `.tap_groups`,
+/// `demo.tap_data` in `demo.tap_groups`, and any other file name gets `.tap_groups` appended
+pub(crate) fn groups_path_for(data: &Path) -> PathBuf {
+    let file_name = data.file_name().unwrap_or_default().to_string_lossy();
+    match file_name.strip_suffix("tap_data") {
+        Some(stem) => data.with_file_name(format!("{stem}tap_groups")),
+        None => data.with_file_name(format!("{file_name}.tap_groups")),
+    }
+}
+
+/// The links of group `name`, none when there is no such group
+pub(crate) fn members<'a>(groups: &'a Groups, name: &str) -> &'a [(String, String)] {
+    groups
+        .iter()
+        .find(|(group, _)| group == name)
+        .map_or(&[], |(_, links)| links.as_slice())
+}
+
+/// Makes `groups` agree with the metadata of the links, returning the group, parent entity, and
+/// link of every link dropped from a group. Links whose metadata no longer names the group, like
+/// deleted links, are dropped, links whose metadata names a group they are not listed in are
+/// added at its end, and groups left without links are removed.
+pub(crate) fn reconcile(
+    groups: &mut Groups,
+    meta: &BTreeMap<(String, String), LinkMeta>,
+) -> Vec<(String, String, String)> {
+    let in_group = |group: &str, parent: &str, link: &str| {
+        meta.get(&(parent.to_string(), link.to_string()))
+            .is_some_and(|meta| meta.groups.iter().any(|g| g == group))
+    };
+    let mut dropped = vec![];
+    for (group, links) in groups.iter_mut() {
+        links.retain(|(parent, link)| {
+            let keep = in_group(group, parent, link);
+            if !keep {
+                dropped.push((group.clone(), parent.clone(), link.clone()));
+            }
+            keep
+        });
+    }
+    for ((parent, link), meta) in meta {
+        for group in &meta.groups {
+            let member = (parent.clone(), link.clone());
+            match groups.iter_mut().find(|(name, _)| name == group) {
+                Some((_, links)) if links.contains(&member) => {}
+                Some((_, links)) => links.push(member),
+                None => groups.push((group.clone(), vec![member])),
+            }
+        }
+    }
+    groups.retain(|(_, links)| !links.is_empty());
+    dropped
+}
+
+/// The groups in the file at `path`, none when there is no such file. Lines that are not a link
+/// of a group are skipped.
+pub(crate) fn read(path: &Path) -> std::io::Result<Groups> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Groups::new()),
+        Err(e) => return Err(e),
+    };
+    let mut groups = Groups::new();
+    for line in contents.lines() {
+        let mut fields = line.splitn(3, '\t').map(str::trim);
+        if let (Some(name), Some(parent), Some(link)) =
+            (fields.next(), fields.next(), fields.next())
+            && !name.is_empty()
+            && !parent.is_empty()
+            && !link.is_empty()
+        {
+            let member = (parent.to_string(), link.to_string());
+            match groups.iter_mut().find(|(group, _)| group == name) {
+                Some((_, links)) => links.push(member),
+                None => groups.push((name.to_string(), vec![member])),
+            }
+        }
+    }
+    Ok(groups)
+}
+
+/// Replaces the file at `path` with the groups, removing it when there are none
+pub(crate) fn write(path: &Path, groups: &Groups) -> std::io::Result<()> {
+    if groups.is_empty() {
+        return match fs::remove_file(path) {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        };
+    }
+    let lines: String = groups
+        .iter()
+        .flat_map(|(name, links)| {
+            links
+                .iter()
+                .map(move |(parent, link)| format!("{name}\t{parent}\t{link}\n"))
+        })
+        .collect();
+    fs::write(path, lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(parent: &str, link: &str) -> (String, String) {
+        (parent.to_string(), link.to_string())
+    }
+
+    fn meta_in(groups: &[&str]) -> LinkMeta {
+        LinkMeta {
+            groups: groups.iter().map(|g| g.to_string()).collect(),
+            ..LinkMeta::default()
+        }
+    }
+
+    #[test]
+    fn test_groups_path_for() {
+        assert_eq!(
+            groups_path_for(Path::new("/data/.tap_data")),
+            Path::new("/data/.tap_groups")
+        );
+        assert_eq!(
+            groups_path_for(Path::new("/data/links")),
+            Path::new("/data/links.tap_groups")
+        );
+    }
+
+    #[test]
+    fn test_reconcile() {
+        let mut groups: Groups = vec![
+            (
+                "oncall".to_string(),
+                vec![member("work", "pagerduty"), member("infra", "grafana")],
+            ),
+            ("old".to_string(), vec![member("work", "wiki")]),
+        ];
+        let meta = BTreeMap::from([
+            (member("infra", "grafana"), meta_in(&["oncall"])),
+            (member("work", "ci"), meta_in(&["oncall", "builds"])),
+            (member("work", "wiki"), LinkMeta::default()),
+        ]);
+        let dropped = reconcile(&mut groups, &meta);
+        assert_eq!(
+            dropped,
+            [
+                (
+                    "oncall".to_string(),
+                    "work".to_string(),
+                    "pagerduty".to_string()
+                ),
+                ("old".to_string(), "work".to_string(), "wiki".to_string()),
+            ]
+        );
+        assert_eq!(
+            groups,
+            [
+                (
+                    "oncall".to_string(),
+                    vec![member("infra", "grafana"), member("work", "ci")]
+                ),
+                ("builds".to_string(), vec![member("work", "ci")]),
+            ]
+        );
+        // Groups that agree with the metadata are left as they are
+        assert_eq!(reconcile(&mut groups.clone(), &meta), []);
+    }
+
+    #[test]
+    fn test_groups_round_trip() {
+        let dir = std::env::temp_dir().join(format!("tap_groups_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(".tap_groups");
+        assert_eq!(read(&path).unwrap(), Groups::new());
+        // Groups and their links keep their order
+        let groups: Groups = vec![
+            (
+                "oncall".to_string(),
+                vec![member("work", "pagerduty"), member("infra", "grafana")],
+            ),
+            ("builds".to_string(), vec![member("work", "ci")]),
+        ];
+        write(&path, &groups).unwrap();
+        assert_eq!(read(&path).unwrap(), groups);
+        write(&path, &Groups::new()).unwrap();
+        assert!(!path.exists());
+    }
+}
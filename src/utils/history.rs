@@ -0,0 +1,202 @@
+use crate::utils::{backup::utc_timestamp, log};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{fmt, fs};
+
+/// Once the history file grows past this size, it is moved to `<name>.1` (replacing the previous
+/// one) and a new history file is started, so at most twice this much history is kept
+const MAX_BYTES: u64 = 1024 * 1024;
+
+/// A change to a single link, one line of the history file. The fields are separated by tabs,
+/// written as `\t` here:
+///
+/// ```text
+/// 2024-06-01T12:30:05Z\tupsert\twork\tci\thttps://ci.internal\thttps://ci.example.com
+/// ```
+///
+/// `old` is empty for added links and `new` is empty for deleted ones.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Entry {
+    /// When the change was made, see `utc_timestamp`
+    pub time: String,
+    /// `add`, `upsert`, `delete`, `import`, `archive`, `unarchive`, or `restore`
+    pub operation: String,
+    pub parent: String,
+    pub link: String,
+    pub old: String,
+    pub new: String,
+}
+
+impl Entry {
+    pub fn new(operation: &str, parent: &str, link: &str, old: &str, new: &str) -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        Self {
+            time: utc_timestamp(now),
+            operation: operation.to_string(),
+            parent: parent.trim().to_string(),
+            link: link.trim().to_string(),
+            old: old.trim().to_string(),
+            new: new.trim().to_string(),
+        }
+    }
+
+    /// `None` for lines that are not an entry, which are skipped when reading the history
+    fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.splitn(6, '\t').map(str::to_string);
+        Some(Self {
+            time: fields.next()?,
+            operation: fields.next()?,
+            parent: fields.next()?,
+            link: fields.next()?,
+            old: fields.next()?,
+            new: fields.next()?,
+        })
+    }
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let fields = [
+            &self.time,
+            &self.operation,
+            &self.parent,
+            &self.link,
+            &self.old,
+            &self.new,
+        ];
+        // Tabs separate the fields, so the rare tab in a value is written as a space
+        let line = fields
+            .iter()
+            .map(|field| field.replace('\t', " "))
+            .collect::<Vec<String>>()
+            .join("\t");
+        write!(f, "{line}")
+    }
+}
+
+/// The history file kept next to a data file: `.tap_data` keeps its history in `.tap_history`,
+/// `demo.tap_data` in `demo.tap_history`, and any other file name gets `.tap_history` appended
+pub(crate) fn history_path_for(data: &Path) -> PathBuf {
+    let file_name = data.file_name().unwrap_or_default().to_string_lossy();
+    match file_name.strip_suffix("tap_data") {
+        Some(stem) => data.with_file_name(format!("{stem}tap_history")),
+        None => data.with_file_name(format!("{file_name}.tap_history")),
+    }
+}
+
+/// The history file the history is moved to once it grows too large
+pub(crate) fn rotated_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".1");
+    path.with_file_name(file_name)
+}
+
+/// Appends the entries to the history file at `path` in a single write. The change they describe
+/// was already saved, so the history is best effort: failing to write it is only noted.
+pub(crate) fn append(path: &Path, entries: &[Entry]) {
+    if let Err(e) = append_capped(path, entries, MAX_BYTES) {
+        log::note(format_args!(
+            "Could not add to the history in {}: {e}",
+            path.display()
+        ));
+    }
+}
+
+fn append_capped(path: &Path, entries: &[Entry], max_bytes: u64) -> std::io::Result<()> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+    if fs::metadata(path).is_ok_and(|m| m.len() >= max_bytes) {
+        fs::rename(path, rotated_path(path))?;
+    }
+    let lines: String = entries.iter().map(|e| format!("{e}\n")).collect();
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(lines.as_bytes())
+}
+
+/// Every entry of the history file at `path`, oldest first, including the ones that were rotated
+/// out of it
+pub(crate) fn read(path: &Path) -> std::io::Result<Vec<Entry>> {
+    let mut entries = vec![];
+    for path in [rotated_path(path), path.to_path_buf()] {
+        if path.exists() {
+            entries.extend(fs::read_to_string(&path)?.lines().filter_map(Entry::parse));
+        }
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_history(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("tap_history_{name}_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join(".tap_history")
+    }
+
+    fn entry(link: &str) -> Entry {
+        Entry {
+            time: "2024-06-01T12:30:05Z".to_string(),
+            operation: "upsert".to_string(),
+            parent: "work".to_string(),
+            link: link.to_string(),
+            old: "https://ci.internal".to_string(),
+            new: "https://ci.example.com".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_entry_round_trip() {
+        let line = entry("ci").to_string();
+        assert_eq!(
+            line,
+            "2024-06-01T12:30:05Z\tupsert\twork\tci\thttps://ci.internal\thttps://ci.example.com"
+        );
+        assert_eq!(Entry::parse(&line), Some(entry("ci")));
+        assert_eq!(Entry::parse("not an entry"), None);
+    }
+
+    #[test]
+    fn test_history_path_for() {
+        assert_eq!(
+            history_path_for(Path::new("/data/.tap_data")),
+            Path::new("/data/.tap_history")
+        );
+        assert_eq!(
+            history_path_for(Path::new("/data/demo.tap_data")),
+            Path::new("/data/demo.tap_history")
+        );
+        assert_eq!(
+            history_path_for(Path::new("/data/links")),
+            Path::new("/data/links.tap_history")
+        );
+    }
+
+    #[test]
+    fn test_append_rotates() {
+        let path = temp_history("rotates");
+        let line_length = entry("a").to_string().len() as u64 + 1;
+        append_capped(&path, &[entry("a"), entry("b")], 2 * line_length).unwrap();
+        append_capped(&path, &[entry("c")], 2 * line_length).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(rotated_path(&path))
+                .unwrap()
+                .lines()
+                .count(),
+            2
+        );
+        assert_eq!(read(&path).unwrap(), [entry("a"), entry("b"), entry("c")]);
+        // Only one rotated file is kept
+        append_capped(&path, &[entry("d")], line_length).unwrap();
+        assert_eq!(read(&path).unwrap(), [entry("c"), entry("d")]);
+    }
+}
@@ -0,0 +1,110 @@
+use crate::utils::{
+    config::{Config, default_config_path},
+    history::Entry,
+    log,
+    os_implementations::run_hook,
+};
+
+/// The config file key of the command run after the data file was changed
+const POST_WRITE_CONFIG_KEY: &str = "hooks.post_write";
+
+/// Runs the `hooks.post_write` command of the config file, if any, after the data file was saved
+/// with the `changes`. The command is run by the shell with what changed in the environment, see
+/// `hook_env`. A hook that fails is reported, but the change it follows stands. Hooks do not run
+/// with the global `--no-hooks` flag, see `StoreOptions::no_hooks`.
+pub(crate) fn run_post_write(changes: &[Entry]) {
+    let Some(command) = default_config_path()
+        .and_then(|path| Config::load(Some(path)).ok())
+        .and_then(|config| config.get(POST_WRITE_CONFIG_KEY).map(str::to_string))
+        .filter(|command| !command.trim().is_empty())
+    else {
+        return;
+    };
+    if let Err(e) = run_hook(&command, &hook_env(changes)) {
+        log::note(format_args!(
+            "The {POST_WRITE_CONFIG_KEY} hook failed, the change was saved anyway: {e}"
+        ));
+    }
+}
+
+/// What a hook is told about the change: `TAP_OPERATION` is the kind of change, like `add` or
+/// `delete`, or `update` for changes that are not in the history, like pinning a link.
+/// `TAP_PARENT` and `TAP_LINK` name the parent entity and link changed, and are empty when
+/// several were.
+fn hook_env(changes: &[Entry]) -> Vec<(&'static str, String)> {
+    let only = |field: fn(&Entry) -> &str| -> String {
+        match changes.split_first() {
+            Some((first, rest)) if rest.iter().all(|e| field(e) == field(first)) => {
+                field(first).to_string()
+            }
+            _ => String::new(),
+        }
+    };
+    let operation = changes
+        .first()
+        .map_or("update".to_string(), |e| e.operation.clone());
+    let parent = only(|e| &e.parent);
+    let link = match parent.is_empty() {
+        true => String::new(),
+        false => only(|e| &e.link),
+    };
+    vec![
+        ("TAP_OPERATION", operation),
+        ("TAP_PARENT", parent),
+        ("TAP_LINK", link),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hook_env_one_link() {
+        let changes = [Entry::new("add", "work", "ci", "", "https://ci.internal")];
+        assert_eq!(
+            hook_env(&changes),
+            vec![
+                ("TAP_OPERATION", "add".to_string()),
+                ("TAP_PARENT", "work".to_string()),
+                ("TAP_LINK", "ci".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hook_env_several_links() {
+        let changes = [
+            Entry::new("delete", "work", "ci", "https://ci.internal", ""),
+            Entry::new("delete", "work", "jira", "https://jira.internal", ""),
+        ];
+        assert_eq!(
+            hook_env(&changes),
+            vec![
+                ("TAP_OPERATION", "delete".to_string()),
+                ("TAP_PARENT", "work".to_string()),
+                ("TAP_LINK", String::new()),
+            ]
+        );
+        let changes = [
+            Entry::new("import", "work", "ci", "", "https://ci.internal"),
+            Entry::new("import", "home", "ci", "", "https://ci.home"),
+        ];
+        assert_eq!(
+            hook_env(&changes)[1..],
+            [("TAP_PARENT", String::new()), ("TAP_LINK", String::new())]
+        );
+    }
+
+    #[test]
+    fn test_hook_env_no_history() {
+        assert_eq!(
+            hook_env(&[]),
+            vec![
+                ("TAP_OPERATION", "update".to_string()),
+                ("TAP_PARENT", String::new()),
+                ("TAP_LINK", String::new()),
+            ]
+        );
+    }
+}
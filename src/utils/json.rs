@@ -0,0 +1,126 @@
+use std::fmt;
+
+/// A JSON value, written out by `--json` output. Objects keep the order their keys were added in.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Json {
+    Bool(bool),
+    Number(u64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Shorthand for building an object from string keys
+    pub fn object<const N: usize>(entries: [(&str, Json); N]) -> Self {
+        Json::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+}
+
+impl From<&str> for Json {
+    fn from(s: &str) -> Self {
+        Json::String(s.to_string())
+    }
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Bool(b) => write!(f, "{b}"),
+            Json::Number(n) => write!(f, "{n}"),
+            Json::String(s) => write_string(f, s),
+            Json::Array(values) => {
+                write!(f, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                write!(f, "]")
+            }
+            Json::Object(entries) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{value}")?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{c}")?,
+        }
+    }
+    write!(f, "\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_string_escapes() {
+        let json = Json::from("a \"quoted\" \\ path\nwith\ttabs\u{1}");
+        assert_eq!(
+            json.to_string(),
+            "\"a \\\"quoted\\\" \\\\ path\\nwith\\ttabs\\u0001\""
+        );
+    }
+
+    #[test]
+    fn test_display_bool_and_number() {
+        let json = Json::object([("exists", Json::Bool(true)), ("size", Json::Number(42))]);
+        assert_eq!(json.to_string(), r#"{"exists":true,"size":42}"#);
+    }
+
+    #[test]
+    fn test_display_empty() {
+        assert_eq!(Json::Array(vec![]).to_string(), "[]");
+        assert_eq!(Json::Object(vec![]).to_string(), "{}");
+    }
+
+    #[test]
+    fn test_display_nested() {
+        let json = Json::object([
+            ("parent", Json::from("search-engines")),
+            (
+                "links",
+                Json::Array(vec![
+                    Json::object([
+                        ("name", Json::from("google")),
+                        ("value", Json::from("https://google.com")),
+                    ]),
+                    Json::object([
+                        ("name", Json::from("yahoo")),
+                        ("value", Json::from("https://yahoo.com")),
+                    ]),
+                ]),
+            ),
+        ]);
+        assert_eq!(
+            json.to_string(),
+            r#"{"parent":"search-engines","links":[{"name":"google","value":"https://google.com"},{"name":"yahoo","value":"https://yahoo.com"}]}"#
+        );
+    }
+}
@@ -0,0 +1,193 @@
+/// Link names derived from titles are cut to about this many characters, at a word boundary
+const MAX_LEN: usize = 40;
+
+/// Whether `value` is a web page address, the only values a name can be fetched for
+pub(crate) fn is_web_url(value: &str) -> bool {
+    value.starts_with("http://") || value.starts_with("https://")
+}
+
+/// A link name for `url`: the title of `page` when it has one, otherwise the last part of the URL
+/// path, or its host when there is no path. `None` if neither has anything to name the link by.
+pub(crate) fn derive(url: &str, page: Option<&str>) -> Option<String> {
+    page.and_then(title_of)
+        .map(|title| slugify(main_part(&title)))
+        .filter(|name| !name.is_empty())
+        .or_else(|| from_url(url))
+}
+
+/// The text of the `<title>` element of an HTML page
+fn title_of(html: &str) -> Option<String> {
+    // ASCII lowercasing keeps every byte where it was, so positions carry over to `html`
+    let lower = html.to_ascii_lowercase();
+    let open = lower.find("<title")?;
+    let start = open + lower[open..].find('>')? + 1;
+    let end = start + lower[start..].find("</title")?;
+    Some(without_entities(&html[start..end]))
+}
+
+/// Titles often end with the name of the site, like `Some Post | Blog`, which is left out
+fn main_part(title: &str) -> &str {
+    [" | ", " - ", " – ", " — "]
+        .iter()
+        .filter_map(|separator| title.find(separator))
+        .min()
+        .map(|end| &title[..end])
+        .filter(|part| !part.trim().is_empty())
+        .unwrap_or(title)
+}
+
+/// Drops character references like `&amp;` and `&#39;`, they are punctuation in titles
+fn without_entities(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(i) = rest.find('&') {
+        out.push_str(&rest[..i]);
+        rest = &rest[i..];
+        let entity = rest[1..].find(';').filter(|&end| {
+            end > 0
+                && rest[1..=end]
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '#')
+        });
+        match entity {
+            Some(end) => {
+                out.push(' ');
+                rest = &rest[end + 2..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The last part of the URL path without a page extension, or the host, like `github` for
+/// `https://www.github.com/`
+fn from_url(url: &str) -> Option<String> {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let rest = rest.split(['?', '#']).next().unwrap_or_default();
+    let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+    let segment = path.split('/').rfind(|s| !s.is_empty());
+    let name = match segment {
+        Some(segment) => {
+            let segment = percent_decode(segment);
+            let stem = match segment.rsplit_once('.') {
+                Some((stem, "html" | "htm" | "php" | "asp" | "aspx")) => stem.to_string(),
+                _ => segment,
+            };
+            slugify(&stem)
+        }
+        None => {
+            let host = host.rsplit('@').next().unwrap_or_default();
+            let host = host.split(':').next().unwrap_or_default();
+            let host = host.strip_prefix("www.").unwrap_or(host);
+            slugify(host.split('.').next().unwrap_or_default())
+        }
+    };
+    (!name.is_empty()).then_some(name)
+}
+
+/// Decodes `%20` and the like, leaving sequences that are not valid UTF-8 as they are
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = s
+            .get(i + 1..i + 3)
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(out).unwrap_or_else(|_| s.to_string())
+}
+
+/// Lowercase words joined by `-`, like `rust-1-80-released` for `Rust 1.80 released!`, cut to
+/// `MAX_LEN` characters at a word boundary
+fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    for word in text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+    {
+        let len = slug.chars().count();
+        if len > 0 && len + 1 + word.chars().count() > MAX_LEN {
+            break;
+        }
+        if len > 0 {
+            slug.push('-');
+        }
+        slug.extend(word.chars().flat_map(char::to_lowercase));
+    }
+    match slug.chars().count() > MAX_LEN {
+        // A single word longer than the limit
+        true => slug.chars().take(MAX_LEN).collect(),
+        false => slug,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derive_from_title() {
+        let page = "<html><head><TITLE lang=\"en\">Rust 1.80 released &amp; more! | Rust Blog</TITLE></head></html>";
+        assert_eq!(
+            derive(
+                "https://blog.rust-lang.org/2024/07/25/Rust-1.80.0.html",
+                Some(page)
+            ),
+            Some("rust-1-80-released-more".to_string())
+        );
+    }
+
+    #[test]
+    fn test_derive_falls_back_to_url() {
+        assert_eq!(
+            derive("https://some.blog/posts/long-post-slug-here/", None),
+            Some("long-post-slug-here".to_string())
+        );
+        assert_eq!(
+            derive(
+                "https://some.blog/a/Read%20Me.html?x=1#top",
+                Some("<p>No title</p>")
+            ),
+            Some("read-me".to_string())
+        );
+        assert_eq!(
+            derive("https://www.github.com:443/", Some("<title> | </title>")),
+            Some("github".to_string())
+        );
+        assert_eq!(derive("https://", None), None);
+    }
+
+    #[test]
+    fn test_slugify_cuts_long_titles() {
+        assert_eq!(
+            slugify("How we made our build twice as fast with incremental caching"),
+            "how-we-made-our-build-twice-as-fast-with"
+        );
+        assert_eq!(slugify(&"a".repeat(50)), "a".repeat(MAX_LEN));
+        assert_eq!(slugify("Über Café"), "über-café");
+    }
+
+    #[test]
+    fn test_is_web_url() {
+        assert!(is_web_url("https://example.com"));
+        assert!(is_web_url("http://localhost:8080"));
+        assert!(!is_web_url("~/notes.txt"));
+        assert!(!is_web_url("ftp://example.com"));
+    }
+}
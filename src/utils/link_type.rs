@@ -0,0 +1,86 @@
+use std::fmt;
+use std::path::Path;
+
+/// What the value of a link is, deciding how it is opened. Written as `type=cmd` after the value,
+/// see `LinkMeta`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum LinkType {
+    /// A web page, opened in the browser
+    Url,
+    /// A file, opened with the program the OS picks for it
+    File,
+    /// A directory, opened in the file manager
+    Dir,
+    /// A shell command, run instead of opened
+    Cmd,
+}
+
+impl LinkType {
+    pub const ALL: [LinkType; 4] = [LinkType::Url, LinkType::File, LinkType::Dir, LinkType::Cmd];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LinkType::Url => "url",
+            LinkType::File => "file",
+            LinkType::Dir => "dir",
+            LinkType::Cmd => "cmd",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<LinkType> {
+        Self::ALL.into_iter().find(|t| t.name() == name.trim())
+    }
+
+    /// The type of `value` as far as it can be told: web addresses are URLs, and paths that exist
+    /// are files or directories. Commands can not be told from anything else, so `None` is
+    /// returned for them and for paths that do not exist.
+    pub fn detect(value: &str) -> Option<LinkType> {
+        let value = value.trim();
+        if value.starts_with("http://") || value.starts_with("https://") {
+            return Some(LinkType::Url);
+        }
+        let path = Path::new(value);
+        if path.is_dir() {
+            Some(LinkType::Dir)
+        } else if path.is_file() {
+            Some(LinkType::File)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for LinkType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        for t in LinkType::ALL {
+            assert_eq!(LinkType::parse(t.name()), Some(t));
+        }
+        assert_eq!(LinkType::parse("script"), None);
+    }
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(LinkType::detect("https://example.com"), Some(LinkType::Url));
+        let dir = std::env::temp_dir();
+        assert_eq!(LinkType::detect(dir.to_str().unwrap()), Some(LinkType::Dir));
+        let file = dir.join(format!("tap_link_type_{}", std::process::id()));
+        std::fs::write(&file, "").unwrap();
+        assert_eq!(
+            LinkType::detect(file.to_str().unwrap()),
+            Some(LinkType::File)
+        );
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(LinkType::detect(file.to_str().unwrap()), None);
+        assert_eq!(LinkType::detect("wg-quick up wg0"), None);
+    }
+}
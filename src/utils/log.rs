@@ -0,0 +1,45 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// How much tap prints, set by the global `-q/--quiet` and `--verbose` flags. Everything logged
+/// here goes to stderr so it never mixes with the output scripts parse from stdout.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub(crate) enum Level {
+    /// Only errors are printed
+    Quiet = 0,
+    /// Command output and notes are printed
+    Normal = 1,
+    /// Diagnostics like the data files loaded and the commands spawned are printed as well
+    Verbose = 2,
+}
+
+/// The `level` of the `Context` a command runs in, see `cli::OutputScope`. Unlike the other global
+/// flags it is not passed down: notes and diagnostics are printed from deep in the data store and
+/// the OS helpers, which never see a `Context`.
+static LEVEL: AtomicU8 = AtomicU8::new(Level::Normal as u8);
+
+pub(crate) fn set_level(level: Level) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+pub(crate) fn level() -> Level {
+    match LEVEL.load(Ordering::Relaxed) {
+        0 => Level::Quiet,
+        1 => Level::Normal,
+        _ => Level::Verbose,
+    }
+}
+
+/// Prints a note for the user to stderr, unless tap is quiet
+pub(crate) fn note(message: fmt::Arguments) {
+    if level() >= Level::Normal {
+        eprintln!("NOTE: {message}");
+    }
+}
+
+/// Prints a diagnostic line to stderr, only when tap is verbose
+pub(crate) fn verbose(message: fmt::Arguments) {
+    if level() >= Level::Verbose {
+        eprintln!("tap: {message}");
+    }
+}
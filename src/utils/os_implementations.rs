@@ -1,15 +1,32 @@
-use std::{env::consts::OS, fmt, process::Command};
+// NOTE: tests must never start programs or reach the network, so under test every function
+// below that would is replaced by a double of the same name doing nothing
+#[cfg(not(test))]
+use crate::utils::log;
+use std::fmt;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(not(test))]
+use std::{
+    env::{self, consts::OS},
+    io::Write,
+    process::{Command, Stdio},
+};
 
+#[cfg(not(test))]
 pub fn open_link(link: &str) -> Result<(), OsImplementationError> {
     let mut cmd = match OS {
-        "macos" => Command::new("open")
-            .arg(link)
-            .spawn()
-            .map_err(|e| OsImplementationError {
-                kind: OsImplementationErrorKind::CommandFailedToStart,
-                message: format!("Failed to start command open: {e}"),
-            })?,
+        "macos" => {
+            log::verbose(format_args!("Running open {link}"));
+            Command::new("open")
+                .arg(link)
+                .spawn()
+                .map_err(|e| OsImplementationError {
+                    kind: OsImplementationErrorKind::CommandFailedToStart,
+                    message: format!("Failed to start command open: {e}"),
+                })?
+        }
         "linux" => {
+            log::verbose(format_args!("Running xdg-open {link}"));
             Command::new("xdg-open")
                 .arg(link)
                 .spawn()
@@ -18,14 +35,17 @@ pub fn open_link(link: &str) -> Result<(), OsImplementationError> {
                     message: format!("Failed to start command xdg-open: {e}"),
                 })?
         }
-        "windows" => Command::new("start")
-            .arg("\"\"")
-            .arg(format!("\"{link}\""))
-            .spawn()
-            .map_err(|e| OsImplementationError {
-                kind: OsImplementationErrorKind::CommandFailedToStart,
-                message: format!("Failed to start command start: {e}"),
-            })?,
+        "windows" => {
+            log::verbose(format_args!("Running start \"\" \"{link}\""));
+            Command::new("start")
+                .arg("\"\"")
+                .arg(format!("\"{link}\""))
+                .spawn()
+                .map_err(|e| OsImplementationError {
+                    kind: OsImplementationErrorKind::CommandFailedToStart,
+                    message: format!("Failed to start command start: {e}"),
+                })?
+        }
         _ => {
             return Err(OsImplementationError {
                 kind: OsImplementationErrorKind::OsNotSupported,
@@ -40,9 +60,617 @@ pub fn open_link(link: &str) -> Result<(), OsImplementationError> {
     Ok(())
 }
 
+/// Tests must never spawn a browser or file manager, so opening is a no-op under test
+#[cfg(test)]
+pub fn open_link(_link: &str) -> Result<(), OsImplementationError> {
+    Ok(())
+}
+
+/// A browser of `BROWSERS`
+type Browser = (
+    &'static str,
+    &'static str,
+    &'static str,
+    &'static str,
+    Option<&'static str>,
+);
+
+/// The browsers `open_in_browser` knows: the name given to `--browser`, the application on macOS,
+/// the program on Linux and Windows, and the flag opening a private window, if it has one
+const BROWSERS: [Browser; 6] = [
+    (
+        "chrome",
+        "Google Chrome",
+        "google-chrome",
+        "chrome",
+        Some("--incognito"),
+    ),
+    (
+        "chromium",
+        "Chromium",
+        "chromium",
+        "chromium",
+        Some("--incognito"),
+    ),
+    (
+        "brave",
+        "Brave Browser",
+        "brave-browser",
+        "brave",
+        Some("--incognito"),
+    ),
+    (
+        "edge",
+        "Microsoft Edge",
+        "microsoft-edge",
+        "msedge",
+        Some("--inprivate"),
+    ),
+    (
+        "firefox",
+        "Firefox",
+        "firefox",
+        "firefox",
+        Some("--private-window"),
+    ),
+    ("safari", "Safari", "safari", "safari", None),
+];
+
+/// Opens `link` in `browser` instead of the default program, in a private window when `private`
+/// is set. The browsers of `BROWSERS` are started by their name on the OS, like Google Chrome on
+/// macOS, and any other name is started as it is, without a private window.
+pub fn open_in_browser(
+    link: &str,
+    browser: &str,
+    private: bool,
+) -> Result<(), OsImplementationError> {
+    let known = BROWSERS
+        .iter()
+        .find(|(name, ..)| name.eq_ignore_ascii_case(browser));
+    let private_flag = match (private, known.and_then(|(.., flag)| *flag)) {
+        (false, _) => None,
+        (true, Some(flag)) => Some(flag),
+        (true, None) => {
+            let names: Vec<&str> = BROWSERS
+                .iter()
+                .filter(|(.., flag)| flag.is_some())
+                .map(|(name, ..)| *name)
+                .collect();
+            return Err(OsImplementationError {
+                kind: OsImplementationErrorKind::OsNotSupported,
+                message: format!(
+                    "Can not open a private window of {browser}, only of {}",
+                    names.join(", ")
+                ),
+            });
+        }
+    };
+    launch_browser(link, browser, known, private_flag)
+}
+
+/// Starts `browser`, one of `BROWSERS` when `known`, on `link`, passing `private_flag` to it
+#[cfg(not(test))]
+fn launch_browser(
+    link: &str,
+    browser: &str,
+    known: Option<&Browser>,
+    private_flag: Option<&str>,
+) -> Result<(), OsImplementationError> {
+    let mut args: Vec<&str> = vec![];
+    let program = match OS {
+        "macos" => {
+            let app = known.map_or(browser, |(_, app, ..)| app);
+            // A private window needs a new instance to be passed the flag
+            match private_flag {
+                Some(flag) => args.extend(["-na", app, "--args", flag]),
+                None => args.extend(["-a", app]),
+            }
+            "open"
+        }
+        "linux" => {
+            args.extend(private_flag);
+            known.map_or(browser, |(_, _, program, ..)| program)
+        }
+        "windows" => {
+            let program = known.map_or(browser, |(.., program, _)| program);
+            args.extend(["/C", "start", "", program]);
+            args.extend(private_flag);
+            "cmd"
+        }
+        _ => {
+            return Err(OsImplementationError {
+                kind: OsImplementationErrorKind::OsNotSupported,
+                message: format!("Unsupported OS: {}", OS),
+            });
+        }
+    };
+    args.push(link);
+    log::verbose(format_args!("Running {program} {}", args.join(" ")));
+    let mut child = Command::new(program)
+        .args(&args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| OsImplementationError {
+            kind: OsImplementationErrorKind::CommandFailedToStart,
+            message: format!("Failed to start command {program}: {e}"),
+        })?;
+    // A browser started directly on Linux runs until it is closed, so it is left running
+    if OS != "linux" {
+        child.wait().map_err(|e| OsImplementationError {
+            kind: OsImplementationErrorKind::CommandNotRunning,
+            message: format!("No exit status from {program}: {e}"),
+        })?;
+    }
+    Ok(())
+}
+
+/// Tests must never spawn a browser, so opening is a no-op under test
+#[cfg(test)]
+fn launch_browser(
+    _link: &str,
+    _browser: &str,
+    _known: Option<&Browser>,
+    _private_flag: Option<&str>,
+) -> Result<(), OsImplementationError> {
+    Ok(())
+}
+
+/// Puts `value` on the system clipboard with `pbcopy` on macOS, `clip` on Windows, and the first
+/// of `wl-copy`, `xclip`, `xsel`, and `clip.exe` (for WSL) found on Linux. Fails naming what to
+/// install when none is found.
+#[cfg(not(test))]
+pub fn copy_to_clipboard(value: &str) -> Result<(), OsImplementationError> {
+    let (program, args): (&str, &[&str]) = match OS {
+        "macos" => ("pbcopy", &[]),
+        "windows" => ("clip", &[]),
+        "linux" => [
+            ("wl-copy", &[][..]),
+            ("xclip", &["-selection", "clipboard"][..]),
+            ("xsel", &["--clipboard", "--input"][..]),
+            ("clip.exe", &[][..]),
+        ]
+        .into_iter()
+        .find(|(program, _)| is_on_path(program))
+        .ok_or_else(|| OsImplementationError {
+            kind: OsImplementationErrorKind::CommandFailedToStart,
+            message: "No clipboard program found, install wl-clipboard on Wayland or xclip on X11"
+                .to_string(),
+        })?,
+        _ => {
+            return Err(OsImplementationError {
+                kind: OsImplementationErrorKind::OsNotSupported,
+                message: format!("Unsupported OS: {}", OS),
+            });
+        }
+    };
+    log::verbose(format_args!("Running {program} {}", args.join(" ")));
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .map_err(|e| OsImplementationError {
+            kind: OsImplementationErrorKind::CommandFailedToStart,
+            message: format!("Failed to start command {program}: {e}"),
+        })?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(value.as_bytes())
+            .map_err(|e| OsImplementationError {
+                kind: OsImplementationErrorKind::CommandFailed,
+                message: format!("Could not write to {program}: {e}"),
+            })?;
+    }
+    let status = child.wait().map_err(|e| OsImplementationError {
+        kind: OsImplementationErrorKind::CommandNotRunning,
+        message: format!("No exit status from {program}: {e}"),
+    })?;
+    match status.code() {
+        Some(0) => Ok(()),
+        Some(code) => Err(OsImplementationError {
+            kind: OsImplementationErrorKind::CommandFailed,
+            message: format!("{program} exited with code {code}"),
+        }),
+        None => Err(OsImplementationError {
+            kind: OsImplementationErrorKind::CommandFailed,
+            message: format!("{program} was stopped by a signal"),
+        }),
+    }
+}
+
+/// Tests must never overwrite the clipboard, so copying is a no-op under test
+#[cfg(test)]
+pub fn copy_to_clipboard(_value: &str) -> Result<(), OsImplementationError> {
+    Ok(())
+}
+
+/// Runs `command` with the shell, `sh -c` or `cmd /C` on Windows, in the terminal tap runs in and
+/// waits for it to finish. Fails when the command exits with an error.
+#[cfg(not(test))]
+pub fn run_shell(command: &str) -> Result<(), OsImplementationError> {
+    let (shell, flag) = match OS {
+        "windows" => ("cmd", "/C"),
+        _ => ("sh", "-c"),
+    };
+    log::verbose(format_args!("Running {shell} {flag} {command}"));
+    let status = Command::new(shell)
+        .args([flag, command])
+        .status()
+        .map_err(|e| OsImplementationError {
+            kind: OsImplementationErrorKind::CommandFailedToStart,
+            message: format!("Failed to start command {shell}: {e}"),
+        })?;
+    match status.code() {
+        Some(0) => Ok(()),
+        Some(code) => Err(OsImplementationError {
+            kind: OsImplementationErrorKind::CommandFailed,
+            message: format!("{command} exited with code {code}"),
+        }),
+        None => Err(OsImplementationError {
+            kind: OsImplementationErrorKind::CommandFailed,
+            message: format!("{command} was stopped by a signal"),
+        }),
+    }
+}
+
+/// Tests must never run commands from their data, so running is a no-op under test
+#[cfg(test)]
+pub fn run_shell(_command: &str) -> Result<(), OsImplementationError> {
+    Ok(())
+}
+
+/// Runs the hook `command` with the shell, like `run_shell`, with `env` added to its environment.
+/// Its output goes to stderr so it never mixes with the output of tap. Fails when the command
+/// exits with an error.
+#[cfg(not(test))]
+pub fn run_hook(command: &str, env: &[(&str, String)]) -> Result<(), OsImplementationError> {
+    let (shell, flag) = match OS {
+        "windows" => ("cmd", "/C"),
+        _ => ("sh", "-c"),
+    };
+    log::verbose(format_args!("Running hook {shell} {flag} {command}"));
+    let status = Command::new(shell)
+        .args([flag, command])
+        .envs(env.iter().map(|(name, value)| (name, value)))
+        .stdout(Stdio::from(std::io::stderr()))
+        .status()
+        .map_err(|e| OsImplementationError {
+            kind: OsImplementationErrorKind::CommandFailedToStart,
+            message: format!("Failed to start command {shell}: {e}"),
+        })?;
+    match status.code() {
+        Some(0) => Ok(()),
+        Some(code) => Err(OsImplementationError {
+            kind: OsImplementationErrorKind::CommandFailed,
+            message: format!("{command} exited with code {code}"),
+        }),
+        None => Err(OsImplementationError {
+            kind: OsImplementationErrorKind::CommandFailed,
+            message: format!("{command} was stopped by a signal"),
+        }),
+    }
+}
+
+/// Tests must never run hooks from the config file, so running is a no-op under test
+#[cfg(test)]
+pub fn run_hook(_command: &str, _env: &[(&str, String)]) -> Result<(), OsImplementationError> {
+    Ok(())
+}
+
+/// Runs `git` with `args` in the directory `dir` and returns what it printed to stdout. Fails with
+/// what git printed to stderr when it exits with an error.
+#[cfg(not(test))]
+pub fn run_git(dir: &Path, args: &[&str]) -> Result<String, OsImplementationError> {
+    log::verbose(format_args!(
+        "Running git {} in {}",
+        args.join(" "),
+        dir.display()
+    ));
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|e| OsImplementationError {
+            kind: OsImplementationErrorKind::CommandFailedToStart,
+            message: format!("Failed to start command git: {e}"),
+        })?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        // git writes some failures, like merge conflicts, to stdout
+        let reason = match stderr.trim().is_empty() {
+            true => stdout.trim().to_string(),
+            false => stderr.trim().to_string(),
+        };
+        return Err(OsImplementationError {
+            kind: OsImplementationErrorKind::CommandFailed,
+            message: format!("git {} failed: {reason}", args.join(" ")),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Tests must never change a real repository, so git is never run under test
+#[cfg(test)]
+pub fn run_git(_dir: &Path, _args: &[&str]) -> Result<String, OsImplementationError> {
+    Err(OsImplementationError {
+        kind: OsImplementationErrorKind::CommandNotRunning,
+        message: "git is not run under test".to_string(),
+    })
+}
+
+/// What came back from a request sent with `http_status`
+#[derive(Clone, Debug, PartialEq)]
+// Only curl answers with a status, and it is never run under test
+#[cfg_attr(test, allow(dead_code))]
+pub enum HttpResponse {
+    /// The status code of the last response, after following redirects
+    Status(u16),
+    /// No response came back in time
+    TimedOut,
+    /// The request failed before any response, e.g. because the host could not be resolved or
+    /// connected to, with the reason curl gave
+    Failed(String),
+}
+
+/// Sends an HTTP HEAD request to `url` with curl, following redirects, and returns the status code
+/// of the last response. `None` means no response came back within `timeout_secs`, e.g. because
+/// the host could not be resolved or connected to.
+pub fn http_head_status(
+    url: &str,
+    timeout_secs: u64,
+) -> Result<Option<u16>, OsImplementationError> {
+    match http_status(url, true, timeout_secs)? {
+        HttpResponse::Status(status) => Ok(Some(status)),
+        HttpResponse::TimedOut | HttpResponse::Failed(_) => Ok(None),
+    }
+}
+
+/// Sends an HTTP HEAD request, or a GET request unless `head` is set, to `url` with curl,
+/// following redirects, and tells what came back within `timeout_secs`. The body of a GET
+/// response is thrown away.
+#[cfg(not(test))]
+pub fn http_status(
+    url: &str,
+    head: bool,
+    timeout_secs: u64,
+) -> Result<HttpResponse, OsImplementationError> {
+    let null = if OS == "windows" { "NUL" } else { "/dev/null" };
+    let method = if head { "--head" } else { "--get" };
+    log::verbose(format_args!("Running curl {method} {url}"));
+    let output = Command::new("curl")
+        .args([
+            "--silent",
+            "--show-error",
+            method,
+            "--location",
+            "--output",
+            null,
+        ])
+        .args(["--write-out", "%{http_code}", "--max-time"])
+        .arg(timeout_secs.to_string())
+        .arg(url)
+        .output()
+        .map_err(|e| OsImplementationError {
+            kind: OsImplementationErrorKind::CommandFailedToStart,
+            message: format!("Failed to start command curl: {e}"),
+        })?;
+    // curl writes 000 when there was no response, and exits with 28 when it timed out
+    match String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse::<u16>()
+    {
+        Ok(status) if status != 0 => Ok(HttpResponse::Status(status)),
+        _ if output.status.code() == Some(28) => Ok(HttpResponse::TimedOut),
+        _ => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            // e.g. curl: (6) Could not resolve host: wiki.internal
+            let reason = stderr
+                .trim()
+                .rsplit_once(") ")
+                .map_or(stderr.trim(), |(_, reason)| reason);
+            Ok(HttpResponse::Failed(match reason.is_empty() {
+                true => "no response".to_string(),
+                false => reason.to_string(),
+            }))
+        }
+    }
+}
+
+/// Tests must never reach the network, so every request fails under test
+#[cfg(test)]
+pub fn http_status(
+    _url: &str,
+    _head: bool,
+    _timeout_secs: u64,
+) -> Result<HttpResponse, OsImplementationError> {
+    Ok(HttpResponse::Failed("no network under test".to_string()))
+}
+
+/// Downloads the page at `url` with curl, following redirects, and returns it when the server
+/// says it is HTML. `None` means there was no successful response within `timeout_secs` or the
+/// content is something else, like a PDF.
+#[cfg(not(test))]
+pub fn http_get_html(
+    url: &str,
+    timeout_secs: u64,
+) -> Result<Option<String>, OsImplementationError> {
+    log::verbose(format_args!("Running curl {url}"));
+    let output = Command::new("curl")
+        .args([
+            "--silent",
+            "--fail",
+            "--location",
+            "--max-filesize",
+            "5000000",
+        ])
+        .args(["--write-out", "\n%{content_type}", "--max-time"])
+        .arg(timeout_secs.to_string())
+        .arg(url)
+        .output()
+        .map_err(|e| OsImplementationError {
+            kind: OsImplementationErrorKind::CommandFailedToStart,
+            message: format!("Failed to start command curl: {e}"),
+        })?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+    let body = String::from_utf8_lossy(&output.stdout);
+    // The content type is written on a line of its own after the page, see `--write-out`
+    Ok(body
+        .rsplit_once('\n')
+        .filter(|(_, content_type)| content_type.to_ascii_lowercase().contains("text/html"))
+        .map(|(page, _)| page.to_string()))
+}
+
+/// Tests must never reach the network, so every URL is unreachable under test
+#[cfg(test)]
+pub fn http_get_html(
+    _url: &str,
+    _timeout_secs: u64,
+) -> Result<Option<String>, OsImplementationError> {
+    Ok(None)
+}
+
+/// Whether `program` can be run, i.e. is an executable file in one of the directories on PATH
+#[cfg(not(test))]
+pub fn is_on_path(program: &str) -> bool {
+    let names = match OS {
+        "windows" => vec![format!("{program}.exe"), program.to_string()],
+        _ => vec![program.to_string()],
+    };
+    env::var_os("PATH").is_some_and(|path| {
+        env::split_paths(&path).any(|dir| names.iter().any(|name| dir.join(name).is_file()))
+    })
+}
+
+/// Tests must never depend on the programs installed, so none are found under test
+#[cfg(test)]
+pub fn is_on_path(_program: &str) -> bool {
+    false
+}
+
+/// Lets the user pick among `candidates`, one per line, with fzf run with `args`. fzf draws on
+/// the terminal itself, so only the candidates and the picked lines go through its stdin and
+/// stdout. `None` means nothing was picked, e.g. because the user pressed Esc.
+#[cfg(not(test))]
+pub fn fzf_select(
+    candidates: &[String],
+    args: &[&str],
+) -> Result<Option<Vec<String>>, OsImplementationError> {
+    log::verbose(format_args!("Running fzf {}", args.join(" ")));
+    let mut child = Command::new("fzf")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| OsImplementationError {
+            kind: OsImplementationErrorKind::CommandFailedToStart,
+            message: format!("Failed to start command fzf: {e}"),
+        })?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // fzf stops reading once something is picked, so a closed pipe is not an error
+        let _ = stdin.write_all(candidates.join("\n").as_bytes());
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| OsImplementationError {
+            kind: OsImplementationErrorKind::CommandNotRunning,
+            message: format!("No exit status from fzf: {e}"),
+        })?;
+    // fzf exits with 1 when nothing matched and 130 when it was left with Esc or Ctrl-C
+    match output.status.code() {
+        Some(0) => {}
+        Some(1) | Some(130) | None => return Ok(None),
+        Some(code) => {
+            return Err(OsImplementationError {
+                kind: OsImplementationErrorKind::CommandFailed,
+                message: format!("fzf exited with code {code}"),
+            });
+        }
+    }
+    let picked: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect();
+    Ok((!picked.is_empty()).then_some(picked))
+}
+
+/// Tests must never wait on the terminal, so nothing is picked under test
+#[cfg(test)]
+pub fn fzf_select(
+    _candidates: &[String],
+    _args: &[&str],
+) -> Result<Option<Vec<String>>, OsImplementationError> {
+    Ok(None)
+}
+
+/// Set once the user asked the process to stop, see `catch_interrupt`
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Records an interrupt instead of ending the process, so a long running command can check
+/// `interrupted` and stop on its own terms
+#[cfg(all(not(test), any(unix, windows)))]
+extern "C" fn on_interrupt(_signal: i32) {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+/// Makes Ctrl-C, and SIGTERM on Unix, set `interrupted` instead of ending the process at once.
+/// Only long running commands call it, the others are still ended by Ctrl-C right away.
+#[cfg(all(not(test), unix))]
+pub fn catch_interrupt() {
+    const SIGINT: i32 = 2;
+    const SIGTERM: i32 = 15;
+    unsafe extern "C" {
+        fn signal(signum: i32, handler: extern "C" fn(i32)) -> usize;
+    }
+    // SAFETY: the handler only stores to an atomic, which is safe to do from a signal handler
+    unsafe {
+        signal(SIGINT, on_interrupt);
+        signal(SIGTERM, on_interrupt);
+    }
+}
+
+/// Makes Ctrl-C and Ctrl-Break set `interrupted` instead of ending the process at once.
+/// Only long running commands call it, the others are still ended by Ctrl-C right away.
+#[cfg(all(not(test), windows))]
+pub fn catch_interrupt() {
+    unsafe extern "system" {
+        fn SetConsoleCtrlHandler(handler: extern "system" fn(u32) -> i32, add: i32) -> i32;
+    }
+    extern "system" fn on_ctrl(_event: u32) -> i32 {
+        on_interrupt(0);
+        1
+    }
+    // SAFETY: the handler only stores to an atomic and is never removed
+    unsafe {
+        SetConsoleCtrlHandler(on_ctrl, 1);
+    }
+}
+
+/// Other platforms keep the default handling, where Ctrl-C ends the process at once
+#[cfg(all(not(test), not(unix), not(windows)))]
+pub fn catch_interrupt() {}
+
+/// Tests share one process, so they never take over its interrupt handling
+#[cfg(test)]
+pub fn catch_interrupt() {}
+
+/// Whether the user asked the process to stop since `catch_interrupt` was called
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
 // Errors
 #[derive(Debug, PartialEq)]
+// Programs are never started under test, so they never fail to
+#[cfg_attr(test, allow(dead_code))]
 pub enum OsImplementationErrorKind {
+    CommandFailed,
     CommandFailedToStart,
     CommandNotRunning,
     OsNotSupported,
@@ -63,6 +691,7 @@ impl fmt::Display for OsImplementationError {
 impl fmt::Display for OsImplementationErrorKind {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
+            OsImplementationErrorKind::CommandFailed => write!(f, "Command failed"),
             OsImplementationErrorKind::CommandFailedToStart => write!(f, "Command failed to start"),
             OsImplementationErrorKind::CommandNotRunning => write!(f, "Command not running"),
             OsImplementationErrorKind::OsNotSupported => write!(f, "OS not supported"),
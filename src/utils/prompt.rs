@@ -0,0 +1,217 @@
+use crate::utils::selection;
+use std::cell::RefCell;
+use std::fmt;
+use std::io::{BufRead, BufReader, Write};
+
+/// Asks the user for missing arguments. Questions are written to stderr so stdout only ever
+/// holds the output of a command.
+pub(crate) struct Prompt {
+    input: RefCell<Box<dyn BufRead>>,
+    output: RefCell<Box<dyn Write>>,
+    interactive: bool,
+}
+
+#[cfg(not(test))]
+fn stdin_is_terminal() -> bool {
+    use std::io::IsTerminal;
+    std::io::stdin().is_terminal()
+}
+
+/// Tests must never wait on the terminal, so prompts are only scripted under test
+#[cfg(test)]
+fn stdin_is_terminal() -> bool {
+    false
+}
+
+impl Prompt {
+    /// Prompts on the terminal. Only interactive if stdin is a terminal and `no_input`, set by
+    /// the global `--no-input` flag, is not, so scripts keep getting an error for missing
+    /// arguments.
+    pub fn stdin(no_input: bool) -> Self {
+        Self {
+            // Not locked up front, so several commands can hold a prompt at once
+            input: RefCell::new(Box::new(BufReader::new(std::io::stdin()))),
+            output: RefCell::new(Box::new(std::io::stderr())),
+            interactive: stdin_is_terminal() && !no_input,
+        }
+    }
+
+    /// An interactive prompt answering with the given lines
+    #[cfg(test)]
+    pub fn scripted(answers: &'static str) -> Self {
+        Self {
+            input: RefCell::new(Box::new(answers.as_bytes())),
+            output: RefCell::new(Box::new(std::io::sink())),
+            interactive: true,
+        }
+    }
+
+    pub fn is_interactive(&self) -> bool {
+        self.interactive
+    }
+
+    /// Asks `question` until `validate` accepts the trimmed answer, printing the reason an
+    /// answer was rejected before asking again.
+    pub fn ask(
+        &self,
+        question: &str,
+        validate: impl Fn(&str) -> Result<(), String>,
+    ) -> Result<String, PromptError> {
+        loop {
+            let answer = self.read_answer(&format!("{question}: "))?;
+            if answer.is_empty() {
+                self.write(&format!("{question} can not be empty\n"))?;
+                continue;
+            }
+            match validate(&answer) {
+                Ok(()) => return Ok(answer),
+                Err(reason) => self.write(&format!("{reason}\n"))?,
+            }
+        }
+    }
+
+    /// Asks a yes or no question, anything but `y` or `yes` is a no
+    pub fn confirm(&self, question: &str) -> Result<bool, PromptError> {
+        let answer = self.read_answer(&format!("{question} [y/N]: "))?;
+        Ok(matches!(answer.to_lowercase().as_str(), "y" | "yes"))
+    }
+
+    /// Lists `options` numbered from 1 and asks for a number until a listed one is given.
+    /// Returns the index of the chosen option.
+    pub fn choose(&self, question: &str, options: &[String]) -> Result<usize, PromptError> {
+        let mut list = String::new();
+        for (i, option) in options.iter().enumerate() {
+            list.push_str(&format!("  {}) {option}\n", i + 1));
+        }
+        self.write(&list)?;
+        loop {
+            let answer = self.read_answer(&format!("{question} [1-{}]: ", options.len()))?;
+            match answer.parse::<usize>() {
+                Ok(n) if n >= 1 && n <= options.len() => return Ok(n - 1),
+                _ => self.write(&format!("Enter a number between 1 and {}\n", options.len()))?,
+            }
+        }
+    }
+
+    /// Shows `list`, the `count` items numbered from 1, and asks for a selection like `1,3-5`
+    /// until a valid one is given. Returns the indexes of the picked items, see `selection::parse`.
+    pub fn pick(
+        &self,
+        question: &str,
+        list: &str,
+        count: usize,
+    ) -> Result<Vec<usize>, PromptError> {
+        self.write(&format!(
+            "{list}
+"
+        ))?;
+        let answer = self.ask(&format!("{question} (like 1,3-5)"), |answer| {
+            selection::parse(answer, count).map(|_| ())
+        })?;
+        Ok(selection::parse(&answer, count).unwrap_or_default())
+    }
+
+    fn read_answer(&self, prompt: &str) -> Result<String, PromptError> {
+        self.write(prompt)?;
+        let mut answer = String::new();
+        let read = self
+            .input
+            .borrow_mut()
+            .read_line(&mut answer)
+            .map_err(|e| PromptError {
+                kind: PromptErrorKind::ReadFailed,
+                message: format!("Could not read answer: {e}"),
+            })?;
+        if read == 0 {
+            return Err(PromptError {
+                kind: PromptErrorKind::InputClosed,
+                message: "Input closed before every question was answered".to_string(),
+            });
+        }
+        Ok(answer.trim().to_string())
+    }
+
+    fn write(&self, s: &str) -> Result<(), PromptError> {
+        let mut output = self.output.borrow_mut();
+        output
+            .write_all(s.as_bytes())
+            .and_then(|_| output.flush())
+            .map_err(|e| PromptError {
+                kind: PromptErrorKind::WriteFailed,
+                message: format!("Could not write prompt: {e}"),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ask_skips_empty_and_invalid_answers() {
+        let prompt = Prompt::scripted("\nbad|name\ngoogle\n");
+        let res = prompt.ask("Link name", |a| match a.contains('|') {
+            true => Err("no bars".to_string()),
+            false => Ok(()),
+        });
+        assert_eq!(res.unwrap(), "google");
+    }
+
+    #[test]
+    fn test_ask_input_closed() {
+        let prompt = Prompt::scripted("");
+        let res = prompt.ask("Link name", |_| Ok(()));
+        assert_eq!(res.unwrap_err().kind, PromptErrorKind::InputClosed);
+    }
+
+    #[test]
+    fn test_confirm() {
+        assert!(Prompt::scripted("y\n").confirm("Save?").unwrap());
+        assert!(Prompt::scripted("YES\n").confirm("Save?").unwrap());
+        assert!(!Prompt::scripted("\n").confirm("Save?").unwrap());
+        assert!(!Prompt::scripted("nope\n").confirm("Save?").unwrap());
+    }
+
+    #[test]
+    fn test_choose() {
+        let options = vec!["google".to_string(), "yahoo".to_string()];
+        let prompt = Prompt::scripted("0\nthree\n2\n");
+        assert_eq!(prompt.choose("Link", &options).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_pick() {
+        let prompt = Prompt::scripted("\n1-9\nfirst\n3,1\n");
+        assert_eq!(prompt.pick("Links", "  1) a", 4).unwrap(), vec![0, 2]);
+    }
+}
+
+// Errors
+#[derive(Debug, PartialEq)]
+pub enum PromptErrorKind {
+    InputClosed,
+    ReadFailed,
+    WriteFailed,
+}
+
+#[derive(Debug)]
+pub struct PromptError {
+    kind: PromptErrorKind,
+    message: String,
+}
+
+impl fmt::Display for PromptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (prompt error: {})", self.message, self.kind)
+    }
+}
+
+impl fmt::Display for PromptErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PromptErrorKind::InputClosed => write!(f, "Input closed"),
+            PromptErrorKind::ReadFailed => write!(f, "Read failed"),
+            PromptErrorKind::WriteFailed => write!(f, "Write failed"),
+        }
+    }
+}
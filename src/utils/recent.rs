@@ -0,0 +1,148 @@
+use crate::utils::backup::utc_timestamp;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{fmt, fs};
+
+/// How many opened links the recent file keeps, the oldest ones are dropped first
+const MAX_ENTRIES: usize = 50;
+
+/// A link opened by tap, one line of the recent file. The fields are separated by tabs, written
+/// as `\t` here:
+///
+/// ```text
+/// 2024-06-01T12:30:05Z\twork\tci
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Entry {
+    /// When the link was opened, see `utc_timestamp`
+    pub time: String,
+    pub parent: String,
+    pub link: String,
+}
+
+impl Entry {
+    pub fn new(parent: &str, link: &str) -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        Self {
+            time: utc_timestamp(now),
+            parent: parent.trim().to_string(),
+            link: link.trim().to_string(),
+        }
+    }
+
+    /// `None` for lines that are not an entry, which are skipped when reading the recent file
+    fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.splitn(3, '\t').map(str::to_string);
+        Some(Self {
+            time: fields.next()?,
+            parent: fields.next()?,
+            link: fields.next()?,
+        })
+    }
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}\t{}\t{}", self.time, self.parent, self.link)
+    }
+}
+
+/// The recent file kept next to a data file: `.tap_data` keeps the links opened last in
+/// `.tap_recent`, `demo.tap_data` in `demo.tap_recent`, and any other file name gets
+/// `.tap_recent` appended
+pub(crate) fn recent_path_for(data: &Path) -> PathBuf {
+    let file_name = data.file_name().unwrap_or_default().to_string_lossy();
+    match file_name.strip_suffix("tap_data") {
+        Some(stem) => data.with_file_name(format!("{stem}tap_recent")),
+        None => data.with_file_name(format!("{file_name}.tap_recent")),
+    }
+}
+
+/// Adds `opened` as the most recent entry of `entries`, oldest first. A link is listed once, at
+/// the time it was opened last, and only the last `MAX_ENTRIES` links are kept.
+pub(crate) fn push(entries: &mut Vec<Entry>, opened: Entry) {
+    entries.retain(|e| e.parent != opened.parent || e.link != opened.link);
+    entries.push(opened);
+    let extra = entries.len().saturating_sub(MAX_ENTRIES);
+    entries.drain(..extra);
+}
+
+/// Every entry of the recent file at `path`, oldest first. A missing file has none.
+pub(crate) fn read(path: &Path) -> std::io::Result<Vec<Entry>> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(contents.lines().filter_map(Entry::parse).collect()),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(vec![]),
+        Err(e) => Err(e),
+    }
+}
+
+/// Replaces the recent file at `path` with `entries`
+pub(crate) fn write(path: &Path, entries: &[Entry]) -> std::io::Result<()> {
+    let lines: String = entries.iter().map(|e| format!("{e}\n")).collect();
+    fs::write(path, lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(parent: &str, link: &str) -> Entry {
+        Entry {
+            time: "2024-06-01T12:30:05Z".to_string(),
+            parent: parent.to_string(),
+            link: link.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_entry_round_trip() {
+        let line = entry("work", "ci").to_string();
+        assert_eq!(line, "2024-06-01T12:30:05Z\twork\tci");
+        assert_eq!(Entry::parse(&line), Some(entry("work", "ci")));
+        assert_eq!(Entry::parse("not an entry"), None);
+    }
+
+    #[test]
+    fn test_recent_path_for() {
+        assert_eq!(
+            recent_path_for(Path::new("/data/.tap_data")),
+            Path::new("/data/.tap_recent")
+        );
+        assert_eq!(
+            recent_path_for(Path::new("/data/links")),
+            Path::new("/data/links.tap_recent")
+        );
+    }
+
+    #[test]
+    fn test_push() {
+        let mut entries = vec![entry("work", "ci"), entry("work", "jira")];
+        // Opening a link again moves it to the end
+        push(&mut entries, entry("work", "ci"));
+        assert_eq!(entries, [entry("work", "jira"), entry("work", "ci")]);
+        let mut entries: Vec<Entry> = (0..MAX_ENTRIES)
+            .map(|i| entry("work", &i.to_string()))
+            .collect();
+        push(&mut entries, entry("home", "nas"));
+        assert_eq!(entries.len(), MAX_ENTRIES);
+        assert_eq!(entries[0], entry("work", "1"));
+        assert_eq!(entries.last(), Some(&entry("home", "nas")));
+    }
+
+    #[test]
+    fn test_read_write() {
+        let dir = std::env::temp_dir().join(format!("tap_recent_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(".tap_recent");
+        assert_eq!(read(&path).unwrap(), []);
+        write(&path, &[entry("work", "ci"), entry("home", "nas")]).unwrap();
+        assert_eq!(
+            read(&path).unwrap(),
+            [entry("work", "ci"), entry("home", "nas")]
+        );
+    }
+}
@@ -0,0 +1,763 @@
+use std::fmt;
+
+/// A regular expression for `tap --grep`. It supports the common syntax: literals, `.`, classes
+/// like `[A-Z]` and `[^/]`, the `\d \w \s` escapes (and their `\D \W \S` negations), anchors `^ $`
+/// and `\b`, groups `(...)`, `(?:...)` and `(?<name>...)` with `|` alternation, the
+/// `* + ? {n} {n,} {n,m}` repetitions, lazy when followed by `?`, and a leading `(?i)` to ignore
+/// case. Any other syntax, like POSIX classes `[[:alpha:]]`, Unicode classes `\p{L}`, other flags
+/// or look-around, is refused with an error instead of being read as something else.
+///
+/// The pattern is compiled to a program for a Pike VM: matching follows every way the pattern can
+/// match at once, one character of the text at a time, never backtracking. It takes time linear
+/// in the length of the text whatever the pattern, so patterns like `(a+)+$` can not blow up.
+#[derive(Debug)]
+pub(crate) struct Regex {
+    program: Vec<Inst>,
+    case_insensitive: bool,
+}
+
+/// The most instructions a pattern compiles to, reached by nesting counted repetitions like
+/// `(a{1000}){1000}`
+const MAX_PROGRAM_LEN: usize = 100_000;
+
+/// An instruction of the program a pattern compiles to, see `Compiler`
+#[derive(Debug)]
+enum Inst {
+    /// Consumes this character
+    Char(char),
+    /// Consumes any character but a newline
+    Any,
+    /// Consumes a character of the class
+    Class(Class),
+    /// Goes on only where the assertion holds
+    Assert(Assertion),
+    /// Goes on at both instructions
+    Split(usize, usize),
+    Jump(usize),
+    Match,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Assertion {
+    /// `^`
+    Start,
+    /// `$`
+    End,
+    /// `\b`, or `\B` when negated
+    WordBoundary { negated: bool },
+}
+
+/// A parsed pattern
+#[derive(Debug, PartialEq)]
+enum Node {
+    Empty,
+    Char(char),
+    /// `.`, any character but a newline
+    Any,
+    Class(Class),
+    Assert(Assertion),
+    Concat(Vec<Node>),
+    Alternation(Vec<Node>),
+    /// Whether the repetition is lazy is not kept: it only changes which match is found, not
+    /// whether there is one
+    Repeat {
+        node: Box<Node>,
+        min: u32,
+        max: Option<u32>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Class {
+    ranges: Vec<(char, char)>,
+    negated: bool,
+}
+
+impl Class {
+    fn digit() -> Vec<(char, char)> {
+        vec![('0', '9')]
+    }
+
+    fn word() -> Vec<(char, char)> {
+        vec![('0', '9'), ('A', 'Z'), ('_', '_'), ('a', 'z')]
+    }
+
+    fn space() -> Vec<(char, char)> {
+        vec![('\t', '\r'), (' ', ' ')]
+    }
+
+    fn matches(&self, c: char, case_insensitive: bool) -> bool {
+        let in_ranges = |c: char| self.ranges.iter().any(|&(lo, hi)| lo <= c && c <= hi);
+        let found = in_ranges(c)
+            || (case_insensitive
+                && (c.to_lowercase().any(in_ranges) || c.to_uppercase().any(in_ranges)));
+        found != self.negated
+    }
+}
+
+impl Regex {
+    /// Parses `pattern`, see `Regex` for the supported syntax
+    pub fn new(pattern: &str) -> Result<Self, RegexError> {
+        let case_insensitive = pattern.starts_with("(?i)");
+        let mut parser = Parser {
+            pattern,
+            chars: pattern.chars().collect(),
+            pos: match case_insensitive {
+                true => "(?i)".len(),
+                false => 0,
+            },
+        };
+        let root = parser.alternation()?;
+        if parser.pos < parser.chars.len() {
+            // Alternation only stops early at a `)` without a `(`
+            return Err(parser.error(parser.pos, "unopened group"));
+        }
+        let mut compiler = Compiler { program: vec![] };
+        compiler.node(&root);
+        compiler.program.push(Inst::Match);
+        if compiler.program.len() > MAX_PROGRAM_LEN {
+            return Err(parser.error(0, "pattern too large once its repetitions are expanded"));
+        }
+        Ok(Self {
+            program: compiler.program,
+            case_insensitive,
+        })
+    }
+
+    /// Makes letters match regardless of their case
+    pub fn case_insensitive(mut self) -> Self {
+        self.case_insensitive = true;
+        self
+    }
+
+    /// Whether the pattern matches anywhere in `text`. Every thread of the program is stepped
+    /// over the text together, and a thread that reaches an instruction another one already
+    /// reached at the same position is dropped, so each character is looked at once per
+    /// instruction at most.
+    pub fn is_match(&self, text: &str) -> bool {
+        let text: Vec<char> = text.chars().collect();
+        let mut current = Threads::new(self.program.len());
+        let mut next = Threads::new(self.program.len());
+        for pos in 0..=text.len() {
+            // A match can start at any position
+            self.add(&mut current, 0, &text, pos);
+            for &pc in &current.pcs {
+                let consumed = match &self.program[pc] {
+                    Inst::Match => return true,
+                    Inst::Char(c) => text.get(pos).is_some_and(|&t| self.same_char(t, *c)),
+                    Inst::Any => text.get(pos).is_some_and(|&t| t != '\n'),
+                    Inst::Class(class) => text
+                        .get(pos)
+                        .is_some_and(|&t| class.matches(t, self.case_insensitive)),
+                    Inst::Assert(_) | Inst::Split(..) | Inst::Jump(_) => false,
+                };
+                if consumed {
+                    self.add(&mut next, pc + 1, &text, pos + 1);
+                }
+            }
+            std::mem::swap(&mut current, &mut next);
+            next.clear();
+        }
+        false
+    }
+
+    /// Adds the thread at `pc` to `threads`, following the jumps, splits, and assertions that
+    /// hold at `pos` to the instructions consuming a character or matching
+    fn add(&self, threads: &mut Threads, pc: usize, text: &[char], pos: usize) {
+        let mut stack = vec![pc];
+        while let Some(pc) = stack.pop() {
+            if !threads.insert(pc) {
+                continue;
+            }
+            match &self.program[pc] {
+                Inst::Jump(to) => stack.push(*to),
+                Inst::Split(first, second) => stack.extend([*second, *first]),
+                Inst::Assert(assertion) if holds(*assertion, text, pos) => stack.push(pc + 1),
+                _ => {}
+            }
+        }
+    }
+
+    fn same_char(&self, a: char, b: char) -> bool {
+        a == b || (self.case_insensitive && a.to_lowercase().eq(b.to_lowercase()))
+    }
+}
+
+/// Whether `assertion` holds at `pos` in `text`
+fn holds(assertion: Assertion, text: &[char], pos: usize) -> bool {
+    match assertion {
+        Assertion::Start => pos == 0,
+        Assertion::End => pos == text.len(),
+        Assertion::WordBoundary { negated } => {
+            let is_word = |c: &char| c.is_alphanumeric() || *c == '_';
+            let before = pos > 0 && is_word(&text[pos - 1]);
+            let after = text.get(pos).is_some_and(is_word);
+            (before != after) != negated
+        }
+    }
+}
+
+/// The instructions reached at one position of the text, each once, in the order reached
+struct Threads {
+    pcs: Vec<usize>,
+    seen: Vec<bool>,
+}
+
+impl Threads {
+    fn new(len: usize) -> Self {
+        Self {
+            pcs: Vec::with_capacity(len),
+            seen: vec![false; len],
+        }
+    }
+
+    /// Adds `pc`, `false` when it was already there
+    fn insert(&mut self, pc: usize) -> bool {
+        let added = !self.seen[pc];
+        if added {
+            self.seen[pc] = true;
+            self.pcs.push(pc);
+        }
+        added
+    }
+
+    fn clear(&mut self) {
+        for &pc in &self.pcs {
+            self.seen[pc] = false;
+        }
+        self.pcs.clear();
+    }
+}
+
+/// Compiles a parsed pattern to the instructions of a `Regex`
+struct Compiler {
+    program: Vec<Inst>,
+}
+
+impl Compiler {
+    fn node(&mut self, node: &Node) {
+        // Counted repetitions can expand past any sensible size, which `Regex::new` refuses
+        if self.program.len() > MAX_PROGRAM_LEN {
+            return;
+        }
+        match node {
+            Node::Empty => {}
+            Node::Char(c) => self.program.push(Inst::Char(*c)),
+            Node::Any => self.program.push(Inst::Any),
+            Node::Class(class) => self.program.push(Inst::Class(class.clone())),
+            Node::Assert(assertion) => self.program.push(Inst::Assert(*assertion)),
+            Node::Concat(nodes) => nodes.iter().for_each(|n| self.node(n)),
+            Node::Alternation(nodes) => {
+                let mut jumps = vec![];
+                for (i, n) in nodes.iter().enumerate() {
+                    if i + 1 == nodes.len() {
+                        self.node(n);
+                        break;
+                    }
+                    let split = self.placeholder();
+                    self.node(n);
+                    jumps.push(self.placeholder());
+                    self.program[split] = Inst::Split(split + 1, self.program.len());
+                }
+                let end = self.program.len();
+                for jump in jumps {
+                    self.program[jump] = Inst::Jump(end);
+                }
+            }
+            Node::Repeat { node, min, max } => {
+                for _ in 0..*min {
+                    self.node(node);
+                }
+                match max {
+                    // `x*`: split into another x or past it, jumping back after each x
+                    None => {
+                        let split = self.placeholder();
+                        self.node(node);
+                        self.program.push(Inst::Jump(split));
+                        self.program[split] = Inst::Split(split + 1, self.program.len());
+                    }
+                    // `x?` for each count above the minimum
+                    Some(max) => {
+                        for _ in *min..*max {
+                            let split = self.placeholder();
+                            self.node(node);
+                            self.program[split] = Inst::Split(split + 1, self.program.len());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// An instruction filled in once the ones after it are compiled
+    fn placeholder(&mut self) -> usize {
+        self.program.push(Inst::Match);
+        self.program.len() - 1
+    }
+}
+
+struct Parser<'a> {
+    pattern: &'a str,
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let found = self.peek() == Some(c);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn error(&self, pos: usize, message: &str) -> RegexError {
+        RegexError {
+            pattern: self.pattern.to_string(),
+            pos,
+            message: message.to_string(),
+        }
+    }
+
+    /// `a|b|c`, up to the end of the pattern or of the group
+    fn alternation(&mut self) -> Result<Node, RegexError> {
+        let mut branches = vec![self.concat()?];
+        while self.eat('|') {
+            branches.push(self.concat()?);
+        }
+        Ok(match branches.len() {
+            1 => branches.pop().expect("There is one branch"),
+            _ => Node::Alternation(branches),
+        })
+    }
+
+    fn concat(&mut self) -> Result<Node, RegexError> {
+        let mut nodes = vec![];
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let atom = self.atom()?;
+            nodes.push(self.repetition(atom)?);
+        }
+        Ok(match nodes.len() {
+            0 => Node::Empty,
+            1 => nodes.pop().expect("There is one node"),
+            _ => Node::Concat(nodes),
+        })
+    }
+
+    fn atom(&mut self) -> Result<Node, RegexError> {
+        let start = self.pos;
+        let c = self.chars[self.pos];
+        self.pos += 1;
+        match c {
+            '(' => {
+                if self.eat('?') {
+                    self.group_kind(start)?;
+                }
+                let node = self.alternation()?;
+                if !self.eat(')') {
+                    return Err(self.error(start, "unclosed group"));
+                }
+                Ok(node)
+            }
+            '[' => self.class(start),
+            '.' => Ok(Node::Any),
+            '^' => Ok(Node::Assert(Assertion::Start)),
+            '$' => Ok(Node::Assert(Assertion::End)),
+            '\\' => self.escape(start),
+            '*' | '+' | '?' | '{' => {
+                Err(self.error(start, "repetition operator missing expression"))
+            }
+            c => Ok(Node::Char(c)),
+        }
+    }
+
+    /// The kind of a group after its `(?` at `start`, leaving the position at its first character.
+    /// Groups are never captured, so `(?:...)` and named groups are plain groups.
+    fn group_kind(&mut self, start: usize) -> Result<(), RegexError> {
+        if self.eat(':') {
+            return Ok(());
+        }
+        if self.eat('=')
+            || self.eat('!')
+            || self.chars[self.pos..].starts_with(&['<', '='])
+            || self.chars[self.pos..].starts_with(&['<', '!'])
+        {
+            return Err(self.error(start, "look-around is not supported"));
+        }
+        if (self.eat('P') && self.peek() == Some('<')) || self.peek() == Some('<') {
+            self.pos += 1;
+            let name = self.pos;
+            while self.peek().is_some_and(|c| c.is_alphanumeric() || c == '_') {
+                self.pos += 1;
+            }
+            if self.pos == name || !self.eat('>') {
+                return Err(self.error(start, "invalid group name"));
+            }
+            return Ok(());
+        }
+        Err(self.error(
+            start,
+            "flags are not supported, other than (?i) at the start of the pattern",
+        ))
+    }
+
+    /// The escape after a `\` at `start`
+    fn escape(&mut self, start: usize) -> Result<Node, RegexError> {
+        let Some(c) = self.peek() else {
+            return Err(self.error(start, "incomplete escape sequence"));
+        };
+        self.pos += 1;
+        let class = |ranges, negated| Ok(Node::Class(Class { ranges, negated }));
+        match c {
+            'd' => class(Class::digit(), false),
+            'D' => class(Class::digit(), true),
+            'w' => class(Class::word(), false),
+            'W' => class(Class::word(), true),
+            's' => class(Class::space(), false),
+            'S' => class(Class::space(), true),
+            'b' => Ok(Node::Assert(Assertion::WordBoundary { negated: false })),
+            'B' => Ok(Node::Assert(Assertion::WordBoundary { negated: true })),
+            c => self.escaped_char(start, c).map(Node::Char),
+        }
+    }
+
+    /// A character given as an escape, like `\.` or `\n`
+    fn escaped_char(&self, start: usize, c: char) -> Result<char, RegexError> {
+        match c {
+            'n' => Ok('\n'),
+            't' => Ok('\t'),
+            'r' => Ok('\r'),
+            'p' | 'P' => Err(self.error(start, "Unicode classes like \\p{L} are not supported")),
+            c if c.is_alphanumeric() => Err(self.error(start, "unrecognized escape sequence")),
+            c => Ok(c),
+        }
+    }
+
+    /// A class like `[^a-z_]`, after its `[` at `start`
+    fn class(&mut self, start: usize) -> Result<Node, RegexError> {
+        let negated = self.eat('^');
+        let mut ranges = vec![];
+        let mut first = true;
+        loop {
+            let item = self.pos;
+            let Some(c) = self.peek() else {
+                return Err(self.error(start, "unclosed character class"));
+            };
+            self.pos += 1;
+            if c == ']' && !first {
+                break;
+            }
+            first = false;
+            match (c, self.peek()) {
+                ('[', Some(':')) => {
+                    return Err(self.error(
+                        item,
+                        "POSIX classes like [:alpha:] are not supported, use a range like a-zA-Z",
+                    ));
+                }
+                ('[', _) => {
+                    return Err(self.error(
+                        item,
+                        "nested classes are not supported, escape a [ in a class as \\[",
+                    ));
+                }
+                ('&', Some('&')) | ('~', Some('~')) | ('-', Some('-')) => {
+                    return Err(self.error(item, "class set operations are not supported"));
+                }
+                _ => {}
+            }
+            let lo = match c {
+                '\\' => match self.peek() {
+                    Some(e @ ('d' | 'w' | 's')) => {
+                        self.pos += 1;
+                        ranges.extend(match e {
+                            'd' => Class::digit(),
+                            'w' => Class::word(),
+                            _ => Class::space(),
+                        });
+                        continue;
+                    }
+                    Some(e) => {
+                        self.pos += 1;
+                        self.escaped_char(item, e)?
+                    }
+                    None => return Err(self.error(item, "incomplete escape sequence")),
+                },
+                c => c,
+            };
+            // A `-` before the closing `]` is a literal dash
+            if self.peek() == Some('-') && self.chars.get(self.pos + 1).is_some_and(|&c| c != ']') {
+                self.pos += 1;
+                let hi = self.chars[self.pos];
+                self.pos += 1;
+                let hi = match hi {
+                    '\\' => match self.peek() {
+                        Some(e) => {
+                            self.pos += 1;
+                            self.escaped_char(item, e)?
+                        }
+                        None => return Err(self.error(item, "incomplete escape sequence")),
+                    },
+                    hi => hi,
+                };
+                if hi < lo {
+                    return Err(self.error(item, "invalid character class range"));
+                }
+                ranges.push((lo, hi));
+            } else {
+                ranges.push((lo, lo));
+            }
+        }
+        Ok(Node::Class(Class { ranges, negated }))
+    }
+
+    /// The repetition operator following `atom`, if any
+    fn repetition(&mut self, atom: Node) -> Result<Node, RegexError> {
+        let start = self.pos;
+        let (min, max) = match self.peek() {
+            Some('*') => (0, None),
+            Some('+') => (1, None),
+            Some('?') => (0, Some(1)),
+            Some('{') => {
+                self.pos += 1;
+                let bounds = self.bounds(start)?;
+                self.pos -= 1;
+                bounds
+            }
+            _ => return Ok(atom),
+        };
+        self.pos += 1;
+        if matches!(atom, Node::Assert(_)) {
+            return Err(self.error(start, "repetition operator missing expression"));
+        }
+        // Lazy repetitions match the same texts
+        self.eat('?');
+        if matches!(self.peek(), Some('*' | '+' | '?' | '{')) {
+            return Err(self.error(self.pos, "repetition operator missing expression"));
+        }
+        Ok(Node::Repeat {
+            node: Box::new(atom),
+            min,
+            max,
+        })
+    }
+
+    /// The `n}`, `n,}` or `n,m}` after a `{` at `start`, leaving the position after the `}`
+    fn bounds(&mut self, start: usize) -> Result<(u32, Option<u32>), RegexError> {
+        let number = |parser: &mut Self| -> Option<u32> {
+            let from = parser.pos;
+            while parser.peek().is_some_and(|c| c.is_ascii_digit()) {
+                parser.pos += 1;
+            }
+            parser.chars[from..parser.pos]
+                .iter()
+                .collect::<String>()
+                .parse()
+                .ok()
+        };
+        let invalid = |parser: &Self| parser.error(start, "invalid repetition count");
+        let min = number(self).ok_or_else(|| invalid(self))?;
+        let max = match self.eat(',') {
+            true if self.peek() == Some('}') => None,
+            true => Some(number(self).ok_or_else(|| invalid(self))?),
+            false => Some(min),
+        };
+        if !self.eat('}') {
+            return Err(self.error(start, "unclosed counted repetition"));
+        }
+        if max.is_some_and(|max| max < min) {
+            return Err(invalid(self));
+        }
+        Ok((min, max))
+    }
+}
+
+/// A pattern that can not be parsed, or uses syntax that is not supported. It is printed with the
+/// pattern and a caret under the position of the problem.
+#[derive(Debug, PartialEq)]
+pub(crate) struct RegexError {
+    pattern: String,
+    /// Position of the problem in the pattern, in characters
+    pos: usize,
+    message: String,
+}
+
+impl fmt::Display for RegexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Invalid pattern, {}:", self.message)?;
+        writeln!(f, "    {}", self.pattern)?;
+        write!(f, "    {}^", " ".repeat(self.pos))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_match(pattern: &str, text: &str) -> bool {
+        Regex::new(pattern).unwrap().is_match(text)
+    }
+
+    fn error(pattern: &str) -> String {
+        Regex::new(pattern).unwrap_err().message
+    }
+
+    #[test]
+    fn test_literals_and_any() {
+        assert!(is_match("google", "https://google.com"));
+        assert!(!is_match("bing", "https://google.com"));
+        assert!(is_match("g..gle", "https://google.com"));
+        assert!(is_match(r"google\.com", "https://google.com"));
+        assert!(!is_match(r"google\.com", "https://googleXcom"));
+        assert!(is_match("", "anything"));
+    }
+
+    #[test]
+    fn test_anchors() {
+        assert!(is_match("^https://", "https://google.com"));
+        assert!(!is_match("^google", "https://google.com"));
+        assert!(is_match(r"\.com$", "https://google.com"));
+        assert!(!is_match(r"\.com$", "https://google.com/x"));
+        assert!(is_match(r"\bcom\b", "google.com"));
+        assert!(!is_match(r"\bgoo\b", "google.com"));
+    }
+
+    #[test]
+    fn test_classes() {
+        assert!(is_match("[A-Z]+-[0-9]", "https://jira.internal/ENG-42"));
+        assert!(!is_match("[A-Z]+-[0-9]", "https://jira.internal/eng-42"));
+        assert!(is_match("^[^/]+$", "google"));
+        assert!(!is_match("^[^/]+$", "google/x"));
+        assert!(is_match("[-a]", "-"));
+        assert!(is_match("[a-]", "-"));
+        assert!(is_match("[]]", "]"));
+        assert!(is_match(r"[\d.]+", "1.2"));
+        assert!(is_match(r"\w+@\w+", "me@host"));
+        assert!(!is_match(r"\S", "  \t"));
+    }
+
+    #[test]
+    fn test_repetitions() {
+        assert!(is_match(
+            r"jira\.internal/[A-Z]+-\d+",
+            "https://jira.internal/ENG-42"
+        ));
+        assert!(!is_match(
+            r"jira\.internal/[A-Z]+-\d+",
+            "https://jira.internal/ENG-"
+        ));
+        assert!(is_match("^a{2,3}$", "aaa"));
+        assert!(!is_match("^a{2,3}$", "aaaa"));
+        assert!(is_match("^a{2}$", "aa"));
+        assert!(is_match("^a{2,}$", "aaaaa"));
+        assert!(is_match("^ab*?c$", "abbbc"));
+        assert!(is_match("^(a*)*$", "aaaa"));
+        assert!(is_match("^colou?r$", "color"));
+    }
+
+    #[test]
+    fn test_pathological_patterns() {
+        // Backtracking would try every way of splitting the a's between the repetitions
+        let text = format!("{}b", "a".repeat(5_000));
+        assert!(!is_match("^(a+)+$", &text));
+        assert!(!is_match("^(a|aa)*c", &text));
+        assert!(is_match("(a*)*b", &text));
+        assert!(!is_match("^(a?){30}a{30}$", &"a".repeat(29)));
+        assert!(is_match("^(a?){30}a{30}$", &"a".repeat(30)));
+    }
+
+    #[test]
+    fn test_long_values() {
+        let text = "a".repeat(200_000);
+        assert!(!is_match(".*z", &text));
+        assert!(is_match(".*z", &format!("{text}z")));
+        assert!(is_match("^a+$", &text));
+        assert!(is_match(r"^(a|b)*\b$", &text));
+    }
+
+    #[test]
+    fn test_groups_and_alternation() {
+        assert!(is_match("^(http|ftp)s?://", "ftps://files"));
+        assert!(!is_match("^(http|ftp)s?://", "file://x"));
+        assert!(is_match("^(?:ab)+$", "ababab"));
+        assert!(is_match("google|yahoo", "https://yahoo.com"));
+        assert!(is_match("^(|x)$", ""));
+    }
+
+    #[test]
+    fn test_case_insensitive() {
+        let regex = Regex::new("JIRA[a-z]").unwrap().case_insensitive();
+        assert!(regex.is_match("https://jirAx.y"));
+        assert!(!Regex::new("JIRA").unwrap().is_match("https://jira.x"));
+        assert!(is_match("(?i)^JIRA$", "jira"));
+        assert!(is_match("(?i)", "anything"));
+    }
+
+    #[test]
+    fn test_named_groups() {
+        assert!(is_match("^(?<scheme>https?)://", "https://x"));
+        assert!(is_match("^(?P<ticket>[A-Z]+-\\d+)$", "ENG-42"));
+        assert!(!is_match("^(?P<ticket>[A-Z]+-\\d+)$", "ENG-"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(error("[A-Z"), "unclosed character class");
+        assert_eq!(error("(ab"), "unclosed group");
+        assert_eq!(error("ab)"), "unopened group");
+        assert_eq!(error("*a"), "repetition operator missing expression");
+        assert_eq!(error("a**"), "repetition operator missing expression");
+        assert_eq!(error(r"\q"), "unrecognized escape sequence");
+        assert_eq!(error("[z-a]"), "invalid character class range");
+        assert_eq!(error("a{3,1}"), "invalid repetition count");
+        assert_eq!(error("a{2"), "unclosed counted repetition");
+        assert_eq!(
+            error("a(?i)b"),
+            "flags are not supported, other than (?i) at the start of the pattern"
+        );
+        assert_eq!(
+            error("(?s).*"),
+            "flags are not supported, other than (?i) at the start of the pattern"
+        );
+        assert_eq!(error("a(?=b)"), "look-around is not supported");
+        assert_eq!(error("(?<!a)b"), "look-around is not supported");
+        assert_eq!(error("(?<>a)"), "invalid group name");
+        assert_eq!(
+            error("[[:alpha:]]"),
+            "POSIX classes like [:alpha:] are not supported, use a range like a-zA-Z"
+        );
+        assert_eq!(
+            error("[a[b]]"),
+            "nested classes are not supported, escape a [ in a class as \\["
+        );
+        assert_eq!(error("[a-z&&b]"), "class set operations are not supported");
+        assert_eq!(
+            error(r"\p{L}"),
+            r"Unicode classes like \p{L} are not supported"
+        );
+        assert_eq!(
+            error(r"[\pL]"),
+            r"Unicode classes like \p{L} are not supported"
+        );
+        assert_eq!(error(r"\D[\D]"), "unrecognized escape sequence");
+        assert_eq!(
+            error("(a{1000}){1000}"),
+            "pattern too large once its repetitions are expanded"
+        );
+    }
+
+    #[test]
+    fn test_display_error() {
+        assert_eq!(
+            Regex::new(r"jira\.internal/[A-Z+").unwrap_err().to_string(),
+            "Invalid pattern, unclosed character class:\n    jira\\.internal/[A-Z+\n                   ^"
+        );
+    }
+}
@@ -0,0 +1,52 @@
+/// The items picked by a selection like `1,3-5` out of `count` numbered from 1: single numbers
+/// and ranges separated by commas, spaces allowed around them. Returns the indexes of the
+/// picked items from 0, in order and each only once, or why the selection is not valid.
+pub(crate) fn parse(selection: &str, count: usize) -> Result<Vec<usize>, String> {
+    let number = |s: &str| -> Result<usize, String> {
+        match s.trim().parse::<usize>() {
+            Ok(n) if n >= 1 && n <= count => Ok(n),
+            Ok(n) => Err(format!("{n} is not between 1 and {count}")),
+            Err(_) => Err(format!("'{}' is not a number", s.trim())),
+        }
+    };
+    let mut picked = vec![false; count];
+    for part in selection.split(',').map(str::trim) {
+        if part.is_empty() {
+            return Err("Expected numbers or ranges like 1,3-5 separated by commas".to_string());
+        }
+        let (first, last) = match part.split_once('-') {
+            Some((first, last)) => (number(first)?, number(last)?),
+            None => (number(part)?, number(part)?),
+        };
+        if first > last {
+            return Err(format!("The range {part} ends before it starts"));
+        }
+        picked[first - 1..last].fill(true);
+    }
+    Ok((0..count).filter(|&i| picked[i]).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_numbers_and_ranges() {
+        assert_eq!(parse("2", 5), Ok(vec![1]));
+        assert_eq!(parse("1,3-5", 5), Ok(vec![0, 2, 3, 4]));
+        assert_eq!(parse(" 4 , 1 - 2 ,2", 5), Ok(vec![0, 1, 3]));
+        assert_eq!(parse("3-3", 3), Ok(vec![2]));
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_selections() {
+        assert!(parse("", 5).is_err());
+        assert!(parse("1,,2", 5).is_err());
+        assert!(parse("0", 5).is_err());
+        assert!(parse("6", 5).is_err());
+        assert!(parse("4-2", 5).is_err());
+        assert!(parse("1-", 5).is_err());
+        assert!(parse("all", 5).is_err());
+        assert!(parse("-1", 5).is_err());
+    }
+}
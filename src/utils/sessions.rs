@@ -0,0 +1,113 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// Named lists of links opened together, by session name, each link as its parent entity and
+/// name in the order they were saved. Sessions are saved with `tap --session save` and kept in
+/// their own file next to the data file, one link per line with the fields separated by tabs,
+/// written as `\t` here:
+///
+/// ```text
+/// morning\twork\tci
+/// morning\tpersonal\tcalendar
+/// ```
+///
+/// Links are kept by name and looked up again when the session is opened, so a link that was
+/// deleted since is only reported then.
+pub(crate) type Sessions = BTreeMap<String, Vec<(String, String)>>;
+
+/// The sessions file kept next to a data file: `.tap_data` keeps its sessions in
+/// `.tap_sessions`, `demo.tap_data` in `demo.tap_sessions`, and any other file name gets
+/// `.tap_sessions` appended
+pub(crate) fn sessions_path_for(data: &Path) -> PathBuf {
+    let file_name = data.file_name().unwrap_or_default().to_string_lossy();
+    match file_name.strip_suffix("tap_data") {
+        Some(stem) => data.with_file_name(format!("{stem}tap_sessions")),
+        None => data.with_file_name(format!("{file_name}.tap_sessions")),
+    }
+}
+
+/// The sessions in the file at `path`, none when there is no such file. Lines that are not a
+/// link of a session are skipped.
+pub(crate) fn read(path: &Path) -> std::io::Result<Sessions> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Sessions::new()),
+        Err(e) => return Err(e),
+    };
+    let mut sessions = Sessions::new();
+    for line in contents.lines() {
+        let mut fields = line.splitn(3, '\t').map(str::trim);
+        if let (Some(name), Some(parent), Some(link)) =
+            (fields.next(), fields.next(), fields.next())
+            && !name.is_empty()
+            && !parent.is_empty()
+            && !link.is_empty()
+        {
+            sessions
+                .entry(name.to_string())
+                .or_default()
+                .push((parent.to_string(), link.to_string()));
+        }
+    }
+    Ok(sessions)
+}
+
+/// Replaces the file at `path` with the sessions, removing it when there are none
+pub(crate) fn write(path: &Path, sessions: &Sessions) -> std::io::Result<()> {
+    if sessions.is_empty() {
+        return match fs::remove_file(path) {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        };
+    }
+    let lines: String = sessions
+        .iter()
+        .flat_map(|(name, links)| {
+            links
+                .iter()
+                .map(move |(parent, link)| format!("{name}\t{parent}\t{link}\n"))
+        })
+        .collect();
+    fs::write(path, lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sessions_path_for() {
+        assert_eq!(
+            sessions_path_for(Path::new("/data/.tap_data")),
+            Path::new("/data/.tap_sessions")
+        );
+        assert_eq!(
+            sessions_path_for(Path::new("/data/links")),
+            Path::new("/data/links.tap_sessions")
+        );
+    }
+
+    #[test]
+    fn test_sessions_round_trip() {
+        let dir = std::env::temp_dir().join(format!("tap_sessions_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(".tap_sessions");
+        assert_eq!(read(&path).unwrap(), Sessions::new());
+        // The links keep the order they were saved in
+        let sessions = Sessions::from([(
+            "morning".to_string(),
+            vec![
+                ("work".to_string(), "standup".to_string()),
+                ("personal".to_string(), "calendar".to_string()),
+                ("work".to_string(), "ci".to_string()),
+            ],
+        )]);
+        write(&path, &sessions).unwrap();
+        assert_eq!(read(&path).unwrap(), sessions);
+        write(&path, &Sessions::new()).unwrap();
+        assert!(!path.exists());
+    }
+}
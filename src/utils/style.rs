@@ -0,0 +1,118 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// The `no_color` of the `Context` a command runs in, see `cli::OutputScope`. Like `log::LEVEL`
+/// it is not passed down, as messages are styled where they are built, mostly far from a
+/// `Context`.
+static NO_COLOR: AtomicBool = AtomicBool::new(false);
+
+/// Turns colors off, or back on, for everything printed afterwards, see `cli::OutputScope`
+pub(crate) fn set_no_color(no_color: bool) {
+    NO_COLOR.store(no_color, Ordering::Relaxed);
+}
+
+/// Where styled text is printed, colors are only used if that stream is a terminal
+#[derive(Clone, Copy)]
+enum Stream {
+    Stdout,
+    Stderr,
+}
+
+const BOLD: &str = "1";
+const RED: &str = "31";
+const GREEN: &str = "32";
+const BLUE: &str = "34";
+const CYAN: &str = "36";
+const BOLD_YELLOW: &str = "1;33";
+
+/// Colors are used unless `--no-color` was passed, the `NO_COLOR` environment variable is set
+/// (see https://no-color.org), or the text is not printed to a terminal, so piped output stays
+/// plain.
+fn enabled(stream: Stream) -> bool {
+    if NO_COLOR.load(Ordering::Relaxed) {
+        return false;
+    }
+    if std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+        return false;
+    }
+    is_terminal(stream)
+}
+
+#[cfg(not(test))]
+fn is_terminal(stream: Stream) -> bool {
+    use std::io::IsTerminal;
+    match stream {
+        Stream::Stdout => std::io::stdout().is_terminal(),
+        Stream::Stderr => std::io::stderr().is_terminal(),
+    }
+}
+
+/// Tests compare plain output, so they never print to a terminal
+#[cfg(test)]
+fn is_terminal(_stream: Stream) -> bool {
+    false
+}
+
+/// Whether stdout gets more than plain ASCII, like the box-drawing characters of `tap --tree`.
+/// Like colors, they are left out when printing to a file or another program, or with `--no-color`.
+pub(crate) fn fancy() -> bool {
+    enabled(Stream::Stdout)
+}
+
+/// The width of the terminal stdout prints to, from the `COLUMNS` environment variable shells set,
+/// or 80 columns. `None` when stdout is not a terminal, so output read by programs is never cut.
+pub(crate) fn terminal_width() -> Option<usize> {
+    if !is_terminal(Stream::Stdout) {
+        return None;
+    }
+    Some(
+        std::env::var("COLUMNS")
+            .ok()
+            .and_then(|c| c.parse().ok())
+            .unwrap_or(80),
+    )
+}
+
+fn paint(code: &str, s: &str, stream: Stream) -> String {
+    match enabled(stream) {
+        true => format!("\x1b[{code}m{s}\x1b[0m"),
+        false => s.to_string(),
+    }
+}
+
+/// Section titles, like `Commands:` in the usage table
+pub(crate) fn title(s: &str) -> String {
+    paint(BOLD, s, Stream::Stdout)
+}
+
+/// Command names in the usage table
+pub(crate) fn command(s: &str) -> String {
+    paint(CYAN, s, Stream::Stdout)
+}
+
+/// Parent entity names in listings
+pub(crate) fn parent(s: &str) -> String {
+    paint(BLUE, s, Stream::Stdout)
+}
+
+/// Confirmations that a change was made
+pub(crate) fn success(s: &str) -> String {
+    paint(GREEN, s, Stream::Stdout)
+}
+
+/// The part of a name or value that matched a search
+pub(crate) fn highlight(s: &str) -> String {
+    paint(BOLD_YELLOW, s, Stream::Stdout)
+}
+
+/// Errors, which are printed to stderr
+pub(crate) fn error(s: &str) -> String {
+    paint(RED, s, Stream::Stderr)
+}
+
+/// The marker shown next to pinned links, a star in a terminal and `[pinned]` otherwise, see `fancy`
+pub(crate) fn pin_marker() -> &'static str {
+    match fancy() {
+        true => "★",
+        false => "[pinned]",
+    }
+}
@@ -0,0 +1,158 @@
+/// The most suggestions shown for a name that was not found
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Returns up to three of `candidates` that `name` was likely meant to be. A candidate is close if
+/// `name` is part of it (ignoring case), or if it is only a few edits away, where longer names
+/// allow more edits. Candidates starting with `name` come first, then the fewest edits away.
+pub(crate) fn suggestions<'a>(name: &str, candidates: &'a [String]) -> Vec<&'a str> {
+    let name = name.to_lowercase();
+    let max_distance = (name.chars().count() / 3).max(1);
+    let mut close: Vec<(bool, usize, &str)> = candidates
+        .iter()
+        .filter_map(|candidate| {
+            let lower = candidate.to_lowercase();
+            if lower == name {
+                return None;
+            }
+            let distance = edit_distance(&name, &lower);
+            match lower.contains(&name) || distance <= max_distance {
+                true => Some((!lower.starts_with(&name), distance, candidate.as_str())),
+                false => None,
+            }
+        })
+        .collect();
+    close.sort_by_key(|(not_prefix, distance, _)| (*not_prefix, *distance));
+    close
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, _, candidate)| candidate)
+        .collect()
+}
+
+/// The "Did you mean" line for `name`, or `None` if nothing in `candidates` is close to it
+pub(crate) fn did_you_mean(name: &str, candidates: &[String]) -> Option<String> {
+    let suggestions = suggestions(name, candidates);
+    match suggestions.is_empty() {
+        true => None,
+        false => Some(format!("Did you mean: {}?", suggestions.join(", "))),
+    }
+}
+
+/// How a name that may be shortened matches the known names
+#[derive(Debug, PartialEq)]
+pub(crate) enum PrefixMatch<'a> {
+    /// The name is known as it is, even if it is also the prefix of other names
+    Exact,
+    /// The name is the start of exactly one known name
+    Unique(&'a str),
+    /// The name is the start of several known names
+    Ambiguous(Vec<&'a str>),
+    /// The name is not the start of any known name
+    NoMatch,
+}
+
+/// Matches `name` against `candidates`, where `name` can be the start of a candidate
+pub(crate) fn match_prefix<'a>(name: &str, candidates: &'a [String]) -> PrefixMatch<'a> {
+    if candidates.iter().any(|candidate| candidate == name) {
+        return PrefixMatch::Exact;
+    }
+    let mut matches: Vec<&str> = candidates
+        .iter()
+        .filter(|candidate| candidate.starts_with(name))
+        .map(|candidate| candidate.as_str())
+        .collect();
+    match matches.len() {
+        0 => PrefixMatch::NoMatch,
+        1 => PrefixMatch::Unique(matches.remove(0)),
+        _ => PrefixMatch::Ambiguous(matches),
+    }
+}
+
+/// Levenshtein distance between `a` and `b`, counted in chars
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut curr = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(ca != *cb);
+            curr[j + 1] = substitution.min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        prev = curr;
+    }
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("search", "serach"), 2);
+        assert_eq!(edit_distance("google", "googel"), 2);
+        assert_eq!(edit_distance("work", "work"), 0);
+    }
+
+    #[test]
+    fn test_suggestions_typo() {
+        let candidates = names(&["search-engines", "work", "personal"]);
+        assert_eq!(
+            suggestions("serach-engines", &candidates),
+            vec!["search-engines"]
+        );
+    }
+
+    #[test]
+    fn test_suggestions_prefix_and_substring() {
+        let candidates = names(&["work-docs", "homework", "personal"]);
+        assert_eq!(
+            suggestions("work", &candidates),
+            vec!["work-docs", "homework"]
+        );
+    }
+
+    #[test]
+    fn test_suggestions_at_most_three_prefixes_first() {
+        let candidates = names(&["dev", "dev-ccc", "dev-bb", "dev-a"]);
+        assert_eq!(
+            suggestions("dev-", &candidates),
+            vec!["dev-a", "dev-bb", "dev-ccc"]
+        );
+    }
+
+    #[test]
+    fn test_suggestions_none() {
+        assert!(suggestions("work", &[]).is_empty());
+        assert_eq!(did_you_mean("work", &names(&["search-engines"])), None);
+    }
+
+    #[test]
+    fn test_did_you_mean() {
+        let candidates = names(&["google", "yahoo"]);
+        assert_eq!(
+            did_you_mean("gogle", &candidates),
+            Some("Did you mean: google?".to_string())
+        );
+    }
+
+    #[test]
+    fn test_match_prefix() {
+        let candidates = names(&["work", "work-docs", "search-engines", "security"]);
+        assert_eq!(match_prefix("work", &candidates), PrefixMatch::Exact);
+        assert_eq!(
+            match_prefix("sea", &candidates),
+            PrefixMatch::Unique("search-engines")
+        );
+        assert_eq!(
+            match_prefix("se", &candidates),
+            PrefixMatch::Ambiguous(vec!["search-engines", "security"])
+        );
+        assert_eq!(match_prefix("personal", &candidates), PrefixMatch::NoMatch);
+    }
+}
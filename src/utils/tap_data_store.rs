@@ -1,12 +1,352 @@
-use std::io::{Read, Seek, SeekFrom};
+use crate::utils::{
+    aliases::{self, Aliases, aliases_path_for},
+    backup::{Archive, FNV_OFFSET_BASIS, fnv1a, utc_timestamp},
+    config::{Config, ConfigError, default_config_path},
+    expiry,
+    groups::{self, Groups, groups_path_for},
+    history, hooks,
+    link_type::LinkType,
+    log,
+    recent::{self, recent_path_for},
+    sessions::{self, Sessions, sessions_path_for},
+    suggest,
+    trash::{self, trash_path_for},
+};
+use std::cell::{RefCell, RefMut};
+use std::collections::{BTreeMap, HashSet};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
 use std::path::Path;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::{fmt, fs, fs::File, path::PathBuf};
 
 type LinkValue = (String, String);
+type ParentLinks = (String, Vec<LinkValue>);
 type IndexEntry = (String, usize);
+/// The metadata of the links that have any, by trimmed parent entity and link name
+type Metadata = BTreeMap<(String, String), LinkMeta>;
 
 type IndexOffsetLength = (usize, usize);
 
+/// A line of a data file that was skipped while salvaging it
+#[derive(Debug, PartialEq)]
+pub(crate) struct RejectedLine {
+    pub line_number: usize,
+    pub line: String,
+    pub reason: String,
+}
+
+/// What `tap --compact` cleaned up while rewriting the data file
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct CompactSummary {
+    /// Link lines dropped because the same link and value was already stored under the parent
+    pub duplicates_removed: usize,
+    /// Repeated parent sections that were merged into the first section of the parent
+    pub parents_merged: usize,
+    /// Parent sections dropped because they had no links
+    pub empty_parents_removed: usize,
+    pub bytes_before: usize,
+    pub bytes_after: usize,
+    /// Lines skipped because they could not be parsed, only set when salvaging
+    pub rejected: Vec<RejectedLine>,
+}
+
+/// What `tap --prune` removed, see `DataStore::prune`
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct PruneSummary {
+    /// Parent entities without any links
+    pub empty_parents: Vec<String>,
+    /// Parent entities listed by the index file that are not in the data file
+    pub orphaned_index_entries: Vec<String>,
+}
+
+impl PruneSummary {
+    pub fn is_empty(&self) -> bool {
+        self.empty_parents.is_empty() && self.orphaned_index_entries.is_empty()
+    }
+}
+
+/// How serious a problem found by `tap --doctor` is
+#[derive(Debug, PartialEq)]
+pub(crate) enum Severity {
+    /// Tap copes with it, but the data file is not in the shape tap writes it
+    Warning,
+    /// Tap may fail or show the wrong links until it is fixed
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// What `tap --doctor --fix` can do about a finding
+#[derive(Debug, PartialEq)]
+pub(crate) enum Repair {
+    /// Rebuild the index file from the data file
+    RebuildIndex,
+    /// Merge repeated parent sections and drop repeated links, like `tap --compact` does
+    MergeDuplicates,
+    /// Remove parent entities without links, like `tap --prune` does
+    Prune,
+}
+
+/// A problem with the data or index file found by `DataStoreHandle::diagnose`
+#[derive(Debug, PartialEq)]
+pub(crate) struct Finding {
+    pub severity: Severity,
+    pub message: String,
+    /// How to fix the problem
+    pub fix: String,
+    /// `None` when the problem has to be fixed by hand
+    pub repair: Option<Repair>,
+}
+
+/// Which data files a `DataStoreHandle` opens and what it may do with them, set by the global
+/// flags when `cli::run` builds the `Context`. Every path of the data store is resolved from here.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct StoreOptions {
+    /// The data and index files given to `--file` and `--index`, see `StoreOptions::with_files`
+    pub files: Option<(PathBuf, PathBuf)>,
+    /// The profile given to `--profile`, see `StoreOptions::active_profile`
+    pub profile: Option<String>,
+    /// Set by `--read-only` or the `TAP_READONLY` environment variable, see `read_only_env`.
+    /// Every mutation fails with `TapDataStoreErrorKind::ReadOnly`.
+    pub read_only: bool,
+    /// Set by `--no-hooks`, keeps `hooks::run_post_write` from running
+    pub no_hooks: bool,
+    /// The command line of the running command, e.g. `tap -d work`, so that `tap --undo` can say
+    /// which change it reverts
+    pub operation: Option<String>,
+}
+
+impl StoreOptions {
+    /// Points at the given data file instead of the one of the profile. The index file defaults
+    /// to a sibling of the data file, see `index_path_for`.
+    pub fn with_files(mut self, data: PathBuf, index: Option<PathBuf>) -> Self {
+        let index = index.unwrap_or_else(|| index_path_for(&data));
+        self.files = Some((data, index));
+        self
+    }
+
+    /// The data and index files to open, creating the directory of a profile used for the first
+    /// time, see `StoreOptions::store_dir`
+    pub fn paths(&self) -> Result<(PathBuf, PathBuf), TapDataStoreError> {
+        if let Some(files) = &self.files {
+            return Ok(files.clone());
+        }
+        // NOTE: workaround for command tests running at the same time.
+        // Use test pathing for tests, otherwise use standard
+        // I want to use this over cfg!(test) as I do not want to compile test code in prod builds
+        #[allow(unused_mut, unused_assignments)]
+        let mut test_paths: Option<(PathBuf, PathBuf)> = None;
+        #[cfg(test)]
+        {
+            test_paths = Some((
+                get_test_file_path(FileType::Data)?,
+                get_test_file_path(FileType::Index)?,
+            ));
+        }
+        match test_paths {
+            Some(test_paths) => Ok(test_paths),
+            None => {
+                let dir = self.store_dir()?;
+                Ok((dir.join(".tap_data"), dir.join(".tap_index")))
+            }
+        }
+    }
+
+    /// The data and index files a data store opened now would use, along with the rule that
+    /// picked them. Nothing is created, unlike `StoreOptions::paths`.
+    pub fn resolve_paths(&self) -> Result<[(PathBuf, PathSource); 2], TapDataStoreError> {
+        if let Some((data, index)) = &self.files {
+            return Ok([
+                (data.clone(), PathSource::Flag),
+                (index.clone(), PathSource::Flag),
+            ]);
+        }
+        let (data_dir, source) = locate_data_dir()?;
+        let dir = profile_dir(&data_dir, &self.active_profile()?);
+        Ok([
+            (dir.join(".tap_data"), source),
+            (dir.join(".tap_index"), source),
+        ])
+    }
+
+    /// The profile in use: the one given to `--profile`, then the `TAP_PROFILE` environment
+    /// variable, then the `profile` of the config file, and `default` otherwise
+    pub fn active_profile(&self) -> Result<String, TapDataStoreError> {
+        if let Some(profile) = &self.profile {
+            return Ok(profile.clone());
+        }
+        let env = std::env::var("TAP_PROFILE").ok();
+        let config = match default_config_path() {
+            Some(path) => Config::load(Some(path)).map_err(config_error)?,
+            None => return choose_profile(env.as_deref(), None),
+        };
+        choose_profile(env.as_deref(), config.get(PROFILE_CONFIG_KEY))
+    }
+
+    /// Returns the directory holding the data files of the profile in use, see `profile_dir`,
+    /// creating the directory of a profile used for the first time
+    /// ## Errors
+    /// - See `get_data_dir` and `StoreOptions::active_profile`
+    /// - `TapDataStoreErrorKind::FileCreateFailed` - if unable to create the directory of the
+    ///   profile
+    fn store_dir(&self) -> Result<PathBuf, TapDataStoreError> {
+        let profile = self.active_profile()?;
+        let dir = profile_dir(&get_data_dir()?, &profile);
+        if !dir.exists() {
+            fs::create_dir_all(&dir).map_err(|e| TapDataStoreError {
+                kind: TapDataStoreErrorKind::FileCreateFailed,
+                message: format!(
+                    "Could not create the directory of profile {profile}, {}: {e}",
+                    dir.display()
+                ),
+            })?;
+            log::verbose(format_args!(
+                "Created {} for the data files of profile {profile}",
+                dir.display()
+            ));
+        }
+        Ok(dir)
+    }
+
+    /// Fails with `TapDataStoreErrorKind::ReadOnly` in read-only mode, for commands that should
+    /// stop before doing any work that would only be thrown away
+    pub fn check_writable(&self) -> Result<(), TapDataStoreError> {
+        match self.read_only {
+            true => Err(read_only_error()),
+            false => Ok(()),
+        }
+    }
+}
+
+/// Whether the `TAP_READONLY` environment variable is set to `1` or `true`, which is the same as
+/// passing `--read-only`
+pub(crate) fn read_only_env() -> bool {
+    std::env::var("TAP_READONLY")
+        .is_ok_and(|v| v.trim() == "1" || v.trim().eq_ignore_ascii_case("true"))
+}
+
+/// The profile used when none is picked. Its data files are the ones right in the data directory,
+/// the way they were before there were profiles.
+pub(crate) const DEFAULT_PROFILE: &str = "default";
+
+/// The config key naming the profile used when neither `--profile` nor `TAP_PROFILE` pick one
+pub(crate) const PROFILE_CONFIG_KEY: &str = "profile";
+
+/// Whether `name` can name a profile: letters, digits, `-`, `_`, and `.` that do not start with a
+/// `.` or `-`, so it is a plain directory name, and not one of the words of `tap --profile`
+pub(crate) fn is_profile_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(['.', '-'])
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        && !["list", "use"].contains(&name)
+}
+
+/// The profile picked by the `TAP_PROFILE` environment variable or the config file, see
+/// `StoreOptions::active_profile`
+fn choose_profile(env: Option<&str>, config: Option<&str>) -> Result<String, TapDataStoreError> {
+    let picked = [("TAP_PROFILE", env), ("the config file", config)]
+        .into_iter()
+        .find_map(|(source, name)| Some((source, name?.trim())).filter(|(_, n)| !n.is_empty()));
+    match picked {
+        None => Ok(DEFAULT_PROFILE.to_string()),
+        Some((_, name)) if is_profile_name(name) => Ok(name.to_string()),
+        Some((source, name)) => Err(TapDataStoreError {
+            kind: TapDataStoreErrorKind::ConfigFailed,
+            message: format!(
+                "Profile '{name}' from {source} is not a valid profile name, use letters, digits, -, _, and ."
+            ),
+        }),
+    }
+}
+
+/// The directory holding the data files of `profile` in the data directory `data_dir`: the
+/// default profile keeps them in `data_dir` itself and any other profile in a directory of its
+/// own, like `data_dir/work`
+pub(crate) fn profile_dir(data_dir: &Path, profile: &str) -> PathBuf {
+    match profile {
+        DEFAULT_PROFILE => data_dir.to_path_buf(),
+        profile => data_dir.join(profile),
+    }
+}
+
+/// The profiles that have a data file, sorted by name. Nothing is created.
+pub(crate) fn profiles() -> Result<Vec<String>, TapDataStoreError> {
+    let (data_dir, _) = locate_data_dir()?;
+    Ok(profiles_in(&data_dir))
+}
+
+fn profiles_in(data_dir: &Path) -> Vec<String> {
+    let mut profiles: Vec<String> = fs::read_dir(data_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| is_profile_name(name) && name != DEFAULT_PROFILE)
+        .filter(|name| data_dir.join(name).join(".tap_data").is_file())
+        .collect();
+    if data_dir.join(".tap_data").is_file() {
+        profiles.push(DEFAULT_PROFILE.to_string());
+    }
+    profiles.sort();
+    profiles
+}
+
+/// The index file kept next to a data file: `demo.tap_data` is indexed by `demo.tap_index`, and
+/// any other file name gets `.tap_index` appended, e.g. `links` is indexed by `links.tap_index`.
+pub(crate) fn index_path_for(data: &Path) -> PathBuf {
+    let file_name = data.file_name().unwrap_or_default().to_string_lossy();
+    match file_name.strip_suffix("tap_data") {
+        Some(stem) => data.with_file_name(format!("{stem}tap_index")),
+        None => data.with_file_name(format!("{file_name}.tap_index")),
+    }
+}
+
+/// The archive file kept next to a data file, in the same format: `.tap_data` archives parent
+/// entities to `.tap_archive`, `demo.tap_data` to `demo.tap_archive`, and any other file name gets
+/// `.tap_archive` appended
+pub(crate) fn archive_path_for(data: &Path) -> PathBuf {
+    let file_name = data.file_name().unwrap_or_default().to_string_lossy();
+    match file_name.strip_suffix("tap_data") {
+        Some(stem) => data.with_file_name(format!("{stem}tap_archive")),
+        None => data.with_file_name(format!("{file_name}.tap_archive")),
+    }
+}
+
+/// The files kept next to a data file, by the name they are backed up under. They are moved
+/// along with the data file by `migrate_data_dir`, and all but the undo snapshot are backed up
+/// with it by `tap --backup`.
+pub(crate) fn sidecar_paths(data: &Path) -> [(&'static str, PathBuf); 9] {
+    let history = history::history_path_for(data);
+    [
+        ("aliases", aliases_path_for(data)),
+        ("groups", groups_path_for(data)),
+        ("sessions", sessions_path_for(data)),
+        ("recent", recent_path_for(data)),
+        ("history.1", history::rotated_path(&history)),
+        ("history", history),
+        ("trash", trash_path_for(data)),
+        ("archive", archive_path_for(data)),
+        ("undo", undo_path_for(data)),
+    ]
+}
+
+fn read_only_error() -> TapDataStoreError {
+    TapDataStoreError {
+        kind: TapDataStoreErrorKind::ReadOnly,
+        message: "Tap is in read-only mode, so the data file can not be changed. Drop the --read-only flag and unset TAP_READONLY to make changes".to_string(),
+    }
+}
+
 /// A struct containing the data file and index file. The ReadDataStore struct utilizes the
 /// index file to speed up reads via Seeks. The DataStore struct does not utilize the index file.
 /// Note: The ReadDataStore struct is intended to be used in a read-only context and therefore
@@ -17,10 +357,25 @@ pub(crate) struct ReadDataStore {
 }
 
 impl ReadDataStore {
-    pub fn new(path: Option<PathBuf>, parent: String) -> Result<Self, TapDataStoreError> {
-        let index = Index::new(path.clone())?;
-        let index_offset_length = index.find_parent_offset_and_length(parent)?;
-        let data = Data::new(path, Some(index_offset_length))?;
+    pub fn new(data: PathBuf, index: PathBuf, parent: String) -> Result<Self, TapDataStoreError> {
+        // Reading never creates missing data files
+        let index = Index::open(Some(index), false)?;
+        let data = match index.find_parent_offset_and_length(parent.clone()) {
+            Ok(index_offset_length) => {
+                log::verbose(format_args!(
+                    "Index hit for parent {parent} at byte offset {}",
+                    index_offset_length.0
+                ));
+                Data::read_parent_section(Some(data), index_offset_length, &parent)?
+            }
+            // The index may be missing or stale, so look through the data file itself
+            Err(_) => {
+                log::verbose(format_args!(
+                    "Index miss for parent {parent}, scanning the data file"
+                ));
+                Data::read_parent_streaming(Some(data), &parent)?
+            }
+        };
         Ok(Self {
             data,
             _index: index,
@@ -37,22 +392,197 @@ impl ReadDataStore {
         self.data.get(parent, None)
     }
 
-    pub fn links(&self, parent: &str) -> Result<Vec<String>, TapDataStoreError> {
-        let links = self.read_parent(parent)?;
-        Ok(links.iter().map(|(l, _)| l.clone()).collect())
+    pub fn read_meta(&self, parent: &str) -> BTreeMap<String, LinkMeta> {
+        self.data.meta_of_parent(parent)
     }
 }
 
 pub(crate) struct DataStore {
     data: Data,
     index: Index,
+    /// The aliases of parent entities, see `aliases::Aliases`
+    aliases: Aliases,
+    /// The parent entities moved out of the data file by `DataStore::archive`, opened on first use
+    archive: Option<Data>,
+    /// The links removed by `Batch::delete`, see `trash::Entry`, read on first use
+    trash: Option<Vec<trash::Entry>>,
+    /// The trash entries `DataStore::restore_trash` took out since `DataStoreHandle::write` last
+    /// recorded them for `tap --undo`
+    untrashed: Vec<trash::Entry>,
+    /// When set, every mutation fails with `TapDataStoreErrorKind::ReadOnly`
+    read_only: bool,
+    /// The changes saved since `DataStoreHandle::write` last wrote them to the history file
+    history: Vec<history::Entry>,
+    /// Number of times the data and index files were saved, used to assert batching in tests
+    #[cfg(test)]
+    saves: usize,
 }
 
 impl DataStore {
-    pub fn new(path: Option<PathBuf>) -> Result<Self, TapDataStoreError> {
-        let data = Data::new(path.clone(), None)?;
-        let index = Index::new(path)?;
-        Ok(Self { data, index })
+    pub fn new(data: PathBuf, index: PathBuf) -> Result<Self, TapDataStoreError> {
+        let data = Data::new(Some(data), None)?;
+        let index = Index::new(Some(index))?;
+        Ok(Self {
+            aliases: load_aliases(&data)?,
+            archive: None,
+            trash: None,
+            untrashed: vec![],
+            data,
+            index,
+            read_only: false,
+            history: vec![],
+            #[cfg(test)]
+            saves: 0,
+        })
+    }
+
+    /// Opens the store at the given data and index files, creating them when missing. A missing
+    /// or empty index next to a data file with links is rebuilt right away, so the parents are
+    /// listed correctly.
+    pub fn open_files(data: PathBuf, index: PathBuf) -> Result<Self, TapDataStoreError> {
+        let data = Data::new(Some(data), None)?;
+        let mut ds = Self {
+            aliases: load_aliases(&data)?,
+            archive: None,
+            trash: None,
+            untrashed: vec![],
+            data,
+            index: Index::new(Some(index))?,
+            read_only: false,
+            history: vec![],
+            #[cfg(test)]
+            saves: 0,
+        };
+        if ds.index.state.is_empty() && !ds.data.state.is_empty() {
+            ds.save()?;
+        }
+        Ok(ds)
+    }
+
+    /// Opens the store without ever writing to disk: missing data files are treated as empty
+    /// instead of being created, and every mutation fails with `TapDataStoreErrorKind::ReadOnly`.
+    pub fn new_read_only(data: PathBuf, index: PathBuf) -> Result<Self, TapDataStoreError> {
+        let data = Data::open(Some(data), None, false)?;
+        let index = Index::open(Some(index), false)?;
+        Ok(Self {
+            aliases: load_aliases(&data)?,
+            archive: None,
+            trash: None,
+            untrashed: vec![],
+            data,
+            index,
+            read_only: true,
+            history: vec![],
+            #[cfg(test)]
+            saves: 0,
+        })
+    }
+
+    /// Creates a store that lives entirely in memory. Mutations behave exactly as they do for the
+    /// file backed store (including sorting and index updates), but nothing is ever written to disk.
+    #[allow(dead_code)]
+    pub fn in_memory(state: Vec<(String, Vec<LinkValue>)>) -> Self {
+        let mut data = Data::in_memory(state);
+        let mut index = Index::in_memory();
+        // Saving an in memory store only sorts the state and computes the index offsets
+        let index_offsets = data
+            .save_to_file()
+            .expect("Saving an in memory data store can not fail");
+        index.update(index_offsets);
+        Self {
+            data,
+            index,
+            aliases: Aliases::new(),
+            archive: None,
+            trash: None,
+            untrashed: vec![],
+            read_only: false,
+            history: vec![],
+            #[cfg(test)]
+            saves: 0,
+        }
+    }
+
+    /// Opens the store while skipping every unparseable line of the data file. The skipped lines
+    /// are appended to the rejected file, and the data and index files are rewritten from what
+    /// could be parsed.
+    pub fn salvage(
+        data: PathBuf,
+        index: PathBuf,
+    ) -> Result<(Self, Vec<RejectedLine>), TapDataStoreError> {
+        let (data, rejected) = Data::salvage(Some(data))?;
+        data.save_rejected(&rejected)?;
+        let mut ds = Self {
+            aliases: load_aliases(&data)?,
+            archive: None,
+            trash: None,
+            untrashed: vec![],
+            data,
+            // The index is rebuilt from the salvaged data, so a corrupt index is fine here
+            index: Index::empty(Some(index))?,
+            read_only: false,
+            history: vec![],
+            #[cfg(test)]
+            saves: 0,
+        };
+        ds.save()?;
+        Ok((ds, rejected))
+    }
+
+    /// Opens the store, then rewrites the data and index files in their canonical form: trimmed,
+    /// sorted, with duplicate parents merged and duplicate or empty entries removed. Parsing is
+    /// strict unless `salvage` is set, in which case unparseable lines are skipped and appended to
+    /// the rejected file like `DataStore::salvage` does.
+    pub fn compact(
+        data: PathBuf,
+        index: PathBuf,
+        salvage: bool,
+    ) -> Result<(Self, CompactSummary), TapDataStoreError> {
+        let (data, summary) = Data::compact(Some(data), salvage)?;
+        data.save_rejected(&summary.rejected)?;
+        let mut ds = Self {
+            aliases: load_aliases(&data)?,
+            archive: None,
+            trash: None,
+            untrashed: vec![],
+            data,
+            // The index is rebuilt from the compacted data
+            index: Index::empty(Some(index))?,
+            read_only: false,
+            history: vec![],
+            #[cfg(test)]
+            saves: 0,
+        };
+        ds.save()?;
+        Ok((ds, summary))
+    }
+
+    /// Opens the store at the given files and rebuilds the index file from the data file,
+    /// whatever state the index file is in
+    pub fn rebuild_index(data: PathBuf, index: PathBuf) -> Result<Self, TapDataStoreError> {
+        let data = Data::new(Some(data), None)?;
+        let mut ds = Self {
+            aliases: load_aliases(&data)?,
+            archive: None,
+            trash: None,
+            untrashed: vec![],
+            data,
+            index: Index::empty(Some(index))?,
+            read_only: false,
+            history: vec![],
+            #[cfg(test)]
+            saves: 0,
+        };
+        ds.save()?;
+        Ok(ds)
+    }
+
+    pub fn rejected_path(&self) -> PathBuf {
+        self.data.rejected_path()
+    }
+
+    pub fn parents(&self) -> Vec<String> {
+        self.index.parents()
     }
 
     pub fn add_link(
@@ -61,11 +591,62 @@ impl DataStore {
         link: String,
         value: String,
     ) -> Result<(), TapDataStoreError> {
-        self.data.add_link(&parent, &link, &value)?;
-        let index_offsets = self.data.save_to_file()?;
-        self.index.update(index_offsets);
-        self.index.save_to_file()?;
-        Ok(())
+        self.batch(|tx| tx.add_link(&parent, &link, &value))
+    }
+
+    /// Applies every mutation made through the `Batch` passed to `f`, then saves the data and
+    /// index files exactly once. If `f` returns an error, the in-memory state is rolled back and
+    /// the files on disk are left untouched.
+    pub fn batch<T>(
+        &mut self,
+        f: impl FnOnce(&mut Batch) -> Result<T, TapDataStoreError>,
+    ) -> Result<T, TapDataStoreError> {
+        if self.read_only {
+            return Err(read_only_error());
+        }
+        let snapshot = (self.data.state.clone(), self.data.meta.clone());
+        let mut history = vec![];
+        let mut trashed = vec![];
+        let res = f(&mut Batch {
+            data: &mut self.data,
+            history: &mut history,
+            trash: &mut trashed,
+            aliases: &self.aliases,
+        });
+        match res {
+            Ok(res) => {
+                // The trash is written first, so deleted links are never lost when saving fails
+                self.update_trash(trashed)?;
+                self.save()?;
+                self.history.append(&mut history);
+                Ok(res)
+            }
+            Err(e) => {
+                (self.data.state, self.data.meta) = snapshot;
+                Err(e)
+            }
+        }
+    }
+
+    /// Runs `f` like `batch` but rolls every mutation back afterwards, so nothing is saved and
+    /// the history and trash are left alone. Read only stores refuse it like a batch, so a dry
+    /// run fails wherever the real one would.
+    pub fn dry_run<T>(
+        &mut self,
+        f: impl FnOnce(&mut Batch) -> Result<T, TapDataStoreError>,
+    ) -> Result<T, TapDataStoreError> {
+        if self.read_only {
+            return Err(read_only_error());
+        }
+        let snapshot = (self.data.state.clone(), self.data.meta.clone());
+        let res = f(&mut Batch {
+            data: &mut self.data,
+            history: &mut vec![],
+            trash: &mut vec![],
+            aliases: &self.aliases,
+        });
+        (self.data.state, self.data.meta) = snapshot;
+        res
     }
 
     pub fn delete(
@@ -73,16 +654,11 @@ impl DataStore {
         parent: String,
         link: Option<String>,
     ) -> Result<(), TapDataStoreError> {
-        self.data.remove(&parent, link.as_deref())?;
-        let index_offsets = self.data.save_to_file()?;
-        self.index.update(index_offsets);
-        self.index.save_to_file()?;
-        Ok(())
+        self.batch(|tx| tx.delete(&parent, link.as_deref()))
     }
 
     /// Returns the first link in the list. This does not utilize the index and is therefore slow.
     /// To use the indexed version, create a new `ReadDataStore` struct and call `read_link` or `read_parent`.
-    #[allow(dead_code)]
     pub fn read_link_slow(
         &self,
         parent: &str,
@@ -94,7 +670,6 @@ impl DataStore {
 
     /// Returns the first link in the list. This does not utilize the index and is therefore slow.
     /// To use the indexed version, create a new `ReadDataStore` struct and call `read_link` or `read_parent`.
-    #[allow(dead_code)]
     pub fn read_parent_slow(&self, parent: &str) -> Result<Vec<LinkValue>, TapDataStoreError> {
         self.data.get(parent, None)
     }
@@ -105,11 +680,7 @@ impl DataStore {
         link: String,
         value: String,
     ) -> Result<(), TapDataStoreError> {
-        self.data.upsert_link(&parent, &link, &value)?;
-        let index_offsets = self.data.save_to_file()?;
-        self.index.update(index_offsets);
-        self.index.save_to_file()?;
-        Ok(())
+        self.batch(|tx| tx.upsert_link(&parent, &link, &value))
     }
 
     pub fn import(
@@ -117,345 +688,3311 @@ impl DataStore {
         path: PathBuf,
         import_type: ImportType,
     ) -> Result<(), TapDataStoreError> {
-        self.data.import(import_type, path.clone())?;
-        let index_offsets = self.data.save_to_file()?;
-        self.index.update(index_offsets);
-        self.index.save_to_file()?;
-        Ok(())
+        self.batch(|tx| tx.import(import_type, path))
     }
-}
 
-pub(super) struct Data {
-    path: PathBuf,
-    state: Vec<(String, Vec<LinkValue>)>,
-}
+    /// Makes `alias` another name of `parent`, returning the parent it named before. An alias of
+    /// an alias names the same parent entity.
+    pub fn set_alias(
+        &mut self,
+        alias: &str,
+        parent: &str,
+    ) -> Result<Option<String>, TapDataStoreError> {
+        if self.read_only {
+            return Err(read_only_error());
+        }
+        validate_alias(alias)?;
+        let alias = alias.trim();
+        let parent = canonical_parent(&self.aliases, parent);
+        self.data.get(&parent, None)?;
+        if self.data.parent_position(alias).is_ok() {
+            return Err(TapDataStoreError {
+                kind: TapDataStoreErrorKind::ReservedKeyword,
+                message: format!("Alias {alias} is already the name of a parent entity"),
+            });
+        }
+        let old = self
+            .aliases
+            .insert(alias.to_string(), parent.trim().to_string());
+        self.save_aliases()?;
+        Ok(old)
+    }
 
-// Publicly exposed
-impl Data {
-    pub fn new(
-        path: Option<PathBuf>,
-        index_offset_length: Option<IndexOffsetLength>,
-    ) -> Result<Self, TapDataStoreError> {
-        // Determine path
-        let (file_exists, path) = if let Some(path) = path {
-            (path.exists(), path)
-        } else {
-            let mut tap_data_path = get_parent_dir_of_tap()?;
+    /// Removes `alias`, returning the parent it named
+    pub fn remove_alias(&mut self, alias: &str) -> Result<Option<String>, TapDataStoreError> {
+        if self.read_only {
+            return Err(read_only_error());
+        }
+        let old = self.aliases.remove(alias.trim());
+        if old.is_some() {
+            self.save_aliases()?;
+        }
+        Ok(old)
+    }
 
-            // NOTE: workaround for command tests running at the same time.
-            // Use test pathing for tests, otherwise use standard
-            // I want to use this over cfg!(test) as I do not want to compile test code in prod builds
-            #[allow(unused_mut)]
-            let mut test_path: Option<PathBuf> = None;
-            #[cfg(test)]
-            {
-                test_path = Some(get_test_file_path(FileType::Data)?);
+    /// Removes the parent entities without links and the index entries of parent entities that are
+    /// not in the data file, saving only when there was anything to remove. Parsing already drops
+    /// the sections of parent entities without links, so those are looked for in the data file as
+    /// it was read.
+    pub fn prune(&mut self) -> Result<PruneSummary, TapDataStoreError> {
+        if self.read_only {
+            return Err(read_only_error());
+        }
+        let mut empty_parents: Vec<String> = vec![];
+        let sections = match self.data.on_disk {
+            Some(_) => read_data_file_at(&self.data.path)?.map(|data| empty_sections(&data)),
+            None => None,
+        };
+        for parent in sections.unwrap_or_default() {
+            if self.data.parent_position(&parent).is_err() && !empty_parents.contains(&parent) {
+                empty_parents.push(parent);
             }
-            if let Some(test_path) = test_path {
-                tap_data_path = test_path;
-            } else {
-                tap_data_path = tap_data_path.join(".tap_data");
+        }
+        self.data.state.retain(|(parent, links)| {
+            if links.is_empty() {
+                empty_parents.push(parent.trim().to_string());
             }
-            (tap_data_path.exists(), tap_data_path)
+            !links.is_empty()
+        });
+        let data = &self.data;
+        let orphaned_index_entries: Vec<String> = self
+            .index
+            .parents()
+            .into_iter()
+            .map(|parent| parent.trim().to_string())
+            .filter(|parent| data.parent_position(parent).is_err())
+            .filter(|parent| !empty_parents.contains(parent))
+            .collect();
+        let summary = PruneSummary {
+            empty_parents,
+            orphaned_index_entries,
         };
+        if !summary.is_empty() {
+            self.save()?;
+        }
+        Ok(summary)
+    }
 
-        // Parse file if it exists
-        if file_exists {
-            #[allow(unused_assignments)]
-            let mut file_as_str: String = String::new();
+    /// Moves `parent` with its links and their metadata from the data file to the archive file,
+    /// returning how many links it had. The archive file is saved first, so links are never lost
+    /// when saving the data file fails.
+    pub fn archive(&mut self, parent: &str) -> Result<usize, TapDataStoreError> {
+        if self.read_only {
+            return Err(read_only_error());
+        }
+        let parent = canonical_parent(&self.aliases, parent).trim().to_string();
+        let links = self.data.get(&parent, None)?;
+        let meta = self.data.meta_of_parent(&parent);
+        let archive = self.archive_data()?;
+        if archive.parent_position(&parent).is_ok() {
+            return Err(TapDataStoreError {
+                kind: TapDataStoreErrorKind::ParentEntityAlreadyExists,
+                message: format!("Parent '{parent}' is already archived"),
+            });
+        }
+        archive.add_parent(&parent, &links, meta)?;
+        archive.save_to_file()?;
+        if let Err(e) = self.batch(|tx| tx.archive(&parent)) {
+            let archive = self.archive_data()?;
+            archive.remove(&parent, None)?;
+            archive.save_to_file()?;
+            return Err(e);
+        }
+        Ok(links.len())
+    }
 
-            // If index_offset_length is set, then we are reading from the index file
-            if let Some((offset, length)) = index_offset_length {
-                let mut f = File::open(&path).map_err(|e| TapDataStoreError {
-                    kind: TapDataStoreErrorKind::FileOpenFailed,
-                    message: format!("Could not open data file at {}: {e}", path.display()),
-                })?;
-                f.seek(SeekFrom::Start(offset as u64))
-                    .map_err(|e| TapDataStoreError {
-                        kind: TapDataStoreErrorKind::FileSeekFailed,
-                        message: format!(
-                            "Could not seek to offset {offset} in data file at {}: {e}",
-                            path.display()
-                        ),
-                    })?;
-                let mut buf = if length == 0 {
-                    // If length is 0, then we are reading the rest of the file
-                    let metadata = f.metadata().map_err(|e| TapDataStoreError {
-                        kind: TapDataStoreErrorKind::FileReadMetadataFailed,
-                        message: format!(
-                            "Could not read metadata for data file at {}: {e}",
-                            path.display()
-                        ),
-                    })?;
-                    let length = metadata.len() - offset as u64;
-                    vec![0u8; length as usize]
-                } else {
-                    vec![0u8; length]
-                };
-                f.read_exact(&mut buf).map_err(|e| TapDataStoreError {
-                    kind: TapDataStoreErrorKind::FileReadFailed,
-                    message: format!("Could not read data file at {}: {e}", path.display()),
-                })?;
-                file_as_str = buf.iter().map(|b| *b as char).collect();
-            } else {
-                file_as_str = fs::read_to_string(&path).map_err(|e| TapDataStoreError {
-                    kind: TapDataStoreErrorKind::FileReadFailed,
-                    message: format!("Could not read data file at {}: {e}", path.display()),
-                })?;
-            }
-            let state = Data::parse_file(&file_as_str)?;
-            Ok(Self { path, state })
-        } else {
-            File::create_new(&path).map_err(|e| TapDataStoreError {
-                kind: TapDataStoreErrorKind::FileCreateFailed,
-                message: format!("Could not create data file: {e}"),
-            })?;
-            Ok(Self {
-                path,
-                state: vec![],
-            })
+    /// Moves an archived `parent` back from the archive file to the data file, returning how many
+    /// links it has. A parent entity of the same name in the data file is never merged into.
+    pub fn unarchive(&mut self, parent: &str) -> Result<usize, TapDataStoreError> {
+        if self.read_only {
+            return Err(read_only_error());
+        }
+        let parent = canonical_parent(&self.aliases, parent).trim().to_string();
+        if self.data.parent_position(&parent).is_ok() {
+            return Err(TapDataStoreError {
+                kind: TapDataStoreErrorKind::ParentEntityAlreadyExists,
+                message: format!(
+                    "Parent '{parent}' already exists, rename or delete it before unarchiving"
+                ),
+            });
         }
+        let archive = self.archive_data()?;
+        let links = archive.get(&parent, None).map_err(|e| match e.kind {
+            TapDataStoreErrorKind::ParentEntityNotFound => TapDataStoreError {
+                kind: TapDataStoreErrorKind::ParentEntityNotFound,
+                message: format!("Parent '{parent}' is not archived"),
+            },
+            _ => e,
+        })?;
+        let meta = archive.meta_of_parent(&parent);
+        self.batch(|tx| tx.unarchive(&parent, &links, meta))?;
+        let archive = self.archive_data()?;
+        archive.remove(&parent, None)?;
+        archive.save_to_file()?;
+        Ok(links.len())
     }
 
-    pub fn add_link(
+    /// The archived parent entities with their links
+    pub fn archived(&mut self) -> Result<Vec<ParentLinks>, TapDataStoreError> {
+        Ok(self.archive_data()?.state.clone())
+    }
+
+    /// Adds back the links of `parent` from the trash file, or only `link`, returning them. A link
+    /// that was deleted more than once gets the value it had when it was deleted last. Links that
+    /// exist again are never replaced.
+    pub fn restore_trash(
         &mut self,
         parent: &str,
-        link: &str,
-        value: &str,
-    ) -> Result<(), TapDataStoreError> {
-        validate_parent(parent)?;
-        validate_link(link)?;
-        if let Some((_, links)) = self.state.iter_mut().find(|(p, _)| p == parent) {
-            if links.iter().any(|(l, _)| l.trim() == link) {
-                return Err(TapDataStoreError {
-                    kind: TapDataStoreErrorKind::LinkAlreadyExists,
-                    message: format!("Link {link} already exists for parent {parent}"),
-                });
+        link: Option<&str>,
+    ) -> Result<Vec<LinkValue>, TapDataStoreError> {
+        if self.read_only {
+            return Err(read_only_error());
+        }
+        let parent = parent.trim().to_string();
+        let link = link.map(str::trim);
+        let mut restored: Vec<LinkValue> = vec![];
+        // Newest first, so the last deletion of a link is the one picked
+        for entry in self.trash_entries()?.iter().rev() {
+            if entry.parent == parent
+                && link.is_none_or(|link| entry.link == link)
+                && !restored.iter().any(|(l, _)| *l == entry.link)
+            {
+                restored.push((entry.link.clone(), entry.value.clone()));
             }
-            links.push((link.trim().to_string(), value.trim().to_string()));
-        } else {
-            self.state.push((
-                parent.to_string(),
-                vec![(link.trim().to_string(), value.trim().to_string())],
-            ));
         }
-        Ok(())
+        if restored.is_empty() {
+            return Err(match link {
+                Some(link) => TapDataStoreError {
+                    kind: TapDataStoreErrorKind::LinkNotFound,
+                    message: format!("Link '{link}' of parent '{parent}' is not in the trash"),
+                },
+                None => TapDataStoreError {
+                    kind: TapDataStoreErrorKind::ParentEntityNotFound,
+                    message: format!("Parent '{parent}' is not in the trash"),
+                },
+            });
+        }
+        restored.sort();
+        if let Some((link, _)) = restored
+            .iter()
+            .find(|(link, _)| self.data.get(&parent, Some(link)).is_ok())
+        {
+            return Err(TapDataStoreError {
+                kind: TapDataStoreErrorKind::LinkAlreadyExists,
+                message: format!(
+                    "Link '{link}' already exists in parent '{parent}', delete or rename it before restoring it from the trash"
+                ),
+            });
+        }
+        self.batch(|tx| tx.restore(&parent, &restored))?;
+        let (untrashed, kept) = std::mem::take(self.trash_entries()?)
+            .into_iter()
+            .partition(|e| e.parent == parent && restored.iter().any(|(l, _)| *l == e.link));
+        *self.trash_entries()? = kept;
+        self.untrashed.extend::<Vec<trash::Entry>>(untrashed);
+        self.save_trash()?;
+        Ok(restored)
     }
 
-    pub fn get(
-        &self,
-        parent: &str,
-        link: Option<&str>,
-    ) -> Result<Vec<LinkValue>, TapDataStoreError> {
-        validate_parent(parent)?;
-        let links = self
-            .state
-            .iter()
-            .find(|(p, _)| p.trim() == parent)
-            .map(|(_, links)| links.clone())
-            .ok_or(TapDataStoreError {
-                kind: TapDataStoreErrorKind::ParentEntityNotFound,
-                message: format!("Parent '{parent}' not found"),
-            })?;
-        if let Some(link) = link {
-            validate_link(link)?;
-            let link = link.trim();
-            let found_link = links.iter().find(|(l, _)| l.trim() == link);
-            if let Some(found_link) = found_link {
-                return Ok(vec![found_link.clone()]);
-            } else {
-                return Err(TapDataStoreError {
-                    kind: TapDataStoreErrorKind::LinkNotFound,
-                    message: format!("Link '{link}' not found in parent '{parent}'"),
-                });
-            }
+    /// The links in the trash file, oldest first
+    pub fn trashed(&mut self) -> Result<Vec<trash::Entry>, TapDataStoreError> {
+        Ok(self.trash_entries()?.clone())
+    }
+
+    /// Adds the links deleted by a batch to the trash file, dropping the ones deleted longer ago
+    /// than `trash_retention_days` on the way. This runs with every batch, so old links leave the
+    /// trash even when nothing else is deleted.
+    fn update_trash(&mut self, deleted: Vec<trash::Entry>) -> Result<(), TapDataStoreError> {
+        let exists =
+            self.trash.is_some() || self.data.in_memory || trash_path_for(&self.data.path).exists();
+        if deleted.is_empty() && !exists {
+            return Ok(());
         }
-        Ok(links)
+        let days = trash_retention_days();
+        let entries = self.trash_entries()?;
+        let before = entries.len();
+        trash::drop_expired(entries, days);
+        if entries.len() == before && deleted.is_empty() {
+            return Ok(());
+        }
+        entries.extend(deleted);
+        self.save_trash()
     }
 
-    pub fn remove(&mut self, parent: &str, link: Option<&str>) -> Result<(), TapDataStoreError> {
-        validate_parent(parent)?;
-        if let Some(link) = link {
-            validate_link(link)?;
+    /// The trash file, read the first time it is needed. An in memory store keeps its trash in
+    /// memory as well.
+    fn trash_entries(&mut self) -> Result<&mut Vec<trash::Entry>, TapDataStoreError> {
+        if self.trash.is_none() {
+            self.trash = Some(match self.data.in_memory {
+                true => vec![],
+                false => read_trash_of(&self.data.path)?,
+            });
         }
-        if let Some(parent_idx) = self.state.iter().position(|(p, _)| p == parent) {
-            let (_, links) = &mut self.state[parent_idx];
-            // If there is a link to remove, find and remove. Otherwise, remove parent
-            if let Some(link) = link {
-                if let Some(index) = links.iter().position(|(l, _)| l.trim() == link) {
-                    links.remove(index);
-                    // If no links left, remove parent as well
-                    if links.is_empty() {
-                        self.state.remove(parent_idx);
-                    }
-                } else {
-                    return Err(TapDataStoreError {
-                        kind: TapDataStoreErrorKind::LinkNotFound,
-                        message: format!("Link '{link}' not found in parent '{parent}'"),
-                    });
-                }
-            } else {
-                self.state.remove(parent_idx);
-            }
-        } else {
-            return Err(TapDataStoreError {
-                kind: TapDataStoreErrorKind::ParentEntityNotFound,
-                message: format!("Parent '{parent}' not found"),
+        Ok(self.trash.as_mut().expect("Trash was read above"))
+    }
+
+    fn save_trash(&self) -> Result<(), TapDataStoreError> {
+        let Some(entries) = self.trash.as_ref().filter(|_| !self.data.in_memory) else {
+            return Ok(());
+        };
+        let path = trash_path_for(&self.data.path);
+        trash::write(&path, entries).map_err(|e| TapDataStoreError {
+            kind: TapDataStoreErrorKind::FileWriteFailed,
+            message: format!("Could not write {}: {e}", path.display()),
+        })
+    }
+
+    /// The archive file, opened the first time it is needed. An in memory store archives to
+    /// memory as well.
+    fn archive_data(&mut self) -> Result<&mut Data, TapDataStoreError> {
+        if self.archive.is_none() {
+            self.archive = Some(match self.data.in_memory {
+                true => Data::in_memory(vec![]),
+                false => Data::open(Some(archive_path_for(&self.data.path)), None, false)?,
             });
         }
+        Ok(self.archive.as_mut().expect("Archive was opened above"))
+    }
+
+    /// Removes the aliases of parent entities that no longer exist, and the ones a parent entity
+    /// is now named as, noting each one. Archived parent entities still exist, so their aliases
+    /// are kept for when they are unarchived.
+    fn prune_aliases(&mut self) -> Result<(), TapDataStoreError> {
+        let before = self.aliases.len();
+        let missing: Vec<String> = self
+            .aliases
+            .values()
+            .filter(|parent| self.data.parent_position(parent).is_err())
+            .cloned()
+            .collect();
+        // The archive file is only opened when an alias points outside the data file
+        let archived: HashSet<String> = match missing.is_empty() {
+            true => HashSet::new(),
+            false => {
+                let archive = self.archive_data()?;
+                missing
+                    .into_iter()
+                    .filter(|parent| archive.parent_position(parent).is_ok())
+                    .collect()
+            }
+        };
+        let data = &self.data;
+        self.aliases.retain(|alias, parent| {
+            if data.parent_position(alias).is_ok() {
+                log::note(format_args!(
+                    "Removed alias {alias} of parent '{parent}', a parent entity is now named {alias}"
+                ));
+                return false;
+            }
+            if data.parent_position(parent).is_err() && !archived.contains(parent) {
+                log::note(format_args!(
+                    "Removed alias {alias}, its parent '{parent}' no longer exists"
+                ));
+                return false;
+            }
+            true
+        });
+        match self.aliases.len() == before || self.read_only {
+            true => Ok(()),
+            false => self.save_aliases(),
+        }
+    }
+
+    fn save_aliases(&self) -> Result<(), TapDataStoreError> {
+        if self.data.in_memory {
+            return Ok(());
+        }
+        let path = aliases_path_for(&self.data.path);
+        aliases::write(&path, &self.aliases).map_err(|e| TapDataStoreError {
+            kind: TapDataStoreErrorKind::FileWriteFailed,
+            message: format!("Could not write {}: {e}", path.display()),
+        })
+    }
+
+    /// Writes the data file and rebuilds the index from the resulting offsets
+    fn save(&mut self) -> Result<(), TapDataStoreError> {
+        let index_offsets = self.data.save_to_file()?;
+        self.index.update(index_offsets);
+        self.index.save_to_file()?;
+        #[cfg(test)]
+        {
+            self.saves += 1;
+        }
         Ok(())
     }
+}
 
-    pub fn upsert_link(
+/// A set of pending mutations created by `DataStore::batch`. Mutations only touch the in-memory
+/// state; the batch is saved once after the closure given to `DataStore::batch` returns. Every
+/// changed link is noted for the history file.
+pub(crate) struct Batch<'a> {
+    data: &'a mut Data,
+    history: &'a mut Vec<history::Entry>,
+    /// The links removed by `Batch::delete`, added to the trash file once the batch succeeds
+    trash: &'a mut Vec<trash::Entry>,
+    /// Mutations of an alias change the parent entity it names
+    aliases: &'a Aliases,
+}
+
+impl Batch<'_> {
+    pub fn add_link(
         &mut self,
         parent: &str,
         link: &str,
         value: &str,
     ) -> Result<(), TapDataStoreError> {
-        validate_parent(parent)?;
-        validate_link(link)?;
-        if let Some((_, links)) = self.state.iter_mut().find(|(p, _)| p == parent) {
-            // If link already exists, update, else add
-            if let Some((_, v)) = links.iter_mut().find(|(l, _)| l.trim() == link) {
-                *v = value.trim().to_string();
-            } else {
-                links.push((link.trim().to_string(), value.trim().to_string()));
+        let parent = &canonical_parent(self.aliases, parent);
+        self.data.add_link(parent, link, value)?;
+        self.history
+            .push(history::Entry::new("add", parent, link, "", value));
+        Ok(())
+    }
+
+    /// Removes a link, or a parent with all of its links, keeping them in the trash file so they
+    /// can be restored with `DataStore::restore_trash`
+    pub fn delete(&mut self, parent: &str, link: Option<&str>) -> Result<(), TapDataStoreError> {
+        self.remove(parent, link, true)
+    }
+
+    /// Removes a link, or a parent with all of its links, for good
+    pub fn purge(&mut self, parent: &str, link: Option<&str>) -> Result<(), TapDataStoreError> {
+        self.remove(parent, link, false)
+    }
+
+    fn remove(
+        &mut self,
+        parent: &str,
+        link: Option<&str>,
+        keep_in_trash: bool,
+    ) -> Result<(), TapDataStoreError> {
+        let parent = &canonical_parent(self.aliases, parent);
+        let removed = self.data.get(parent, link).unwrap_or_default();
+        self.data.remove(parent, link)?;
+        for (link, value) in removed {
+            self.history
+                .push(history::Entry::new("delete", parent, &link, &value, ""));
+            if keep_in_trash {
+                self.trash.push(trash::Entry::new(parent, &link, &value));
             }
-        } else {
-            // If parent does not exist, add parent and new link/value pair
-            self.state.push((
-                parent.to_string(),
-                vec![(link.trim().to_string(), value.trim().to_string())],
-            ));
+        }
+        Ok(())
+    }
+
+    /// Adds back links taken from the trash file, see `DataStore::restore_trash`
+    fn restore(&mut self, parent: &str, links: &[LinkValue]) -> Result<(), TapDataStoreError> {
+        for (link, value) in links {
+            self.data.add_link(parent, link, value)?;
+            self.history
+                .push(history::Entry::new("restore", parent, link, "", value));
         }
         Ok(())
     }
 
     pub fn import(
         &mut self,
-        file_type: ImportType,
+        import_type: ImportType,
         path: PathBuf,
     ) -> Result<(), TapDataStoreError> {
-        validate_path(&file_type, &path)?;
-        let file_exists = path.try_exists().map_err(|e| TapDataStoreError {
-            kind: TapDataStoreErrorKind::FileOpenFailed,
-            message: format!("Unable to determine if file {} exists: {e}", path.display()),
-        })?;
-        if !file_exists {
-            return Err(TapDataStoreError {
-                kind: TapDataStoreErrorKind::FileOpenFailed,
-                message: format!("File {} does not exist", path.display()),
-            });
-        }
-        match file_type {
-            ImportType::Tap => {
-                let file_as_str = fs::read_to_string(&path).map_err(|e| TapDataStoreError {
-                    kind: TapDataStoreErrorKind::FileReadFailed,
-                    message: format!("Could not read data file at {}: {e}", path.display()),
-                })?;
-                let state = Data::parse_file(&file_as_str)?;
-                // TODO: refactor to hashmap? This would speed up import
-                state.iter().for_each(|(parent, links)| {
-                    links.iter().for_each(|(link, value)| {
-                        self.upsert_link(parent, link, value).unwrap();
-                    })
-                });
+        let before = self.data.state.clone();
+        self.data.import(import_type, path)?;
+        for (parent, links) in &self.data.state {
+            let old_links = before
+                .iter()
+                .find(|(p, _)| p == parent)
+                .map(|(_, links)| links.as_slice())
+                .unwrap_or_default();
+            for (link, value) in links {
+                let old = old_links.iter().find(|(l, _)| l == link).map(|(_, v)| v);
+                if old != Some(value) {
+                    self.history.push(history::Entry::new(
+                        "import",
+                        parent,
+                        link,
+                        old.map_or("", String::as_str),
+                        value,
+                    ));
+                }
             }
         }
+        Ok(())
+    }
+
+    /// Removes `parent` with its links for the archive file, see `DataStore::archive`
+    fn archive(&mut self, parent: &str) -> Result<(), TapDataStoreError> {
+        let removed = self.data.get(parent, None)?;
+        self.data.remove(parent, None)?;
+        for (link, value) in removed {
+            self.history
+                .push(history::Entry::new("archive", parent, &link, &value, ""));
+        }
+        Ok(())
+    }
+
+    /// Adds back the links of an archived `parent`, see `DataStore::unarchive`
+    fn unarchive(
+        &mut self,
+        parent: &str,
+        links: &[LinkValue],
+        meta: BTreeMap<String, LinkMeta>,
+    ) -> Result<(), TapDataStoreError> {
+        self.data.add_parent(parent, links, meta)?;
+        for (link, value) in links {
+            self.history
+                .push(history::Entry::new("unarchive", parent, link, "", value));
+        }
+        Ok(())
+    }
+
+    /// Changes the metadata of a link with `f`, returning the metadata it had before
+    pub fn update_meta(
+        &mut self,
+        parent: &str,
+        link: &str,
+        f: impl FnOnce(&mut LinkMeta),
+    ) -> Result<LinkMeta, TapDataStoreError> {
+        let parent = &canonical_parent(self.aliases, parent);
+        let old = self.data.get_meta(parent, link)?;
+        let mut meta = old.clone();
+        f(&mut meta);
+        self.data.set_meta(parent, link, meta)?;
+        Ok(old)
+    }
+
+    /// Makes `link` the default link of `parent`, or leaves the parent without one when `link` is
+    /// `None`, returning the link that was the default before
+    pub fn set_default(
+        &mut self,
+        parent: &str,
+        link: Option<&str>,
+    ) -> Result<Option<String>, TapDataStoreError> {
+        let parent = &canonical_parent(self.aliases, parent);
+        self.data.get(parent, link)?;
+        let old = self
+            .data
+            .meta_of_parent(parent)
+            .into_iter()
+            .find(|(_, meta)| meta.default)
+            .map(|(link, _)| link);
+        if let Some(old) = &old {
+            self.update_meta(parent, old, |meta| meta.default = false)?;
+        }
+        if let Some(link) = link {
+            self.update_meta(parent, link, |meta| meta.default = true)?;
+        }
+        Ok(old)
+    }
 
+    pub fn upsert_link(
+        &mut self,
+        parent: &str,
+        link: &str,
+        value: &str,
+    ) -> Result<(), TapDataStoreError> {
+        let parent = &canonical_parent(self.aliases, parent);
+        let old = self
+            .data
+            .get(parent, Some(link))
+            .ok()
+            .and_then(|links| links.into_iter().next())
+            .map(|(_, value)| value);
+        self.data.upsert_link(parent, link, value)?;
+        if old.as_deref() != Some(value.trim()) {
+            self.history.push(history::Entry::new(
+                "upsert",
+                parent,
+                link,
+                old.as_deref().unwrap_or_default(),
+                value,
+            ));
+        }
         Ok(())
     }
 }
 
 #[cfg(test)]
-mod data_public {
-    use super::{Data, FileType, ImportType, TapDataStoreErrorKind, get_test_file_path};
+mod data_store_batch {
+    use super::{
+        Aliases, Data, DataStore, FileType, Index, TapDataStoreError, TapDataStoreErrorKind,
+        get_test_file_path,
+    };
     use std::fs;
-    use std::path::PathBuf;
 
-    // TODO: GH-45 Move the following to an integration test
     #[test]
-    #[ignore = "GH-45: Should really be an integration test - move this out"]
-    fn test_new_no_path_correct_file_name() {
-        let expected_file_name = ".tap_data";
-        let mut data = Data::new(None, None).unwrap();
-        assert!(data.path.to_str().unwrap().ends_with(expected_file_name));
-        data.cleanup().expect("Could not clean up data store");
+    fn test_new_read_only_does_not_create_files() {
+        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
+        let mut ds = DataStore::new_read_only(
+            data_path.clone(),
+            get_test_file_path(FileType::Index).unwrap(),
+        )
+        .unwrap();
+        assert!(!data_path.exists());
+        assert!(ds.parents().is_empty());
+        let res = ds.add_link("repo".into(), "gh".into(), "github.com".into());
+        assert_eq!(res.unwrap_err().kind, TapDataStoreErrorKind::ReadOnly);
+        assert!(!data_path.exists());
     }
 
     #[test]
-    fn test_new_with_path_correct_file_name() {
-        let expected_file_name =
-            get_test_file_path(FileType::Data).expect("Could not get test file path");
-        let mut data = Data::new(Some(PathBuf::from(&expected_file_name)), None).unwrap();
-        assert_eq!(data.path, expected_file_name);
-        data.cleanup().expect("Could not clean up data store");
+    fn test_new_read_only_leaves_files_untouched() {
+        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
+        let index_path = get_test_file_path(FileType::Index).expect("Could not get test file path");
+        fs::write(&data_path, "repo->\n  gh|github.com\n").unwrap();
+        let mut ds = DataStore {
+            data: Data::open(Some(data_path.clone()), None, false).unwrap(),
+            index: Index::open(Some(index_path.clone()), false).unwrap(),
+            aliases: Aliases::new(),
+            archive: None,
+            trash: None,
+            untrashed: vec![],
+            read_only: true,
+            history: vec![],
+            saves: 0,
+        };
+        assert!(!index_path.exists());
+        assert_eq!(
+            ds.read_link_slow("repo", "gh").unwrap(),
+            Some(("gh".to_string(), "github.com".to_string()))
+        );
+        let res = ds.delete("repo".into(), None);
+        assert_eq!(res.unwrap_err().kind, TapDataStoreErrorKind::ReadOnly);
+        assert_eq!(
+            fs::read_to_string(&data_path).unwrap(),
+            "repo->\n  gh|github.com\n"
+        );
+        fs::remove_file(&data_path).unwrap();
     }
 
     #[test]
-    fn test_set_state_correct() {
-        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
-        fs::write(
-            &data_path,
-            "parent1->\nlink1|value1\nlink2|value2\nparent2 ->\nlink3|value3\nlink4|value4",
-        )
+    fn test_batch_saves_once() {
+        let mut ds = DataStore::in_memory(vec![]);
+        ds.batch(|tx| {
+            tx.add_link("repo", "gh", "github.com")?;
+            tx.add_link("repo", "ci", "ci.example.com")?;
+            tx.upsert_link("repo", "gh", "https://github.com")?;
+            tx.add_link("search-engines", "google", "google.com")
+        })
         .unwrap();
-        let mut data = Data::new(Some(data_path), None).unwrap();
+        assert_eq!(ds.saves, 1);
         assert_eq!(
-            data.state,
+            ds.parents(),
+            vec!["repo".to_string(), "search-engines".to_string()]
+        );
+        assert_eq!(
+            ds.read_parent_slow("repo").unwrap(),
             vec![
-                (
-                    "parent1".to_string(),
-                    vec![
-                        ("link1".to_string(), "value1".to_string()),
-                        ("link2".to_string(), "value2".to_string())
-                    ]
-                ),
-                (
-                    "parent2 ".to_string(),
-                    vec![
-                        ("link3".to_string(), "value3".to_string()),
-                        ("link4".to_string(), "value4".to_string())
-                    ]
-                ),
+                ("ci".to_string(), "ci.example.com".to_string()),
+                ("gh".to_string(), "https://github.com".to_string()),
             ]
         );
-        data.cleanup().expect("Could not clean up data store");
     }
 
     #[test]
-    fn test_set_state_correct_reader_eof() {
-        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
-        fs::write(
-            &data_path,
-            "parent1->\nlink1|value1\nlink2|value2\nparent2 ->\nlink3|value3\nlink4|value4",
-        )
-        .unwrap();
-        let mut data = Data::new(Some(data_path), Some((36, 0))).unwrap();
+    fn test_batch_error_rolls_back_state() {
+        let mut ds = DataStore::in_memory(vec![(
+            "repo".to_string(),
+            vec![("gh".to_string(), "github.com".to_string())],
+        )]);
+        let res = ds.batch(|tx| {
+            tx.delete("repo", Some("gh"))?;
+            tx.add_link("repo", "ci", "ci.example.com")?;
+            tx.add_link("repo", "ci", "duplicate")
+        });
         assert_eq!(
-            data.state,
-            vec![(
-                "parent2 ".to_string(),
-                vec![
-                    ("link3".to_string(), "value3".to_string()),
-                    ("link4".to_string(), "value4".to_string())
-                ]
-            ),]
+            res.unwrap_err().kind,
+            TapDataStoreErrorKind::LinkAlreadyExists
+        );
+        assert_eq!(ds.saves, 0);
+        assert_eq!(
+            ds.read_parent_slow("repo").unwrap(),
+            vec![("gh".to_string(), "github.com".to_string())]
         );
-        data.cleanup().expect("Could not clean up data store");
     }
 
     #[test]
-    fn test_set_state_correct_reader() {
+    fn test_batch_error_leaves_files_untouched() {
         let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
-        fs::write(
-            &data_path,
-            "parent1->\nlink1|value1\nlink2|value2\nparent2 ->\nlink3|value3\nlink4|value4",
-        )
-        .unwrap();
-        let mut data = Data::new(Some(data_path), Some((0, 35))).unwrap();
-        assert_eq!(
-            data.state,
+        let index_path = get_test_file_path(FileType::Index).expect("Could not get test file path");
+        fs::write(&data_path, "repo->\n  gh|github.com\n").unwrap();
+        fs::write(&index_path, "repo|0\n").unwrap();
+        let mut ds = DataStore {
+            data: Data::new(Some(data_path.clone()), None).unwrap(),
+            index: Index::new(Some(index_path.clone())).unwrap(),
+            aliases: Aliases::new(),
+            archive: None,
+            trash: None,
+            untrashed: vec![],
+            read_only: false,
+            history: vec![],
+            saves: 0,
+        };
+        let res: Result<(), TapDataStoreError> = ds.batch(|tx| {
+            tx.add_link("apple", "homepage", "apple.com")?;
+            tx.delete("missing", None)
+        });
+        assert_eq!(
+            res.unwrap_err().kind,
+            TapDataStoreErrorKind::ParentEntityNotFound
+        );
+        assert_eq!(
+            fs::read_to_string(&data_path).unwrap(),
+            "repo->\n  gh|github.com\n"
+        );
+        assert_eq!(fs::read_to_string(&index_path).unwrap(), "repo|0\n");
+        ds.data.cleanup().expect("Could not clean up data store");
+        ds.index.cleanup().expect("Could not clean up index store");
+    }
+}
+
+/// A lazily opened data store shared by a command. By default, the data files next to the
+/// executable are only opened once the command actually needs them, and reads of a single parent
+/// go through the index via `ReadDataStore`. A handle created with `DataStoreHandle::in_memory`
+/// never touches the filesystem, which lets commands run hermetically in tests.
+pub(crate) struct DataStoreHandle {
+    store: RefCell<Option<DataStore>>,
+    /// Where the data files are and what may be done with them, see `StoreOptions`
+    options: StoreOptions,
+    read_only: bool,
+    /// The links opened last by an in memory store, which has no recent file to keep them in
+    opened: RefCell<Vec<recent::Entry>>,
+    /// The sessions of an in memory store, which has no sessions file to keep them in
+    sessions: RefCell<Sessions>,
+    /// The groups of an in memory store, which has no groups file to keep them in
+    groups: RefCell<Groups>,
+}
+
+impl DataStoreHandle {
+    /// A handle on the data files picked by `options`, opened on first use
+    pub fn new(options: StoreOptions) -> Self {
+        Self {
+            store: RefCell::new(None),
+            read_only: options.read_only,
+            options,
+            opened: RefCell::new(vec![]),
+            sessions: RefCell::new(Sessions::new()),
+            groups: RefCell::new(Groups::new()),
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn in_memory(state: Vec<(String, Vec<LinkValue>)>) -> Self {
+        Self {
+            store: RefCell::new(Some(DataStore::in_memory(state))),
+            options: StoreOptions::default(),
+            read_only: false,
+            opened: RefCell::new(vec![]),
+            sessions: RefCell::new(Sessions::new()),
+            groups: RefCell::new(Groups::new()),
+        }
+    }
+
+    /// Makes every mutation made through this handle fail with `TapDataStoreErrorKind::ReadOnly`
+    #[allow(dead_code)]
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self.options.read_only = true;
+        if let Some(ds) = self.store.get_mut() {
+            ds.read_only = true;
+        }
+        self
+    }
+
+    /// The options the handle was created with, see `StoreOptions`
+    pub fn options(&self) -> &StoreOptions {
+        &self.options
+    }
+
+    /// Switches the handle to the data files of `profile`, opened on the next read. In memory
+    /// stores have no files to switch and are kept.
+    pub fn use_profile(&mut self, profile: String) {
+        self.options.profile = Some(profile);
+        self.reload();
+    }
+
+    fn data_path(&self) -> Result<PathBuf, TapDataStoreError> {
+        Ok(self.options.paths()?.0)
+    }
+
+    /// Closes the data files, so the next read opens them again and sees the changes made since,
+    /// e.g. by another tap. In memory stores have no files to read again and are kept.
+    pub fn reload(&self) {
+        let mut store = self.store.borrow_mut();
+        if store.as_ref().is_some_and(|ds| !ds.data.in_memory) {
+            *store = None;
+        }
+    }
+
+    /// How many times the underlying `DataStore` was saved
+    #[cfg(test)]
+    pub fn saves(&self) -> usize {
+        self.store.borrow().as_ref().map_or(0, |ds| ds.saves)
+    }
+
+    /// Runs `f` against the underlying `DataStore`, opening the data files first if needed.
+    /// Changes to the data file are recorded for `tap --undo`, see `record_undo`, every changed
+    /// link is added to the history file, see `history::append`, and the post write hook is run,
+    /// see `hooks::run_post_write`.
+    pub fn write<T>(
+        &self,
+        f: impl FnOnce(&mut DataStore) -> Result<T, TapDataStoreError>,
+    ) -> Result<T, TapDataStoreError> {
+        let mut ds = self.open_for_writing()?;
+        let before = (!ds.data.in_memory).then_some(ds.data.on_disk);
+        // The contents the change replaces are only read for the snapshot, `Data` does not keep them
+        let replaced = match before {
+            Some(Some(_)) => Some(read_data_file_at(&ds.data.path)?.unwrap_or_default()),
+            _ => None,
+        };
+        // The snapshot is also what tells whether something else changed the data file since it
+        // was read, so saving does not have to read it again
+        if let (Some(replaced), Some(Some(on_disk))) = (&replaced, before) {
+            if FileStamp::of(replaced) != on_disk {
+                return Err(data_file_changed(&ds.data.path));
+            }
+            ds.data.verified = true;
+        }
+        let res = f(&mut ds)?;
+        ds.prune_aliases()?;
+        self.prune_groups(&ds)?;
+        let untrashed = std::mem::take(&mut ds.untrashed);
+        if let Some(replaced) = &replaced {
+            record_undo(
+                &ds.data.path,
+                replaced,
+                ds.data.on_disk,
+                &untrashed,
+                &self.options,
+            );
+        }
+        let changes = std::mem::take(&mut ds.history);
+        if !ds.data.in_memory {
+            history::append(&history::history_path_for(&ds.data.path), &changes);
+        }
+        // Hooks only run when the data file was written with something new
+        if !self.options.no_hooks && before.is_some_and(|before| before != ds.data.on_disk) {
+            hooks::run_post_write(&changes);
+        }
+        Ok(res)
+    }
+
+    /// Runs `f` like `DataStore::batch` would, failing the same way, but rolls every mutation
+    /// back afterwards, see `DataStore::dry_run`. No file is written.
+    pub fn dry_run<T>(
+        &self,
+        f: impl FnOnce(&mut Batch) -> Result<T, TapDataStoreError>,
+    ) -> Result<T, TapDataStoreError> {
+        self.open_for_writing()?.dry_run(f)
+    }
+
+    fn open_for_writing(&self) -> Result<RefMut<'_, DataStore>, TapDataStoreError> {
+        let mut store = self.store.borrow_mut();
+        if store.is_none() {
+            let (data, index) = self.options.paths()?;
+            *store = Some(if self.read_only {
+                DataStore::new_read_only(data, index)?
+            } else {
+                DataStore::new(data, index)?
+            });
+        }
+        Ok(RefMut::map(store, |store| {
+            store.as_mut().expect("Data store was opened above")
+        }))
+    }
+
+    /// Replaces the store with a salvaged copy of the data files (see `DataStore::salvage`),
+    /// returning the skipped lines and the file they were written to. In memory stores are never
+    /// corrupt, so there is nothing to salvage.
+    pub fn salvage(&self) -> Result<(Vec<RejectedLine>, PathBuf), TapDataStoreError> {
+        if self.read_only {
+            return Err(read_only_error());
+        }
+        let mut store = self.store.borrow_mut();
+        if let Some(ds) = store.as_ref()
+            && ds.data.in_memory
+        {
+            return Ok((vec![], ds.rejected_path()));
+        }
+        let (data, index) = self.options.paths()?;
+        let before = read_data_file_at(&data)?;
+        let (ds, rejected) = DataStore::salvage(data, index)?;
+        if let Some(before) = before {
+            record_undo(&ds.data.path, &before, ds.data.on_disk, &[], &self.options);
+        }
+        let rejected_path = ds.rejected_path();
+        *store = Some(ds);
+        Ok((rejected, rejected_path))
+    }
+
+    /// Replaces the store with a compacted copy of the data files (see `DataStore::compact`),
+    /// returning what was cleaned up and the file that skipped lines were written to.
+    pub fn compact(&self, salvage: bool) -> Result<(CompactSummary, PathBuf), TapDataStoreError> {
+        if self.read_only {
+            return Err(read_only_error());
+        }
+        let mut store = self.store.borrow_mut();
+        if let Some(ds) = store.as_mut()
+            && ds.data.in_memory
+        {
+            let summary = ds.data.compact_in_memory()?;
+            ds.save()?;
+            return Ok((summary, ds.rejected_path()));
+        }
+        let (data, index) = self.options.paths()?;
+        let before = read_data_file_at(&data)?;
+        let (ds, summary) = DataStore::compact(data, index, salvage)?;
+        if let Some(before) = before {
+            record_undo(&ds.data.path, &before, ds.data.on_disk, &[], &self.options);
+        }
+        let rejected_path = ds.rejected_path();
+        *store = Some(ds);
+        Ok((summary, rejected_path))
+    }
+
+    /// Replaces the store with one whose index file was rebuilt from the data file (see
+    /// `DataStore::rebuild_index`)
+    pub fn rebuild_index(&self) -> Result<(), TapDataStoreError> {
+        if self.read_only {
+            return Err(read_only_error());
+        }
+        let mut store = self.store.borrow_mut();
+        if let Some(ds) = store.as_mut()
+            && ds.data.in_memory
+        {
+            return ds.save();
+        }
+        let (data, index) = self.options.paths()?;
+        *store = Some(DataStore::rebuild_index(data, index)?);
+        Ok(())
+    }
+
+    /// Checks the data and index files for problems, see `diagnose_files`. In memory stores keep
+    /// their index up to date with every mutation, so they never have any.
+    pub fn diagnose(&self) -> Result<Vec<Finding>, TapDataStoreError> {
+        if let Some(ds) = self.store.borrow().as_ref()
+            && ds.data.in_memory
+        {
+            return Ok(vec![]);
+        }
+        let read = |path: &Path| match path.exists() {
+            true => fs::read_to_string(path)
+                .map(Some)
+                .map_err(|e| TapDataStoreError {
+                    kind: TapDataStoreErrorKind::FileReadFailed,
+                    message: format!("Could not read {}: {e}", path.display()),
+                }),
+            false => Ok(None),
+        };
+        let (data, index) = self.options.paths()?;
+        let data = read(&data)?.unwrap_or_default();
+        let index = read(&index)?;
+        Ok(diagnose_files(&data, index.as_deref()))
+    }
+
+    pub fn parents(&self) -> Result<Vec<String>, TapDataStoreError> {
+        if let Some(ds) = self.store.borrow().as_ref() {
+            return Ok(ds.parents());
+        }
+        // Reading never creates missing data files
+        let (data, index) = self.options.paths()?;
+        let index = Index::open(Some(index), false)?;
+        if !index.path.exists() {
+            // A data file without an index yet, e.g. one passed with --file
+            let data = Data::open(Some(data), None, false)?;
+            return Ok(data
+                .state
+                .into_iter()
+                .map(|(parent, _)| parent.trim().to_string())
+                .collect());
+        }
+        Ok(index.parents())
+    }
+
+    pub fn read_parent(&self, parent: &str) -> Result<Vec<LinkValue>, TapDataStoreError> {
+        let parent = &self.resolve_alias(parent)?;
+        let links = match self.store.borrow().as_ref() {
+            Some(ds) => ds.read_parent_slow(parent),
+            None => self
+                .options
+                .paths()
+                .and_then(|(data, index)| ReadDataStore::new(data, index, parent.to_string()))
+                .and_then(|store| store.read_parent(parent)),
+        };
+        links.map_err(|e| self.with_suggestions(e, parent, None))
+    }
+
+    pub fn read_link(&self, parent: &str, link: &str) -> Result<LinkValue, TapDataStoreError> {
+        let parent = &self.resolve_alias(parent)?;
+        let link_value = match self.store.borrow().as_ref() {
+            Some(ds) => ds.read_link_slow(parent, link).and_then(|link_value| {
+                link_value.ok_or(TapDataStoreError {
+                    kind: TapDataStoreErrorKind::LinkNotFound,
+                    message: format!("Link '{link}' not found in parent '{parent}'"),
+                })
+            }),
+            None => self
+                .options
+                .paths()
+                .and_then(|(data, index)| ReadDataStore::new(data, index, parent.to_string()))
+                .and_then(|store| store.read_link(parent, link)),
+        };
+        link_value.map_err(|e| self.with_suggestions(e, parent, Some(link)))
+    }
+
+    /// Adds the closest known names to an error for a parent or link that was not found, see
+    /// `suggest::did_you_mean`. Any other error is returned as it is.
+    pub fn with_suggestions(
+        &self,
+        mut e: TapDataStoreError,
+        parent: &str,
+        link: Option<&str>,
+    ) -> TapDataStoreError {
+        // Suggestions are best effort, so failing to list the names just leaves them out
+        let suggestion = match (&e.kind, link) {
+            (TapDataStoreErrorKind::ParentEntityNotFound, _) => self
+                .parents()
+                .ok()
+                .and_then(|parents| suggest::did_you_mean(parent, &parents)),
+            (TapDataStoreErrorKind::LinkNotFound, Some(link)) => self
+                .links(parent)
+                .ok()
+                .and_then(|links| suggest::did_you_mean(link, &links)),
+            _ => None,
+        };
+        if let Some(suggestion) = suggestion {
+            e.message = format!("{}. {suggestion}", e.message);
+        }
+        e
+    }
+
+    /// Every parent entity with its links, read with a single parse of the data file instead of
+    /// one index lookup per parent
+    pub fn all_links(&self) -> Result<Vec<(String, Vec<LinkValue>)>, TapDataStoreError> {
+        let state = match self.store.borrow().as_ref() {
+            Some(ds) => ds.data.state.clone(),
+            // Reading never creates missing data files
+            None => Data::open(Some(self.data_path()?), None, false)?.state,
+        };
+        Ok(state
+            .into_iter()
+            .map(|(parent, links)| (parent.trim().to_string(), links))
+            .collect())
+    }
+
+    pub fn links(&self, parent: &str) -> Result<Vec<String>, TapDataStoreError> {
+        let links = self.read_parent(parent)?;
+        Ok(links.iter().map(|(l, _)| l.clone()).collect())
+    }
+
+    /// The aliases of parent entities, see `aliases::Aliases`
+    pub fn aliases(&self) -> Result<Aliases, TapDataStoreError> {
+        match self.store.borrow().as_ref() {
+            Some(ds) => Ok(ds.aliases.clone()),
+            None => load_aliases_of(&self.data_path()?),
+        }
+    }
+
+    /// The changes recorded in the history file, oldest first, see `history::append`. In memory
+    /// stores keep no history.
+    pub fn history(&self) -> Result<Vec<history::Entry>, TapDataStoreError> {
+        let data = match self.store.borrow().as_ref() {
+            Some(ds) if ds.data.in_memory => return Ok(vec![]),
+            Some(ds) => ds.data.path.clone(),
+            None => self.data_path()?,
+        };
+        let path = history::history_path_for(&data);
+        history::read(&path).map_err(|e| TapDataStoreError {
+            kind: TapDataStoreErrorKind::FileReadFailed,
+            message: format!("Could not read {}: {e}", path.display()),
+        })
+    }
+
+    /// Records that `link` of `parent` was just opened in the recent file, see `recent::push`. The
+    /// link was opened already, so like the history this is best effort: failing to write it is
+    /// only noted. Read only stores record nothing.
+    pub fn record_opened(&self, parent: &str, link: &str) {
+        if self.read_only {
+            return;
+        }
+        let opened = recent::Entry::new(parent, link);
+        let path = match self.store.borrow().as_ref() {
+            Some(ds) if ds.data.in_memory => {
+                recent::push(&mut self.opened.borrow_mut(), opened);
+                return;
+            }
+            Some(ds) => recent_path_for(&ds.data.path),
+            None => match self.data_path() {
+                Ok(data) => recent_path_for(&data),
+                // The link was opened, so there is a data file, it just can not be named again
+                Err(_) => return,
+            },
+        };
+        let recorded = recent::read(&path).and_then(|mut entries| {
+            recent::push(&mut entries, opened);
+            recent::write(&path, &entries)
+        });
+        if let Err(e) = recorded {
+            log::note(format_args!(
+                "Could not record the opened link in {}: {e}",
+                path.display()
+            ));
+        }
+    }
+
+    /// The links opened last, oldest first, see `record_opened`
+    pub fn recently_opened(&self) -> Result<Vec<recent::Entry>, TapDataStoreError> {
+        let data = match self.store.borrow().as_ref() {
+            Some(ds) if ds.data.in_memory => return Ok(self.opened.borrow().clone()),
+            Some(ds) => ds.data.path.clone(),
+            None => self.data_path()?,
+        };
+        let path = recent_path_for(&data);
+        recent::read(&path).map_err(|e| TapDataStoreError {
+            kind: TapDataStoreErrorKind::FileReadFailed,
+            message: format!("Could not read {}: {e}", path.display()),
+        })
+    }
+
+    /// The saved sessions, see `sessions::Sessions`
+    pub fn sessions(&self) -> Result<Sessions, TapDataStoreError> {
+        let data = match self.store.borrow().as_ref() {
+            Some(ds) if ds.data.in_memory => return Ok(self.sessions.borrow().clone()),
+            Some(ds) => ds.data.path.clone(),
+            None => self.data_path()?,
+        };
+        let path = sessions_path_for(&data);
+        sessions::read(&path).map_err(|e| TapDataStoreError {
+            kind: TapDataStoreErrorKind::FileReadFailed,
+            message: format!("Could not read {}: {e}", path.display()),
+        })
+    }
+
+    /// Replaces the saved sessions with `sessions`. The data file is not changed, so this is not
+    /// recorded for `tap --undo`.
+    pub fn write_sessions(&self, sessions: &Sessions) -> Result<(), TapDataStoreError> {
+        if self.read_only {
+            return Err(read_only_error());
+        }
+        let data = match self.store.borrow().as_ref() {
+            Some(ds) if ds.data.in_memory => {
+                *self.sessions.borrow_mut() = sessions.clone();
+                return Ok(());
+            }
+            Some(ds) => ds.data.path.clone(),
+            None => self.data_path()?,
+        };
+        let path = sessions_path_for(&data);
+        sessions::write(&path, sessions).map_err(|e| TapDataStoreError {
+            kind: TapDataStoreErrorKind::FileWriteFailed,
+            message: format!("Could not write {}: {e}", path.display()),
+        })
+    }
+
+    /// The groups, see `groups::Groups`. Changes to the data file made outside of tap, like with
+    /// `tap --edit`, are picked up as in `groups::reconcile`, noting the links dropped from a group.
+    pub fn groups(&self) -> Result<Groups, TapDataStoreError> {
+        let mut groups = self.read_groups()?;
+        let dropped = groups::reconcile(&mut groups, &self.all_meta()?);
+        note_dropped(&dropped);
+        Ok(groups)
+    }
+
+    /// Replaces the groups with `groups`. Which links are in a group is kept in their metadata,
+    /// so this only changes the order of the groups and of their links.
+    pub fn write_groups(&self, groups: &Groups) -> Result<(), TapDataStoreError> {
+        if self.read_only {
+            return Err(read_only_error());
+        }
+        let data = match self.store.borrow().as_ref() {
+            Some(ds) if ds.data.in_memory => {
+                *self.groups.borrow_mut() = groups.clone();
+                return Ok(());
+            }
+            Some(ds) => ds.data.path.clone(),
+            None => self.data_path()?,
+        };
+        let path = groups_path_for(&data);
+        groups::write(&path, groups).map_err(|e| TapDataStoreError {
+            kind: TapDataStoreErrorKind::FileWriteFailed,
+            message: format!("Could not write {}: {e}", path.display()),
+        })
+    }
+
+    fn read_groups(&self) -> Result<Groups, TapDataStoreError> {
+        let data = match self.store.borrow().as_ref() {
+            Some(ds) if ds.data.in_memory => return Ok(self.groups.borrow().clone()),
+            Some(ds) => ds.data.path.clone(),
+            None => self.data_path()?,
+        };
+        let path = groups_path_for(&data);
+        groups::read(&path).map_err(|e| TapDataStoreError {
+            kind: TapDataStoreErrorKind::FileReadFailed,
+            message: format!("Could not read {}: {e}", path.display()),
+        })
+    }
+
+    /// Keeps the groups in line with the links just written by `ds`, like deleting a link drops
+    /// it from its groups, noting each link dropped
+    fn prune_groups(&self, ds: &DataStore) -> Result<(), TapDataStoreError> {
+        let mut groups = match ds.data.in_memory {
+            true => self.groups.borrow().clone(),
+            false => match groups::read(&groups_path_for(&ds.data.path)) {
+                Ok(groups) => groups,
+                // The data file was written already, the groups are set right when read next
+                Err(_) => return Ok(()),
+            },
+        };
+        let before = groups.clone();
+        let dropped = groups::reconcile(&mut groups, &ds.data.meta);
+        note_dropped(&dropped);
+        if groups == before || ds.read_only {
+            return Ok(());
+        }
+        match ds.data.in_memory {
+            true => {
+                *self.groups.borrow_mut() = groups;
+                Ok(())
+            }
+            false => {
+                let path = groups_path_for(&ds.data.path);
+                groups::write(&path, &groups).map_err(|e| TapDataStoreError {
+                    kind: TapDataStoreErrorKind::FileWriteFailed,
+                    message: format!("Could not write {}: {e}", path.display()),
+                })
+            }
+        }
+    }
+
+    /// The links in the trash file, oldest first, see `Batch::delete`
+    pub fn trashed(&self) -> Result<Vec<trash::Entry>, TapDataStoreError> {
+        match self.store.borrow_mut().as_mut() {
+            Some(ds) => ds.trashed(),
+            None => read_trash_of(&self.data_path()?),
+        }
+    }
+
+    /// The archived parent entities with their links, see `DataStore::archive`
+    pub fn archived(&self) -> Result<Vec<(String, Vec<LinkValue>)>, TapDataStoreError> {
+        let state = match self.store.borrow_mut().as_mut() {
+            Some(ds) => ds.archived()?,
+            // Reading never creates a missing archive file
+            None => {
+                let path = archive_path_for(&self.data_path()?);
+                Data::open(Some(path), None, false)?.state
+            }
+        };
+        Ok(state
+            .into_iter()
+            .map(|(parent, links)| (parent.trim().to_string(), links))
+            .collect())
+    }
+
+    /// The parent entity `parent` names, which is `parent` itself unless it is an alias
+    pub fn resolve_alias(&self, parent: &str) -> Result<String, TapDataStoreError> {
+        Ok(canonical_parent(&self.aliases()?, parent))
+    }
+
+    /// The metadata of every link that has any, by parent entity and link name, read with a single
+    /// parse of the data file like `all_links`
+    pub fn all_meta(&self) -> Result<BTreeMap<(String, String), LinkMeta>, TapDataStoreError> {
+        match self.store.borrow().as_ref() {
+            Some(ds) => Ok(ds.data.meta.clone()),
+            // Reading never creates missing data files
+            None => Ok(Data::open(Some(self.data_path()?), None, false)?.meta),
+        }
+    }
+
+    /// The metadata of the links of `parent` that have any, by link name. A parent that does not
+    /// exist has none.
+    pub fn read_meta(&self, parent: &str) -> Result<BTreeMap<String, LinkMeta>, TapDataStoreError> {
+        let parent = &self.resolve_alias(parent)?;
+        match self.store.borrow().as_ref() {
+            Some(ds) => Ok(ds.data.meta_of_parent(parent)),
+            None => {
+                let (data, index) = self.options.paths()?;
+                Ok(ReadDataStore::new(data, index, parent.to_string())?.read_meta(parent))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod data_store_handle {
+    use super::{DataStoreHandle, TapDataStoreErrorKind};
+
+    fn search_engines() -> DataStoreHandle {
+        DataStoreHandle::in_memory(vec![(
+            "search-engines".to_string(),
+            vec![
+                ("yahoo".to_string(), "www.yahoo.com".to_string()),
+                ("google".to_string(), "www.google.com".to_string()),
+            ],
+        )])
+    }
+
+    #[test]
+    fn test_in_memory_parents() {
+        let handle = search_engines();
+        assert_eq!(
+            handle.parents().unwrap(),
+            vec!["search-engines".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_in_memory_read_parent_is_sorted() {
+        let handle = search_engines();
+        assert_eq!(
+            handle.read_parent("search-engines").unwrap(),
+            vec![
+                ("google".to_string(), "www.google.com".to_string()),
+                ("yahoo".to_string(), "www.yahoo.com".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_in_memory_all_links() {
+        let handle = search_engines();
+        assert_eq!(
+            handle.all_links().unwrap(),
+            vec![(
+                "search-engines".to_string(),
+                vec![
+                    ("google".to_string(), "www.google.com".to_string()),
+                    ("yahoo".to_string(), "www.yahoo.com".to_string()),
+                ]
+            )]
+        );
+    }
+
+    #[test]
+    fn test_in_memory_read_link_not_found() {
+        let handle = search_engines();
+        assert_eq!(
+            handle.read_link("search-engines", "bing").unwrap_err().kind,
+            TapDataStoreErrorKind::LinkNotFound
+        );
+    }
+
+    #[test]
+    fn test_in_memory_read_parent_not_found_suggests_parents() {
+        let handle = search_engines();
+        let err = handle.read_parent("serach-engines").unwrap_err();
+        assert_eq!(err.kind, TapDataStoreErrorKind::ParentEntityNotFound);
+        assert_eq!(
+            err.message,
+            "Parent 'serach-engines' not found. Did you mean: search-engines?"
+        );
+    }
+
+    #[test]
+    fn test_in_memory_read_link_not_found_suggests_links() {
+        let handle = search_engines();
+        let err = handle.read_link("search-engines", "gogle").unwrap_err();
+        assert_eq!(
+            err.message,
+            "Link 'gogle' not found in parent 'search-engines'. Did you mean: google?"
+        );
+    }
+
+    #[test]
+    fn test_in_memory_not_found_without_suggestions() {
+        let handle = DataStoreHandle::in_memory(vec![]);
+        let err = handle.read_parent("search-engines").unwrap_err();
+        assert_eq!(err.message, "Parent 'search-engines' not found");
+        let err = search_engines()
+            .read_link("search-engines", "bing")
+            .unwrap_err();
+        assert_eq!(
+            err.message,
+            "Link 'bing' not found in parent 'search-engines'"
+        );
+    }
+
+    #[test]
+    fn test_in_memory_write_updates_index() {
+        let handle = search_engines();
+        handle
+            .write(|ds| {
+                ds.add_link(
+                    "repo".to_string(),
+                    "gh".to_string(),
+                    "github.com".to_string(),
+                )
+            })
+            .unwrap();
+        assert_eq!(
+            handle.parents().unwrap(),
+            vec!["repo".to_string(), "search-engines".to_string()]
+        );
+        assert_eq!(
+            handle.read_link("repo", "gh").unwrap(),
+            ("gh".to_string(), "github.com".to_string())
+        );
+    }
+
+    #[test]
+    fn test_in_memory_delete_parent() {
+        let handle = search_engines();
+        handle
+            .write(|ds| ds.delete("search-engines".to_string(), None))
+            .unwrap();
+        assert!(handle.parents().unwrap().is_empty());
+        assert_eq!(
+            handle.read_parent("search-engines").unwrap_err().kind,
+            TapDataStoreErrorKind::ParentEntityNotFound
+        );
+    }
+
+    #[test]
+    fn test_read_only_rejects_mutations() {
+        let handle = search_engines().read_only();
+        let res = handle.write(|ds| ds.add_link("repo".into(), "gh".into(), "github.com".into()));
+        assert_eq!(res.unwrap_err().kind, TapDataStoreErrorKind::ReadOnly);
+        let res = handle.write(|ds| ds.delete("search-engines".into(), None));
+        assert_eq!(res.unwrap_err().kind, TapDataStoreErrorKind::ReadOnly);
+        let res = handle
+            .write(|ds| ds.upsert_link("search-engines".into(), "google".into(), "g.com".into()));
+        assert_eq!(res.unwrap_err().kind, TapDataStoreErrorKind::ReadOnly);
+        assert_eq!(
+            handle.salvage().unwrap_err().kind,
+            TapDataStoreErrorKind::ReadOnly
+        );
+        assert_eq!(
+            handle.compact(false).unwrap_err().kind,
+            TapDataStoreErrorKind::ReadOnly
+        );
+    }
+
+    #[test]
+    fn test_read_only_allows_reads() {
+        let handle = search_engines().read_only();
+        assert_eq!(
+            handle.read_link("search-engines", "google").unwrap(),
+            ("google".to_string(), "www.google.com".to_string())
+        );
+        assert_eq!(
+            handle.links("search-engines").unwrap(),
+            vec!["google".to_string(), "yahoo".to_string()]
+        );
+    }
+}
+
+/// Comment lines (starting with `#`) of a data file, kept so that saving does not destroy them
+#[derive(Debug, Default, PartialEq)]
+struct Comments {
+    /// Comments above the first parent, always written at the top of the file
+    header: Vec<String>,
+    /// Comments written directly above the parent that followed them in the file
+    parents: Vec<(String, Vec<String>)>,
+    /// Comments below the last parent
+    trailing: Vec<String>,
+}
+
+impl Comments {
+    fn parse(file_as_str: &str) -> Self {
+        let mut parser = CommentsParser::default();
+        file_as_str.lines().for_each(|line| parser.line(line));
+        parser.finish()
+    }
+
+    /// The comments to write above `parent`, merged if the parent appeared more than once
+    fn of_parent(&self, parent: &str) -> Vec<&String> {
+        self.parents
+            .iter()
+            .filter(|(p, _)| p == parent.trim())
+            .flat_map(|(_, c)| c.iter())
+            .collect()
+    }
+}
+
+/// Collects the `Comments` of a data file a line at a time, so it can be done while the file is
+/// streamed through `Data::parse_lines`
+#[derive(Default)]
+struct CommentsParser {
+    comments: Comments,
+    /// Comments not yet known to be above a parent
+    pending: Vec<String>,
+    seen_parent: bool,
+    seen_line: bool,
+}
+
+impl CommentsParser {
+    fn line(&mut self, line: &str) {
+        // The header is written again when it is needed, see `Data::state_to_file_string`
+        if !self.seen_line && has_format_header(line) {
+            self.seen_line = true;
+            return;
+        }
+        self.seen_line = true;
+        if is_comment(line) {
+            self.pending.push(line.trim().to_string());
+        } else if line.ends_with("->") {
+            let parent = line.trim_end_matches("->").trim().to_string();
+            if !self.seen_parent {
+                self.comments.header.append(&mut self.pending);
+                self.seen_parent = true;
+            } else if !self.pending.is_empty() {
+                self.comments
+                    .parents
+                    .push((parent, std::mem::take(&mut self.pending)));
+            }
+        }
+    }
+
+    fn finish(mut self) -> Comments {
+        self.comments.trailing = self.pending;
+        self.comments
+    }
+}
+
+fn note_dropped(dropped: &[(String, String, String)]) {
+    for (group, parent, link) in dropped {
+        log::note(format_args!(
+            "Dropped {parent}/{link} from group {group}, it was deleted or taken out of the group"
+        ));
+    }
+}
+
+fn is_comment(line: &str) -> bool {
+    line.trim_start().starts_with('#')
+}
+
+/// Metadata of a link, written on its line after the value as fields separated by `|`, like
+/// `ci|https://ci.internal|pinned`. Only known fields are metadata, anything else after a `|`
+/// stays part of the value as it did before links had metadata.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct LinkMeta {
+    /// Pinned links are listed first and are the ones opened by `tap <Parent Entity> --pinned`
+    pub pinned: bool,
+    /// Set with `tap --note`, written as `note=<text>`
+    pub note: Option<String>,
+    /// The link `tap <Parent Entity>` opens instead of all of them, at most one per parent
+    pub default: bool,
+    /// The day the link expires on, set with `tap --add --expires` and written as
+    /// `expires=2024-07-01`, see `expiry::is_expired`
+    pub expires: Option<String>,
+    /// How the value is opened, set when the link is added and written as `type=cmd`. URLs are
+    /// the default and not written, links without a type are opened like they always were.
+    pub link_type: Option<LinkType>,
+    /// The groups the link was added to with `tap --group add`, each written as `group=<name>`,
+    /// see `groups::Groups`
+    pub groups: Vec<String>,
+}
+
+impl LinkMeta {
+    /// Reads one unescaped field of a link line into the metadata, `false` when the field is not
+    /// metadata
+    fn parse_field(&mut self, field: &str) -> bool {
+        match field.trim().split_once('=') {
+            None if field.trim() == "pinned" => self.pinned = true,
+            None if field.trim() == "default" => self.default = true,
+            Some(("note", note)) => self.note = Some(note.trim().to_string()),
+            Some(("expires", date)) if expiry::is_date(date.trim()) => {
+                self.expires = Some(date.trim().to_string())
+            }
+            Some(("type", name)) if LinkType::parse(name).is_some() => {
+                self.link_type = LinkType::parse(name)
+            }
+            Some(("group", name)) if !name.trim().is_empty() => {
+                if !self.groups.iter().any(|g| g == name.trim()) {
+                    self.groups.push(name.trim().to_string());
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// The escaped fields written after the value of the link
+    fn fields(&self) -> Vec<String> {
+        let mut fields = vec![];
+        if self.pinned {
+            fields.push("pinned".to_string());
+        }
+        if self.default {
+            fields.push("default".to_string());
+        }
+        if let Some(note) = &self.note {
+            fields.push(format!("note={}", escape_field(note)));
+        }
+        if let Some(date) = &self.expires {
+            fields.push(format!("expires={date}"));
+        }
+        if let Some(link_type) = self.link_type.filter(|t| *t != LinkType::Url) {
+            fields.push(format!("type={link_type}"));
+        }
+        for group in &self.groups {
+            fields.push(format!("group={}", escape_field(group)));
+        }
+        fields
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == LinkMeta::default()
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires.as_deref().is_some_and(expiry::is_expired)
+    }
+}
+
+/// The first line of a data file whose link lines may have metadata and escapes, see
+/// `split_link_line`. Data files without it are read the way they were before links had
+/// metadata, and are still written that way while nothing needs it, see
+/// `Data::state_to_file_string`.
+const FORMAT_HEADER: &str = "# tap-data 2";
+
+/// Whether the data file starting with `first_line` has metadata and escapes, see `FORMAT_HEADER`
+fn has_format_header(first_line: &str) -> bool {
+    first_line.trim_end() == FORMAT_HEADER
+}
+
+/// Splits a link line into its link name, value and metadata, see `LinkMeta`. `None` for lines
+/// without a `|`. Only the link lines of a data file with the `FORMAT_HEADER` are `escaped`,
+/// the others are split on their first `|` and everything after it is the value, as it is.
+fn split_link_line(line: &str, escaped: bool) -> Option<(&str, String, LinkMeta)> {
+    let (link, rest) = line.split_once('|')?;
+    if !escaped {
+        return Some((link, rest.trim().to_string(), LinkMeta::default()));
+    }
+    let mut fields = split_fields(rest).into_iter();
+    let mut value = fields.next().unwrap_or_default().to_string();
+    let mut meta = LinkMeta::default();
+    for field in fields {
+        if !meta.parse_field(&unescape_field(field)) {
+            value.push('|');
+            value.push_str(field);
+        }
+    }
+    Some((link, unescape_field(value.trim()), meta))
+}
+
+/// Parses links written like a data file, `parent->` lines each followed by indented
+/// `link|value` lines, or one `parent|link|value` per line, as `tap --upsert --from` reads them.
+/// Metadata after the value is left out. Every line that can not be parsed is returned with the
+/// reason, so nothing is used unless the whole text is valid.
+pub(crate) fn parse_snippet(
+    text: &str,
+) -> Result<Vec<(String, String, String)>, Vec<RejectedLine>> {
+    let mut links: Vec<(String, String, String)> = vec![];
+    let mut rejected = vec![];
+    let mut parent: Option<String> = None;
+    let escaped = text.lines().next().is_some_and(has_format_header);
+    for (idx, line) in text.lines().enumerate() {
+        if line.trim().is_empty() || is_comment(line) {
+            continue;
+        }
+        let mut reject = |reason: String| {
+            rejected.push(RejectedLine {
+                line_number: idx + 1,
+                line: line.to_string(),
+                reason,
+            })
+        };
+        if let Some(name) = line.trim_end().strip_suffix("->") {
+            parent = validate_parent(name.trim())
+                .map(|_| name.trim().to_string())
+                .map_err(|e| reject(e.message))
+                .ok();
+            continue;
+        }
+        let indented = line.starts_with(char::is_whitespace);
+        let (link_parent, link_line) = match (split_fields(line).len(), &parent) {
+            (1, _) => {
+                reject("Expected parent->, link|value, or parent|link|value".to_string());
+                continue;
+            }
+            (2, None) => {
+                reject("Link does not have a parent, add a parent-> line above it".to_string());
+                continue;
+            }
+            // Unindented lines with more fields are parent|link|value lines
+            (n, Some(parent)) if n == 2 || indented => (parent.clone(), line),
+            _ => {
+                let (link_parent, rest) = line.split_once('|').expect("The line has fields");
+                if let Err(e) = validate_parent(link_parent.trim()) {
+                    reject(e.message);
+                    continue;
+                }
+                (link_parent.trim().to_string(), rest)
+            }
+        };
+        let Some((link, value, _)) = split_link_line(link_line, escaped) else {
+            continue;
+        };
+        let link = link.trim();
+        if let Err(e) = validate_link(link) {
+            reject(e.message);
+            continue;
+        }
+        if link.is_empty() || value.is_empty() {
+            reject("Expected a link name and a value".to_string());
+            continue;
+        }
+        match links
+            .iter()
+            .find(|(p, l, _)| *p == link_parent && l == link)
+        {
+            Some((_, _, v)) if *v == value => {}
+            Some((_, _, v)) => reject(format!(
+                "Link '{link}' of parent entity '{link_parent}' was already given with value '{v}'"
+            )),
+            None => links.push((link_parent, link.to_string(), value)),
+        }
+    }
+    match rejected.is_empty() {
+        true => Ok(links),
+        false => Err(rejected),
+    }
+}
+
+/// Splits the fields of a link line on every `|` that is not escaped as `\|`
+fn split_fields(s: &str) -> Vec<&str> {
+    let mut fields = vec![];
+    let (mut start, mut escaped) = (0, false);
+    for (i, c) in s.char_indices() {
+        match c {
+            '|' if !escaped => {
+                fields.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => escaped = c == '\\' && !escaped,
+        }
+    }
+    fields.push(&s[start..]);
+    fields
+}
+
+/// Escapes a value or metadata field for a link line: `|` as `\|` and line breaks as `\n`. A `\`
+/// is only escaped as `\\` when it would otherwise be read as the start of an escape or ends the
+/// field, so paths like `C:\Users` are written as they are.
+fn escape_field(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '|' => escaped.push_str("\\|"),
+            '\n' => escaped.push_str("\\n"),
+            // The end of the field is followed by the `|` of the next one
+            '\\' if matches!(chars.peek(), Some('\\' | '|' | 'n') | None) => {
+                escaped.push_str("\\\\")
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Reverses `escape_field`, a `\` that does not start an escape is kept as it is
+fn unescape_field(s: &str) -> String {
+    let mut unescaped = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('\\', Some('\\')) => unescaped.push('\\'),
+            ('\\', Some('|')) => unescaped.push('|'),
+            ('\\', Some('n')) => unescaped.push('\n'),
+            (c, _) => {
+                unescaped.push(c);
+                continue;
+            }
+        }
+        chars.next();
+    }
+    unescaped
+}
+
+pub(super) struct Data {
+    path: PathBuf,
+    state: Vec<(String, Vec<LinkValue>)>,
+    comments: Comments,
+    /// The metadata of the links, kept on their lines in the data file
+    meta: Metadata,
+    /// The stamp of the data file as last read or written, to skip saves that change nothing and
+    /// to refuse to overwrite changes made by something else. `None` when the contents are
+    /// unknown, in which case the file is always written.
+    on_disk: Option<FileStamp>,
+    /// Whether the data file was just read whole and found to have the stamp `on_disk`, so the
+    /// next save only has to check its length, see `DataStoreHandle::write`
+    verified: bool,
+    /// When set, the state is never read from or written to `path`
+    in_memory: bool,
+}
+
+// Publicly exposed
+impl Data {
+    pub fn new(
+        path: Option<PathBuf>,
+        index_offset_length: Option<IndexOffsetLength>,
+    ) -> Result<Self, TapDataStoreError> {
+        Data::open(path, index_offset_length, true)
+    }
+
+    /// Loads the data file like `Data::new`. When the file does not exist, it is only created if
+    /// `create_if_missing` is set; otherwise the data is empty and nothing is written to disk.
+    pub fn open(
+        path: Option<PathBuf>,
+        index_offset_length: Option<IndexOffsetLength>,
+        create_if_missing: bool,
+    ) -> Result<Self, TapDataStoreError> {
+        let path = Data::resolve_path(path)?;
+        let file_exists = path.exists();
+        log::verbose(format_args!("Loading data file {}", path.display()));
+
+        // Parse file if it exists
+        if file_exists {
+            let mut f = File::open(&path).map_err(|e| TapDataStoreError {
+                kind: TapDataStoreErrorKind::FileOpenFailed,
+                message: format!("Could not open data file at {}: {e}", path.display()),
+            })?;
+            let mut meta = Metadata::new();
+
+            // If index_offset_length is set, then we are reading from the index file
+            let (mut state, comments, on_disk) = if let Some((offset, length)) = index_offset_length
+            {
+                let mut first_line = String::new();
+                BufReader::new(&mut f)
+                    .read_line(&mut first_line)
+                    .map_err(|e| TapDataStoreError {
+                        kind: TapDataStoreErrorKind::FileReadFailed,
+                        message: format!("Could not read data file at {}: {e}", path.display()),
+                    })?;
+                f.seek(SeekFrom::Start(offset as u64))
+                    .map_err(|e| TapDataStoreError {
+                        kind: TapDataStoreErrorKind::FileSeekFailed,
+                        message: format!(
+                            "Could not seek to offset {offset} in data file at {}: {e}",
+                            path.display()
+                        ),
+                    })?;
+                let mut buf = if length == 0 {
+                    // If length is 0, then we are reading the rest of the file
+                    let metadata = f.metadata().map_err(|e| TapDataStoreError {
+                        kind: TapDataStoreErrorKind::FileReadMetadataFailed,
+                        message: format!(
+                            "Could not read metadata for data file at {}: {e}",
+                            path.display()
+                        ),
+                    })?;
+                    let length = metadata.len() - offset as u64;
+                    vec![0u8; length as usize]
+                } else {
+                    vec![0u8; length]
+                };
+                f.read_exact(&mut buf).map_err(|e| TapDataStoreError {
+                    kind: TapDataStoreErrorKind::FileReadFailed,
+                    message: format!("Could not read data file at {}: {e}", path.display()),
+                })?;
+                // Index offsets are byte offsets, so an offset that is not on a character boundary
+                // of the UTF-8 file means the index is stale
+                let section = String::from_utf8(buf).map_err(|e| TapDataStoreError {
+                    kind: TapDataStoreErrorKind::ParseError,
+                    message: format!(
+                        "Data file at {} is not valid UTF-8 at offset {offset}: {e}",
+                        path.display()
+                    ),
+                })?;
+                // The section is read the way the whole file would be, see `FORMAT_HEADER`
+                let header = has_format_header(&first_line).then_some(FORMAT_HEADER);
+                let lines = header.into_iter().chain(section.lines()).map(Ok);
+                let state = Data::parse_lines(lines, None, None, &mut meta)?;
+                // Comments are only kept when the whole file was read, as only then can it be saved
+                (state, Comments::default(), None)
+            } else {
+                // The whole file is parsed as it is read, it is never held in memory at once
+                let mut reader = BufReader::new(StampingReader::new(f));
+                let mut comments = CommentsParser::default();
+                let lines = Data::lines(&mut reader).inspect(|line| {
+                    if let Ok(line) = line {
+                        comments.line(line);
+                    }
+                });
+                let state = Data::parse_lines(lines, None, None, &mut meta)?;
+                let stamp = reader.into_inner().stamp();
+                (state, comments.finish(), Some(stamp))
+            };
+            Data::sort_state(&mut state);
+            Ok(Self {
+                path,
+                state,
+                comments,
+                meta,
+                on_disk,
+                verified: false,
+                in_memory: false,
+            })
+        } else {
+            if create_if_missing {
+                File::create_new(&path).map_err(|e| TapDataStoreError {
+                    kind: TapDataStoreErrorKind::FileCreateFailed,
+                    message: format!("Could not create data file: {e}"),
+                })?;
+            }
+            Ok(Self {
+                path,
+                state: vec![],
+                comments: Comments::default(),
+                meta: Metadata::new(),
+                on_disk: Some(FileStamp::of("")),
+                verified: false,
+                in_memory: false,
+            })
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn in_memory(mut state: Vec<(String, Vec<LinkValue>)>) -> Self {
+        Data::sort_state(&mut state);
+        Self {
+            path: PathBuf::new(),
+            state,
+            comments: Comments::default(),
+            meta: Metadata::new(),
+            on_disk: None,
+            verified: false,
+            in_memory: true,
+        }
+    }
+
+    /// Reads only the section of `parent` using the offset and length from the index. If the read
+    /// section is not exactly that parent (for example because the index is stale), the whole
+    /// data file is parsed instead so that the links of another parent are never returned.
+    pub fn read_parent_section(
+        path: Option<PathBuf>,
+        index_offset_length: IndexOffsetLength,
+        parent: &str,
+    ) -> Result<Self, TapDataStoreError> {
+        let path = Data::resolve_path(path)?;
+        match Data::open(Some(path.clone()), Some(index_offset_length), false) {
+            Ok(data) if data.state.len() == 1 && data.state[0].0.trim() == parent.trim() => {
+                Ok(data)
+            }
+            _ => Data::read_parent_streaming(Some(path), parent),
+        }
+    }
+
+    /// Reads only the section of `parent` without the index, streaming the data file instead of
+    /// loading it into memory (see `Data::parse_parent`). A missing data file has no parents.
+    pub fn read_parent_streaming(
+        path: Option<PathBuf>,
+        parent: &str,
+    ) -> Result<Self, TapDataStoreError> {
+        let path = Data::resolve_path(path)?;
+        log::verbose(format_args!("Scanning data file {}", path.display()));
+        let mut meta = Metadata::new();
+        let state = if path.exists() {
+            let f = File::open(&path).map_err(|e| TapDataStoreError {
+                kind: TapDataStoreErrorKind::FileOpenFailed,
+                message: format!("Could not open data file at {}: {e}", path.display()),
+            })?;
+            Data::parse_parent(BufReader::new(f), parent, &mut meta)?
+        } else {
+            vec![]
+        };
+        Ok(Self {
+            path,
+            state,
+            comments: Comments::default(),
+            meta,
+            on_disk: None,
+            verified: false,
+            in_memory: false,
+        })
+    }
+
+    /// Loads the data file like `Data::new`, but skips any line that can not be parsed instead of
+    /// failing. The skipped lines are returned alongside the data so they can be reported and
+    /// written to the rejected file with `save_rejected`.
+    pub fn salvage(path: Option<PathBuf>) -> Result<(Self, Vec<RejectedLine>), TapDataStoreError> {
+        let path = Data::resolve_path(path)?;
+        let mut meta = Metadata::new();
+        let (state, rejected, comments) = if path.exists() {
+            let file_as_str = fs::read_to_string(&path).map_err(|e| TapDataStoreError {
+                kind: TapDataStoreErrorKind::FileReadFailed,
+                message: format!("Could not read data file at {}: {e}", path.display()),
+            })?;
+            let (mut state, rejected) = Data::parse_file_lenient(&file_as_str, &mut meta);
+            Data::sort_state(&mut state);
+            (state, rejected, Comments::parse(&file_as_str))
+        } else {
+            (vec![], vec![], Comments::default())
+        };
+        Ok((
+            Self {
+                path,
+                state,
+                comments,
+                meta,
+                on_disk: None,
+                verified: false,
+                in_memory: false,
+            },
+            rejected,
+        ))
+    }
+
+    /// Loads the data file for `tap --compact`, counting what parsing cleaned up. Unparseable lines
+    /// are an error unless `salvage` is set, in which case they are skipped and returned in the
+    /// summary so they can be written with `save_rejected`.
+    pub fn compact(
+        path: Option<PathBuf>,
+        salvage: bool,
+    ) -> Result<(Self, CompactSummary), TapDataStoreError> {
+        let path = Data::resolve_path(path)?;
+        let file_as_str = if path.exists() {
+            fs::read_to_string(&path).map_err(|e| TapDataStoreError {
+                kind: TapDataStoreErrorKind::FileReadFailed,
+                message: format!("Could not read data file at {}: {e}", path.display()),
+            })?
+        } else {
+            String::new()
+        };
+        let mut meta = Metadata::new();
+        let (mut state, summary) = Data::compact_file_str(&file_as_str, salvage, &mut meta)?;
+        Data::sort_state(&mut state);
+        let data = Self {
+            path,
+            state,
+            comments: Comments::parse(&file_as_str),
+            meta,
+            on_disk: None,
+            verified: false,
+            in_memory: false,
+        };
+        let summary = CompactSummary {
+            bytes_after: data.state_to_file_string().0.len(),
+            ..summary
+        };
+        Ok((data, summary))
+    }
+
+    /// Compacts the state of an in memory data store, see `Data::compact`
+    pub fn compact_in_memory(&mut self) -> Result<CompactSummary, TapDataStoreError> {
+        let (file_as_str, _) = self.state_to_file_string();
+        let mut meta = Metadata::new();
+        let (mut state, summary) = Data::compact_file_str(&file_as_str, false, &mut meta)?;
+        Data::sort_state(&mut state);
+        self.state = state;
+        self.meta = meta;
+        Ok(CompactSummary {
+            bytes_after: self.state_to_file_string().0.len(),
+            ..summary
+        })
+    }
+
+    /// The file that `save_rejected` appends unparseable lines to, e.g. `.tap_data.rejected`
+    pub fn rejected_path(&self) -> PathBuf {
+        let mut file_name = self.path.file_name().unwrap_or_default().to_os_string();
+        file_name.push(".rejected");
+        self.path.with_file_name(file_name)
+    }
+
+    /// Appends the rejected lines to the rejected file so that nothing is silently lost
+    pub fn save_rejected(&self, rejected: &[RejectedLine]) -> Result<(), TapDataStoreError> {
+        if self.in_memory || rejected.is_empty() {
+            return Ok(());
+        }
+        let path = self.rejected_path();
+        let mut f = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| TapDataStoreError {
+                kind: TapDataStoreErrorKind::FileOpenFailed,
+                message: format!("Could not open rejected file at {}: {e}", path.display()),
+            })?;
+        let lines: String = rejected.iter().map(|r| format!("{}\n", r.line)).collect();
+        f.write_all(lines.as_bytes())
+            .map_err(|e| TapDataStoreError {
+                kind: TapDataStoreErrorKind::FileWriteFailed,
+                message: format!("Could not write rejected file at {}: {e}", path.display()),
+            })
+    }
+
+    pub fn add_link(
+        &mut self,
+        parent: &str,
+        link: &str,
+        value: &str,
+    ) -> Result<(), TapDataStoreError> {
+        validate_parent(parent)?;
+        validate_link(link)?;
+        match self.parent_position(parent) {
+            Ok(parent_idx) => {
+                let links = &mut self.state[parent_idx].1;
+                match Data::link_position(links, link) {
+                    Ok(_) => {
+                        return Err(TapDataStoreError {
+                            kind: TapDataStoreErrorKind::LinkAlreadyExists,
+                            message: format!("Link {link} already exists for parent {parent}"),
+                        });
+                    }
+                    Err(link_idx) => links.insert(
+                        link_idx,
+                        (link.trim().to_string(), value.trim().to_string()),
+                    ),
+                }
+            }
+            Err(parent_idx) => self.state.insert(
+                parent_idx,
+                (
+                    parent.to_string(),
+                    vec![(link.trim().to_string(), value.trim().to_string())],
+                ),
+            ),
+        }
+        Ok(())
+    }
+
+    /// Adds `parent` with its links and their metadata, failing like `Data::add_link` when a link
+    /// already exists
+    pub fn add_parent(
+        &mut self,
+        parent: &str,
+        links: &[LinkValue],
+        meta: BTreeMap<String, LinkMeta>,
+    ) -> Result<(), TapDataStoreError> {
+        for (link, value) in links {
+            self.add_link(parent, link, value)?;
+        }
+        for (link, meta) in meta {
+            self.set_meta(parent, &link, meta)?;
+        }
+        Ok(())
+    }
+
+    pub fn get(
+        &self,
+        parent: &str,
+        link: Option<&str>,
+    ) -> Result<Vec<LinkValue>, TapDataStoreError> {
+        validate_parent(parent)?;
+        let links = self
+            .parent_position(parent)
+            .map(|parent_idx| &self.state[parent_idx].1)
+            .map_err(|_| TapDataStoreError {
+                kind: TapDataStoreErrorKind::ParentEntityNotFound,
+                message: format!("Parent '{parent}' not found"),
+            })?;
+        if let Some(link) = link {
+            validate_link(link)?;
+            let link = link.trim();
+            if let Ok(link_idx) = Data::link_position(links, link) {
+                return Ok(vec![links[link_idx].clone()]);
+            } else {
+                return Err(TapDataStoreError {
+                    kind: TapDataStoreErrorKind::LinkNotFound,
+                    message: format!("Link '{link}' not found in parent '{parent}'"),
+                });
+            }
+        }
+        Ok(links.clone())
+    }
+
+    /// The metadata of the links of `parent` that have any, by link name
+    pub fn meta_of_parent(&self, parent: &str) -> BTreeMap<String, LinkMeta> {
+        self.meta
+            .iter()
+            .filter(|((p, _), _)| p == parent.trim())
+            .map(|((_, link), meta)| (link.clone(), meta.clone()))
+            .collect()
+    }
+
+    /// The metadata of a link, failing like `Data::get` when the link does not exist
+    pub fn get_meta(&self, parent: &str, link: &str) -> Result<LinkMeta, TapDataStoreError> {
+        self.get(parent, Some(link))?;
+        Ok(self
+            .meta
+            .get(&(parent.trim().to_string(), link.trim().to_string()))
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    /// Replaces the metadata of a link, failing like `Data::get` when the link does not exist
+    pub fn set_meta(
+        &mut self,
+        parent: &str,
+        link: &str,
+        meta: LinkMeta,
+    ) -> Result<(), TapDataStoreError> {
+        self.get(parent, Some(link))?;
+        let key = (parent.trim().to_string(), link.trim().to_string());
+        match meta.is_empty() {
+            true => self.meta.remove(&key),
+            false => self.meta.insert(key, meta),
+        };
+        Ok(())
+    }
+
+    pub fn remove(&mut self, parent: &str, link: Option<&str>) -> Result<(), TapDataStoreError> {
+        validate_parent(parent)?;
+        if let Some(link) = link {
+            validate_link(link)?;
+        }
+        if let Ok(parent_idx) = self.parent_position(parent) {
+            let (_, links) = &mut self.state[parent_idx];
+            // If there is a link to remove, find and remove. Otherwise, remove parent
+            if let Some(link) = link {
+                if let Ok(index) = Data::link_position(links, link) {
+                    links.remove(index);
+                    self.meta
+                        .remove(&(parent.trim().to_string(), link.trim().to_string()));
+                    // If no links left, remove parent as well
+                    if links.is_empty() {
+                        self.state.remove(parent_idx);
+                    }
+                } else {
+                    return Err(TapDataStoreError {
+                        kind: TapDataStoreErrorKind::LinkNotFound,
+                        message: format!("Link '{link}' not found in parent '{parent}'"),
+                    });
+                }
+            } else {
+                self.state.remove(parent_idx);
+                self.meta.retain(|(p, _), _| p != parent.trim());
+            }
+        } else {
+            return Err(TapDataStoreError {
+                kind: TapDataStoreErrorKind::ParentEntityNotFound,
+                message: format!("Parent '{parent}' not found"),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn upsert_link(
+        &mut self,
+        parent: &str,
+        link: &str,
+        value: &str,
+    ) -> Result<(), TapDataStoreError> {
+        validate_parent(parent)?;
+        validate_link(link)?;
+        match self.parent_position(parent) {
+            Ok(parent_idx) => {
+                let links = &mut self.state[parent_idx].1;
+                // If link already exists, update, else add
+                match Data::link_position(links, link) {
+                    Ok(link_idx) => links[link_idx].1 = value.trim().to_string(),
+                    Err(link_idx) => links.insert(
+                        link_idx,
+                        (link.trim().to_string(), value.trim().to_string()),
+                    ),
+                }
+            }
+            // If parent does not exist, add parent and new link/value pair
+            Err(parent_idx) => self.state.insert(
+                parent_idx,
+                (
+                    parent.to_string(),
+                    vec![(link.trim().to_string(), value.trim().to_string())],
+                ),
+            ),
+        }
+        Ok(())
+    }
+
+    pub fn import(
+        &mut self,
+        file_type: ImportType,
+        path: PathBuf,
+    ) -> Result<(), TapDataStoreError> {
+        let (state, mut meta) = read_import_file(&file_type, &path)?;
+        // TODO: refactor to hashmap? This would speed up import
+        for (parent, links) in state.iter() {
+            for (link, value) in links.iter() {
+                self.upsert_link(parent, link, value)?;
+            }
+        }
+        self.meta.append(&mut meta);
+        Ok(())
+    }
+}
+
+/// The links and metadata of the file at `path`, read the way `tap --import` reads them, so
+/// `tap --diff` shows what an import would change
+pub(crate) fn read_import_file(
+    file_type: &ImportType,
+    path: &Path,
+) -> Result<(Vec<ParentLinks>, Metadata), TapDataStoreError> {
+    validate_path(file_type, path)?;
+    match file_type {
+        ImportType::Tap => read_tap_file(path),
+    }
+}
+
+/// Reads the links of a file written like a data file, whatever its extension, as `tap --diff`
+/// compares them with the data file
+pub(crate) fn read_tap_file(
+    path: &Path,
+) -> Result<(Vec<ParentLinks>, Metadata), TapDataStoreError> {
+    let file_exists = path.try_exists().map_err(|e| TapDataStoreError {
+        kind: TapDataStoreErrorKind::FileOpenFailed,
+        message: format!("Unable to determine if file {} exists: {e}", path.display()),
+    })?;
+    if !file_exists {
+        return Err(TapDataStoreError {
+            kind: TapDataStoreErrorKind::FileOpenFailed,
+            message: format!("File {} does not exist", path.display()),
+        });
+    }
+    let file_as_str = fs::read_to_string(path).map_err(|e| TapDataStoreError {
+        kind: TapDataStoreErrorKind::FileReadFailed,
+        message: format!("Could not read data file at {}: {e}", path.display()),
+    })?;
+    let mut meta = Metadata::new();
+    let state = Data::parse_lines(file_as_str.lines().map(Ok), None, None, &mut meta)?;
+    Ok((state, meta))
+}
+
+#[cfg(test)]
+mod data_public {
+    use super::{Data, FileType, ImportType, TapDataStoreErrorKind, get_test_file_path};
+    use std::fs;
+    use std::path::PathBuf;
+
+    // TODO: GH-45 Move the following to an integration test
+    #[test]
+    #[ignore = "GH-45: Should really be an integration test - move this out"]
+    fn test_new_no_path_correct_file_name() {
+        let expected_file_name = ".tap_data";
+        let mut data = Data::new(None, None).unwrap();
+        assert!(data.path.to_str().unwrap().ends_with(expected_file_name));
+        data.cleanup().expect("Could not clean up data store");
+    }
+
+    #[test]
+    fn test_new_with_path_correct_file_name() {
+        let expected_file_name =
+            get_test_file_path(FileType::Data).expect("Could not get test file path");
+        let mut data = Data::new(Some(PathBuf::from(&expected_file_name)), None).unwrap();
+        assert_eq!(data.path, expected_file_name);
+        data.cleanup().expect("Could not clean up data store");
+    }
+
+    #[test]
+    fn test_set_state_correct() {
+        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
+        fs::write(
+            &data_path,
+            "parent1->\nlink1|value1\nlink2|value2\nparent2 ->\nlink3|value3\nlink4|value4",
+        )
+        .unwrap();
+        let mut data = Data::new(Some(data_path), None).unwrap();
+        assert_eq!(
+            data.state,
+            vec![
+                (
+                    "parent1".to_string(),
+                    vec![
+                        ("link1".to_string(), "value1".to_string()),
+                        ("link2".to_string(), "value2".to_string())
+                    ]
+                ),
+                (
+                    "parent2 ".to_string(),
+                    vec![
+                        ("link3".to_string(), "value3".to_string()),
+                        ("link4".to_string(), "value4".to_string())
+                    ]
+                ),
+            ]
+        );
+        data.cleanup().expect("Could not clean up data store");
+    }
+
+    #[test]
+    fn test_set_state_correct_reader_eof() {
+        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
+        fs::write(
+            &data_path,
+            "parent1->\nlink1|value1\nlink2|value2\nparent2 ->\nlink3|value3\nlink4|value4",
+        )
+        .unwrap();
+        let mut data = Data::new(Some(data_path), Some((36, 0))).unwrap();
+        assert_eq!(
+            data.state,
+            vec![(
+                "parent2 ".to_string(),
+                vec![
+                    ("link3".to_string(), "value3".to_string()),
+                    ("link4".to_string(), "value4".to_string())
+                ]
+            ),]
+        );
+        data.cleanup().expect("Could not clean up data store");
+    }
+
+    #[test]
+    fn test_set_state_correct_reader() {
+        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
+        fs::write(
+            &data_path,
+            "parent1->\nlink1|value1\nlink2|value2\nparent2 ->\nlink3|value3\nlink4|value4",
+        )
+        .unwrap();
+        let mut data = Data::new(Some(data_path), Some((0, 35))).unwrap();
+        assert_eq!(
+            data.state,
+            vec![(
+                "parent1".to_string(),
+                vec![
+                    ("link1".to_string(), "value1".to_string()),
+                    ("link2".to_string(), "value2".to_string())
+                ]
+            ),]
+        );
+        data.cleanup().expect("Could not clean up data store");
+    }
+
+    #[test]
+    fn test_add_link_when_parent_doesnt_exist() {
+        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
+        let mut data = Data::new(Some(data_path), None).unwrap();
+        let res = data.add_link("parent1", "link1", "value1");
+        assert!(res.is_ok());
+        assert_eq!(
+            data.state,
+            vec![(
+                "parent1".to_string(),
+                vec![("link1".to_string(), "value1".to_string())]
+            )]
+        );
+        data.cleanup().expect("Could not clean up data store");
+    }
+
+    #[test]
+    fn test_add_link_when_link_doesnt_exist() {
+        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
+        let mut data = Data::new(Some(data_path), None).unwrap();
+        data.state = vec![(
+            "search-engines".to_string(),
+            vec![
+                ("google".to_string(), "www.google.com".to_string()),
+                ("yahoo".to_string(), "www.yahoo.com".to_string()),
+            ],
+        )];
+        let res = data.add_link("search-engines", "link1", "value1");
+        assert!(res.is_ok());
+        assert_eq!(
+            data.state,
+            vec![(
+                "search-engines".to_string(),
+                vec![
+                    ("google".to_string(), "www.google.com".to_string()),
+                    ("link1".to_string(), "value1".to_string()),
+                    ("yahoo".to_string(), "www.yahoo.com".to_string())
+                ]
+            )]
+        );
+        data.cleanup().expect("Could not clean up data store");
+    }
+
+    #[test]
+    fn test_add_link_when_link_already_exists() {
+        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
+        let mut data = Data::new(Some(data_path), None).unwrap();
+        data.state = vec![(
+            "search-engines".to_string(),
+            vec![("google".to_string(), "www.google.com".to_string())],
+        )];
+        let res = data.add_link("search-engines", "google", "something else");
+        assert_eq!(
+            res.unwrap_err().kind,
+            TapDataStoreErrorKind::LinkAlreadyExists
+        );
+        assert_eq!(
+            data.state,
+            vec![(
+                "search-engines".to_string(),
+                vec![("google".to_string(), "www.google.com".to_string()),]
+            )]
+        );
+        data.cleanup().expect("Could not clean up data store");
+    }
+
+    #[test]
+    fn test_get_parent_when_parent_exists() {
+        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
+        let mut data = Data::new(Some(data_path), None).unwrap();
+        data.state = vec![(
+            "search-engines".to_string(),
+            vec![
+                ("google".to_string(), "www.google.com".to_string()),
+                ("yahoo".to_string(), "www.yahoo.com".to_string()),
+            ],
+        )];
+        let res = data.get("search-engines", None);
+        assert!(res.is_ok());
+        assert_eq!(
+            res.unwrap(),
+            vec![
+                ("google".to_string(), "www.google.com".to_string()),
+                ("yahoo".to_string(), "www.yahoo.com".to_string()),
+            ]
+        );
+        data.cleanup().expect("Could not clean up data store");
+    }
+
+    #[test]
+    fn test_get_parent_when_parent_does_not_exist() {
+        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
+        let mut data = Data::new(Some(data_path), None).unwrap();
+        let res = data.get("search-engines", None);
+        assert_eq!(
+            res.unwrap_err().kind,
+            TapDataStoreErrorKind::ParentEntityNotFound
+        );
+        assert_eq!(data.state, vec![]);
+        data.cleanup().expect("Could not clean up data store");
+    }
+
+    #[test]
+    fn test_get_parent_and_link_when_parent_exists_and_link_exists() {
+        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
+        let mut data = Data::new(Some(data_path), None).unwrap();
+        data.state = vec![(
+            "search-engines".to_string(),
+            vec![
+                ("google".to_string(), "www.google.com".to_string()),
+                ("yahoo".to_string(), "www.yahoo.com".to_string()),
+            ],
+        )];
+        let res = data.get("search-engines", Some("google"));
+        assert!(res.is_ok());
+        assert_eq!(
+            res.unwrap(),
+            vec![("google".to_string(), "www.google.com".to_string())]
+        );
+        data.cleanup().expect("Could not clean up data store");
+    }
+
+    #[test]
+    fn test_get_parent_and_link_when_parent_exists_and_link_does_not_exist() {
+        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
+        let mut data = Data::new(Some(data_path), None).unwrap();
+        data.state = vec![(
+            "search-engines".to_string(),
+            vec![
+                ("google".to_string(), "www.google.com".to_string()),
+                ("yahoo".to_string(), "www.yahoo.com".to_string()),
+            ],
+        )];
+        let res = data.get("search-engines", Some("link1"));
+        assert_eq!(res.unwrap_err().kind, TapDataStoreErrorKind::LinkNotFound);
+        assert_eq!(
+            data.state,
+            vec![(
+                "search-engines".to_string(),
+                vec![
+                    ("google".to_string(), "www.google.com".to_string()),
+                    ("yahoo".to_string(), "www.yahoo.com".to_string()),
+                ],
+            )]
+        );
+        data.cleanup().expect("Could not clean up data store");
+    }
+
+    #[test]
+    fn test_import_tap_bad_file() {
+        let import_path = "rando_file.pdf";
+        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
+        let mut data = Data::new(Some(data_path), None).unwrap();
+        let res = data.import(ImportType::Tap, PathBuf::from(import_path));
+        assert_eq!(
+            res.unwrap_err().kind,
+            TapDataStoreErrorKind::InvalidFileExtension
+        );
+        data.cleanup().expect("Could not clean up data store");
+    }
+
+    #[test]
+    fn test_import_tap_data_parent_exists_but_link_does_not() {
+        let import_path = get_test_file_path(FileType::Tap).expect("Could not get test file path");
+        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
+        let mut data = Data::new(Some(data_path), None).unwrap();
+        data.state = vec![(
+            "search-engines".to_string(),
+            vec![("google".to_string(), "www.google.com".to_string())],
+        )];
+        fs::write(&import_path, "search-engines->\nyahoo|www.yahoo.com\n").unwrap();
+
+        let res = data.import(ImportType::Tap, import_path.clone());
+        assert!(res.is_ok());
+        assert_eq!(
+            data.state,
+            vec![(
+                "search-engines".to_string(),
+                vec![
+                    ("google".to_string(), "www.google.com".to_string()),
+                    ("yahoo".to_string(), "www.yahoo.com".to_string())
+                ]
+            ),]
+        );
+        data.cleanup().expect("Could not clean up data store");
+        fs::remove_file(import_path).expect("Could not remove import file");
+    }
+
+    #[test]
+    fn test_import_tap_data_parent_does_not_exist() {
+        let import_path = get_test_file_path(FileType::Tap).expect("Could not get test file path");
+        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
+        let mut data = Data::new(Some(data_path), None).unwrap();
+        data.state = vec![(
+            "search-engines".to_string(),
+            vec![("google".to_string(), "www.google.com".to_string())],
+        )];
+        fs::write(&import_path, "repo->\ngh|www.github.com\n").unwrap();
+
+        let res = data.import(ImportType::Tap, import_path.clone());
+        assert!(res.is_ok());
+        assert_eq!(
+            data.state,
+            vec![
+                (
+                    "repo".to_string(),
+                    vec![("gh".to_string(), "www.github.com".to_string()),]
+                ),
+                (
+                    "search-engines".to_string(),
+                    vec![("google".to_string(), "www.google.com".to_string()),]
+                ),
+            ]
+        );
+        data.cleanup().expect("Could not clean up data store");
+        fs::remove_file(import_path).expect("Could not remove import file");
+    }
+
+    #[test]
+    fn test_import_tap_data_parent_exists_and_link_exists() {
+        let import_path = get_test_file_path(FileType::Tap).expect("Could not get test file path");
+        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
+        let mut data = Data::new(Some(data_path), None).unwrap();
+        data.state = vec![(
+            "search-engines".to_string(),
+            vec![("google".to_string(), "www.google.com".to_string())],
+        )];
+        fs::write(&import_path, "search-engines->\ngoogle|abc\n").unwrap();
+
+        let res = data.import(ImportType::Tap, import_path.clone());
+        // TODO: currently the link is overwritten, make this a param instead?
+        assert!(res.is_ok());
+        assert_eq!(
+            data.state,
+            vec![(
+                "search-engines".to_string(),
+                vec![("google".to_string(), "abc".to_string()),]
+            ),]
+        );
+        data.cleanup().expect("Could not clean up data store");
+        fs::remove_file(import_path).expect("Could not remove import file");
+    }
+
+    #[test]
+    fn test_remove_parent_when_parent_exists() {
+        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
+        let mut data = Data::new(Some(data_path), None).unwrap();
+        data.state = vec![(
+            "search-engines".to_string(),
+            vec![("google".to_string(), "www.google.com".to_string())],
+        )];
+        let res = data.remove("search-engines", None);
+        assert!(res.is_ok());
+        assert_eq!(data.state, vec![]);
+        data.cleanup().expect("Could not clean up data store");
+    }
+
+    #[test]
+    fn test_remove_parent_when_parent_does_not_exists() {
+        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
+        let mut data = Data::new(Some(data_path), None).unwrap();
+        let res = data.remove("search-engines", None);
+        assert_eq!(
+            res.unwrap_err().kind,
+            TapDataStoreErrorKind::ParentEntityNotFound
+        );
+        assert_eq!(data.state, vec![]);
+        data.cleanup().expect("Could not clean up data store");
+    }
+
+    #[test]
+    fn test_remove_parent_and_link_when_parent_exists_and_link_exists() {
+        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
+        let mut data = Data::new(Some(data_path), None).unwrap();
+        data.state = vec![(
+            "search-engines".to_string(),
+            vec![("google".to_string(), "www.google.com".to_string())],
+        )];
+        let res = data.remove("search-engines", Some("google"));
+        assert!(res.is_ok());
+        assert_eq!(data.state, vec![]);
+        data.cleanup().expect("Could not clean up data store");
+    }
+
+    #[test]
+    fn test_remove_link_when_parent_exists_and_link_exists() {
+        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
+        let mut data = Data::new(Some(data_path), None).unwrap();
+        data.state = vec![(
+            "search-engines".to_string(),
+            vec![
+                ("google".to_string(), "www.google.com".to_string()),
+                ("yahoo".to_string(), "www.yahoo.com".to_string()),
+            ],
+        )];
+        let res = data.remove("search-engines", Some("google"));
+        assert!(res.is_ok());
+        assert_eq!(
+            data.state,
+            vec![(
+                "search-engines".to_string(),
+                vec![("yahoo".to_string(), "www.yahoo.com".to_string())],
+            ),]
+        );
+        data.cleanup().expect("Could not clean up data store");
+    }
+
+    #[test]
+    fn test_remove_link_when_parent_does_not_exist_and_link_exists_in_other_parent() {
+        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
+        let mut data = Data::new(Some(data_path), None).unwrap();
+        data.state = vec![(
+            "search-engines".to_string(),
+            vec![("google".to_string(), "www.google.com".to_string())],
+        )];
+        let res = data.remove("search-engines2", Some("google"));
+        assert_eq!(
+            res.unwrap_err().kind,
+            TapDataStoreErrorKind::ParentEntityNotFound
+        );
+        assert_eq!(
+            data.state,
+            vec![(
+                "search-engines".to_string(),
+                vec![("google".to_string(), "www.google.com".to_string())],
+            )]
+        );
+        data.cleanup().expect("Could not clean up data store");
+    }
+
+    #[test]
+    fn test_upsert_link_when_link_does_not_exists() {
+        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
+        let mut data = Data::new(Some(data_path), None).unwrap();
+        data.state = vec![(
+            "search-engines".to_string(),
+            vec![("google".to_string(), "www.google.com".to_string())],
+        )];
+        let res = data.upsert_link("search-engines", "yahoo", "www.yahoo.com");
+        assert!(res.is_ok());
+        assert_eq!(
+            data.state,
+            vec![(
+                "search-engines".to_string(),
+                vec![
+                    ("google".to_string(), "www.google.com".to_string()),
+                    ("yahoo".to_string(), "www.yahoo.com".to_string())
+                ]
+            )]
+        );
+        data.cleanup().expect("Could not clean up data store");
+    }
+
+    #[test]
+    fn test_upsert_link_when_link_already_exists() {
+        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
+        let mut data = Data::new(Some(data_path), None).unwrap();
+        data.state = vec![(
+            "search-engines".to_string(),
+            vec![("google".to_string(), "www.google.com".to_string())],
+        )];
+        let res = data.upsert_link("search-engines", "google", "something else");
+        assert!(res.is_ok());
+        assert_eq!(
+            data.state,
+            vec![(
+                "search-engines".to_string(),
+                vec![("google".to_string(), "something else".to_string()),]
+            )]
+        );
+        data.cleanup().expect("Could not clean up data store");
+    }
+
+    #[test]
+    fn test_upsert_link_when_no_parent() {
+        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
+        let mut data = Data::new(Some(data_path), None).unwrap();
+        data.state = vec![(
+            "search-engines".to_string(),
+            vec![("google".to_string(), "www.google.com".to_string())],
+        )];
+        let res = data.upsert_link(
+            "a-different-parent",
+            "google",
+            "the same link name should not matter for different parent",
+        );
+        assert!(res.is_ok());
+        assert_eq!(
+            data.state,
+            vec![
+                (
+                    "a-different-parent".to_string(),
+                    vec![(
+                        "google".to_string(),
+                        "the same link name should not matter for different parent".to_string()
+                    ),]
+                ),
+                (
+                    "search-engines".to_string(),
+                    vec![("google".to_string(), "www.google.com".to_string()),]
+                )
+            ]
+        );
+        data.cleanup().expect("Could not clean up data store");
+    }
+}
+
+// Private
+impl Data {
+    /// Returns the given path, or the data file of the default options when none is given, see
+    /// `StoreOptions::paths`
+    fn resolve_path(path: Option<PathBuf>) -> Result<PathBuf, TapDataStoreError> {
+        match path {
+            Some(path) => Ok(path),
+            None => Ok(StoreOptions::default().paths()?.0),
+        }
+    }
+
+    /// Sorts the state by parent, then by link. Every mutation keeps the state sorted, so this is
+    /// only needed for state that was just parsed from a file.
+    fn sort_state(state: &mut [ParentLinks]) {
+        state.sort_by(|a, b| a.0.trim().cmp(b.0.trim()));
+        state.iter_mut().for_each(|(_, links)| {
+            links.sort_by(|a, b| a.0.trim().cmp(b.0.trim()));
+        });
+    }
+
+    fn is_sorted(state: &[ParentLinks]) -> bool {
+        state.is_sorted_by(|a, b| a.0.trim() <= b.0.trim())
+            && state
+                .iter()
+                .all(|(_, links)| links.is_sorted_by(|a, b| a.0.trim() <= b.0.trim()))
+    }
+
+    /// Binary searches the sorted state for `parent`, see `slice::binary_search_by`
+    fn parent_position(&self, parent: &str) -> Result<usize, usize> {
+        self.state
+            .binary_search_by(|(p, _)| p.trim().cmp(parent.trim()))
+    }
+
+    /// Binary searches the sorted links of a parent for `link`, see `slice::binary_search_by`
+    fn link_position(links: &[LinkValue], link: &str) -> Result<usize, usize> {
+        links.binary_search_by(|(l, _)| l.trim().cmp(link.trim()))
+    }
+
+    /// Parses a data file line by line, leaving out the metadata of its links
+    fn parse_file(
+        reader: impl BufRead,
+    ) -> Result<Vec<(String, Vec<LinkValue>)>, TapDataStoreError> {
+        Data::parse_lines(Data::lines(reader), None, None, &mut Metadata::new())
+    }
+
+    /// The lines of a data file, read one at a time
+    fn lines(reader: impl BufRead) -> impl Iterator<Item = Result<String, TapDataStoreError>> {
+        reader.lines().map(|line| {
+            line.map_err(|e| TapDataStoreError {
+                kind: TapDataStoreErrorKind::FileReadFailed,
+                message: format!("Could not read data file: {e}"),
+            })
+        })
+    }
+
+    /// Parses a data file, skipping (and returning) every line that can not be parsed
+    fn parse_file_lenient(
+        file_as_str: &str,
+        meta: &mut Metadata,
+    ) -> (Vec<(String, Vec<LinkValue>)>, Vec<RejectedLine>) {
+        let mut rejected = vec![];
+        let state = Data::parse_lines(file_as_str.lines().map(Ok), Some(&mut rejected), None, meta)
+            .expect("Lenient parsing rejects lines instead of failing");
+        (state, rejected)
+    }
+
+    /// Parses only the section of `parent`, reading the data file line by line and stopping as
+    /// soon as the section ends. The lines of other parents are still checked for errors, but are
+    /// never kept in memory.
+    fn parse_parent(
+        reader: impl BufRead,
+        parent: &str,
+        meta: &mut Metadata,
+    ) -> Result<Vec<(String, Vec<LinkValue>)>, TapDataStoreError> {
+        Data::parse_lines(Data::lines(reader), None, Some(parent), meta)
+    }
+
+    /// Parses a data file like `Data::parse_file` (or `Data::parse_file_lenient` when salvaging),
+    /// comparing the lines of the file against the parsed state to count what parsing dropped.
+    /// The `bytes_after` of the returned summary is left for the caller to fill in.
+    fn compact_file_str(
+        file_as_str: &str,
+        salvage: bool,
+        meta: &mut Metadata,
+    ) -> Result<(Vec<ParentLinks>, CompactSummary), TapDataStoreError> {
+        let (state, rejected) = if salvage {
+            Data::parse_file_lenient(file_as_str, meta)
+        } else {
+            let lines = file_as_str.lines().map(Ok);
+            (Data::parse_lines(lines, None, None, meta)?, vec![])
+        };
+
+        let mut parent_sections: Vec<String> = vec![];
+        let mut link_lines = 0;
+        for (idx, line) in file_as_str.lines().enumerate() {
+            if is_comment(line) || rejected.iter().any(|r| r.line_number == idx + 1) {
+                continue;
+            }
+            if line.ends_with("->") {
+                parent_sections.push(line.trim_end_matches("->").trim().to_string());
+            } else if line.contains('|') {
+                link_lines += 1;
+            }
+        }
+
+        let links_kept: usize = state.iter().map(|(_, links)| links.len()).sum();
+        let mut parents_seen: Vec<&str> = vec![];
+        let mut summary = CompactSummary {
+            duplicates_removed: link_lines - links_kept,
+            bytes_before: file_as_str.len(),
+            rejected,
+            ..CompactSummary::default()
+        };
+        for parent in parent_sections.iter() {
+            if parents_seen.contains(&parent.as_str()) {
+                // Only count each parent once, whether it is kept or dropped
+                if state.iter().any(|(p, _)| p.trim() == parent) {
+                    summary.parents_merged += 1;
+                }
+                continue;
+            }
+            parents_seen.push(parent);
+            if !state.iter().any(|(p, _)| p.trim() == parent) {
+                summary.empty_parents_removed += 1;
+            }
+        }
+        Ok((state, summary))
+    }
+
+    /// Parses the lines of a data file. When `rejected` is `None`, the first unparseable line is an
+    /// error. Otherwise, unparseable lines are pushed to `rejected` and parsing continues. When
+    /// `only_parent` is set, only that parent is kept and parsing stops once its section ends.
+    /// The metadata of the kept links is added to `meta`.
+    fn parse_lines<L: AsRef<str>>(
+        lines: impl Iterator<Item = Result<L, TapDataStoreError>>,
+        mut rejected: Option<&mut Vec<RejectedLine>>,
+        only_parent: Option<&str>,
+        meta: &mut Metadata,
+    ) -> Result<Vec<(String, Vec<LinkValue>)>, TapDataStoreError> {
+        fn no_parent_error(parent: &str, links: &[LinkValue]) -> Result<(), TapDataStoreError> {
+            if !links.is_empty() && parent.is_empty() {
+                return Err(TapDataStoreError {
+                    kind: TapDataStoreErrorKind::ParseError,
+                    message: format!(
+                        "Links in a data file must have a parent. The following links do not have a parent: {}",
+                        links
+                            .iter()
+                            .map(|(l, _)| l.to_string())
+                            .collect::<Vec<String>>()
+                            .join(", ")
+                    ),
+                });
+            }
+            Ok(())
+        }
+
+        fn update_state_reset_temps(
+            parent: &mut str,
+            links: &mut Vec<(String, String)>,
+            state: &mut Vec<(String, Vec<(String, String)>)>,
+        ) {
+            if !parent.is_empty() && !links.is_empty() {
+                // A parent repeated later in the file is merged into its first section
+                match state.iter_mut().find(|(p, _)| p.trim() == parent.trim()) {
+                    Some((_, existing)) => existing.append(links),
+                    None => {
+                        state.push((parent.to_string(), links.clone()));
+                        links.clear();
+                    }
+                }
+            }
+        }
+
+        /// Finds the value already parsed for a link of the parent, looking at both the parent's
+        /// current section and any earlier section of the same parent
+        fn existing_value<'a>(
+            parent: &str,
+            link: &str,
+            links: &'a [LinkValue],
+            state: &'a [(String, Vec<LinkValue>)],
+        ) -> Option<&'a str> {
+            links
+                .iter()
+                .chain(
+                    state
+                        .iter()
+                        .filter(|(p, _)| p.trim() == parent.trim())
+                        .flat_map(|(_, l)| l.iter()),
+                )
+                .find(|(l, _)| l == link)
+                .map(|(_, v)| v.as_str())
+        }
+
+        /// Records the line as rejected when parsing leniently, otherwise returns the error
+        fn reject(
+            rejected: &mut Option<&mut Vec<RejectedLine>>,
+            line_number: usize,
+            line: &str,
+            e: TapDataStoreError,
+        ) -> Result<(), TapDataStoreError> {
+            match rejected {
+                Some(rejected) => {
+                    rejected.push(RejectedLine {
+                        line_number,
+                        line: line.to_string(),
+                        reason: e.message,
+                    });
+                    Ok(())
+                }
+                None => Err(e),
+            }
+        }
+
+        let mut state = vec![];
+        let mut temp_parent = String::new();
+        let mut temp_links: Vec<(String, String)> = vec![];
+        let mut escaped = false;
+        for (idx, line) in lines.enumerate() {
+            let line = line?;
+            let line = line.as_ref();
+            let line_number = idx + 1;
+            if idx == 0 && has_format_header(line) {
+                escaped = true;
+                continue;
+            }
+            if is_comment(line) {
+                // Comment lines are kept separately by `Comments::parse`
+                continue;
+            } else if line.ends_with("->") {
+                // This is a parent line
+                if only_parent.is_some_and(|p| p.trim() == temp_parent.trim()) {
+                    // The section of the only parent we are interested in just ended
+                    break;
+                }
+                // If links not empty but no parent, this is an error
+                no_parent_error(&temp_parent, &temp_links)?;
+                // If temp holders not empty, done with current parent, add to state
+                update_state_reset_temps(&mut temp_parent, &mut temp_links, &mut state);
+                // NOTE: silent error if parent has no links (this is fine, not stored in internal state)
+                temp_parent = line.trim_end_matches("->").to_string();
+                if let Err(e) = validate_parent(&temp_parent) {
+                    // The links of a rejected parent have no parent and are rejected as well
+                    temp_parent.clear();
+                    reject(&mut rejected, line_number, line, e)?;
+                }
+            } else if line.contains('|') {
+                // This is a link line, a `|` in its value or metadata is escaped as `\|`
+                let (link, value, link_meta) = split_link_line(line, escaped)
+                    .ok_or(TapDataStoreError {
+                        kind: TapDataStoreErrorKind::ParseError,
+                        message: "A link/value line of a data file is expected to contain '|' character separating link and value. For example, google|https://google.com".to_string(),
+                    })?;
+                if let Err(e) = validate_link(link) {
+                    reject(&mut rejected, line_number, line, e)?;
+                    continue;
+                }
+                if rejected.is_some() && temp_parent.is_empty() {
+                    reject(
+                        &mut rejected,
+                        line_number,
+                        line,
+                        TapDataStoreError {
+                            kind: TapDataStoreErrorKind::ParseError,
+                            message: format!("Link '{}' does not have a parent", link.trim()),
+                        },
+                    )?;
+                    continue;
+                }
+                if let Some(only_parent) = only_parent
+                    && !temp_parent.is_empty()
+                    && only_parent.trim() != temp_parent.trim()
+                {
+                    continue;
+                }
+                match existing_value(&temp_parent, link.trim(), &temp_links, &state) {
+                    // The same link repeated with the same value is kept once
+                    Some(v) if v == value.trim() => {}
+                    Some(v) => {
+                        let e = TapDataStoreError {
+                            kind: TapDataStoreErrorKind::ParseError,
+                            message: format!(
+                                "Link '{}' of parent entity '{}' appears more than once with conflicting values '{}' and '{}'",
+                                link.trim(),
+                                temp_parent.trim(),
+                                v,
+                                value.trim()
+                            ),
+                        };
+                        reject(&mut rejected, line_number, line, e)?;
+                        continue;
+                    }
+                    None => temp_links.push((link.trim().to_string(), value.trim().to_string())),
+                }
+                if !link_meta.is_empty() && !temp_parent.is_empty() {
+                    let key = (temp_parent.trim().to_string(), link.trim().to_string());
+                    meta.entry(key).or_insert(link_meta);
+                }
+            } else {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                reject(
+                    &mut rejected,
+                    line_number,
+                    line,
+                    TapDataStoreError {
+                        kind: TapDataStoreErrorKind::ParseError,
+                        message: format!(
+                            "Unknown format for data file. Line '{line}' does not match expected format of parent ->\\n link|value"
+                        ),
+                    },
+                )?;
+            }
+        }
+        // When out of lines, update state
+        no_parent_error(&temp_parent, &temp_links)?;
+        update_state_reset_temps(&mut temp_parent, &mut temp_links, &mut state);
+
+        Ok(state)
+    }
+
+    /// The contents of the data file and the index entries pointing into it. The
+    /// `FORMAT_HEADER` is only written when a link has metadata or a value spans several lines,
+    /// otherwise the file is written as it was before links had metadata.
+    fn state_to_file_string(&self) -> (String, Vec<IndexEntry>) {
+        // Track offsets for fast reads using index file
+        let mut offsets: Vec<IndexEntry> = vec![];
+        // Build return string
+        let mut res = String::new();
+        let escaped = self.meta.values().any(|meta| !meta.fields().is_empty())
+            || self
+                .state
+                .iter()
+                .flat_map(|(_, links)| links.iter())
+                .any(|(_, value)| value.trim().contains('\n'));
+        if escaped {
+            res.push_str(&format!("{FORMAT_HEADER}\n"));
+        }
+
+        debug_assert!(
+            Data::is_sorted(&self.state),
+            "Data state must be kept sorted by parent, then by link"
+        );
+
+        self.comments.header.iter().for_each(|c| {
+            res.push_str(&format!("{c}\n"));
+        });
+
+        // Build return string & track offsets
+        self.state.iter().for_each(|(parent, links)| {
+            // Comments go above the parent line, so the parent's offset points past them
+            self.comments.of_parent(parent).iter().for_each(|c| {
+                res.push_str(&format!("{c}\n"));
+            });
+            offsets.push((parent.trim().to_string(), res.len()));
+
+            res.push_str(&format!("{}->\n", parent.trim()));
+            links.iter().for_each(|(link, value)| {
+                let value = match escaped {
+                    true => escape_field(value.trim()),
+                    false => value.trim().to_string(),
+                };
+                res.push_str(&format!("  {}|{value}", link.trim()));
+                if let Some(meta) = self
+                    .meta
+                    .get(&(parent.trim().to_string(), link.trim().to_string()))
+                {
+                    meta.fields()
+                        .iter()
+                        .for_each(|field| res.push_str(&format!("|{field}")));
+                }
+                res.push('\n');
+            });
+        });
+
+        // Comments of parents that no longer exist are moved to the end instead of being lost
+        self.comments
+            .parents
+            .iter()
+            .filter(|(p, _)| !self.state.iter().any(|(parent, _)| parent.trim() == p))
+            .flat_map(|(_, c)| c.iter())
+            .chain(self.comments.trailing.iter())
+            .for_each(|c| {
+                res.push_str(&format!("{c}\n"));
+            });
+        (res, offsets)
+    }
+
+    /// Writes the data file next to itself and renames it over the old one, see
+    /// `write_data_file`, so a failed save never leaves it half written. A change that only adds
+    /// to the end of the file (a new last parent entity, or links added to it) is appended
+    /// instead, see `append_data_file`. Index offsets are always recomputed from the new contents.
+    fn save_to_file(&mut self) -> Result<Vec<IndexEntry>, TapDataStoreError> {
+        let (str, offsets) = self.state_to_file_string();
+        if self.in_memory {
+            return Ok(offsets);
+        }
+        let stamp = FileStamp::of(&str);
+        let verified = std::mem::take(&mut self.verified);
+        if let Some(on_disk) = self.on_disk {
+            let unchanged = match verified {
+                true => on_disk.matches_len(&self.path)?,
+                false => on_disk.matches(&self.path)?,
+            };
+            if !unchanged {
+                return Err(data_file_changed(&self.path));
+            }
+            if on_disk == stamp {
+                return Ok(offsets);
+            }
+            if let Some(tail) = appended(on_disk, &str) {
+                append_data_file(&self.path, on_disk.len, tail)?;
+                self.on_disk = Some(stamp);
+                return Ok(offsets);
+            }
+        }
+        write_data_file(&self.path, &str)?;
+        self.on_disk = Some(stamp);
+        Ok(offsets)
+    }
+}
+
+/// Reads through to `inner`, keeping the `FileStamp` of everything read so far
+struct StampingReader<R> {
+    inner: R,
+    len: u64,
+    hash: u64,
+}
+
+impl<R> StampingReader<R> {
+    fn new(inner: R) -> Self {
+        Self {
+            inner,
+            len: 0,
+            hash: FNV_OFFSET_BASIS,
+        }
+    }
+
+    fn stamp(&self) -> FileStamp {
+        FileStamp {
+            len: self.len,
+            hash: self.hash,
+        }
+    }
+}
+
+impl<R: Read> Read for StampingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.len += n as u64;
+        self.hash = fnv1a(self.hash, &buf[..n]);
+        Ok(n)
+    }
+}
+
+/// The length and FNV-1a hash of the contents of a data file, enough to tell whether the file was
+/// changed without keeping a copy of it
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct FileStamp {
+    len: u64,
+    hash: u64,
+}
+
+impl FileStamp {
+    fn of(contents: &str) -> Self {
+        Self {
+            len: contents.len() as u64,
+            hash: fnv1a(FNV_OFFSET_BASIS, contents.as_bytes()),
+        }
+    }
+
+    /// Whether the file at `path` still has this stamp, a missing file being empty. The file is
+    /// only hashed, a chunk at a time, when its length matches.
+    fn matches(&self, path: &Path) -> Result<bool, TapDataStoreError> {
+        let read_failed = |e: std::io::Error| TapDataStoreError {
+            kind: TapDataStoreErrorKind::FileReadFailed,
+            message: format!("Could not read data file at {}: {e}", path.display()),
+        };
+        let mut f = match File::open(path) {
+            Ok(f) => f,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(self.len == 0),
+            Err(e) => return Err(read_failed(e)),
+        };
+        if f.metadata().map_err(read_failed)?.len() != self.len {
+            return Ok(false);
+        }
+        let mut hash = FNV_OFFSET_BASIS;
+        let mut buf = [0u8; 8192];
+        loop {
+            match f.read(&mut buf).map_err(read_failed)? {
+                0 => return Ok(hash == self.hash),
+                n => hash = fnv1a(hash, &buf[..n]),
+            }
+        }
+    }
+
+    /// Whether the file at `path` still has the length of this stamp, a missing file being empty.
+    /// Only for a file that was just found to have this stamp, as nothing is read.
+    fn matches_len(&self, path: &Path) -> Result<bool, TapDataStoreError> {
+        match fs::metadata(path) {
+            Ok(m) => Ok(m.len() == self.len),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(self.len == 0),
+            Err(e) => Err(TapDataStoreError {
+                kind: TapDataStoreErrorKind::FileReadFailed,
+                message: format!("Could not read data file at {}: {e}", path.display()),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod data_private {
+    use super::{
+        Comments, Data, FileStamp, FileType, Index, LinkMeta, LinkType, Metadata,
+        TapDataStoreErrorKind, appended, escape_field, get_test_file_path, parse_snippet,
+        split_link_line,
+    };
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn cleanup_test_file(file_path: &PathBuf) {
+        fs::remove_file(file_path).expect("Could not remove test file");
+    }
+
+    #[test]
+    fn test_parse_file_empty() {
+        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
+        fs::write(&data_path, "").unwrap();
+        let res = Data::parse_file(fs::read_to_string(&data_path).unwrap().as_bytes())
+            .expect("Could not parse file");
+        assert_eq!(res, vec![]);
+        cleanup_test_file(&data_path);
+    }
+    #[test]
+    fn test_parse_file_valid_one_parent() {
+        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
+        fs::write(&data_path, "parent1->\nlink1|value1\nlink2|value2").unwrap();
+        let res = Data::parse_file(fs::read_to_string(&data_path).unwrap().as_bytes())
+            .expect("Could not parse file");
+        assert_eq!(
+            res,
             vec![(
                 "parent1".to_string(),
                 vec![
@@ -464,622 +4001,979 @@ mod data_public {
                 ]
             ),]
         );
-        data.cleanup().expect("Could not clean up data store");
+        cleanup_test_file(&data_path);
+    }
+
+    #[test]
+    fn test_parse_file_valid_two_parents() {
+        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
+        fs::write(&data_path, "search engines->\ngoogle|www.google.com\nyahoo|www.yahoo.com\ncoding->\ngh|https://github.com").unwrap();
+        let res = Data::parse_file(fs::read_to_string(&data_path).unwrap().as_bytes())
+            .expect("Could not parse file");
+        assert_eq!(
+            res,
+            vec![
+                (
+                    "search engines".to_string(),
+                    vec![
+                        ("google".to_string(), "www.google.com".to_string()),
+                        ("yahoo".to_string(), "www.yahoo.com".to_string())
+                    ]
+                ),
+                (
+                    "coding".to_string(),
+                    vec![("gh".to_string(), "https://github.com".to_string())]
+                ),
+            ]
+        );
+        cleanup_test_file(&data_path);
+    }
+
+    #[test]
+    fn test_parse_file_invalid_links() {
+        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
+        fs::write(
+            &data_path,
+            "search engines->\ngoogle|www.google.com\nyahoo|www.yahoo.com\ninvalid link",
+        )
+        .unwrap();
+        let res = Data::parse_file(fs::read_to_string(&data_path).unwrap().as_bytes());
+        assert_eq!(res.unwrap_err().kind, TapDataStoreErrorKind::ParseError);
+        cleanup_test_file(&data_path);
+    }
+
+    #[test]
+    fn test_parse_file_invalid_parent() {
+        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
+        fs::write(&data_path, "invalid parent->\n").unwrap();
+        let res = Data::parse_file(fs::read_to_string(&data_path).unwrap().as_bytes())
+            .expect("Could not parse file");
+        // Silent error, if parent has no links no big deal
+        assert_eq!(res, vec![]);
+        cleanup_test_file(&data_path);
+    }
+
+    #[test]
+    fn test_parse_file_invalid_random_file() {
+        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
+        fs::write(
+            &data_path,
+            "Something that is completely not a data file was read",
+        )
+        .unwrap();
+        let res = Data::parse_file(fs::read_to_string(&data_path).unwrap().as_bytes());
+        assert_eq!(res.unwrap_err().kind, TapDataStoreErrorKind::ParseError);
+        cleanup_test_file(&data_path);
+    }
+
+    #[test]
+    fn test_parse_file_duplicate_parent_adjacent() {
+        let res = Data::parse_file(
+            "parent1->\nlink1|value1\nparent1->\nlink2|value2\nlink1|value1\nparent2->\nlink3|value3".as_bytes(),
+        )
+        .expect("Could not parse file");
+        assert_eq!(
+            res,
+            vec![
+                (
+                    "parent1".to_string(),
+                    vec![
+                        ("link1".to_string(), "value1".to_string()),
+                        ("link2".to_string(), "value2".to_string())
+                    ]
+                ),
+                (
+                    "parent2".to_string(),
+                    vec![("link3".to_string(), "value3".to_string())]
+                ),
+            ]
+        );
     }
 
     #[test]
-    fn test_add_link_when_parent_doesnt_exist() {
-        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
-        let mut data = Data::new(Some(data_path), None).unwrap();
-        let res = data.add_link("parent1", "link1", "value1");
-        assert!(res.is_ok());
+    fn test_parse_file_duplicate_parent_separated() {
+        let res = Data::parse_file(
+            "parent1->\nlink1|value1\nparent2->\nlink3|value3\nparent1 ->\nlink2|value2".as_bytes(),
+        )
+        .expect("Could not parse file");
         assert_eq!(
-            data.state,
+            res,
+            vec![
+                (
+                    "parent1".to_string(),
+                    vec![
+                        ("link1".to_string(), "value1".to_string()),
+                        ("link2".to_string(), "value2".to_string())
+                    ]
+                ),
+                (
+                    "parent2".to_string(),
+                    vec![("link3".to_string(), "value3".to_string())]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_file_duplicate_link_in_section() {
+        let res = Data::parse_file("parent1->\nlink1|value1\nlink1|value1\n".as_bytes())
+            .expect("Could not parse file");
+        assert_eq!(
+            res,
             vec![(
                 "parent1".to_string(),
                 vec![("link1".to_string(), "value1".to_string())]
             )]
         );
-        data.cleanup().expect("Could not clean up data store");
     }
 
     #[test]
-    fn test_add_link_when_link_doesnt_exist() {
-        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
-        let mut data = Data::new(Some(data_path), None).unwrap();
-        data.state = vec![(
-            "search-engines".to_string(),
-            vec![
-                ("google".to_string(), "www.google.com".to_string()),
-                ("yahoo".to_string(), "www.yahoo.com".to_string()),
-            ],
-        )];
-        let res = data.add_link("search-engines", "link1", "value1");
-        assert!(res.is_ok());
+    fn test_parse_file_duplicate_parent_conflicting_link() {
+        let res = Data::parse_file(
+            "parent1->\nlink1|value1\nparent2->\nlink3|value3\nparent1->\nlink1|other".as_bytes(),
+        );
+        let err = res.unwrap_err();
+        assert_eq!(err.kind, TapDataStoreErrorKind::ParseError);
+        assert!(
+            err.message
+                .contains("conflicting values 'value1' and 'other'")
+        );
+    }
+
+    #[test]
+    fn test_parse_file_lenient_duplicate_parent_conflicting_link() {
+        let (state, rejected) = Data::parse_file_lenient(
+            "parent1->\nlink1|value1\nparent1->\nlink1|other\nlink2|value2",
+            &mut Metadata::new(),
+        );
         assert_eq!(
-            data.state,
+            state,
             vec![(
-                "search-engines".to_string(),
+                "parent1".to_string(),
                 vec![
-                    ("google".to_string(), "www.google.com".to_string()),
-                    ("yahoo".to_string(), "www.yahoo.com".to_string()),
-                    ("link1".to_string(), "value1".to_string())
+                    ("link1".to_string(), "value1".to_string()),
+                    ("link2".to_string(), "value2".to_string())
                 ]
             )]
         );
-        data.cleanup().expect("Could not clean up data store");
+        assert_eq!(
+            rejected
+                .iter()
+                .map(|r| (r.line_number, r.line.as_str()))
+                .collect::<Vec<(usize, &str)>>(),
+            vec![(4, "link1|other")]
+        );
     }
 
     #[test]
-    fn test_add_link_when_link_already_exists() {
-        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
-        let mut data = Data::new(Some(data_path), None).unwrap();
-        data.state = vec![(
-            "search-engines".to_string(),
-            vec![("google".to_string(), "www.google.com".to_string())],
-        )];
-        let res = data.add_link("search-engines", "google", "something else");
-        assert_eq!(
-            res.unwrap_err().kind,
-            TapDataStoreErrorKind::LinkAlreadyExists
-        );
+    fn test_parse_file_skips_comments() {
+        let res = Data::parse_file(
+            "# my links\nparent1->\n  # search engines\n  link1|value1\n# a|b is not a link\n"
+                .as_bytes(),
+        )
+        .expect("Could not parse file");
         assert_eq!(
-            data.state,
+            res,
             vec![(
-                "search-engines".to_string(),
-                vec![("google".to_string(), "www.google.com".to_string()),]
+                "parent1".to_string(),
+                vec![("link1".to_string(), "value1".to_string())]
             )]
         );
-        data.cleanup().expect("Could not clean up data store");
     }
 
     #[test]
-    fn test_get_parent_when_parent_exists() {
-        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
-        let mut data = Data::new(Some(data_path), None).unwrap();
-        data.state = vec![(
-            "search-engines".to_string(),
-            vec![
-                ("google".to_string(), "www.google.com".to_string()),
-                ("yahoo".to_string(), "www.yahoo.com".to_string()),
-            ],
-        )];
-        let res = data.get("search-engines", None);
-        assert!(res.is_ok());
+    fn test_comments_parse() {
+        let comments = Comments::parse(
+            "# header\nparent1->\n  link1|value1\n# about parent2\nparent2 ->\n  link2|value2\n# trailing\n",
+        );
         assert_eq!(
-            res.unwrap(),
-            vec![
-                ("google".to_string(), "www.google.com".to_string()),
-                ("yahoo".to_string(), "www.yahoo.com".to_string()),
-            ]
+            comments,
+            Comments {
+                header: vec!["# header".to_string()],
+                parents: vec![("parent2".to_string(), vec!["# about parent2".to_string()])],
+                trailing: vec!["# trailing".to_string()],
+            }
         );
-        data.cleanup().expect("Could not clean up data store");
     }
 
     #[test]
-    fn test_get_parent_when_parent_does_not_exist() {
+    fn test_comments_survive_add_link() {
         let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
-        let mut data = Data::new(Some(data_path), None).unwrap();
-        let res = data.get("search-engines", None);
+        let file = "# my links\n# second line\nparent1->\n  link1|value1\n# work stuff below\nwork->\n  jira|https://jira.com\n# the end\n";
+        fs::write(&data_path, file).unwrap();
+        let mut data = Data::new(Some(data_path.clone()), None).unwrap();
+        data.add_link("another", "link2", "value2").unwrap();
+        data.save_to_file().unwrap();
         assert_eq!(
-            res.unwrap_err().kind,
-            TapDataStoreErrorKind::ParentEntityNotFound
+            fs::read_to_string(&data_path).unwrap(),
+            "# my links\n# second line\nanother->\n  link2|value2\nparent1->\n  link1|value1\n# work stuff below\nwork->\n  jira|https://jira.com\n# the end\n"
         );
-        assert_eq!(data.state, vec![]);
-        data.cleanup().expect("Could not clean up data store");
+        cleanup_test_file(&data_path);
     }
 
     #[test]
-    fn test_get_parent_and_link_when_parent_exists_and_link_exists() {
+    fn test_link_meta_groups() {
         let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
-        let mut data = Data::new(Some(data_path), None).unwrap();
-        data.state = vec![(
-            "search-engines".to_string(),
-            vec![
-                ("google".to_string(), "www.google.com".to_string()),
-                ("yahoo".to_string(), "www.yahoo.com".to_string()),
-            ],
-        )];
-        let res = data.get("search-engines", Some("google"));
-        assert!(res.is_ok());
+        let file = "# tap-data 2\nwork->\n  ci|https://ci.internal|group=oncall|group=builds\n";
+        fs::write(&data_path, file).unwrap();
+        let mut data = Data::new(Some(data_path.clone()), None).unwrap();
         assert_eq!(
-            res.unwrap(),
-            vec![("google".to_string(), "www.google.com".to_string())]
+            data.get_meta("work", "ci").unwrap().groups,
+            ["oncall", "builds"]
         );
-        data.cleanup().expect("Could not clean up data store");
+        data.upsert_link("work", "ci", "https://ci.example.com")
+            .unwrap();
+        data.save_to_file().unwrap();
+        assert_eq!(
+            fs::read_to_string(&data_path).unwrap(),
+            "# tap-data 2\nwork->\n  ci|https://ci.example.com|group=oncall|group=builds\n"
+        );
+        cleanup_test_file(&data_path);
     }
 
     #[test]
-    fn test_get_parent_and_link_when_parent_exists_and_link_does_not_exist() {
+    fn test_link_meta_round_trip() {
         let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
-        let mut data = Data::new(Some(data_path), None).unwrap();
-        data.state = vec![(
-            "search-engines".to_string(),
-            vec![
-                ("google".to_string(), "www.google.com".to_string()),
-                ("yahoo".to_string(), "www.yahoo.com".to_string()),
-            ],
-        )];
-        let res = data.get("search-engines", Some("link1"));
-        assert_eq!(res.unwrap_err().kind, TapDataStoreErrorKind::LinkNotFound);
+        let file =
+            "# tap-data 2\nwork->\n  ci|https://ci.internal|pinned\n  mr|https://mr.internal\n";
+        fs::write(&data_path, file).unwrap();
+        let mut data = Data::new(Some(data_path.clone()), None).unwrap();
+        assert!(data.get_meta("work", "ci").unwrap().pinned);
+        assert!(data.get_meta("work", "mr").unwrap().is_empty());
+        // Values stay as they are, whatever metadata their link has
         assert_eq!(
-            data.state,
-            vec![(
-                "search-engines".to_string(),
-                vec![
-                    ("google".to_string(), "www.google.com".to_string()),
-                    ("yahoo".to_string(), "www.yahoo.com".to_string()),
-                ],
-            )]
+            data.get("work", Some("ci")).unwrap(),
+            vec![("ci".to_string(), "https://ci.internal".to_string())]
         );
-        data.cleanup().expect("Could not clean up data store");
+        data.upsert_link("work", "ci", "https://ci.example.com")
+            .unwrap();
+        data.set_meta(
+            "work",
+            "mr",
+            LinkMeta {
+                pinned: true,
+                ..LinkMeta::default()
+            },
+        )
+        .unwrap();
+        data.save_to_file().unwrap();
+        assert_eq!(
+            fs::read_to_string(&data_path).unwrap(),
+            "# tap-data 2\nwork->\n  ci|https://ci.example.com|pinned\n  mr|https://mr.internal|pinned\n"
+        );
+        // Removed links lose their metadata, so adding them back does not bring it back
+        data.remove("work", Some("ci")).unwrap();
+        data.add_link("work", "ci", "https://ci.internal").unwrap();
+        assert!(data.get_meta("work", "ci").unwrap().is_empty());
+        assert_eq!(
+            data.set_meta(
+                "work",
+                "wiki",
+                LinkMeta {
+                    pinned: true,
+                    ..LinkMeta::default()
+                }
+            )
+            .unwrap_err()
+            .kind,
+            TapDataStoreErrorKind::LinkNotFound
+        );
+        cleanup_test_file(&data_path);
     }
 
     #[test]
-    fn test_import_tap_bad_file() {
-        let import_path = "rando_file.pdf";
-        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
-        let mut data = Data::new(Some(data_path), None).unwrap();
-        let res = data.import(ImportType::Tap, PathBuf::from(import_path));
+    fn test_link_line_escapes() {
+        for value in [
+            "a|b",
+            "two\nlines",
+            r"C:\Users\me",
+            r"ends with \",
+            r"\|",
+            r"\\n",
+            "pinned",
+        ] {
+            let line = format!("  link|{}|pinned", escape_field(value));
+            assert_eq!(
+                split_link_line(&line, true),
+                Some((
+                    "  link",
+                    value.to_string(),
+                    LinkMeta {
+                        pinned: true,
+                        ..LinkMeta::default()
+                    }
+                )),
+                "{line}"
+            );
+        }
+        assert_eq!(escape_field(r"C:\Users"), r"C:\Users");
+        assert_eq!(escape_field("a|b"), r"a\|b");
+        // A bare `|` that is not followed by metadata is part of the value, like it always was
         assert_eq!(
-            res.unwrap_err().kind,
-            TapDataStoreErrorKind::InvalidFileExtension
+            split_link_line("q|a|b", true),
+            Some(("q", "a|b".to_string(), LinkMeta::default()))
+        );
+        assert_eq!(
+            split_link_line("ci|https://ci.internal|default|pinned", true),
+            Some((
+                "ci",
+                "https://ci.internal".to_string(),
+                LinkMeta {
+                    pinned: true,
+                    default: true,
+                    ..LinkMeta::default()
+                }
+            ))
+        );
+        assert_eq!(split_link_line("no separator", true), None);
+        // Without the header, everything after the first `|` is the value as it is
+        assert_eq!(
+            split_link_line(r"dir|C:\new\docs|pinned", false),
+            Some((
+                "dir",
+                r"C:\new\docs|pinned".to_string(),
+                LinkMeta::default()
+            ))
         );
-        data.cleanup().expect("Could not clean up data store");
     }
 
     #[test]
-    fn test_import_tap_data_parent_exists_but_link_does_not() {
-        let import_path = get_test_file_path(FileType::Tap).expect("Could not get test file path");
+    fn test_legacy_file_round_trip() {
         let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
-        let mut data = Data::new(Some(data_path), None).unwrap();
-        data.state = vec![(
-            "search-engines".to_string(),
-            vec![("google".to_string(), "www.google.com".to_string())],
-        )];
-        fs::write(&import_path, "search-engines->\nyahoo|www.yahoo.com\n").unwrap();
-
-        let res = data.import(ImportType::Tap, import_path.clone());
-        assert!(res.is_ok());
+        // Written before links had metadata, so nothing in it is escaped
+        let file = "paths->\n  dir|C:\\new\\docs\n  pipe|a|b\n  share|\\\\server\\share\\notes\n";
+        fs::write(&data_path, file).unwrap();
+        let expected = vec![
+            ("dir".to_string(), r"C:\new\docs".to_string()),
+            ("pipe".to_string(), "a|b".to_string()),
+            ("share".to_string(), r"\\server\share\notes".to_string()),
+        ];
+        let mut data = Data::new(Some(data_path.clone()), None).unwrap();
+        assert_eq!(data.get("paths", None).unwrap(), expected);
+        // An unrelated change keeps writing the file as it was, so older versions still read it
+        data.add_link("news", "bbc", "https://bbc.com").unwrap();
+        data.save_to_file().unwrap();
         assert_eq!(
-            data.state,
-            vec![(
-                "search-engines".to_string(),
-                vec![
-                    ("google".to_string(), "www.google.com".to_string()),
-                    ("yahoo".to_string(), "www.yahoo.com".to_string())
-                ]
-            ),]
+            fs::read_to_string(&data_path).unwrap(),
+            format!("news->\n  bbc|https://bbc.com\n{file}")
         );
-        data.cleanup().expect("Could not clean up data store");
-        fs::remove_file(import_path).expect("Could not remove import file");
+        let mut data = Data::new(Some(data_path.clone()), None).unwrap();
+        assert_eq!(data.get("paths", None).unwrap(), expected);
+        // Metadata needs the header, and the values are escaped from then on
+        data.set_meta(
+            "paths",
+            "dir",
+            LinkMeta {
+                pinned: true,
+                ..LinkMeta::default()
+            },
+        )
+        .unwrap();
+        let offsets = data.save_to_file().unwrap();
+        let saved = fs::read_to_string(&data_path).unwrap();
+        assert!(saved.starts_with("# tap-data 2\nnews->\n"), "{saved}");
+        assert!(saved.contains("  dir|C:\\\\new\\docs|pinned\n"), "{saved}");
+        assert!(saved.contains("  pipe|a\\|b\n"), "{saved}");
+        let data = Data::new(Some(data_path.clone()), None).unwrap();
+        assert_eq!(data.get("paths", None).unwrap(), expected);
+        // Reading a single section through the index still unescapes it
+        let (_, offset) = offsets.iter().find(|(p, _)| p == "paths").unwrap();
+        let data =
+            Data::read_parent_section(Some(data_path.clone()), (*offset, 0), "paths").unwrap();
+        assert_eq!(data.get("paths", None).unwrap(), expected);
+        assert!(data.get_meta("paths", "dir").unwrap().pinned);
+        cleanup_test_file(&data_path);
     }
 
     #[test]
-    fn test_import_tap_data_parent_does_not_exist() {
-        let import_path = get_test_file_path(FileType::Tap).expect("Could not get test file path");
+    fn test_link_note_round_trip() {
         let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
-        let mut data = Data::new(Some(data_path), None).unwrap();
-        data.state = vec![(
-            "search-engines".to_string(),
-            vec![("google".to_string(), "www.google.com".to_string())],
-        )];
-        fs::write(&import_path, "repo->\ngh|www.github.com\n").unwrap();
+        fs::write(&data_path, "work->\n  ci|https://ci.internal\n").unwrap();
+        let mut data = Data::new(Some(data_path.clone()), None).unwrap();
+        let meta = LinkMeta {
+            pinned: true,
+            note: Some("ask | ops\nfirst".to_string()),
+            ..LinkMeta::default()
+        };
+        data.set_meta("work", "ci", meta.clone()).unwrap();
+        data.save_to_file().unwrap();
+        assert_eq!(
+            fs::read_to_string(&data_path).unwrap(),
+            "# tap-data 2\nwork->\n  ci|https://ci.internal|pinned|note=ask \\| ops\\nfirst\n"
+        );
+        let data = Data::new(Some(data_path.clone()), None).unwrap();
+        assert_eq!(data.get_meta("work", "ci").unwrap(), meta);
+        cleanup_test_file(&data_path);
+    }
 
-        let res = data.import(ImportType::Tap, import_path.clone());
-        assert!(res.is_ok());
+    #[test]
+    fn test_parse_snippet() {
+        let snippet = "# tap-data 2\nwork->\n  ci|https://ci.internal|pinned\n  pipe|a\\|b\nnews|bbc|https://bbc.com\n";
         assert_eq!(
-            data.state,
-            vec![
+            parse_snippet(snippet),
+            Ok(vec![
                 (
-                    "search-engines".to_string(),
-                    vec![("google".to_string(), "www.google.com".to_string()),]
+                    "work".to_string(),
+                    "ci".to_string(),
+                    "https://ci.internal".to_string()
                 ),
+                ("work".to_string(), "pipe".to_string(), "a|b".to_string()),
                 (
-                    "repo".to_string(),
-                    vec![("gh".to_string(), "www.github.com".to_string()),]
+                    "news".to_string(),
+                    "bbc".to_string(),
+                    "https://bbc.com".to_string()
                 ),
-            ]
+            ])
         );
-        data.cleanup().expect("Could not clean up data store");
-        fs::remove_file(import_path).expect("Could not remove import file");
-    }
-
-    #[test]
-    fn test_import_tap_data_parent_exists_and_link_exists() {
-        let import_path = get_test_file_path(FileType::Tap).expect("Could not get test file path");
-        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
-        let mut data = Data::new(Some(data_path), None).unwrap();
-        data.state = vec![(
-            "search-engines".to_string(),
-            vec![("google".to_string(), "www.google.com".to_string())],
-        )];
-        fs::write(&import_path, "search-engines->\ngoogle|abc\n").unwrap();
-
-        let res = data.import(ImportType::Tap, import_path.clone());
-        // TODO: currently the link is overwritten, make this a param instead?
-        assert!(res.is_ok());
+        let rejected =
+            parse_snippet("work->\n  ci|https://a.com\n  ci|https://b.com\n  ci|\n").unwrap_err();
         assert_eq!(
-            data.state,
-            vec![(
-                "search-engines".to_string(),
-                vec![("google".to_string(), "abc".to_string()),]
-            ),]
+            rejected
+                .iter()
+                .map(|r| (r.line_number, r.reason.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    3,
+                    "Link 'ci' of parent entity 'work' was already given with value 'https://a.com'"
+                ),
+                (4, "Expected a link name and a value"),
+            ]
         );
-        data.cleanup().expect("Could not clean up data store");
-        fs::remove_file(import_path).expect("Could not remove import file");
-    }
-
-    #[test]
-    fn test_remove_parent_when_parent_exists() {
-        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
-        let mut data = Data::new(Some(data_path), None).unwrap();
-        data.state = vec![(
-            "search-engines".to_string(),
-            vec![("google".to_string(), "www.google.com".to_string())],
-        )];
-        let res = data.remove("search-engines", None);
-        assert!(res.is_ok());
-        assert_eq!(data.state, vec![]);
-        data.cleanup().expect("Could not clean up data store");
     }
 
     #[test]
-    fn test_remove_parent_when_parent_does_not_exists() {
+    fn test_link_expiry_round_trip() {
         let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
-        let mut data = Data::new(Some(data_path), None).unwrap();
-        let res = data.remove("search-engines", None);
+        fs::write(
+            &data_path,
+            "# tap-data 2\nwork->\n  ci|https://ci.internal\n  doc|https://doc.internal|expires=someday\n",
+        )
+        .unwrap();
+        let mut data = Data::new(Some(data_path.clone()), None).unwrap();
+        // Only dates are read as an expiry, anything else stays part of the value
         assert_eq!(
-            res.unwrap_err().kind,
-            TapDataStoreErrorKind::ParentEntityNotFound
+            data.get("work", Some("doc")).unwrap()[0].1,
+            "https://doc.internal|expires=someday"
         );
-        assert_eq!(data.state, vec![]);
-        data.cleanup().expect("Could not clean up data store");
+        let meta = LinkMeta {
+            expires: Some("2024-07-01".to_string()),
+            ..LinkMeta::default()
+        };
+        data.set_meta("work", "ci", meta.clone()).unwrap();
+        data.save_to_file().unwrap();
+        assert!(
+            fs::read_to_string(&data_path)
+                .unwrap()
+                .starts_with("# tap-data 2\nwork->\n  ci|https://ci.internal|expires=2024-07-01\n")
+        );
+        let data = Data::new(Some(data_path.clone()), None).unwrap();
+        assert_eq!(data.get_meta("work", "ci").unwrap(), meta);
+        assert!(meta.is_expired());
+        cleanup_test_file(&data_path);
     }
 
     #[test]
-    fn test_remove_parent_and_link_when_parent_exists_and_link_exists() {
+    fn test_link_type_round_trip() {
         let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
-        let mut data = Data::new(Some(data_path), None).unwrap();
-        data.state = vec![(
-            "search-engines".to_string(),
-            vec![("google".to_string(), "www.google.com".to_string())],
-        )];
-        let res = data.remove("search-engines", Some("google"));
-        assert!(res.is_ok());
-        assert_eq!(data.state, vec![]);
-        data.cleanup().expect("Could not clean up data store");
+        fs::write(
+            &data_path,
+            "# tap-data 2\nvpn->\n  up|wg-quick up wg0|type=cmd\n  odd|value|type=script\n  docs|https://vpn.internal|type=url\n",
+        )
+        .unwrap();
+        let mut data = Data::new(Some(data_path.clone()), None).unwrap();
+        assert_eq!(
+            data.get_meta("vpn", "up").unwrap().link_type,
+            Some(LinkType::Cmd)
+        );
+        // Only known types are read as a type, anything else stays part of the value
+        assert_eq!(
+            data.get("vpn", Some("odd")).unwrap()[0].1,
+            "value|type=script"
+        );
+        data.save_to_file().unwrap();
+        // The unknown type is escaped so it stays part of the value when read again, and the
+        // default type is not written
+        assert_eq!(
+            fs::read_to_string(&data_path).unwrap(),
+            "# tap-data 2\nvpn->\n  docs|https://vpn.internal\n  odd|value\\|type=script\n  up|wg-quick up wg0|type=cmd\n"
+        );
+        cleanup_test_file(&data_path);
     }
 
     #[test]
-    fn test_remove_link_when_parent_exists_and_link_exists() {
+    fn test_comments_of_removed_parent_are_kept() {
         let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
-        let mut data = Data::new(Some(data_path), None).unwrap();
-        data.state = vec![(
-            "search-engines".to_string(),
-            vec![
-                ("google".to_string(), "www.google.com".to_string()),
-                ("yahoo".to_string(), "www.yahoo.com".to_string()),
-            ],
-        )];
-        let res = data.remove("search-engines", Some("google"));
-        assert!(res.is_ok());
-        assert_eq!(
-            data.state,
-            vec![(
-                "search-engines".to_string(),
-                vec![("yahoo".to_string(), "www.yahoo.com".to_string())],
-            ),]
+        let file =
+            "parent1->\n  link1|value1\n# work stuff below\nwork->\n  jira|https://jira.com\n";
+        fs::write(&data_path, file).unwrap();
+        let mut data = Data::new(Some(data_path.clone()), None).unwrap();
+        data.remove("work", None).unwrap();
+        let (offsets, res) = (
+            data.save_to_file().unwrap(),
+            fs::read_to_string(&data_path).unwrap(),
         );
-        data.cleanup().expect("Could not clean up data store");
+        assert_eq!(res, "parent1->\n  link1|value1\n# work stuff below\n");
+        assert_eq!(offsets, vec![("parent1".to_string(), 0)]);
+        cleanup_test_file(&data_path);
     }
 
     #[test]
-    fn test_remove_link_when_parent_does_not_exist_and_link_exists_in_other_parent() {
-        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
-        let mut data = Data::new(Some(data_path), None).unwrap();
-        data.state = vec![(
-            "search-engines".to_string(),
-            vec![("google".to_string(), "www.google.com".to_string())],
-        )];
-        let res = data.remove("search-engines2", Some("google"));
+    fn test_comments_offsets_point_to_parent_line() {
+        let mut data = Data::in_memory(vec![
+            (
+                "parent1".to_string(),
+                vec![("link1".to_string(), "value1".to_string())],
+            ),
+            (
+                "parent2".to_string(),
+                vec![("link2".to_string(), "value2".to_string())],
+            ),
+        ]);
+        data.comments = Comments::parse("# header\nparent1->\n# about parent2\nparent2->\n");
+        let (res, offsets) = data.state_to_file_string();
         assert_eq!(
-            res.unwrap_err().kind,
-            TapDataStoreErrorKind::ParentEntityNotFound
+            res,
+            "# header\nparent1->\n  link1|value1\n# about parent2\nparent2->\n  link2|value2\n"
         );
         assert_eq!(
-            data.state,
-            vec![(
-                "search-engines".to_string(),
-                vec![("google".to_string(), "www.google.com".to_string())],
-            )]
+            offsets,
+            vec![("parent1".to_string(), 9), ("parent2".to_string(), 50)]
         );
-        data.cleanup().expect("Could not clean up data store");
     }
 
     #[test]
-    fn test_upsert_link_when_link_does_not_exists() {
+    fn test_read_parent_section_multibyte() {
         let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
-        let mut data = Data::new(Some(data_path), None).unwrap();
-        data.state = vec![(
-            "search-engines".to_string(),
-            vec![("google".to_string(), "www.google.com".to_string())],
-        )];
-        let res = data.upsert_link("search-engines", "yahoo", "www.yahoo.com");
-        assert!(res.is_ok());
+        let mut data = Data::new(Some(data_path.clone()), None).unwrap();
+        data.add_link("café", "menu", "https://café.fr/🍰").unwrap();
+        data.add_link("émojis", "party", "🎉🎉🎉").unwrap();
+        data.add_link("zebra", "ü", "https://zoo.de/ü").unwrap();
+        let offsets = data.save_to_file().unwrap();
+        let mut index = Index::in_memory();
+        index.update(offsets);
+
+        for (parent, link, value) in [
+            ("café", "menu", "https://café.fr/🍰"),
+            ("émojis", "party", "🎉🎉🎉"),
+            ("zebra", "ü", "https://zoo.de/ü"),
+        ] {
+            let offset_length = index
+                .find_parent_offset_and_length(parent.to_string())
+                .unwrap();
+            // Read purely via the index, the full file is never parsed
+            let section = Data::new(Some(data_path.clone()), Some(offset_length)).unwrap();
+            assert_eq!(
+                section.state,
+                vec![(
+                    parent.to_string(),
+                    vec![(link.to_string(), value.to_string())]
+                )]
+            );
+        }
+        cleanup_test_file(&data_path);
+    }
+
+    #[test]
+    fn test_read_parent_section_offset_inside_character() {
+        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
+        fs::write(&data_path, "café->\n  menu|🍰\nzebra->\n  zoo|z\n").unwrap();
+        // Offset 4 is in the middle of 'é'
+        let res = Data::new(Some(data_path.clone()), Some((4, 0)));
         assert_eq!(
-            data.state,
-            vec![(
-                "search-engines".to_string(),
-                vec![
-                    ("google".to_string(), "www.google.com".to_string()),
-                    ("yahoo".to_string(), "www.yahoo.com".to_string())
-                ]
-            )]
+            res.err().map(|e| e.kind),
+            Some(TapDataStoreErrorKind::ParseError)
         );
-        data.cleanup().expect("Could not clean up data store");
+        let data = Data::read_parent_section(Some(data_path.clone()), (4, 0), "zebra").unwrap();
+        assert_eq!(
+            data.get("zebra", None).unwrap(),
+            vec![("zoo".to_string(), "z".to_string())]
+        );
+        cleanup_test_file(&data_path);
     }
 
     #[test]
-    fn test_upsert_link_when_link_already_exists() {
+    fn test_read_parent_section_wrong_offset_falls_back() {
         let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
-        let mut data = Data::new(Some(data_path), None).unwrap();
-        data.state = vec![(
-            "search-engines".to_string(),
-            vec![("google".to_string(), "www.google.com".to_string())],
-        )];
-        let res = data.upsert_link("search-engines", "google", "something else");
-        assert!(res.is_ok());
+        fs::write(&data_path, "café->\n  menu|🍰\nzebra->\n  zoo|z\n").unwrap();
+        // A stale index pointing at café instead of zebra must not return café's links
+        let data = Data::read_parent_section(Some(data_path.clone()), (0, 20), "zebra").unwrap();
         assert_eq!(
-            data.state,
-            vec![(
-                "search-engines".to_string(),
-                vec![("google".to_string(), "something else".to_string()),]
-            )]
+            data.get("zebra", None).unwrap(),
+            vec![("zoo".to_string(), "z".to_string())]
         );
-        data.cleanup().expect("Could not clean up data store");
+        cleanup_test_file(&data_path);
     }
 
     #[test]
-    fn test_upsert_link_when_no_parent() {
+    fn test_save_to_file_matches_full_rewrite() {
         let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
-        let mut data = Data::new(Some(data_path), None).unwrap();
-        data.state = vec![(
-            "search-engines".to_string(),
-            vec![("google".to_string(), "www.google.com".to_string())],
-        )];
-        let res = data.upsert_link(
-            "a-different-parent",
-            "google",
-            "the same link name should not matter for different parent",
-        );
-        assert!(res.is_ok());
+        fs::write(
+            &data_path,
+            "parent1->\n  link1|value1\nparent2->\n  link2|value2\nparent3->\n  link3|value3\n",
+        )
+        .unwrap();
+        let mut data = Data::new(Some(data_path.clone()), None).unwrap();
+        let mutations: Vec<fn(&mut Data)> = vec![
+            // Same length change in a middle section
+            |d| d.upsert_link("parent2", "link2", "VALUE2").unwrap(),
+            // Append to the last section
+            |d| d.add_link("parent3", "link4", "value4").unwrap(),
+            // Grow an earlier section
+            |d| d.add_link("parent1", "link0", "value0").unwrap(),
+            // New parent sorted to the end
+            |d| d.add_link("zebra", "zoo", "https://zoo.com").unwrap(),
+            // Remove the first section
+            |d| d.remove("parent1", None).unwrap(),
+            // No change at all
+            |_| {},
+        ];
+        for mutate in mutations {
+            mutate(&mut data);
+            data.save_to_file().unwrap();
+            let (full_rewrite, _) = data.state_to_file_string();
+            assert_eq!(fs::read_to_string(&data_path).unwrap(), full_rewrite);
+        }
+        cleanup_test_file(&data_path);
+    }
+
+    #[test]
+    fn test_open_streams_the_file() {
+        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
+        let contents = "# my links\r\nparent1->\r\n  link1|value1\r\n# about parent2\nparent2->\n  link2|value2\n# the end";
+        fs::write(&data_path, contents).unwrap();
+        let data = Data::new(Some(data_path.clone()), None).unwrap();
         assert_eq!(
             data.state,
             vec![
                 (
-                    "search-engines".to_string(),
-                    vec![("google".to_string(), "www.google.com".to_string()),]
+                    "parent1".to_string(),
+                    vec![("link1".to_string(), "value1".to_string())]
                 ),
                 (
-                    "a-different-parent".to_string(),
-                    vec![(
-                        "google".to_string(),
-                        "the same link name should not matter for different parent".to_string()
-                    ),]
-                )
+                    "parent2".to_string(),
+                    vec![("link2".to_string(), "value2".to_string())]
+                ),
             ]
         );
-        data.cleanup().expect("Could not clean up data store");
+        assert_eq!(data.comments, Comments::parse(contents));
+        // The stamp covers the bytes as read, line endings included
+        assert_eq!(data.on_disk, Some(FileStamp::of(contents)));
+        cleanup_test_file(&data_path);
     }
-}
-
-// Private
-impl Data {
-    fn parse_file(file_as_str: &str) -> Result<Vec<(String, Vec<LinkValue>)>, TapDataStoreError> {
-        fn no_parent_error(parent: &str, links: &[LinkValue]) -> Result<(), TapDataStoreError> {
-            if !links.is_empty() && parent.is_empty() {
-                return Err(TapDataStoreError {
-                    kind: TapDataStoreErrorKind::ParseError,
-                    message: format!(
-                        "Links in a data file must have a parent. The following links do not have a parent: {}",
-                        links
-                            .iter()
-                            .map(|(l, _)| l.to_string())
-                            .collect::<Vec<String>>()
-                            .join(", ")
-                    ),
-                });
-            }
-            Ok(())
-        }
 
-        fn update_state_reset_temps(
-            parent: &mut str,
-            links: &mut Vec<(String, String)>,
-            state: &mut Vec<(String, Vec<(String, String)>)>,
-        ) {
-            if !parent.is_empty() && !links.is_empty() {
-                state.push((parent.to_string(), links.clone()));
-                links.clear();
-            }
-        }
+    #[test]
+    fn test_save_to_file_after_external_change_fails() {
+        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
+        fs::write(&data_path, "parent1->\n  link1|value1\n").unwrap();
+        let mut data = Data::new(Some(data_path.clone()), None).unwrap();
+        // Same length as the file that was read, only the hash tells them apart
+        fs::write(&data_path, "parent1->\n  link1|VALUE1\n").unwrap();
+        data.add_link("parent1", "link2", "value2").unwrap();
+        let err = data.save_to_file().unwrap_err();
+        assert_eq!(*err.kind(), TapDataStoreErrorKind::DataFileChanged);
+        assert_eq!(
+            fs::read_to_string(&data_path).unwrap(),
+            "parent1->\n  link1|VALUE1\n"
+        );
+        cleanup_test_file(&data_path);
+    }
 
-        let mut state = vec![];
-        let mut temp_parent = String::new();
-        let mut temp_links: Vec<(String, String)> = vec![];
-        for line in file_as_str.lines() {
-            if line.ends_with("->") {
-                // This is a parent line
-                // If links not empty but no parent, this is an error
-                no_parent_error(&temp_parent, &temp_links)?;
-                // If temp holders not empty, done with current parent, add to state
-                update_state_reset_temps(&mut temp_parent, &mut temp_links, &mut state);
-                // NOTE: silent error if parent has no links (this is fine, not stored in internal state)
-                temp_parent = line.trim_end_matches("->").to_string();
-                validate_parent(&temp_parent)?;
-            } else if line.contains('|') {
-                // This is a link line
-                // TODO: GH-46 in future, would be nice to support escaped pipes
-                let (link, value) = line
-                    .split_once('|')
-                    .ok_or(TapDataStoreError {
-                        kind: TapDataStoreErrorKind::ParseError,
-                        message: "A link/value line of a data file is expected to contain '|' character separating link and value. For example, google|https://google.com".to_string(),
-                    })?;
-                validate_link(link)?;
-                temp_links.push((link.trim().to_string(), value.trim().to_string()));
-            } else {
-                if line.trim().is_empty() {
-                    continue;
-                }
-                return Err(TapDataStoreError {
-                    kind: TapDataStoreErrorKind::ParseError,
-                    message: format!(
-                        "Unknown format for data file. Line '{line}' does not match expected format of parent ->\\n link|value"
-                    ),
-                });
-            }
-        }
-        // When out of lines, update state
-        no_parent_error(&temp_parent, &temp_links)?;
-        update_state_reset_temps(&mut temp_parent, &mut temp_links, &mut state);
+    #[test]
+    fn test_save_to_file_leaves_no_temp_file() {
+        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
+        fs::write(&data_path, "parent1->\n  link1|value1\n").unwrap();
+        let mut data = Data::new(Some(data_path.clone()), None).unwrap();
+        data.add_link("parent1", "link2", "value2").unwrap();
+        data.save_to_file().unwrap();
+        let file_name = data_path.file_name().unwrap().to_string_lossy();
+        let siblings: Vec<_> = fs::read_dir(data_path.parent().unwrap())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .filter(|name| name.starts_with(&*file_name) && *name != file_name)
+            .collect();
+        assert_eq!(siblings, Vec::<String>::new());
+        cleanup_test_file(&data_path);
+    }
 
-        Ok(state)
+    #[test]
+    fn test_appended() {
+        let old = "a->\n  x|1\n";
+        let stamp = FileStamp::of(old);
+        assert_eq!(appended(stamp, "a->\n  x|1\n  y|2\n"), Some("  y|2\n"));
+        assert_eq!(
+            appended(stamp, "a->\n  x|1\nb->\n  y|2\n"),
+            Some("b->\n  y|2\n")
+        );
+        assert_eq!(appended(stamp, old), None);
+        assert_eq!(appended(stamp, "a->\n  x|2\n  y|2\n"), None);
+        assert_eq!(appended(stamp, "a->\n"), None);
+        assert_eq!(appended(FileStamp::of(""), old), None);
     }
 
-    fn state_to_file_string(&mut self) -> (String, Vec<IndexEntry>) {
-        // Track offsets for fast reads using index file
-        let mut offsets: Vec<IndexEntry> = vec![];
-        // Build return string
-        let mut res = String::new();
+    #[test]
+    fn test_save_to_file_appends_to_the_end() {
+        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
+        fs::write(&data_path, "parent1->\n  link1|value1\n").unwrap();
+        // A handle opened before the save sees what was appended, a file renamed over the data
+        // file would not be the one it reads
+        let mut before = fs::File::open(&data_path).unwrap();
+        let mut data = Data::new(Some(data_path.clone()), None).unwrap();
+        data.add_link("parent2", "link2", "value2").unwrap();
+        data.save_to_file().unwrap();
+        let mut contents = String::new();
+        std::io::Read::read_to_string(&mut before, &mut contents).unwrap();
+        assert_eq!(
+            contents,
+            "parent1->\n  link1|value1\nparent2->\n  link2|value2\n"
+        );
+        // Anything else is written whole
+        data.add_link("parent1", "link3", "value3").unwrap();
+        data.save_to_file().unwrap();
+        assert_eq!(
+            fs::read_to_string(&data_path).unwrap(),
+            data.state_to_file_string().0
+        );
+        cleanup_test_file(&data_path);
+    }
 
-        // Sort state based on parent, then by link
-        self.state.sort_by(|a, b| a.0.trim().cmp(b.0.trim()));
-        self.state.iter_mut().for_each(|(_, links)| {
-            links.sort_by(|a, b| a.0.trim().cmp(b.0.trim()));
-        });
+    /// Counts the bytes read from the wrapped reader
+    struct CountingReader<'a> {
+        inner: &'a [u8],
+        read: &'a std::cell::Cell<usize>,
+    }
 
-        // Build return string & track offsets
-        self.state.iter().for_each(|(parent, links)| {
-            offsets.push((parent.trim().to_string(), res.len()));
+    impl std::io::Read for CountingReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = self.inner.read(buf)?;
+            self.read.set(self.read.get() + n);
+            Ok(n)
+        }
+    }
 
-            res.push_str(&format!("{}->\n", parent.trim()));
-            links.iter().for_each(|(link, value)| {
-                res.push_str(&format!("  {}|{}\n", link.trim(), value.trim()));
-            });
+    #[test]
+    fn test_parse_parent_stops_after_section() {
+        let mut file = String::new();
+        for i in 0..20_000 {
+            file.push_str(&format!(
+                "parent{i:05}->\n  docs|https://docs.example.com/{i}\n  repo|https://github.com/{i}\n"
+            ));
+        }
+        let read = std::cell::Cell::new(0);
+        let reader = std::io::BufReader::new(CountingReader {
+            inner: file.as_bytes(),
+            read: &read,
         });
-        (res, offsets)
+        let state = Data::parse_parent(reader, "parent00010", &mut Metadata::new()).unwrap();
+        assert_eq!(
+            state,
+            vec![(
+                "parent00010".to_string(),
+                vec![
+                    (
+                        "docs".to_string(),
+                        "https://docs.example.com/10".to_string()
+                    ),
+                    ("repo".to_string(), "https://github.com/10".to_string())
+                ]
+            )]
+        );
+        // Only the first buffer of the (over 1 MB) file was read
+        assert!(file.len() > 1024 * 1024);
+        assert!(read.get() <= 8 * 1024, "read {} bytes", read.get());
     }
 
-    fn save_to_file(&mut self) -> Result<Vec<IndexEntry>, TapDataStoreError> {
-        let (str, offsets) = self.state_to_file_string();
-        fs::write(&self.path, str).map_err(|e| TapDataStoreError {
-            kind: TapDataStoreErrorKind::FileWriteFailed,
-            message: format!("Could not write data file: {e}"),
-        })?;
-        Ok(offsets)
+    #[test]
+    fn test_parse_parent_reports_errors_before_section() {
+        let file = "parent1->\n  link1|value1\nnot a valid line\nparent2->\n  link2|value2\n";
+        let res = Data::parse_parent(file.as_bytes(), "parent2", &mut Metadata::new());
+        assert_eq!(res.unwrap_err().kind, TapDataStoreErrorKind::ParseError);
+        // Errors after the section are never read
+        let file = "parent1->\n  link1|value1\nparent2->\nnot a valid line\n";
+        let state = Data::parse_parent(file.as_bytes(), "parent1", &mut Metadata::new()).unwrap();
+        assert_eq!(
+            state,
+            vec![(
+                "parent1".to_string(),
+                vec![("link1".to_string(), "value1".to_string())]
+            )]
+        );
     }
-}
-
-#[cfg(test)]
-mod data_private {
-    use super::{Data, FileType, TapDataStoreErrorKind, get_test_file_path};
-    use std::fs;
-    use std::path::PathBuf;
 
-    fn cleanup_test_file(file_path: &PathBuf) {
-        fs::remove_file(file_path).expect("Could not remove test file");
+    #[test]
+    fn test_parse_parent_missing() {
+        let file = "parent1->\n  link1|value1\n";
+        assert_eq!(
+            Data::parse_parent(file.as_bytes(), "parent2", &mut Metadata::new()).unwrap(),
+            vec![]
+        );
     }
 
     #[test]
-    fn test_parse_file_empty() {
+    fn test_read_parent_streaming() {
         let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
-        fs::write(&data_path, "").unwrap();
-        let res = Data::parse_file(fs::read_to_string(&data_path).unwrap().as_str())
-            .expect("Could not parse file");
-        assert_eq!(res, vec![]);
+        fs::write(&data_path, "a->\n  x|1\nb->\n  y|2\n").unwrap();
+        let data = Data::read_parent_streaming(Some(data_path.clone()), "b").unwrap();
+        assert_eq!(
+            data.get("b", None).unwrap(),
+            vec![("y".to_string(), "2".to_string())]
+        );
+        assert_eq!(
+            data.get("a", None).unwrap_err().kind,
+            TapDataStoreErrorKind::ParentEntityNotFound
+        );
         cleanup_test_file(&data_path);
     }
+
     #[test]
-    fn test_parse_file_valid_one_parent() {
-        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
-        fs::write(&data_path, "parent1->\nlink1|value1\nlink2|value2").unwrap();
-        let res = Data::parse_file(fs::read_to_string(&data_path).unwrap().as_str())
-            .expect("Could not parse file");
+    fn test_compact_file_str_counts_cleanup() {
+        let file_as_str = "parent1->\n  link1|value1\nempty->\nparent2->\n  link2|value2\nparent1->\n  link1|value1\n  link3|value3\n";
+        let (state, summary) =
+            Data::compact_file_str(file_as_str, false, &mut Metadata::new()).unwrap();
+        assert_eq!(state.len(), 2);
+        assert_eq!(summary.duplicates_removed, 1);
+        assert_eq!(summary.parents_merged, 1);
+        assert_eq!(summary.empty_parents_removed, 1);
+        assert_eq!(summary.bytes_before, file_as_str.len());
+        assert_eq!(summary.rejected, vec![]);
+    }
+
+    #[test]
+    fn test_compact_file_str_strict_fails_on_bad_lines() {
+        let res = Data::compact_file_str(
+            "parent1->\n  link1|value1\ngarbage\n",
+            false,
+            &mut Metadata::new(),
+        );
+        assert_eq!(res.unwrap_err().kind, TapDataStoreErrorKind::ParseError);
+    }
+
+    #[test]
+    fn test_compact_file_str_salvage_skips_bad_lines() {
+        let (state, summary) = Data::compact_file_str(
+            "parent1->\n  link1|value1\ngarbage\n",
+            true,
+            &mut Metadata::new(),
+        )
+        .unwrap();
         assert_eq!(
-            res,
+            state,
             vec![(
                 "parent1".to_string(),
-                vec![
-                    ("link1".to_string(), "value1".to_string()),
-                    ("link2".to_string(), "value2".to_string())
-                ]
-            ),]
+                vec![("link1".to_string(), "value1".to_string())]
+            )]
         );
-        cleanup_test_file(&data_path);
+        assert_eq!(summary.duplicates_removed, 0);
+        assert_eq!(summary.rejected.len(), 1);
     }
 
     #[test]
-    fn test_parse_file_valid_two_parents() {
+    fn test_compact_writes_canonical_file() {
         let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
-        fs::write(&data_path, "search engines->\ngoogle|www.google.com\nyahoo|www.yahoo.com\ncoding->\ngh|https://github.com").unwrap();
-        let res = Data::parse_file(fs::read_to_string(&data_path).unwrap().as_str())
-            .expect("Could not parse file");
+        fs::write(
+            &data_path,
+            "parent2 ->\nlink2|value2\nparent1->\n link1 | value1\nparent2->\nlink2|value2\n",
+        )
+        .unwrap();
+        let (mut data, summary) = Data::compact(Some(data_path.clone()), false).unwrap();
+        data.save_to_file().unwrap();
+        let expected = "parent1->\n  link1|value1\nparent2->\n  link2|value2\n";
+        assert_eq!(fs::read_to_string(&data_path).unwrap(), expected);
+        assert_eq!(summary.bytes_after, expected.len());
+        // Compacting again changes nothing
+        let (_, summary) = Data::compact(Some(data_path.clone()), false).unwrap();
         assert_eq!(
-            res,
+            (
+                summary.duplicates_removed,
+                summary.parents_merged,
+                summary.empty_parents_removed,
+                summary.bytes_before,
+                summary.bytes_after
+            ),
+            (0, 0, 0, expected.len(), expected.len())
+        );
+        cleanup_test_file(&data_path);
+    }
+
+    #[test]
+    fn test_parse_file_lenient_valid_file() {
+        let (state, rejected) = Data::parse_file_lenient(
+            "parent1->\nlink1|value1\nparent2->\nlink2|value2\n",
+            &mut Metadata::new(),
+        );
+        assert_eq!(
+            state,
             vec![
                 (
-                    "search engines".to_string(),
-                    vec![
-                        ("google".to_string(), "www.google.com".to_string()),
-                        ("yahoo".to_string(), "www.yahoo.com".to_string())
-                    ]
+                    "parent1".to_string(),
+                    vec![("link1".to_string(), "value1".to_string())]
                 ),
                 (
-                    "coding".to_string(),
-                    vec![("gh".to_string(), "https://github.com".to_string())]
+                    "parent2".to_string(),
+                    vec![("link2".to_string(), "value2".to_string())]
                 ),
             ]
         );
-        cleanup_test_file(&data_path);
+        assert_eq!(rejected, vec![]);
     }
 
     #[test]
-    fn test_parse_file_invalid_links() {
-        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
-        fs::write(
-            &data_path,
-            "search engines->\ngoogle|www.google.com\nyahoo|www.yahoo.com\ninvalid link",
-        )
-        .unwrap();
-        let res = Data::parse_file(fs::read_to_string(&data_path).unwrap().as_str());
-        assert_eq!(res.unwrap_err().kind, TapDataStoreErrorKind::ParseError);
-        cleanup_test_file(&data_path);
+    fn test_parse_file_lenient_skips_bad_lines() {
+        let (state, rejected) = Data::parse_file_lenient(
+            "orphan|value0\nparent1->\nlink1|value1\nnot a valid line\n|->\nlink2|value2\nparent2->\nlink3|value3",
+            &mut Metadata::new(),
+        );
+        assert_eq!(
+            state,
+            vec![
+                (
+                    "parent1".to_string(),
+                    vec![("link1".to_string(), "value1".to_string())]
+                ),
+                (
+                    "parent2".to_string(),
+                    vec![("link3".to_string(), "value3".to_string())]
+                ),
+            ]
+        );
+        assert_eq!(
+            rejected
+                .iter()
+                .map(|r| (r.line_number, r.line.as_str()))
+                .collect::<Vec<(usize, &str)>>(),
+            vec![
+                (1, "orphan|value0"),
+                (4, "not a valid line"),
+                (5, "|->"),
+                (6, "link2|value2"),
+            ]
+        );
     }
 
     #[test]
-    fn test_parse_file_invalid_parent() {
-        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
-        fs::write(&data_path, "invalid parent->\n").unwrap();
-        let res = Data::parse_file(fs::read_to_string(&data_path).unwrap().as_str())
-            .expect("Could not parse file");
-        // Silent error, if parent has no links no big deal
-        assert_eq!(res, vec![]);
-        cleanup_test_file(&data_path);
+    fn test_parse_file_strict_still_fails() {
+        let res = Data::parse_file("parent1->\nlink1|value1\nnot a valid line\n".as_bytes());
+        assert_eq!(res.unwrap_err().kind, TapDataStoreErrorKind::ParseError);
     }
 
     #[test]
-    fn test_parse_file_invalid_random_file() {
+    fn test_salvage_writes_rejected_file() {
         let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
-        fs::write(
-            &data_path,
-            "Something that is completely not a data file was read",
-        )
-        .unwrap();
-        let res = Data::parse_file(fs::read_to_string(&data_path).unwrap().as_str());
-        assert_eq!(res.unwrap_err().kind, TapDataStoreErrorKind::ParseError);
-        cleanup_test_file(&data_path);
+        fs::write(&data_path, "parent1->\nlink1|value1\ngarbage\n").unwrap();
+        let (mut data, rejected) = Data::salvage(Some(data_path.clone())).unwrap();
+        assert_eq!(
+            data.state,
+            vec![(
+                "parent1".to_string(),
+                vec![("link1".to_string(), "value1".to_string())]
+            )]
+        );
+        data.save_rejected(&rejected).unwrap();
+        let rejected_path = data.rejected_path();
+        assert!(rejected_path.to_str().unwrap().ends_with(".rejected"));
+        assert_eq!(fs::read_to_string(&rejected_path).unwrap(), "garbage\n");
+        cleanup_test_file(&rejected_path);
+        data.cleanup().expect("Could not clean up data store");
     }
 
     #[test]
@@ -1110,22 +5004,10 @@ mod data_private {
     fn test_state_to_file_string_sorted() {
         let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
         let mut data = Data::new(Some(data_path), None).unwrap();
-        data.state = vec![
-            (
-                "parent1".to_string(),
-                vec![("link1".to_string(), "value1".to_string())],
-            ),
-            (
-                "apple".to_string(),
-                vec![
-                    ("homepage".to_string(), "www.apple.com".to_string()),
-                    (
-                        "dev".to_string(),
-                        "https://developer.apple.com/".to_string(),
-                    ),
-                ],
-            ),
-        ];
+        data.add_link("parent1", "link1", "value1").unwrap();
+        data.add_link("apple", "homepage", "www.apple.com").unwrap();
+        data.add_link("apple", "dev", "https://developer.apple.com/")
+            .unwrap();
         let res = data.state_to_file_string();
         assert_eq!(
             res.0,
@@ -1138,6 +5020,93 @@ mod data_private {
         data.cleanup().expect("Could not clean up data store");
     }
 
+    #[test]
+    fn test_state_is_sorted_after_parse() {
+        let data = Data::in_memory(
+            Data::parse_file("b->\n  y|2\n  x|1\na->\n  z|3\n".as_bytes()).unwrap(),
+        );
+        assert_eq!(
+            data.state,
+            vec![
+                ("a".to_string(), vec![("z".to_string(), "3".to_string())]),
+                (
+                    "b".to_string(),
+                    vec![
+                        ("x".to_string(), "1".to_string()),
+                        ("y".to_string(), "2".to_string())
+                    ]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sorted_insert_matches_sorting_on_save() {
+        // Serializes the state the way saving did before the state was kept sorted
+        fn sort_then_serialize(state: &[(String, Vec<(String, String)>)]) -> String {
+            let mut state = state.to_vec();
+            state.sort_by(|a, b| a.0.trim().cmp(b.0.trim()));
+            state.iter_mut().for_each(|(_, links)| {
+                links.sort_by(|a, b| a.0.trim().cmp(b.0.trim()));
+            });
+            state
+                .iter()
+                .map(|(parent, links)| {
+                    let links: String = links
+                        .iter()
+                        .map(|(l, v)| format!("  {}|{}\n", l.trim(), v.trim()))
+                        .collect();
+                    format!("{}->\n{links}", parent.trim())
+                })
+                .collect()
+        }
+
+        let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
+        let mut data = Data::new(Some(data_path.clone()), None).unwrap();
+        let mut appended: Vec<(String, Vec<(String, String)>)> = vec![];
+        let ops = [
+            ("zebra", "stripes", "https://zebra.com"),
+            ("apple", "homepage", "www.apple.com"),
+            ("mango", "b", "2"),
+            ("apple", "dev", "https://developer.apple.com/"),
+            ("mango", "a", "1"),
+            ("mango", "c", "3"),
+            ("banana", "z", "26"),
+            (" apple", "aaa", "first"),
+            ("zebra", "a", "first"),
+        ];
+        for (i, (parent, link, value)) in ops.into_iter().enumerate() {
+            data.add_link(parent, link, value).unwrap();
+            match appended.iter_mut().find(|(p, _)| p.trim() == parent.trim()) {
+                Some((_, links)) => links.push((link.to_string(), value.to_string())),
+                None => appended.push((
+                    parent.to_string(),
+                    vec![(link.to_string(), value.to_string())],
+                )),
+            }
+            if i % 2 == 0 {
+                data.save_to_file().unwrap();
+                assert_eq!(
+                    fs::read_to_string(&data_path).unwrap(),
+                    sort_then_serialize(&appended)
+                );
+            }
+        }
+        data.upsert_link("mango", "b", "two").unwrap();
+        data.upsert_link("cherry", "pie", "yum").unwrap();
+        data.save_to_file().unwrap();
+        appended[2].1[0].1 = "two".to_string();
+        appended.push((
+            "cherry".to_string(),
+            vec![("pie".to_string(), "yum".to_string())],
+        ));
+        assert_eq!(
+            fs::read_to_string(&data_path).unwrap(),
+            sort_then_serialize(&appended)
+        );
+        cleanup_test_file(&data_path);
+    }
+
     #[test]
     fn test_save_to_file() {
         let data_path = get_test_file_path(FileType::Data).expect("Could not get test file path");
@@ -1165,35 +5134,27 @@ impl Data {
     }
 }
 
+/// The index of a data file. Offsets are byte offsets into the UTF-8 encoded data file (not
+/// character offsets), pointing at the start of the parent's `parent->` line.
 pub struct Index {
     path: PathBuf,
     state: Vec<IndexEntry>, // parent, offset
+    /// When set, the state is never read from or written to `path`
+    in_memory: bool,
 }
 
 // Publicly exposed
 impl Index {
     pub fn new(path: Option<PathBuf>) -> Result<Self, TapDataStoreError> {
-        let (file_exists, path) = if let Some(path) = path {
-            (path.exists(), path)
-        } else {
-            let mut tap_data_path = get_parent_dir_of_tap()?;
+        Index::open(path, true)
+    }
 
-            // NOTE: workaround for command tests running at the same time.
-            // Use test pathing for tests, otherwise use standard
-            // I want to use this over cfg!(test) as I do not want to compile test code in prod builds
-            #[allow(unused_mut)]
-            let mut test_path: Option<PathBuf> = None;
-            #[cfg(test)]
-            {
-                test_path = Some(get_test_file_path(FileType::Index)?);
-            }
-            if let Some(test_path) = test_path {
-                tap_data_path = test_path;
-            } else {
-                tap_data_path = tap_data_path.join(".tap_index");
-            }
-            (tap_data_path.exists(), tap_data_path)
-        };
+    /// Loads the index file like `Index::new`. When the file does not exist, it is only created
+    /// if `create_if_missing` is set; otherwise the index is empty and nothing is written to disk.
+    pub fn open(path: Option<PathBuf>, create_if_missing: bool) -> Result<Self, TapDataStoreError> {
+        let path = Index::resolve_path(path)?;
+        let file_exists = path.exists();
+        log::verbose(format_args!("Loading index file {}", path.display()));
 
         // Parse file if it exists
         if file_exists {
@@ -1201,22 +5162,58 @@ impl Index {
                 kind: TapDataStoreErrorKind::FileReadFailed,
                 message: format!("Could not read index file at {}: {e}", path.display()),
             })?;
-            let state = Index::parse_file(&file_as_str)?;
-            Ok(Self { path, state })
+            let mut state = Index::parse_file(&file_as_str)?;
+            Index::sort_state(&mut state);
+            Ok(Self {
+                path,
+                state,
+                in_memory: false,
+            })
         } else {
-            File::create_new(&path).map_err(|e| TapDataStoreError {
-                kind: TapDataStoreErrorKind::FileCreateFailed,
-                message: format!("Could not create index file: {e}"),
-            })?;
+            if create_if_missing {
+                File::create_new(&path).map_err(|e| TapDataStoreError {
+                    kind: TapDataStoreErrorKind::FileCreateFailed,
+                    message: format!("Could not create index file: {e}"),
+                })?;
+            }
             Ok(Self {
                 path,
                 state: vec![],
+                in_memory: false,
             })
         }
     }
 
+    #[allow(dead_code)]
+    pub fn in_memory() -> Self {
+        Self {
+            path: PathBuf::new(),
+            state: vec![],
+            in_memory: true,
+        }
+    }
+
+    /// Replaces the entries of the index, sorting them by parent if needed so that lookups can
+    /// binary search
     fn update(&mut self, offsets: Vec<IndexEntry>) {
-        self.state = offsets
+        self.state = offsets;
+        Index::sort_state(&mut self.state);
+    }
+
+    /// Binary searches the index for the entry of `parent`
+    pub fn lookup(&self, parent: &str) -> Option<&IndexEntry> {
+        self.state
+            .binary_search_by(|(p, _)| p.trim().cmp(parent.trim()))
+            .ok()
+            .map(|idx| &self.state[idx])
+    }
+
+    /// Returns the entries whose parent starts with `prefix`, in sorted order
+    #[allow(dead_code)]
+    pub fn entries_with_prefix(&self, prefix: &str) -> &[IndexEntry] {
+        let start = self.state.partition_point(|(p, _)| p.trim() < prefix);
+        let len = self.state[start..].partition_point(|(p, _)| p.trim().starts_with(prefix));
+        &self.state[start..start + len]
     }
 
     pub fn parents(&self) -> Vec<String> {
@@ -1278,34 +5275,119 @@ mod index_public {
         );
         index.cleanup().expect("Could not clean up index store");
     }
+
+    fn fruit_index() -> Index {
+        let mut index = Index::in_memory();
+        index.update(vec![
+            ("banana".to_string(), 20),
+            ("apple".to_string(), 0),
+            ("cherry".to_string(), 60),
+            ("blueberry".to_string(), 40),
+        ]);
+        index
+    }
+
+    #[test]
+    fn test_update_state_unsorted() {
+        assert_eq!(
+            fruit_index().state,
+            vec![
+                ("apple".to_string(), 0),
+                ("banana".to_string(), 20),
+                ("blueberry".to_string(), 40),
+                ("cherry".to_string(), 60),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lookup_exact_match() {
+        let index = fruit_index();
+        assert_eq!(index.lookup("banana"), Some(&("banana".to_string(), 20)));
+        assert_eq!(index.lookup(" banana "), Some(&("banana".to_string(), 20)));
+    }
+
+    #[test]
+    fn test_lookup_first_and_last() {
+        let index = fruit_index();
+        assert_eq!(index.lookup("apple"), Some(&("apple".to_string(), 0)));
+        assert_eq!(index.lookup("cherry"), Some(&("cherry".to_string(), 60)));
+    }
+
+    #[test]
+    fn test_lookup_missing() {
+        let index = fruit_index();
+        assert_eq!(index.lookup("ban"), None);
+        assert_eq!(index.lookup("aardvark"), None);
+        assert_eq!(index.lookup("zucchini"), None);
+        assert_eq!(Index::in_memory().lookup("apple"), None);
+    }
+
+    #[test]
+    fn test_entries_with_prefix() {
+        let index = fruit_index();
+        assert_eq!(
+            index.entries_with_prefix("b"),
+            &[("banana".to_string(), 20), ("blueberry".to_string(), 40)]
+        );
+        assert_eq!(
+            index.entries_with_prefix("apple"),
+            &[("apple".to_string(), 0)]
+        );
+        assert_eq!(
+            index.entries_with_prefix("ch"),
+            &[("cherry".to_string(), 60)]
+        );
+        assert_eq!(index.entries_with_prefix("").len(), 4);
+        assert!(index.entries_with_prefix("d").is_empty());
+        assert!(index.entries_with_prefix("apples").is_empty());
+    }
 }
 
 // Privately exposed
 impl Index {
+    /// Returns the given path, or the index file of the default options when none is given, see
+    /// `StoreOptions::paths`
+    fn resolve_path(path: Option<PathBuf>) -> Result<PathBuf, TapDataStoreError> {
+        match path {
+            Some(path) => Ok(path),
+            None => Ok(StoreOptions::default().paths()?.1),
+        }
+    }
+
+    /// Creates an empty index at the given (or default) path without reading the existing file.
+    /// Used when the index is about to be rebuilt from the data file anyway.
+    fn empty(path: Option<PathBuf>) -> Result<Self, TapDataStoreError> {
+        Ok(Self {
+            path: Index::resolve_path(path)?,
+            state: vec![],
+            in_memory: false,
+        })
+    }
+
     /// If the index goes to the end of the file, return 0 for length. This indicates to
     /// the caller that they need to make a buffer large enough to read to EOF.
     fn find_parent_offset_and_length(
         &self,
         parent: String,
     ) -> Result<IndexOffsetLength, TapDataStoreError> {
-        let elem = self
+        let (_, offset) = self.lookup(&parent).ok_or(TapDataStoreError {
+            kind: TapDataStoreErrorKind::ParseError,
+            message: format!("Could not find parent '{parent}' in index"),
+        })?;
+        // The data file is sorted like the index, so the section ends where the next one starts
+        let next = self
             .state
-            .iter()
-            .position(|(p, _)| p.trim() == parent.trim());
-        match elem {
-            Some(elem) => {
-                if elem == self.state.len() - 1 {
-                    return Ok((self.state[elem].1, 0));
-                }
-                Ok((
-                    self.state[elem].1,
-                    self.state[elem + 1].1 - self.state[elem].1,
-                ))
-            }
-            None => Err(TapDataStoreError {
-                kind: TapDataStoreErrorKind::ParseError,
-                message: format!("Could not find parent '{parent}' in index"),
-            }),
+            .partition_point(|(p, _)| p.trim() <= parent.trim());
+        match self.state.get(next) {
+            Some((_, next_offset)) => Ok((*offset, next_offset - offset)),
+            None => Ok((*offset, 0)),
+        }
+    }
+
+    fn sort_state(state: &mut [IndexEntry]) {
+        if !state.is_sorted_by(|a, b| a.0.trim() <= b.0.trim()) {
+            state.sort_by(|a, b| a.0.trim().cmp(b.0.trim()));
         }
     }
 
@@ -1350,6 +5432,9 @@ impl Index {
 
     fn save_to_file(&mut self) -> Result<(), TapDataStoreError> {
         let str = self.state_to_file_string();
+        if self.in_memory {
+            return Ok(());
+        }
         fs::write(&self.path, str).map_err(|e| TapDataStoreError {
             kind: TapDataStoreErrorKind::FileWriteFailed,
             message: format!("Could not write index file: {e}"),
@@ -1428,6 +5513,42 @@ mod index_private {
         cleanup_test_file(&index_path);
     }
 
+    #[test]
+    fn test_find_parent_offset_and_length() {
+        let mut index = Index::in_memory();
+        index.update(vec![
+            ("b".to_string(), 10),
+            ("a".to_string(), 0),
+            ("c".to_string(), 25),
+        ]);
+        assert_eq!(
+            index
+                .find_parent_offset_and_length("a".to_string())
+                .unwrap(),
+            (0, 10)
+        );
+        assert_eq!(
+            index
+                .find_parent_offset_and_length("b".to_string())
+                .unwrap(),
+            (10, 15)
+        );
+        // The last parent is read to the end of the file
+        assert_eq!(
+            index
+                .find_parent_offset_and_length("c".to_string())
+                .unwrap(),
+            (25, 0)
+        );
+        assert_eq!(
+            index
+                .find_parent_offset_and_length("d".to_string())
+                .unwrap_err()
+                .kind,
+            TapDataStoreErrorKind::ParseError
+        );
+    }
+
     #[test]
     fn test_state_to_file_string_empty() {
         let index_path = get_test_file_path(FileType::Index).expect("Could not get test file path");
@@ -1485,7 +5606,318 @@ impl Index {
 }
 
 // Utils
-/// Returns the parent directory of the current executable.
+/// The directory holding the data files, decided once per run so that files never split across
+/// directories, see `get_data_dir`
+static DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Returns the directory holding the data files (and any other file tap stores). This is the
+/// `data_dir` set in the config file by `tap --migrate-data`, if any. Otherwise it is the
+/// directory of the executable, unless it is not writable (e.g. `/usr/local/bin` or a Nix store
+/// path) and holds no data file yet, in which case a per-user data directory is used instead.
+/// ## Errors
+/// - See `get_parent_dir_of_tap`
+/// - `TapDataStoreErrorKind::ConfigFailed` - if unable to read the config file
+/// - `TapDataStoreErrorKind::FileCreateFailed` - if unable to create the per-user data directory
+fn get_data_dir() -> Result<PathBuf, TapDataStoreError> {
+    if let Some(data_dir) = DATA_DIR.get() {
+        return Ok(data_dir.clone());
+    }
+    let (data_dir, source) = locate_data_dir()?;
+    match source {
+        PathSource::Config => log::verbose(format_args!(
+            "Using data directory {} from the config file",
+            data_dir.display()
+        )),
+        PathSource::Env | PathSource::Xdg if !data_dir.join(".tap_data").exists() => {
+            fs::create_dir_all(&data_dir).map_err(|e| TapDataStoreError {
+                kind: TapDataStoreErrorKind::FileCreateFailed,
+                message: format!(
+                    "Could not create data directory {}: {e}",
+                    data_dir.display()
+                ),
+            })?;
+            log::note(format_args!(
+                "{} is not writable, so tap stores its data files in {} instead",
+                get_parent_dir_of_tap()?.display(),
+                data_dir.display()
+            ));
+        }
+        _ => {}
+    }
+    Ok(DATA_DIR.get_or_init(|| data_dir).clone())
+}
+
+/// Decides the directory holding the data files like `get_data_dir` does, without creating it
+fn locate_data_dir() -> Result<(PathBuf, PathSource), TapDataStoreError> {
+    if let Some(config_path) = default_config_path() {
+        let config = Config::load(Some(config_path)).map_err(config_error)?;
+        if let Some(data_dir) = config.get(DATA_DIR_CONFIG_KEY) {
+            return Ok((PathBuf::from(data_dir), PathSource::Config));
+        }
+    }
+    let exe_dir = get_parent_dir_of_tap()?;
+    match user_data_dir() {
+        Some(user_dir) if choose_data_dir(&exe_dir, &user_dir, is_dir_writable) == user_dir => {
+            Ok((user_dir, xdg_source("XDG_DATA_HOME")))
+        }
+        _ => Ok((exe_dir, PathSource::ExeDir)),
+    }
+}
+
+/// Why a file tap uses is where it is, see `StoreOptions::resolve_paths`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum PathSource {
+    /// The `--file` or `--index` flag
+    Flag,
+    /// An `XDG_*_HOME` environment variable
+    Env,
+    /// The `data_dir` of the config file, set by `tap --migrate-data`
+    Config,
+    /// The XDG default under the home directory, used when no `XDG_*_HOME` variable is set
+    Xdg,
+    /// The directory of the tap executable
+    ExeDir,
+}
+
+impl fmt::Display for PathSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PathSource::Flag => write!(f, "flag"),
+            PathSource::Env => write!(f, "env"),
+            PathSource::Config => write!(f, "config"),
+            PathSource::Xdg => write!(f, "XDG"),
+            PathSource::ExeDir => write!(f, "exe-dir"),
+        }
+    }
+}
+
+/// `PathSource::Env` when the XDG variable `var` holds an absolute path, which is when it is
+/// used, and `PathSource::Xdg` when its default under the home directory is used
+pub(crate) fn xdg_source(var: &str) -> PathSource {
+    match std::env::var_os(var).is_some_and(|p| Path::new(&p).is_absolute()) {
+        true => PathSource::Env,
+        false => PathSource::Xdg,
+    }
+}
+
+/// Chooses between the directory of the executable and the per-user data directory. An existing
+/// data file always wins, so a store is never split across the two directories.
+fn choose_data_dir(
+    exe_dir: &Path,
+    user_dir: &Path,
+    is_writable: impl Fn(&Path) -> bool,
+) -> PathBuf {
+    if exe_dir.join(".tap_data").exists() {
+        exe_dir.to_path_buf()
+    } else if user_dir.join(".tap_data").exists() || !is_writable(exe_dir) {
+        user_dir.to_path_buf()
+    } else {
+        exe_dir.to_path_buf()
+    }
+}
+
+/// The per-user data directory, `$XDG_DATA_HOME/tap` or `~/.local/share/tap`
+fn user_data_dir() -> Option<PathBuf> {
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share"))
+        })?;
+    Some(data_home.join("tap"))
+}
+
+/// Checks whether files can be created in `dir` by creating (and removing) a probe file
+fn is_dir_writable(dir: &Path) -> bool {
+    let probe = dir.join(format!(".tap_write_probe_{}", std::process::id()));
+    match fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&probe)
+    {
+        Ok(_) => fs::remove_file(&probe).is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// The config key pointing at the directory the data files were migrated to
+const DATA_DIR_CONFIG_KEY: &str = "data_dir";
+
+fn config_error(e: ConfigError) -> TapDataStoreError {
+    TapDataStoreError {
+        kind: TapDataStoreErrorKind::ConfigFailed,
+        message: e.to_string(),
+    }
+}
+
+/// Moves the data files from the current data directory to `dest_dir` (the per-user data
+/// directory if not given), see `migrate_data_dir`. Returns the old and new data directories.
+/// ## Errors
+/// - `TapDataStoreErrorKind::ReadOnly` - if `options` are read-only
+/// - `TapDataStoreErrorKind::ConfigFailed` - if unable to load the config file
+/// - See `get_data_dir` and `migrate_data_dir`
+pub(crate) fn migrate_data(
+    options: &StoreOptions,
+    dest_dir: Option<PathBuf>,
+    force: bool,
+) -> Result<(PathBuf, PathBuf), TapDataStoreError> {
+    options.check_writable()?;
+    let src_dir = get_data_dir()?;
+    let dest_dir = match dest_dir {
+        Some(dest_dir) => dest_dir,
+        None => user_data_dir().ok_or(TapDataStoreError {
+            kind: TapDataStoreErrorKind::FileCreateFailed,
+            message: "Could not find the per-user data directory, set XDG_DATA_HOME or HOME or pass a destination directory".to_string(),
+        })?,
+    };
+    let mut config = Config::load(None).map_err(config_error)?;
+    migrate_data_dir(&src_dir, &dest_dir, &mut config, force)?;
+    Ok((src_dir, dest_dir))
+}
+
+/// Copies the data files of every profile from `src_dir` to `dest_dir`, the data and index
+/// files with the files kept next to them (see `sidecar_paths`), checks that the copied data and
+/// index files can be parsed, points `data_dir` of the config at `dest_dir`, and only then
+/// removes the originals. Profiles other than the default one keep their directory of their own,
+/// see `profile_dir`. If any step before the removal fails, the original files are left
+/// untouched.
+/// ## Errors
+/// - `TapDataStoreErrorKind::DataStoreNotFound` - if `src_dir` has no data file in any profile
+/// - `TapDataStoreErrorKind::DataStoreAlreadyExists` - if `dest_dir` is `src_dir`, or already
+///   holds a non-empty data file of one of the profiles and `force` is not set
+/// - `TapDataStoreErrorKind::FileCreateFailed` - if unable to create `dest_dir`
+/// - `TapDataStoreErrorKind::FileWriteFailed` - if unable to copy or move a file
+/// - `TapDataStoreErrorKind::ParseError` - if a copied file can not be parsed
+/// - `TapDataStoreErrorKind::ConfigFailed` - if unable to save the config file
+/// - `TapDataStoreErrorKind::FileDeleteFailed` - if unable to remove an original file
+fn migrate_data_dir(
+    src_dir: &Path,
+    dest_dir: &Path,
+    config: &mut Config,
+    force: bool,
+) -> Result<(), TapDataStoreError> {
+    let profiles = profiles_in(src_dir);
+    if profiles.is_empty() {
+        return Err(TapDataStoreError {
+            kind: TapDataStoreErrorKind::DataStoreNotFound,
+            message: format!("No data file found in {}", src_dir.display()),
+        });
+    }
+    if fs::canonicalize(dest_dir).ok() == fs::canonicalize(src_dir).ok() {
+        return Err(TapDataStoreError {
+            kind: TapDataStoreErrorKind::DataStoreAlreadyExists,
+            message: format!(
+                "The data files are already stored in {}",
+                dest_dir.display()
+            ),
+        });
+    }
+    for profile in profiles.iter() {
+        let dest_data = profile_dir(dest_dir, profile).join(".tap_data");
+        if !force && fs::metadata(&dest_data).is_ok_and(|m| m.len() > 0) {
+            return Err(TapDataStoreError {
+                kind: TapDataStoreErrorKind::DataStoreAlreadyExists,
+                message: format!(
+                    "{} already holds a data file, pass --force to overwrite it",
+                    dest_data.parent().unwrap_or(dest_dir).display()
+                ),
+            });
+        }
+    }
+
+    // Every file of every profile, as (original, copy next to the destination, destination)
+    let mut files: Vec<(PathBuf, PathBuf, PathBuf)> = vec![];
+    for profile in profiles.iter() {
+        let (src, dest) = (
+            profile_dir(src_dir, profile),
+            profile_dir(dest_dir, profile),
+        );
+        fs::create_dir_all(&dest).map_err(|e| TapDataStoreError {
+            kind: TapDataStoreErrorKind::FileCreateFailed,
+            message: format!("Could not create directory {}: {e}", dest.display()),
+        })?;
+        let data = src.join(".tap_data");
+        let paths = [data.clone(), index_path_for(&data)]
+            .into_iter()
+            .chain(sidecar_paths(&data).into_iter().map(|(_, path)| path));
+        for path in paths.filter(|path| path.is_file()) {
+            let file_name = path.file_name().expect("Data file names are set above");
+            let mut tmp_name = file_name.to_os_string();
+            tmp_name.push(".migrating");
+            files.push((path.clone(), dest.join(tmp_name), dest.join(file_name)));
+        }
+    }
+
+    // Copy next to the destination first, so a failed copy never leaves a half written store
+    let remove_copies = || {
+        for (_, tmp, _) in files.iter() {
+            let _ = fs::remove_file(tmp);
+        }
+    };
+    for (src, tmp, _) in files.iter() {
+        if let Err(e) = fs::copy(src, tmp) {
+            remove_copies();
+            return Err(TapDataStoreError {
+                kind: TapDataStoreErrorKind::FileWriteFailed,
+                message: format!("Could not copy {} to {}: {e}", src.display(), tmp.display()),
+            });
+        }
+    }
+    let verified = files.iter().try_for_each(|(src, tmp, _)| {
+        match src.file_name().and_then(|name| name.to_str()) {
+            Some(".tap_data") => Data::open(Some(tmp.clone()), None, false).map(|_| ()),
+            Some(".tap_index") => Index::open(Some(tmp.clone()), false).map(|_| ()),
+            _ => Ok(()),
+        }
+    });
+    if let Err(e) = verified {
+        remove_copies();
+        return Err(e);
+    }
+    for (_, tmp, dest) in files.iter() {
+        if let Err(e) = fs::rename(tmp, dest) {
+            remove_copies();
+            return Err(TapDataStoreError {
+                kind: TapDataStoreErrorKind::FileWriteFailed,
+                message: format!(
+                    "Could not move {} to {}: {e}",
+                    tmp.display(),
+                    dest.display()
+                ),
+            });
+        }
+    }
+
+    config.set(DATA_DIR_CONFIG_KEY, &dest_dir.display().to_string());
+    if let Err(e) = config.save() {
+        for (_, _, dest) in files.iter() {
+            let _ = fs::remove_file(dest);
+        }
+        return Err(config_error(e));
+    }
+
+    for (src, _, _) in files.iter() {
+        fs::remove_file(src).map_err(|e| TapDataStoreError {
+            kind: TapDataStoreErrorKind::FileDeleteFailed,
+            message: format!(
+                "The data files were copied to {}, but {} could not be removed: {e}",
+                dest_dir.display(),
+                src.display()
+            ),
+        })?;
+    }
+    // The directories of other profiles are left behind empty, unless something else is in them
+    for profile in profiles.iter().filter(|p| *p != DEFAULT_PROFILE) {
+        let _ = fs::remove_dir(profile_dir(src_dir, profile));
+    }
+    Ok(())
+}
+
+/// Set once the migration hint of `get_parent_dir_of_tap` was printed, so it is only shown once
+static MIGRATION_HINT_SHOWN: AtomicBool = AtomicBool::new(false);
+
+/// Returns the parent directory of the current executable. Symlinks to the executable are
+/// resolved, so the data files are always found next to the real executable.
 /// ## Errors
 /// - `TapDataStoreErrorKind::ExecutablePathNotFound` - if unable to get current executable path
 /// - `TapDataStoreErrorKind::ExecutablePathParentDirectoryNotFound` - if unable to get parent directory
@@ -1494,55 +5926,683 @@ fn get_parent_dir_of_tap() -> Result<PathBuf, TapDataStoreError> {
         kind: TapDataStoreErrorKind::ExecutablePathNotFound,
         message: e.to_string(),
     })?;
+    let parent_dir = get_parent_dir_of_executable(&executable_path)?;
+    if let Some(hint) = migration_hint(&executable_path, &parent_dir)
+        && !MIGRATION_HINT_SHOWN.swap(true, Ordering::Relaxed)
+    {
+        log::note(format_args!("{hint}"));
+    }
+    Ok(parent_dir)
+}
+
+/// Returns the parent directory of the executable after resolving symlinks. If the path can not
+/// be canonicalized, the parent directory of the path as given is used instead.
+/// ## Errors
+/// - `TapDataStoreErrorKind::ExecutablePathParentDirectoryNotFound` - if unable to get parent directory
+fn get_parent_dir_of_executable(executable_path: &Path) -> Result<PathBuf, TapDataStoreError> {
+    let executable_path =
+        fs::canonicalize(executable_path).unwrap_or_else(|_| executable_path.to_path_buf());
     Ok(executable_path
         .parent()
         .ok_or(TapDataStoreError {
             kind: TapDataStoreErrorKind::ExecutablePathParentDirectoryNotFound,
-            message: "".to_string(),
+            message: format!(
+                "Could not get parent directory of executable at {}",
+                executable_path.display()
+            ),
         })?
         .to_path_buf())
 }
 
-/// Check if the parent name is valid
+/// Earlier versions stored the data files next to the symlink tap was invoked through. When a
+/// data file exists there but not next to the real executable, returns a hint on moving it.
+fn migration_hint(executable_path: &Path, parent_dir: &Path) -> Option<String> {
+    let symlink_dir = executable_path.parent()?;
+    if symlink_dir == parent_dir
+        || !symlink_dir.join(".tap_data").exists()
+        || parent_dir.join(".tap_data").exists()
+    {
+        return None;
+    }
+    Some(format!(
+        "Found .tap_data in {}, but tap now stores its data next to the real executable in {}. Move .tap_data and .tap_index there to keep using your links.",
+        symlink_dir.display(),
+        parent_dir.display()
+    ))
+}
+
+/// Checks that `contents` parses as a data file, returning its parent entities and links
+/// ## Errors
+/// - `TapDataStoreErrorKind::ParseError` - listing every line that can not be parsed with its line
+///   number
+pub(crate) fn check_data_file(contents: &str) -> Result<Vec<ParentLinks>, TapDataStoreError> {
+    let e = match Data::parse_file(contents.as_bytes()) {
+        Ok(state) => return Ok(state),
+        Err(e) => e,
+    };
+    let (_, rejected) = Data::parse_file_lenient(contents, &mut Metadata::new());
+    if rejected.is_empty() {
+        return Err(e);
+    }
+    Err(TapDataStoreError {
+        kind: TapDataStoreErrorKind::ParseError,
+        message: rejected
+            .iter()
+            .map(|r| {
+                format!(
+                    "line {}: {} ({})",
+                    r.line_number,
+                    r.line.trim(),
+                    r.reason.trim()
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n"),
+    })
+}
+
+// The data file as a whole, replaced and put back outside of `DataStoreHandle::write`
+impl DataStoreHandle {
+    /// Replaces the data file with `contents` and rebuilds the index. The contents are checked
+    /// with `check_data_file` first, then written next to the data file and renamed over it, so
+    /// the data file is never left half written. The replaced contents are recorded for
+    /// `tap --undo`.
+    pub fn replace_data_file(&self, contents: &str) -> Result<(), TapDataStoreError> {
+        let original = read_data_file_at(&self.data_path()?)?;
+        self.replace_unchanged_data_file(original.as_deref(), contents)
+    }
+
+    /// Replaces the data file like `replace_data_file`, but only when it still holds `original`, the
+    /// contents it had when it was read (`None` when there was no data file). Anything that changed
+    /// it in the meantime is not overwritten, a `DataFileChanged` error is returned instead.
+    pub fn replace_unchanged_data_file(
+        &self,
+        original: Option<&str>,
+        contents: &str,
+    ) -> Result<(), TapDataStoreError> {
+        self.options.check_writable()?;
+        check_data_file(contents)?;
+        let (path, index) = self.options.paths()?;
+        let before = read_data_file_at(&path)?;
+        if before.as_deref() != original {
+            return Err(TapDataStoreError {
+                kind: TapDataStoreErrorKind::DataFileChanged,
+                message: format!(
+                    "The data file {} was changed by something else since it was read, it was not replaced",
+                    path.display()
+                ),
+            });
+        }
+        write_data_file(&path, contents)?;
+        if let Some(before) = before {
+            record_undo(
+                &path,
+                &before,
+                Some(FileStamp::of(contents)),
+                &[],
+                &self.options,
+            );
+        }
+        DataStore::rebuild_index(path, index).map(|_| ())
+    }
+
+    /// Puts back the data file exactly as it was before the last change and indexes it. The
+    /// snapshot is removed, so undoing twice does not flip back and forth between the two
+    /// versions. `None` when there is nothing to undo.
+    pub fn undo(&self) -> Result<Option<Undone>, TapDataStoreError> {
+        self.options.check_writable()?;
+        let (path, index) = self.options.paths()?;
+        let undo = undo_path_for(&path);
+        if !undo.exists() {
+            return Ok(None);
+        }
+        let archive = fs::read_to_string(&undo).map_err(|e| TapDataStoreError {
+            kind: TapDataStoreErrorKind::FileReadFailed,
+            message: format!("Could not read {}: {e}", undo.display()),
+        })?;
+        let archive = Archive::parse(&archive).map_err(|e| TapDataStoreError {
+            kind: TapDataStoreErrorKind::ParseError,
+            message: format!("{} is corrupt. {e}", undo.display()),
+        })?;
+        let Some(restored) = archive.file("data") else {
+            return Err(TapDataStoreError {
+                kind: TapDataStoreErrorKind::ParseError,
+                message: format!("{} has no data file", undo.display()),
+            });
+        };
+        let replaced = read_data_file_at(&path)?.unwrap_or_default();
+        write_data_file(&path, restored)?;
+        let mut index = Index::empty(Some(index))?;
+        index.update(index_entries(restored));
+        index.save_to_file()?;
+        untrash_restored(&path, restored, &replaced)?;
+        if let Some(untrashed) = archive.file("trash") {
+            retrash(&path, untrashed)?;
+        }
+        fs::remove_file(&undo).map_err(|e| TapDataStoreError {
+            kind: TapDataStoreErrorKind::FileDeleteFailed,
+            message: format!("Could not remove {}: {e}", undo.display()),
+        })?;
+        Ok(Some(Undone {
+            operation: archive.file("operation").map(str::to_string),
+            restored: restored.to_string(),
+            replaced,
+        }))
+    }
+}
+
+/// Writes `contents` next to the data file at `path` and renames it over the data file, so it is
+/// either replaced whole or not at all
+fn write_data_file(path: &Path, contents: &str) -> Result<(), TapDataStoreError> {
+    let file_name = path
+        .file_name()
+        .map_or(".tap_data".into(), |name| name.to_string_lossy());
+    let tmp = path.with_file_name(format!("{file_name}.saving"));
+    let write_failed = |e: std::io::Error| TapDataStoreError {
+        kind: TapDataStoreErrorKind::FileWriteFailed,
+        message: format!("Could not write data file {}: {e}", path.display()),
+    };
+    fs::write(&tmp, contents).map_err(write_failed)?;
+    if let Err(e) = fs::rename(&tmp, path) {
+        let _ = fs::remove_file(&tmp);
+        return Err(write_failed(e));
+    }
+    Ok(())
+}
+
+/// The part of `contents` that follows the contents with the stamp `on_disk`, when `contents` only
+/// adds to their end
+fn appended(on_disk: FileStamp, contents: &str) -> Option<&str> {
+    let len = usize::try_from(on_disk.len).ok()?;
+    if len == 0 || len >= contents.len() || !contents.is_char_boundary(len) {
+        return None;
+    }
+    (FileStamp::of(&contents[..len]) == on_disk).then(|| &contents[len..])
+}
+
+/// Appends `tail` to the data file at `path`, which is `len` bytes long. A failed append is cut
+/// off again, so the file is left as it was.
+fn append_data_file(path: &Path, len: u64, tail: &str) -> Result<(), TapDataStoreError> {
+    let write_failed = |e: std::io::Error| TapDataStoreError {
+        kind: TapDataStoreErrorKind::FileWriteFailed,
+        message: format!("Could not write data file {}: {e}", path.display()),
+    };
+    let mut f = fs::OpenOptions::new()
+        .append(true)
+        .open(path)
+        .map_err(|e| TapDataStoreError {
+            kind: TapDataStoreErrorKind::FileOpenFailed,
+            message: format!("Could not open data file at {}: {e}", path.display()),
+        })?;
+    if let Err(e) = f.write_all(tail.as_bytes()).and_then(|_| f.sync_data()) {
+        let _ = f.set_len(len);
+        return Err(write_failed(e));
+    }
+    Ok(())
+}
+
+/// The error of a save that would overwrite changes made to the data file at `path` by something
+/// else since it was read
+fn data_file_changed(path: &Path) -> TapDataStoreError {
+    TapDataStoreError {
+        kind: TapDataStoreErrorKind::DataFileChanged,
+        message: format!(
+            "The data file {} was changed by something else since it was read, it was not saved",
+            path.display()
+        ),
+    }
+}
+
+/// The contents of the data file at `path`, `None` when there is no data file yet
+fn read_data_file_at(path: &Path) -> Result<Option<String>, TapDataStoreError> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    fs::read_to_string(path)
+        .map(Some)
+        .map_err(|e| TapDataStoreError {
+            kind: TapDataStoreErrorKind::FileReadFailed,
+            message: format!("Could not read data file at {}: {e}", path.display()),
+        })
+}
+
+/// The aliases kept next to the data file, in memory data has none
+fn load_aliases(data: &Data) -> Result<Aliases, TapDataStoreError> {
+    match data.in_memory {
+        true => Ok(Aliases::new()),
+        false => load_aliases_of(&data.path),
+    }
+}
+
+fn load_aliases_of(data: &Path) -> Result<Aliases, TapDataStoreError> {
+    let path = aliases_path_for(data);
+    aliases::read(&path).map_err(|e| TapDataStoreError {
+        kind: TapDataStoreErrorKind::FileReadFailed,
+        message: format!("Could not read {}: {e}", path.display()),
+    })
+}
+
+fn read_trash_of(data: &Path) -> Result<Vec<trash::Entry>, TapDataStoreError> {
+    let path = trash_path_for(data);
+    trash::read(&path).map_err(|e| TapDataStoreError {
+        kind: TapDataStoreErrorKind::FileReadFailed,
+        message: format!("Could not read {}: {e}", path.display()),
+    })
+}
+
+/// How many days deleted links are kept in the trash file, the `trash_days` of the config file or
+/// `trash::DEFAULT_RETENTION_DAYS`. A config file that can not be read just keeps the default.
+fn trash_retention_days() -> u64 {
+    default_config_path()
+        .and_then(|path| Config::load(Some(path)).ok())
+        .and_then(|config| config.get(trash::TRASH_DAYS_CONFIG_KEY)?.parse().ok())
+        .unwrap_or(trash::DEFAULT_RETENTION_DAYS)
+}
+
+/// The parent entity `parent` names when it is an alias, otherwise `parent` itself
+fn canonical_parent(aliases: &Aliases, parent: &str) -> String {
+    match aliases.get(parent.trim()) {
+        Some(resolved) => {
+            log::verbose(format_args!(
+                "Resolved alias {} to {resolved}",
+                parent.trim()
+            ));
+            resolved.clone()
+        }
+        None => parent.to_string(),
+    }
+}
+
+/// The snapshot of the data file taken before the last change, kept next to the data file: the
+/// snapshot of `.tap_data` is `.tap_data.undo`
+pub(crate) fn undo_path_for(data: &Path) -> PathBuf {
+    let mut file_name = data.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".undo");
+    data.with_file_name(file_name)
+}
+
+/// Saves `before`, the contents of the data file at `path` before a change, as a backup archive
+/// along with the command that made the change, see `StoreOptions::operation`, and the trash
+/// entries the change took out of the trash, replacing the previous snapshot. Nothing is recorded
+/// when `after`, the stamp of the contents after the change, shows they did not change. The
+/// change was already saved, so failing to record it is only noted.
+fn record_undo(
+    path: &Path,
+    before: &str,
+    after: Option<FileStamp>,
+    untrashed: &[trash::Entry],
+    options: &StoreOptions,
+) {
+    if after == Some(FileStamp::of(before)) {
+        return;
+    }
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let mut files = vec![("data".to_string(), before.to_string())];
+    if let Some(operation) = &options.operation {
+        files.push(("operation".to_string(), operation.clone()));
+    }
+    if !untrashed.is_empty() {
+        let lines = untrashed.iter().map(|e| format!("{e}\n")).collect();
+        files.push(("trash".to_string(), lines));
+    }
+    let archive = Archive {
+        created: utc_timestamp(now),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        files,
+    };
+    let undo = undo_path_for(path);
+    if let Err(e) = fs::write(&undo, archive.to_string()) {
+        log::note(format_args!(
+            "Could not save {}, the change can not be undone: {e}",
+            undo.display()
+        ));
+    }
+}
+
+/// The index of a data file as it is, without rewriting it first: the offset of the first line of
+/// every parent entity that has links. Saving a data store writes its data file in a form where
+/// every parent entity has a single section, which is what the index is made for, but the file a
+/// change replaced may not be in that form (e.g. the one `tap --compact` cleaned up).
+fn index_entries(data: &str) -> Vec<IndexEntry> {
+    let (state, _) = Data::parse_file_lenient(data, &mut Metadata::new());
+    let mut entries: Vec<IndexEntry> = vec![];
+    let mut offset = 0;
+    for line in data.split_inclusive('\n') {
+        let trimmed = line.trim_end();
+        if !line.starts_with(char::is_whitespace)
+            && !is_comment(line)
+            && let Some(parent) = trimmed.strip_suffix("->").map(str::trim)
+            && state.iter().any(|(p, _)| p.trim() == parent)
+            && !entries.iter().any(|(p, _)| p == parent)
+        {
+            entries.push((parent.to_string(), offset));
+        }
+        offset += line.len();
+    }
+    entries
+}
+
+/// A change reverted by `DataStoreHandle::undo`
+#[derive(Debug, PartialEq)]
+pub(crate) struct Undone {
+    /// The command that made the change, e.g. `tap -d work`, when it is known
+    pub operation: Option<String>,
+    /// The contents of the data file before the change, which are now back in place
+    pub restored: String,
+    /// The contents of the data file that were replaced
+    pub replaced: String,
+}
+
+/// Takes the links that undoing brought back to the data file at `path` out of its trash file, the
+/// newest entry of each, so a deleted link that was put back is not in the trash as well
+fn untrash_restored(path: &Path, restored: &str, replaced: &str) -> Result<(), TapDataStoreError> {
+    let mut entries = read_trash_of(path)?;
+    if entries.is_empty() {
+        return Ok(());
+    }
+    let links = |data: &str| -> HashSet<(String, String, String)> {
+        let (state, _) = Data::parse_file_lenient(data, &mut Metadata::new());
+        state
+            .into_iter()
+            .flat_map(|(parent, links)| {
+                links.into_iter().map(move |(link, value)| {
+                    let [parent, link, value] =
+                        [&parent, &link, &value].map(|s| s.trim().to_string());
+                    (parent, link, value)
+                })
+            })
+            .collect()
+    };
+    let before = links(replaced);
+    let mut untrashed = false;
+    for (parent, link, value) in links(restored).difference(&before) {
+        if let Some(i) = entries
+            .iter()
+            .rposition(|e| e.parent == *parent && e.link == *link && e.value == *value)
+        {
+            entries.remove(i);
+            untrashed = true;
+        }
+    }
+    if !untrashed {
+        return Ok(());
+    }
+    let trash = trash_path_for(path);
+    trash::write(&trash, &entries).map_err(|e| TapDataStoreError {
+        kind: TapDataStoreErrorKind::FileWriteFailed,
+        message: format!("Could not write {}: {e}", trash.display()),
+    })
+}
+
+/// Puts the trash entries written in `untrashed`, one per line, back in the trash file of the data
+/// file at `path`, for undoing `tap --restore-trash`. Entries that are still there are not added
+/// twice.
+fn retrash(path: &Path, untrashed: &str) -> Result<(), TapDataStoreError> {
+    let mut entries = read_trash_of(path)?;
+    for entry in untrashed.lines().filter_map(trash::Entry::parse) {
+        if !entries.contains(&entry) {
+            entries.push(entry);
+        }
+    }
+    // Entries are kept oldest first
+    entries.sort_by(|a, b| a.time.cmp(&b.time));
+    let trash = trash_path_for(path);
+    trash::write(&trash, &entries).map_err(|e| TapDataStoreError {
+        kind: TapDataStoreErrorKind::FileWriteFailed,
+        message: format!("Could not write {}: {e}", trash.display()),
+    })
+}
+
+/// Checks a data file and its index file (`None` when it does not exist) for problems: lines that
+/// can not be parsed, parent entities or links that appear more than once, separators in names and
+/// values, and index entries that are missing or do not point at their parent entity
+fn diagnose_files(data: &str, index: Option<&str>) -> Vec<Finding> {
+    let mut findings = vec![];
+    let (state, rejected) = Data::parse_file_lenient(data, &mut Metadata::new());
+    if let Err(e) = Data::parse_file(data.as_bytes())
+        && rejected.is_empty()
+    {
+        findings.push(Finding {
+            severity: Severity::Error,
+            message: e.message,
+            fix: "Fix the data file with tap --edit".to_string(),
+            repair: None,
+        });
+    }
+    for r in rejected.iter() {
+        findings.push(Finding {
+            severity: Severity::Error,
+            message: format!(
+                "Line {} of the data file can not be parsed: {}",
+                r.line_number,
+                r.reason.trim()
+            ),
+            fix: "Fix the line with tap --edit, or set it aside with tap --doctor --salvage"
+                .to_string(),
+            repair: None,
+        });
+    }
+
+    let mut sections: Vec<(String, usize)> = vec![];
+    let mut links: Vec<(String, String, String)> = vec![];
+    let mut parent: Option<String> = None;
+    let escaped = data.lines().next().is_some_and(has_format_header);
+    for (idx, line) in data.lines().enumerate() {
+        let line_number = idx + 1;
+        if is_comment(line) || rejected.iter().any(|r| r.line_number == line_number) {
+            continue;
+        }
+        if line.ends_with("->") {
+            let name = line.trim_end_matches("->").trim().to_string();
+            if name.contains('|') {
+                findings.push(Finding {
+                    severity: Severity::Error,
+                    message: format!("Line {line_number} is read as parent entity {name}, as it ends with '->', but it contains the separator '|'"),
+                    fix: "If it is a link whose value ends with '->', change the value with tap --edit"
+                        .to_string(),
+                    repair: None,
+                });
+            }
+            match sections.iter_mut().find(|(p, _)| *p == name) {
+                Some((_, count)) => *count += 1,
+                None => sections.push((name.clone(), 1)),
+            }
+            parent = Some(name);
+        } else if let (Some(parent), Some((link, value, _))) =
+            (&parent, split_link_line(line, escaped))
+        {
+            let (link, value) = (link.trim(), value.as_str());
+            // An escaped `\|` is fine, but a bare one that is not followed by metadata is only
+            // read as part of the value. Without the header, every `|` after the first is.
+            let (_, rest) = line.split_once('|').unwrap_or_default();
+            if escaped
+                && split_fields(rest)
+                    .iter()
+                    .skip(1)
+                    .any(|field| !LinkMeta::default().parse_field(&unescape_field(field)))
+            {
+                findings.push(Finding {
+                    severity: Severity::Warning,
+                    message: format!("The value of link {link} of parent entity {parent} contains the separator '|'"),
+                    fix: "Escape the '|' in the value as \\| with tap --edit, or let tap --compact escape it".to_string(),
+                    repair: None,
+                });
+            }
+            if links
+                .iter()
+                .any(|(p, l, v)| p == parent && l == link && v == value)
+            {
+                findings.push(Finding {
+                    severity: Severity::Warning,
+                    message: format!(
+                        "Link {link} of parent entity {parent} is repeated on line {line_number}"
+                    ),
+                    fix: "Remove the repeated links with tap --doctor --fix".to_string(),
+                    repair: Some(Repair::MergeDuplicates),
+                });
+            } else {
+                links.push((parent.clone(), link.to_string(), value.to_string()));
+            }
+        }
+    }
+    for (parent, count) in sections.iter().filter(|(_, count)| *count > 1) {
+        findings.push(Finding {
+            severity: Severity::Warning,
+            message: format!("Parent entity {parent} appears in {count} sections"),
+            fix: "Merge the sections with tap --doctor --fix".to_string(),
+            repair: Some(Repair::MergeDuplicates),
+        });
+    }
+
+    for parent in empty_sections(data)
+        .iter()
+        .filter(|parent| !state.iter().any(|(p, _)| p.trim() == parent.as_str()))
+    {
+        findings.push(Finding {
+            severity: Severity::Warning,
+            message: format!("Parent entity {parent} has no links"),
+            fix: "Remove it with tap --prune or tap --doctor --fix".to_string(),
+            repair: Some(Repair::Prune),
+        });
+    }
+
+    let rebuild = |severity: Severity, message: String| Finding {
+        severity,
+        message,
+        fix: "Rebuild the index with tap --doctor --fix".to_string(),
+        repair: Some(Repair::RebuildIndex),
+    };
+    let entries = match index.map(Index::parse_file) {
+        None if state.is_empty() => return findings,
+        None => {
+            findings.push(rebuild(
+                Severity::Warning,
+                "The index file is missing".to_string(),
+            ));
+            return findings;
+        }
+        Some(Err(e)) => {
+            findings.push(rebuild(
+                Severity::Error,
+                format!("The index file can not be parsed: {}", e.message.trim()),
+            ));
+            return findings;
+        }
+        Some(Ok(entries)) => entries,
+    };
+    for (parent, offset) in entries.iter() {
+        let parent = parent.trim();
+        if !state.iter().any(|(p, _)| p.trim() == parent) {
+            findings.push(rebuild(
+                Severity::Error,
+                format!("The index lists parent entity {parent}, which is not in the data file"),
+            ));
+            continue;
+        }
+        let at_line_start = *offset == 0 || data.as_bytes().get(offset - 1) == Some(&b'\n');
+        let line = data.get(*offset..).and_then(|rest| rest.lines().next());
+        if !at_line_start
+            || line.is_none_or(|line| {
+                !line.ends_with("->") || line.trim_end_matches("->").trim() != parent
+            })
+        {
+            findings.push(rebuild(
+                Severity::Error,
+                format!(
+                    "The index entry of parent entity {parent} points at byte {offset}, which is not the start of its {parent}-> line"
+                ),
+            ));
+        }
+    }
+    for (parent, _) in state.iter() {
+        if !entries.iter().any(|(p, _)| p.trim() == parent.trim()) {
+            findings.push(rebuild(
+                Severity::Error,
+                format!("Parent entity {} has no entry in the index", parent.trim()),
+            ));
+        }
+    }
+    findings
+}
+
+/// The parent entities of a data file whose section has no links, in the order of the file. Only
+/// the contents are looked at, a parent entity that has links in another section is listed too.
+fn empty_sections(data: &str) -> Vec<String> {
+    let mut empty = vec![];
+    let mut section: Option<String> = None;
+    for line in data.lines() {
+        if is_comment(line) || line.trim().is_empty() {
+            continue;
+        }
+        match line.strip_suffix("->") {
+            Some(parent) => empty.extend(section.replace(parent.trim().to_string())),
+            None => section = None,
+        }
+    }
+    empty.extend(section);
+    empty
+}
+
+/// Check if the parent name is valid. Names nest with `/`, like `work/infra`, and every segment
+/// of the name is checked on its own.
 /// ## Errors
-/// - `TapDataStoreErrorKind::ReservedKeyword` - if parent uses a reserved keyword
-fn validate_parent(parent: &str) -> Result<(), TapDataStoreError> {
-    // Check rules for parent
-    if vec![
-        "-a",
-        "--add",
-        "-d",
-        "--delete",
-        "--export",
-        "--help",
-        "-i",
-        "--init",
-        "--import",
-        "-s",
-        "--show",
-        "-u",
-        "--update",
-        "--upsert",
-        "-v",
-        "--version",
-        "--parent-entity",
-        "here",
-        "|",
-    ]
-    .contains(&parent)
-    {
-        return Err(TapDataStoreError {
-            kind: TapDataStoreErrorKind::ReservedKeyword,
-            message: format!("Parent entity name {parent} is reserved"),
-        });
+/// - `TapDataStoreErrorKind::ReservedKeyword` - if a segment of parent uses a reserved keyword
+pub(crate) fn validate_parent(parent: &str) -> Result<(), TapDataStoreError> {
+    // Names like `here` or `--show` are fine, they can be passed after `--` to be taken as names
+    for segment in parent.split('/') {
+        if segment == "|" {
+            return Err(TapDataStoreError {
+                kind: TapDataStoreErrorKind::ReservedKeyword,
+                message: format!("Parent entity name {parent} is reserved"),
+            });
+        }
+        if segment.trim_start().starts_with('#') {
+            let starts = match segment.len() == parent.len() {
+                true => "starts",
+                false => "has a segment that starts",
+            };
+            return Err(TapDataStoreError {
+                kind: TapDataStoreErrorKind::ReservedKeyword,
+                message: format!(
+                    "Parent entity name {parent} {starts} with '#' which is reserved for comments"
+                ),
+            });
+        }
     }
     Ok(())
 }
 
+/// Checks that an alias can be told apart from parent entities and commands. Aliases are keys of
+/// the aliases file and words of shell completions, so they are a single word without path
+/// separators.
+fn validate_alias(alias: &str) -> Result<(), TapDataStoreError> {
+    validate_parent(alias)?;
+    let reserved = match alias {
+        _ if alias.trim().is_empty() => Some("an alias can not be empty"),
+        _ if alias.contains(char::is_whitespace) => Some("an alias can not contain whitespace"),
+        _ if alias.contains(['/', '\\']) => {
+            Some("path separators like '/' and '\\' are not allowed in an alias")
+        }
+        "here" => Some("here is reserved for the current directory"),
+        _ if alias.starts_with('-') => Some("names starting with '-' are reserved for commands"),
+        _ if alias.contains("->") => Some("'->' is reserved for parent entities"),
+        _ => None,
+    };
+    match reserved {
+        Some(reason) => Err(TapDataStoreError {
+            kind: TapDataStoreErrorKind::ReservedKeyword,
+            message: format!("Alias {alias} is reserved, {reason}"),
+        }),
+        None => Ok(()),
+    }
+}
+
 /// Check if the link name is valid
 /// ## Errors
 /// - `TapDataStoreErrorKind::ReservedKeyword` - if link name uses a reserved keyword
-fn validate_link(link: &str) -> Result<(), TapDataStoreError> {
+pub(crate) fn validate_link(link: &str) -> Result<(), TapDataStoreError> {
     // TODO: GH-47 Add new error for invalid file URI / URL
     if link.contains("|") {
         return Err(TapDataStoreError {
@@ -1550,6 +6610,12 @@ fn validate_link(link: &str) -> Result<(), TapDataStoreError> {
             message: format!("Link name {link} contains a vertical bar '|' which is reserved"),
         });
     }
+    if link.trim_start().starts_with('#') {
+        return Err(TapDataStoreError {
+            kind: TapDataStoreErrorKind::ReservedKeyword,
+            message: format!("Link name {link} starts with '#' which is reserved for comments"),
+        });
+    }
     Ok(())
 }
 
@@ -1620,10 +6686,414 @@ fn get_test_file_path(file_type: FileType) -> Result<PathBuf, TapDataStoreError>
     Ok(path_buf)
 }
 
+/// A `demo.tap_data` file in a directory of its own under the system temp dir, so the index and
+/// sidecar files kept next to it never clash with those of another test. The directory is removed
+/// when it is dropped, and it derefs to the path of the data file.
+#[cfg(test)]
+pub(crate) struct TestDataFile(PathBuf);
+
+#[cfg(test)]
+impl TestDataFile {
+    /// A data file holding `contents`
+    pub fn new(contents: &str) -> Self {
+        let file = Self::missing();
+        fs::write(&file.0, contents).expect("Could not write test data file");
+        file
+    }
+
+    /// A data file that does not exist yet, in an existing directory
+    pub fn missing() -> Self {
+        static COUNT: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+        // The thread is named after the test, see `get_test_file_path`
+        let thread = std::thread::current();
+        let test_name = thread
+            .name()
+            .expect("Could not get thread name")
+            .replace("::", "_");
+        let dir = std::env::temp_dir().join(format!(
+            "tap_{test_name}_{}_{}",
+            std::process::id(),
+            COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("Could not create test directory");
+        Self(dir.join("demo.tap_data"))
+    }
+}
+
+#[cfg(test)]
+impl std::ops::Deref for TestDataFile {
+    type Target = PathBuf;
+
+    fn deref(&self) -> &PathBuf {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+impl AsRef<Path> for TestDataFile {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+impl Drop for TestDataFile {
+    fn drop(&mut self) {
+        if let Some(dir) = self.0.parent() {
+            let _ = fs::remove_dir_all(dir);
+        }
+    }
+}
+
 #[cfg(test)]
 mod util_tests {
     use super::*;
 
+    fn temp_dir(name: &str) -> PathBuf {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("tap_{name}_{timestamp}"));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_check_data_file() {
+        assert!(check_data_file("work->\n  ci|https://ci.internal\n").is_ok());
+        let e = check_data_file("work->\n  ci|https://ci.internal\n  broken\n").unwrap_err();
+        assert_eq!(e.kind, TapDataStoreErrorKind::ParseError);
+        assert!(e.message.starts_with("line 3: broken ("), "{}", e.message);
+    }
+
+    #[test]
+    fn test_write_after_external_change_fails() {
+        let data = temp_dir("write_after_external_change").join("links.tap_data");
+        fs::write(&data, "work->\n  ci|https://ci.internal\n").unwrap();
+        let store = DataStoreHandle::new(StoreOptions::default().with_files(data.clone(), None));
+        store.write(|_| Ok(())).unwrap();
+        fs::write(&data, "work->\n  ci|https://ci.INTERNAL\n").unwrap();
+        let e = store
+            .write(|ds| ds.add_link("news".into(), "bbc".into(), "https://bbc.com".into()))
+            .unwrap_err();
+        assert_eq!(e.kind, TapDataStoreErrorKind::DataFileChanged);
+        assert_eq!(
+            fs::read_to_string(&data).unwrap(),
+            "work->\n  ci|https://ci.INTERNAL\n"
+        );
+    }
+
+    #[test]
+    fn test_replace_data_file() {
+        let data = temp_dir("replace_data_file").join("links.tap_data");
+        let index = index_path_for(&data);
+        fs::write(&data, "work->\n  ci|https://ci.internal\n").unwrap();
+        let store = DataStoreHandle::new(StoreOptions::default().with_files(data.clone(), None));
+        store
+            .replace_data_file("personal->\n  mail|https://mail.me\n")
+            .unwrap();
+        assert_eq!(
+            fs::read_to_string(&data).unwrap(),
+            "personal->\n  mail|https://mail.me\n"
+        );
+        assert_eq!(fs::read_to_string(&index).unwrap(), "personal|0\n");
+        // Contents that do not parse leave the files alone
+        assert!(store.replace_data_file("  orphan|https://x\n").is_err());
+        assert!(fs::read_to_string(&data).unwrap().starts_with("personal->"));
+        // A data file changed since it was read is not replaced
+        let e = store
+            .replace_unchanged_data_file(
+                Some("work->\n  ci|https://ci.internal\n"),
+                "news->\n  bbc|https://bbc.com\n",
+            )
+            .unwrap_err();
+        assert_eq!(e.kind, TapDataStoreErrorKind::DataFileChanged);
+        assert!(fs::read_to_string(&data).unwrap().starts_with("personal->"));
+    }
+
+    /// The severity and message of every finding
+    fn diagnose(data: &str, index: Option<&str>) -> Vec<(Severity, String)> {
+        diagnose_files(data, index)
+            .into_iter()
+            .map(|f| (f.severity, f.message))
+            .collect()
+    }
+
+    #[test]
+    fn test_diagnose_files_clean() {
+        let data = "# links\nwork->\n  ci|https://ci.internal\nzoo->\n  a|https://a.b\n";
+        assert_eq!(diagnose(data, Some("work|8\nzoo|40\n")), vec![]);
+        assert_eq!(diagnose("", None), vec![]);
+    }
+
+    #[test]
+    fn test_diagnose_files_parse_errors() {
+        let findings = diagnose_files("work->\n  broken\n  ci|https://ci.internal\n", None);
+        assert_eq!(findings[0].severity, Severity::Error);
+        assert!(
+            findings[0]
+                .message
+                .starts_with("Line 2 of the data file can not be parsed: "),
+            "{}",
+            findings[0].message
+        );
+        assert_eq!(findings[0].repair, None);
+    }
+
+    #[test]
+    fn test_diagnose_files_duplicates_and_separators() {
+        let data = "# tap-data 2\nwork->\n  ci|https://ci.internal\n  ci|https://ci.internal\n  q|a|b\nwork->\n  x|y\nx|y->\n  z|z\n";
+        assert_eq!(
+            diagnose(data, Some("work|13\n")),
+            vec![
+                (
+                    Severity::Warning,
+                    "Link ci of parent entity work is repeated on line 4".to_string()
+                ),
+                (
+                    Severity::Warning,
+                    "The value of link q of parent entity work contains the separator '|'"
+                        .to_string()
+                ),
+                (
+                    Severity::Error,
+                    "Line 8 is read as parent entity x|y, as it ends with '->', but it contains the separator '|'".to_string()
+                ),
+                (
+                    Severity::Warning,
+                    "Parent entity work appears in 2 sections".to_string()
+                ),
+                (
+                    Severity::Error,
+                    "Parent entity x|y has no entry in the index".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diagnose_files_index() {
+        let data = "work->\n  ci|https://ci.internal\nzoo->\n  a|https://a.b\n";
+        assert_eq!(
+            diagnose(data, None),
+            vec![(Severity::Warning, "The index file is missing".to_string())]
+        );
+        assert_eq!(diagnose(data, Some("work|zero\n")).len(), 1);
+        assert_eq!(
+            diagnose(data, Some("gone|0\nwork|1\n")),
+            vec![
+                (
+                    Severity::Error,
+                    "The index lists parent entity gone, which is not in the data file"
+                        .to_string()
+                ),
+                (
+                    Severity::Error,
+                    "The index entry of parent entity work points at byte 1, which is not the start of its work-> line".to_string()
+                ),
+                (
+                    Severity::Error,
+                    "Parent entity zoo has no entry in the index".to_string()
+                ),
+            ]
+        );
+        assert!(
+            diagnose_files(data, Some("zoo|0\n"))
+                .iter()
+                .all(|f| f.repair == Some(Repair::RebuildIndex))
+        );
+    }
+
+    #[test]
+    fn test_diagnose_files_empty_parents() {
+        let findings = diagnose_files("old->\nwork->\n  ci|https://ci.internal\n", None);
+        let empty: Vec<&Finding> = findings
+            .iter()
+            .filter(|f| f.repair == Some(Repair::Prune))
+            .collect();
+        assert_eq!(empty.len(), 1);
+        assert_eq!(empty[0].severity, Severity::Warning);
+        assert_eq!(empty[0].message, "Parent entity old has no links");
+    }
+
+    #[test]
+    fn test_prune() {
+        let data = temp_dir("prune").join(".tap_data");
+        let index = index_path_for(&data);
+        fs::write(&data, "old->\nwork->\n  ci|https://ci.internal\n").unwrap();
+        fs::write(&index, "gone|0\nold|0\nwork|6\n").unwrap();
+        let mut ds = DataStore::open_files(data.clone(), index.clone()).unwrap();
+        assert_eq!(
+            ds.prune().unwrap(),
+            PruneSummary {
+                empty_parents: vec!["old".to_string()],
+                orphaned_index_entries: vec!["gone".to_string()],
+            }
+        );
+        assert_eq!(
+            fs::read_to_string(&data).unwrap(),
+            "work->\n  ci|https://ci.internal\n"
+        );
+        assert_eq!(fs::read_to_string(&index).unwrap(), "work|0\n");
+        // Pruning again finds nothing
+        let mut ds = DataStore::open_files(data.clone(), index).unwrap();
+        assert!(ds.prune().unwrap().is_empty());
+        fs::remove_dir_all(data.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_choose_data_dir_writable() {
+        let (exe_dir, user_dir) = (PathBuf::from("/exe"), PathBuf::from("/user"));
+        assert_eq!(choose_data_dir(&exe_dir, &user_dir, |_| true), exe_dir);
+    }
+
+    #[test]
+    fn test_choose_data_dir_not_writable() {
+        let (exe_dir, user_dir) = (PathBuf::from("/exe"), PathBuf::from("/user"));
+        assert_eq!(choose_data_dir(&exe_dir, &user_dir, |_| false), user_dir);
+    }
+
+    #[test]
+    fn test_choose_data_dir_existing_store_wins() {
+        let root = temp_dir("choose_data_dir");
+        let (exe_dir, user_dir) = (root.join("bin"), root.join("user"));
+        fs::create_dir_all(&exe_dir).unwrap();
+        fs::create_dir_all(&user_dir).unwrap();
+        // A store in the user directory is used even if the executable directory is writable
+        fs::write(user_dir.join(".tap_data"), "").unwrap();
+        assert_eq!(choose_data_dir(&exe_dir, &user_dir, |_| true), user_dir);
+        // A store next to the executable is used even if it can not be written to
+        fs::write(exe_dir.join(".tap_data"), "").unwrap();
+        assert_eq!(choose_data_dir(&exe_dir, &user_dir, |_| false), exe_dir);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_is_profile_name() {
+        for name in ["work", "personal", "client-a", "side_project", "v2.0"] {
+            assert!(is_profile_name(name), "{name}");
+        }
+        for name in [
+            "", ".hidden", "-work", "../work", "a/b", "my work", "list", "use",
+        ] {
+            assert!(!is_profile_name(name), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_choose_profile() {
+        assert_eq!(choose_profile(None, None).unwrap(), DEFAULT_PROFILE);
+        assert_eq!(choose_profile(None, Some("work")).unwrap(), "work");
+        // TAP_PROFILE wins over the config file, and blank values are ignored
+        assert_eq!(
+            choose_profile(Some("personal"), Some("work")).unwrap(),
+            "personal"
+        );
+        assert_eq!(choose_profile(Some(" "), Some("work")).unwrap(), "work");
+        let err = choose_profile(Some("../work"), None).unwrap_err();
+        assert_eq!(err.kind(), &TapDataStoreErrorKind::ConfigFailed);
+    }
+
+    #[test]
+    fn test_store_options_profile() {
+        let options = StoreOptions {
+            profile: Some("work".to_string()),
+            ..StoreOptions::default()
+        };
+        // The profile given wins over TAP_PROFILE and the config file
+        assert_eq!(options.active_profile().unwrap(), "work");
+        let [(data, _), (index, _)] = options.resolve_paths().unwrap();
+        assert!(data.ends_with("work/.tap_data"), "{}", data.display());
+        assert!(index.ends_with("work/.tap_index"), "{}", index.display());
+        // --file picks the data file itself
+        let options = options.with_files(PathBuf::from("links.tap_data"), None);
+        let [(data, source), _] = options.resolve_paths().unwrap();
+        assert_eq!(data, PathBuf::from("links.tap_data"));
+        assert_eq!(source, PathSource::Flag);
+    }
+
+    #[test]
+    fn test_profile_dir() {
+        let data_dir = PathBuf::from("/data");
+        // The default profile keeps its files where they were before there were profiles
+        assert_eq!(profile_dir(&data_dir, DEFAULT_PROFILE), data_dir);
+        assert_eq!(profile_dir(&data_dir, "work"), data_dir.join("work"));
+    }
+
+    #[test]
+    fn test_profiles_in() {
+        let root = temp_dir("profiles_in");
+        assert_eq!(profiles_in(&root), Vec::<String>::new());
+        fs::create_dir_all(root.join("work")).unwrap();
+        fs::write(root.join("work").join(".tap_data"), "").unwrap();
+        // Directories without a data file are not profiles
+        fs::create_dir_all(root.join("empty")).unwrap();
+        assert_eq!(profiles_in(&root), ["work"]);
+        fs::write(root.join(".tap_data"), "").unwrap();
+        assert_eq!(profiles_in(&root), [DEFAULT_PROFILE, "work"]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_is_dir_writable_read_only_dir() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = temp_dir("read_only_dir");
+        assert!(is_dir_writable(&dir));
+        // The probe file is removed again
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o555)).unwrap();
+        let probe = dir.join("probe");
+        // Permissions are not enforced for root, in which case there is nothing to simulate
+        if fs::write(&probe, "").is_err() {
+            assert!(!is_dir_writable(&dir));
+            assert_eq!(
+                choose_data_dir(&dir, Path::new("/user"), is_dir_writable),
+                PathBuf::from("/user")
+            );
+        }
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o755)).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_get_parent_dir_of_executable_symlink() {
+        let root = temp_dir("symlink");
+        let (real_dir, bin_dir) = (root.join("Cellar"), root.join("bin"));
+        fs::create_dir_all(&real_dir).unwrap();
+        fs::create_dir_all(&bin_dir).unwrap();
+        fs::write(real_dir.join("tap"), "").unwrap();
+        std::os::unix::fs::symlink(real_dir.join("tap"), bin_dir.join("tap")).unwrap();
+
+        let parent_dir = get_parent_dir_of_executable(&bin_dir.join("tap")).unwrap();
+        assert_eq!(parent_dir, fs::canonicalize(&real_dir).unwrap());
+        assert_eq!(migration_hint(&bin_dir.join("tap"), &parent_dir), None);
+
+        // Data files next to the symlink, but not next to the real executable
+        fs::write(bin_dir.join(".tap_data"), "").unwrap();
+        let hint = migration_hint(&bin_dir.join("tap"), &parent_dir).unwrap();
+        assert!(hint.contains(&bin_dir.display().to_string()));
+        assert!(hint.contains(&parent_dir.display().to_string()));
+
+        // Once the data files were moved, there is nothing to hint at
+        fs::write(real_dir.join(".tap_data"), "").unwrap();
+        assert_eq!(migration_hint(&bin_dir.join("tap"), &parent_dir), None);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_get_parent_dir_of_executable_missing_file() {
+        // Canonicalizing fails for a missing file, so the path is used as given
+        let path = PathBuf::from("/this/does/not/exist/tap");
+        assert_eq!(
+            get_parent_dir_of_executable(&path).unwrap(),
+            PathBuf::from("/this/does/not/exist")
+        );
+    }
+
     #[test]
     fn test_validate_parent_success() {
         assert!(validate_parent("test").is_ok());
@@ -1633,33 +7103,32 @@ mod util_tests {
         assert!(validate_parent("well-what-do-we-have-here").is_ok());
         assert!(validate_parent("Sure, spaces also are valid!").is_ok());
         assert!(validate_parent("parent-entity").is_ok());
+        assert!(validate_parent("work/infra/grafana-dashboards").is_ok());
     }
 
     #[test]
     fn test_validate_parent_failure() {
-        assert!(validate_parent("-a").is_err());
-        assert!(validate_parent("--add").is_err());
-        assert!(validate_parent("-d").is_err());
-        assert!(validate_parent("--delete").is_err());
-        assert!(validate_parent("--export").is_err());
-        assert!(validate_parent("--help").is_err());
-        assert!(validate_parent("-i").is_err());
-        assert!(validate_parent("--init").is_err());
-        assert!(validate_parent("--import").is_err());
-        assert!(validate_parent("-s").is_err());
-        assert!(validate_parent("--show").is_err());
-        assert!(validate_parent("-u").is_err());
-        assert!(validate_parent("--update").is_err());
-        assert!(validate_parent("--upsert").is_err());
-        assert!(validate_parent("-v").is_err());
-        assert!(validate_parent("--version").is_err());
-        assert!(validate_parent("--parent-entity").is_err());
-        assert_eq!(
-            validate_parent("here").unwrap_err().kind,
+        assert!(validate_parent("|").is_err());
+        assert_eq!(
+            validate_parent("#work").unwrap_err().kind,
+            TapDataStoreErrorKind::ReservedKeyword
+        );
+        // Every segment of a nested name is checked
+        assert!(validate_parent("work/|").is_err());
+        assert_eq!(
+            validate_parent("work/#infra").unwrap_err().kind,
             TapDataStoreErrorKind::ReservedKeyword
         );
     }
 
+    #[test]
+    fn test_validate_parent_command_names() {
+        // Only taken as names when passed after `--`, see `utils::command::Args`
+        assert!(validate_parent("here").is_ok());
+        assert!(validate_parent("--show").is_ok());
+        assert!(validate_parent("-a").is_ok());
+    }
+
     #[test]
     fn test_validate_link_success() {
         assert!(validate_link("test").is_ok());
@@ -1674,11 +7143,210 @@ mod util_tests {
     fn test_validate_link_failure() {
         assert!(validate_link("|").is_err());
         assert!(validate_link("search|engines").is_err());
+        assert!(validate_link("#search-engines").is_err());
         assert_eq!(
             validate_link("search|engines").unwrap_err().kind,
             TapDataStoreErrorKind::ReservedKeyword
         );
     }
+
+    #[test]
+    fn test_index_path_for() {
+        assert_eq!(
+            index_path_for(Path::new("/a/.tap_data")),
+            PathBuf::from("/a/.tap_index")
+        );
+        assert_eq!(
+            index_path_for(Path::new("/a/demo.tap_data")),
+            PathBuf::from("/a/demo.tap_index")
+        );
+        assert_eq!(
+            index_path_for(Path::new("/a/links")),
+            PathBuf::from("/a/links.tap_index")
+        );
+    }
+
+    #[test]
+    fn test_archive_path_for() {
+        assert_eq!(
+            archive_path_for(Path::new("/a/.tap_data")),
+            PathBuf::from("/a/.tap_archive")
+        );
+        assert_eq!(
+            archive_path_for(Path::new("/a/links")),
+            PathBuf::from("/a/links.tap_archive")
+        );
+    }
+
+    /// Returns the source and destination directories with a store in the source directory, and
+    /// a config file inside the temporary directory
+    fn migrate_setup(name: &str) -> (PathBuf, PathBuf, PathBuf, Config) {
+        let root = temp_dir(name);
+        let (src_dir, dest_dir) = (root.join("src"), root.join("dest"));
+        fs::create_dir_all(&src_dir).unwrap();
+        fs::write(
+            src_dir.join(".tap_data"),
+            "search-engines->\n  google|https://google.com\n",
+        )
+        .unwrap();
+        fs::write(src_dir.join(".tap_index"), "search-engines|0\n").unwrap();
+        let config = Config::load(Some(root.join("config").join("tap"))).unwrap();
+        (root, src_dir, dest_dir, config)
+    }
+
+    #[test]
+    fn test_migrate_data_dir() {
+        let (root, src_dir, dest_dir, mut config) = migrate_setup("migrate");
+        migrate_data_dir(&src_dir, &dest_dir, &mut config, false).unwrap();
+        assert!(!src_dir.join(".tap_data").exists());
+        assert!(!src_dir.join(".tap_index").exists());
+        assert_eq!(
+            fs::read_to_string(dest_dir.join(".tap_data")).unwrap(),
+            "search-engines->\n  google|https://google.com\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dest_dir.join(".tap_index")).unwrap(),
+            "search-engines|0\n"
+        );
+        assert!(!dest_dir.join(".tap_data.migrating").exists());
+        let config = Config::load(Some(root.join("config").join("tap"))).unwrap();
+        assert_eq!(
+            config.get(DATA_DIR_CONFIG_KEY),
+            Some(dest_dir.display().to_string().as_str())
+        );
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_migrate_data_dir_profiles_and_sidecars() {
+        let (root, src_dir, dest_dir, mut config) = migrate_setup("migrate_profiles");
+        let data = src_dir.join(".tap_data");
+        for (_, path) in sidecar_paths(&data) {
+            fs::write(path, "kept\n").unwrap();
+        }
+        let personal = profile_dir(&src_dir, "personal");
+        fs::create_dir_all(&personal).unwrap();
+        fs::write(
+            personal.join(".tap_data"),
+            "home->\n  nas|https://nas.local\n",
+        )
+        .unwrap();
+        fs::write(aliases_path_for(&personal.join(".tap_data")), "h\thome\n").unwrap();
+        migrate_data_dir(&src_dir, &dest_dir, &mut config, false).unwrap();
+        for (name, path) in sidecar_paths(&dest_dir.join(".tap_data")) {
+            assert_eq!(fs::read_to_string(&path).unwrap(), "kept\n", "{name}");
+        }
+        for (name, path) in sidecar_paths(&data) {
+            assert!(!path.exists(), "{name}");
+        }
+        let dest_personal = profile_dir(&dest_dir, "personal").join(".tap_data");
+        assert_eq!(
+            fs::read_to_string(&dest_personal).unwrap(),
+            "home->\n  nas|https://nas.local\n"
+        );
+        assert_eq!(
+            fs::read_to_string(aliases_path_for(&dest_personal)).unwrap(),
+            "h\thome\n"
+        );
+        assert!(!personal.exists());
+        assert_eq!(profiles_in(&dest_dir), ["default", "personal"]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_migrate_data_dir_non_default_profile() {
+        let (root, src_dir, dest_dir, mut config) = migrate_setup("migrate_work_profile");
+        // Only the work profile has links, the default profile was never used
+        fs::remove_file(src_dir.join(".tap_data")).unwrap();
+        fs::remove_file(src_dir.join(".tap_index")).unwrap();
+        let work = profile_dir(&src_dir, "work").join(".tap_data");
+        fs::create_dir_all(work.parent().unwrap()).unwrap();
+        fs::write(&work, "work->\n  ci|https://ci.internal\n").unwrap();
+        fs::write(index_path_for(&work), "work|0\n").unwrap();
+        migrate_data_dir(&src_dir, &dest_dir, &mut config, false).unwrap();
+        assert!(!work.exists());
+        // The work profile resolves to its own directory in the data directory of the config
+        let config = Config::load(Some(root.join("config").join("tap"))).unwrap();
+        let data_dir = PathBuf::from(config.get(DATA_DIR_CONFIG_KEY).unwrap());
+        assert_eq!(profiles_in(&data_dir), ["work"]);
+        let data = profile_dir(&data_dir, "work").join(".tap_data");
+        let handle = DataStoreHandle::new(StoreOptions::default().with_files(data, None));
+        assert_eq!(handle.links("work").unwrap(), ["ci"]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_migrate_data_dir_no_store() {
+        let (root, src_dir, dest_dir, mut config) = migrate_setup("migrate_no_store");
+        fs::remove_file(src_dir.join(".tap_data")).unwrap();
+        let res = migrate_data_dir(&src_dir, &dest_dir, &mut config, false);
+        assert_eq!(
+            res.unwrap_err().kind,
+            TapDataStoreErrorKind::DataStoreNotFound
+        );
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_migrate_data_dir_same_dir() {
+        let (root, src_dir, _, mut config) = migrate_setup("migrate_same_dir");
+        let res = migrate_data_dir(&src_dir, &src_dir, &mut config, true);
+        assert_eq!(
+            res.unwrap_err().kind,
+            TapDataStoreErrorKind::DataStoreAlreadyExists
+        );
+        assert!(src_dir.join(".tap_data").exists());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_migrate_data_dir_populated_destination() {
+        let (root, src_dir, dest_dir, mut config) = migrate_setup("migrate_populated");
+        fs::create_dir_all(&dest_dir).unwrap();
+        fs::write(dest_dir.join(".tap_data"), "other->\n  a|b\n").unwrap();
+        let res = migrate_data_dir(&src_dir, &dest_dir, &mut config, false);
+        assert_eq!(
+            res.unwrap_err().kind,
+            TapDataStoreErrorKind::DataStoreAlreadyExists
+        );
+        assert!(src_dir.join(".tap_data").exists());
+        assert_eq!(
+            fs::read_to_string(dest_dir.join(".tap_data")).unwrap(),
+            "other->\n  a|b\n"
+        );
+        // --force overwrites the destination
+        migrate_data_dir(&src_dir, &dest_dir, &mut config, true).unwrap();
+        assert_eq!(
+            fs::read_to_string(dest_dir.join(".tap_data")).unwrap(),
+            "search-engines->\n  google|https://google.com\n"
+        );
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_migrate_data_dir_empty_destination_store() {
+        let (root, src_dir, dest_dir, mut config) = migrate_setup("migrate_empty_dest");
+        fs::create_dir_all(&dest_dir).unwrap();
+        fs::write(dest_dir.join(".tap_data"), "").unwrap();
+        migrate_data_dir(&src_dir, &dest_dir, &mut config, false).unwrap();
+        assert!(!src_dir.join(".tap_data").exists());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_migrate_data_dir_unparseable_copy_keeps_original() {
+        let (root, src_dir, dest_dir, mut config) = migrate_setup("migrate_unparseable");
+        fs::write(src_dir.join(".tap_data"), "not a parent entity\n").unwrap();
+        let res = migrate_data_dir(&src_dir, &dest_dir, &mut config, false);
+        assert_eq!(res.unwrap_err().kind, TapDataStoreErrorKind::ParseError);
+        assert!(src_dir.join(".tap_data").exists());
+        assert!(src_dir.join(".tap_index").exists());
+        assert!(!dest_dir.join(".tap_data").exists());
+        assert!(!dest_dir.join(".tap_data.migrating").exists());
+        assert!(!dest_dir.join(".tap_index.migrating").exists());
+        assert_eq!(config.get(DATA_DIR_CONFIG_KEY), None);
+        fs::remove_dir_all(&root).unwrap();
+    }
 }
 
 // Errors
@@ -1686,10 +7354,13 @@ mod util_tests {
 pub enum TapDataStoreErrorKind {
     #[cfg(test)]
     CurrentTimeError,
+    ConfigFailed,
+    DataFileChanged,
+    DataStoreAlreadyExists,
+    DataStoreNotFound,
     ExecutablePathNotFound,
     ExecutablePathParentDirectoryNotFound,
     FileCreateFailed,
-    #[cfg(test)]
     FileDeleteFailed,
     FileReadFailed,
     FileReadMetadataFailed,
@@ -1699,8 +7370,10 @@ pub enum TapDataStoreErrorKind {
     InvalidFileExtension,
     LinkAlreadyExists,
     LinkNotFound,
+    ParentEntityAlreadyExists,
     ParentEntityNotFound,
     ParseError,
+    ReadOnly,
     ReservedKeyword,
 }
 
@@ -1710,6 +7383,12 @@ pub struct TapDataStoreError {
     message: String,
 }
 
+impl TapDataStoreError {
+    pub fn kind(&self) -> &TapDataStoreErrorKind {
+        &self.kind
+    }
+}
+
 impl fmt::Display for TapDataStoreError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{} (tap data store error: {})", self.message, self.kind)
@@ -1721,6 +7400,9 @@ impl fmt::Display for TapDataStoreErrorKind {
         match self {
             #[cfg(test)]
             TapDataStoreErrorKind::CurrentTimeError => write!(f, "Current time error"),
+            TapDataStoreErrorKind::ConfigFailed => write!(f, "Config failed"),
+            TapDataStoreErrorKind::DataStoreAlreadyExists => write!(f, "Data store already exists"),
+            TapDataStoreErrorKind::DataStoreNotFound => write!(f, "Data store not found"),
             TapDataStoreErrorKind::ExecutablePathNotFound => {
                 write!(f, "Executable path not found")
             }
@@ -1728,7 +7410,6 @@ impl fmt::Display for TapDataStoreErrorKind {
                 write!(f, "Executable path parent directory not found")
             }
             TapDataStoreErrorKind::FileCreateFailed => write!(f, "File create failed"),
-            #[cfg(test)]
             TapDataStoreErrorKind::FileDeleteFailed => write!(f, "File delete failed"),
             TapDataStoreErrorKind::FileOpenFailed => write!(f, "File open failed"),
             TapDataStoreErrorKind::FileSeekFailed => write!(f, "File seek failed"),
@@ -1737,11 +7418,16 @@ impl fmt::Display for TapDataStoreErrorKind {
                 write!(f, "File read metadata failed")
             }
             TapDataStoreErrorKind::FileWriteFailed => write!(f, "File write failed"),
+            TapDataStoreErrorKind::DataFileChanged => write!(f, "Data file changed"),
             TapDataStoreErrorKind::InvalidFileExtension => write!(f, "Invalid file extension"),
             TapDataStoreErrorKind::LinkAlreadyExists => write!(f, "Link already exists"),
             TapDataStoreErrorKind::LinkNotFound => write!(f, "Link not found"),
+            TapDataStoreErrorKind::ParentEntityAlreadyExists => {
+                write!(f, "Parent entity already exists")
+            }
             TapDataStoreErrorKind::ParentEntityNotFound => write!(f, "Parent entity not found"),
             TapDataStoreErrorKind::ParseError => write!(f, "Parse error"),
+            TapDataStoreErrorKind::ReadOnly => write!(f, "Read only"),
             TapDataStoreErrorKind::ReservedKeyword => write!(f, "Reserved keyword used"),
         }
     }
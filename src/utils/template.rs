@@ -0,0 +1,149 @@
+/// A part of a value: text kept as it is, or a `{name}` placeholder to fill
+#[derive(Debug, PartialEq)]
+enum Part<'a> {
+    Text(String),
+    Placeholder(&'a str),
+}
+
+/// The parts of `value`. `{{` and `}}` stand for literal braces, and a brace that does not start a
+/// placeholder, like the one in `{not a name}` or in the environment variable `${HOME}`, is kept as
+/// it is.
+fn parts(value: &str) -> Vec<Part<'_>> {
+    let mut parts = vec![];
+    let mut text = String::new();
+    let mut rest = value;
+    while let Some(c) = rest.chars().next() {
+        if rest.starts_with("{{") || rest.starts_with("}}") {
+            text.push(c);
+            rest = &rest[2..];
+            continue;
+        }
+        let name = Some(rest)
+            .filter(|_| !text.ends_with('$'))
+            .and_then(|rest| rest.strip_prefix('{'))
+            .and_then(|after| after.split_once('}'))
+            .map(|(name, _)| name)
+            .filter(|name| is_name(name));
+        match name {
+            Some(name) => {
+                parts.push(Part::Text(std::mem::take(&mut text)));
+                parts.push(Part::Placeholder(name));
+                rest = &rest[name.len() + 2..];
+            }
+            None => {
+                text.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    parts.push(Part::Text(text));
+    parts
+}
+
+/// Placeholder names are made of letters, digits, `_`, and `-`
+fn is_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+}
+
+/// The names of the placeholders in `value`, in the order they first appear
+pub(crate) fn placeholders(value: &str) -> Vec<String> {
+    let mut names: Vec<String> = vec![];
+    for part in parts(value) {
+        if let Part::Placeholder(name) = part
+            && !names.iter().any(|n| n == name)
+        {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+/// `value` with every placeholder replaced by the URL encoded value given for it in `args`, and
+/// `{{` and `}}` by single braces. Fails with the names of the placeholders no value was given for.
+pub(crate) fn fill(value: &str, args: &[(String, String)]) -> Result<String, Vec<String>> {
+    let mut filled = String::with_capacity(value.len());
+    let mut missing: Vec<String> = vec![];
+    for part in parts(value) {
+        match part {
+            Part::Text(text) => filled.push_str(&text),
+            Part::Placeholder(name) => match args.iter().find(|(n, _)| n == name) {
+                Some((_, arg)) => filled.push_str(&url_encode(arg)),
+                None if !missing.iter().any(|n| n == name) => missing.push(name.to_string()),
+                None => {}
+            },
+        }
+    }
+    match missing.is_empty() {
+        true => Ok(filled),
+        false => Err(missing),
+    }
+}
+
+/// Percent encodes every byte of `s` but letters, digits, and `-._~`, so a value fills one part
+/// of a URL whatever it holds
+fn url_encode(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_placeholders() {
+        assert_eq!(
+            placeholders("https://github.com/me/{repo}/issues?q={query}&r={repo}"),
+            vec!["repo".to_string(), "query".to_string()]
+        );
+        assert!(placeholders("https://example.com/{{literal}}/{not a name}/{}").is_empty());
+        assert!(placeholders("https://${JIRA_HOST}/browse").is_empty());
+    }
+
+    #[test]
+    fn test_fill() {
+        let value = "https://github.com/me/{repo}/issues?q={query}";
+        assert_eq!(
+            fill(value, &args(&[("repo", "tap"), ("query", "crash on open")])),
+            Ok("https://github.com/me/tap/issues?q=crash%20on%20open".to_string())
+        );
+        assert_eq!(
+            fill("https://x.com/?q={q}", &args(&[("q", "a&b=ü")])),
+            Ok("https://x.com/?q=a%26b%3D%C3%BC".to_string())
+        );
+        assert_eq!(
+            fill("{{json}} {x}}}", &args(&[("x", "1")])),
+            Ok("{json} 1}".to_string())
+        );
+    }
+
+    #[test]
+    fn test_fill_missing() {
+        assert_eq!(
+            fill("{a}/{b}/{a}", &args(&[("b", "1")])),
+            Err(vec!["a".to_string()])
+        );
+        assert_eq!(
+            fill("https://example.com", &[]),
+            Ok("https://example.com".to_string())
+        );
+    }
+}
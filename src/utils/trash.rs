@@ -0,0 +1,167 @@
+use crate::utils::backup::utc_timestamp;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{fmt, fs};
+
+/// How many days deleted links are kept in the trash file when the config file does not say,
+/// see `TRASH_DAYS_CONFIG_KEY`
+pub(crate) const DEFAULT_RETENTION_DAYS: u64 = 30;
+
+/// The config file key setting how many days deleted links are kept in the trash file
+pub(crate) const TRASH_DAYS_CONFIG_KEY: &str = "trash_days";
+
+/// A link removed with `tap --delete`, one line of the trash file. The fields are separated by
+/// tabs, written as `\t` here:
+///
+/// ```text
+/// 2024-06-01T12:30:05Z\twork\tci\thttps://ci.internal
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Entry {
+    /// When the link was deleted, see `utc_timestamp`
+    pub time: String,
+    pub parent: String,
+    pub link: String,
+    pub value: String,
+}
+
+impl Entry {
+    pub fn new(parent: &str, link: &str, value: &str) -> Self {
+        Self {
+            time: utc_timestamp(now()),
+            parent: parent.trim().to_string(),
+            link: link.trim().to_string(),
+            value: value.trim().to_string(),
+        }
+    }
+
+    /// `None` for lines that are not an entry, which are skipped when reading the trash
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.splitn(4, '\t').map(str::to_string);
+        Some(Self {
+            time: fields.next()?,
+            parent: fields.next()?,
+            link: fields.next()?,
+            value: fields.next()?,
+        })
+    }
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let fields = [&self.time, &self.parent, &self.link, &self.value];
+        // Tabs separate the fields, so the rare tab in a value is written as a space
+        let line = fields
+            .iter()
+            .map(|field| field.replace('\t', " "))
+            .collect::<Vec<String>>()
+            .join("\t");
+        write!(f, "{line}")
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// The trash file kept next to a data file: `.tap_data` keeps its deleted links in `.tap_trash`,
+/// `demo.tap_data` in `demo.tap_trash`, and any other file name gets `.tap_trash` appended
+pub(crate) fn trash_path_for(data: &Path) -> PathBuf {
+    let file_name = data.file_name().unwrap_or_default().to_string_lossy();
+    match file_name.strip_suffix("tap_data") {
+        Some(stem) => data.with_file_name(format!("{stem}tap_trash")),
+        None => data.with_file_name(format!("{file_name}.tap_trash")),
+    }
+}
+
+/// Drops the entries deleted more than `days` days ago. Timestamps sort like the times they
+/// stand for, so they are compared as they are.
+pub(crate) fn drop_expired(entries: &mut Vec<Entry>, days: u64) {
+    let cutoff = utc_timestamp(now().saturating_sub(days * 86_400));
+    entries.retain(|entry| entry.time >= cutoff);
+}
+
+/// Every entry of the trash file at `path`, oldest first, none when there is no such file
+pub(crate) fn read(path: &Path) -> std::io::Result<Vec<Entry>> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(contents.lines().filter_map(Entry::parse).collect()),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(vec![]),
+        Err(e) => Err(e),
+    }
+}
+
+/// Replaces the trash file at `path` with the entries, removing it when there are none
+pub(crate) fn write(path: &Path, entries: &[Entry]) -> std::io::Result<()> {
+    if entries.is_empty() {
+        return match fs::remove_file(path) {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        };
+    }
+    let lines: String = entries.iter().map(|e| format!("{e}\n")).collect();
+    fs::write(path, lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(time: &str, link: &str) -> Entry {
+        Entry {
+            time: time.to_string(),
+            parent: "work".to_string(),
+            link: link.to_string(),
+            value: "https://ci.internal".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_entry_round_trip() {
+        let line = entry("2024-06-01T12:30:05Z", "ci").to_string();
+        assert_eq!(line, "2024-06-01T12:30:05Z\twork\tci\thttps://ci.internal");
+        assert_eq!(
+            Entry::parse(&line),
+            Some(entry("2024-06-01T12:30:05Z", "ci"))
+        );
+        assert_eq!(Entry::parse("not an entry"), None);
+    }
+
+    #[test]
+    fn test_trash_path_for() {
+        assert_eq!(
+            trash_path_for(Path::new("/data/.tap_data")),
+            Path::new("/data/.tap_trash")
+        );
+        assert_eq!(
+            trash_path_for(Path::new("/data/links")),
+            Path::new("/data/links.tap_trash")
+        );
+    }
+
+    #[test]
+    fn test_drop_expired() {
+        let recent = Entry::new("work", "mr", "https://mr.internal");
+        let mut entries = vec![entry("2024-06-01T12:30:05Z", "ci"), recent.clone()];
+        drop_expired(&mut entries, DEFAULT_RETENTION_DAYS);
+        assert_eq!(entries, vec![recent]);
+    }
+
+    #[test]
+    fn test_trash_round_trip() {
+        let dir = std::env::temp_dir().join(format!("tap_trash_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(".tap_trash");
+        assert_eq!(read(&path).unwrap(), vec![]);
+        let entries = vec![entry("2024-06-01T12:30:05Z", "ci")];
+        write(&path, &entries).unwrap();
+        assert_eq!(read(&path).unwrap(), entries);
+        // Emptying the trash removes the file
+        write(&path, &[]).unwrap();
+        assert!(!path.exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
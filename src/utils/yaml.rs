@@ -0,0 +1,195 @@
+use crate::utils::json::Json;
+use std::fmt;
+
+/// A JSON value written out as YAML by `--format yaml`. Strings are only quoted when YAML would
+/// read them as something else, see `quoted`.
+pub(crate) struct Yaml<'a>(pub &'a Json);
+
+impl fmt::Display for Yaml<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", lines(self.0).join("\n"))
+    }
+}
+
+/// The lines of a value, not indented
+fn lines(value: &Json) -> Vec<String> {
+    match value {
+        Json::Bool(b) => vec![b.to_string()],
+        Json::Number(n) => vec![n.to_string()],
+        Json::String(s) => vec![string(s)],
+        Json::Array(items) if items.is_empty() => vec!["[]".to_string()],
+        Json::Object(entries) if entries.is_empty() => vec!["{}".to_string()],
+        Json::Array(items) => items
+            .iter()
+            .flat_map(|item| {
+                lines(item)
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, line)| match i {
+                        0 => format!("- {line}"),
+                        _ => format!("  {line}"),
+                    })
+            })
+            .collect(),
+        Json::Object(entries) => entries
+            .iter()
+            .flat_map(|(key, value)| match value {
+                Json::Array(v) if !v.is_empty() => block(key, value),
+                Json::Object(v) if !v.is_empty() => block(key, value),
+                scalar => vec![format!("{}: {}", string(key), lines(scalar).concat())],
+            })
+            .collect(),
+    }
+}
+
+/// A key followed by its collection, indented below it
+fn block(key: &str, value: &Json) -> Vec<String> {
+    let mut block = vec![format!("{}:", string(key))];
+    block.extend(lines(value).into_iter().map(|line| format!("  {line}")));
+    block
+}
+
+fn string(s: &str) -> String {
+    match needs_quotes(s) {
+        true => quoted(s),
+        false => s.to_string(),
+    }
+}
+
+/// `s` as a double quoted string. It uses the escapes of JSON, which YAML shares, and also
+/// escapes the characters YAML does not allow as they are, like C1 controls or a byte order mark.
+fn quoted(s: &str) -> String {
+    let mut quoted = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if not_printable(c) => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Whether YAML needs `c` escaped in a double quoted string
+fn not_printable(c: char) -> bool {
+    c.is_control() || matches!(c, '\u{feff}' | '\u{2028}' | '\u{2029}')
+}
+
+/// Whether YAML would read `s` as something other than the same plain string. Both YAML 1.1 and
+/// 1.2 readers are covered, so booleans like `yes`, the `<<` merge key, and anything that could
+/// start a number, like `+1` or `.5`, are quoted.
+fn needs_quotes(s: &str) -> bool {
+    const RESERVED: [&str; 13] = [
+        "true", "false", "yes", "no", "on", "off", "y", "n", "null", "~", "", "=", "<<",
+    ];
+    RESERVED.contains(&s.to_lowercase().as_str())
+        || s.trim() != s
+        || s.starts_with([
+            '-', '+', '?', ':', ',', '[', ']', '{', '}', '#', '&', '*', '!', '|', '>', '\'', '"',
+            '%', '@', '`', '.',
+        ])
+        || s.starts_with(|c: char| c.is_ascii_digit())
+        || s.ends_with(':')
+        || s.contains(": ")
+        || s.contains(" #")
+        || s.contains(not_printable)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_scalars() {
+        assert_eq!(Yaml(&Json::Bool(true)).to_string(), "true");
+        assert_eq!(Yaml(&Json::Number(42)).to_string(), "42");
+        assert_eq!(Yaml(&Json::from("google")).to_string(), "google");
+        assert_eq!(
+            Yaml(&Json::from("https://google.com")).to_string(),
+            "https://google.com"
+        );
+    }
+
+    #[test]
+    fn test_display_quoted_strings() {
+        for s in [
+            "",
+            "yes",
+            "No",
+            "null",
+            "42",
+            " padded",
+            "-flag",
+            "a: b",
+            "a #b",
+            "key:",
+            "+1",
+            "+.5",
+            ".inf",
+            "=",
+            "<<",
+            "a\tb",
+            "\"hi\" \\ there",
+        ] {
+            assert_eq!(Yaml(&Json::from(s)).to_string(), Json::from(s).to_string());
+        }
+        assert_eq!(
+            Yaml(&Json::from("two\nlines")).to_string(),
+            "\"two\\nlines\""
+        );
+        assert_eq!(
+            Yaml(&Json::from("\u{feff}bom\u{85}\u{2028}")).to_string(),
+            "\"\\ufeffbom\\u0085\\u2028\""
+        );
+    }
+
+    #[test]
+    fn test_display_empty() {
+        assert_eq!(Yaml(&Json::Array(vec![])).to_string(), "[]");
+        assert_eq!(
+            Yaml(&Json::object([("links", Json::Array(vec![]))])).to_string(),
+            "links: []"
+        );
+    }
+
+    #[test]
+    fn test_display_nested() {
+        let json = Json::object([
+            ("parent", Json::from("search-engines")),
+            (
+                "links",
+                Json::Array(vec![
+                    Json::object([
+                        ("name", Json::from("google")),
+                        ("value", Json::from("https://google.com")),
+                    ]),
+                    Json::object([
+                        ("name", Json::from("yes")),
+                        ("value", Json::from("https://yahoo.com")),
+                    ]),
+                ]),
+            ),
+            (
+                "rows",
+                Json::Array(vec![Json::Array(vec![Json::from("a"), Json::from("b")])]),
+            ),
+        ]);
+        assert_eq!(
+            Yaml(&json).to_string(),
+            "parent: search-engines\n\
+             links:\n  \
+               - name: google\n    \
+                 value: https://google.com\n  \
+               - name: \"yes\"\n    \
+                 value: https://yahoo.com\n\
+             rows:\n  \
+               - - a\n    \
+                 - b"
+        );
+    }
+}
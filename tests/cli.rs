@@ -0,0 +1,196 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Returns a data file in a new temporary directory, filled with `contents`
+fn data_file(name: &str, contents: &str) -> PathBuf {
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    let dir = std::env::temp_dir().join(format!("tap_it_{name}_{timestamp}"));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(".tap_data");
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+/// Runs the tap binary against the given data file
+fn tap(data_file: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_tap"))
+        .arg("--file")
+        .arg(data_file)
+        .args(args)
+        .env_remove("TAP_READONLY")
+        .output()
+        .expect("Could not run tap")
+}
+
+fn cleanup(data_file: &Path) {
+    std::fs::remove_dir_all(data_file.parent().unwrap()).unwrap();
+}
+
+const LINKS: &str = "search-engines->\n  google|https://google.com\n";
+
+#[test]
+fn test_success_exit_code() {
+    let file = data_file("success", LINKS);
+    let out = tap(&file, &["--show", "search-engines", "google"]);
+    assert_eq!(out.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8_lossy(&out.stdout),
+        "google: https://google.com\n"
+    );
+    assert!(out.stderr.is_empty());
+    cleanup(&file);
+}
+
+#[test]
+fn test_usage_error_exit_code() {
+    let file = data_file("usage", LINKS);
+    let out = tap(&file, &["--madeupflag"]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(out.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&out.stderr).starts_with("ERROR: unknown command"));
+    let out = tap(&file, &["--show", "a", "b", "c"]);
+    assert_eq!(out.status.code(), Some(2));
+    cleanup(&file);
+}
+
+#[test]
+fn test_parent_not_found_exit_code() {
+    let file = data_file("parent_not_found", LINKS);
+    let out = tap(&file, &["--show", "missing", "google"]);
+    assert_eq!(out.status.code(), Some(3));
+    assert!(out.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&out.stderr).starts_with("ERROR: "));
+    cleanup(&file);
+}
+
+#[test]
+fn test_link_not_found_exit_code() {
+    let file = data_file("link_not_found", LINKS);
+    let out = tap(&file, &["--show", "search-engines", "missing"]);
+    assert_eq!(out.status.code(), Some(4));
+    assert!(out.stdout.is_empty());
+    cleanup(&file);
+}
+
+#[test]
+fn test_invalid_data_exit_code() {
+    let file = data_file("invalid_data", "not a parent entity\n");
+    let out = tap(&file, &["--doctor"]);
+    assert_eq!(out.status.code(), Some(6));
+    cleanup(&file);
+}
+
+#[test]
+fn test_read_only_exit_code() {
+    let file = data_file("read_only", LINKS);
+    let out = tap(
+        &file,
+        &["--read-only", "--add", "news", "bbc", "https://bbc.com"],
+    );
+    assert_eq!(out.status.code(), Some(7));
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), LINKS);
+    cleanup(&file);
+}
+
+#[test]
+fn test_json_error_on_stderr() {
+    let file = data_file("json_error", LINKS);
+    let out = tap(&file, &["--show", "search-engines", "missing", "--json"]);
+    assert_eq!(out.status.code(), Some(4));
+    assert!(out.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&out.stderr).starts_with("{\"error\":"));
+    cleanup(&file);
+}
+
+#[test]
+fn test_list_output() {
+    let file = data_file("list", LINKS);
+    let out = tap(&file, &["--list"]);
+    assert_eq!(out.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&out.stdout), "search-engines\n");
+    let out = tap(&file, &["--list", "search-engines"]);
+    assert_eq!(String::from_utf8_lossy(&out.stdout), "google\n");
+    cleanup(&file);
+
+    let file = data_file("list_empty", "");
+    let out = tap(&file, &["--list"]);
+    assert_eq!(out.status.code(), Some(0));
+    assert!(out.stdout.is_empty());
+    assert!(out.stderr.is_empty());
+    cleanup(&file);
+}
+
+#[test]
+fn test_end_of_options_names() {
+    let file = data_file("end_of_options", LINKS);
+    let out = tap(&file, &["--add", "--", "here", "docs", "https://docs.rs"]);
+    assert_eq!(out.status.code(), Some(0));
+    let out = tap(&file, &["--list", "--", "here"]);
+    assert_eq!(String::from_utf8_lossy(&out.stdout), "docs\n");
+    let out = tap(&file, &["--show", "--", "here", "docs"]);
+    assert_eq!(
+        String::from_utf8_lossy(&out.stdout),
+        "docs: https://docs.rs\n"
+    );
+    cleanup(&file);
+}
+
+#[test]
+fn test_completions_match_init() {
+    let file = data_file("completions", LINKS);
+    let home = file.parent().unwrap().join("home");
+    let out = tap(&file, &["--completions", "fish"]);
+    assert_eq!(out.status.code(), Some(0));
+    assert!(out.stderr.is_empty());
+    let init = Command::new(env!("CARGO_BIN_EXE_tap"))
+        .args(["--init", "fish"])
+        .env("HOME", &home)
+        .env_remove("XDG_CONFIG_HOME")
+        .output()
+        .expect("Could not run tap");
+    assert_eq!(init.status.code(), Some(0));
+    let installed =
+        std::fs::read_to_string(home.join(".config/fish/completions/tap.fish")).unwrap();
+    assert_eq!(String::from_utf8_lossy(&out.stdout), installed);
+
+    let out = tap(&file, &["--completions", "tcsh"]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("bash, fish, zsh"));
+    cleanup(&file);
+}
+
+#[test]
+fn test_format_flag_output() {
+    let file = data_file("format", LINKS);
+    let stdout = |args: &[&str]| String::from_utf8(tap(&file, args).stdout).unwrap();
+    // Plain output is what completion scripts read, so it must not change
+    assert_eq!(stdout(&["--list"]), "search-engines\n");
+    assert_eq!(stdout(&["--format", "plain", "--list"]), "search-engines\n");
+    assert_eq!(
+        stdout(&["--format", "json", "--list"]),
+        "[\"search-engines\"]\n"
+    );
+    assert_eq!(
+        stdout(&["--format", "yaml", "--list"]),
+        "- search-engines\n"
+    );
+    assert_eq!(
+        stdout(&["--format", "yaml", "--show", "search-engines"]),
+        "parent: search-engines\nlinks:\n  - name: google\n    value: https://google.com\n"
+    );
+    assert_eq!(
+        stdout(&["--format", "table", "--which", "search-engines"]),
+        "+--------+--------------------+\n| google | https://google.com |\n+--------+--------------------+\n"
+    );
+    assert_eq!(
+        stdout(&["--format", "table", "-s", "search-engines"]),
+        "Links of parent entity search-engines:\n+--------+\n| google |\n+--------+\n"
+    );
+    let out = tap(&file, &["--format", "yaml", "--show", "work"]);
+    assert_eq!(out.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&out.stderr).starts_with("error: "));
+    cleanup(&file);
+}